@@ -8,9 +8,12 @@ use tracing::info;
 use tracing_subscriber::EnvFilter;
 
 use cherub::enforcement::policy::Policy;
+use cherub::providers::Provider;
+use cherub::providers::ollama::OllamaProvider;
 use cherub::telegram::approval::{self, ApprovalMessage};
 use cherub::telegram::connector;
 use cherub::telegram::session::{SessionCommand, SessionConfig};
+use cherub::tools::process_group;
 
 const DEFAULT_POLICY_PATH: &str = "config/default_policy.toml";
 const DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
@@ -36,8 +39,10 @@ async fn main() -> Result<()> {
     let provider_type = std::env::var("CHERUB_PROVIDER").unwrap_or_else(|_| "anthropic".to_owned());
     let base_url = std::env::var("CHERUB_BASE_URL").ok();
 
-    // Load API key — required for Anthropic, optional for OpenAI (local providers).
-    let api_key: Option<SecretString> = if provider_type == "openai" {
+    // Load API key — required for Anthropic, optional for OpenAI (local providers), unused for Ollama and Bedrock (AWS_* credentials).
+    let api_key: Option<SecretString> = if provider_type == "ollama" || provider_type == "bedrock" {
+        None
+    } else if provider_type == "openai" {
         std::env::var("OPENAI_API_KEY")
             .ok()
             .filter(|k| !k.is_empty())
@@ -87,14 +92,25 @@ async fn main() -> Result<()> {
         Some(ids)
     };
 
-    let model = std::env::var("CHERUB_MODEL").unwrap_or_else(|_| {
-        if provider_type == "openai" {
-            "gpt-4o".to_owned()
-        } else {
-            DEFAULT_MODEL.to_owned()
-        }
+    let model = std::env::var("CHERUB_MODEL").unwrap_or_else(|_| match provider_type.as_str() {
+        "openai" => "gpt-4o".to_owned(),
+        "ollama" => "llama3.2".to_owned(),
+        "bedrock" => "us.anthropic.claude-sonnet-4-20250514-v1:0".to_owned(),
+        _ => DEFAULT_MODEL.to_owned(),
     });
 
+    // Sessions create providers lazily; check a local server up front.
+    if provider_type == "ollama" {
+        let mut local = OllamaProvider::new(&model, DEFAULT_MAX_TOKENS)?;
+        if let Some(ref url) = base_url {
+            local = local.with_base_url(url.clone());
+        }
+        local
+            .health_check()
+            .await
+            .map_err(|e| anyhow::anyhow!("provider health check failed: {e}"))?;
+    }
+
     // Connect to PostgreSQL if DATABASE_URL is set (sessions and/or memory).
     #[cfg(any(feature = "sessions", feature = "memory"))]
     let db_pool = {
@@ -236,6 +252,8 @@ async fn main() -> Result<()> {
         .build()
         .dispatch()
         .await;
+    // The dispatcher stopped (Ctrl-C): kill commands still running in unfinished turns.
+    process_group::kill_all();
 
     Ok(())
 }
@@ -0,0 +1,237 @@
+//! Per-tool circuit breaker.
+//!
+//! A tool that keeps failing (crashing binary, broken MCP server, flaky API)
+//! should not keep getting write access while the agent retries it. The agent
+//! loop records each execution outcome in `ToolFailures`; enforcement consults
+//! it through `SessionContext` and, while a tool's breaker is open, turns its
+//! Act/Commit decisions into the policy's `on_open` response. Observe is never
+//! affected — reading is how the agent diagnoses the failure.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Instant, SystemTime};
+
+use tracing::info;
+
+use super::policy::{CompiledBreaker, OnConstraintFailure};
+use super::tier::Tier;
+use super::{DecisionKind, RejectReason};
+
+/// Recent execution failures per tool (policy name). Owned by the agent loop.
+#[derive(Debug, Default)]
+pub struct ToolFailures {
+    by_tool: HashMap<String, VecDeque<Instant>>,
+}
+
+impl ToolFailures {
+    /// Record an execution outcome. A failure is timestamped; a success clears
+    /// the tool's history (closing its breaker).
+    pub(super) fn record(
+        &mut self,
+        breaker: &CompiledBreaker,
+        tool: &str,
+        succeeded: bool,
+        now: Instant,
+    ) {
+        if succeeded {
+            self.by_tool.remove(tool);
+            return;
+        }
+        let failures = self.by_tool.entry(tool.to_owned()).or_default();
+        failures.push_back(now);
+        // Keep only what can still count toward the threshold.
+        while failures.len() > breaker.failure_threshold as usize {
+            failures.pop_front();
+        }
+    }
+
+    /// The failures as wall-clock times, for a session file: an `Instant`
+    /// means nothing to another process.
+    pub(crate) fn to_wall_clock(&self) -> HashMap<String, Vec<SystemTime>> {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        self.by_tool
+            .iter()
+            .map(|(tool, failures)| {
+                let times = failures
+                    .iter()
+                    .map(|&t| wall - now.saturating_duration_since(t))
+                    .collect();
+                (tool.clone(), times)
+            })
+            .collect()
+    }
+
+    /// Restore failures saved by `to_wall_clock`. Time that passed in between
+    /// counts toward the window; a failure older than this process's clock
+    /// can represent is kept as the oldest one it can.
+    pub(crate) fn from_wall_clock(saved: HashMap<String, Vec<SystemTime>>) -> Self {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let by_tool = saved
+            .into_iter()
+            .map(|(tool, times)| {
+                let failures = times
+                    .into_iter()
+                    .map(|t| {
+                        let age = wall.duration_since(t).unwrap_or_default();
+                        now.checked_sub(age).unwrap_or_else(|| earliest(now, age))
+                    })
+                    .collect();
+                (tool, failures)
+            })
+            .collect();
+        Self { by_tool }
+    }
+
+    /// Whether `tool` has failed `failure_threshold` times within the window.
+    pub(super) fn is_open(&self, breaker: &CompiledBreaker, tool: &str, now: Instant) -> bool {
+        self.by_tool.get(tool).is_some_and(|failures| {
+            let recent = failures
+                .iter()
+                .filter(|&&t| now.saturating_duration_since(t) < breaker.window)
+                .count();
+            recent >= breaker.failure_threshold as usize
+        })
+    }
+}
+
+/// An instant before `now`, at most `age` back, for an age the monotonic
+/// clock cannot reach (it may start at boot). Erring recent keeps the
+/// failure counting, never dropping it early.
+fn earliest(now: Instant, age: std::time::Duration) -> Instant {
+    let mut back = age;
+    loop {
+        back /= 2;
+        if let Some(t) = now.checked_sub(back) {
+            return t;
+        }
+    }
+}
+
+/// Downgrade an Act/Commit decision for a tool whose breaker is open.
+pub(super) fn apply_breaker(
+    decision: DecisionKind,
+    failures: &ToolFailures,
+    breaker: &CompiledBreaker,
+    tool: &str,
+) -> DecisionKind {
+    let tier = match decision {
+        DecisionKind::Allow { tier } | DecisionKind::Escalate { tier } if tier > Tier::Observe => {
+            tier
+        }
+        other => return other,
+    };
+    if !failures.is_open(breaker, tool, Instant::now()) {
+        return decision;
+    }
+    match breaker.on_open {
+        OnConstraintFailure::Escalate => {
+            info!(decision = "escalate", reason = "circuit_open", tool);
+            DecisionKind::Escalate { tier }
+        }
+        OnConstraintFailure::Reject => {
+            info!(decision = "reject", reason = "circuit_open", tool);
+            DecisionKind::Reject {
+                reason: RejectReason::CircuitOpen,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn breaker(on_open: OnConstraintFailure) -> CompiledBreaker {
+        CompiledBreaker {
+            failure_threshold: 2,
+            window: Duration::from_secs(60),
+            on_open,
+        }
+    }
+
+    #[test]
+    fn opens_after_threshold_failures() {
+        let b = breaker(OnConstraintFailure::Reject);
+        let mut failures = ToolFailures::default();
+        let now = Instant::now();
+
+        failures.record(&b, "bash", false, now);
+        assert!(!failures.is_open(&b, "bash", now));
+        failures.record(&b, "bash", false, now);
+        assert!(failures.is_open(&b, "bash", now));
+        assert!(!failures.is_open(&b, "http", now));
+    }
+
+    #[test]
+    fn survives_a_round_trip_through_wall_clock_time() {
+        let b = breaker(OnConstraintFailure::Reject);
+        let mut failures = ToolFailures::default();
+        let now = Instant::now();
+        failures.record(&b, "bash", false, now);
+        failures.record(&b, "bash", false, now);
+
+        let restored = ToolFailures::from_wall_clock(failures.to_wall_clock());
+        assert!(restored.is_open(&b, "bash", Instant::now()));
+        assert!(!restored.is_open(&b, "bash", Instant::now() + b.window));
+    }
+
+    #[test]
+    fn failures_age_out_of_window() {
+        let b = breaker(OnConstraintFailure::Reject);
+        let mut failures = ToolFailures::default();
+        let now = Instant::now();
+
+        failures.record(&b, "bash", false, now);
+        failures.record(&b, "bash", false, now);
+        assert!(!failures.is_open(&b, "bash", now + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn success_closes_breaker() {
+        let b = breaker(OnConstraintFailure::Reject);
+        let mut failures = ToolFailures::default();
+        let now = Instant::now();
+
+        failures.record(&b, "bash", false, now);
+        failures.record(&b, "bash", false, now);
+        failures.record(&b, "bash", true, now);
+        assert!(!failures.is_open(&b, "bash", now));
+    }
+
+    #[test]
+    fn open_breaker_downgrades_writes_only() {
+        let b = breaker(OnConstraintFailure::Escalate);
+        let mut failures = ToolFailures::default();
+        let now = Instant::now();
+        failures.record(&b, "bash", false, now);
+        failures.record(&b, "bash", false, now);
+
+        let observe = DecisionKind::Allow {
+            tier: Tier::Observe,
+        };
+        assert_eq!(apply_breaker(observe, &failures, &b, "bash"), observe);
+        assert_eq!(
+            apply_breaker(
+                DecisionKind::Allow { tier: Tier::Act },
+                &failures,
+                &b,
+                "bash"
+            ),
+            DecisionKind::Escalate { tier: Tier::Act }
+        );
+
+        let reject = breaker(OnConstraintFailure::Reject);
+        assert_eq!(
+            apply_breaker(
+                DecisionKind::Escalate { tier: Tier::Commit },
+                &failures,
+                &reject,
+                "bash"
+            ),
+            DecisionKind::Reject {
+                reason: RejectReason::CircuitOpen
+            }
+        );
+    }
+}
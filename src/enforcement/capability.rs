@@ -1,4 +1,137 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::policy::CompiledDestinations;
 use super::tier::Tier;
+use crate::error::CherubError;
+use crate::retry::RetryPolicy;
+use crate::tools::bash::ShellConfig;
+use crate::tools::sandbox::SandboxBackend;
+use crate::tools::seccomp::Syscall;
+use crate::tools::sql::Database;
+use crate::tools::{Evaluated, ToolInvocation};
+
+/// What a token is bound to: the invocation id and the params digest recorded
+/// at its transition to `Evaluated`.
+pub(crate) type InvocationBinding = (Uuid, Option<[u8; 32]>);
+
+/// Length of the HMAC-SHA256 tag appended to a sealed token.
+const TAG_LEN: usize = 32;
+
+/// How long `SpentTokens` remembers the nonce of a token without a TTL.
+/// `unseal()` refuses such tokens once they are older than this, so a pruned
+/// nonce cannot be replayed.
+const UNEXPIRING_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Sealed wire form of a token, followed by its HMAC tag. Issuance is carried as
+/// wall-clock time since `Instant` is meaningless across processes.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Claims {
+    tier: Tier,
+    tool: String,
+    action: String,
+    invocation_id: Uuid,
+    params_digest: Option<[u8; 32]>,
+    issuance: Issuance,
+    ttl_ms: Option<u64>,
+    caveats: Vec<Caveat>,
+    limits: ExecutionLimits,
+    workspace_root: Option<PathBuf>,
+    env: Vec<String>,
+    seccomp: Vec<Syscall>,
+    redirects: Option<CompiledDestinations>,
+    shell: Option<ShellConfig>,
+    retry: Option<RetryPolicy>,
+    databases: BTreeMap<String, Database>,
+}
+
+/// Why a token exists, recorded when it is minted so post-incident review can
+/// answer "which rule authorized this?" per token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issuance {
+    /// Unique per minted token (UUID v7, time-ordered). Doubles as the token's
+    /// single-use nonce; see `SpentTokens`.
+    pub decision_id: Uuid,
+    /// Wall-clock issuance time, Unix epoch milliseconds.
+    pub issued_at_ms: u64,
+    /// Hex SHA-256 of the policy source that allowed the action. `None` when a
+    /// human approved an escalation.
+    pub policy_hash: Option<String>,
+    /// What authorized the token: `tools.<tool>.actions.<name>` (comma-separated
+    /// when several actions matched), `tools.<tool>.git`, or `escalation_approved`.
+    /// Delegated tokens inherit their parent's rule.
+    pub rule: String,
+    /// Decision ids of the tokens this one was delegated from, root first; the
+    /// last entry is the direct parent. Empty for tokens minted by enforcement.
+    #[serde(default)]
+    pub delegated_from: Vec<Uuid>,
+    /// Who the token was delegated to (e.g. a sub-agent id). `None` unless delegated.
+    #[serde(default)]
+    pub delegate: Option<String>,
+}
+
+/// Resource limits for executing a token's invocation, set by enforcement from
+/// the policy's `[execution.<tier>]` table. Unset fields fall back to the tool's
+/// own defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionLimits {
+    /// Wall-clock limit for a spawned process; the process group is killed on expiry.
+    pub timeout: Option<Duration>,
+    /// Bytes of output kept; anything beyond is discarded and counted.
+    pub max_output_bytes: Option<usize>,
+    /// OS sandbox the process runs in (see `tools::sandbox`). `None` → unconfined.
+    pub sandbox: Option<SandboxBackend>,
+    /// Bytes the process may keep in its scratch directory (`tools::scratch`);
+    /// past it the process group is killed. `None` → unlimited.
+    pub tmp_quota_bytes: Option<u64>,
+}
+
+/// A condition attached to a token that the execution layer verifies before
+/// running the tool (output size instead caps what the tool captures, and the
+/// result is truncated to it). Caveats are append-only:
+/// `with_caveat()` adds one, nothing removes one, and the sealed form signs the
+/// full list. Each added caveat can only narrow what the token authorizes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Caveat {
+    /// The tool's output may be at most this many bytes.
+    MaxOutputBytes(usize),
+    /// The string param `field` must be a path under `prefix`. Compared by
+    /// component; a path containing `..` never satisfies it.
+    PathPrefix { field: String, prefix: String },
+    /// The token is refused after this wall-clock time, Unix epoch milliseconds.
+    Deadline(u64),
+}
+
+impl Caveat {
+    /// Whether the caveat holds for `params` at time `now_ms`. Output size is
+    /// enforced separately, as an execution limit.
+    fn admits(&self, params: &serde_json::Value, now_ms: u64) -> bool {
+        match self {
+            Self::MaxOutputBytes(_) => true,
+            Self::PathPrefix { field, prefix } => params
+                .get(field)
+                .and_then(|v| v.as_str())
+                .is_some_and(|value| {
+                    let path = Path::new(value);
+                    !path.components().any(|c| c == Component::ParentDir)
+                        && path.starts_with(prefix)
+                }),
+            Self::Deadline(deadline_ms) => now_ms <= *deadline_ms,
+        }
+    }
+}
 
 /// Unforgeable capability token. Proof that the enforcement layer has evaluated
 /// and approved an action at a specific tier.
@@ -8,38 +141,638 @@ use super::tier::Tier;
 /// 2. `new()` is `pub(super)` — only `enforcement/` submodules can call it.
 ///
 /// No `Clone`, `Copy`, `Default`, or `From` — token is consumed on use (move semantics).
+///
+/// A token minted with a TTL (policy `token_ttl_secs`) expires that long after
+/// issuance; `ToolInvocation::execute()` refuses expired tokens.
+///
+/// Every token is bound to the invocation it was minted for (id + params
+/// digest). `ToolInvocation::execute()` refuses a token bound to any other
+/// invocation, so a token for `ls /tmp` cannot authorize an unrelated call.
+///
+/// Tokens are also scoped to the tool (policy name) and action they were
+/// minted for. The dispatch layer and each tool's `execute()` check the scope,
+/// so a bash token is never accepted by another tool at the same tier.
+///
+/// `seal()` / `unseal()` carry a token across a process boundary (a supervisor
+/// evaluates, a sandboxed worker executes). The wire form is HMAC-SHA256 signed
+/// with a key shared by both processes, so the worker can only obtain tokens
+/// the supervisor minted; unforgeability reduces to keeping the key secret.
+///
+/// Caveats (see `Caveat`) narrow a token further than its tier: policy attaches
+/// them per tool, and `ToolInvocation::execute()` refuses an invocation that
+/// violates any of them.
+///
+/// Tokens are single-use even across `seal()`/`unseal()`: the registry spends
+/// each token's nonce on execution (see `SpentTokens`).
 pub struct CapabilityToken {
     pub(crate) tier: Tier,
+    tool: String,
+    action: String,
+    issued_at: Instant,
+    ttl: Option<Duration>,
+    binding: InvocationBinding,
+    pub(crate) issuance: Issuance,
+    pub(crate) caveats: Vec<Caveat>,
+    pub(crate) limits: ExecutionLimits,
+    pub(crate) workspace_root: Option<PathBuf>, // Tools run with this as cwd
+    pub(crate) env: Vec<String>,                // Extra env var names tool processes may see
+    pub(crate) seccomp: Vec<Syscall>,           // Denied to spawned processes; empty = no filter
+    pub(crate) redirects: Option<CompiledDestinations>, // Re-checked for the request and each redirect hop
+    pub(crate) shell: Option<ShellConfig>,              // Shell for command tools; None = bash -c
+    pub(crate) retry: Option<RetryPolicy>, // Attempts left include this one; None = no retries
+    pub(crate) databases: BTreeMap<String, Database>, // SQL tool connections, by name
     _seal: Seal,
 }
 
 struct Seal;
 
+/// Read-only view of a token, from `CapabilityToken::inspect()`. Safe to log or
+/// show in an approval UI: it carries no binding digest and cannot be turned
+/// back into a token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenInfo {
+    pub tier: Tier,
+    pub tool: String,
+    pub action: String,
+    pub decision_id: Uuid,
+    /// Unix epoch milliseconds.
+    pub issued_at_ms: u64,
+    /// Unix epoch milliseconds; `None` for tokens without a TTL.
+    pub expires_at_ms: Option<u64>,
+    pub caveats: Vec<Caveat>,
+    /// Delegation chain, root first. Empty unless delegated.
+    pub delegated_from: Vec<Uuid>,
+    pub delegate: Option<String>,
+}
+
 impl CapabilityToken {
-    pub(super) fn new(tier: Tier) -> Self {
-        Self { tier, _seal: Seal }
+    /// Mint a token for `invocation`, scoped to its tool and action, and log its
+    /// issuance record.
+    pub(super) fn new(
+        tier: Tier,
+        ttl: Option<Duration>,
+        invocation: &ToolInvocation<Evaluated>,
+        rule: String,
+        policy_hash: Option<String>,
+    ) -> Self {
+        let issuance = Issuance {
+            decision_id: Uuid::now_v7(),
+            issued_at_ms: millis(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default(),
+            ),
+            policy_hash,
+            rule,
+            delegated_from: Vec::new(),
+            delegate: None,
+        };
+        info!(
+            decision_id = %issuance.decision_id,
+            invocation_id = %invocation.id,
+            tool = %invocation.tool,
+            tier = tier.as_str(),
+            rule = %issuance.rule,
+            policy_hash = issuance.policy_hash.as_deref().unwrap_or("none"),
+            "capability token issued"
+        );
+        Self {
+            tier,
+            tool: invocation.tool.clone(),
+            action: invocation.action.clone(),
+            issued_at: Instant::now(),
+            ttl,
+            binding: invocation.binding(),
+            issuance,
+            caveats: Vec::new(),
+            limits: ExecutionLimits::default(),
+            workspace_root: None,
+            env: Vec::new(),
+            seccomp: Vec::new(),
+            redirects: None,
+            shell: None,
+            retry: None,
+            databases: BTreeMap::new(),
+            _seal: Seal,
+        }
+    }
+
+    /// Whether the token was minted for this tool and action.
+    pub(crate) fn is_scoped_to(&self, tool: &str, action: &str) -> bool {
+        self.tool == tool && self.action == action
+    }
+
+    /// Fail with `NotPermitted` unless the token was minted for `tool`.
+    /// Called by each tool's `execute()` with its own policy name.
+    pub(crate) fn check_tool(&self, tool: &str) -> Result<(), CherubError> {
+        if self.tool == tool {
+            return Ok(());
+        }
+        error!(token_tool = %self.tool, tool, "capability token scoped to another tool");
+        Err(CherubError::NotPermitted)
+    }
+
+    /// Whether the token was minted for the invocation with this binding.
+    pub(crate) fn is_bound_to(&self, binding: InvocationBinding) -> bool {
+        self.binding == binding
+    }
+
+    /// Whether the token's TTL has elapsed. Tokens without a TTL never expire.
+    pub(crate) fn is_expired(&self) -> bool {
+        self.ttl.is_some_and(|ttl| self.issued_at.elapsed() > ttl)
+    }
+
+    /// Read-only view of the token's tier, scope, issuance time, and expiry.
+    pub fn inspect(&self) -> TokenInfo {
+        let expires_at_ms = self.ttl.map(|ttl| {
+            let remaining = ttl.saturating_sub(self.issued_at.elapsed());
+            millis(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    + remaining,
+            )
+        });
+        TokenInfo {
+            tier: self.tier,
+            tool: self.tool.clone(),
+            action: self.action.clone(),
+            decision_id: self.issuance.decision_id,
+            issued_at_ms: self.issuance.issued_at_ms,
+            expires_at_ms,
+            caveats: self.caveats.clone(),
+            delegated_from: self.issuance.delegated_from.clone(),
+            delegate: self.issuance.delegate.clone(),
+        }
+    }
+
+    /// Attach the tier's execution limits. Called by enforcement when minting.
+    pub(super) fn with_limits(mut self, limits: ExecutionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Pin the policy's `[workspace]` root. Called by enforcement when minting.
+    pub(super) fn with_workspace(mut self, root: Option<PathBuf>) -> Self {
+        self.workspace_root = root;
+        self
+    }
+
+    /// Attach the policy's `[execution] env` allowlist. Called by enforcement when minting.
+    pub(super) fn with_env(mut self, env: Vec<String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Attach the syscalls denied to processes spawned under this token.
+    /// Called by enforcement when minting.
+    pub(super) fn with_seccomp(mut self, denied: Vec<Syscall>) -> Self {
+        self.seccomp = denied;
+        self
+    }
+
+    /// Attach the destinations redirects are re-checked against. Called by
+    /// enforcement when minting.
+    pub(super) fn with_redirects(mut self, redirects: Option<CompiledDestinations>) -> Self {
+        self.redirects = redirects;
+        self
+    }
+
+    /// Attach the shell a command tool runs with. Called by enforcement when minting.
+    pub(super) fn with_shell(mut self, shell: Option<ShellConfig>) -> Self {
+        self.shell = shell;
+        self
     }
+
+    /// Attach the tool's retry policy. Called by enforcement when minting.
+    pub(super) fn with_retry(mut self, retry: Option<RetryPolicy>) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Attach the databases the SQL tool may connect to. Called by enforcement
+    /// when minting.
+    pub(super) fn with_databases(mut self, databases: BTreeMap<String, Database>) -> Self {
+        self.databases = databases;
+        self
+    }
+
+    /// A token for the next attempt at the same invocation, if the retry
+    /// policy has attempts left after this one. Same grant (decision id,
+    /// binding, TTL clock, caveats), one attempt fewer. Taken before the
+    /// token is consumed; the executor re-checks its freshness before use.
+    pub(crate) fn for_retry(&self) -> Option<Self> {
+        let retry = self.retry.as_ref().filter(|r| r.max_attempts > 1)?;
+        Some(Self {
+            tier: self.tier,
+            tool: self.tool.clone(),
+            action: self.action.clone(),
+            issued_at: self.issued_at,
+            ttl: self.ttl,
+            binding: self.binding,
+            issuance: self.issuance.clone(),
+            caveats: self.caveats.clone(),
+            limits: self.limits.clone(),
+            workspace_root: self.workspace_root.clone(),
+            env: self.env.clone(),
+            seccomp: self.seccomp.clone(),
+            redirects: self.redirects.clone(),
+            shell: self.shell,
+            retry: Some(RetryPolicy {
+                max_attempts: retry.max_attempts - 1,
+                ..retry.clone()
+            }),
+            databases: self.databases.clone(),
+            _seal: Seal,
+        })
+    }
+
+    /// Append a caveat. There is no way to remove one.
+    pub fn with_caveat(mut self, caveat: Caveat) -> Self {
+        self.caveats.push(caveat);
+        self
+    }
+
+    /// Fail with `NotPermitted` if `params` (or the current time) violates any
+    /// caveat. Called by `ToolInvocation::execute()` before dispatch.
+    pub(crate) fn check_caveats(&self, params: &serde_json::Value) -> Result<(), CherubError> {
+        let now_ms = millis(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default(),
+        );
+        match self.caveats.iter().find(|c| !c.admits(params, now_ms)) {
+            None => Ok(()),
+            Some(caveat) => {
+                error!(tool = %self.tool, ?caveat, "capability token caveat not satisfied");
+                Err(CherubError::NotPermitted)
+            }
+        }
+    }
+
+    /// The tightest `MaxOutputBytes` caveat, if any.
+    pub(crate) fn max_output_bytes(&self) -> Option<usize> {
+        self.caveats
+            .iter()
+            .filter_map(|c| match c {
+                Caveat::MaxOutputBytes(max) => Some(*max),
+                _ => None,
+            })
+            .min()
+    }
+
+    /// Consume the token and return one for a strictly lower tier (Commit → Act
+    /// → Observe), keeping its scope, binding, expiry, caveats, and limits. Lets an orchestrator
+    /// delegate a weaker token without another policy evaluation.
+    ///
+    /// Fails with `NotPermitted` unless `tier` is below the token's tier.
+    pub fn attenuate(self, tier: Tier) -> Result<Self, CherubError> {
+        if tier >= self.tier {
+            error!(
+                from = self.tier.as_str(),
+                to = tier.as_str(),
+                "capability token attenuation must lower the tier"
+            );
+            return Err(CherubError::NotPermitted);
+        }
+        Ok(Self { tier, ..self })
+    }
+
+    /// Consume the token and derive a child for `delegate` at `tier` (at most the
+    /// parent's). The child gets a fresh decision id and records the parent's
+    /// chain plus the parent itself; scope, binding, expiry, caveats, rule, and
+    /// policy hash are inherited. Called by `enforcement::delegate()`.
+    ///
+    /// Fails with `NotPermitted` if `tier` exceeds the parent's, the parent has
+    /// expired, or the chain would be deeper than `max_depth`.
+    pub(super) fn delegate(
+        self,
+        delegate: &str,
+        tier: Tier,
+        max_depth: u32,
+    ) -> Result<Self, CherubError> {
+        let depth = self.issuance.delegated_from.len() + 1;
+        if tier > self.tier
+            || self.is_expired()
+            || u32::try_from(depth).map_or(true, |d| d > max_depth)
+        {
+            error!(
+                decision_id = %self.issuance.decision_id,
+                delegate,
+                from = self.tier.as_str(),
+                to = tier.as_str(),
+                depth,
+                max_depth,
+                "capability token delegation refused"
+            );
+            return Err(CherubError::NotPermitted);
+        }
+
+        let mut delegated_from = self.issuance.delegated_from.clone();
+        delegated_from.push(self.issuance.decision_id);
+        let issuance = Issuance {
+            decision_id: Uuid::now_v7(),
+            issued_at_ms: millis(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default(),
+            ),
+            policy_hash: self.issuance.policy_hash.clone(),
+            rule: self.issuance.rule.clone(),
+            delegated_from,
+            delegate: Some(delegate.to_owned()),
+        };
+        info!(
+            decision_id = %issuance.decision_id,
+            parent_decision_id = %self.issuance.decision_id,
+            delegate,
+            tool = %self.tool,
+            tier = tier.as_str(),
+            depth,
+            "capability token delegated"
+        );
+        Ok(Self {
+            tier,
+            issuance,
+            ..self
+        })
+    }
+
+    /// Consume the token into its signed wire form.
+    pub fn seal(self, key: &[u8; 32]) -> Vec<u8> {
+        let claims = Claims {
+            tier: self.tier,
+            tool: self.tool,
+            action: self.action,
+            invocation_id: self.binding.0,
+            params_digest: self.binding.1,
+            issuance: self.issuance,
+            ttl_ms: self.ttl.map(millis),
+            caveats: self.caveats,
+            limits: self.limits,
+            workspace_root: self.workspace_root,
+            env: self.env,
+            seccomp: self.seccomp,
+            redirects: self.redirects,
+            shell: self.shell,
+            retry: self.retry,
+            databases: self.databases,
+        };
+        // Serializing plain fields to JSON cannot fail.
+        let mut bytes = serde_json::to_vec(&claims).unwrap_or_default();
+        let tag = token_mac(key, &bytes).finalize().into_bytes();
+        bytes.extend_from_slice(&tag);
+        bytes
+    }
+
+    /// Verify and decode a token produced by `seal()` under the same key.
+    ///
+    /// Fails with `NotPermitted` on a bad signature, malformed claims, or an
+    /// elapsed TTL; a token without a TTL is refused once it is older than
+    /// `UNEXPIRING_RETENTION`. The remaining TTL is carried over, so the token expires at
+    /// the same wall-clock time it would have in the sealing process.
+    pub fn unseal(bytes: &[u8], key: &[u8; 32]) -> Result<Self, CherubError> {
+        let Some(split) = bytes.len().checked_sub(TAG_LEN) else {
+            error!("sealed capability token too short");
+            return Err(CherubError::NotPermitted);
+        };
+        let (payload, tag) = bytes.split_at(split);
+        if token_mac(key, payload).verify_slice(tag).is_err() {
+            error!("sealed capability token signature mismatch");
+            return Err(CherubError::NotPermitted);
+        }
+        let claims: Claims = serde_json::from_slice(payload).map_err(|_| {
+            error!("sealed capability token malformed");
+            CherubError::NotPermitted
+        })?;
+
+        let issued = UNIX_EPOCH + Duration::from_millis(claims.issuance.issued_at_ms);
+        // Issued "in the future" (clock skew) counts as just issued.
+        let age = SystemTime::now().duration_since(issued).unwrap_or_default();
+        let ttl = claims.ttl_ms.map(Duration::from_millis);
+        if age > ttl.unwrap_or(UNEXPIRING_RETENTION) {
+            error!(tool = %claims.tool, "sealed capability token expired");
+            return Err(CherubError::NotPermitted);
+        }
+        let ttl = ttl.map(|ttl| ttl - age);
+
+        Ok(Self {
+            tier: claims.tier,
+            tool: claims.tool,
+            action: claims.action,
+            issued_at: Instant::now(),
+            ttl,
+            binding: (claims.invocation_id, claims.params_digest),
+            issuance: claims.issuance,
+            caveats: claims.caveats,
+            limits: claims.limits,
+            workspace_root: claims.workspace_root,
+            env: claims.env,
+            seccomp: claims.seccomp,
+            redirects: claims.redirects,
+            shell: claims.shell,
+            retry: claims.retry,
+            databases: claims.databases,
+            _seal: Seal,
+        })
+    }
+}
+
+// Hand-written so the invocation binding (id + params digest) never reaches a
+// log line; only the fields of `TokenInfo` are shown.
+impl fmt::Debug for CapabilityToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let info = self.inspect();
+        f.debug_struct("CapabilityToken")
+            .field("tier", &info.tier)
+            .field("tool", &info.tool)
+            .field("action", &info.action)
+            .field("decision_id", &info.decision_id)
+            .field("issued_at_ms", &info.issued_at_ms)
+            .field("expires_at_ms", &info.expires_at_ms)
+            .field("caveats", &info.caveats)
+            .field("delegated_from", &info.delegated_from)
+            .field("delegate", &info.delegate)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for CapabilityToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} token for {}/{} ({})",
+            self.tier.as_str(),
+            self.tool,
+            self.action,
+            self.issuance.decision_id
+        )
+    }
+}
+
+/// Nonces of tokens already presented for execution.
+///
+/// Move semantics stop a token from being used twice in-process, but a sealed
+/// token can be unsealed any number of times, and a tool that re-enters
+/// dispatch could present a copy. `ToolRegistry` keeps one of these and spends
+/// each token's nonce (its `decision_id`) before running the tool; a second
+/// presentation is refused. Attenuated tokens keep the nonce of their source,
+/// so spending either spends both; a delegated token spends its ancestors'
+/// nonces too, so a parent and its children authorize at most one execution.
+///
+/// Entries are dropped once the token they record has expired, since
+/// `execute()` refuses expired tokens anyway. Tokens without a TTL stay spent
+/// for `UNEXPIRING_RETENTION`, after which `unseal()` no longer accepts them.
+#[derive(Default)]
+pub(crate) struct SpentTokens {
+    // Mutex: `execute()` takes `&ToolRegistry`, and concurrent dispatches must
+    // not both observe a nonce as unspent. Never held across an await.
+    spent: Mutex<HashMap<Uuid, Instant>>, // nonce → when it may be forgotten
+}
+
+impl SpentTokens {
+    /// Mark the token spent. Fails with `NotPermitted` if it already was.
+    pub(crate) fn spend(&self, token: &CapabilityToken) -> Result<(), CherubError> {
+        // The map is consistent after every operation, so a poisoned lock is safe to reuse.
+        let mut spent = self.spent.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        spent.retain(|_, forget_at| *forget_at > now);
+
+        let issuance = &token.issuance;
+        let mut nonces = issuance
+            .delegated_from
+            .iter()
+            .chain(std::iter::once(&issuance.decision_id));
+        if nonces.clone().any(|nonce| spent.contains_key(nonce)) {
+            error!(decision_id = %issuance.decision_id, tool = %token.tool, "capability token already spent");
+            return Err(CherubError::NotPermitted);
+        }
+        let forget_at = token.issued_at + token.ttl.unwrap_or(UNEXPIRING_RETENTION);
+        nonces.by_ref().for_each(|nonce| {
+            spent.insert(*nonce, forget_at);
+        });
+        Ok(())
+    }
+}
+
+/// Typestate: a `TierToken` holding an Observe-tier token.
+pub struct ObserveTier;
+
+/// Typestate: a `TierToken` holding an Act-tier token.
+pub struct ActTier;
+
+/// Typestate: a `TierToken` holding a Commit-tier token.
+pub struct CommitTier;
+
+/// Tier markers for `TierToken`. Sealed — the three markers above are the only
+/// implementors.
+pub trait TokenTier: sealed::Sealed {
+    const TIER: Tier;
+}
+
+impl TokenTier for ObserveTier {
+    const TIER: Tier = Tier::Observe;
+}
+
+impl TokenTier for ActTier {
+    const TIER: Tier = Tier::Act;
+}
+
+impl TokenTier for CommitTier {
+    const TIER: Tier = Tier::Commit;
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::ObserveTier {}
+    impl Sealed for super::ActTier {}
+    impl Sealed for super::CommitTier {}
+}
+
+/// A `CapabilityToken` whose tier is known at compile time, so a tool API can
+/// demand a privilege in its signature (`fn delete(.., token: CommitToken)`)
+/// instead of comparing tiers at runtime.
+///
+/// Built only by `TryFrom<CapabilityToken>`, which requires an exact tier
+/// match — attenuate first to get a lower-tier wrapper. Same move-only rules
+/// as `CapabilityToken`.
+pub struct TierToken<T: TokenTier> {
+    token: CapabilityToken,
+    _tier: PhantomData<T>,
+}
+
+pub type ObserveToken = TierToken<ObserveTier>;
+pub type ActToken = TierToken<ActTier>;
+pub type CommitToken = TierToken<CommitTier>;
+
+impl<T: TokenTier> TryFrom<CapabilityToken> for TierToken<T> {
+    type Error = CherubError;
+
+    fn try_from(token: CapabilityToken) -> Result<Self, CherubError> {
+        if token.tier != T::TIER {
+            error!(
+                token_tier = token.tier.as_str(),
+                required = T::TIER.as_str(),
+                "capability token has the wrong tier"
+            );
+            return Err(CherubError::NotPermitted);
+        }
+        Ok(Self {
+            token,
+            _tier: PhantomData,
+        })
+    }
+}
+
+impl<T: TokenTier> TierToken<T> {
+    /// Unwrap into the underlying token, e.g. to pass to `ToolInvocation::execute()`.
+    pub fn into_inner(self) -> CapabilityToken {
+        self.token
+    }
+}
+
+fn token_mac(key: &[u8; 32], payload: &[u8]) -> Hmac<Sha256> {
+    // HMAC accepts keys of any length; this cannot fail.
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(payload);
+    mac
+}
+
+fn millis(d: Duration) -> u64 {
+    u64::try_from(d.as_millis()).unwrap_or(u64::MAX)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn invocation(tool: &str) -> ToolInvocation<Evaluated> {
+        ToolInvocation::new(tool, "execute", serde_json::json!({})).transition()
+    }
+
+    fn mint(
+        tier: Tier,
+        ttl: Option<Duration>,
+        invocation: &ToolInvocation<Evaluated>,
+    ) -> CapabilityToken {
+        CapabilityToken::new(tier, ttl, invocation, "test".to_owned(), None)
+    }
+
     #[test]
     fn capability_token_carries_tier() {
-        let token = CapabilityToken::new(Tier::Observe);
+        let token = mint(Tier::Observe, None, &invocation("bash"));
         assert_eq!(token.tier, Tier::Observe);
 
-        let token = CapabilityToken::new(Tier::Act);
+        let token = mint(Tier::Act, None, &invocation("bash"));
         assert_eq!(token.tier, Tier::Act);
 
-        let token = CapabilityToken::new(Tier::Commit);
+        let token = mint(Tier::Commit, None, &invocation("bash"));
         assert_eq!(token.tier, Tier::Commit);
     }
 
     #[test]
     fn capability_token_is_consumed() {
-        let token = CapabilityToken::new(Tier::Observe);
+        let token = mint(Tier::Observe, None, &invocation("bash"));
         // Move token into a function — if CapabilityToken were Copy/Clone,
         // this test would still compile after using `token` again below.
         let _moved = consume(token);
@@ -47,6 +780,330 @@ mod tests {
         // let _ = token.tier;
     }
 
+    #[test]
+    fn token_expires_after_ttl() {
+        assert!(!mint(Tier::Act, None, &invocation("bash")).is_expired());
+        assert!(
+            !mint(
+                Tier::Act,
+                Some(Duration::from_secs(60)),
+                &invocation("bash")
+            )
+            .is_expired()
+        );
+
+        let token = mint(
+            Tier::Act,
+            Some(Duration::from_millis(1)),
+            &invocation("bash"),
+        );
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(token.is_expired());
+    }
+
+    #[test]
+    fn token_is_bound_to_one_invocation() {
+        let bound = invocation("bash");
+        let token = mint(Tier::Act, None, &bound);
+        assert!(token.is_bound_to(bound.binding()));
+        assert!(!token.is_bound_to(invocation("bash").binding()));
+        assert!(!token.is_bound_to((bound.id, Some([0; 32]))));
+        assert!(!token.is_bound_to((Uuid::now_v7(), bound.binding().1)));
+    }
+
+    #[test]
+    fn token_is_scoped_to_tool_and_action() {
+        let token = mint(Tier::Observe, None, &invocation("bash"));
+        assert!(token.is_scoped_to("bash", "execute"));
+        assert!(!token.is_scoped_to("file", "execute"));
+        assert!(!token.is_scoped_to("bash", "delete"));
+        assert!(token.check_tool("bash").is_ok());
+        assert!(matches!(
+            token.check_tool("file"),
+            Err(CherubError::NotPermitted)
+        ));
+    }
+
+    #[test]
+    fn attenuation_only_lowers_tier() {
+        let bound = invocation("bash");
+        let token = mint(Tier::Commit, None, &bound)
+            .attenuate(Tier::Act)
+            .unwrap()
+            .attenuate(Tier::Observe)
+            .unwrap();
+        assert_eq!(token.tier, Tier::Observe);
+        assert!(token.is_scoped_to("bash", "execute"));
+        assert!(token.is_bound_to(bound.binding()));
+
+        for (from, to) in [
+            (Tier::Observe, Tier::Observe),
+            (Tier::Observe, Tier::Act),
+            (Tier::Act, Tier::Commit),
+        ] {
+            let token = mint(from, None, &bound);
+            assert!(matches!(
+                token.attenuate(to),
+                Err(CherubError::NotPermitted)
+            ));
+        }
+    }
+
+    #[test]
+    fn tier_tokens_require_exact_tier() {
+        fn commit_only(token: CommitToken) -> Tier {
+            token.into_inner().tier
+        }
+
+        let bound = invocation("bash");
+        let commit = CommitToken::try_from(mint(Tier::Commit, None, &bound));
+        assert_eq!(commit.map(commit_only).ok(), Some(Tier::Commit));
+
+        assert!(CommitToken::try_from(mint(Tier::Act, None, &bound)).is_err());
+        assert!(ObserveToken::try_from(mint(Tier::Act, None, &bound)).is_err());
+
+        let act = mint(Tier::Commit, None, &bound)
+            .attenuate(Tier::Act)
+            .and_then(ActToken::try_from);
+        assert!(act.is_ok());
+    }
+
+    #[test]
+    fn caveats_constrain_params_and_time() {
+        let token = mint(Tier::Act, None, &invocation("file")).with_caveat(Caveat::PathPrefix {
+            field: "path".to_owned(),
+            prefix: "/tmp/work".to_owned(),
+        });
+        let path = |p: &str| serde_json::json!({ "path": p });
+        assert!(token.check_caveats(&path("/tmp/work/a.txt")).is_ok());
+        assert!(token.check_caveats(&path("/tmp/workspace/a.txt")).is_err());
+        assert!(
+            token
+                .check_caveats(&path("/tmp/work/../../etc/passwd"))
+                .is_err()
+        );
+        assert!(token.check_caveats(&serde_json::json!({})).is_err());
+
+        let past = mint(Tier::Act, None, &invocation("file")).with_caveat(Caveat::Deadline(1));
+        assert!(matches!(
+            past.check_caveats(&path("/tmp/work/a.txt")),
+            Err(CherubError::NotPermitted)
+        ));
+    }
+
+    #[test]
+    fn appended_caveats_only_narrow() {
+        let token = mint(Tier::Act, None, &invocation("bash"))
+            .with_caveat(Caveat::MaxOutputBytes(100))
+            .with_caveat(Caveat::MaxOutputBytes(1000));
+        assert_eq!(token.max_output_bytes(), Some(100));
+        let token = token.attenuate(Tier::Observe).unwrap();
+        assert_eq!(token.caveats.len(), 2);
+    }
+
+    const KEY: [u8; 32] = [7; 32];
+
+    #[test]
+    fn sealed_token_keeps_caveats() {
+        let token =
+            mint(Tier::Act, None, &invocation("bash")).with_caveat(Caveat::MaxOutputBytes(64));
+        let sealed = token.seal(&KEY);
+        let token = CapabilityToken::unseal(&sealed, &KEY).unwrap();
+        assert_eq!(token.caveats, vec![Caveat::MaxOutputBytes(64)]);
+
+        // Dropping the caveat from the signed payload breaks the signature.
+        let stripped = String::from_utf8(sealed[..sealed.len() - TAG_LEN].to_vec())
+            .unwrap()
+            .replace("{\"max_output_bytes\":64}", "");
+        let mut stripped = stripped.into_bytes();
+        stripped.extend_from_slice(&sealed[sealed.len() - TAG_LEN..]);
+        assert!(CapabilityToken::unseal(&stripped, &KEY).is_err());
+    }
+
+    #[test]
+    fn sealed_token_round_trips() {
+        let bound = invocation("bash");
+        let token = mint(Tier::Act, Some(Duration::from_secs(60)), &bound);
+        let token = CapabilityToken::unseal(&token.seal(&KEY), &KEY).unwrap();
+        assert_eq!(token.tier, Tier::Act);
+        assert!(token.is_scoped_to("bash", "execute"));
+        assert!(token.is_bound_to(bound.binding()));
+        assert!(!token.is_expired());
+        assert_eq!(token.issuance.rule, "test");
+    }
+
+    #[test]
+    fn each_token_gets_its_own_decision_id() {
+        let bound = invocation("bash");
+        let a = CapabilityToken::new(
+            Tier::Act,
+            None,
+            &bound,
+            "tools.bash.actions.write".to_owned(),
+            Some("ab".repeat(32)),
+        );
+        let b = mint(Tier::Act, None, &bound);
+        assert_ne!(a.issuance.decision_id, b.issuance.decision_id);
+        assert_eq!(a.issuance.rule, "tools.bash.actions.write");
+        assert!(a.issuance.issued_at_ms > 0);
+    }
+
+    #[test]
+    fn tampered_sealed_token_rejected() {
+        let sealed = mint(Tier::Observe, None, &invocation("bash")).seal(&KEY);
+        let forged = String::from_utf8(sealed[..sealed.len() - TAG_LEN].to_vec())
+            .unwrap()
+            .replace("\"observe\"", "\"commit\"");
+        let mut forged = forged.into_bytes();
+        forged.extend_from_slice(&sealed[sealed.len() - TAG_LEN..]);
+        assert!(matches!(
+            CapabilityToken::unseal(&forged, &KEY),
+            Err(CherubError::NotPermitted)
+        ));
+        assert!(matches!(
+            CapabilityToken::unseal(&sealed, &[8; 32]),
+            Err(CherubError::NotPermitted)
+        ));
+        assert!(matches!(
+            CapabilityToken::unseal(&sealed[..10], &KEY),
+            Err(CherubError::NotPermitted)
+        ));
+    }
+
+    #[test]
+    fn expired_sealed_token_rejected() {
+        let token = mint(
+            Tier::Act,
+            Some(Duration::from_millis(1)),
+            &invocation("bash"),
+        );
+        let sealed = token.seal(&KEY);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(matches!(
+            CapabilityToken::unseal(&sealed, &KEY),
+            Err(CherubError::NotPermitted)
+        ));
+    }
+
+    #[test]
+    fn token_can_be_spent_once() {
+        let spent = SpentTokens::default();
+        let sealed = mint(Tier::Act, None, &invocation("bash")).seal(&KEY);
+        let first = CapabilityToken::unseal(&sealed, &KEY).unwrap();
+        let replay = CapabilityToken::unseal(&sealed, &KEY).unwrap();
+        assert!(spent.spend(&first).is_ok());
+        assert!(matches!(
+            spent.spend(&replay),
+            Err(CherubError::NotPermitted)
+        ));
+        assert!(
+            spent
+                .spend(&first.attenuate(Tier::Observe).unwrap())
+                .is_err()
+        );
+        assert!(
+            spent
+                .spend(&mint(Tier::Act, None, &invocation("bash")))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn expired_nonces_are_pruned() {
+        let spent = SpentTokens::default();
+        let token = mint(
+            Tier::Act,
+            Some(Duration::from_millis(1)),
+            &invocation("bash"),
+        );
+        spent.spend(&token).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        spent
+            .spend(&mint(Tier::Act, None, &invocation("bash")))
+            .unwrap();
+        assert_eq!(spent.spent.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn unexpiring_nonces_are_retained_for_a_bounded_time() {
+        let spent = SpentTokens::default();
+        let token = mint(Tier::Act, None, &invocation("bash"));
+        let nonce = token.issuance.decision_id;
+        spent.spend(&token).unwrap();
+        let forget_at = spent.spent.lock().unwrap()[&nonce];
+        assert!(forget_at <= Instant::now() + UNEXPIRING_RETENTION);
+
+        // Past the retention window the sealed form is refused instead.
+        let mut old = mint(Tier::Act, None, &invocation("bash"));
+        old.issuance.issued_at_ms -= millis(UNEXPIRING_RETENTION) + 1_000;
+        assert!(matches!(
+            CapabilityToken::unseal(&old.seal(&KEY), &KEY),
+            Err(CherubError::NotPermitted)
+        ));
+    }
+
+    #[test]
+    fn debug_and_inspect_omit_binding() {
+        let bound = invocation("bash");
+        let token = mint(Tier::Act, Some(Duration::from_secs(60)), &bound);
+        let info = token.inspect();
+        assert_eq!(info.tier, Tier::Act);
+        assert_eq!(
+            (info.tool.as_str(), info.action.as_str()),
+            ("bash", "execute")
+        );
+        let expires = info.expires_at_ms.unwrap();
+        assert!(expires > info.issued_at_ms && expires <= info.issued_at_ms + 60_000);
+
+        let debug = format!("{token:?}");
+        assert!(debug.contains("tier: Act"));
+        assert!(!debug.contains(&bound.id.to_string()));
+        assert!(!debug.contains("binding"));
+        assert_eq!(
+            token.to_string(),
+            format!("act token for bash/execute ({})", info.decision_id)
+        );
+    }
+
+    #[test]
+    fn delegation_records_chain_and_limits_depth() {
+        let parent = mint(Tier::Act, None, &invocation("bash"));
+        let root_id = parent.issuance.decision_id;
+        let child = parent.delegate("worker-1", Tier::Act, 2).unwrap();
+        assert_eq!(child.issuance.delegated_from, vec![root_id]);
+        assert_eq!(child.issuance.delegate.as_deref(), Some("worker-1"));
+        assert_ne!(child.issuance.decision_id, root_id);
+        assert_eq!(child.issuance.rule, "test");
+
+        let child_id = child.issuance.decision_id;
+        let grandchild = child.delegate("worker-2", Tier::Observe, 2).unwrap();
+        assert_eq!(grandchild.issuance.delegated_from, vec![root_id, child_id]);
+        assert!(matches!(
+            grandchild.delegate("worker-3", Tier::Observe, 2),
+            Err(CherubError::NotPermitted)
+        ));
+
+        let observe = mint(Tier::Observe, None, &invocation("bash"));
+        assert!(observe.delegate("worker", Tier::Act, 2).is_err());
+        let root = mint(Tier::Act, None, &invocation("bash"));
+        assert!(root.delegate("worker", Tier::Act, 0).is_err());
+    }
+
+    #[test]
+    fn delegated_token_spends_its_ancestors() {
+        let spent = SpentTokens::default();
+        let sealed = mint(Tier::Act, None, &invocation("bash")).seal(&KEY);
+        let child = CapabilityToken::unseal(&sealed, &KEY)
+            .unwrap()
+            .delegate("worker", Tier::Act, 1)
+            .unwrap();
+        let child = CapabilityToken::unseal(&child.seal(&KEY), &KEY).unwrap();
+        assert_eq!(child.issuance.delegate.as_deref(), Some("worker"));
+        spent.spend(&child).unwrap();
+        let parent = CapabilityToken::unseal(&sealed, &KEY).unwrap();
+        assert!(spent.spend(&parent).is_err());
+    }
+
     fn consume(token: CapabilityToken) -> Tier {
         token.tier
     }
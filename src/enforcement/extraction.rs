@@ -4,20 +4,29 @@
 //! - `bash` puts it in `params["command"]`, parsed via the shell module
 //! - `memory` puts it in `params["action"]`, optionally qualified by `params["path"]`
 //! - `http` puts it in `params["action"]` (method) + `params["url"]` (host)
+//! - `patch` puts a unified diff in `params["patch"]`, one action per touched file
+//! - `sql` puts statements in `params["query"]`, one action per statement
+//! - `kubectl` puts the verb in `params["action"]` + `params["namespace"]`
+//! - plugin tools (WASM, container) name their own field via `match_field`
 //!
 //! `MatchSource` selects the extraction strategy at policy-compile time.
 //! No changes to `evaluate()` are needed when adding new structured tools.
 
-use super::shell;
+use super::{shell, sql};
+use crate::tools::kubectl;
+use crate::tools::patch::{self, FilePatch};
 
 /// How to extract matchable action strings from a tool invocation's params.
-///
-/// `Copy` so it can be stored on `CompiledTool` without affecting cloneability.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(super) enum MatchSource {
     /// Extract `params["command"]`, parse via the shell module.
     /// Each sub-command (split on `;`, `&&`, `|`, etc.) becomes a separate action string.
     Command,
+    /// Like `Command`, but reading a policy-named param (`command_param`).
+    /// With `array`, the param is a list of command strings (`"commands[]"`);
+    /// every element is parsed and all sub-commands are evaluated. Any missing,
+    /// empty, non-string, or unparseable element → `None` → Reject.
+    CommandField { field: String, array: bool },
     /// Extract `params["action"]`, optionally qualified by `params["path"]`.
     /// Produces a single action string: `"{action}:{path}"` or `"{action}"`.
     Structured,
@@ -29,14 +38,40 @@ pub(super) enum MatchSource {
     /// Produces a single action string: `"{server}:{tool}"`, e.g. `"google-workspace:list_events"`.
     /// Missing/empty fields → `None` → Reject.
     McpStructured,
+    /// Parse `params["patch"]` as a unified diff. Produces `"{op}:{path}"` per
+    /// touched file, op being `create`, `modify`, or `delete` (a rename is a
+    /// delete plus a create). Malformed diff or unsafe path → `None` → Reject.
+    Patch,
+    /// Split `params["query"]` into statements (see `enforcement::sql`).
+    /// Produces `"{verb}:{database}"` per statement, e.g. `"select:analytics"`,
+    /// `database` being `params["database"]`. Unclassifiable SQL or a missing or
+    /// malformed database name → `None` → Reject.
+    Sql,
+    /// Extract `params["action"]` (kubectl verb) + `params["namespace"]`.
+    /// Produces `"{verb}:{namespace}"`, e.g. `"delete:staging"`, or `"drain"`
+    /// for the cluster-scoped node drain. A missing or malformed namespace →
+    /// `None` → Reject.
+    Kubectl,
+    /// Extract a single policy-named param, e.g. `params["query"]`.
+    /// A string produces one action string; an array of strings produces one per
+    /// element. Missing, empty, or non-string values → `None` → Reject.
+    Field(String),
 }
 
 impl MatchSource {
+    /// Whether the action strings are shell command segments.
+    pub(super) fn is_command(&self) -> bool {
+        matches!(
+            self,
+            MatchSource::Command | MatchSource::CommandField { .. }
+        )
+    }
+
     /// Extract matchable action strings from tool invocation params.
     ///
     /// Returns `None` if the params are malformed or unparseable (→ Reject).
     /// Returns `Some([])` is never produced — an empty list is treated as `None`.
-    pub(super) fn extract(self, params: &serde_json::Value) -> Option<Vec<String>> {
+    pub(super) fn extract(&self, params: &serde_json::Value) -> Option<Vec<String>> {
         match self {
             MatchSource::Command => {
                 let command = params
@@ -44,13 +79,27 @@ impl MatchSource {
                     .and_then(|v| v.as_str())
                     .filter(|s| !s.is_empty())?;
 
-                let sub_commands = shell::parse_commands(command)?;
+                parse_command(command)
+            }
+            MatchSource::CommandField { field, array } => {
+                let value = params.get(field)?;
+                let commands: Vec<&str> = if *array {
+                    value
+                        .as_array()
+                        .filter(|items| !items.is_empty())?
+                        .iter()
+                        .map(|v| v.as_str())
+                        .collect::<Option<_>>()?
+                } else {
+                    vec![value.as_str()?]
+                };
 
-                if sub_commands.is_empty() {
-                    return None;
+                // Flatten; any element that fails to parse (incl. empty) rejects the lot.
+                let mut actions = Vec::new();
+                for command in commands {
+                    actions.extend(parse_command(command)?);
                 }
-
-                Some(sub_commands.iter().map(|s| s.to_string()).collect())
+                Some(actions)
             }
             MatchSource::Structured => {
                 let action = params
@@ -92,10 +141,66 @@ impl MatchSource {
 
                 Some(vec![format!("{server}:{tool}")])
             }
+            MatchSource::Patch => {
+                let diff = params.get("patch").and_then(|v| v.as_str())?;
+                let files = patch::parse(diff).ok()?;
+                Some(files.iter().flat_map(FilePatch::actions).collect())
+            }
+            MatchSource::Sql => {
+                let database = params
+                    .get("database")
+                    .and_then(|v| v.as_str())
+                    .filter(|d| is_database_name(d))?;
+                let query = params.get("query").and_then(|v| v.as_str())?;
+                let verbs = sql::verbs(query)?;
+                Some(verbs.iter().map(|v| format!("{v}:{database}")).collect())
+            }
+            MatchSource::Kubectl => {
+                let verb = params
+                    .get("action")
+                    .and_then(|v| v.as_str())
+                    .filter(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_lowercase()))?;
+                match verb {
+                    "drain" => Some(vec![verb.to_owned()]),
+                    _ => {
+                        let namespace = kubectl::namespace(verb, params)?;
+                        Some(vec![format!("{verb}:{namespace}")])
+                    }
+                }
+            }
+            MatchSource::Field(field) => match params.get(field)? {
+                serde_json::Value::String(s) if !s.is_empty() => Some(vec![s.clone()]),
+                serde_json::Value::Array(items) if !items.is_empty() => items
+                    .iter()
+                    .map(|v| v.as_str().filter(|s| !s.is_empty()).map(str::to_owned))
+                    .collect(),
+                _ => None,
+            },
         }
     }
 }
 
+/// Database names, as configured under `[tools.<name>.databases]` and passed
+/// in `params["database"]`: ASCII letters, digits, `_`, and `-`.
+pub(super) fn is_database_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Split one shell command string into its sub-command action strings.
+fn parse_command(command: &str) -> Option<Vec<String>> {
+    if command.is_empty() {
+        return None;
+    }
+    let sub_commands = shell::parse_commands(command)?;
+    if sub_commands.is_empty() {
+        return None;
+    }
+    Some(sub_commands.iter().map(|s| s.to_string()).collect())
+}
+
 /// Extract the host component from a URL string.
 ///
 /// Handles standard HTTP/HTTPS URLs. Does not handle IPv6 addresses with brackets.
@@ -114,6 +219,50 @@ fn extract_url_host(url: &str) -> Option<&str> {
     if host.is_empty() { None } else { Some(host) }
 }
 
+/// Parse the destination host out of a URL for `destinations` checks.
+///
+/// Stricter than `extract_url_host()`: strips userinfo (`user@host` — the classic
+/// `https://trusted.com@evil.com/` confusion), handles bracketed IPv6 literals,
+/// lowercases, and drops a trailing root dot. Anything unexpected → `None` → Reject.
+pub(super) fn parse_destination_host(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    if scheme.is_empty()
+        || !scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+')
+    {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    // WHATWG parsers (reqwest) end the authority at `\` and drop tabs and
+    // newlines, so `https://evil.com\@trusted.com/` connects to evil.com.
+    // Refuse anything a stricter reading could disagree about.
+    if authority
+        .chars()
+        .any(|c| c == '\\' || c.is_control() || c.is_whitespace())
+    {
+        return None;
+    }
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+
+    let host = if let Some(bracketed) = host_port.strip_prefix('[') {
+        let (ip, after) = bracketed.split_once(']')?;
+        if !(after.is_empty() || after.starts_with(':')) {
+            return None;
+        }
+        ip
+    } else {
+        host_port.split(':').next()?
+    };
+
+    let host = host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase();
+    if host.is_empty() || host.chars().any(|c| c.is_whitespace() || c == '\\') {
+        None
+    } else {
+        Some(host)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,4 +503,235 @@ mod tests {
             Some(vec!["fireflies:get_transcript".to_owned()])
         );
     }
+
+    // --- Field extraction ---
+
+    fn commands() -> MatchSource {
+        MatchSource::CommandField {
+            field: "commands".to_owned(),
+            array: true,
+        }
+    }
+
+    #[test]
+    fn command_array_flattens_sub_commands() {
+        let params = json!({"commands": ["cd x", "make && make test"]});
+        assert_eq!(
+            commands().extract(&params),
+            Some(vec![
+                "cd x".to_owned(),
+                "make".to_owned(),
+                "make test".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn command_array_rejects_bad_elements() {
+        assert!(commands().extract(&json!({"commands": []})).is_none());
+        assert!(
+            commands()
+                .extract(&json!({"commands": ["ls", ""]}))
+                .is_none()
+        );
+        assert!(
+            commands()
+                .extract(&json!({"commands": ["ls", 3]}))
+                .is_none()
+        );
+        assert!(commands().extract(&json!({"commands": "ls"})).is_none());
+        assert!(commands().extract(&json!({"command": "ls"})).is_none());
+    }
+
+    #[test]
+    fn command_field_single_string() {
+        let source = MatchSource::CommandField {
+            field: "cmd".to_owned(),
+            array: false,
+        };
+        assert_eq!(
+            source.extract(&json!({"cmd": "ls; pwd"})),
+            Some(vec!["ls".to_owned(), "pwd".to_owned()])
+        );
+        assert!(source.extract(&json!({"cmd": ["ls"]})).is_none());
+    }
+
+    #[test]
+    fn patch_one_action_per_file() {
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-a\n+b\n\
+            --- /dev/null\n+++ b/.env\n@@ -0,0 +1 @@\n+KEY=1\n";
+        assert_eq!(
+            MatchSource::Patch.extract(&json!({"patch": diff})),
+            Some(vec![
+                "modify:src/lib.rs".to_owned(),
+                "create:.env".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn patch_malformed_returns_none() {
+        assert!(MatchSource::Patch.extract(&json!({})).is_none());
+        assert!(
+            MatchSource::Patch
+                .extract(&json!({"patch": "not a diff"}))
+                .is_none()
+        );
+        let escape = "--- a/../x\n+++ b/../x\n@@ -1 +1 @@\n-a\n+b\n";
+        assert!(
+            MatchSource::Patch
+                .extract(&json!({"patch": escape}))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn sql_one_action_per_statement() {
+        let params = json!({"database": "app", "query": "SELECT 1; DELETE FROM t"});
+        assert_eq!(
+            MatchSource::Sql.extract(&params),
+            Some(vec!["select:app".to_owned(), "delete:app".to_owned()])
+        );
+        for params in [
+            json!({"query": "SELECT 1"}),
+            json!({"database": "a:b", "query": "SELECT 1"}),
+            json!({"database": "app", "query": "SELECT $$;$$"}),
+            json!({"database": "app"}),
+        ] {
+            assert!(MatchSource::Sql.extract(&params).is_none(), "{params}");
+        }
+    }
+
+    #[test]
+    fn kubectl_verb_and_namespace() {
+        let params = json!({"action": "delete", "namespace": "staging", "resource": "pods"});
+        assert_eq!(
+            MatchSource::Kubectl.extract(&params),
+            Some(vec!["delete:staging".to_owned()])
+        );
+        assert_eq!(
+            MatchSource::Kubectl.extract(&json!({"action": "drain", "node": "n1"})),
+            Some(vec!["drain".to_owned()])
+        );
+        for params in [
+            json!({"action": "get"}),
+            json!({"action": "get", "namespace": "dev$|prod"}),
+            json!({"action": "get:prod", "namespace": "dev"}),
+            json!({"namespace": "dev"}),
+        ] {
+            assert!(MatchSource::Kubectl.extract(&params).is_none(), "{params}");
+        }
+    }
+
+    fn field(name: &str) -> MatchSource {
+        MatchSource::Field(name.to_owned())
+    }
+
+    #[test]
+    fn field_string() {
+        let params = json!({"query": "SELECT 1", "limit": 5});
+        assert_eq!(
+            field("query").extract(&params),
+            Some(vec!["SELECT 1".to_owned()])
+        );
+    }
+
+    #[test]
+    fn field_array_of_strings() {
+        let params = json!({"paths": ["src/a.rs", "src/b.rs"]});
+        assert_eq!(
+            field("paths").extract(&params),
+            Some(vec!["src/a.rs".to_owned(), "src/b.rs".to_owned()])
+        );
+    }
+
+    #[test]
+    fn field_missing_returns_none() {
+        let params = json!({"command": "ls"});
+        assert!(field("query").extract(&params).is_none());
+    }
+
+    #[test]
+    fn field_empty_string_returns_none() {
+        let params = json!({"query": ""});
+        assert!(field("query").extract(&params).is_none());
+    }
+
+    #[test]
+    fn field_empty_array_returns_none() {
+        let params = json!({"paths": []});
+        assert!(field("paths").extract(&params).is_none());
+    }
+
+    #[test]
+    fn field_array_with_non_string_returns_none() {
+        // One bad element poisons the whole extraction — no partial evaluation.
+        let params = json!({"paths": ["src/a.rs", 42]});
+        assert!(field("paths").extract(&params).is_none());
+    }
+
+    #[test]
+    fn field_non_string_returns_none() {
+        let params = json!({"query": {"nested": true}});
+        assert!(field("query").extract(&params).is_none());
+    }
+
+    // --- parse_destination_host ---
+
+    #[test]
+    fn destination_host_simple() {
+        assert_eq!(
+            parse_destination_host("https://API.Stripe.com:443/v1?x=1"),
+            Some("api.stripe.com".to_owned())
+        );
+    }
+
+    #[test]
+    fn destination_host_strips_userinfo() {
+        assert_eq!(
+            parse_destination_host("https://api.stripe.com@evil.com/steal"),
+            Some("evil.com".to_owned())
+        );
+    }
+
+    #[test]
+    fn destination_host_ipv6_literal() {
+        assert_eq!(
+            parse_destination_host("http://[::1]:8080/admin"),
+            Some("::1".to_owned())
+        );
+    }
+
+    #[test]
+    fn destination_host_trailing_dot() {
+        assert_eq!(
+            parse_destination_host("https://example.com./"),
+            Some("example.com".to_owned())
+        );
+    }
+
+    #[test]
+    fn destination_host_query_without_path() {
+        assert_eq!(
+            parse_destination_host("https://example.com?next=https://evil.com"),
+            Some("example.com".to_owned())
+        );
+    }
+
+    #[test]
+    fn destination_host_rejects_malformed() {
+        assert_eq!(parse_destination_host("example.com/path"), None);
+        assert_eq!(parse_destination_host("https:///path"), None);
+        assert_eq!(parse_destination_host("http://[::1/"), None);
+        // Userinfo confusion through characters WHATWG parsers treat specially.
+        assert_eq!(
+            parse_destination_host("https://evil.com\\@api.stripe.com/x"),
+            None
+        );
+        assert_eq!(
+            parse_destination_host("https://evil.com\t@api.stripe.com/x"),
+            None
+        );
+        assert_eq!(parse_destination_host("http://[::1]x/"), None);
+    }
 }
@@ -0,0 +1,375 @@
+//! Git subcommand classifier.
+//!
+//! A single `^git ` pattern puts `git status` and `git push --force` in the same
+//! tier, and regexes over the raw segment are easy to sidestep with global
+//! options (`git -C repo push`). When a command tool has a `[tools.<name>.git]`
+//! table, segments whose program is `git` bypass the action patterns and are
+//! classified here instead:
+//!
+//! - safe global options (`-C <path>`, `--no-pager`, ...) are skipped;
+//! - any other global option (notably `-c`, which can define aliases and
+//!   hooks) makes the segment unclassifiable;
+//! - words are unquoted as the shell would (`"--force"` is `--force`); a
+//!   word holding a variable, command, or brace expansion makes the segment
+//!   unclassifiable, since it could expand to any flag;
+//! - the subcommand and its flags are matched against the configured rules,
+//!   highest tier first. A `push` refspec starting with `+` is a force push
+//!   and matches `--force`/`-f` rules as well as `+` rules.
+//!
+//! Anything unclassifiable or unlisted (including user-defined aliases) is
+//! rejected — deny by default, like the action patterns.
+
+use super::tier::Tier;
+use super::workspace::split_words;
+use crate::tools::bash::Shell;
+
+/// Global options that take a separate value argument and are safe to skip.
+const GLOBAL_OPTS_WITH_VALUE: &[&str] = &["-C", "--git-dir", "--work-tree", "--namespace"];
+
+/// Global options without a value that are safe to skip.
+const GLOBAL_FLAGS: &[&str] = &[
+    "--no-pager",
+    "-P",
+    "--paginate",
+    "-p",
+    "--bare",
+    "--no-optional-locks",
+    "--literal-pathspecs",
+    "--no-replace-objects",
+];
+
+/// One `"subcommand [flag ...]"` entry. Matches when the subcommand is equal and
+/// every listed flag is present among the arguments. The flag `+` matches an
+/// argument starting with `+` (a forced refspec).
+#[derive(Debug, Clone)]
+struct GitRule {
+    subcommand: String,
+    flags: Vec<String>,
+}
+
+impl GitRule {
+    fn parse(entry: &str) -> Result<Self, String> {
+        let mut words = entry.split_whitespace();
+        let subcommand = match words.next() {
+            Some(s) if !s.starts_with('-') => s.to_owned(),
+            _ => {
+                return Err(format!(
+                    "invalid git rule '{entry}': must start with a subcommand"
+                ));
+            }
+        };
+        let flags: Vec<String> = words.map(str::to_owned).collect();
+        if let Some(flag) = flags
+            .iter()
+            .find(|f| *f != "+" && (!is_flag(f) || f.contains('=')))
+        {
+            return Err(format!(
+                "invalid git rule '{entry}': '{flag}' is not a flag (expected -x, --name, or +)"
+            ));
+        }
+        Ok(Self { subcommand, flags })
+    }
+
+    fn matches(&self, subcommand: &str, args: &[&str]) -> bool {
+        self.subcommand == subcommand
+            && self
+                .flags
+                .iter()
+                .all(|flag| args.iter().any(|arg| flag_present(flag, arg)))
+    }
+}
+
+/// Compiled `[tools.<name>.git]` table.
+#[derive(Debug, Clone)]
+pub(super) struct CompiledGitRules {
+    rules: Vec<(Tier, GitRule)>, // Ordered: Commit first, then Act, then Observe
+}
+
+impl CompiledGitRules {
+    /// Build from per-tier rule lists. Errors describe the offending entry.
+    pub(super) fn new(
+        observe: &[String],
+        act: &[String],
+        commit: &[String],
+    ) -> Result<Self, String> {
+        let mut rules = Vec::with_capacity(observe.len() + act.len() + commit.len());
+        for (tier, entries) in [
+            (Tier::Commit, commit),
+            (Tier::Act, act),
+            (Tier::Observe, observe),
+        ] {
+            for entry in entries {
+                rules.push((tier, GitRule::parse(entry)?));
+            }
+        }
+        if rules.is_empty() {
+            return Err("git table must list at least one rule".to_owned());
+        }
+        Ok(Self { rules })
+    }
+
+    /// Classify a command segment, quoted as `shell` quotes.
+    ///
+    /// `None` if the segment is not a git invocation at all; `Some(None)` if it
+    /// is git but cannot be classified (rejected); `Some(Some(tier))` otherwise.
+    pub(super) fn classify(&self, segment: &str, shell: Shell) -> Option<Option<Tier>> {
+        let Some(words) = split_words(segment, shell) else {
+            // Unbalanced quotes: the shell refuses it, and so do we.
+            return (segment.split_whitespace().next() == Some("git")).then_some(None);
+        };
+        let (program, words) = words.split_first()?;
+        if program.text != "git" {
+            return None;
+        }
+        if words
+            .iter()
+            .any(|w| w.expands && w.text.contains(['$', '`', '{']))
+        {
+            return Some(None);
+        }
+        let words: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        let Some((subcommand, args)) = split_subcommand(&words) else {
+            return Some(None);
+        };
+        let mut args = args.to_vec();
+        if subcommand == "push" && args.iter().any(|a| a.starts_with('+')) {
+            args.extend(["--force", "-f"]);
+        }
+        Some(
+            self.rules
+                .iter()
+                .find(|(_, rule)| rule.matches(subcommand, &args))
+                .map(|(tier, _)| *tier),
+        )
+    }
+}
+
+/// Skip safe global options and return `(subcommand, args)`. `None` on an
+/// unknown global option or a missing subcommand.
+fn split_subcommand<'a, 'b>(words: &'b [&'a str]) -> Option<(&'a str, &'b [&'a str])> {
+    let mut i = 0;
+    while let Some(&word) = words.get(i) {
+        if !word.starts_with('-') {
+            return Some((word, &words[i + 1..]));
+        }
+        if GLOBAL_OPTS_WITH_VALUE.contains(&word) {
+            i += 2;
+        } else if GLOBAL_FLAGS.contains(&word)
+            || GLOBAL_OPTS_WITH_VALUE
+                .iter()
+                .any(|opt| opt.starts_with("--") && word.starts_with(&format!("{opt}=")))
+        {
+            i += 1;
+        } else {
+            return None;
+        }
+    }
+    None
+}
+
+fn is_flag(word: &str) -> bool {
+    match word.strip_prefix("--") {
+        Some(long) => !long.is_empty(),
+        None => word.len() == 2 && word.starts_with('-') && word != "--",
+    }
+}
+
+/// Whether `flag` (from a rule) appears in `arg`. Long flags match exactly or
+/// with `=value`; short flags also match inside bundles (`-f` in `-fd`).
+fn flag_present(flag: &str, arg: &str) -> bool {
+    if flag == "+" {
+        return arg.starts_with('+');
+    }
+    if flag.starts_with("--") {
+        return arg == flag
+            || arg
+                .strip_prefix(flag)
+                .is_some_and(|rest| rest.starts_with('='));
+    }
+    let letter = &flag[1..];
+    arg.starts_with('-') && !arg.starts_with("--") && arg[1..].contains(letter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> CompiledGitRules {
+        let list = |entries: &[&str]| entries.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        CompiledGitRules::new(
+            &list(&["status", "log", "diff"]),
+            &list(&["add", "commit", "reset", "clean -n", "push"]),
+            &list(&["push --force", "push -f", "reset --hard", "clean -f"]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn non_git_segments_are_ignored() {
+        assert_eq!(rules().classify("ls /tmp", Shell::Bash), None);
+        assert_eq!(rules().classify("gitk", Shell::Bash), None);
+    }
+
+    #[test]
+    fn subcommands_map_to_tiers() {
+        let r = rules();
+        assert_eq!(
+            r.classify("git status", Shell::Bash),
+            Some(Some(Tier::Observe))
+        );
+        assert_eq!(
+            r.classify("git commit -m wip", Shell::Bash),
+            Some(Some(Tier::Act))
+        );
+        assert_eq!(
+            r.classify("git push origin main", Shell::Bash),
+            Some(Some(Tier::Act))
+        );
+    }
+
+    #[test]
+    fn flags_raise_tier() {
+        let r = rules();
+        assert_eq!(
+            r.classify("git push --force origin", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            r.classify("git push -f", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            r.classify("git reset --hard HEAD~1", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            r.classify("git reset HEAD file", Shell::Bash),
+            Some(Some(Tier::Act))
+        );
+    }
+
+    #[test]
+    fn short_flag_bundles_match() {
+        assert_eq!(
+            rules().classify("git clean -fd", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            rules().classify("git clean -dn", Shell::Bash),
+            Some(Some(Tier::Act))
+        );
+    }
+
+    #[test]
+    fn long_flag_with_value_matches() {
+        assert_eq!(
+            rules().classify("git push --force=true", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+    }
+
+    #[test]
+    fn safe_global_options_are_skipped() {
+        let r = rules();
+        assert_eq!(
+            r.classify("git -C /repo push -f", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            r.classify("git --no-pager log", Shell::Bash),
+            Some(Some(Tier::Observe))
+        );
+        assert_eq!(
+            r.classify("git --git-dir=/repo/.git status", Shell::Bash),
+            Some(Some(Tier::Observe))
+        );
+    }
+
+    #[test]
+    fn unknown_global_option_is_unclassifiable() {
+        assert_eq!(
+            rules().classify("git -c alias.st=!sh st", Shell::Bash),
+            Some(None)
+        );
+        assert_eq!(
+            rules().classify("git --exec-path=/tmp status", Shell::Bash),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn unlisted_subcommand_or_alias_is_unclassifiable() {
+        assert_eq!(rules().classify("git rebase main", Shell::Bash), Some(None));
+        assert_eq!(rules().classify("git st", Shell::Bash), Some(None));
+        assert_eq!(rules().classify("git", Shell::Bash), Some(None));
+    }
+
+    #[test]
+    fn quoted_flags_match_unquoted() {
+        let r = rules();
+        assert_eq!(
+            r.classify("git push \"--force\" origin main", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            r.classify("git push '-f' origin main", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            r.classify("git push --for\\ce origin", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            r.classify("git commit -m \"wip: x\"", Shell::Bash),
+            Some(Some(Tier::Act))
+        );
+    }
+
+    #[test]
+    fn expansions_and_unbalanced_quotes_are_unclassifiable() {
+        let r = rules();
+        assert_eq!(
+            r.classify("git push $FLAGS origin", Shell::Bash),
+            Some(None)
+        );
+        assert_eq!(
+            r.classify("git push \"$(echo -f)\"", Shell::Bash),
+            Some(None)
+        );
+        assert_eq!(r.classify("git push {-f,origin}", Shell::Bash), Some(None));
+        assert_eq!(r.classify("git push '-f", Shell::Bash), Some(None));
+        assert_eq!(r.classify("echo 'unbalanced", Shell::Bash), None);
+        assert_eq!(
+            r.classify("git -C ~/repo status", Shell::Bash),
+            Some(Some(Tier::Observe))
+        );
+    }
+
+    #[test]
+    fn forced_refspec_is_a_force_push() {
+        let r = rules();
+        assert_eq!(
+            r.classify("git push origin +main", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        let plus =
+            CompiledGitRules::new(&[], &["push".to_owned()], &["push +".to_owned()]).unwrap();
+        assert_eq!(
+            plus.classify("git push origin +main:main", Shell::Bash),
+            Some(Some(Tier::Commit))
+        );
+        assert_eq!(
+            plus.classify("git push origin main", Shell::Bash),
+            Some(Some(Tier::Act))
+        );
+    }
+
+    #[test]
+    fn invalid_rules_rejected() {
+        let one = |s: &str| vec![s.to_owned()];
+        assert!(CompiledGitRules::new(&one("--force"), &[], &[]).is_err());
+        assert!(CompiledGitRules::new(&one("push origin"), &[], &[]).is_err());
+        assert!(CompiledGitRules::new(&one("push --force=yes"), &[], &[]).is_err());
+        assert!(CompiledGitRules::new(&[], &[], &[]).is_err());
+    }
+}
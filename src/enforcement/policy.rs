@@ -1,14 +1,30 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use regex::{Regex, RegexSet};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::{info, info_span};
 
-use super::extraction::MatchSource;
+use super::DecisionKind;
+use super::capability::{Caveat, ExecutionLimits};
+use super::extraction::{MatchSource, is_database_name, parse_destination_host};
+use super::git::CompiledGitRules;
+use super::heuristics::CompiledHeuristics;
+use super::output::{ANSI_PATTERN, CompiledOutputRules, OutputFilter};
+use super::secrets::{CompiledSecretRules, KNOWN_FORMATS, SecretAction};
+use super::shell;
 use super::tier::Tier;
+use super::workspace::CompiledWorkspace;
 use crate::error::CherubError;
+use crate::retry::RetryPolicy;
+use crate::tools::bash::ShellConfig;
+use crate::tools::sandbox::{ContainerMount, ContainerRuntime, ContainerSandbox, SandboxBackend};
+use crate::tools::seccomp::{self, Syscall};
+use crate::tools::sql::Database;
 
 const MAX_POLICY_FILE_SIZE: u64 = 64 * 1024; // 64 KiB
 
@@ -17,10 +33,167 @@ const MAX_POLICY_FILE_SIZE: u64 = 64 * 1024; // 64 KiB
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 struct PolicyFile {
+    #[serde(default)]
+    token_ttl_secs: Option<u64>,
+    #[serde(default)]
+    max_delegation_depth: u32,
     #[serde(default)]
     tools: HashMap<String, ToolConfig>,
     #[serde(default)]
     budget: Option<BudgetConfig>,
+    #[serde(default)]
+    risk: Option<RiskConfig>,
+    #[serde(default)]
+    output: Option<OutputConfig>,
+    #[serde(default)]
+    secrets: Option<SecretsConfig>,
+    #[serde(default)]
+    escalation: Option<EscalationConfig>,
+    #[serde(default)]
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    #[serde(default)]
+    heuristics: Option<HeuristicsConfig>,
+    #[serde(default)]
+    execution: Option<ExecutionConfig>,
+    #[serde(default)]
+    workspace: Option<WorkspaceConfig>,
+}
+
+/// `[workspace]`: directory that command tools are confined to.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkspaceConfig {
+    root: String,
+    #[serde(default = "default_on_escape")]
+    on_escape: OnConstraintFailureValue,
+    #[serde(default = "default_workspace_allow_paths")]
+    allow_paths: Vec<String>,
+}
+
+fn default_on_escape() -> OnConstraintFailureValue {
+    OnConstraintFailureValue::Reject
+}
+
+fn default_workspace_allow_paths() -> Vec<String> {
+    vec!["/dev/null".to_owned()]
+}
+
+/// `[execution]`: per-tier resource limits applied by the execution layer.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExecutionConfig {
+    /// Extra environment variables passed to tool processes (beyond PATH, HOME, LANG).
+    #[serde(default)]
+    env: Vec<String>,
+    /// Settings for `sandbox = "container"`.
+    #[serde(default)]
+    container: Option<ContainerConfig>,
+    #[serde(default)]
+    observe: Option<TierExecutionConfig>,
+    #[serde(default)]
+    act: Option<TierExecutionConfig>,
+    #[serde(default)]
+    commit: Option<TierExecutionConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TierExecutionConfig {
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+    #[serde(default)]
+    max_output_bytes: Option<usize>,
+    #[serde(default)]
+    sandbox: Option<SandboxValue>,
+    #[serde(default)]
+    seccomp: bool,
+    #[serde(default)]
+    tmp_quota_bytes: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SandboxValue {
+    None,
+    Bubblewrap,
+    Container,
+}
+
+/// `[execution.container]`: the ephemeral container used by `sandbox = "container"`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContainerConfig {
+    image: String,
+    #[serde(default = "default_container_runtime")]
+    runtime: ContainerRuntime,
+    #[serde(default)]
+    network: bool,
+    #[serde(default)]
+    mounts: Vec<ContainerMountConfig>,
+}
+
+fn default_container_runtime() -> ContainerRuntime {
+    ContainerRuntime::Docker
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContainerMountConfig {
+    host: String,
+    container: String,
+    #[serde(default = "default_true")]
+    read_only: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HeuristicsConfig {
+    #[serde(default = "default_heuristics_max_length")]
+    max_length: usize,
+    #[serde(default = "default_true")]
+    base64_blobs: bool,
+    #[serde(default = "default_true")]
+    hex_payloads: bool,
+    #[serde(default = "default_true")]
+    pipe_to_shell: bool,
+    #[serde(default = "default_true")]
+    dotfile_writes: bool,
+}
+
+fn default_heuristics_max_length() -> usize {
+    4096
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CircuitBreakerConfig {
+    failure_threshold: u32,
+    window_secs: u64,
+    #[serde(default = "default_on_exceeded")]
+    on_open: OnConstraintFailureValue,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EscalationConfig {
+    timeout_secs: u64,
+    #[serde(default = "default_on_timeout")]
+    on_timeout: TimeoutFallbackValue,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TimeoutFallbackValue {
+    Deny,
+    Observe,
+}
+
+fn default_on_timeout() -> TimeoutFallbackValue {
+    TimeoutFallbackValue::Deny
 }
 
 #[derive(Deserialize)]
@@ -36,6 +209,86 @@ fn default_on_exceeded() -> OnConstraintFailureValue {
     OnConstraintFailureValue::Escalate
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RiskConfig {
+    #[serde(default = "default_act_weight")]
+    act_weight: u32,
+    #[serde(default = "default_commit_weight")]
+    commit_weight: u32,
+    escalate_threshold: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OutputConfig {
+    #[serde(default)]
+    filters: Vec<OutputFilterValue>,
+    max_bytes: Option<usize>,
+    #[serde(default)]
+    block: Vec<String>,
+    #[serde(default)]
+    redact: Vec<String>,
+    #[serde(default)]
+    redact_secrets: bool,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum OutputFilterValue {
+    StripAnsi,
+    CollapseBinary,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SecretsConfig {
+    #[serde(default = "default_secret_min_entropy")]
+    min_entropy: f64,
+    #[serde(default = "default_secret_min_length")]
+    min_length: usize,
+    #[serde(default)]
+    patterns: Vec<String>,
+    #[serde(default = "default_secret_on_escalate")]
+    on_observe: SecretActionValue,
+    #[serde(default = "default_secret_on_escalate")]
+    on_act: SecretActionValue,
+    #[serde(default = "default_secret_on_commit")]
+    on_commit: SecretActionValue,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SecretActionValue {
+    Allow,
+    Escalate,
+    Reject,
+}
+
+fn default_secret_min_entropy() -> f64 {
+    4.5
+}
+
+fn default_secret_min_length() -> usize {
+    24
+}
+
+fn default_secret_on_escalate() -> SecretActionValue {
+    SecretActionValue::Escalate
+}
+
+fn default_secret_on_commit() -> SecretActionValue {
+    SecretActionValue::Reject
+}
+
+fn default_act_weight() -> u32 {
+    1
+}
+
+fn default_commit_weight() -> u32 {
+    5
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "snake_case")]
 enum MatchSourceValue {
@@ -45,17 +298,70 @@ enum MatchSourceValue {
     HttpStructured,
     /// For MCP tools: extracts `"{server}:{tool}"` from params.
     McpStructured,
+    /// For the `patch` tool: extracts `"{op}:{path}"` per file in the diff.
+    Patch,
+    /// For the `sql` tool: extracts `"{verb}:{database}"` per statement.
+    Sql,
+    /// For the `kubectl` tool: extracts `"{verb}:{namespace}"`.
+    Kubectl,
+    /// Extracts the param named by the tool's `match_field`.
+    Field,
 }
 
-impl From<MatchSourceValue> for MatchSource {
-    fn from(v: MatchSourceValue) -> Self {
-        match v {
-            MatchSourceValue::Command => MatchSource::Command,
-            MatchSourceValue::Structured => MatchSource::Structured,
-            MatchSourceValue::HttpStructured => MatchSource::HttpStructured,
-            MatchSourceValue::McpStructured => MatchSource::McpStructured,
-        }
+/// Resolve the tool's `match_source` (+ `match_field` / `command_param`) into an
+/// extraction strategy. `match_field` is required by `"field"` and rejected
+/// everywhere else; `command_param` is only valid with `"command"`.
+fn compile_match_source(
+    name: &str,
+    source: MatchSourceValue,
+    field: Option<String>,
+    command_param: Option<String>,
+) -> Result<MatchSource, CherubError> {
+    if command_param.is_some() && !matches!(source, MatchSourceValue::Command) {
+        return Err(CherubError::PolicyValidation(format!(
+            "tool '{name}': command_param is only valid with match_source \"command\""
+        )));
+    }
+    match (source, field) {
+        (MatchSourceValue::Field, Some(f)) if !f.is_empty() => Ok(MatchSource::Field(f)),
+        (MatchSourceValue::Field, _) => Err(CherubError::PolicyValidation(format!(
+            "tool '{name}': match_source \"field\" requires a non-empty match_field"
+        ))),
+        (_, Some(_)) => Err(CherubError::PolicyValidation(format!(
+            "tool '{name}': match_field is only valid with match_source \"field\""
+        ))),
+        (MatchSourceValue::Command, None) => compile_command_param(name, command_param),
+        (MatchSourceValue::Structured, None) => Ok(MatchSource::Structured),
+        (MatchSourceValue::HttpStructured, None) => Ok(MatchSource::HttpStructured),
+        (MatchSourceValue::McpStructured, None) => Ok(MatchSource::McpStructured),
+        (MatchSourceValue::Patch, None) => Ok(MatchSource::Patch),
+        (MatchSourceValue::Sql, None) => Ok(MatchSource::Sql),
+        (MatchSourceValue::Kubectl, None) => Ok(MatchSource::Kubectl),
+    }
+}
+
+/// `command_param = "cmd"` reads a single command from `params["cmd"]`;
+/// `"commands[]"` reads a list of commands from `params["commands"]`.
+fn compile_command_param(name: &str, param: Option<String>) -> Result<MatchSource, CherubError> {
+    let Some(param) = param else {
+        return Ok(MatchSource::Command);
+    };
+    let (field, array) = match param.strip_suffix("[]") {
+        Some(field) => (field, true),
+        None => (param.as_str(), false),
+    };
+    if field.is_empty() || field.contains("[]") {
+        return Err(CherubError::PolicyValidation(format!(
+            "tool '{name}': invalid command_param '{param}'"
+        )));
     }
+    if field == "command" && !array {
+        return Ok(MatchSource::Command);
+    }
+    Ok(MatchSource::CommandField {
+        field: field.to_owned(),
+        array,
+    })
 }
 
 fn default_match_source() -> MatchSourceValue {
@@ -69,9 +375,113 @@ struct ToolConfig {
     #[serde(default = "default_match_source")]
     match_source: MatchSourceValue,
     #[serde(default)]
+    match_field: Option<String>,
+    #[serde(default)]
+    command_param: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
     actions: HashMap<String, ActionConfig>,
     #[serde(default)]
     constraints: Vec<ConstraintConfig>,
+    #[serde(default)]
+    destinations: Option<DestinationsConfig>,
+    #[serde(default)]
+    suggestions: Vec<SuggestionConfig>,
+    #[serde(default)]
+    git: Option<GitConfig>,
+    #[serde(default)]
+    caveats: Option<CaveatsConfig>,
+    #[serde(default)]
+    seccomp: Option<SeccompConfig>,
+    #[serde(default)]
+    shell: Option<ShellConfig>,
+    #[serde(default)]
+    retry: Option<RetryPolicy>,
+    #[serde(default)]
+    databases: HashMap<String, DatabaseConfig>,
+}
+
+/// `[tools.<name>.seccomp]`: replaces the shipped syscall deny list for a tier.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SeccompConfig {
+    #[serde(default)]
+    observe: Option<Vec<Syscall>>,
+    #[serde(default)]
+    act: Option<Vec<Syscall>>,
+}
+
+/// `[tools.<name>.caveats]`: conditions attached to every token minted for the
+/// tool and verified at execution time.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CaveatsConfig {
+    #[serde(default)]
+    max_output_bytes: Option<usize>,
+    #[serde(default)]
+    path_prefix: Option<String>,
+    #[serde(default = "default_path_field")]
+    path_field: String,
+    #[serde(default)]
+    deadline_secs: Option<u64>,
+}
+
+fn default_path_field() -> String {
+    "path".to_owned()
+}
+
+/// `[tools.<name>.databases.<db>]`: one SQL tool connection. Exactly one of
+/// `sqlite` (a file path) or `postgres` (a connection string).
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DatabaseConfig {
+    #[serde(default)]
+    sqlite: Option<PathBuf>,
+    #[serde(default)]
+    postgres: Option<String>,
+    #[serde(default)]
+    password_env: Option<String>,
+}
+
+/// `[tools.<name>.git]`: per-tier lists of `"subcommand [flag ...]"` rules.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GitConfig {
+    #[serde(default)]
+    observe: Vec<String>,
+    #[serde(default)]
+    act: Vec<String>,
+    #[serde(default)]
+    commit: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuggestionConfig {
+    pattern: String,
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DestinationsConfig {
+    #[serde(default = "default_url_field")]
+    url_field: String,
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    escalate: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    allow_ip_literals: bool,
+    #[serde(default)]
+    max_redirects: u8,
+}
+
+fn default_url_field() -> String {
+    "url".to_owned()
 }
 
 #[derive(Deserialize)]
@@ -104,6 +514,10 @@ enum ConstraintOp {
     OneOf,
     ContainsAll,
     Matches,
+    /// String value does not match the regex `value` (screening, e.g. script bodies).
+    NotMatches,
+    /// Byte length of a string, or element count of an array, is at most `value`.
+    MaxLen,
 }
 
 #[derive(Deserialize)]
@@ -141,10 +555,107 @@ pub struct CompiledBudget {
     pub(crate) on_exceeded: OnConstraintFailure,
 }
 
+/// Compiled cumulative risk scoring from the `[risk]` section.
+///
+/// Every executed Act/Commit action adds its weight to the session's risk score.
+/// Once the score reaches `escalate_threshold`, subsequent Act decisions escalate.
+#[derive(Debug, Clone)]
+pub struct CompiledRisk {
+    pub(crate) act_weight: u32,
+    pub(crate) commit_weight: u32,
+    pub(crate) escalate_threshold: u32,
+}
+
+impl CompiledRisk {
+    /// Risk weight contributed by one executed action at `tier`. Observe is free.
+    pub(crate) fn weight(&self, tier: Tier) -> u32 {
+        match tier {
+            Tier::Observe => 0,
+            Tier::Act => self.act_weight,
+            Tier::Commit => self.commit_weight,
+        }
+    }
+}
+
+/// Compiled `[circuit_breaker]` section.
+///
+/// A tool whose executions failed `failure_threshold` times within `window`
+/// has its breaker open: Act/Commit proposals for it get `on_open` until the
+/// failures age out of the window (or a success clears them).
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledBreaker {
+    pub(crate) failure_threshold: u32,
+    pub(crate) window: Duration,
+    pub(crate) on_open: OnConstraintFailure,
+}
+
 #[derive(Clone)]
 pub struct Policy {
     tools: Vec<CompiledTool>,
     pub(crate) budget: Option<CompiledBudget>,
+    pub(crate) risk: Option<CompiledRisk>,
+    pub(crate) output: Option<CompiledOutputRules>,
+    pub(crate) secrets: Option<CompiledSecretRules>,
+    pub(crate) escalation: Option<CompiledEscalation>,
+    pub(crate) breaker: Option<CompiledBreaker>,
+    pub(crate) heuristics: Option<CompiledHeuristics>,
+    pub(crate) token_ttl: Option<Duration>, // Lifetime of tokens minted by evaluate()
+    pub(crate) hash: String, // Hex SHA-256 of the TOML source, recorded on every token
+    pub(crate) max_delegation_depth: u32, // 0 disables `enforcement::delegate()`
+    pub(crate) execution: CompiledExecution,
+    pub(crate) workspace: Option<CompiledWorkspace>, // Root pinned on every token
+}
+
+/// Compiled `[execution]` section. Tiers without a table get no limits (tool
+/// defaults apply).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CompiledExecution {
+    observe: ExecutionLimits,
+    act: ExecutionLimits,
+    commit: ExecutionLimits,
+    pub(crate) env: Vec<String>, // Passed through to spawned tool processes
+    seccomp: Vec<Tier>,          // Tiers whose processes get a syscall filter
+}
+
+impl CompiledExecution {
+    /// Limits for executing at `tier`, attached to every token minted for it.
+    pub(crate) fn limits(&self, tier: Tier) -> ExecutionLimits {
+        match tier {
+            Tier::Observe => self.observe.clone(),
+            Tier::Act => self.act.clone(),
+            Tier::Commit => self.commit.clone(),
+        }
+    }
+
+    /// Whether processes spawned at `tier` run under a seccomp filter.
+    pub(crate) fn seccomp(&self, tier: Tier) -> bool {
+        self.seccomp.contains(&tier)
+    }
+}
+
+/// Compiled `[escalation]` section: how long to wait for a human, and what to
+/// do when nobody answers.
+#[derive(Clone, Debug)]
+pub(crate) struct CompiledEscalation {
+    pub(crate) timeout: Duration,
+    pub(crate) on_timeout: TimeoutFallback,
+}
+
+/// Resolution of an escalation that timed out without a human response.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TimeoutFallback {
+    /// Treat as denied.
+    Deny,
+    /// Approve only if the escalation was at Observe tier; deny anything higher,
+    /// and anything a security stage flagged (see `flagged_by_guards`).
+    Observe,
+}
+
+impl TimeoutFallback {
+    /// Whether a timed-out escalation at `tier` should be approved.
+    pub(crate) fn approves(self, tier: Tier) -> bool {
+        matches!((self, tier), (TimeoutFallback::Observe, Tier::Observe))
+    }
 }
 
 impl std::fmt::Debug for Policy {
@@ -152,29 +663,191 @@ impl std::fmt::Debug for Policy {
         f.debug_struct("Policy")
             .field("tool_count", &self.tools.len())
             .field("has_budget", &self.budget.is_some())
+            .field("has_risk", &self.risk.is_some())
+            .field("has_output_rules", &self.output.is_some())
+            .field("has_secret_rules", &self.secrets.is_some())
+            .field("escalation", &self.escalation)
+            .field("breaker", &self.breaker)
+            .field("has_heuristics", &self.heuristics.is_some())
+            .field("token_ttl", &self.token_ttl)
+            .field("hash", &self.hash)
+            .field("max_delegation_depth", &self.max_delegation_depth)
+            .field("execution", &self.execution)
+            .field("workspace", &self.workspace)
             .finish()
     }
 }
 
 #[derive(Clone)]
 pub(super) struct CompiledTool {
-    name: String,
+    pub(super) name: String,
+    aliases: Vec<String>, // Alternate names resolving to this tool
     enabled: bool,
     match_source: MatchSource, // How to extract action strings from params
     actions: Vec<CompiledAction>, // Ordered: Commit first, then Act, then Observe
     constraints: Vec<CompiledConstraint>, // Tool-level: hard reject on failure
+    destinations: Option<CompiledDestinations>, // Host gating for URL-bearing tools
+    suggestions: Vec<CompiledSuggestion>, // Rejection feedback, first match wins
+    git: Option<CompiledGitRules>, // Subcommand tiers for `git` segments
+    caveats: CompiledCaveats,  // Attached to every token minted for the tool
+    seccomp: CompiledSeccomp,  // Per-tier deny-list overrides
+    shell: Option<ShellConfig>, // Shell for command tools; None = bash
+    retry: Option<RetryPolicy>, // Transient-failure retries; None = run once
+    databases: BTreeMap<String, Database>, // SQL tool connections, by name
+}
+
+/// Compiled `[tools.<name>.seccomp]` section. `None` → the shipped profile.
+#[derive(Clone, Default)]
+struct CompiledSeccomp {
+    observe: Option<Vec<Syscall>>,
+    act: Option<Vec<Syscall>>,
+}
+
+/// Compiled `[tools.<name>.caveats]` section. Empty when the table is absent.
+#[derive(Clone, Default)]
+struct CompiledCaveats {
+    max_output_bytes: Option<usize>,
+    path_prefix: Option<(String, String)>, // (param field, prefix)
+    deadline: Option<Duration>,            // Relative to issuance
+}
+
+/// Operator-authored hint returned with a rejection whose action string matches.
+#[derive(Clone)]
+struct CompiledSuggestion {
+    pattern: Regex,
+    message: String,
+}
+
+/// Compiled `[tools.<name>.destinations]` section.
+///
+/// Host patterns are matched against the parsed host only (never the full URL),
+/// so path/query tricks and `trusted@evil` userinfo cannot satisfy a pattern.
+///
+/// Carried in capability tokens of tools with destinations so the tool can
+/// re-check the URL it requests and each redirect hop; serialized as its
+/// source config.
+#[derive(Clone)]
+pub(crate) struct CompiledDestinations {
+    url_field: String,
+    allow: RegexSet,
+    escalate: RegexSet,
+    deny: RegexSet,
+    allow_ip_literals: bool,
+    pub(crate) max_redirects: u8, // 0 = redirects are returned, not followed
+}
+
+impl Serialize for CompiledDestinations {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        DestinationsConfig {
+            url_field: self.url_field.clone(),
+            allow: self.allow.patterns().to_vec(),
+            escalate: self.escalate.patterns().to_vec(),
+            deny: self.deny.patterns().to_vec(),
+            allow_ip_literals: self.allow_ip_literals,
+            max_redirects: self.max_redirects,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CompiledDestinations {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let config = DestinationsConfig::deserialize(deserializer)?;
+        compile_destinations("sealed", config).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Outcome of a destination check. Deny by default: unmatched hosts are `Deny`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DestinationVerdict {
+    Allow,
+    Escalate,
+    Deny,
+}
+
+impl CompiledDestinations {
+    fn check(&self, params: &serde_json::Value) -> DestinationVerdict {
+        match params.get(&self.url_field).and_then(|v| v.as_str()) {
+            Some(url) => self.check_url(url),
+            None => DestinationVerdict::Deny,
+        }
+    }
+
+    /// Whether a redirect to `url` may be followed without a new evaluation:
+    /// only hosts the policy allows outright. Escalate needs a human, so it
+    /// stops the chain like Deny.
+    #[cfg_attr(not(feature = "credentials"), allow(dead_code))]
+    pub(crate) fn allows_redirect(&self, url: &str) -> bool {
+        self.check_url(url) == DestinationVerdict::Allow
+    }
+
+    /// Whether the URL a tool is about to request, as its HTTP client parsed
+    /// it, is one the policy could have cleared (allowed or escalated). Catches
+    /// any disagreement between enforcement's host parsing and the client's.
+    #[cfg_attr(not(feature = "credentials"), allow(dead_code))]
+    pub(crate) fn permits_request(&self, url: &str) -> bool {
+        self.check_url(url) != DestinationVerdict::Deny
+    }
+
+    fn check_url(&self, url: &str) -> DestinationVerdict {
+        let Some(host) = parse_destination_host(url) else {
+            return DestinationVerdict::Deny;
+        };
+
+        if self.deny.is_match(&host) {
+            return DestinationVerdict::Deny;
+        }
+        if !self.allow_ip_literals && host.parse::<std::net::IpAddr>().is_ok() {
+            return DestinationVerdict::Deny;
+        }
+        if self.escalate.is_match(&host) {
+            DestinationVerdict::Escalate
+        } else if self.allow.is_match(&host) {
+            DestinationVerdict::Allow
+        } else {
+            DestinationVerdict::Deny
+        }
+    }
 }
 
 #[derive(Clone)]
 pub(super) struct CompiledAction {
-    #[allow(dead_code)] // Used for diagnostics and future constraint error messages
     pub(super) name: String,
     pub(super) tier: Tier,
     patterns: RegexSet,
+    /// Per-pattern match counters, indexed like `patterns`.
+    ///
+    /// `Arc` is justified: `Policy` is cloned once per session (Telegram), and the
+    /// operator wants hit counts aggregated across every clone. Atomics keep
+    /// `match_action()` on `&self` — no lock, no `&mut Policy` threading.
+    hits: Arc<[AtomicU64]>,
     pub(super) constraints: Vec<CompiledConstraint>,
     pub(super) on_constraint_failure: OnConstraintFailure,
 }
 
+/// Hit count for a single policy pattern, as reported by `Policy::stats()`.
+///
+/// Operator-facing diagnostics only. Never surfaced to the agent — policy opacity
+/// still applies to everything the model sees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternStats {
+    pub tool: String,
+    pub action: String,
+    pub tier: Tier,
+    pub pattern: String,
+    pub hits: u64,
+}
+
+/// What the agent may be told about the policy, for the system prompt: tool
+/// names only. Action names, patterns, and tiers stay opaque.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PolicySummary {
+    /// Enabled tools, by configured name.
+    pub enabled: Vec<String>,
+    /// Enabled tools with some operations that wait for human approval.
+    pub approval_required: Vec<String>,
+}
+
 #[derive(Clone)]
 pub(super) struct CompiledConstraint {
     field: String,
@@ -191,6 +864,8 @@ enum Predicate {
     OneOf(Vec<serde_json::Value>),
     ContainsAll(Vec<String>),
     Matches(Regex),
+    NotMatches(Regex),
+    MaxLen(usize),
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -212,6 +887,7 @@ impl FromStr for Policy {
             .into_iter()
             .map(|(name, config)| compile_tool(name, config))
             .collect::<Result<Vec<_>, _>>()?;
+        check_tool_names_unique(&tools)?;
 
         let budget = file.budget.map(|b| CompiledBudget {
             session_limit_usd: b.session_limit_usd,
@@ -222,19 +898,248 @@ impl FromStr for Policy {
             },
         });
 
-        Ok(Self { tools, budget })
-    }
-}
-
-impl Policy {
-    /// Load a policy from a TOML file. Checks file size before reading.
-    pub fn load(path: &Path) -> Result<Self, CherubError> {
-        let _span = info_span!("policy_load", path = %path.display()).entered();
-
-        let metadata = std::fs::metadata(path)
-            .map_err(|e| CherubError::PolicyLoad(format!("cannot read {}: {e}", path.display())))?;
-
-        if metadata.len() > MAX_POLICY_FILE_SIZE {
+        let risk = file
+            .risk
+            .map(|r| {
+                if r.escalate_threshold == 0 {
+                    return Err(CherubError::PolicyValidation(
+                        "risk: escalate_threshold must be greater than 0".to_owned(),
+                    ));
+                }
+                Ok(CompiledRisk {
+                    act_weight: r.act_weight,
+                    commit_weight: r.commit_weight,
+                    escalate_threshold: r.escalate_threshold,
+                })
+            })
+            .transpose()?;
+
+        let output = file.output.map(compile_output_rules).transpose()?;
+        let secrets = file.secrets.map(compile_secret_rules).transpose()?;
+        let breaker = file
+            .circuit_breaker
+            .map(|b| {
+                if b.failure_threshold == 0 || b.window_secs == 0 {
+                    return Err(CherubError::PolicyValidation(
+                        "circuit_breaker: failure_threshold and window_secs must be greater than 0"
+                            .to_owned(),
+                    ));
+                }
+                Ok(CompiledBreaker {
+                    failure_threshold: b.failure_threshold,
+                    window: Duration::from_secs(b.window_secs),
+                    on_open: match b.on_open {
+                        OnConstraintFailureValue::Reject => OnConstraintFailure::Reject,
+                        OnConstraintFailureValue::Escalate => OnConstraintFailure::Escalate,
+                    },
+                })
+            })
+            .transpose()?;
+        let heuristics = file
+            .heuristics
+            .map(|h| {
+                if h.max_length == 0 {
+                    return Err(CherubError::PolicyValidation(
+                        "heuristics: max_length must be greater than 0".to_owned(),
+                    ));
+                }
+                CompiledHeuristics::new(
+                    Some(h.max_length),
+                    h.base64_blobs,
+                    h.hex_payloads,
+                    h.pipe_to_shell,
+                    h.dotfile_writes,
+                )
+                .map_err(|e| CherubError::PolicyValidation(format!("heuristics: {e}")))
+            })
+            .transpose()?;
+        let escalation = file
+            .escalation
+            .map(|e| {
+                if e.timeout_secs == 0 {
+                    return Err(CherubError::PolicyValidation(
+                        "escalation: timeout_secs must be greater than 0".to_owned(),
+                    ));
+                }
+                Ok(CompiledEscalation {
+                    timeout: Duration::from_secs(e.timeout_secs),
+                    on_timeout: match e.on_timeout {
+                        TimeoutFallbackValue::Deny => TimeoutFallback::Deny,
+                        TimeoutFallbackValue::Observe => TimeoutFallback::Observe,
+                    },
+                })
+            })
+            .transpose()?;
+
+        let execution = file
+            .execution
+            .map(compile_execution)
+            .transpose()?
+            .unwrap_or_default();
+
+        let workspace = file
+            .workspace
+            .map(|w| {
+                let on_escape = match w.on_escape {
+                    OnConstraintFailureValue::Reject => OnConstraintFailure::Reject,
+                    OnConstraintFailureValue::Escalate => OnConstraintFailure::Escalate,
+                };
+                CompiledWorkspace::new(&w.root, on_escape, &w.allow_paths)
+                    .map_err(CherubError::PolicyValidation)
+            })
+            .transpose()?;
+
+        let token_ttl = match file.token_ttl_secs {
+            Some(0) => {
+                return Err(CherubError::PolicyValidation(
+                    "token_ttl_secs must be greater than 0".to_owned(),
+                ));
+            }
+            secs => secs.map(Duration::from_secs),
+        };
+
+        Ok(Self {
+            tools,
+            budget,
+            risk,
+            output,
+            secrets,
+            escalation,
+            breaker,
+            heuristics,
+            token_ttl,
+            hash: source_hash(content),
+            max_delegation_depth: file.max_delegation_depth,
+            execution,
+            workspace,
+        })
+    }
+}
+
+fn compile_execution(config: ExecutionConfig) -> Result<CompiledExecution, CherubError> {
+    let container = config.container.map(compile_container).transpose()?;
+    let tier = |name: &str, c: Option<TierExecutionConfig>| {
+        let Some(c) = c else {
+            return Ok(ExecutionLimits::default());
+        };
+        let invalid = |msg: &str| CherubError::PolicyValidation(format!("execution.{name}: {msg}"));
+        if c.timeout_secs == Some(0) {
+            return Err(invalid("timeout_secs must be greater than 0"));
+        }
+        if c.max_output_bytes == Some(0) {
+            return Err(invalid("max_output_bytes must be greater than 0"));
+        }
+        if c.tmp_quota_bytes == Some(0) {
+            return Err(invalid("tmp_quota_bytes must be greater than 0"));
+        }
+        // Commit runs unconfined: it only ever executes after a human approved it.
+        let confined = c.seccomp
+            || matches!(
+                c.sandbox,
+                Some(SandboxValue::Bubblewrap | SandboxValue::Container)
+            );
+        if confined && name == "commit" {
+            return Err(invalid(
+                "sandbox and seccomp are not supported; commit runs unconfined",
+            ));
+        }
+        let sandbox = match c.sandbox {
+            None | Some(SandboxValue::None) => None,
+            Some(SandboxValue::Bubblewrap) => Some(SandboxBackend::Bubblewrap),
+            Some(SandboxValue::Container) => match container {
+                Some(ref container) => Some(SandboxBackend::Container(container.clone())),
+                None => {
+                    return Err(invalid(
+                        "sandbox = \"container\" requires [execution.container]",
+                    ));
+                }
+            },
+        };
+        // The filter would apply to the sandbox launcher, which needs the very
+        // syscalls (mount, connect to the runtime daemon) the profiles deny.
+        if c.seccomp && sandbox.is_some() {
+            return Err(invalid("seccomp cannot be combined with a sandbox"));
+        }
+        Ok(ExecutionLimits {
+            timeout: c.timeout_secs.map(Duration::from_secs),
+            max_output_bytes: c.max_output_bytes,
+            sandbox,
+            tmp_quota_bytes: c.tmp_quota_bytes,
+        })
+    };
+    if let Some(bad) = config
+        .env
+        .iter()
+        .find(|name| name.is_empty() || name.contains(['=', '\0']))
+    {
+        return Err(CherubError::PolicyValidation(format!(
+            "execution.env: invalid variable name '{bad}'"
+        )));
+    }
+    let seccomp = [(Tier::Observe, &config.observe), (Tier::Act, &config.act)]
+        .into_iter()
+        .filter(|(_, c)| c.as_ref().is_some_and(|c| c.seccomp))
+        .map(|(tier, _)| tier)
+        .collect();
+    Ok(CompiledExecution {
+        env: config.env,
+        seccomp,
+        observe: tier("observe", config.observe)?,
+        act: tier("act", config.act)?,
+        commit: tier("commit", config.commit)?,
+    })
+}
+
+fn compile_container(config: ContainerConfig) -> Result<ContainerSandbox, CherubError> {
+    let invalid =
+        |msg: String| CherubError::PolicyValidation(format!("execution.container: {msg}"));
+    if config.image.trim().is_empty() {
+        return Err(invalid("image must not be empty".to_owned()));
+    }
+    let mounts = config
+        .mounts
+        .into_iter()
+        .map(|m| {
+            for path in [&m.host, &m.container] {
+                if !Path::new(path).is_absolute() || path.contains(':') {
+                    return Err(invalid(format!(
+                        "mount path '{path}' must be absolute and contain no ':'"
+                    )));
+                }
+            }
+            Ok(ContainerMount {
+                host: m.host.into(),
+                container: m.container.into(),
+                read_only: m.read_only,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(ContainerSandbox {
+        runtime: config.runtime,
+        image: config.image,
+        network: config.network,
+        mounts,
+    })
+}
+
+/// Hex SHA-256 of a policy's TOML source.
+fn source_hash(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+impl Policy {
+    /// Load a policy from a TOML file. Checks file size before reading.
+    pub fn load(path: &Path) -> Result<Self, CherubError> {
+        let _span = info_span!("policy_load", path = %path.display()).entered();
+
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| CherubError::PolicyLoad(format!("cannot read {}: {e}", path.display())))?;
+
+        if metadata.len() > MAX_POLICY_FILE_SIZE {
             return Err(CherubError::PolicyLoad(format!(
                 "policy file exceeds {MAX_POLICY_FILE_SIZE} byte limit"
             )));
@@ -248,8 +1153,76 @@ impl Policy {
         Ok(policy)
     }
 
+    /// Resolve an alias to its tool's configured name. Unknown names pass through.
+    pub(super) fn canonical_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.find_tool(name).map_or(name, |t| t.name.as_str())
+    }
+
+    /// Look up a tool by its name or any of its `aliases`.
     pub(super) fn find_tool(&self, name: &str) -> Option<&CompiledTool> {
-        self.tools.iter().find(|t| t.name == name)
+        self.tools
+            .iter()
+            .find(|t| t.name == name || t.aliases.iter().any(|a| a == name))
+    }
+
+    /// Preview the decision class for a proposed call without evaluating it.
+    ///
+    /// Borrows instead of consuming a `ToolInvocation`, mints no token, and does
+    /// not count pattern hits — for UIs that show what the agent is about to do.
+    /// Session state (budget, risk) is not considered; the real `evaluate()` can
+    /// still escalate or reject where this preview allows.
+    pub fn check(&self, tool: &str, params: &serde_json::Value) -> DecisionKind {
+        let _span = info_span!("policy_check", tool).entered();
+        super::decide(tool, params, self, None, false)
+    }
+
+    /// Snapshot of per-pattern hit counters, sorted by tool, then tier (highest first),
+    /// then action name. Patterns with `hits == 0` have never decided an action and
+    /// are candidates for pruning.
+    ///
+    /// Counters are shared across clones of this policy.
+    pub fn stats(&self) -> Vec<PatternStats> {
+        let mut stats: Vec<PatternStats> = self
+            .tools
+            .iter()
+            .flat_map(|tool| {
+                tool.actions.iter().flat_map(move |action| {
+                    action
+                        .patterns
+                        .patterns()
+                        .iter()
+                        .zip(action.hits.iter())
+                        .map(move |(pattern, hits)| PatternStats {
+                            tool: tool.name.clone(),
+                            action: action.name.clone(),
+                            tier: action.tier,
+                            pattern: pattern.clone(),
+                            hits: hits.load(Ordering::Relaxed),
+                        })
+                })
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            a.tool
+                .cmp(&b.tool)
+                .then(b.tier.cmp(&a.tier))
+                .then(a.action.cmp(&b.action))
+        });
+        stats
+    }
+    /// Summary of the policy for the system prompt, sorted by tool name. See
+    /// `PolicySummary`.
+    pub fn summary(&self) -> PolicySummary {
+        let mut summary = PolicySummary::default();
+        for tool in self.tools.iter().filter(|t| t.enabled) {
+            summary.enabled.push(tool.name.clone());
+            if tool.actions.iter().any(|a| a.tier == Tier::Commit) {
+                summary.approval_required.push(tool.name.clone());
+            }
+        }
+        summary.enabled.sort();
+        summary.approval_required.sort();
+        summary
     }
 }
 
@@ -293,6 +1266,12 @@ impl Predicate {
                 _ => false,
             },
             Predicate::Matches(regex) => value.as_str().is_some_and(|s| regex.is_match(s)),
+            Predicate::NotMatches(regex) => value.as_str().is_some_and(|s| !regex.is_match(s)),
+            Predicate::MaxLen(max) => match value {
+                serde_json::Value::String(s) => s.len() <= *max,
+                serde_json::Value::Array(arr) => arr.len() <= *max,
+                _ => false,
+            },
         }
     }
 }
@@ -302,8 +1281,8 @@ impl CompiledTool {
         self.enabled
     }
 
-    pub(super) fn match_source(&self) -> MatchSource {
-        self.match_source
+    pub(super) fn match_source(&self) -> &MatchSource {
+        &self.match_source
     }
 
     /// Check tool-level constraints against params.
@@ -312,10 +1291,113 @@ impl CompiledTool {
         self.constraints.iter().all(|c| c.evaluate(params))
     }
 
+    /// First configured suggestion whose pattern matches any of `actions`.
+    pub(super) fn suggest(&self, actions: &[String]) -> Option<&str> {
+        self.suggestions
+            .iter()
+            .find(|s| actions.iter().any(|a| s.pattern.is_match(a)))
+            .map(|s| s.message.as_str())
+    }
+
+    /// Check the invocation's destination host. `None` if the tool has no
+    /// `destinations` section configured.
+    pub(super) fn check_destination(
+        &self,
+        params: &serde_json::Value,
+    ) -> Option<DestinationVerdict> {
+        self.destinations.as_ref().map(|d| d.check(params))
+    }
+
+    /// The destinations a token for this tool must re-check its request and
+    /// any redirects against. `None` if the tool has no `destinations`.
+    pub(super) fn redirect_policy(&self) -> Option<CompiledDestinations> {
+        self.destinations.clone()
+    }
+
+    /// The shell a token for this tool runs its command with.
+    pub(super) fn shell(&self) -> Option<ShellConfig> {
+        self.shell
+    }
+
+    /// The retry policy a token for this tool carries.
+    pub(super) fn retry(&self) -> Option<RetryPolicy> {
+        self.retry.clone()
+    }
+
+    /// The databases a token for this tool may connect to.
+    pub(super) fn databases(&self) -> BTreeMap<String, Database> {
+        self.databases.clone()
+    }
+
+    /// Whether an action segment could run something in the tool's shell that
+    /// the POSIX parser does not split out (zsh `=(...)`, fish `(...)`,
+    /// PowerShell and cmd quoting). Such commands are unparseable for that shell.
+    pub(super) fn hides_substitution(&self, actions: &[String]) -> bool {
+        self.shell
+            .is_some_and(|s| actions.iter().any(|a| shell::unparseable_for(a, s.program)))
+    }
+
+    /// Classify a `git` segment by subcommand. `None` if the tool has no git
+    /// table or the segment is not git; see `CompiledGitRules::classify`.
+    pub(super) fn classify_git(&self, command: &str) -> Option<Option<Tier>> {
+        let shell = self.shell.unwrap_or_default().program;
+        self.git.as_ref()?.classify(command, shell)
+    }
+
+    /// Caveats for a token minted now. Deadlines are resolved to wall-clock time.
+    /// Syscalls denied to the tool's processes at `tier`: the tool's
+    /// `[tools.<name>.seccomp]` list if set, else the shipped profile.
+    pub(super) fn seccomp_denied(&self, tier: Tier) -> Vec<Syscall> {
+        let over = match tier {
+            Tier::Observe => &self.seccomp.observe,
+            Tier::Act => &self.seccomp.act,
+            Tier::Commit => return Vec::new(),
+        };
+        match over {
+            Some(list) => list.clone(),
+            None => seccomp::default_denied(tier).to_vec(),
+        }
+    }
+
+    pub(super) fn token_caveats(&self) -> Vec<Caveat> {
+        let c = &self.caveats;
+        let mut caveats = Vec::new();
+        if let Some(max) = c.max_output_bytes {
+            caveats.push(Caveat::MaxOutputBytes(max));
+        }
+        if let Some((field, prefix)) = &c.path_prefix {
+            caveats.push(Caveat::PathPrefix {
+                field: field.clone(),
+                prefix: prefix.clone(),
+            });
+        }
+        if let Some(deadline) = c.deadline {
+            let at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                + deadline;
+            caveats.push(Caveat::Deadline(
+                u64::try_from(at.as_millis()).unwrap_or(u64::MAX),
+            ));
+        }
+        caveats
+    }
+
     /// Find the first matching action for a command.
     /// Actions are stored in descending privilege order (Commit first),
     /// so the highest-privilege match always wins.
+    ///
+    /// Increments the hit counter of every pattern in the winning action that
+    /// matched. Patterns in lower-privilege actions are not counted — they did
+    /// not decide the outcome.
     pub(super) fn match_action(&self, command: &str) -> Option<&CompiledAction> {
+        let action = self.peek_action(command)?;
+        action.record_hits(command);
+        Some(action)
+    }
+
+    /// Like `match_action()`, without touching the hit counters.
+    pub(super) fn peek_action(&self, command: &str) -> Option<&CompiledAction> {
         self.actions.iter().find(|a| a.patterns.is_match(command))
     }
 
@@ -332,6 +1414,12 @@ impl CompiledAction {
     pub(super) fn check_constraints(&self, params: &serde_json::Value) -> bool {
         self.constraints.iter().all(|c| c.evaluate(params))
     }
+
+    fn record_hits(&self, command: &str) {
+        for idx in self.patterns.matches(command).iter() {
+            self.hits[idx].fetch_add(1, Ordering::Relaxed);
+        }
+    }
 }
 
 /// Compile a single constraint config into a validated predicate.
@@ -408,25 +1496,22 @@ fn compile_constraint(
                 .collect::<Result<Vec<_>, _>>()?;
             Predicate::ContainsAll(strings)
         }
-        ConstraintOp::Matches => {
-            let pattern = config.value.as_str().ok_or_else(|| {
-                CherubError::PolicyValidation(format!(
-                    "{context}, constraint on '{}': 'matches' requires a string value",
-                    config.field
-                ))
-            })?;
-            let regex = regex::RegexBuilder::new(pattern)
-                .size_limit(1 << 20)
-                .nest_limit(50)
-                .unicode(false)
-                .build()
-                .map_err(|e| {
+        ConstraintOp::MaxLen => {
+            let n = config
+                .value
+                .as_u64()
+                .and_then(|n| usize::try_from(n).ok())
+                .ok_or_else(|| {
                     CherubError::PolicyValidation(format!(
-                        "{context}, constraint on '{}': invalid regex: {e}",
+                        "{context}, constraint on '{}': 'max_len' requires a non-negative integer value",
                         config.field
                     ))
                 })?;
-            Predicate::Matches(regex)
+            Predicate::MaxLen(n)
+        }
+        ConstraintOp::Matches => Predicate::Matches(constraint_regex(context, &config, "matches")?),
+        ConstraintOp::NotMatches => {
+            Predicate::NotMatches(constraint_regex(context, &config, "not_matches")?)
         }
     };
 
@@ -436,8 +1521,244 @@ fn compile_constraint(
     })
 }
 
+/// The regex `value` of a `matches`/`not_matches` constraint.
+fn constraint_regex(
+    context: &str,
+    config: &ConstraintConfig,
+    op: &str,
+) -> Result<Regex, CherubError> {
+    let pattern = config.value.as_str().ok_or_else(|| {
+        CherubError::PolicyValidation(format!(
+            "{context}, constraint on '{}': '{op}' requires a string value",
+            config.field
+        ))
+    })?;
+    regex::RegexBuilder::new(pattern)
+        .size_limit(1 << 20)
+        .nest_limit(50)
+        .unicode(false)
+        .build()
+        .map_err(|e| {
+            CherubError::PolicyValidation(format!(
+                "{context}, constraint on '{}': invalid regex: {e}",
+                config.field
+            ))
+        })
+}
+
+/// Compile the `[output]` section. Patterns use the same regex limits as action
+/// patterns; `max_bytes = 0` is rejected (it would withhold every result).
+fn compile_output_rules(config: OutputConfig) -> Result<CompiledOutputRules, CherubError> {
+    if config.max_bytes == Some(0) {
+        return Err(CherubError::PolicyValidation(
+            "output: max_bytes must be greater than 0".to_owned(),
+        ));
+    }
+
+    let block = if config.block.is_empty() {
+        None
+    } else {
+        Some(
+            regex::RegexSetBuilder::new(&config.block)
+                .size_limit(1 << 20)
+                .nest_limit(50)
+                .unicode(false)
+                .build()
+                .map_err(|e| CherubError::PolicyValidation(format!("output.block: {e}")))?,
+        )
+    };
+
+    let filters = config
+        .filters
+        .iter()
+        .map(|filter| match filter {
+            // Built-in pattern; its negated class needs Unicode mode to match
+            // only whole characters of a `&str`.
+            OutputFilterValue::StripAnsi => regex::RegexBuilder::new(ANSI_PATTERN)
+                .size_limit(1 << 20)
+                .build()
+                .map(OutputFilter::StripAnsi)
+                .map_err(|e| CherubError::PolicyValidation(format!("output.filters: {e}"))),
+            OutputFilterValue::CollapseBinary => Ok(OutputFilter::CollapseBinary),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let builtin = if config.redact_secrets {
+        KNOWN_FORMATS
+    } else {
+        &[]
+    };
+    let redact = builtin
+        .iter()
+        .copied()
+        .chain(config.redact.iter().map(String::as_str))
+        .map(|pattern| {
+            regex::RegexBuilder::new(pattern)
+                .size_limit(1 << 20)
+                .nest_limit(50)
+                .unicode(false)
+                .build()
+                .map_err(|e| CherubError::PolicyValidation(format!("output.redact: {e}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(CompiledOutputRules {
+        filters,
+        max_bytes: config.max_bytes,
+        block,
+        redact,
+    })
+}
+
+/// Check `[tools.<name>.retry]`: a bounded attempt count, a sane backoff, and
+/// at least one condition to retry on.
+fn validate_retry(tool_context: &str, retry: &RetryPolicy) -> Result<(), CherubError> {
+    if !(2..=10).contains(&retry.max_attempts) {
+        return Err(CherubError::PolicyValidation(format!(
+            "{tool_context}: retry.max_attempts must be between 2 and 10"
+        )));
+    }
+    if retry.backoff_ms > retry.max_backoff_ms {
+        return Err(CherubError::PolicyValidation(format!(
+            "{tool_context}: retry.backoff_ms must not exceed retry.max_backoff_ms"
+        )));
+    }
+    if retry.exit_codes.is_empty() && !retry.timeout && !retry.errors {
+        return Err(CherubError::PolicyValidation(format!(
+            "{tool_context}: retry needs exit_codes, timeout, or errors"
+        )));
+    }
+    Ok(())
+}
+
+/// Compile the `[secrets]` section. Extra `patterns` are appended to the
+/// built-in credential formats and share the standard regex limits.
+fn compile_secret_rules(config: SecretsConfig) -> Result<CompiledSecretRules, CherubError> {
+    if !config.min_entropy.is_finite() || config.min_entropy <= 0.0 {
+        return Err(CherubError::PolicyValidation(
+            "secrets: min_entropy must be a positive number".to_owned(),
+        ));
+    }
+    if config.min_length < 8 {
+        return Err(CherubError::PolicyValidation(
+            "secrets: min_length must be at least 8".to_owned(),
+        ));
+    }
+
+    let formats = regex::RegexSetBuilder::new(
+        KNOWN_FORMATS
+            .iter()
+            .copied()
+            .chain(config.patterns.iter().map(String::as_str)),
+    )
+    .size_limit(1 << 20)
+    .nest_limit(50)
+    .unicode(false)
+    .build()
+    .map_err(|e| CherubError::PolicyValidation(format!("secrets.patterns: {e}")))?;
+
+    let action = |v: SecretActionValue| match v {
+        SecretActionValue::Allow => SecretAction::Allow,
+        SecretActionValue::Escalate => SecretAction::Escalate,
+        SecretActionValue::Reject => SecretAction::Reject,
+    };
+
+    Ok(CompiledSecretRules {
+        formats,
+        min_entropy: config.min_entropy,
+        min_length: config.min_length,
+        on_observe: action(config.on_observe),
+        on_act: action(config.on_act),
+        on_commit: action(config.on_commit),
+    })
+}
+
+fn compile_host_set(context: &str, patterns: &[String]) -> Result<RegexSet, CherubError> {
+    regex::RegexSetBuilder::new(patterns)
+        .size_limit(1 << 20)
+        .nest_limit(50)
+        .unicode(false)
+        .build()
+        .map_err(|e| CherubError::PolicyValidation(format!("{context}: {e}")))
+}
+
+fn compile_destinations(
+    name: &str,
+    config: DestinationsConfig,
+) -> Result<CompiledDestinations, CherubError> {
+    let context = format!("tool '{name}', destinations");
+    if config.allow.is_empty() && config.escalate.is_empty() {
+        return Err(CherubError::PolicyValidation(format!(
+            "{context}: at least one allow or escalate pattern is required"
+        )));
+    }
+    Ok(CompiledDestinations {
+        allow: compile_host_set(&format!("{context}.allow"), &config.allow)?,
+        escalate: compile_host_set(&format!("{context}.escalate"), &config.escalate)?,
+        deny: compile_host_set(&format!("{context}.deny"), &config.deny)?,
+        url_field: config.url_field,
+        allow_ip_literals: config.allow_ip_literals,
+        max_redirects: config.max_redirects,
+    })
+}
+
+/// Every tool name and alias must resolve to exactly one tool.
+fn check_tool_names_unique(tools: &[CompiledTool]) -> Result<(), CherubError> {
+    let mut seen = std::collections::HashSet::new();
+    for tool in tools {
+        for name in std::iter::once(&tool.name).chain(&tool.aliases) {
+            if !seen.insert(name.as_str()) {
+                return Err(CherubError::PolicyValidation(format!(
+                    "tool '{}': name or alias '{name}' is already in use",
+                    tool.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn compile_tool(name: String, config: ToolConfig) -> Result<CompiledTool, CherubError> {
     let tool_context = format!("tool '{name}'");
+    let match_source = compile_match_source(
+        &name,
+        config.match_source,
+        config.match_field,
+        config.command_param,
+    )?;
+    let destinations = config
+        .destinations
+        .map(|d| compile_destinations(&name, d))
+        .transpose()?;
+    let git = config
+        .git
+        .map(|g| {
+            if !matches!(
+                match_source,
+                MatchSource::Command | MatchSource::CommandField { .. }
+            ) {
+                return Err(CherubError::PolicyValidation(format!(
+                    "{tool_context}: git is only valid with match_source \"command\""
+                )));
+            }
+            CompiledGitRules::new(&g.observe, &g.act, &g.commit)
+                .map_err(|e| CherubError::PolicyValidation(format!("{tool_context}: {e}")))
+        })
+        .transpose()?;
+    if config.shell.is_some() && !match_source.is_command() {
+        return Err(CherubError::PolicyValidation(format!(
+            "{tool_context}: shell is only valid with match_source \"command\""
+        )));
+    }
+    if let Some(ref retry) = config.retry {
+        validate_retry(&tool_context, retry)?;
+    }
+    let databases = compile_databases(&tool_context, &match_source, config.databases)?;
+    let caveats = config
+        .caveats
+        .map(|c| compile_caveats(&tool_context, c))
+        .transpose()?
+        .unwrap_or_default();
 
     // Compile tool-level constraints.
     let tool_constraints = config
@@ -477,10 +1798,13 @@ fn compile_tool(name: String, config: ToolConfig) -> Result<CompiledTool, Cherub
                 Some(OnConstraintFailureValue::Escalate) => OnConstraintFailure::Escalate,
             };
 
+            let hits = (0..patterns.len()).map(|_| AtomicU64::new(0)).collect();
+
             Ok(CompiledAction {
                 name: action_name,
                 tier,
                 patterns,
+                hits,
                 constraints,
                 on_constraint_failure,
             })
@@ -488,14 +1812,153 @@ fn compile_tool(name: String, config: ToolConfig) -> Result<CompiledTool, Cherub
         .collect::<Result<Vec<_>, _>>()?;
 
     // Sort: highest privilege first (Commit > Act > Observe) so first match wins.
-    actions.sort_by(|a, b| b.tier.cmp(&a.tier));
+    actions.sort_by_key(|a| std::cmp::Reverse(a.tier));
+
+    let suggestions = config
+        .suggestions
+        .into_iter()
+        .map(|s| {
+            if s.message.trim().is_empty() {
+                return Err(CherubError::PolicyValidation(format!(
+                    "{tool_context}, suggestion '{}': message must not be empty",
+                    s.pattern
+                )));
+            }
+            let pattern = regex::RegexBuilder::new(&s.pattern)
+                .size_limit(1 << 20)
+                .nest_limit(50)
+                .unicode(false)
+                .build()
+                .map_err(|e| {
+                    CherubError::PolicyValidation(format!("{tool_context}, suggestion: {e}"))
+                })?;
+            Ok(CompiledSuggestion {
+                pattern,
+                message: s.message,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(alias) = config.aliases.iter().find(|a| a.is_empty()) {
+        return Err(CherubError::PolicyValidation(format!(
+            "{tool_context}: invalid alias '{alias}'"
+        )));
+    }
 
     Ok(CompiledTool {
         name,
+        aliases: config.aliases,
         enabled: config.enabled,
-        match_source: config.match_source.into(),
+        match_source,
         actions,
         constraints: tool_constraints,
+        destinations,
+        suggestions,
+        git,
+        caveats,
+        seccomp: config
+            .seccomp
+            .map(|c| CompiledSeccomp {
+                observe: c.observe,
+                act: c.act,
+            })
+            .unwrap_or_default(),
+        shell: config.shell,
+        retry: config.retry,
+        databases,
+    })
+}
+
+/// `match_source = "sql"` needs at least one database, and databases are only
+/// valid with it.
+fn compile_databases(
+    tool_context: &str,
+    match_source: &MatchSource,
+    configs: HashMap<String, DatabaseConfig>,
+) -> Result<BTreeMap<String, Database>, CherubError> {
+    let invalid = |msg: String| CherubError::PolicyValidation(format!("{tool_context}: {msg}"));
+    match (match_source, configs.is_empty()) {
+        (MatchSource::Sql, true) => {
+            return Err(invalid(
+                "match_source \"sql\" requires at least one database".to_owned(),
+            ));
+        }
+        (MatchSource::Sql, false) | (_, true) => {}
+        (_, false) => {
+            return Err(invalid(
+                "databases are only valid with match_source \"sql\"".to_owned(),
+            ));
+        }
+    }
+    configs
+        .into_iter()
+        .map(|(name, config)| {
+            if !is_database_name(&name) {
+                return Err(invalid(format!(
+                    "invalid database name '{name}' (letters, digits, '_' and '-' only)"
+                )));
+            }
+            let database = match config {
+                DatabaseConfig {
+                    sqlite: Some(path),
+                    postgres: None,
+                    password_env: None,
+                } => Database::Sqlite { path },
+                DatabaseConfig {
+                    sqlite: None,
+                    postgres: Some(config),
+                    password_env,
+                } => Database::Postgres {
+                    config,
+                    password_env,
+                },
+                DatabaseConfig {
+                    sqlite: Some(_),
+                    password_env: Some(_),
+                    ..
+                } => {
+                    return Err(invalid(format!(
+                        "database '{name}': password_env is only valid with postgres"
+                    )));
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "database '{name}' must set exactly one of sqlite or postgres"
+                    )));
+                }
+            };
+            Ok((name, database))
+        })
+        .collect()
+}
+
+fn compile_caveats(
+    tool_context: &str,
+    config: CaveatsConfig,
+) -> Result<CompiledCaveats, CherubError> {
+    let invalid = |msg: &str| CherubError::PolicyValidation(format!("{tool_context}: {msg}"));
+    if config.max_output_bytes == Some(0) {
+        return Err(invalid("caveats.max_output_bytes must be greater than 0"));
+    }
+    if config.deadline_secs == Some(0) {
+        return Err(invalid("caveats.deadline_secs must be greater than 0"));
+    }
+    if let Some(prefix) = &config.path_prefix {
+        let path = Path::new(prefix);
+        if !path.is_absolute()
+            || path
+                .components()
+                .any(|c| c == std::path::Component::ParentDir)
+        {
+            return Err(invalid(
+                "caveats.path_prefix must be an absolute path without '..'",
+            ));
+        }
+    }
+    Ok(CompiledCaveats {
+        max_output_bytes: config.max_output_bytes,
+        path_prefix: config.path_prefix.map(|p| (config.path_field, p)),
+        deadline: config.deadline_secs.map(Duration::from_secs),
     })
 }
 
@@ -515,115 +1978,838 @@ patterns = [
     "^wc ", "^file ", "^which ", "^echo ", "^pwd$", "^env$", "^whoami$",
 ]
 
-[tools.bash.actions.write]
-tier = "act"
-patterns = ["^mkdir ", "^cp ", "^mv ", "^touch ", "^tee ", "^git "]
+[tools.bash.actions.write]
+tier = "act"
+patterns = ["^mkdir ", "^cp ", "^mv ", "^touch ", "^tee ", "^git "]
+
+[tools.bash.actions.destructive]
+tier = "commit"
+patterns = [
+    "^rm ", "^chmod ", "^chown ", "^kill ", "^pkill ",
+    "^sudo ", "^apt ", "^pip install", "^cargo install",
+]
+"#;
+
+    #[test]
+    fn retry_validated() {
+        let retry = |body: &str| {
+            Policy::from_str(&format!(
+                "[tools.bash]\nenabled = true\n\n[tools.bash.retry]\n{body}\n"
+            ))
+        };
+        assert!(retry("max_attempts = 3\nexit_codes = [75]").is_ok());
+        for body in [
+            "max_attempts = 1\ntimeout = true",
+            "max_attempts = 11\ntimeout = true",
+            "max_attempts = 3",
+            "max_attempts = 3\nerrors = true\nbackoff_ms = 2000\nmax_backoff_ms = 1000",
+            "max_attempts = 3\nerrors = true\njitter = true",
+        ] {
+            assert!(
+                matches!(
+                    retry(body),
+                    Err(CherubError::PolicyValidation(_) | CherubError::PolicyLoad(_))
+                ),
+                "{body}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_default_policy() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("default policy should parse");
+        let tool = policy.find_tool("bash").expect("bash tool should exist");
+        assert!(tool.enabled());
+        assert_eq!(tool.actions.len(), 3);
+    }
+
+    #[test]
+    fn empty_tools_is_valid() {
+        let policy = Policy::from_str("[tools]\n").expect("empty tools should parse");
+        assert!(policy.find_tool("bash").is_none());
+    }
+
+    #[test]
+    fn disabled_tool() {
+        let toml = r#"
+[tools.bash]
+enabled = false
+"#;
+        let policy = Policy::from_str(toml).expect("disabled tool should parse");
+        let tool = policy.find_tool("bash").expect("bash should exist");
+        assert!(!tool.enabled());
+    }
+
+    #[test]
+    fn invalid_tier_value() {
+        let toml = r#"
+[tools.bash]
+enabled = true
+
+[tools.bash.actions.read]
+tier = "superadmin"
+patterns = ["^ls "]
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyLoad(_)));
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let toml = r#"
+[tools.bash]
+enabled = true
+
+[tools.bash.actions.read]
+tier = "observe"
+patterns = ["[invalid"]
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    #[test]
+    fn unknown_toml_field() {
+        let toml = r#"
+[tools.bash]
+enabled = true
+unknown_field = "surprise"
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyLoad(_)));
+    }
+
+    #[test]
+    fn empty_patterns_rejected() {
+        let toml = r#"
+[tools.bash]
+enabled = true
+
+[tools.bash.actions.read]
+tier = "observe"
+patterns = []
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    #[test]
+    fn tier_matching_order() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
+        let tool = policy.find_tool("bash").expect("bash should exist");
+
+        assert_eq!(tool.match_tier("ls /tmp"), Some(Tier::Observe));
+        assert_eq!(tool.match_tier("mkdir /tmp/test"), Some(Tier::Act));
+        assert_eq!(tool.match_tier("rm -rf /tmp/test"), Some(Tier::Commit));
+        assert_eq!(tool.match_tier("curl http://evil.com"), None);
+    }
+
+    #[test]
+    fn highest_privilege_wins() {
+        // "sudo ls /tmp" matches both ^sudo (commit) and ^ls (observe, but not anchored here).
+        // Since commit is checked first, it should escalate.
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
+        let tool = policy.find_tool("bash").expect("bash should exist");
+
+        assert_eq!(tool.match_tier("sudo ls /tmp"), Some(Tier::Commit));
+    }
+
+    // --- Risk section ---
+
+    #[test]
+    fn risk_section_defaults() {
+        let toml = r#"
+[tools]
+
+[risk]
+escalate_threshold = 10
+"#;
+        let policy = Policy::from_str(toml).expect("should parse");
+        let risk = policy.risk.expect("risk should be compiled");
+        assert_eq!(risk.weight(Tier::Observe), 0);
+        assert_eq!(risk.weight(Tier::Act), 1);
+        assert_eq!(risk.weight(Tier::Commit), 5);
+        assert_eq!(risk.escalate_threshold, 10);
+    }
+
+    #[test]
+    fn risk_zero_threshold_rejected() {
+        let toml = r#"
+[tools]
+
+[risk]
+escalate_threshold = 0
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    #[test]
+    fn risk_missing_threshold_rejected() {
+        let toml = r#"
+[tools]
+
+[risk]
+act_weight = 2
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyLoad(_)));
+    }
+
+    // --- Heuristics section ---
+
+    #[test]
+    fn heuristics_section_defaults_enable_everything() {
+        let policy = Policy::from_str("[tools]\n\n[heuristics]\n").expect("should parse");
+        let h = policy.heuristics.expect("heuristics should be configured");
+        assert_eq!(h.max_length, Some(4096));
+        assert_eq!(h.signals.len(), 4);
+    }
+
+    #[test]
+    fn policy_hash_tracks_source() {
+        let a = Policy::from_str("[tools]\n").unwrap();
+        let b = Policy::from_str("[tools]\n\n").unwrap();
+        assert_eq!(a.hash.len(), 64);
+        assert_eq!(a.hash, Policy::from_str("[tools]\n").unwrap().hash);
+        assert_ne!(a.hash, b.hash);
+    }
+
+    #[test]
+    fn token_ttl_parsed_and_validated() {
+        let policy = Policy::from_str("token_ttl_secs = 30\n\n[tools]\n").expect("should parse");
+        assert_eq!(policy.token_ttl, Some(Duration::from_secs(30)));
+        assert_eq!(Policy::from_str("[tools]\n").unwrap().token_ttl, None);
+        let err = Policy::from_str("token_ttl_secs = 0\n").unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    #[test]
+    fn caveats_parsed_and_validated() {
+        let policy = Policy::from_str(
+            r#"
+[tools.file]
+enabled = true
+match_source = "field"
+match_field = "action"
+
+[tools.file.caveats]
+max_output_bytes = 4096
+path_prefix = "/srv/data"
+deadline_secs = 60
+
+[tools.file.actions.read]
+tier = "observe"
+patterns = ["^read$"]
+"#,
+        )
+        .expect("should parse");
+        let caveats = policy.find_tool("file").unwrap().token_caveats();
+        assert_eq!(caveats[0], Caveat::MaxOutputBytes(4096));
+        assert_eq!(
+            caveats[1],
+            Caveat::PathPrefix {
+                field: "path".to_owned(),
+                prefix: "/srv/data".to_owned()
+            }
+        );
+        assert!(matches!(caveats[2], Caveat::Deadline(_)));
+
+        for bad in [
+            "max_output_bytes = 0",
+            "deadline_secs = 0",
+            "path_prefix = \"relative\"",
+            "path_prefix = \"/srv/../etc\"",
+            "unknown = 1",
+        ] {
+            let toml = format!("[tools.bash]\nenabled = true\n\n[tools.bash.caveats]\n{bad}\n");
+            assert!(Policy::from_str(&toml).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn execution_limits_per_tier() {
+        let policy = Policy::from_str(
+            "[tools]\n\n[execution.observe]\ntimeout_secs = 30\nmax_output_bytes = 1024\n\n[execution.act]\ntimeout_secs = 300\ntmp_quota_bytes = 1048576\n",
+        )
+        .expect("should parse");
+        let timeout = |tier| policy.execution.limits(tier).timeout;
+        assert_eq!(timeout(Tier::Observe), Some(Duration::from_secs(30)));
+        assert_eq!(timeout(Tier::Act), Some(Duration::from_secs(300)));
+        assert_eq!(timeout(Tier::Commit), None);
+        assert_eq!(
+            policy.execution.limits(Tier::Observe).max_output_bytes,
+            Some(1024)
+        );
+        assert_eq!(policy.execution.limits(Tier::Act).max_output_bytes, None);
+        assert_eq!(
+            policy.execution.limits(Tier::Act).tmp_quota_bytes,
+            Some(1_048_576)
+        );
+        assert_eq!(policy.execution.limits(Tier::Observe).tmp_quota_bytes, None);
+
+        let err = Policy::from_str("[execution.commit]\ntimeout_secs = 0\n").unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+        let err = Policy::from_str("[execution.act]\nmax_output_bytes = 0\n").unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+        let err = Policy::from_str("[execution.act]\ntmp_quota_bytes = 0\n").unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+        let policy = Policy::from_str("[execution.observe]\nsandbox = \"bubblewrap\"\n").unwrap();
+        assert_eq!(
+            policy.execution.limits(Tier::Observe).sandbox,
+            Some(SandboxBackend::Bubblewrap)
+        );
+        assert_eq!(policy.execution.limits(Tier::Act).sandbox, None);
+        let err = Policy::from_str("[execution.commit]\nsandbox = \"bubblewrap\"\n").unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+
+        let policy = Policy::from_str("[execution]\nenv = [\"RUST_LOG\"]\n").unwrap();
+        assert_eq!(policy.execution.env, ["RUST_LOG"]);
+        let err = Policy::from_str("[execution]\nenv = [\"A=B\"]\n").unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    #[test]
+    fn container_sandbox_configured() {
+        let policy = Policy::from_str(
+            "[execution.container]\nimage = \"debian:stable-slim\"\nruntime = \"podman\"\nmounts = [{ host = \"/opt/cache\", container = \"/cache\" }]\n\n[execution.act]\nsandbox = \"container\"\n",
+        )
+        .expect("should parse");
+        let Some(SandboxBackend::Container(container)) = policy.execution.limits(Tier::Act).sandbox
+        else {
+            panic!("expected a container sandbox");
+        };
+        assert_eq!(container.runtime, ContainerRuntime::Podman);
+        assert!(!container.network);
+        assert!(container.mounts[0].read_only);
+        assert_eq!(policy.execution.limits(Tier::Observe).sandbox, None);
+
+        for bad in [
+            "[execution.act]\nsandbox = \"container\"\n",
+            "[execution.container]\nimage = \"\"\n",
+            "[execution.container]\nimage = \"x\"\nmounts = [{ host = \"cache\", container = \"/cache\" }]\n",
+            "[execution.observe]\nsandbox = \"bubblewrap\"\nseccomp = true\n",
+        ] {
+            assert!(Policy::from_str(bad).is_err(), "should reject: {bad}");
+        }
+    }
+
+    #[test]
+    fn seccomp_profiles_enabled_per_tier_and_overridable_per_tool() {
+        let policy = Policy::from_str(
+            "[tools.bash]\nenabled = true\n\n[tools.bash.seccomp]\nact = [\"unlinkat\"]\n\n[tools.file]\nenabled = true\n\n[execution.observe]\nseccomp = true\n",
+        )
+        .expect("should parse");
+        assert!(policy.execution.seccomp(Tier::Observe));
+        assert!(!policy.execution.seccomp(Tier::Act));
+        let bash = policy.find_tool("bash").unwrap();
+        assert_eq!(bash.seccomp_denied(Tier::Act), [Syscall::Unlinkat]);
+        assert_eq!(
+            bash.seccomp_denied(Tier::Observe),
+            seccomp::default_denied(Tier::Observe)
+        );
+        assert!(bash.seccomp_denied(Tier::Commit).is_empty());
+
+        for bad in [
+            "[execution.commit]\nseccomp = true\n",
+            "[tools.bash]\nenabled = true\n\n[tools.bash.seccomp]\nact = [\"execve2\"]\n",
+            "[tools.bash]\nenabled = true\n\n[tools.bash.seccomp]\ncommit = []\n",
+        ] {
+            assert!(Policy::from_str(bad).is_err(), "should reject: {bad}");
+        }
+    }
+
+    #[test]
+    fn workspace_section_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml = format!(
+            "[tools]\n\n[workspace]\nroot = \"{}\"\n",
+            dir.path().display()
+        );
+        let policy = Policy::from_str(&toml).expect("should parse");
+        let workspace = policy.workspace.expect("workspace should be configured");
+        assert_eq!(workspace.root, dir.path().canonicalize().unwrap());
+
+        for bad in [
+            "[workspace]\nroot = \"relative/dir\"\n".to_owned(),
+            "[workspace]\nroot = \"/nonexistent/cherub/workspace\"\n".to_owned(),
+            format!("{toml}allow_paths = [\"tmp\"]\n"),
+            format!("{toml}on_escape = \"ignore\"\n"),
+            format!("{toml}unknown = 1\n"),
+        ] {
+            assert!(Policy::from_str(&bad).is_err(), "should reject: {bad}");
+        }
+    }
+
+    #[test]
+    fn heuristics_zero_max_length_rejected() {
+        let err = Policy::from_str("[tools]\n\n[heuristics]\nmax_length = 0\n").unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    // --- Circuit breaker section ---
+
+    #[test]
+    fn circuit_breaker_section_parses() {
+        let toml = "[tools]\n\n[circuit_breaker]\nfailure_threshold = 3\nwindow_secs = 60\non_open = \"reject\"\n";
+        let policy = Policy::from_str(toml).expect("should parse");
+        let breaker = policy.breaker.expect("breaker should be configured");
+        assert_eq!(breaker.failure_threshold, 3);
+        assert_eq!(breaker.window, Duration::from_secs(60));
+        assert_eq!(breaker.on_open, OnConstraintFailure::Reject);
+    }
+
+    #[test]
+    fn circuit_breaker_zero_values_rejected() {
+        for toml in [
+            "[tools]\n[circuit_breaker]\nfailure_threshold = 0\nwindow_secs = 60\n",
+            "[tools]\n[circuit_breaker]\nfailure_threshold = 3\nwindow_secs = 0\n",
+        ] {
+            assert!(Policy::from_str(toml).is_err(), "should reject: {toml}");
+        }
+    }
+
+    // --- check() ---
+
+    use crate::enforcement::RejectReason;
+
+    #[test]
+    fn check_returns_decision_class() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
+        assert_eq!(
+            policy.check("bash", &json!({"command": "ls /tmp"})),
+            DecisionKind::Allow {
+                tier: Tier::Observe
+            }
+        );
+        assert_eq!(
+            policy.check("bash", &json!({"command": "rm -rf /tmp/x"})),
+            DecisionKind::Escalate { tier: Tier::Commit }
+        );
+        assert_eq!(
+            policy.check("bash", &json!({"command": "curl x"})),
+            DecisionKind::Reject {
+                reason: RejectReason::NoMatch
+            }
+        );
+        assert_eq!(
+            policy.check("nope", &json!({})),
+            DecisionKind::Reject {
+                reason: RejectReason::UnknownTool
+            }
+        );
+    }
+
+    #[test]
+    fn check_does_not_record_hits() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
+        policy.check("bash", &json!({"command": "ls /tmp"}));
+        assert!(policy.stats().iter().all(|s| s.hits == 0));
+    }
+
+    // --- Escalation section ---
+
+    #[test]
+    fn escalation_section_parses() {
+        let toml = "[tools]\n\n[escalation]\ntimeout_secs = 30\non_timeout = \"observe\"\n";
+        let policy = Policy::from_str(toml).expect("should parse");
+        let esc = policy.escalation.expect("escalation should be configured");
+        assert_eq!(esc.timeout, Duration::from_secs(30));
+        assert_eq!(esc.on_timeout, TimeoutFallback::Observe);
+    }
+
+    #[test]
+    fn escalation_defaults_to_deny() {
+        let policy =
+            Policy::from_str("[tools]\n\n[escalation]\ntimeout_secs = 5\n").expect("should parse");
+        let esc = policy.escalation.expect("escalation should be configured");
+        assert_eq!(esc.on_timeout, TimeoutFallback::Deny);
+    }
+
+    #[test]
+    fn escalation_zero_timeout_rejected() {
+        let err = Policy::from_str("[tools]\n\n[escalation]\ntimeout_secs = 0\n").unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    #[test]
+    fn timeout_fallback_observe_only_approves_observe() {
+        assert!(TimeoutFallback::Observe.approves(Tier::Observe));
+        assert!(!TimeoutFallback::Observe.approves(Tier::Act));
+        assert!(!TimeoutFallback::Observe.approves(Tier::Commit));
+        assert!(!TimeoutFallback::Deny.approves(Tier::Observe));
+    }
+
+    // --- Suggestions ---
+
+    #[test]
+    fn suggestion_first_match_wins() {
+        let toml = r#"
+[tools.bash]
+enabled = true
+
+[[tools.bash.suggestions]]
+pattern = "^rm -rf"
+message = "use `trash` or request escalation"
+
+[[tools.bash.suggestions]]
+pattern = "^rm "
+message = "remove files one at a time"
+"#;
+        let policy = Policy::from_str(toml).expect("should parse");
+        let tool = policy.find_tool("bash").expect("bash should exist");
+        assert_eq!(
+            tool.suggest(&["ls".to_owned(), "rm -rf /tmp/x".to_owned()]),
+            Some("use `trash` or request escalation")
+        );
+        assert_eq!(
+            tool.suggest(&["rm a.txt".to_owned()]),
+            Some("remove files one at a time")
+        );
+        assert_eq!(tool.suggest(&["ls".to_owned()]), None);
+    }
+
+    #[test]
+    fn suggestion_empty_message_rejected() {
+        let toml = r#"
+[tools.bash]
+enabled = true
+
+[[tools.bash.suggestions]]
+pattern = "^rm "
+message = "  "
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    // --- Secrets section ---
+
+    #[test]
+    fn secrets_section_defaults() {
+        let policy = Policy::from_str("[tools]\n\n[secrets]\n").expect("should parse");
+        let secrets = policy.secrets.expect("secrets should be configured");
+        assert_eq!(secrets.action_for(Tier::Observe), SecretAction::Escalate);
+        assert_eq!(secrets.action_for(Tier::Act), SecretAction::Escalate);
+        assert_eq!(secrets.action_for(Tier::Commit), SecretAction::Reject);
+    }
+
+    #[test]
+    fn secrets_section_absent_is_none() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
+        assert!(policy.secrets.is_none());
+    }
+
+    #[test]
+    fn secrets_invalid_settings_rejected() {
+        for toml in [
+            "[tools]\n[secrets]\nmin_entropy = 0.0\n",
+            "[tools]\n[secrets]\nmin_length = 4\n",
+            "[tools]\n[secrets]\npatterns = [\"(\"]\n",
+            "[tools]\n[secrets]\non_act = \"ignore\"\n",
+        ] {
+            assert!(Policy::from_str(toml).is_err(), "should reject: {toml}");
+        }
+    }
+
+    // --- Destinations ---
+
+    const DESTINATIONS_POLICY: &str = r#"
+[tools.http]
+enabled = true
+match_source = "http_structured"
+
+[tools.http.destinations]
+allow = ["^api\\.stripe\\.com$", "^([a-z0-9-]+\\.)*example\\.com$"]
+escalate = ["^uploads\\.example\\.com$"]
+deny = ["^internal\\.example\\.com$"]
+
+[tools.http.actions.read]
+tier = "observe"
+patterns = ["^get:"]
+"#;
+
+    fn destination(policy: &Policy, url: &str) -> Option<DestinationVerdict> {
+        policy
+            .find_tool("http")
+            .expect("http should exist")
+            .check_destination(&json!({"action": "get", "url": url}))
+    }
+
+    #[test]
+    fn destinations_allow_escalate_deny() {
+        let policy = Policy::from_str(DESTINATIONS_POLICY).expect("should parse");
+        assert_eq!(
+            destination(&policy, "https://api.stripe.com/v1/charges"),
+            Some(DestinationVerdict::Allow)
+        );
+        assert_eq!(
+            destination(&policy, "https://uploads.example.com/f"),
+            Some(DestinationVerdict::Escalate)
+        );
+        assert_eq!(
+            destination(&policy, "https://internal.example.com/"),
+            Some(DestinationVerdict::Deny)
+        );
+        assert_eq!(
+            destination(&policy, "https://unknown.org/"),
+            Some(DestinationVerdict::Deny)
+        );
+    }
+
+    #[test]
+    fn destinations_userinfo_confusion_denied() {
+        let policy = Policy::from_str(DESTINATIONS_POLICY).expect("should parse");
+        assert_eq!(
+            destination(&policy, "https://api.stripe.com@evil.org/"),
+            Some(DestinationVerdict::Deny)
+        );
+    }
+
+    #[test]
+    fn destinations_ip_literals_denied_by_default() {
+        let policy = Policy::from_str(DESTINATIONS_POLICY).expect("should parse");
+        assert_eq!(
+            destination(&policy, "http://169.254.169.254/latest/meta-data"),
+            Some(DestinationVerdict::Deny)
+        );
+        assert_eq!(
+            destination(&policy, "http://[::1]/"),
+            Some(DestinationVerdict::Deny)
+        );
+    }
+
+    #[test]
+    fn destinations_ip_literals_opt_in() {
+        let toml = r#"
+[tools.http]
+enabled = true
+match_source = "http_structured"
 
-[tools.bash.actions.destructive]
-tier = "commit"
-patterns = [
-    "^rm ", "^chmod ", "^chown ", "^kill ", "^pkill ",
-    "^sudo ", "^apt ", "^pip install", "^cargo install",
-]
+[tools.http.destinations]
+allow = ["^10\\.0\\.0\\.5$"]
+allow_ip_literals = true
 "#;
+        let policy = Policy::from_str(toml).expect("should parse");
+        assert_eq!(
+            destination(&policy, "http://10.0.0.5:8080/health"),
+            Some(DestinationVerdict::Allow)
+        );
+    }
 
     #[test]
-    fn parse_default_policy() {
-        let policy = Policy::from_str(DEFAULT_POLICY).expect("default policy should parse");
-        let tool = policy.find_tool("bash").expect("bash tool should exist");
-        assert!(tool.enabled());
-        assert_eq!(tool.actions.len(), 3);
+    fn redirect_policy_carries_destinations() {
+        let policy = Policy::from_str(DESTINATIONS_POLICY).expect("should parse");
+        let tool = policy.find_tool("http").expect("http should exist");
+        let destinations = tool.redirect_policy().expect("destinations configured");
+        assert_eq!(destinations.max_redirects, 0, "redirects not followed");
+        // The request itself may go to escalated hosts (after approval), never
+        // denied or unmatched ones.
+        assert!(destinations.permits_request("https://uploads.example.com/f"));
+        assert!(!destinations.permits_request("https://internal.example.com/"));
+        assert!(!destinations.permits_request("https://evil.com/@api.stripe.com/x"));
+
+        let toml = DESTINATIONS_POLICY.replace(
+            "deny = [\"^internal\\\\.example\\\\.com$\"]",
+            "deny = [\"^internal\\\\.example\\\\.com$\"]\nmax_redirects = 3",
+        );
+        let policy = Policy::from_str(&toml).expect("should parse");
+        let tool = policy.find_tool("http").expect("http should exist");
+        let redirects = tool.redirect_policy().expect("redirects enabled");
+        assert_eq!(redirects.max_redirects, 3);
+        assert!(redirects.allows_redirect("https://www.example.com/next"));
+        assert!(!redirects.allows_redirect("https://uploads.example.com/f"));
+        assert!(!redirects.allows_redirect("https://internal.example.com/"));
+
+        // Round-trips through its source config, as in a sealed token.
+        let json = serde_json::to_string(&redirects).unwrap();
+        let back: CompiledDestinations = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.max_redirects, 3);
+        assert!(back.allows_redirect("https://api.stripe.com/v1"));
+        assert!(!back.allows_redirect("https://unknown.org/"));
     }
 
     #[test]
-    fn empty_tools_is_valid() {
-        let policy = Policy::from_str("[tools]\n").expect("empty tools should parse");
-        assert!(policy.find_tool("bash").is_none());
+    fn destinations_missing_url_denied() {
+        let policy = Policy::from_str(DESTINATIONS_POLICY).expect("should parse");
+        let tool = policy.find_tool("http").expect("http should exist");
+        assert_eq!(
+            tool.check_destination(&json!({"action": "get"})),
+            Some(DestinationVerdict::Deny)
+        );
     }
 
     #[test]
-    fn disabled_tool() {
-        let toml = r#"
-[tools.bash]
-enabled = false
-"#;
-        let policy = Policy::from_str(toml).expect("disabled tool should parse");
+    fn destinations_absent_is_none() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
         let tool = policy.find_tool("bash").expect("bash should exist");
-        assert!(!tool.enabled());
+        assert_eq!(
+            tool.check_destination(&json!({"url": "https://x.com"})),
+            None
+        );
     }
 
     #[test]
-    fn invalid_tier_value() {
+    fn destinations_require_allow_or_escalate() {
         let toml = r#"
-[tools.bash]
+[tools.http]
 enabled = true
 
-[tools.bash.actions.read]
-tier = "superadmin"
-patterns = ["^ls "]
+[tools.http.destinations]
+deny = ["^evil\\.com$"]
 "#;
         let err = Policy::from_str(toml).unwrap_err();
-        assert!(matches!(err, CherubError::PolicyLoad(_)));
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
     }
 
+    // --- Output section ---
+
     #[test]
-    fn invalid_regex() {
+    fn output_invalid_regex_rejected() {
         let toml = r#"
-[tools.bash]
-enabled = true
+[tools]
 
-[tools.bash.actions.read]
-tier = "observe"
-patterns = ["[invalid"]
+[output]
+redact = ["[invalid"]
 "#;
         let err = Policy::from_str(toml).unwrap_err();
         assert!(matches!(err, CherubError::PolicyValidation(_)));
     }
 
     #[test]
-    fn unknown_toml_field() {
+    fn output_zero_max_bytes_rejected() {
         let toml = r#"
-[tools.bash]
-enabled = true
-unknown_field = "surprise"
+[tools]
+
+[output]
+max_bytes = 0
 "#;
         let err = Policy::from_str(toml).unwrap_err();
-        assert!(matches!(err, CherubError::PolicyLoad(_)));
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    // --- Pattern hit counters ---
+
+    fn hits_for(policy: &Policy, pattern: &str) -> u64 {
+        policy
+            .stats()
+            .into_iter()
+            .find(|s| s.pattern == pattern)
+            .map(|s| s.hits)
+            .expect("pattern should be in stats")
     }
 
     #[test]
-    fn empty_patterns_rejected() {
+    fn summary_names_tools_only() {
+        let policy = Policy::from_str(
+            r#"
+[tools.bash]
+enabled = true
+
+[tools.bash.actions.read]
+tier = "observe"
+patterns = ["^ls "]
+
+[tools.bash.actions.destructive]
+tier = "commit"
+patterns = ["^rm "]
+
+[tools.file]
+enabled = true
+
+[tools.file.actions.read]
+tier = "observe"
+patterns = ["^read$"]
+
+[tools.http]
+enabled = false
+"#,
+        )
+        .expect("should parse");
+        assert_eq!(
+            policy.summary(),
+            PolicySummary {
+                enabled: vec!["bash".to_owned(), "file".to_owned()],
+                approval_required: vec!["bash".to_owned()],
+            }
+        );
+    }
+
+    #[test]
+    fn stats_start_at_zero() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
+        let stats = policy.stats();
+        assert_eq!(stats.len(), 29);
+        assert!(stats.iter().all(|s| s.hits == 0));
+    }
+
+    #[test]
+    fn stats_count_winning_pattern() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
+        let tool = policy.find_tool("bash").expect("bash should exist");
+
+        tool.match_action("ls /tmp");
+        tool.match_action("ls -la");
+        tool.match_action("mkdir /tmp/x");
+
+        assert_eq!(hits_for(&policy, "^ls "), 2);
+        assert_eq!(hits_for(&policy, "^mkdir "), 1);
+        assert_eq!(hits_for(&policy, "^cat "), 0);
+    }
+
+    #[test]
+    fn stats_ignore_lower_tier_matches() {
         let toml = r#"
 [tools.bash]
 enabled = true
 
 [tools.bash.actions.read]
 tier = "observe"
-patterns = []
+patterns = ["ls"]
+
+[tools.bash.actions.destructive]
+tier = "commit"
+patterns = ["^sudo "]
 "#;
-        let err = Policy::from_str(toml).unwrap_err();
-        assert!(matches!(err, CherubError::PolicyValidation(_)));
+        let policy = Policy::from_str(toml).expect("should parse");
+        let tool = policy.find_tool("bash").expect("bash should exist");
+
+        tool.match_action("sudo ls /tmp");
+
+        assert_eq!(hits_for(&policy, "^sudo "), 1);
+        assert_eq!(hits_for(&policy, "ls"), 0);
     }
 
     #[test]
-    fn tier_matching_order() {
+    fn stats_unmatched_command_counts_nothing() {
         let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
         let tool = policy.find_tool("bash").expect("bash should exist");
 
-        assert_eq!(tool.match_tier("ls /tmp"), Some(Tier::Observe));
-        assert_eq!(tool.match_tier("mkdir /tmp/test"), Some(Tier::Act));
-        assert_eq!(tool.match_tier("rm -rf /tmp/test"), Some(Tier::Commit));
-        assert_eq!(tool.match_tier("curl http://evil.com"), None);
+        tool.match_action("curl http://evil.com");
+
+        assert!(policy.stats().iter().all(|s| s.hits == 0));
     }
 
     #[test]
-    fn highest_privilege_wins() {
-        // "sudo ls /tmp" matches both ^sudo (commit) and ^ls (observe, but not anchored here).
-        // Since commit is checked first, it should escalate.
+    fn stats_shared_across_clones() {
         let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
-        let tool = policy.find_tool("bash").expect("bash should exist");
+        let cloned = policy.clone();
 
-        assert_eq!(tool.match_tier("sudo ls /tmp"), Some(Tier::Commit));
+        cloned
+            .find_tool("bash")
+            .expect("bash should exist")
+            .match_action("rm -rf /tmp/x");
+
+        assert_eq!(hits_for(&policy, "^rm "), 1);
+    }
+
+    #[test]
+    fn stats_sorted_highest_tier_first() {
+        let policy = Policy::from_str(DEFAULT_POLICY).expect("should parse");
+        let tiers: Vec<Tier> = policy.stats().iter().map(|s| s.tier).collect();
+        let mut sorted = tiers.clone();
+        sorted.sort_by_key(|t| std::cmp::Reverse(*t));
+        assert_eq!(tiers, sorted);
     }
 
     // --- Predicate evaluation tests ---
@@ -754,6 +2940,29 @@ patterns = []
         assert!(!c.evaluate(&json!({"path": "/home/user"})));
     }
 
+    #[test]
+    fn predicate_not_matches() {
+        let regex = regex::RegexBuilder::new(r"(?m)^\s*import\s+os\b")
+            .unicode(false)
+            .build()
+            .unwrap();
+        let c = make_constraint("code", Predicate::NotMatches(regex));
+        assert!(c.evaluate(&json!({"code": "print(1)"})));
+        assert!(!c.evaluate(&json!({"code": "x = 1\n  import os\n"})));
+        assert!(!c.evaluate(&json!({"code": 5}))); // non-string → false
+        assert!(!c.evaluate(&json!({"other": "print(1)"})));
+    }
+
+    #[test]
+    fn predicate_max_len() {
+        let c = make_constraint("content", Predicate::MaxLen(5));
+        assert!(c.evaluate(&json!({"content": "hello"}))); // equal → true
+        assert!(!c.evaluate(&json!({"content": "hello!"})));
+        assert!(c.evaluate(&json!({"content": [1, 2]})));
+        assert!(!c.evaluate(&json!({"content": [1, 2, 3, 4, 5, 6]})));
+        assert!(!c.evaluate(&json!({"content": 3})));
+    }
+
     #[test]
     fn missing_field_is_false() {
         let c = make_constraint("missing", Predicate::Eq(json!("anything")));
@@ -837,6 +3046,27 @@ constraints = [
         assert!(matches!(err, CherubError::PolicyValidation(_)));
     }
 
+    #[test]
+    fn constraint_max_len_with_non_integer_rejected() {
+        for value in ["\"big\"", "-1", "1.5"] {
+            let toml = format!(
+                r#"
+[tools.file]
+enabled = true
+
+[tools.file.actions.write]
+tier = "act"
+patterns = ["^write:"]
+constraints = [
+    {{ field = "content", op = "max_len", value = {value} }},
+]
+"#
+            );
+            let err = Policy::from_str(&toml).unwrap_err();
+            assert!(matches!(err, CherubError::PolicyValidation(_)), "{value}");
+        }
+    }
+
     #[test]
     fn constraint_one_of_with_non_array_rejected() {
         let toml = r#"
@@ -895,7 +3125,7 @@ patterns = ["^google-workspace:send_email$"]
             .find_tool("google-workspace")
             .expect("google-workspace should exist");
         assert!(tool.enabled());
-        assert_eq!(tool.match_source(), MatchSource::McpStructured);
+        assert_eq!(tool.match_source(), &MatchSource::McpStructured);
         assert_eq!(tool.actions.len(), 3);
 
         // Verify tier matching via McpStructured extraction.
@@ -916,6 +3146,260 @@ patterns = ["^google-workspace:send_email$"]
         assert_eq!(tool.match_tier("google-workspace:unknown_tool"), None);
     }
 
+    #[test]
+    fn patch_match_source_parses() {
+        let toml = r#"
+[tools.patch]
+enabled = true
+match_source = "patch"
+
+[tools.patch.actions.edit]
+tier = "act"
+patterns = ["^(create|modify):src/"]
+"#;
+        let policy = Policy::from_str(toml).expect("should parse");
+        let tool = policy.find_tool("patch").expect("patch should exist");
+        assert_eq!(tool.match_source(), &MatchSource::Patch);
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let actions = tool
+            .match_source()
+            .extract(&json!({"patch": diff}))
+            .unwrap();
+        assert_eq!(actions, ["modify:src/lib.rs"]);
+        assert_eq!(tool.match_tier(&actions[0]), Some(Tier::Act));
+    }
+
+    #[test]
+    fn kubectl_match_source_tiers_by_verb_and_namespace() {
+        let toml = r#"
+[tools.kubectl]
+enabled = true
+match_source = "kubectl"
+
+[tools.kubectl.actions.read]
+tier = "observe"
+patterns = ["^(get|logs):(dev|staging)$"]
+
+[tools.kubectl.actions.destructive]
+tier = "commit"
+patterns = ["^delete:dev$", "^drain$"]
+"#;
+        let policy = Policy::from_str(toml).expect("should parse");
+        let tool = policy.find_tool("kubectl").expect("kubectl should exist");
+        assert_eq!(tool.match_source(), &MatchSource::Kubectl);
+        let tier = |params: serde_json::Value| {
+            let actions = tool.match_source().extract(&params).unwrap();
+            tool.match_tier(&actions[0])
+        };
+        assert_eq!(
+            tier(json!({"action": "get", "namespace": "staging"})),
+            Some(Tier::Observe)
+        );
+        assert_eq!(
+            tier(json!({"action": "delete", "namespace": "dev"})),
+            Some(Tier::Commit)
+        );
+        // Namespaces outside the patterns match nothing.
+        assert_eq!(tier(json!({"action": "get", "namespace": "prod"})), None);
+        assert_eq!(
+            tier(json!({"action": "drain", "node": "n1"})),
+            Some(Tier::Commit)
+        );
+    }
+
+    #[test]
+    fn sql_databases_compile_and_validate() {
+        let sql = |databases: &str| {
+            Policy::from_str(&format!(
+                "[tools.sql]\nenabled = true\nmatch_source = \"sql\"\n{databases}\n\
+                 [tools.sql.actions.read]\ntier = \"observe\"\npatterns = [\"^select:\"]\n"
+            ))
+        };
+        let policy = sql("[tools.sql.databases.app]\nsqlite = \"app.db\"\n\
+             [tools.sql.databases.pg]\npostgres = \"host=localhost\"\npassword_env = \"PGPW\"\n")
+        .expect("should parse");
+        let tool = policy.find_tool("sql").expect("sql should exist");
+        assert_eq!(tool.match_source(), &MatchSource::Sql);
+        assert_eq!(
+            tool.databases().get("app"),
+            Some(&Database::Sqlite {
+                path: PathBuf::from("app.db")
+            })
+        );
+        assert_eq!(
+            tool.databases().get("pg"),
+            Some(&Database::Postgres {
+                config: "host=localhost".to_owned(),
+                password_env: Some("PGPW".to_owned()),
+            })
+        );
+
+        for databases in [
+            "",
+            "[tools.sql.databases.app]\n",
+            "[tools.sql.databases.app]\nsqlite = \"a.db\"\npostgres = \"host=x\"\n",
+            "[tools.sql.databases.app]\nsqlite = \"a.db\"\npassword_env = \"X\"\n",
+            "[tools.sql.databases.\"a:b\"]\nsqlite = \"a.db\"\n",
+        ] {
+            assert!(
+                matches!(sql(databases), Err(CherubError::PolicyValidation(_))),
+                "{databases}"
+            );
+        }
+        let misplaced = "[tools.bash]\nenabled = true\n\n\
+             [tools.bash.databases.app]\nsqlite = \"a.db\"\n";
+        assert!(matches!(
+            Policy::from_str(misplaced),
+            Err(CherubError::PolicyValidation(_))
+        ));
+    }
+
+    #[test]
+    fn field_match_source_parses() {
+        let toml = r#"
+[tools.search]
+enabled = true
+match_source = "field"
+match_field = "query"
+
+[tools.search.actions.lookup]
+tier = "observe"
+patterns = ["^[a-z ]+$"]
+"#;
+        let policy = Policy::from_str(toml).expect("should parse");
+        let tool = policy.find_tool("search").expect("search should exist");
+        assert_eq!(tool.match_source(), &MatchSource::Field("query".to_owned()));
+        let actions = tool
+            .match_source()
+            .extract(&json!({"query": "rust traits"}))
+            .unwrap();
+        assert_eq!(tool.match_tier(&actions[0]), Some(Tier::Observe));
+    }
+
+    #[test]
+    fn field_match_source_requires_match_field() {
+        let toml = r#"
+[tools.search]
+enabled = true
+match_source = "field"
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
+    #[test]
+    fn aliases_resolve_to_same_tool() {
+        let toml = r#"
+[tools.bash]
+enabled = true
+aliases = ["sh", "shell", "zsh"]
+
+[tools.bash.actions.read]
+tier = "observe"
+patterns = ["^ls "]
+"#;
+        let policy = Policy::from_str(toml).expect("should parse");
+        for name in ["bash", "sh", "shell", "zsh"] {
+            let tool = policy.find_tool(name).expect("alias should resolve");
+            assert_eq!(tool.name, "bash");
+            assert_eq!(tool.match_tier("ls /tmp"), Some(Tier::Observe));
+        }
+        assert!(policy.find_tool("fish").is_none());
+        assert_eq!(policy.canonical_name("zsh"), "bash");
+        assert_eq!(policy.canonical_name("fish"), "fish");
+    }
+
+    #[test]
+    fn alias_collisions_rejected() {
+        for toml in [
+            "[tools.bash]\nenabled = true\naliases = [\"file\"]\n\n[tools.file]\nenabled = true\n",
+            "[tools.bash]\nenabled = true\naliases = [\"sh\"]\n\n[tools.zsh]\nenabled = true\naliases = [\"sh\"]\n",
+            "[tools.bash]\nenabled = true\naliases = [\"bash\"]\n",
+            "[tools.bash]\nenabled = true\naliases = [\"sh\", \"sh\"]\n",
+            "[tools.bash]\nenabled = true\naliases = [\"\"]\n",
+        ] {
+            let err = Policy::from_str(toml).unwrap_err();
+            assert!(
+                matches!(err, CherubError::PolicyValidation(_)),
+                "should reject: {toml}"
+            );
+        }
+    }
+
+    #[test]
+    fn command_param_array_evaluates_every_element() {
+        let toml = r#"
+[tools.runner]
+enabled = true
+command_param = "commands[]"
+
+[tools.runner.actions.read]
+tier = "observe"
+patterns = ["^ls\\b", "^cd\\b"]
+
+[tools.runner.actions.build]
+tier = "act"
+patterns = ["^make\\b"]
+"#;
+        let policy = Policy::from_str(toml).expect("should parse");
+        let tool = policy.find_tool("runner").expect("runner should exist");
+        assert_eq!(
+            tool.match_source(),
+            &MatchSource::CommandField {
+                field: "commands".to_owned(),
+                array: true
+            }
+        );
+        let actions = tool
+            .match_source()
+            .extract(&json!({"commands": ["cd x && ls", "make"]}))
+            .unwrap();
+        assert_eq!(actions, vec!["cd x", "ls", "make"]);
+    }
+
+    #[test]
+    fn command_param_plain_command_is_default() {
+        let toml = "[tools.bash]\nenabled = true\ncommand_param = \"command\"\n";
+        let policy = Policy::from_str(toml).expect("should parse");
+        let tool = policy.find_tool("bash").expect("bash should exist");
+        assert_eq!(tool.match_source(), &MatchSource::Command);
+    }
+
+    #[test]
+    fn command_param_invalid_rejected() {
+        for toml in [
+            "[tools.x]\nenabled = true\ncommand_param = \"[]\"\n",
+            "[tools.x]\nenabled = true\ncommand_param = \"a[][]\"\n",
+            "[tools.x]\nenabled = true\nmatch_source = \"structured\"\ncommand_param = \"cmd\"\n",
+        ] {
+            assert!(Policy::from_str(toml).is_err(), "should reject: {toml}");
+        }
+    }
+
+    #[test]
+    fn git_table_validated() {
+        for toml in [
+            "[tools.x]\nenabled = true\nmatch_source = \"structured\"\n[tools.x.git]\nobserve = [\"status\"]\n",
+            "[tools.x]\nenabled = true\n[tools.x.git]\nact = [\"-f\"]\n",
+            "[tools.x]\nenabled = true\n[tools.x.git]\n",
+        ] {
+            assert!(Policy::from_str(toml).is_err(), "should reject: {toml}");
+        }
+        let toml = "[tools.x]\nenabled = true\n[tools.x.git]\ncommit = [\"push --force\"]\n";
+        assert!(Policy::from_str(toml).is_ok());
+    }
+
+    #[test]
+    fn match_field_without_field_source_rejected() {
+        let toml = r#"
+[tools.bash]
+enabled = true
+match_field = "command"
+"#;
+        let err = Policy::from_str(toml).unwrap_err();
+        assert!(matches!(err, CherubError::PolicyValidation(_)));
+    }
+
     #[test]
     fn constraint_unknown_field_in_constraint_rejected() {
         let toml = r#"
@@ -1,15 +1,17 @@
+use crate::tools::bash::Shell;
+
 /// Parse a compound bash command into individual simple commands.
 ///
 /// Returns `None` if the syntax cannot be safely parsed (deny-by-default).
 /// Each returned string is a single command (trimmed, non-empty).
 ///
-/// Splits on unquoted `;`, `&&`, `||`, `|`, and `\n`.
+/// Splits on unquoted `;`, `&&`, `||`, `|`, `&`, and `\n`.
 /// Recursively extracts commands from `$(...)` and `` `...` `` substitutions.
 /// Rejects null bytes and unbalanced quotes (deny-by-default).
 ///
 /// Redirections (`>`, `<`) are NOT command boundaries — they are part of the
 /// command they belong to. Addressable via policy constraints.
-pub(super) fn parse_commands(input: &str) -> Option<Vec<&str>> {
+pub(crate) fn parse_commands(input: &str) -> Option<Vec<&str>> {
     // Null bytes anywhere → unparseable.
     if input.bytes().any(|b| b == 0) {
         return None;
@@ -20,6 +22,95 @@ pub(super) fn parse_commands(input: &str) -> Option<Vec<&str>> {
     Some(commands)
 }
 
+/// Whether `shell` could run something in `segment` that the POSIX reading of
+/// `parse_commands` does not see, so the segment cannot be evaluated safely.
+pub(super) fn unparseable_for(segment: &str, shell: Shell) -> bool {
+    match shell {
+        Shell::Sh | Shell::Bash => false,
+        Shell::Zsh | Shell::Fish => has_bare_paren(segment),
+        Shell::Pwsh | Shell::Powershell | Shell::Cmd => {
+            diverges_on_windows(segment, shell == Shell::Cmd)
+        }
+    }
+}
+
+/// Whether `segment` has an unquoted `(` other than `$(`.
+///
+/// POSIX shells only run a command from `$(`, which `parse_commands` extracts.
+/// zsh (`=(...)`) and fish (`(...)`) also run one from a bare paren, so for
+/// those shells such a segment cannot be evaluated safely. A backslash inside
+/// single quotes counts too: fish reads it as an escape, so its quoting (and
+/// what is bare) differs from the POSIX reading here.
+fn has_bare_paren(segment: &str) -> bool {
+    let bytes = segment.as_bytes();
+    let mut quote = Quote::None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match quote {
+            Quote::Single if b == b'\\' => return true,
+            Quote::Single if b == b'\'' => quote = Quote::None,
+            Quote::Double if b == b'\\' => i += 1,
+            Quote::Double if b == b'"' => quote = Quote::None,
+            Quote::None if b == b'\\' => i += 1,
+            Quote::None if b == b'\'' => quote = Quote::Single,
+            Quote::None if b == b'"' => quote = Quote::Double,
+            Quote::None if b == b'(' && (i == 0 || bytes[i - 1] != b'$') => return true,
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether PowerShell (or cmd, if `cmd`) could split or quote `segment`
+/// differently from the POSIX reading. Conservative: a segment that merely
+/// might diverge counts.
+///
+/// - Backslash is literal there, so a POSIX escape of anything but a plain
+///   path character (`\"`, `\$`, `\;` ...) hides a quote or operator.
+/// - A bare `(`, `{`, `#`, a backtick, a here-string (`@'`, `@"`), or a
+///   typographic quote has meaning only to PowerShell.
+/// - cmd has no single quotes, and `^` and `%` act before any quoting.
+fn diverges_on_windows(segment: &str, cmd: bool) -> bool {
+    if segment.contains([
+        '\u{2018}', '\u{2019}', '\u{201a}', '\u{201b}', '\u{201c}', '\u{201d}', '\u{201e}',
+    ]) {
+        return true;
+    }
+    let inert = |b: u8| b.is_ascii_alphanumeric() || b"\\/._-:*? ".contains(&b);
+    let bytes = segment.as_bytes();
+    let mut quote = Quote::None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match quote {
+            Quote::Single if b == b'\'' => quote = Quote::None,
+            Quote::Single => {}
+            _ if b == b'\\' => {
+                if bytes.get(i + 1).is_some_and(|&next| !inert(next)) {
+                    return true;
+                }
+                i += 1;
+            }
+            _ if b == b'`' || (cmd && matches!(b, b'^' | b'%')) => return true,
+            Quote::Double if b == b'"' => quote = Quote::None,
+            Quote::Double => {}
+            Quote::None if b == b'\'' && cmd => return true,
+            Quote::None if b == b'\'' => quote = Quote::Single,
+            Quote::None if b == b'"' => quote = Quote::Double,
+            Quote::None if b == b'@' && matches!(bytes.get(i + 1), Some(b'\'' | b'"')) => {
+                return true;
+            }
+            Quote::None if matches!(b, b'{' | b'}' | b'#') => return true,
+            Quote::None if b == b'(' && (i == 0 || bytes[i - 1] != b'$') => return true,
+            Quote::None => {}
+        }
+        i += 1;
+    }
+    false
+}
+
 /// State machine for tracking quoting context.
 #[derive(Clone, Copy, PartialEq)]
 enum Quote {
@@ -110,6 +201,14 @@ fn collect_commands<'a>(input: &'a str, out: &mut Vec<&'a str>) -> Option<()> {
                     push_trimmed(input, cmd_start, i, out);
                     cmd_start = i + 2;
                     i += 2;
+                } else if b == b'&'
+                    && !matches!(bytes.get(i + 1), Some(b'>'))
+                    && !(i > 0 && matches!(bytes[i - 1], b'>' | b'<'))
+                {
+                    // Background `&` (and cmd's separator); not `&>`, `>&`, `<&`.
+                    push_trimmed(input, cmd_start, i, out);
+                    cmd_start = i + 1;
+                    i += 1;
                 } else if b == b'|' && i + 1 < len && bytes[i + 1] == b'|' {
                     // || boundary.
                     push_trimmed(input, cmd_start, i, out);
@@ -267,6 +366,15 @@ mod tests {
         assert_eq!(parse_commands("ls || pwd"), Some(vec!["ls", "pwd"]));
     }
 
+    #[test]
+    fn background_ampersand() {
+        assert_eq!(parse_commands("ls & rm x"), Some(vec!["ls", "rm x"]));
+        assert_eq!(parse_commands("sleep 1 &"), Some(vec!["sleep 1"]));
+        for redirect in ["ls 2>&1", "ls &> out", "ls >&2", "cat <&3"] {
+            assert_eq!(parse_commands(redirect), Some(vec![redirect]), "{redirect}");
+        }
+    }
+
     #[test]
     fn mixed_operators() {
         assert_eq!(
@@ -384,4 +492,48 @@ mod tests {
     fn unbalanced_backtick() {
         assert_eq!(parse_commands("echo `pwd"), None);
     }
+
+    #[test]
+    fn bare_paren_detection() {
+        for segment in ["echo (rm x)", "cat =(ls)", "echo 'a\\' '(x)' ''", "(cd x)"] {
+            assert!(has_bare_paren(segment), "{segment}");
+        }
+        for segment in [
+            "echo $(pwd)",
+            "echo '(x)'",
+            "echo \"(x)\"",
+            "find . \\( -name x \\)",
+        ] {
+            assert!(!has_bare_paren(segment), "{segment}");
+        }
+    }
+
+    #[test]
+    fn windows_shells_reject_divergent_syntax() {
+        for segment in [
+            "Get-Content \"a\\\"; Remove-Item x; \\\"\"",
+            "Get-Content a\\$(x)",
+            "Get-Content (Remove-Item x)",
+            "Get-ChildItem | % { Remove-Item $_ }",
+            "Get-Content x #'",
+            "Re`move-Item x",
+            "Write-Output @'",
+            "Get-Content \u{2018}a\u{2019}",
+        ] {
+            assert!(unparseable_for(segment, Shell::Pwsh), "{segment}");
+        }
+        for segment in ["echo 'a & del x'", "echo a^&b", "echo %PATH%"] {
+            assert!(unparseable_for(segment, Shell::Cmd), "{segment}");
+            assert!(!unparseable_for(segment, Shell::Bash), "{segment}");
+        }
+        for segment in [
+            "Get-Content C:\\src\\main.rs",
+            "Get-Content 'C:\\Program Files\\x'",
+            "Get-Content \"$(Get-Location)\\x\"",
+            "Select-String -Pattern 'a(b)' *.rs",
+        ] {
+            assert!(!unparseable_for(segment, Shell::Pwsh), "{segment}");
+        }
+        assert!(!unparseable_for("dir /s \"C:\\x y\"", Shell::Cmd));
+    }
 }
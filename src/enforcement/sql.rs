@@ -0,0 +1,276 @@
+//! SQL statement classifier for `match_source = "sql"`.
+//!
+//! Splits a query into statements the way both supported backends (SQLite and
+//! PostgreSQL) would, and names each statement by its verb: `select`, `insert`,
+//! `drop`, ... Policy patterns then tier the `"{verb}:{database}"` strings.
+//!
+//! - `WITH` statements take the verb of the most destructive data-modifying
+//!   statement anywhere inside them (`delete`, `update`, `insert`, `merge`),
+//!   or `select` if there is none.
+//! - `EXPLAIN` takes the verb of the statement it explains, since
+//!   `EXPLAIN ANALYZE` runs it.
+//!
+//! Anything the two backends could read differently is unclassifiable and
+//! rejected: nested block comments, dollar-quoted strings, a backslash before
+//! a quote in a string literal, unterminated quotes or comments, and
+//! statements that do not start with a keyword.
+
+/// Data-modifying verbs that may appear inside a `WITH` statement, most
+/// destructive first.
+const WITH_VERBS: &[&str] = &["delete", "update", "merge", "insert"];
+
+/// `EXPLAIN` options that may precede the explained statement.
+const EXPLAIN_OPTIONS: &[&str] = &["analyze", "analyse", "verbose", "query", "plan"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A keyword or unquoted identifier, lowercased.
+    Word(String),
+    Open,
+    Close,
+    Semicolon,
+    /// Anything else: literals, quoted identifiers, operators, parameters.
+    Other,
+}
+
+/// The verb of each statement in `query`, in order. `None` if the query is
+/// empty or cannot be classified.
+pub(super) fn verbs(query: &str) -> Option<Vec<String>> {
+    let tokens = tokenize(query)?;
+    let verbs: Vec<String> = tokens
+        .split(|t| *t == Token::Semicolon)
+        .filter(|statement| !statement.is_empty())
+        .map(verb)
+        .collect::<Option<_>>()?;
+    if verbs.is_empty() { None } else { Some(verbs) }
+}
+
+fn verb(statement: &[Token]) -> Option<String> {
+    let Some(Token::Word(first)) = statement.first() else {
+        return None;
+    };
+    match first.as_str() {
+        "with" => Some(
+            WITH_VERBS
+                .iter()
+                .find(|v| {
+                    statement
+                        .iter()
+                        .any(|t| matches!(t, Token::Word(w) if w == *v))
+                })
+                .map_or("select", |v| *v)
+                .to_owned(),
+        ),
+        "explain" => {
+            let mut rest = &statement[1..];
+            loop {
+                match rest.first() {
+                    Some(Token::Word(w)) if EXPLAIN_OPTIONS.contains(&w.as_str()) => {
+                        rest = &rest[1..];
+                    }
+                    // Parenthesized option list: EXPLAIN (ANALYZE, FORMAT JSON) ...
+                    Some(Token::Open) => {
+                        let close = rest.iter().position(|t| *t == Token::Close)?;
+                        rest = &rest[close + 1..];
+                    }
+                    _ => break,
+                }
+            }
+            verb(rest)
+        }
+        _ => Some(first.clone()),
+    }
+}
+
+fn tokenize(query: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while let Some(&c) = chars.get(i) {
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '-' if next == Some('-') => {
+                i = chars[i..]
+                    .iter()
+                    .position(|&c| c == '\n')
+                    .map_or(chars.len(), |p| i + p + 1);
+            }
+            '/' if next == Some('*') => {
+                let body = &chars[i + 2..];
+                let end = body.windows(2).position(|w| w == ['*', '/'])?;
+                // PostgreSQL nests block comments, SQLite does not.
+                if body[..end].windows(2).any(|w| w == ['/', '*']) {
+                    return None;
+                }
+                i += end + 4;
+            }
+            '\'' => {
+                i = skip_string(&chars, i)?;
+                tokens.push(Token::Other);
+            }
+            '"' | '`' | '[' => {
+                let close = if c == '[' { ']' } else { c };
+                i = skip_quoted(&chars, i, close)?;
+                tokens.push(Token::Other);
+            }
+            // `$1` is a parameter; `$$` or `$tag$` opens a dollar-quoted string,
+            // which SQLite does not have.
+            '$' if next.is_some_and(|n| n.is_ascii_digit()) => {
+                i += 1;
+                tokens.push(Token::Other);
+            }
+            '$' => return None,
+            '(' => {
+                i += 1;
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                i += 1;
+                tokens.push(Token::Close);
+            }
+            ';' => {
+                i += 1;
+                tokens.push(Token::Semicolon);
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .position(|&c| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                    .unwrap_or(chars.len() - i);
+                let word: String = chars[i..i + len].iter().collect();
+                tokens.push(Token::Word(word.to_lowercase()));
+                i += len;
+            }
+            _ => {
+                i += 1;
+                tokens.push(Token::Other);
+            }
+        }
+    }
+    Some(tokens)
+}
+
+/// Index just past the string literal opening at `start`; `''` is an escaped
+/// quote. `None` for `\'`, which ends the literal in SQLite but escapes the
+/// quote in a PostgreSQL `E'...'` string (or any string, depending on server
+/// settings).
+fn skip_string(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    loop {
+        match chars.get(i)? {
+            '\\' if chars.get(i + 1) == Some(&'\'') => return None,
+            '\'' if chars.get(i + 1) == Some(&'\'') => i += 2,
+            '\'' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+}
+
+/// Index just past the quoted identifier opening at `start`; a doubled `close`
+/// is an escaped one.
+fn skip_quoted(chars: &[char], start: usize, close: char) -> Option<usize> {
+    let mut i = start + 1;
+    loop {
+        match *chars.get(i)? {
+            c if c == close && chars.get(i + 1) == Some(&close) && close != ']' => i += 2,
+            c if c == close => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(query: &str) -> Option<Vec<String>> {
+        verbs(query)
+    }
+
+    #[test]
+    fn statements_are_split_and_named() {
+        assert_eq!(v("SELECT 1"), Some(vec!["select".to_owned()]));
+        assert_eq!(
+            v("insert into t values (1); UPDATE t SET a = 2;\nDrop Table t"),
+            Some(vec![
+                "insert".to_owned(),
+                "update".to_owned(),
+                "drop".to_owned()
+            ])
+        );
+        assert_eq!(v(";; SELECT 1;"), Some(vec!["select".to_owned()]));
+        assert_eq!(v(""), None);
+        assert_eq!(v("  -- only a comment"), None);
+        assert_eq!(v("(SELECT 1)"), None);
+    }
+
+    #[test]
+    fn semicolons_in_literals_and_comments_do_not_split() {
+        for query in [
+            "SELECT 'a; DROP TABLE t'",
+            "SELECT 'it''s; fine'",
+            "SELECT \"weird;name\" FROM t",
+            "SELECT `x;y` FROM [a;b]",
+            "SELECT 1 -- ; DROP TABLE t",
+            "SELECT /* ; DROP TABLE t */ 1",
+        ] {
+            assert_eq!(v(query), Some(vec!["select".to_owned()]), "{query}");
+        }
+        assert_eq!(
+            v("SELECT 1 -- c\n; DROP TABLE t"),
+            Some(vec!["select".to_owned(), "drop".to_owned()])
+        );
+    }
+
+    #[test]
+    fn ambiguous_syntax_is_unclassifiable() {
+        for query in [
+            "SELECT /* /* */ 1; DROP TABLE t; */",
+            "SELECT $$; DROP TABLE t; $$",
+            "SELECT $a$ x $a$",
+            "SELECT 'a\\'; DROP TABLE t; --'",
+            "SELECT E'\\'; DROP TABLE t; --'",
+            "SELECT 'unterminated",
+            "SELECT \"unterminated",
+            "SELECT /* unterminated",
+        ] {
+            assert_eq!(v(query), None, "{query}");
+        }
+        // Positional parameters are fine.
+        assert_eq!(
+            v("SELECT * FROM t WHERE id = $1"),
+            Some(vec!["select".to_owned()])
+        );
+    }
+
+    #[test]
+    fn with_and_explain_take_the_inner_verb() {
+        assert_eq!(
+            v("WITH x AS (SELECT 1) SELECT * FROM x"),
+            Some(vec!["select".to_owned()])
+        );
+        assert_eq!(
+            v("WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"),
+            Some(vec!["delete".to_owned()])
+        );
+        assert_eq!(
+            v("with recursive r(n) as (select 1) insert into t select n from r"),
+            Some(vec!["insert".to_owned()])
+        );
+        assert_eq!(v("EXPLAIN SELECT 1"), Some(vec!["select".to_owned()]));
+        assert_eq!(
+            v("EXPLAIN ANALYZE DELETE FROM t"),
+            Some(vec!["delete".to_owned()])
+        );
+        assert_eq!(
+            v("explain (analyze, format json) update t set a = 1"),
+            Some(vec!["update".to_owned()])
+        );
+        assert_eq!(
+            v("EXPLAIN QUERY PLAN SELECT 1"),
+            Some(vec!["select".to_owned()])
+        );
+        assert_eq!(v("EXPLAIN"), None);
+    }
+}
@@ -1,6 +1,10 @@
+use serde::{Deserialize, Serialize};
+
 /// Capability tiers ordered by privilege level.
 /// Variant order defines the `Ord` derivation: Observe < Act < Commit.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Serializes as `"observe"` / `"act"` / `"commit"`, matching `as_str()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Tier {
     Observe,
     Act,
@@ -27,4 +31,13 @@ mod tests {
         assert!(Tier::Act < Tier::Commit);
         assert!(Tier::Observe < Tier::Commit);
     }
+
+    #[test]
+    fn tier_serde_matches_as_str() {
+        for tier in [Tier::Observe, Tier::Act, Tier::Commit] {
+            let json = serde_json::to_string(&tier).unwrap();
+            assert_eq!(json, format!("\"{}\"", tier.as_str()));
+            assert_eq!(serde_json::from_str::<Tier>(&json).unwrap(), tier);
+        }
+    }
 }
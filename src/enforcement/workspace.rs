@@ -0,0 +1,459 @@
+//! Workspace confinement for command tools.
+//!
+//! With a `[workspace]` table, every token carries the workspace root and the
+//! bash and file tools run inside it. Pinning the working directory is not
+//! enough on its own: `cat /etc/shadow` or `ls ../../..` name paths outside it
+//! directly. This stage resolves every argument of every command segment
+//! against the root and rejects (or escalates) the invocation if one escapes:
+//!
+//! - absolute paths and `..` traversal are normalized lexically;
+//! - the longest existing prefix is canonicalized, so a symlink inside the
+//!   workspace that points outside it counts as an escape;
+//! - arguments the shell would expand (`$VAR`, `~`, backticks) cannot be
+//!   resolved here and count as an escape — deny by default.
+//!
+//! The program itself (first word of a segment) and option flags are not
+//! checked; `--opt=value` is checked on its value.
+
+use std::path::{Component, Path, PathBuf};
+
+use tracing::info;
+
+use super::policy::OnConstraintFailure;
+use super::{DecisionKind, RejectReason, reject};
+use crate::tools::bash::Shell;
+
+/// Compiled `[workspace]` section.
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledWorkspace {
+    pub(crate) root: PathBuf, // Canonical
+    on_escape: OnConstraintFailure,
+    allow_paths: Vec<PathBuf>, // Absolute paths outside the root that are permitted
+}
+
+impl CompiledWorkspace {
+    /// Build from the policy table. `root` must be an existing absolute
+    /// directory; `allow_paths` must be absolute.
+    pub(super) fn new(
+        root: &str,
+        on_escape: OnConstraintFailure,
+        allow_paths: &[String],
+    ) -> Result<Self, String> {
+        let root = Path::new(root);
+        if !root.is_absolute() {
+            return Err("workspace.root must be an absolute path".to_owned());
+        }
+        let root = root
+            .canonicalize()
+            .map_err(|e| format!("workspace.root cannot be resolved: {e}"))?;
+        if !root.is_dir() {
+            return Err("workspace.root must be a directory".to_owned());
+        }
+        let allow_paths = allow_paths
+            .iter()
+            .map(|p| {
+                let path = Path::new(p);
+                if path.is_absolute() {
+                    Ok(normalize(path))
+                } else {
+                    Err(format!("workspace.allow_paths: '{p}' must be absolute"))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            root,
+            on_escape,
+            allow_paths,
+        })
+    }
+
+    /// The first argument of `segment` that resolves outside the root, if any,
+    /// read by `shell`'s word rules. An unparseable segment (unbalanced
+    /// quotes) is returned whole.
+    pub(super) fn escaping_arg(&self, segment: &str, shell: Shell) -> Option<String> {
+        let Some(words) = split_words(segment, shell) else {
+            return Some(segment.to_owned());
+        };
+        words
+            .into_iter()
+            .skip(1)
+            .find(|word| self.escapes(word, shell))
+            .map(|word| word.text)
+    }
+
+    fn escapes(&self, word: &Word, shell: Shell) -> bool {
+        let Some(arg) = path_arg(&word.text, shell) else {
+            return false;
+        };
+        if word.expands || (shell.is_windows() && foreign_root(arg)) {
+            return true;
+        }
+        // Windows shells separate with `\` too (pwsh does on any host).
+        let arg = if shell.is_windows() && !cfg!(windows) {
+            arg.replace('\\', "/")
+        } else {
+            arg.to_owned()
+        };
+        let lexical = normalize(&self.root.join(arg));
+        if self.allow_paths.contains(&lexical) {
+            return false;
+        }
+        if !lexical.starts_with(&self.root) {
+            return true;
+        }
+        let resolved = resolve_existing(&lexical);
+        !resolved.starts_with(&self.root) && !self.allow_paths.contains(&resolved)
+    }
+}
+
+/// Reject or escalate an allowed decision if any command segment names a path
+/// outside the workspace. Rejections pass through unchecked.
+pub(super) fn apply_workspace(
+    decision: DecisionKind,
+    workspace: &CompiledWorkspace,
+    segments: &[String],
+    shell: Shell,
+) -> DecisionKind {
+    let tier = match decision {
+        DecisionKind::Reject { .. } => return decision,
+        DecisionKind::Allow { tier } | DecisionKind::Escalate { tier } => tier,
+    };
+    let Some(path) = segments
+        .iter()
+        .find_map(|s| workspace.escaping_arg(s, shell))
+    else {
+        return decision;
+    };
+    match (workspace.on_escape, decision) {
+        (OnConstraintFailure::Reject, _) => {
+            info!(decision = "reject", reason = "workspace_escape", path = %path);
+            reject(RejectReason::OutsideWorkspace)
+        }
+        (OnConstraintFailure::Escalate, DecisionKind::Allow { .. }) => {
+            info!(decision = "escalate", reason = "workspace_escape", path = %path);
+            DecisionKind::Escalate { tier }
+        }
+        (OnConstraintFailure::Escalate, decision) => decision,
+    }
+}
+
+/// A shell word with quotes removed. `expands` is set if any part outside
+/// single quotes is subject to expansion (`$`, backtick, leading `~`), or an
+/// unquoted brace expansion (`{a,b}`, `{1..3}`) in a POSIX shell.
+#[derive(Debug)]
+pub(super) struct Word {
+    pub(super) text: String,
+    pub(super) expands: bool,
+}
+
+/// Split a simple command into words, honoring quotes and backslashes (which
+/// are path separators, not escapes, in Windows shells). `None` on unbalanced
+/// quotes.
+pub(super) fn split_words(segment: &str, shell: Shell) -> Option<Vec<Word>> {
+    let escapes = !shell.is_windows();
+    let mut words = Vec::new();
+    let mut current: Option<Word> = None;
+    let mut chars = segment.chars();
+    let mut quote: Option<char> = None;
+    // Unquoted `{` seen in this word, and whether a `,` or `..` followed it.
+    let mut brace = None;
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), c) => word(&mut current).text.push(c),
+            (_, '\\') if escapes => {
+                if let Some(next) = chars.next() {
+                    word(&mut current).text.push(next);
+                }
+            }
+            (Some(_), c) => {
+                let w = word(&mut current);
+                w.expands |= matches!(c, '$' | '`');
+                w.text.push(c);
+            }
+            (None, '\'' | '"') => {
+                word(&mut current);
+                quote = Some(c);
+            }
+            (None, c) if c.is_whitespace() => {
+                words.extend(current.take());
+                brace = None;
+            }
+            (None, c) => {
+                let w = word(&mut current);
+                w.expands |= matches!(c, '$' | '`') || (c == '~' && w.text.is_empty());
+                if escapes {
+                    match (c, brace) {
+                        ('{', _) => brace = Some(false),
+                        (',', Some(_)) => brace = Some(true),
+                        ('.', Some(_)) if w.text.ends_with('.') => brace = Some(true),
+                        ('}', Some(list)) => w.expands |= list,
+                        _ => {}
+                    }
+                }
+                w.text.push(c);
+            }
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    words.extend(current);
+    Some(words)
+}
+
+fn word(current: &mut Option<Word>) -> &mut Word {
+    current.get_or_insert_with(|| Word {
+        text: String::new(),
+        expands: false,
+    })
+}
+
+/// The path an argument refers to, if it can name one: redirection targets
+/// (`2>/dev/null`), `--opt=value` values (and PowerShell's `-Opt:value`),
+/// values attached to POSIX short options (`-o/tmp/out`, `-rf../x`), and
+/// plain words. Long flags, cmd `/switches`, fd duplications (`2>&1`), and
+/// empty words are not paths.
+fn path_arg(word: &str, shell: Shell) -> Option<&str> {
+    if shell == Shell::Cmd && word.starts_with('/') {
+        return None;
+    }
+    let separators: &[char] = if shell.is_windows() {
+        &['=', ':']
+    } else {
+        &['=']
+    };
+    let arg = word.trim_start_matches(|c: char| c.is_ascii_digit());
+    let arg = match arg.strip_prefix('&').unwrap_or(arg) {
+        a if a.starts_with(['>', '<']) => a.trim_start_matches(['>', '<']),
+        _ => word,
+    };
+    let arg = match arg.strip_prefix('-') {
+        Some(flag) => match flag.split_once(separators) {
+            Some((_, value)) => value,
+            None if shell.is_windows() || flag.starts_with('-') => return None,
+            // Short options may carry their value attached, after any number
+            // of other letters: from the first path-like character, else
+            // everything after the option letter.
+            None => match flag.find(['/', '.', '~']) {
+                Some(start) => &flag[start..],
+                None => flag.get(1..)?,
+            },
+        },
+        None => arg,
+    };
+    (!arg.is_empty() && !arg.starts_with('&')).then_some(arg)
+}
+
+/// Whether `arg` is rooted somewhere the host's `Path` cannot place: a
+/// PowerShell provider (`HKLM:`, `Env:`) anywhere, and a drive (`C:`) or UNC
+/// (`\\host`) path on a non-Windows host.
+fn foreign_root(arg: &str) -> bool {
+    let prefix = arg.split_once(':').and_then(|(name, rest)| {
+        let named = !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric());
+        (named && !rest.starts_with("//")).then_some(name)
+    });
+    match prefix {
+        Some(name) if name.len() > 1 => true,
+        Some(_) => !cfg!(windows),
+        None => !cfg!(windows) && arg.starts_with("\\\\"),
+    }
+}
+
+/// Lexically resolve `.` and `..` components. `..` at the root stays at the root.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Canonicalize the longest existing prefix of `path` (following symlinks) and
+/// append the rest.
+fn resolve_existing(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest.iter().rev().fold(canonical, |p, c| p.join(c));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(dir: &Path) -> CompiledWorkspace {
+        CompiledWorkspace::new(
+            dir.to_str().unwrap(),
+            OnConstraintFailure::Reject,
+            &["/dev/null".to_owned()],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn paths_inside_root_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let ws = workspace(dir.path());
+        let inside = format!("cat {}/src/main.rs", dir.path().display());
+        for segment in [
+            "ls -la",
+            "cat src/main.rs",
+            "grep -r foo ./src",
+            "ls src/../src",
+            "cargo build 2>/dev/null",
+            "make 2>&1",
+            "sed 's/a/b/' notes.txt",
+            "awk '{print $1}' data.csv",
+            "find . -name x -exec cat {} ;",
+            "head -n5 src/main.rs",
+            "tar -czf out.tar ./src",
+            inside.as_str(),
+        ] {
+            assert_eq!(ws.escaping_arg(segment, Shell::Bash), None, "{segment}");
+        }
+    }
+
+    #[test]
+    fn escapes_are_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = workspace(dir.path());
+        for (segment, arg) in [
+            ("cat /etc/passwd", "/etc/passwd"),
+            ("ls ../..", "../.."),
+            ("ls src/../../x", "src/../../x"),
+            ("echo hi > /tmp/out", "/tmp/out"),
+            ("echo hi >/tmp/out", ">/tmp/out"),
+            ("tar --file=/tmp/a.tar -c .", "--file=/tmp/a.tar"),
+            ("cat \"/etc/shadow\"", "/etc/shadow"),
+            ("cat $HOME/.ssh/id_rsa", "$HOME/.ssh/id_rsa"),
+            ("cat ~/.bashrc", "~/.bashrc"),
+            ("cat \"$SECRET\"", "$SECRET"),
+            ("cat {/etc,.}/passwd", "{/etc,.}/passwd"),
+            ("ls {..,.}/secret", "{..,.}/secret"),
+            ("cat f{1..3}", "f{1..3}"),
+            ("sort -o/home/u/.bashrc in", "-o/home/u/.bashrc"),
+            ("grep -f/etc/shadow .", "-f/etc/shadow"),
+            ("rm -rf../x", "-rf../x"),
+            ("cat -v$HOME/x", "-v$HOME/x"),
+        ] {
+            assert_eq!(
+                ws.escaping_arg(segment, Shell::Bash).as_deref(),
+                Some(arg),
+                "{segment}"
+            );
+        }
+    }
+
+    #[test]
+    fn symlink_out_of_root_is_an_escape() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+        let ws = workspace(dir.path());
+        assert_eq!(
+            ws.escaping_arg("cat link/secret", Shell::Bash).as_deref(),
+            Some("link/secret")
+        );
+        assert_eq!(
+            ws.escaping_arg("ls link", Shell::Bash).as_deref(),
+            Some("link")
+        );
+    }
+
+    #[test]
+    fn windows_shell_word_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = workspace(dir.path());
+        for segment in [
+            "Get-Content src\\main.rs",
+            "Get-ChildItem -Path:src -Recurse",
+            "dir /s /b src",
+        ] {
+            let shell = if segment.starts_with("dir") {
+                Shell::Cmd
+            } else {
+                Shell::Pwsh
+            };
+            assert_eq!(ws.escaping_arg(segment, shell), None, "{segment}");
+        }
+        for (segment, arg) in [
+            ("Get-Content ..\\..\\secret", "..\\..\\secret"),
+            ("Get-ChildItem -Path:/etc", "-Path:/etc"),
+            ("Get-ChildItem HKLM:\\Software", "HKLM:\\Software"),
+            ("Get-Content $env:USERPROFILE\\x", "$env:USERPROFILE\\x"),
+        ] {
+            assert_eq!(
+                ws.escaping_arg(segment, Shell::Pwsh).as_deref(),
+                Some(arg),
+                "{segment}"
+            );
+        }
+    }
+
+    #[test]
+    fn unbalanced_quotes_are_an_escape() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(
+            workspace(dir.path())
+                .escaping_arg("cat 'oops", Shell::Bash)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn invalid_config_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        let reject = OnConstraintFailure::Reject;
+        assert!(CompiledWorkspace::new("relative", reject, &[]).is_err());
+        assert!(CompiledWorkspace::new("/nonexistent/cherub/root", reject, &[]).is_err());
+        assert!(CompiledWorkspace::new(root, reject, &["dev/null".to_owned()]).is_err());
+    }
+
+    #[test]
+    fn escape_rejects_or_escalates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        let segments = ["cat /etc/passwd".to_owned()];
+        let allow = DecisionKind::Allow {
+            tier: super::super::tier::Tier::Observe,
+        };
+
+        let ws = CompiledWorkspace::new(root, OnConstraintFailure::Reject, &[]).unwrap();
+        assert_eq!(
+            apply_workspace(allow, &ws, &segments, Shell::Bash),
+            reject(RejectReason::OutsideWorkspace)
+        );
+
+        let ws = CompiledWorkspace::new(root, OnConstraintFailure::Escalate, &[]).unwrap();
+        assert_eq!(
+            apply_workspace(allow, &ws, &segments, Shell::Bash),
+            DecisionKind::Escalate {
+                tier: super::super::tier::Tier::Observe
+            }
+        );
+        assert_eq!(
+            apply_workspace(allow, &ws, &["ls".to_owned()], Shell::Bash),
+            allow
+        );
+    }
+}
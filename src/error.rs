@@ -1,16 +1,63 @@
 use thiserror::Error;
 
+use crate::enforcement::RejectReason;
+
 #[derive(Debug, Error)]
 pub enum CherubError {
     #[error("action not permitted")]
     NotPermitted,
 
+    /// Enforcement rejected the invocation. Displays identically to
+    /// `NotPermitted` (policy opacity); the reason is for callers to branch on.
+    #[error("action not permitted")]
+    Rejected(RejectReason),
+
     #[error("tool execution failed: {0}")]
     ToolExecution(String),
 
     #[error("provider error: {0}")]
     Provider(String),
 
+    /// A provider kept failing transiently (429, 5xx, connection errors)
+    /// until its retries ran out. Distinct from `Provider` so callers can
+    /// tell "try again later" from a request that will never succeed.
+    #[error("provider retries exhausted: {last_error} (after {retries} retries)")]
+    RetriesExhausted { retries: u32, last_error: String },
+
+    /// A structured completion never produced JSON matching its schema, even
+    /// after reprompting with the parse error.
+    #[error("structured output invalid after {attempts} attempts: {last_error}")]
+    StructuredOutput { attempts: u32, last_error: String },
+
+    /// The session's estimated cost reached the configured ceiling; no
+    /// further provider calls are made.
+    #[error("cost ceiling reached: ${spent_usd:.4} spent, ceiling ${ceiling_usd:.2}")]
+    CostCeilingExceeded { spent_usd: f64, ceiling_usd: f64 },
+
+    /// A turn ran out of its loop budget (`AgentLoop::with_loop_budget`).
+    /// The turn stopped between steps; the history is intact.
+    #[error("turn stopped: loop budget of {limit} exhausted")]
+    BudgetExceeded {
+        limit: crate::runtime::budget::BudgetLimit,
+    },
+
+    /// A provider call ran past its timeout. `partial` is the text that
+    /// streamed in before it was abandoned (empty if none).
+    #[error("provider call timed out after {}s", after.as_secs_f32())]
+    ProviderTimeout {
+        after: std::time::Duration,
+        partial: String,
+    },
+
+    /// The run was cancelled through its `CancellationToken`. `partial` is
+    /// as for `ProviderTimeout`; empty if no inference call was interrupted.
+    #[error("run cancelled")]
+    Cancelled { partial: String },
+
+    /// An `on_before_completion` hook stopped the turn, for this reason.
+    #[error("stopped by hook: {0}")]
+    HookVeto(String),
+
     #[error("invalid tool invocation: {0}")]
     InvalidInvocation(String),
 
@@ -23,7 +70,11 @@ pub enum CherubError {
     #[error("configuration error: {0}")]
     Config(String),
 
-    #[cfg(feature = "postgres")]
+    /// A user-supplied image or document could not be attached.
+    #[error("attachment error: {0}")]
+    Attachment(String),
+
+    /// A database store or session file could not be read or written.
     #[error("storage error: {0}")]
     Storage(String),
 
@@ -56,3 +107,9 @@ pub enum CherubError {
     #[error("mcp error: {0}")]
     Mcp(String),
 }
+
+impl From<RejectReason> for CherubError {
+    fn from(reason: RejectReason) -> Self {
+        CherubError::Rejected(reason)
+    }
+}
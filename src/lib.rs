@@ -3,7 +3,7 @@ pub mod error;
 pub mod providers;
 pub mod retry;
 pub mod runtime;
-#[cfg(feature = "postgres")]
+#[cfg(any(feature = "postgres", feature = "sqlite-store"))]
 pub mod storage;
 #[cfg(feature = "telegram")]
 pub mod telegram;
@@ -1,24 +1,38 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use rustyline::DefaultEditor;
 use rustyline::error::ReadlineError;
 use secrecy::SecretString;
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
 use cherub::enforcement::policy::Policy;
+use cherub::providers::UserContent;
 use cherub::providers::anthropic::AnthropicProvider;
+use cherub::providers::bedrock::BedrockProvider;
+use cherub::providers::cache::{CacheBackend, CachingProvider};
+use cherub::providers::layer::{LayeredProvider, ProviderLayer, SecretScrubber};
+use cherub::providers::ollama::OllamaProvider;
 use cherub::providers::openai::OpenAiProvider;
+use cherub::providers::openrouter::OpenRouterProvider;
+use cherub::providers::sigv4::{self, AwsCredentials};
 use cherub::runtime::AgentLoop;
 use cherub::runtime::approval::CliApprovalGate;
+use cherub::runtime::budget::LoopBudget;
 use cherub::runtime::output::StdoutSink;
-use cherub::runtime::prompt::build_system_prompt;
-use cherub::tools::ToolRegistry;
+use cherub::runtime::prompt::PromptBuilder;
+use cherub::tools::{DRY_RUN_OUTPUT, ToolRegistry, process_group};
 
 const DEFAULT_POLICY_PATH: &str = "config/default_policy.toml";
 const DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
 const DEFAULT_MAX_TOKENS: u32 = 4096;
+const DEFAULT_OLLAMA_MODEL: &str = "llama3.2";
+const DEFAULT_BEDROCK_MODEL: &str = "us.anthropic.claude-sonnet-4-20250514-v1:0";
+const DEFAULT_OPENROUTER_MODEL: &str = "anthropic/claude-sonnet-4";
 
 // ─── CLI argument parsing ─────────────────────────────────────────────────────
 
@@ -28,12 +42,41 @@ enum Command {
     Agent {
         policy_path: PathBuf,
         model: String,
-        /// Provider backend: "anthropic" or "openai".
+        /// Provider backend: "anthropic", "openai", "ollama", "bedrock", or "openrouter".
         provider: String,
-        /// Custom base URL for OpenAI-compatible endpoints (Ollama, vLLM, etc.).
+        /// Custom base URL: an OpenAI-compatible endpoint (Ollama, vLLM, etc.), an
+        /// Anthropic gateway, or a Bedrock runtime endpoint.
         base_url: Option<String>,
         /// Provider configuration file (TOML). Overrides --provider/--base-url/--model.
         providers_config: Option<PathBuf>,
+        /// Replay completions cached in this directory; record new ones there.
+        response_cache: Option<PathBuf>,
+        /// Redact environment secrets from everything sent to the provider.
+        scrub_secrets: bool,
+        /// Log every provider request and response at debug level.
+        log_exchanges: bool,
+        /// Stop making provider calls once the session's estimated cost reaches this (USD).
+        max_cost: Option<f64>,
+        /// Abandon an inference call after this long, keeping its partial output.
+        provider_timeout: Option<Duration>,
+        /// Per-turn limits on model calls, tool calls, time, and tokens.
+        loop_budget: LoopBudget,
+        /// Simulate Act/Commit tool executions, returning this output instead.
+        dry_run: Option<String>,
+        /// Snapshot the workspace before act/commit tool calls (`/rollback` undoes).
+        snapshots: bool,
+        /// Report the files each tool call created, modified, or deleted.
+        track_files: bool,
+        /// Register the kubectl tool (namespaces come from the policy patterns).
+        kubectl: bool,
+        /// Keep the session in this JSON file, resuming it if it exists.
+        session_file: Option<PathBuf>,
+        /// SQLite database for session history and the audit log.
+        #[cfg(feature = "sqlite-store")]
+        sqlite_store: Option<PathBuf>,
+        /// Register the SQL tool (databases come from the policy).
+        #[cfg(any(feature = "sqlite", feature = "postgres"))]
+        sql: bool,
         /// Optional directory of WASM tools to load (M8).
         #[cfg(feature = "wasm")]
         wasm_tools_dir: Option<PathBuf>,
@@ -70,6 +113,7 @@ enum AuditSubcommand {
         decision: Option<String>,
         user_id: Option<String>,
         session_id: Option<String>,
+        invocation_id: Option<String>,
         limit: Option<i64>,
     },
 }
@@ -158,6 +202,21 @@ fn parse_args() -> Result<Command> {
     #[cfg(feature = "mcp")]
     let mut mcp_config: Option<PathBuf> = None;
     let mut providers_config: Option<PathBuf> = None;
+    let mut response_cache: Option<PathBuf> = None;
+    let mut scrub_secrets = true;
+    let mut log_exchanges = false;
+    let mut max_cost: Option<f64> = None;
+    let mut provider_timeout: Option<Duration> = None;
+    let mut loop_budget = LoopBudget::default();
+    let mut dry_run: Option<String> = None;
+    let mut snapshots = false;
+    let mut track_files = false;
+    let mut kubectl = false;
+    let mut session_file: Option<PathBuf> = None;
+    #[cfg(feature = "sqlite-store")]
+    let mut sqlite_store: Option<PathBuf> = None;
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    let mut sql = false;
 
     let mut i = 1;
     while i < args.len() {
@@ -217,18 +276,107 @@ fn parse_args() -> Result<Command> {
                     providers_config = Some(PathBuf::from(&args[i]));
                 }
             }
+            "--response-cache" => {
+                i += 1;
+                if i < args.len() {
+                    response_cache = Some(PathBuf::from(&args[i]));
+                }
+            }
+            "--no-scrub-secrets" => scrub_secrets = false,
+            "--log-exchanges" => log_exchanges = true,
+            "--max-cost" => {
+                i += 1;
+                let usd = args
+                    .get(i)
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .filter(|usd| usd.is_finite() && *usd > 0.0)
+                    .context("--max-cost requires a positive amount in USD")?;
+                max_cost = Some(usd);
+            }
+            "--provider-timeout" => {
+                i += 1;
+                let secs = args
+                    .get(i)
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .filter(|secs| *secs > 0)
+                    .context("--provider-timeout requires a positive number of seconds")?;
+                provider_timeout = Some(Duration::from_secs(secs));
+            }
+            "--max-iterations" => {
+                i += 1;
+                let n = args
+                    .get(i)
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .filter(|n| *n > 0)
+                    .context("--max-iterations requires a positive number")?;
+                loop_budget.max_iterations = Some(n);
+            }
+            "--max-tool-calls" => {
+                i += 1;
+                let n = args
+                    .get(i)
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .filter(|n| *n > 0)
+                    .context("--max-tool-calls requires a positive number")?;
+                loop_budget.max_tool_calls = Some(n);
+            }
+            "--max-turn-time" => {
+                i += 1;
+                let secs = args
+                    .get(i)
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .filter(|secs| *secs > 0)
+                    .context("--max-turn-time requires a positive number of seconds")?;
+                loop_budget.max_duration = Some(Duration::from_secs(secs));
+            }
+            "--max-turn-tokens" => {
+                i += 1;
+                let n = args
+                    .get(i)
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .filter(|n| *n > 0)
+                    .context("--max-turn-tokens requires a positive number")?;
+                loop_budget.max_tokens = Some(n);
+            }
+            "--dry-run" => {
+                dry_run.get_or_insert_with(|| DRY_RUN_OUTPUT.to_owned());
+            }
+            "--dry-run-output" => {
+                i += 1;
+                if i < args.len() {
+                    dry_run = Some(args[i].clone());
+                }
+            }
+            "--snapshots" => snapshots = true,
+            "--track-files" => track_files = true,
+            "--kubectl" => kubectl = true,
+            "--session-file" => {
+                i += 1;
+                if i < args.len() {
+                    session_file = Some(PathBuf::from(&args[i]));
+                }
+            }
+            #[cfg(feature = "sqlite-store")]
+            "--sqlite-store" => {
+                i += 1;
+                if i < args.len() {
+                    sqlite_store = Some(PathBuf::from(&args[i]));
+                }
+            }
+            #[cfg(any(feature = "sqlite", feature = "postgres"))]
+            "--sql" => sql = true,
             _ => {}
         }
         i += 1;
     }
 
     // Default model depends on provider.
-    let model = model.unwrap_or_else(|| {
-        if provider == "openai" {
-            "gpt-4o".to_owned()
-        } else {
-            DEFAULT_MODEL.to_owned()
-        }
+    let model = model.unwrap_or_else(|| match provider.as_str() {
+        "openai" => "gpt-4o".to_owned(),
+        "ollama" => DEFAULT_OLLAMA_MODEL.to_owned(),
+        "bedrock" => DEFAULT_BEDROCK_MODEL.to_owned(),
+        "openrouter" => DEFAULT_OPENROUTER_MODEL.to_owned(),
+        _ => DEFAULT_MODEL.to_owned(),
     });
 
     Ok(Command::Agent {
@@ -237,6 +385,21 @@ fn parse_args() -> Result<Command> {
         provider,
         base_url,
         providers_config,
+        response_cache,
+        scrub_secrets,
+        log_exchanges,
+        max_cost,
+        provider_timeout,
+        loop_budget,
+        dry_run,
+        snapshots,
+        track_files,
+        kubectl,
+        session_file,
+        #[cfg(feature = "sqlite-store")]
+        sqlite_store,
+        #[cfg(any(feature = "sqlite", feature = "postgres"))]
+        sql,
         #[cfg(feature = "wasm")]
         wasm_tools_dir,
         #[cfg(feature = "container")]
@@ -473,6 +636,7 @@ fn parse_audit_args(args: &[String]) -> Result<Command> {
             let mut decision: Option<String> = None;
             let mut user_id: Option<String> = None;
             let mut session_id: Option<String> = None;
+            let mut invocation_id: Option<String> = None;
             let mut limit: Option<i64> = None;
 
             let mut i = 1;
@@ -494,6 +658,10 @@ fn parse_audit_args(args: &[String]) -> Result<Command> {
                         i += 1;
                         session_id = args.get(i).cloned();
                     }
+                    "--invocation" => {
+                        i += 1;
+                        invocation_id = args.get(i).cloned();
+                    }
                     "--limit" => {
                         i += 1;
                         if let Some(v) = args.get(i) {
@@ -510,6 +678,7 @@ fn parse_audit_args(args: &[String]) -> Result<Command> {
                 decision,
                 user_id,
                 session_id,
+                invocation_id,
                 limit,
             }))
         }
@@ -540,25 +709,33 @@ async fn run_audit_command(sub: AuditSubcommand) -> Result<()> {
             decision,
             user_id,
             session_id,
+            invocation_id,
             limit,
         } => {
             let parsed_decision = decision
                 .as_deref()
-                .map(|s| AuditDecision::from_str(s))
+                .map(AuditDecision::from_str)
                 .transpose()
                 .context("invalid --decision value; use: allow, reject, escalate, approve, deny")?;
 
             let parsed_session = session_id
                 .as_deref()
-                .map(|s| Uuid::parse_str(s))
+                .map(Uuid::parse_str)
                 .transpose()
                 .context("invalid --session value; must be a UUID")?;
 
+            let parsed_invocation = invocation_id
+                .as_deref()
+                .map(Uuid::parse_str)
+                .transpose()
+                .context("invalid --invocation value; must be a UUID")?;
+
             let filter = AuditFilter {
                 tool: tool.clone(),
                 decision: parsed_decision,
                 user_id: user_id.clone(),
                 session_id: parsed_session,
+                invocation_id: parsed_invocation,
                 since: None,
                 limit,
             };
@@ -572,16 +749,19 @@ async fn run_audit_command(sub: AuditSubcommand) -> Result<()> {
                 println!("No audit events found.");
             } else {
                 println!(
-                    "{:<26}  {:<10}  {:<8}  {:<10}  {}",
-                    "timestamp", "tool", "decision", "tier", "action"
+                    "{:<26}  {:<36}  {:<10}  {:<8}  {:<10}  action",
+                    "timestamp", "invocation", "tool", "decision", "tier"
                 );
-                println!("{}", "-".repeat(80));
+                println!("{}", "-".repeat(118));
                 for ev in &events {
                     let ts = ev.created_at.format("%Y-%m-%d %H:%M:%S%.3f");
+                    let invocation = ev
+                        .invocation_id
+                        .map_or_else(|| "-".to_owned(), |id| id.to_string());
                     let tier = ev.tier.as_deref().unwrap_or("-");
                     let action = ev.action.as_deref().unwrap_or("-");
                     println!(
-                        "{ts:<26}  {:<10}  {:<8}  {:<10}  {}",
+                        "{ts:<26}  {invocation:<36}  {:<10}  {:<8}  {:<10}  {}",
                         ev.tool, ev.decision, tier, action
                     );
                 }
@@ -604,14 +784,11 @@ fn parse_cost_args(args: &[String]) -> Result<Command> {
             let mut days: u32 = 7;
             let mut i = 1;
             while i < args.len() {
-                match args[i].as_str() {
-                    "--days" => {
-                        i += 1;
-                        if let Some(v) = args.get(i) {
-                            days = v.parse().context("--days must be a positive number")?;
-                        }
+                if args[i].as_str() == "--days" {
+                    i += 1;
+                    if let Some(v) = args.get(i) {
+                        days = v.parse().context("--days must be a positive number")?;
                     }
-                    _ => {}
                 }
                 i += 1;
             }
@@ -887,12 +1064,26 @@ async fn run_pricing_command(sub: PricingSubcommand) -> Result<()> {
 
 // ─── Agent REPL ───────────────────────────────────────────────────────────────
 
+#[allow(clippy::too_many_arguments)]
 async fn run_agent(
     policy_path: PathBuf,
     model: String,
     provider_type: String,
     base_url: Option<String>,
     providers_config: Option<PathBuf>,
+    response_cache: Option<PathBuf>,
+    scrub_secrets: bool,
+    log_exchanges: bool,
+    max_cost: Option<f64>,
+    provider_timeout: Option<Duration>,
+    loop_budget: LoopBudget,
+    dry_run: Option<String>,
+    snapshots: bool,
+    track_files: bool,
+    kubectl: bool,
+    session_file: Option<PathBuf>,
+    #[cfg(feature = "sqlite-store")] sqlite_store: Option<PathBuf>,
+    #[cfg(any(feature = "sqlite", feature = "postgres"))] sql: bool,
     #[cfg(feature = "wasm")] wasm_tools_dir: Option<PathBuf>,
     #[cfg(feature = "container")] container_tools_dir: Option<PathBuf>,
     #[cfg(feature = "container")] sandbox_bash: bool,
@@ -906,21 +1097,22 @@ async fn run_agent(
     })?;
     info!(policy = %policy_path.display(), "policy loaded");
 
+    // Rates from the providers config; DB rows fill in the rest below.
+    let mut pricing_table = cherub::providers::pricing::PricingTable::new();
+
     // Create provider — from config file if --providers is set, otherwise from CLI flags.
     let provider: Box<dyn cherub::providers::Provider> = if let Some(ref config_path) =
         providers_config
     {
-        use cherub::providers::config::{ProvidersConfig, instantiate_named_provider};
+        use cherub::providers::config::ProvidersConfig;
 
         let config = ProvidersConfig::load(config_path)
             .map_err(|e| anyhow::anyhow!("failed to load providers config: {e}"))?;
         info!(config = %config_path.display(), "providers config loaded");
+        pricing_table.clone_from(&config.pricing);
 
-        // Validate that "default" exists.
-        if !config.providers.contains_key("default") {
-            bail!("providers config must contain a [providers.default] entry");
-        }
-        instantiate_named_provider(&config, "default", &mut Vec::new())
+        config
+            .instantiate_default()
             .map_err(|e| anyhow::anyhow!("failed to create default provider: {e}"))?
     } else {
         match provider_type.as_str() {
@@ -940,6 +1132,43 @@ async fn run_agent(
                 }
                 Box::new(p)
             }
+            "ollama" => {
+                let mut p = OllamaProvider::new(&model, DEFAULT_MAX_TOKENS)
+                    .map_err(|e| anyhow::anyhow!("failed to create Ollama provider: {e}"))?;
+                if let Some(url) = base_url {
+                    p = p.with_base_url(url);
+                }
+                Box::new(p)
+            }
+            "bedrock" => {
+                let credentials = AwsCredentials::from_env().context(
+                    "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set for bedrock",
+                )?;
+                let region = sigv4::region_from_env().context("AWS_REGION not set")?;
+                let mut p = BedrockProvider::new(credentials, &region, &model, DEFAULT_MAX_TOKENS)
+                    .map_err(|e| anyhow::anyhow!("failed to create Bedrock provider: {e}"))?;
+                if let Some(url) = base_url {
+                    p = p.with_endpoint(url);
+                }
+                Box::new(p)
+            }
+            "openrouter" => {
+                let api_key_raw = std::env::var("OPENROUTER_API_KEY")
+                    .context("OPENROUTER_API_KEY environment variable not set")?;
+                if api_key_raw.is_empty() {
+                    bail!("OPENROUTER_API_KEY is empty");
+                }
+                let mut p = OpenRouterProvider::new(
+                    SecretString::from(api_key_raw),
+                    &model,
+                    DEFAULT_MAX_TOKENS,
+                )
+                .map_err(|e| anyhow::anyhow!("failed to create OpenRouter provider: {e}"))?;
+                if let Some(url) = base_url {
+                    p = p.with_base_url(url);
+                }
+                Box::new(p)
+            }
             "anthropic" => {
                 let api_key_raw = std::env::var("ANTHROPIC_API_KEY")
                     .context("ANTHROPIC_API_KEY environment variable not set")?;
@@ -947,14 +1176,47 @@ async fn run_agent(
                     bail!("ANTHROPIC_API_KEY is empty");
                 }
                 let api_key = SecretString::from(api_key_raw);
-                Box::new(
-                    AnthropicProvider::new(api_key, &model, DEFAULT_MAX_TOKENS)
-                        .map_err(|e| anyhow::anyhow!("failed to create Anthropic provider: {e}"))?,
+                let mut p = AnthropicProvider::new(api_key, &model, DEFAULT_MAX_TOKENS)
+                    .map_err(|e| anyhow::anyhow!("failed to create Anthropic provider: {e}"))?;
+                if let Some(url) = base_url {
+                    p = p.with_base_url(&url);
+                }
+                Box::new(p)
+            }
+            other => {
+                bail!(
+                    "unknown provider '{other}'. Available: anthropic, openai, ollama, bedrock, openrouter"
                 )
             }
-            other => bail!("unknown provider '{other}'. Available: anthropic, openai"),
         }
     };
+    let provider: Box<dyn cherub::providers::Provider> = match response_cache {
+        Some(dir) => {
+            info!(dir = %dir.display(), "response cache enabled");
+            Box::new(CachingProvider::new(provider, CacheBackend::disk(dir)))
+        }
+        None => provider,
+    };
+    // Outermost, so the cache is keyed on the scrubbed request too.
+    let provider: Box<dyn cherub::providers::Provider> = if scrub_secrets || log_exchanges {
+        let mut layered = LayeredProvider::new(provider);
+        if scrub_secrets {
+            layered = layered.with_layer(ProviderLayer::ScrubSecrets(SecretScrubber::from_env()));
+        }
+        if log_exchanges {
+            layered = layered.with_layer(ProviderLayer::Log);
+        }
+        Box::new(layered)
+    } else {
+        provider
+    };
+
+    // A bad key, an unknown model, or a local server that is down fails here,
+    // not on the first turn.
+    provider
+        .health_check()
+        .await
+        .map_err(|e| anyhow::anyhow!("provider health check failed: {e}"))?;
 
     let cwd = std::env::current_dir()
         .map(|p| p.display().to_string())
@@ -1035,6 +1297,24 @@ async fn run_agent(
         ToolRegistry::new()
     };
 
+    // PowerShell is the native shell on Windows hosts.
+    let registry = if cfg!(windows) && !skip_builtin_bash {
+        registry.with_powershell()
+    } else {
+        registry
+    };
+
+    // Kubectl tool; the policy's patterns name the namespaces it may touch.
+    let registry = if kubectl {
+        registry.with_kubectl()
+    } else {
+        registry
+    };
+
+    // SQL tool; the policy's [tools.sql.databases] names what it may reach.
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    let registry = if sql { registry.with_sql() } else { registry };
+
     // Attach credential broker + HTTP tool if credentials feature is active.
     #[cfg(feature = "credentials")]
     let registry = {
@@ -1208,10 +1488,40 @@ async fn run_agent(
         }
     };
 
-    let system_prompt = build_system_prompt(&cwd);
+    // Simulate Act/Commit executions when trying out a policy.
+    let registry = match dry_run {
+        Some(output) => {
+            info!("dry run enabled: act and commit tool calls are simulated");
+            registry.with_dry_run(output)
+        }
+        None => registry,
+    };
+
+    // Record the workspace before act/commit calls so `/rollback` can undo them.
+    let registry = if snapshots {
+        let store = std::env::temp_dir().join(format!("cherub-snapshots-{}", std::process::id()));
+        info!(store = %store.display(), "workspace snapshots enabled");
+        registry.with_snapshots(store)
+    } else {
+        registry
+    };
+
+    // Report what each tool call changed on disk, to the model and the user.
+    let registry = if track_files {
+        info!("file change tracking enabled");
+        registry.with_artifact_tracking()
+    } else {
+        registry
+    };
+
+    let system_prompt = PromptBuilder::new(&cwd)
+        .with_tools(registry.definitions())
+        .with_policy(&policy)
+        .build();
 
     let approval_gate = CliApprovalGate::new();
     let output = StdoutSink;
+    let agent_model_name = provider.model_name().to_owned();
     let mut agent = AgentLoop::new(
         policy,
         provider,
@@ -1257,25 +1567,33 @@ async fn run_agent(
             agent.with_cost_tracking(cost_store);
 
             let pricing_store = PgPricingStore::new(pool.clone());
-            let pricing_table: cherub::providers::pricing::PricingTable = pricing_store
-                .list()
-                .await
-                .map(|entries| {
-                    entries
-                        .into_iter()
-                        .map(|e| (e.model_pattern.clone(), e.to_model_pricing()))
-                        .collect()
-                })
-                .unwrap_or_default();
-            let count = pricing_table.len();
-            agent.with_pricing_table(pricing_table);
-
-            if count > 0 {
-                info!(entries = count, "pricing table loaded");
+            for entry in pricing_store.list().await.unwrap_or_default() {
+                pricing_table
+                    .entry(entry.model_pattern.clone())
+                    .or_insert_with(|| entry.to_model_pricing());
             }
             info!("cost tracking enabled");
         }
     }
+    if !pricing_table.is_empty() {
+        info!(entries = pricing_table.len(), "pricing table loaded");
+    }
+    if let Some(ceiling) = max_cost {
+        if cherub::providers::pricing::lookup_pricing(&pricing_table, agent_model_name.as_str())
+            .is_none()
+        {
+            tracing::warn!(
+                model = %agent_model_name,
+                "--max-cost set but the model has no pricing entry; its calls count as $0"
+            );
+        }
+        agent.with_cost_ceiling(ceiling);
+    }
+    agent.with_pricing_table(pricing_table);
+    if let Some(timeout) = provider_timeout {
+        agent.with_provider_timeout(timeout);
+    }
+    agent.with_loop_budget(loop_budget);
 
     // Attach session persistence if available.
     #[cfg(feature = "sessions")]
@@ -1302,11 +1620,62 @@ async fn run_agent(
         }
     }
 
+    // SQLite store: session history + audit log in one local file.
+    #[cfg(feature = "sqlite-store")]
+    if let Some(ref path) = sqlite_store {
+        use cherub::storage::sqlite_store::SqliteStore;
+        use std::sync::Arc;
+
+        let store = SqliteStore::open(path)
+            .await
+            .with_context(|| format!("cannot open SQLite store {}", path.display()))?;
+        agent.with_audit_log(Arc::new(store.clone()));
+        agent
+            .with_persistence(Box::new(store), "cli", "default")
+            .await
+            .context("failed to attach SQLite session store")?;
+        let msg_count = agent.session_messages().len();
+        if msg_count > 0 {
+            println!(
+                "Resumed session {} ({msg_count} messages).",
+                agent.session_id()
+            );
+        } else {
+            println!("New session {}.", agent.session_id());
+        }
+        info!(path = %path.display(), "SQLite session store and audit log enabled");
+    }
+
+    // A session file takes over from the database session (crash recovery).
+    if let Some(ref path) = session_file {
+        agent
+            .with_session_file(path)
+            .await
+            .with_context(|| format!("cannot use session file {}", path.display()))?;
+        let msg_count = agent.session_messages().len();
+        if msg_count > 0 {
+            println!(
+                "Resumed session {} from {} ({msg_count} messages).",
+                agent.session_id(),
+                path.display()
+            );
+        } else {
+            println!(
+                "New session {}, saved to {}.",
+                agent.session_id(),
+                path.display()
+            );
+        }
+    }
+
     info!(model = %model, user_id = %user_id, "cherub started");
     println!("cherub: secure agent runtime (model: {model})");
-    println!("Type a message, Ctrl-D to exit, Ctrl-C to cancel input.\n");
+    println!("Type a message, Ctrl-D to exit, Ctrl-C to cancel input or a running turn.");
+    println!("/attach <file> adds an image or PDF to your next message.\n");
 
     let mut rl = DefaultEditor::new().context("failed to init readline")?;
+    // Images and PDFs from `/attach`, sent with the next message.
+    let mut attachments: Vec<UserContent> = Vec::new();
 
     loop {
         match rl.readline("you> ") {
@@ -1317,7 +1686,41 @@ async fn run_agent(
                 }
                 let _ = rl.add_history_entry(line);
 
-                if let Err(e) = agent.run_turn_text(line).await {
+                if line == "/rollback" {
+                    match agent.rollback().await {
+                        Ok(Some(snapshot)) => println!(
+                            "Workspace restored to before {} call {}.",
+                            snapshot.tool, snapshot.invocation_id
+                        ),
+                        Ok(None) => println!("Nothing to roll back (start with --snapshots)."),
+                        Err(e) => eprintln!("[error] {e}"),
+                    }
+                    continue;
+                }
+
+                if let Some(path) = line.strip_prefix("/attach ") {
+                    match UserContent::from_path(Path::new(path.trim())) {
+                        Ok(content) => {
+                            attachments.push(content);
+                            println!("Attached {} (sent with your next message).", path.trim());
+                        }
+                        Err(e) => eprintln!("[error] {e}"),
+                    }
+                    continue;
+                }
+
+                let cancel = CancellationToken::new();
+                agent.with_cancellation(cancel.clone());
+                *running_turn() = Some(cancel);
+                let result = if attachments.is_empty() {
+                    agent.run_turn_text(line).await
+                } else {
+                    let mut content = std::mem::take(&mut attachments);
+                    content.push(UserContent::Text(line.to_owned()));
+                    agent.run_turn(content).await
+                };
+                running_turn().take();
+                if let Err(e) = result {
                     eprintln!("[error] {e}");
                 }
                 println!();
@@ -1338,6 +1741,24 @@ async fn run_agent(
     Ok(())
 }
 
+/// The turn a Ctrl-C cancels, while one is running.
+static RUNNING_TURN: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
+fn running_turn() -> std::sync::MutexGuard<'static, Option<CancellationToken>> {
+    RUNNING_TURN.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Ctrl-C during a turn cancels the turn instead of exiting.
+fn cancel_running_turn() -> bool {
+    match running_turn().take() {
+        Some(cancel) => {
+            cancel.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
 // ─── Entry point ─────────────────────────────────────────────────────────────
 
 #[tokio::main]
@@ -1355,6 +1776,21 @@ async fn main() -> Result<()> {
             provider,
             base_url,
             providers_config,
+            response_cache,
+            scrub_secrets,
+            log_exchanges,
+            max_cost,
+            provider_timeout,
+            loop_budget,
+            dry_run,
+            snapshots,
+            track_files,
+            kubectl,
+            session_file,
+            #[cfg(feature = "sqlite-store")]
+            sqlite_store,
+            #[cfg(any(feature = "sqlite", feature = "postgres"))]
+            sql,
             #[cfg(feature = "wasm")]
             wasm_tools_dir,
             #[cfg(feature = "container")]
@@ -1364,12 +1800,29 @@ async fn main() -> Result<()> {
             #[cfg(feature = "mcp")]
             mcp_config,
         } => {
-            run_agent(
+            // Interrupting the agent must not orphan the commands it spawned.
+            tokio::spawn(process_group::reap_on_shutdown(cancel_running_turn));
+            let result = run_agent(
                 policy_path,
                 model,
                 provider,
                 base_url,
                 providers_config,
+                response_cache,
+                scrub_secrets,
+                log_exchanges,
+                max_cost,
+                provider_timeout,
+                loop_budget,
+                dry_run,
+                snapshots,
+                track_files,
+                kubectl,
+                session_file,
+                #[cfg(feature = "sqlite-store")]
+                sqlite_store,
+                #[cfg(any(feature = "sqlite", feature = "postgres"))]
+                sql,
                 #[cfg(feature = "wasm")]
                 wasm_tools_dir,
                 #[cfg(feature = "container")]
@@ -1379,7 +1832,9 @@ async fn main() -> Result<()> {
                 #[cfg(feature = "mcp")]
                 mcp_config,
             )
-            .await
+            .await;
+            process_group::kill_all();
+            result
         }
         #[cfg(feature = "credentials")]
         Command::Credential(sub) => run_credential_command(sub).await,
@@ -1,53 +1,242 @@
-use std::time::Duration;
-
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use secrecy::{ExposeSecret, SecretString};
-use tracing::{Instrument, info, info_span, warn};
+use tracing::{Instrument, info_span, warn};
 
 use async_trait::async_trait;
 
+use super::batch::{BatchItem, BatchJob, BatchResult, BatchState};
+use super::http::{self, HttpConfig};
+use super::sse::SseDecoder;
 use super::wire::{self, RequestBody};
-use super::{ApiUsage, Message, Provider, ToolDefinition};
+use super::{ApiUsage, CompletionRequest, Message, MessageDelta, Provider};
 use crate::error::CherubError;
-use crate::retry::{RetryConfig, RetryVerdict, classify_status, compute_delay};
+use crate::retry::{RetryConfig, send_with_retry};
 
 const API_URL: &str = "https://api.anthropic.com/v1/messages";
 const API_VERSION: &str = "2023-06-01";
 
-/// Anthropic Messages API provider. Non-streaming for M2.
+/// Anthropic Messages API provider. `complete` is non-streaming;
+/// `complete_streaming` parses the SSE event stream.
+///
+/// Rate limiting (429), overload (529), and auth failures surface as
+/// distinctly worded `CherubError::Provider` errors after retries.
 pub struct AnthropicProvider {
     client: Client,
     api_key: SecretString,
     pub(crate) model: String,
     pub(crate) max_tokens: u32,
+    /// Sampling temperature, 0.0–1.0. `None` leaves the API default (1.0).
+    pub(crate) temperature: Option<f32>,
     api_url: String,
     retry_config: RetryConfig,
 }
 
 impl AnthropicProvider {
     pub fn new(api_key: SecretString, model: &str, max_tokens: u32) -> Result<Self, CherubError> {
-        let client = Client::builder()
-            .connect_timeout(Duration::from_secs(10))
-            .read_timeout(Duration::from_secs(30))
-            .timeout(Duration::from_secs(120))
-            .build()
-            .map_err(|e| CherubError::Provider(e.to_string()))?;
+        let client = http::api_client(&HttpConfig::default())?;
 
         Ok(Self {
             client,
             api_key,
             model: model.to_owned(),
             max_tokens,
+            temperature: None,
             api_url: API_URL.to_owned(),
             retry_config: RetryConfig::new(),
         })
     }
 
+    /// Set the sampling temperature (0.0–1.0; validated by the providers config).
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Override the retry behavior (attempts, backoff bounds).
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry_config = config;
+        self
+    }
+
+    /// Rebuild the HTTP client with proxy, CA bundle, and header settings.
+    pub fn with_http_config(mut self, config: &HttpConfig) -> Result<Self, CherubError> {
+        self.client = http::api_client(config)?;
+        Ok(self)
+    }
+
+    /// Send to a gateway or proxy instead of the Anthropic API. `url` is the
+    /// base the SDKs use, e.g. `https://gateway.example.com`; requests go to
+    /// `{url}/v1/messages`.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.api_url = format!("{}/v1/messages", url.trim_end_matches('/'));
+        self
+    }
+
     /// Override the API URL. Intended for testing with wiremock.
     pub fn with_url(mut self, url: String) -> Self {
         self.api_url = url;
         self
     }
+
+    /// The models endpoint beside the messages endpoint.
+    fn models_url(&self) -> String {
+        match self.api_url.strip_suffix("/messages") {
+            Some(base) => format!("{base}/models"),
+            None => format!("{}/models", self.api_url.trim_end_matches('/')),
+        }
+    }
+}
+
+impl AnthropicProvider {
+    fn wire_request<'a>(
+        &'a self,
+        request: &CompletionRequest<'a>,
+        stream: bool,
+    ) -> RequestBody<'a> {
+        RequestBody {
+            model: request.model.unwrap_or(&self.model),
+            max_tokens: request.max_tokens.unwrap_or(self.max_tokens),
+            temperature: request.temperature.or(self.temperature),
+            top_p: request.top_p,
+            stop_sequences: request.stop_sequences,
+            system: request.system,
+            messages: wire::messages_to_wire(request.messages),
+            tools: request.tools.iter().map(wire::WireTool::from).collect(),
+            stream,
+        }
+    }
+
+    fn request_body(
+        &self,
+        request: &CompletionRequest<'_>,
+        stream: bool,
+    ) -> Result<Vec<u8>, CherubError> {
+        serde_json::to_vec(&self.wire_request(request, stream))
+            .map_err(|e| CherubError::Provider(format!("JSON serialize error: {e}")))
+    }
+
+    /// Send a request through the shared retry layer, wording failures in
+    /// Anthropic's terms.
+    async fn send_with(
+        &self,
+        build: impl FnMut() -> RequestBuilder,
+    ) -> Result<reqwest::Response, CherubError> {
+        match send_with_retry(&self.retry_config, build).await {
+            Ok(response) => Ok(response),
+            Err(e) => Err(e
+                .into_error(|status, _, body| api_error(status, body))
+                .await),
+        }
+    }
+
+    /// Add the API key and version headers.
+    fn authorized(&self, builder: RequestBuilder) -> RequestBuilder {
+        // NEVER log the API key — SecretString redacts on Debug, but we never format it either.
+        builder
+            .header("x-api-key", self.api_key.expose_secret())
+            .header("anthropic-version", API_VERSION)
+    }
+
+    /// POST a request body through the shared retry layer. Returns the
+    /// successful response, body unread.
+    async fn send(&self, json_body: Vec<u8>) -> Result<reqwest::Response, CherubError> {
+        self.send_with(|| {
+            self.authorized(self.client.post(&self.api_url))
+                .header("content-type", "application/json")
+                .body(json_body.clone())
+        })
+        .await
+    }
+
+    // --- Message Batches (see `batch`) ---
+
+    fn batches_url(&self) -> String {
+        format!("{}/batches", self.api_url)
+    }
+
+    pub(crate) async fn submit_batch(
+        &self,
+        items: &[BatchItem<'_>],
+    ) -> Result<BatchJob, CherubError> {
+        let body = wire::BatchCreate {
+            requests: items
+                .iter()
+                .map(|item| wire::BatchEntry {
+                    custom_id: item.custom_id,
+                    params: self.wire_request(&item.request, false),
+                })
+                .collect(),
+        };
+        let json_body = serde_json::to_vec(&body)
+            .map_err(|e| CherubError::Provider(format!("JSON serialize error: {e}")))?;
+        let url = self.batches_url();
+        let response = self
+            .send_with(|| {
+                self.authorized(self.client.post(&url))
+                    .header("content-type", "application/json")
+                    .body(json_body.clone())
+            })
+            .await?;
+        batch_job(response).await
+    }
+
+    pub(crate) async fn batch_status(&self, id: &str) -> Result<BatchJob, CherubError> {
+        let url = format!("{}/{id}", self.batches_url());
+        let response = self
+            .send_with(|| self.authorized(self.client.get(&url)))
+            .await?;
+        batch_job(response).await
+    }
+
+    pub(crate) async fn cancel_batch(&self, id: &str) -> Result<BatchJob, CherubError> {
+        let url = format!("{}/{id}/cancel", self.batches_url());
+        let response = self
+            .send_with(|| self.authorized(self.client.post(&url)))
+            .await?;
+        batch_job(response).await
+    }
+
+    pub(crate) async fn batch_results(
+        &self,
+        job: &BatchJob,
+    ) -> Result<Vec<BatchResult>, CherubError> {
+        let Some(url) = job.result_locations.first() else {
+            return Err(CherubError::Provider(format!(
+                "batch {} has no results URL",
+                job.id
+            )));
+        };
+        let body = self
+            .send_with(|| self.authorized(self.client.get(url)))
+            .await?
+            .text()
+            .await
+            .map_err(|e| CherubError::Provider(format!("failed to read batch results: {e}")))?;
+        body.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let line: wire::BatchResultLine = serde_json::from_str(line).map_err(|e| {
+                    CherubError::Provider(format!("invalid batch result line: {e}"))
+                })?;
+                let outcome = match line.result {
+                    wire::BatchOutcome::Succeeded { message } => {
+                        Ok(wire::response_to_message(message))
+                    }
+                    wire::BatchOutcome::Errored { error } => Err(format!(
+                        "{}: {}",
+                        error_label(None, Some(&error.error.kind)),
+                        error.error.message
+                    )),
+                    wire::BatchOutcome::Canceled => Err("canceled".to_owned()),
+                    wire::BatchOutcome::Expired => Err("expired".to_owned()),
+                };
+                Ok(BatchResult {
+                    custom_id: line.custom_id,
+                    outcome,
+                })
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -56,113 +245,123 @@ impl Provider for AnthropicProvider {
     /// Retries on transient errors (429, 5xx) with exponential backoff.
     async fn complete(
         &self,
-        system: &str,
-        messages: &[Message],
-        tools: &[ToolDefinition],
+        request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError> {
         // Use Instrument instead of entered() — EnteredSpan is !Send, which
         // prevents the future from being Send across await points.
         async {
-            let wire_messages = wire::messages_to_wire(messages);
-            let wire_tools: Vec<_> = tools.iter().map(wire::WireTool::from).collect();
-
-            let body = RequestBody {
-                model: &self.model,
-                max_tokens: self.max_tokens,
-                system,
-                messages: wire_messages,
-                tools: wire_tools,
-                stream: false,
-            };
-
-            let json_body = serde_json::to_vec(&body)
-                .map_err(|e| CherubError::Provider(format!("JSON serialize error: {e}")))?;
-
-            for attempt in 0..=self.retry_config.max_retries {
-                // NEVER log the API key — SecretString redacts on Debug, but we never format it either.
-                let result = self
-                    .client
-                    .post(&self.api_url)
-                    .header("x-api-key", self.api_key.expose_secret())
-                    .header("anthropic-version", API_VERSION)
-                    .header("content-type", "application/json")
-                    .body(json_body.clone())
-                    .send()
-                    .await;
-
-                let response = match result {
-                    Ok(r) => r,
-                    Err(e)
-                        if (e.is_connect() || e.is_timeout())
-                            && attempt < self.retry_config.max_retries =>
-                    {
-                        let delay = compute_delay(&self.retry_config, attempt);
-                        warn!(
-                            error = %e,
-                            attempt,
-                            delay_ms = delay.as_millis() as u64,
-                            "retrying API call (connection/timeout error)"
-                        );
-                        tokio::time::sleep(delay).await;
-                        continue;
-                    }
-                    Err(e) => {
-                        let retries = attempt;
-                        return Err(CherubError::Provider(format!(
-                            "connection error: {e} (after {retries} retries)"
-                        )));
-                    }
-                };
-
-                let status = response.status().as_u16();
-                info!(status);
-
-                match classify_status(status) {
-                    RetryVerdict::Success => {
-                        let resp: wire::ResponseBody = response
-                            .json()
-                            .await
-                            .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))?;
+            let json_body = self.request_body(request, false)?;
+            let resp: wire::ResponseBody = self
+                .send(json_body)
+                .await?
+                .json()
+                .await
+                .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))?;
+            Ok(wire::response_to_message(resp))
+        }
+        .instrument(info_span!("api_call", model = %self.model))
+        .await
+    }
 
-                        return Ok(wire::response_to_message(resp));
-                    }
-                    RetryVerdict::Transient(_) if attempt < self.retry_config.max_retries => {
-                        // Parse Retry-After header (Anthropic sends seconds as integer).
-                        let retry_after = response
-                            .headers()
-                            .get("retry-after")
-                            .and_then(|v| v.to_str().ok())
-                            .and_then(|s| s.parse::<u64>().ok())
-                            .map(Duration::from_secs);
-
-                        let delay = retry_after
-                            .unwrap_or_else(|| compute_delay(&self.retry_config, attempt));
-                        warn!(
-                            status,
-                            attempt,
-                            delay_ms = delay.as_millis() as u64,
-                            "retrying API call"
-                        );
-                        tokio::time::sleep(delay).await;
-                    }
-                    RetryVerdict::Transient(_) | RetryVerdict::Permanent => {
-                        let body_text = response.text().await.unwrap_or_default();
-                        let retries = attempt;
-                        warn!(status, "API error response");
+    /// Stream a completion over SSE. Establishing the stream retries like
+    /// `complete`; an error once events are flowing (an `overloaded_error`
+    /// event, a dropped connection) fails the call without retrying.
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest<'_>,
+        on_delta: &mut (dyn FnMut(MessageDelta) + Send),
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        async {
+            let json_body = self.request_body(request, true)?;
+            let mut response = self.send(json_body).await?;
+            let mut decoder = SseDecoder::default();
+            let mut accumulator = wire::StreamAccumulator::default();
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| CherubError::Provider(format!("stream read error: {e}")))?
+            {
+                for event in decoder.push(&chunk) {
+                    let event: wire::StreamEvent = serde_json::from_str(&event.data)
+                        .map_err(|e| CherubError::Provider(format!("stream parse error: {e}")))?;
+                    if let wire::StreamEvent::Error { error } = event {
+                        warn!(kind = %error.kind, "API error event mid-stream");
                         return Err(CherubError::Provider(format!(
-                            "API error {status}: {body_text} (after {retries} retries)"
+                            "{} mid-stream: {}",
+                            error_label(None, Some(&error.kind)),
+                            error.message
                         )));
                     }
+                    if let Some(delta) = accumulator.apply(event) {
+                        on_delta(delta);
+                    }
                 }
             }
-
-            // Unreachable: the loop always returns or continues.
-            unreachable!("retry loop exhausted without returning")
+            let resp = accumulator
+                .finish()
+                .map_err(|e| CherubError::Provider(format!("stream error: {e}")))?;
+            Ok(wire::response_to_message(resp))
         }
-        .instrument(info_span!("api_call", model = %self.model))
+        .instrument(info_span!("api_stream", model = %self.model))
         .await
     }
 
+    /// Looks the model up directly, which also resolves aliases such as
+    /// `claude-sonnet-4-0` that the model list does not include.
+    async fn health_check(&self) -> Result<(), CherubError> {
+        let response = self
+            .authorized(
+                self.client
+                    .get(format!("{}/{}", self.models_url(), self.model)),
+            )
+            .timeout(http::HEALTH_CHECK_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| CherubError::Provider(format!("Anthropic API unreachable: {e}")))?;
+        match response.status().as_u16() {
+            200..=299 => Ok(()),
+            404 => Err(CherubError::Provider(format!(
+                "model '{}' not found",
+                self.model
+            ))),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(CherubError::Provider(api_error(status, &body)))
+            }
+        }
+    }
+
+    async fn list_models(&self) -> Result<Option<Vec<String>>, CherubError> {
+        let mut models = Vec::new();
+        let mut after_id = None;
+        loop {
+            let mut url = format!("{}?limit=1000", self.models_url());
+            if let Some(ref id) = after_id {
+                url.push_str(&format!("&after_id={id}"));
+            }
+            let response = self
+                .authorized(self.client.get(url))
+                .timeout(http::HEALTH_CHECK_TIMEOUT)
+                .send()
+                .await
+                .map_err(|e| CherubError::Provider(format!("Anthropic API unreachable: {e}")))?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(CherubError::Provider(api_error(status.as_u16(), &body)));
+            }
+            let page: wire::ModelPage = response
+                .json()
+                .await
+                .map_err(|e| CherubError::Provider(format!("invalid model list: {e}")))?;
+            models.extend(page.data.into_iter().map(|m| m.id));
+            match page.last_id {
+                Some(id) if page.has_more => after_id = Some(id),
+                _ => return Ok(Some(models)),
+            }
+        }
+    }
+
     fn model_name(&self) -> &str {
         &self.model
     }
@@ -172,15 +371,58 @@ impl Provider for AnthropicProvider {
     }
 }
 
+/// Parse a Message Batch object.
+async fn batch_job(response: reqwest::Response) -> Result<BatchJob, CherubError> {
+    let batch: wire::MessageBatch = response
+        .json()
+        .await
+        .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))?;
+    let counts = batch.request_counts;
+    Ok(BatchJob {
+        id: batch.id,
+        state: match batch.processing_status.as_str() {
+            "ended" => BatchState::Ended,
+            _ => BatchState::InProgress,
+        },
+        succeeded: counts.succeeded,
+        failed: counts.errored + counts.canceled + counts.expired,
+        pending: counts.processing,
+        result_locations: batch.results_url.into_iter().collect(),
+    })
+}
+
+/// Map a non-2xx response to a provider error, naming rate limiting,
+/// overload, and auth failures. Anthropic's error envelope supplies the
+/// message; any other body (a proxy's HTML page, say) is passed through.
+fn api_error(status: u16, body: &str) -> String {
+    let (kind, message) = match serde_json::from_str::<wire::ErrorBody>(body) {
+        Ok(envelope) => (Some(envelope.error.kind), envelope.error.message),
+        Err(_) => (None, body.to_owned()),
+    };
+    let label = error_label(Some(status), kind.as_deref());
+    format!("{label} ({status}): {message}")
+}
+
+/// Name an error by HTTP status or, failing that, the envelope's error type.
+fn error_label(status: Option<u16>, kind: Option<&str>) -> &'static str {
+    match (status, kind) {
+        (Some(429), _) | (_, Some("rate_limit_error")) => "rate limited",
+        (Some(529), _) | (_, Some("overloaded_error")) => "overloaded",
+        (Some(401), _) | (_, Some("authentication_error")) => "authentication failed",
+        _ => "API error",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::ToolDefinition;
     use serde_json::json;
 
     #[test]
     fn request_body_structure() {
         let messages = vec![Message::user_text("hello")];
-        let tools = vec![ToolDefinition {
+        let tools = [ToolDefinition {
             name: "bash".to_owned(),
             description: "Execute bash commands".to_owned(),
             input_schema: json!({
@@ -198,6 +440,9 @@ mod tests {
         let body = RequestBody {
             model: "claude-sonnet-4-20250514",
             max_tokens: 4096,
+            temperature: Some(0.2),
+            top_p: None,
+            stop_sequences: &[],
             system: "You are helpful.",
             messages: wire_messages,
             tools: wire_tools,
@@ -211,5 +456,54 @@ mod tests {
         assert_eq!(json["stream"], false);
         assert!(json["tools"].is_array());
         assert_eq!(json["tools"][0]["name"], "bash");
+        assert!((json["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn request_overrides_provider_settings() {
+        let provider = AnthropicProvider::new(SecretString::from("k"), "claude-a", 4096)
+            .unwrap()
+            .with_temperature(0.7);
+        let messages = vec![Message::user_text("hello")];
+        let stop = ["</answer>".to_owned()];
+
+        let defaults = CompletionRequest::new("sys", &messages, &[]);
+        let json: serde_json::Value =
+            serde_json::from_slice(&provider.request_body(&defaults, false).unwrap()).unwrap();
+        assert_eq!(json["model"], "claude-a");
+        assert_eq!(json["max_tokens"], 4096);
+        assert!((json["temperature"].as_f64().unwrap() - 0.7).abs() < 1e-6);
+        assert!(json.get("top_p").is_none());
+        assert!(json.get("stop_sequences").is_none());
+
+        let overridden = defaults
+            .with_model("claude-b")
+            .with_max_tokens(256)
+            .with_temperature(0.0)
+            .with_top_p(0.5)
+            .with_stop_sequences(&stop);
+        let json: serde_json::Value =
+            serde_json::from_slice(&provider.request_body(&overridden, false).unwrap()).unwrap();
+        assert_eq!(json["model"], "claude-b");
+        assert_eq!(json["max_tokens"], 256);
+        assert_eq!(json["temperature"], 0.0);
+        assert_eq!(json["top_p"], 0.5);
+        assert_eq!(json["stop_sequences"], json!(["</answer>"]));
+    }
+
+    #[test]
+    fn api_errors_name_rate_limit_and_overload() {
+        let overloaded =
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        assert_eq!(api_error(529, overloaded), "overloaded (529): Overloaded");
+        let limited =
+            r#"{"type":"error","error":{"type":"rate_limit_error","message":"slow down"}}"#;
+        assert_eq!(api_error(429, limited), "rate limited (429): slow down");
+        // The envelope's type wins when a proxy rewrites the status.
+        assert_eq!(api_error(503, overloaded), "overloaded (503): Overloaded");
+        assert_eq!(
+            api_error(400, "bad request"),
+            "API error (400): bad request"
+        );
     }
 }
@@ -0,0 +1,143 @@
+//! Batch completion APIs, for offline workloads such as re-running a dry-run
+//! corpus through a model.
+//!
+//! Anthropic's Message Batches and OpenAI's Batch API both accept many
+//! requests at once, process them asynchronously (within 24 hours) at half
+//! the price, and return results keyed by a caller-chosen `custom_id`.
+//! [`BatchProvider`] submits a batch, polls its status, and retrieves the
+//! results as `Message`s, whichever API is behind it. Jobs outlive the
+//! process: keep the batch ID and call [`BatchProvider::status`] later.
+
+use std::time::Duration;
+
+use tracing::info;
+
+use super::anthropic::AnthropicProvider;
+use super::openai::OpenAiProvider;
+use super::{ApiUsage, CompletionRequest, Message};
+use crate::error::CherubError;
+
+/// One request in a batch. `custom_id` must be unique within the batch
+/// (Anthropic: 1–64 characters of `[a-zA-Z0-9_-]`).
+#[derive(Debug, Clone, Copy)]
+pub struct BatchItem<'a> {
+    pub custom_id: &'a str,
+    pub request: CompletionRequest<'a>,
+}
+
+/// Where a batch is in its lifecycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchState {
+    /// Validating, processing, or cancelling.
+    InProgress,
+    /// Done (completed, cancelled, or expired); results are available.
+    Ended,
+    /// Rejected as a whole (OpenAI: the input file failed validation).
+    Failed(String),
+}
+
+/// A submitted batch, as of its last status check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchJob {
+    pub id: String,
+    pub state: BatchState,
+    /// Requests that have finished successfully.
+    pub succeeded: u32,
+    /// Requests that errored, were cancelled, or expired.
+    pub failed: u32,
+    /// Requests still waiting to be processed.
+    pub pending: u32,
+    /// Where the results are: Anthropic's results URL, or OpenAI's output
+    /// and error file IDs.
+    pub(crate) result_locations: Vec<String>,
+}
+
+/// The outcome of one batch request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResult {
+    pub custom_id: String,
+    /// The response, or why this request produced none.
+    pub outcome: Result<(Message, Option<ApiUsage>), String>,
+}
+
+/// A provider whose batch API is supported.
+pub enum BatchProvider {
+    Anthropic(AnthropicProvider),
+    OpenAi(OpenAiProvider),
+}
+
+impl BatchProvider {
+    /// Submit `items` as one batch.
+    pub async fn submit(&self, items: &[BatchItem<'_>]) -> Result<BatchJob, CherubError> {
+        if items.is_empty() {
+            return Err(CherubError::Provider(
+                "a batch needs at least one request".into(),
+            ));
+        }
+        let job = match self {
+            Self::Anthropic(provider) => provider.submit_batch(items).await?,
+            Self::OpenAi(provider) => provider.submit_batch(items).await?,
+        };
+        info!(batch = %job.id, requests = items.len(), "batch submitted");
+        Ok(job)
+    }
+
+    /// The batch's current status.
+    pub async fn status(&self, id: &str) -> Result<BatchJob, CherubError> {
+        match self {
+            Self::Anthropic(provider) => provider.batch_status(id).await,
+            Self::OpenAi(provider) => provider.batch_status(id).await,
+        }
+    }
+
+    /// Poll every `interval` until the batch is no longer in progress.
+    pub async fn wait(&self, id: &str, interval: Duration) -> Result<BatchJob, CherubError> {
+        loop {
+            let job = self.status(id).await?;
+            if job.state != BatchState::InProgress {
+                return Ok(job);
+            }
+            info!(
+                batch = %job.id,
+                succeeded = job.succeeded,
+                failed = job.failed,
+                pending = job.pending,
+                "batch in progress"
+            );
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Ask the API to stop processing the batch. Requests already done keep
+    /// their results; the batch ends once cancellation completes.
+    pub async fn cancel(&self, id: &str) -> Result<BatchJob, CherubError> {
+        match self {
+            Self::Anthropic(provider) => provider.cancel_batch(id).await,
+            Self::OpenAi(provider) => provider.cancel_batch(id).await,
+        }
+    }
+
+    /// Results of an ended batch, one per request (order is not guaranteed;
+    /// match on `custom_id`).
+    pub async fn results(&self, job: &BatchJob) -> Result<Vec<BatchResult>, CherubError> {
+        match &job.state {
+            BatchState::Ended => {}
+            BatchState::InProgress => {
+                return Err(CherubError::Provider(format!(
+                    "batch {} is still in progress",
+                    job.id
+                )));
+            }
+            BatchState::Failed(reason) => {
+                return Err(CherubError::Provider(format!(
+                    "batch {} failed: {reason}",
+                    job.id
+                )));
+            }
+        }
+        match self {
+            Self::Anthropic(provider) => provider.batch_results(job).await,
+            Self::OpenAi(provider) => provider.batch_results(job).await,
+        }
+    }
+}
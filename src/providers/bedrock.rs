@@ -0,0 +1,204 @@
+use std::time::SystemTime;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Url};
+use tracing::{Instrument, info_span};
+
+use async_trait::async_trait;
+
+use super::bedrock_wire::{self, BrInferenceConfig, BrSystemText, BrTool, BrToolConfig};
+use super::http::{self, HttpConfig};
+use super::sigv4::{self, AwsCredentials, SigningRequest};
+use super::{ApiUsage, CompletionRequest, Message, Provider};
+use crate::error::CherubError;
+use crate::retry::{RetryConfig, send_with_retry};
+
+/// SigV4 service name for the Bedrock runtime.
+const SERVICE: &str = "bedrock";
+
+/// Amazon Bedrock provider over the Converse API, signed with SigV4. The
+/// model is a Bedrock model ID, inference profile, or ARN
+/// (e.g. `us.anthropic.claude-sonnet-4-20250514-v1:0`). Non-streaming.
+pub struct BedrockProvider {
+    client: Client,
+    credentials: AwsCredentials,
+    region: String,
+    pub(crate) model: String,
+    pub(crate) max_tokens: u32,
+    endpoint: String,
+    retry_config: RetryConfig,
+}
+
+impl BedrockProvider {
+    pub fn new(
+        credentials: AwsCredentials,
+        region: &str,
+        model: &str,
+        max_tokens: u32,
+    ) -> Result<Self, CherubError> {
+        let client = http::api_client(&HttpConfig::default())?;
+
+        Ok(Self {
+            client,
+            credentials,
+            region: region.to_owned(),
+            model: model.to_owned(),
+            max_tokens,
+            endpoint: format!("https://bedrock-runtime.{region}.amazonaws.com"),
+            retry_config: RetryConfig::new(),
+        })
+    }
+
+    /// Override the retry behavior (attempts, backoff bounds).
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry_config = config;
+        self
+    }
+
+    /// Rebuild the HTTP client with proxy, CA bundle, and header settings.
+    pub fn with_http_config(mut self, config: &HttpConfig) -> Result<Self, CherubError> {
+        self.client = http::api_client(config)?;
+        Ok(self)
+    }
+
+    /// Override the runtime endpoint (a VPC endpoint, or wiremock in tests).
+    pub fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+}
+
+#[async_trait]
+impl Provider for BedrockProvider {
+    /// Send a Converse request. Retries on transient errors (throttling 429,
+    /// 5xx) with exponential backoff, re-signing each attempt.
+    async fn complete(
+        &self,
+        request: &CompletionRequest<'_>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        async {
+            let body = bedrock_wire::ConverseRequest {
+                system: if request.system.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![BrSystemText {
+                        text: request.system,
+                    }]
+                },
+                messages: bedrock_wire::messages_to_bedrock_wire(request.messages),
+                inference_config: BrInferenceConfig {
+                    max_tokens: request.max_tokens.unwrap_or(self.max_tokens),
+                    temperature: request.temperature,
+                    top_p: request.top_p,
+                    stop_sequences: request.stop_sequences,
+                },
+                tool_config: (!request.tools.is_empty()).then(|| BrToolConfig {
+                    tools: request.tools.iter().map(BrTool::from).collect(),
+                }),
+            };
+            let json_body = serde_json::to_vec(&body)
+                .map_err(|e| CherubError::Provider(format!("JSON serialize error: {e}")))?;
+
+            let model = request.model.unwrap_or(&self.model);
+            let path = format!("/model/{}/converse", encode_segment(model));
+            let url = Url::parse(&format!("{}{path}", self.endpoint))
+                .map_err(|e| CherubError::Provider(format!("invalid Bedrock endpoint: {e}")))?;
+            let host = match (url.host_str(), url.port()) {
+                (Some(host), Some(port)) => format!("{host}:{port}"),
+                (Some(host), None) => host.to_owned(),
+                (None, _) => {
+                    return Err(CherubError::Provider(
+                        "invalid Bedrock endpoint: no host".to_owned(),
+                    ));
+                }
+            };
+
+            // Signed afresh for each attempt: the signature covers x-amz-date.
+            let request = || {
+                let amz_date = sigv4::amz_date(SystemTime::now());
+                let signing = SigningRequest {
+                    method: "POST",
+                    path: &path,
+                    headers: &[("host", host.as_str()), ("x-amz-date", amz_date.as_str())],
+                    payload: &json_body,
+                    region: &self.region,
+                    service: SERVICE,
+                    amz_date: &amz_date,
+                };
+                // NEVER log the signed headers — they carry the session token.
+                let mut req = self
+                    .client
+                    .post(url.clone())
+                    .header("content-type", "application/json")
+                    .header("x-amz-date", &amz_date)
+                    .body(json_body.clone());
+                for (name, value) in sigv4::sign(&signing, &self.credentials) {
+                    req = req.header(name, value);
+                }
+                req
+            };
+            let response = match send_with_retry(&self.retry_config, request).await {
+                Ok(response) => response,
+                Err(e) => return Err(e.into_error(describe_error).await),
+            };
+
+            let resp: bedrock_wire::ConverseResponse = response
+                .json()
+                .await
+                .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))?;
+
+            Ok(bedrock_wire::bedrock_response_to_message(resp))
+        }
+        .instrument(info_span!("api_call", model = %self.model))
+        .await
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn max_output_tokens(&self) -> u32 {
+        self.max_tokens
+    }
+}
+
+/// Bedrock names the error (ThrottlingException, AccessDeniedException, ...)
+/// in a header.
+fn describe_error(status: u16, headers: &HeaderMap, body: &str) -> String {
+    let kind = headers
+        .get("x-amzn-errortype")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(':').next())
+        .unwrap_or("error");
+    format!("API error {status} {kind}: {body}")
+}
+
+/// Percent-encode a path segment: model IDs carry `:`, and ARNs `/`.
+fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_ids_and_arns_encode_as_one_segment() {
+        assert_eq!(
+            encode_segment("anthropic.claude-3-haiku-20240307-v1:0"),
+            "anthropic.claude-3-haiku-20240307-v1%3A0"
+        );
+        assert_eq!(
+            encode_segment("arn:aws:bedrock:us-east-1:123:inference-profile/x"),
+            "arn%3Aaws%3Abedrock%3Aus-east-1%3A123%3Ainference-profile%2Fx"
+        );
+    }
+}
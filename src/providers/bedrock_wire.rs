@@ -0,0 +1,435 @@
+//! Private serde structs for the Amazon Bedrock Converse API JSON format.
+//! Converse is model-agnostic: the same shapes drive Claude, Llama, Mistral,
+//! Nova, etc. on Bedrock. Content blocks are single-key objects
+//! (`{"text": ..}`, `{"toolUse": {..}}`).
+
+use serde::{Deserialize, Serialize};
+
+use super::{ApiUsage, ContentBlock, Message, StopReason, ToolDefinition, UserContent};
+
+// --- Request types ---
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConverseRequest<'a> {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub system: Vec<BrSystemText<'a>>,
+    pub messages: Vec<BrMessage>,
+    pub inference_config: BrInferenceConfig<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_config: Option<BrToolConfig>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct BrSystemText<'a> {
+    pub text: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrInferenceConfig<'a> {
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    pub stop_sequences: &'a [String],
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct BrMessage {
+    pub role: &'static str,
+    pub content: Vec<BrContentBlock>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BrContentBlock {
+    Text(String),
+    Image(BrImage),
+    Document(BrDocument),
+    ToolUse(BrToolUse),
+    ToolResult(BrToolResult),
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct BrImage {
+    /// `png`, `jpeg`, `gif`, or `webp`.
+    pub format: String,
+    pub source: BrImageSource,
+}
+
+/// Bedrock document and image sources share one shape.
+#[derive(Serialize, Debug)]
+pub(crate) struct BrImageSource {
+    /// Base64 in JSON.
+    pub bytes: String,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct BrDocument {
+    /// `pdf`, `txt`, `md`, ...
+    pub format: String,
+    /// Required. Alphanumerics, single spaces, `-()[]` only; see `document_name`.
+    pub name: String,
+    pub source: BrImageSource,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrToolUse {
+    pub tool_use_id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrToolResult {
+    pub tool_use_id: String,
+    pub content: Vec<BrContentBlock>,
+    pub status: &'static str,
+}
+
+#[derive(Serialize)]
+pub(crate) struct BrToolConfig {
+    pub tools: Vec<BrTool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrTool {
+    pub tool_spec: BrToolSpec,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrToolSpec {
+    pub name: String,
+    pub description: String,
+    pub input_schema: BrInputSchema,
+}
+
+#[derive(Serialize)]
+pub(crate) struct BrInputSchema {
+    pub json: serde_json::Value,
+}
+
+// --- Response types ---
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConverseResponse {
+    pub output: BrOutput,
+    pub stop_reason: String,
+    pub usage: Option<BrUsage>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BrOutput {
+    pub message: BrResponseMessage,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BrResponseMessage {
+    pub content: Vec<BrResponseBlock>,
+}
+
+/// One response content block. A struct of options rather than an enum, so
+/// block kinds we don't use (`reasoningContent`, ...) are skipped, not errors.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrResponseBlock {
+    pub text: Option<String>,
+    pub tool_use: Option<BrToolUse>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    #[serde(default)]
+    pub cache_read_input_tokens: u32,
+    #[serde(default)]
+    pub cache_write_input_tokens: u32,
+}
+
+// --- Conversions ---
+
+impl From<&ToolDefinition> for BrTool {
+    fn from(def: &ToolDefinition) -> Self {
+        Self {
+            tool_spec: BrToolSpec {
+                name: def.name.clone(),
+                description: def.description.clone(),
+                input_schema: BrInputSchema {
+                    json: def.input_schema.clone(),
+                },
+            },
+        }
+    }
+}
+
+/// Convert internal messages to Converse messages. Consecutive ToolResult
+/// messages are merged into one `user` message, as Converse requires roles
+/// to alternate.
+pub(crate) fn messages_to_bedrock_wire(messages: &[Message]) -> Vec<BrMessage> {
+    let mut wire = Vec::new();
+    let mut pending_results: Vec<BrContentBlock> = Vec::new();
+
+    for msg in messages {
+        match msg {
+            Message::User { content } => {
+                flush_results(&mut wire, &mut pending_results);
+                let content = content
+                    .iter()
+                    .map(|c| match c {
+                        UserContent::Text(text) => BrContentBlock::Text(text.clone()),
+                        UserContent::Image { media_type, data } => BrContentBlock::Image(BrImage {
+                            format: media_type
+                                .strip_prefix("image/")
+                                .unwrap_or(media_type)
+                                .to_owned(),
+                            source: BrImageSource {
+                                bytes: data.clone(),
+                            },
+                        }),
+                        UserContent::Document {
+                            media_type,
+                            data,
+                            name,
+                        } => BrContentBlock::Document(BrDocument {
+                            format: media_type
+                                .strip_prefix("application/")
+                                .unwrap_or(media_type)
+                                .to_owned(),
+                            name: document_name(name.as_deref()),
+                            source: BrImageSource {
+                                bytes: data.clone(),
+                            },
+                        }),
+                    })
+                    .collect();
+                wire.push(BrMessage {
+                    role: "user",
+                    content,
+                });
+            }
+            Message::Assistant { content, .. } => {
+                flush_results(&mut wire, &mut pending_results);
+                let content = content
+                    .iter()
+                    .map(|block| match block {
+                        ContentBlock::Text { text } => BrContentBlock::Text(text.clone()),
+                        ContentBlock::ToolUse { id, name, input } => {
+                            BrContentBlock::ToolUse(BrToolUse {
+                                tool_use_id: id.clone(),
+                                name: name.clone(),
+                                input: input.clone(),
+                            })
+                        }
+                    })
+                    .collect();
+                wire.push(BrMessage {
+                    role: "assistant",
+                    content,
+                });
+            }
+            Message::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => {
+                pending_results.push(BrContentBlock::ToolResult(BrToolResult {
+                    tool_use_id: tool_use_id.clone(),
+                    content: vec![BrContentBlock::Text(content.clone())],
+                    status: if *is_error { "error" } else { "success" },
+                }));
+            }
+        }
+    }
+
+    flush_results(&mut wire, &mut pending_results);
+    wire
+}
+
+/// Bedrock rejects document names with other characters (such as the `.` of
+/// an extension) or consecutive spaces. The extension is dropped, anything
+/// else disallowed becomes `-`.
+fn document_name(name: Option<&str>) -> String {
+    let stem = name
+        .map(|n| n.rsplit_once('.').map_or(n, |(stem, _)| stem))
+        .unwrap_or_default();
+    let mut out = String::new();
+    for c in stem.chars() {
+        let c = match c {
+            c if c.is_ascii_alphanumeric() || "-()[]".contains(c) => c,
+            c if c.is_whitespace() => ' ',
+            _ => '-',
+        };
+        if !(c == ' ' && out.ends_with(' ')) {
+            out.push(c);
+        }
+    }
+    let out = out.trim();
+    if out.is_empty() {
+        "document".to_owned()
+    } else {
+        out.to_owned()
+    }
+}
+
+fn flush_results(wire: &mut Vec<BrMessage>, pending: &mut Vec<BrContentBlock>) {
+    if !pending.is_empty() {
+        wire.push(BrMessage {
+            role: "user",
+            content: std::mem::take(pending),
+        });
+    }
+}
+
+/// Convert a Converse response to our internal Message type, plus API usage.
+pub(crate) fn bedrock_response_to_message(resp: ConverseResponse) -> (Message, Option<ApiUsage>) {
+    let stop_reason = match resp.stop_reason.as_str() {
+        "tool_use" => StopReason::ToolUse,
+        "max_tokens" => StopReason::MaxTokens,
+        _ => StopReason::EndTurn,
+    };
+
+    let usage = resp.usage.map(|u| ApiUsage {
+        input_tokens: u.input_tokens,
+        output_tokens: u.output_tokens,
+        cache_creation_tokens: u.cache_write_input_tokens,
+        cache_read_tokens: u.cache_read_input_tokens,
+    });
+
+    let content = resp
+        .output
+        .message
+        .content
+        .into_iter()
+        .filter_map(|block| match (block.text, block.tool_use) {
+            (_, Some(tool_use)) => Some(ContentBlock::ToolUse {
+                id: tool_use.tool_use_id,
+                name: tool_use.name,
+                input: tool_use.input,
+            }),
+            (Some(text), None) => Some(ContentBlock::Text { text }),
+            (None, None) => None,
+        })
+        .collect();
+
+    let message = Message::Assistant {
+        content,
+        stop_reason,
+    };
+
+    (message, usage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn request_uses_single_key_blocks_and_merges_tool_results() {
+        let messages = vec![
+            Message::user_text("list files"),
+            Message::Assistant {
+                content: vec![ContentBlock::ToolUse {
+                    id: "t1".to_owned(),
+                    name: "bash".to_owned(),
+                    input: json!({"command": "ls"}),
+                }],
+                stop_reason: StopReason::ToolUse,
+            },
+            Message::ToolResult {
+                tool_use_id: "t1".to_owned(),
+                content: "a.txt".to_owned(),
+                is_error: false,
+            },
+        ];
+        let tools = [ToolDefinition {
+            name: "bash".to_owned(),
+            description: "Run bash".to_owned(),
+            input_schema: json!({"type": "object"}),
+        }];
+        let body = ConverseRequest {
+            system: vec![BrSystemText { text: "Be brief." }],
+            messages: messages_to_bedrock_wire(&messages),
+            inference_config: BrInferenceConfig {
+                max_tokens: 1024,
+                temperature: Some(0.0),
+                top_p: None,
+                stop_sequences: &[],
+            },
+            tool_config: Some(BrToolConfig {
+                tools: tools.iter().map(BrTool::from).collect(),
+            }),
+        };
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["system"][0]["text"], "Be brief.");
+        assert_eq!(json["inferenceConfig"]["maxTokens"], 1024);
+        assert_eq!(json["inferenceConfig"]["temperature"], 0.0);
+        assert!(json["inferenceConfig"].get("stopSequences").is_none());
+        assert_eq!(json["messages"][0]["content"][0]["text"], "list files");
+        assert_eq!(
+            json["messages"][1]["content"][0]["toolUse"]["toolUseId"],
+            "t1"
+        );
+        let result = &json["messages"][2]["content"][0]["toolResult"];
+        assert_eq!(json["messages"][2]["role"], "user");
+        assert_eq!(result["content"][0]["text"], "a.txt");
+        assert_eq!(result["status"], "success");
+        assert_eq!(
+            json["toolConfig"]["tools"][0]["toolSpec"]["inputSchema"]["json"]["type"],
+            "object"
+        );
+    }
+
+    #[test]
+    fn documents_get_format_and_sanitized_name() {
+        let wire = messages_to_bedrock_wire(&[Message::User {
+            content: vec![UserContent::Document {
+                media_type: "application/pdf".to_owned(),
+                data: "JVBERi0...".to_owned(),
+                name: Some("Q3  report_v2.final.pdf".to_owned()),
+            }],
+        }]);
+        let json = serde_json::to_value(&wire[0]).unwrap();
+        let document = &json["content"][0]["document"];
+        assert_eq!(document["format"], "pdf");
+        assert_eq!(document["name"], "Q3 report-v2-final");
+        assert_eq!(document["source"]["bytes"], "JVBERi0...");
+        assert_eq!(document_name(None), "document");
+    }
+
+    #[test]
+    fn response_parsing_skips_unknown_blocks() {
+        let resp: ConverseResponse = serde_json::from_value(json!({
+            "output": {"message": {"role": "assistant", "content": [
+                {"reasoningContent": {"reasoningText": {"text": "hmm"}}},
+                {"text": "Running it."},
+                {"toolUse": {"toolUseId": "t2", "name": "bash", "input": {"command": "pwd"}}}
+            ]}},
+            "stopReason": "tool_use",
+            "usage": {"inputTokens": 40, "outputTokens": 12, "totalTokens": 52}
+        }))
+        .unwrap();
+        let (msg, usage) = bedrock_response_to_message(resp);
+        assert_eq!(usage.unwrap().output_tokens, 12);
+        let Message::Assistant {
+            content,
+            stop_reason,
+        } = msg
+        else {
+            panic!("expected assistant message");
+        };
+        assert_eq!(stop_reason, StopReason::ToolUse);
+        assert_eq!(content.len(), 2);
+        assert!(matches!(&content[1], ContentBlock::ToolUse { id, .. } if id == "t2"));
+    }
+}
@@ -0,0 +1,391 @@
+//! Response cache: wraps a provider and replays earlier completions.
+//!
+//! Keyed by a SHA-256 of everything that shapes a completion — model, generation
+//! settings, system prompt, message history, and tool definitions — so a repeated
+//! deterministic run (tests, CI) answers from the cache instead of the API.
+//! Only successful completions are stored. A hit reports no usage: nothing was
+//! billed.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use super::{ApiUsage, CompletionRequest, Message, MessageDelta, Provider, replay_deltas};
+use crate::error::CherubError;
+
+/// A stored completion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    message: Message,
+}
+
+/// Where cached completions live.
+pub enum CacheBackend {
+    /// Process-local; gone when the provider is dropped.
+    Memory(Mutex<HashMap<String, Message>>),
+    /// One `<key>.json` file per completion in this directory, shared across runs.
+    Disk(PathBuf),
+}
+
+impl CacheBackend {
+    pub fn memory() -> Self {
+        Self::Memory(Mutex::new(HashMap::new()))
+    }
+
+    pub fn disk(dir: impl Into<PathBuf>) -> Self {
+        Self::Disk(dir.into())
+    }
+
+    /// Look up `key`. Unreadable or corrupt entries count as misses.
+    async fn get(&self, key: &str) -> Option<Message> {
+        match self {
+            Self::Memory(entries) => entries
+                .lock()
+                .expect("cache mutex poisoned")
+                .get(key)
+                .cloned(),
+            Self::Disk(dir) => {
+                let path = dir.join(format!("{key}.json"));
+                let bytes = tokio::fs::read(&path).await.ok()?;
+                match serde_json::from_slice::<CachedResponse>(&bytes) {
+                    Ok(cached) => Some(cached.message),
+                    Err(e) => {
+                        warn!(path = %path.display(), error = %e, "ignoring corrupt cache entry");
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Store `message` under `key`. Disk writes go through a temp file and a
+    /// rename so concurrent runs never read a half-written entry.
+    async fn put(&self, key: &str, message: &Message) -> std::io::Result<()> {
+        match self {
+            Self::Memory(entries) => {
+                entries
+                    .lock()
+                    .expect("cache mutex poisoned")
+                    .insert(key.to_owned(), message.clone());
+                Ok(())
+            }
+            Self::Disk(dir) => {
+                let cached = CachedResponse {
+                    message: message.clone(),
+                };
+                let json = serde_json::to_vec_pretty(&cached)?;
+                tokio::fs::create_dir_all(dir).await?;
+                let tmp = dir.join(format!("{key}.json.{}.tmp", std::process::id()));
+                tokio::fs::write(&tmp, json).await?;
+                tokio::fs::rename(&tmp, dir.join(format!("{key}.json"))).await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Provider wrapper that answers repeated requests from a [`CacheBackend`].
+pub struct CachingProvider {
+    inner: Box<dyn Provider>,
+    backend: CacheBackend,
+}
+
+impl CachingProvider {
+    pub fn new(inner: Box<dyn Provider>, backend: CacheBackend) -> Self {
+        Self { inner, backend }
+    }
+
+    /// Hex SHA-256 over the request and the model settings that shape it.
+    fn key(&self, request: &CompletionRequest<'_>) -> String {
+        let tools: Vec<_> = request
+            .tools
+            .iter()
+            .map(|t| {
+                serde_json::json!({
+                    "name": t.name,
+                    "description": t.description,
+                    "input_schema": t.input_schema,
+                })
+            })
+            .collect();
+        let keyed = serde_json::json!({
+            "model": request.model.unwrap_or(self.inner.model_name()),
+            "max_tokens": request.max_tokens.unwrap_or(self.inner.max_output_tokens()),
+            "temperature": request.temperature,
+            "top_p": request.top_p,
+            "stop_sequences": request.stop_sequences,
+            "system": request.system,
+            "messages": request.messages,
+            "tools": tools,
+        });
+        Sha256::digest(keyed.to_string().as_bytes())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    async fn store(&self, key: &str, message: &Message) {
+        // A cache that cannot be written only costs a future API call.
+        if let Err(e) = self.backend.put(key, message).await {
+            warn!(error = %e, "failed to store cached response");
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for CachingProvider {
+    async fn complete(
+        &self,
+        request: &CompletionRequest<'_>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        let key = self.key(request);
+        if let Some(message) = self.backend.get(&key).await {
+            debug!(key = %key, "response cache hit");
+            return Ok((message, None));
+        }
+        let (message, usage) = self.inner.complete(request).await?;
+        self.store(&key, &message).await;
+        Ok((message, usage))
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest<'_>,
+        on_delta: &mut (dyn FnMut(MessageDelta) + Send),
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        let key = self.key(request);
+        if let Some(message) = self.backend.get(&key).await {
+            debug!(key = %key, "response cache hit");
+            replay_deltas(&message, on_delta);
+            return Ok((message, None));
+        }
+        let (message, usage) = self.inner.complete_streaming(request, on_delta).await?;
+        self.store(&key, &message).await;
+        Ok((message, usage))
+    }
+
+    async fn health_check(&self) -> Result<(), CherubError> {
+        self.inner.health_check().await
+    }
+
+    async fn list_models(&self) -> Result<Option<Vec<String>>, CherubError> {
+        self.inner.list_models().await
+    }
+
+    fn enter_commit_tier(&self) {
+        self.inner.enter_commit_tier();
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn max_output_tokens(&self) -> u32 {
+        self.inner.max_output_tokens()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ContentBlock, StopReason};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Answers with the call count, so a replayed answer is distinguishable.
+    struct CountingProvider {
+        calls: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        async fn complete(
+            &self,
+            _request: &CompletionRequest<'_>,
+        ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((
+                Message::Assistant {
+                    content: vec![ContentBlock::Text {
+                        text: format!("answer {n}"),
+                    }],
+                    stop_reason: StopReason::EndTurn,
+                },
+                Some(ApiUsage::new(10, 5)),
+            ))
+        }
+
+        fn model_name(&self) -> &str {
+            "counting"
+        }
+
+        fn max_output_tokens(&self) -> u32 {
+            1024
+        }
+    }
+
+    fn caching(backend: CacheBackend) -> (CachingProvider, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let inner = CountingProvider {
+            calls: Arc::clone(&calls),
+        };
+        (CachingProvider::new(Box::new(inner), backend), calls)
+    }
+
+    fn text(message: &Message) -> &str {
+        match message {
+            Message::Assistant { content, .. } => match &content[0] {
+                ContentBlock::Text { text } => text,
+                other => panic!("unexpected block: {other:?}"),
+            },
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_request_served_from_memory() {
+        let (provider, calls) = caching(CacheBackend::memory());
+        let messages = vec![Message::user_text("hello")];
+
+        let (first, usage) = provider
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await
+            .unwrap();
+        assert_eq!(usage, Some(ApiUsage::new(10, 5)));
+        let (second, usage) = provider
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await
+            .unwrap();
+        assert_eq!(usage, None, "a cache hit bills nothing");
+        assert_eq!(first, second);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn different_history_misses() {
+        let (provider, calls) = caching(CacheBackend::memory());
+
+        let a = provider
+            .complete(&CompletionRequest::new(
+                "system",
+                &[Message::user_text("a")],
+                &[],
+            ))
+            .await
+            .unwrap();
+        let b = provider
+            .complete(&CompletionRequest::new(
+                "system",
+                &[Message::user_text("b")],
+                &[],
+            ))
+            .await
+            .unwrap();
+        let other_system = provider
+            .complete(&CompletionRequest::new(
+                "other",
+                &[Message::user_text("a")],
+                &[],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(text(&a.0), "answer 1");
+        assert_eq!(text(&b.0), "answer 2");
+        assert_eq!(text(&other_system.0), "answer 3");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn generation_overrides_are_part_of_the_key() {
+        let (provider, calls) = caching(CacheBackend::memory());
+        let messages = vec![Message::user_text("hello")];
+        let request = CompletionRequest::new("system", &messages, &[]);
+
+        provider.complete(&request).await.unwrap();
+        provider
+            .complete(&request.with_temperature(0.0))
+            .await
+            .unwrap();
+        provider
+            .complete(&request.with_max_tokens(64))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn disk_cache_survives_new_provider() {
+        let dir = std::env::temp_dir().join(format!("cherub-cache-{}", uuid::Uuid::now_v7()));
+        let messages = vec![Message::user_text("hello")];
+
+        let (provider, _) = caching(CacheBackend::disk(&dir));
+        let (first, _) = provider
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await
+            .unwrap();
+
+        let (provider, calls) = caching(CacheBackend::disk(&dir));
+        let (second, usage) = provider
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(usage, None);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn corrupt_disk_entry_is_a_miss() {
+        let dir = std::env::temp_dir().join(format!("cherub-cache-{}", uuid::Uuid::now_v7()));
+        let messages = vec![Message::user_text("hello")];
+        let (provider, calls) = caching(CacheBackend::disk(&dir));
+        let key = provider.key(&CompletionRequest::new("system", &messages, &[]));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("{key}.json")), "not json").unwrap();
+
+        let (message, _) = provider
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await
+            .unwrap();
+        assert_eq!(text(&message), "answer 1");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // The fresh answer replaced the corrupt entry.
+        let (provider, calls) = caching(CacheBackend::disk(&dir));
+        provider
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn streaming_hit_replays_deltas() {
+        let (provider, calls) = caching(CacheBackend::memory());
+        let messages = vec![Message::user_text("hello")];
+        provider
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await
+            .unwrap();
+
+        let mut deltas = Vec::new();
+        provider
+            .complete_streaming(
+                &CompletionRequest::new("system", &messages, &[]),
+                &mut |d| deltas.push(d),
+            )
+            .await
+            .unwrap();
+        assert_eq!(deltas, vec![MessageDelta::Text("answer 1".to_owned())]);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -6,18 +6,28 @@
 //! (wired in M13d).
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use secrecy::SecretString;
 use serde::Deserialize;
 
 use super::Provider;
 use super::anthropic::AnthropicProvider;
+use super::bedrock::BedrockProvider;
 use super::failover::FailoverProvider;
+use super::http::HttpConfig;
+use super::ollama::OllamaProvider;
 use super::openai::OpenAiProvider;
+use super::openrouter::{OpenRouterProvider, ProviderPreferences, Routing};
+use super::pricing::PricingTable;
+use super::sigv4::{self, AwsCredentials};
 use crate::error::CherubError;
+use crate::retry::RetryConfig;
 
 const MAX_CONFIG_FILE_SIZE: u64 = 64 * 1024; // 64 KiB
+const MAX_RETRIES_LIMIT: u32 = 10;
+/// Provider used as the main orchestrator.
+const DEFAULT_PROVIDER: &str = "default";
 
 /// Top-level providers configuration file.
 #[derive(Clone, Deserialize)]
@@ -31,6 +41,12 @@ pub struct ProvidersConfig {
     /// ToolRegistry (M13d). Parsed here but not wired until M13d.
     #[serde(default)]
     pub agents: HashMap<String, SubAgentDef>,
+
+    /// Per-model rates (USD per million tokens), keyed by model-name prefix
+    /// like the DB pricing table. Entries here override DB rows for the same
+    /// prefix.
+    #[serde(default)]
+    pub pricing: PricingTable,
 }
 
 /// Which provider backend to use.
@@ -39,6 +55,13 @@ pub struct ProvidersConfig {
 pub enum ProviderType {
     Anthropic,
     Openai,
+    /// Local Ollama or llama.cpp server; no API key.
+    Ollama,
+    /// Amazon Bedrock Converse API, SigV4-signed with `AWS_*` credentials.
+    Bedrock,
+    /// OpenRouter: many vendors' models behind one key, with model fallbacks
+    /// and provider routing.
+    Openrouter,
     /// Wraps multiple providers for automatic failover (M13c).
     Failover,
 }
@@ -55,22 +78,68 @@ pub struct ProviderDef {
     pub model: String,
 
     /// Name of environment variable holding the API key.
-    /// Defaults to `ANTHROPIC_API_KEY` or `OPENAI_API_KEY` based on type.
+    /// Defaults to `ANTHROPIC_API_KEY` or `OPENROUTER_API_KEY` based on type.
     /// Optional for local providers (Ollama, vLLM, etc.).
     #[serde(default)]
     pub api_key_env: Option<String>,
 
-    /// Custom base URL for OpenAI-compatible endpoints.
+    /// Where to send requests instead of the public API: an OpenAI-compatible
+    /// endpoint (including `/v1`), the local server for `ollama` (default
+    /// `http://localhost:11434/v1`), an Anthropic gateway (requests go to
+    /// `{base_url}/v1/messages`), or a Bedrock runtime endpoint.
     #[serde(default)]
     pub base_url: Option<String>,
 
+    /// Proxy for this provider's requests. Unset honors `HTTP_PROXY`,
+    /// `HTTPS_PROXY`, and `NO_PROXY`.
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// PEM file of extra CA certificates to trust (e.g. a TLS-intercepting
+    /// corporate proxy's root).
+    #[serde(default)]
+    pub ca_bundle: Option<PathBuf>,
+
+    /// Headers sent with every request (gateway keys, routing tags).
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
     /// Maximum output tokens per completion call.
     #[serde(default = "default_max_tokens")]
     pub max_tokens: u32,
 
+    /// AWS region for `bedrock` (defaults to `AWS_REGION`, then
+    /// `AWS_DEFAULT_REGION`).
+    #[serde(default)]
+    pub region: Option<String>,
+
+    /// Sampling temperature (Anthropic only), 0.0–1.0. Unset uses the API default.
+    #[serde(default)]
+    pub temperature: Option<f32>,
+
+    /// Retries after a transient failure (429, 5xx, connection error), 0–10.
+    /// Unset uses the default of 3; 0 fails on the first error.
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+
+    /// For openrouter: models OpenRouter falls back to, in order, when `model`
+    /// is unavailable.
+    #[serde(default)]
+    pub fallback_models: Vec<String>,
+
+    /// For openrouter: which upstream providers may serve requests.
+    #[serde(default)]
+    pub provider_preferences: Option<ProviderPreferences>,
+
     /// For failover providers (M13c): ordered list of provider names to try.
     #[serde(default)]
     pub providers: Option<Vec<String>>,
+
+    /// For failover providers: whether to keep falling back once the
+    /// conversation has run a Commit-tier action. Unset allows it; `false`
+    /// keeps such a conversation on the provider that was serving it.
+    #[serde(default)]
+    pub commit_fallback: Option<bool>,
 }
 
 fn default_max_tokens() -> u32 {
@@ -129,13 +198,29 @@ impl ProvidersConfig {
         let content = std::fs::read_to_string(path)
             .map_err(|e| CherubError::Config(format!("cannot read {}: {e}", path.display())))?;
 
-        let config: Self = toml::from_str(&content)
+        Self::parse(&content)
+    }
+
+    /// Parse and validate providers config from TOML source, for applications
+    /// that embed the config rather than reading it from a file.
+    pub fn parse(content: &str) -> Result<Self, CherubError> {
+        let config: Self = toml::from_str(content)
             .map_err(|e| CherubError::Config(format!("invalid providers config: {e}")))?;
 
         config.validate()?;
         Ok(config)
     }
 
+    /// Instantiate the `[providers.default]` entry, the main orchestrator.
+    pub fn instantiate_default(&self) -> Result<Box<dyn Provider>, CherubError> {
+        if !self.providers.contains_key(DEFAULT_PROVIDER) {
+            return Err(CherubError::Config(format!(
+                "providers config must contain a [providers.{DEFAULT_PROVIDER}] entry"
+            )));
+        }
+        instantiate_named_provider(self, DEFAULT_PROVIDER, &mut Vec::new())
+    }
+
     /// Validate cross-field constraints that serde can't enforce.
     fn validate(&self) -> Result<(), CherubError> {
         for (name, def) in &self.providers {
@@ -152,6 +237,61 @@ impl ProvidersConfig {
                 )));
             }
 
+            if def.region.is_some() && def.provider_type != ProviderType::Bedrock {
+                return Err(CherubError::Config(format!(
+                    "provider '{name}': 'region' is only valid for bedrock type"
+                )));
+            }
+
+            if let Some(temperature) = def.temperature {
+                if def.provider_type != ProviderType::Anthropic {
+                    return Err(CherubError::Config(format!(
+                        "provider '{name}': 'temperature' is only supported for anthropic type"
+                    )));
+                }
+                if !(0.0..=1.0).contains(&temperature) {
+                    return Err(CherubError::Config(format!(
+                        "provider '{name}': temperature must be between 0.0 and 1.0"
+                    )));
+                }
+            }
+
+            if let Some(max_retries) = def.max_retries {
+                if def.provider_type == ProviderType::Failover {
+                    return Err(CherubError::Config(format!(
+                        "provider '{name}': 'max_retries' is set on each failover child, not the failover"
+                    )));
+                }
+                if max_retries > MAX_RETRIES_LIMIT {
+                    return Err(CherubError::Config(format!(
+                        "provider '{name}': max_retries must be at most {MAX_RETRIES_LIMIT}"
+                    )));
+                }
+            }
+
+            let has_http = def.base_url.is_some()
+                || def.proxy.is_some()
+                || def.ca_bundle.is_some()
+                || !def.headers.is_empty();
+            if has_http && def.provider_type == ProviderType::Failover {
+                return Err(CherubError::Config(format!(
+                    "provider '{name}': connection settings are set on each failover child, not the failover"
+                )));
+            }
+
+            let has_routing = !def.fallback_models.is_empty() || def.provider_preferences.is_some();
+            if has_routing && def.provider_type != ProviderType::Openrouter {
+                return Err(CherubError::Config(format!(
+                    "provider '{name}': 'fallback_models' and 'provider_preferences' are only valid for openrouter type"
+                )));
+            }
+
+            if def.commit_fallback.is_some() && def.provider_type != ProviderType::Failover {
+                return Err(CherubError::Config(format!(
+                    "provider '{name}': 'commit_fallback' is only valid for failover type"
+                )));
+            }
+
             // Failover children must reference existing providers and the list must be non-empty.
             if let Some(ref children) = def.providers {
                 if children.is_empty() {
@@ -175,6 +315,20 @@ impl ProvidersConfig {
             self.detect_cycle(name, &mut ancestry)?;
         }
 
+        for (pattern, rates) in &self.pricing {
+            let all = [
+                rates.input_per_mtok,
+                rates.output_per_mtok,
+                rates.cache_write_per_mtok,
+                rates.cache_read_per_mtok,
+            ];
+            if all.iter().any(|r| !r.is_finite() || *r < 0.0) {
+                return Err(CherubError::Config(format!(
+                    "pricing '{pattern}': rates must be non-negative numbers"
+                )));
+            }
+        }
+
         // Validate sub-agent provider references.
         for (name, agent) in &self.agents {
             if !self.providers.contains_key(&agent.provider) {
@@ -217,9 +371,19 @@ impl ProvidersConfig {
 
 /// Instantiate a concrete provider from a definition.
 ///
-/// Handles Anthropic and OpenAI types. For failover, use [`instantiate_named_provider`]
+/// Handles Anthropic, OpenAI, Ollama, Bedrock, and OpenRouter types. For failover, use [`instantiate_named_provider`]
 /// which resolves child references recursively.
 pub fn instantiate_provider(def: &ProviderDef) -> Result<Box<dyn Provider>, CherubError> {
+    let retry_config = def.max_retries.map(|max_retries| RetryConfig {
+        max_retries,
+        ..RetryConfig::new()
+    });
+    let http_config = (def.proxy.is_some() || def.ca_bundle.is_some() || !def.headers.is_empty())
+        .then(|| HttpConfig {
+            proxy: def.proxy.clone(),
+            ca_bundle: def.ca_bundle.clone(),
+            headers: def.headers.clone(),
+        });
     match def.provider_type {
         ProviderType::Anthropic => {
             let key_env = def.api_key_env.as_deref().unwrap_or("ANTHROPIC_API_KEY");
@@ -228,8 +392,20 @@ pub fn instantiate_provider(def: &ProviderDef) -> Result<Box<dyn Provider>, Cher
             if key_raw.is_empty() {
                 return Err(CherubError::Config(format!("{key_env} is empty")));
             }
-            let provider =
+            let mut provider =
                 AnthropicProvider::new(SecretString::from(key_raw), &def.model, def.max_tokens)?;
+            if let Some(ref url) = def.base_url {
+                provider = provider.with_base_url(url);
+            }
+            if let Some(temperature) = def.temperature {
+                provider = provider.with_temperature(temperature);
+            }
+            if let Some(config) = retry_config {
+                provider = provider.with_retry_config(config);
+            }
+            if let Some(ref config) = http_config {
+                provider = provider.with_http_config(config)?;
+            }
             Ok(Box::new(provider))
         }
         ProviderType::Openai => {
@@ -243,6 +419,73 @@ pub fn instantiate_provider(def: &ProviderDef) -> Result<Box<dyn Provider>, Cher
             if let Some(ref url) = def.base_url {
                 provider = provider.with_base_url(url.clone());
             }
+            if let Some(config) = retry_config {
+                provider = provider.with_retry_config(config);
+            }
+            if let Some(ref config) = http_config {
+                provider = provider.with_http_config(config)?;
+            }
+            Ok(Box::new(provider))
+        }
+        ProviderType::Ollama => {
+            let mut provider = OllamaProvider::new(&def.model, def.max_tokens)?;
+            if let Some(ref url) = def.base_url {
+                provider = provider.with_base_url(url.clone());
+            }
+            if let Some(config) = retry_config {
+                provider = provider.with_retry_config(config);
+            }
+            if let Some(ref config) = http_config {
+                provider = provider.with_http_config(config)?;
+            }
+            Ok(Box::new(provider))
+        }
+        ProviderType::Bedrock => {
+            let credentials = AwsCredentials::from_env().ok_or_else(|| {
+                CherubError::Config(
+                    "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set".to_owned(),
+                )
+            })?;
+            let region = def
+                .region
+                .clone()
+                .or_else(sigv4::region_from_env)
+                .ok_or_else(|| CherubError::Config("AWS_REGION not set".to_owned()))?;
+            let mut provider =
+                BedrockProvider::new(credentials, &region, &def.model, def.max_tokens)?;
+            if let Some(ref url) = def.base_url {
+                provider = provider.with_endpoint(url.clone());
+            }
+            if let Some(config) = retry_config {
+                provider = provider.with_retry_config(config);
+            }
+            if let Some(ref config) = http_config {
+                provider = provider.with_http_config(config)?;
+            }
+            Ok(Box::new(provider))
+        }
+        ProviderType::Openrouter => {
+            let key_env = def.api_key_env.as_deref().unwrap_or("OPENROUTER_API_KEY");
+            let key_raw = std::env::var(key_env)
+                .map_err(|_| CherubError::Config(format!("{key_env} not set")))?;
+            if key_raw.is_empty() {
+                return Err(CherubError::Config(format!("{key_env} is empty")));
+            }
+            let mut provider =
+                OpenRouterProvider::new(SecretString::from(key_raw), &def.model, def.max_tokens)?
+                    .with_routing(Routing {
+                        fallback_models: def.fallback_models.clone(),
+                        preferences: def.provider_preferences.clone().unwrap_or_default(),
+                    });
+            if let Some(ref url) = def.base_url {
+                provider = provider.with_base_url(url.clone());
+            }
+            if let Some(config) = retry_config {
+                provider = provider.with_retry_config(config);
+            }
+            if let Some(ref config) = http_config {
+                provider = provider.with_http_config(config)?;
+            }
             Ok(Box::new(provider))
         }
         ProviderType::Failover => Err(CherubError::Config(
@@ -292,10 +535,10 @@ pub fn instantiate_named_provider(
             }
             ancestry.pop();
 
-            Ok(Box::new(FailoverProvider::new(
-                child_providers,
-                child_names,
-            )))
+            Ok(Box::new(
+                FailoverProvider::new(child_providers, child_names)
+                    .with_commit_fallback(def.commit_fallback.unwrap_or(true)),
+            ))
         }
         _ => instantiate_provider(def),
     }
@@ -347,6 +590,45 @@ base_url = "http://localhost:11434/v1"
         assert!(local.api_key_env.is_none());
     }
 
+    #[test]
+    fn parse_and_instantiate_ollama() {
+        let toml = r#"
+[providers.default]
+type = "ollama"
+model = "qwen2.5:7b"
+base_url = "http://localhost:8080/v1"
+"#;
+        let config: ProvidersConfig = toml::from_str(toml).expect("should parse");
+        assert!(config.validate().is_ok());
+        let def = &config.providers["default"];
+        assert_eq!(def.provider_type, ProviderType::Ollama);
+        // No API key involved.
+        let provider = instantiate_provider(def).unwrap();
+        assert_eq!(provider.model_name(), "qwen2.5:7b");
+    }
+
+    #[test]
+    fn region_only_valid_for_bedrock() {
+        let toml = r#"
+[providers.aws]
+type = "bedrock"
+model = "us.anthropic.claude-sonnet-4-20250514-v1:0"
+region = "eu-central-1"
+
+[providers.local]
+type = "ollama"
+model = "llama3"
+region = "us-east-1"
+"#;
+        let config: ProvidersConfig = toml::from_str(toml).expect("should parse");
+        assert_eq!(config.providers["aws"].provider_type, ProviderType::Bedrock);
+        let err = config.validate().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'region' is only valid for bedrock")
+        );
+    }
+
     #[test]
     fn parse_with_agents() {
         let toml = r#"
@@ -466,6 +748,185 @@ providers = ["other"]
         assert!(err.to_string().contains("only valid for failover"));
     }
 
+    #[test]
+    fn validate_temperature_range_and_provider_type() {
+        let parse = |toml: &str| toml::from_str::<ProvidersConfig>(toml).expect("should parse");
+        let ok = parse(
+            "[providers.claude]\ntype = \"anthropic\"\nmodel = \"claude-sonnet-4-20250514\"\ntemperature = 0.3\n",
+        );
+        assert!(ok.validate().is_ok());
+        assert_eq!(ok.providers["claude"].temperature, Some(0.3));
+
+        let hot = parse(
+            "[providers.claude]\ntype = \"anthropic\"\nmodel = \"claude-sonnet-4-20250514\"\ntemperature = 1.5\n",
+        );
+        let err = hot.validate().unwrap_err();
+        assert!(err.to_string().contains("between 0.0 and 1.0"));
+
+        let openai =
+            parse("[providers.local]\ntype = \"openai\"\nmodel = \"llama3\"\ntemperature = 0.5\n");
+        let err = openai.validate().unwrap_err();
+        assert!(err.to_string().contains("only supported for anthropic"));
+    }
+
+    #[test]
+    fn validate_max_retries_bound_and_provider_type() {
+        let parse = |toml: &str| toml::from_str::<ProvidersConfig>(toml).expect("should parse");
+        let ok =
+            parse("[providers.local]\ntype = \"ollama\"\nmodel = \"llama3\"\nmax_retries = 0\n");
+        assert!(ok.validate().is_ok());
+        assert_eq!(ok.providers["local"].max_retries, Some(0));
+
+        let many =
+            parse("[providers.local]\ntype = \"openai\"\nmodel = \"gpt-4o\"\nmax_retries = 50\n");
+        let err = many.validate().unwrap_err();
+        assert!(err.to_string().contains("at most 10"));
+
+        let failover = parse(
+            "[providers.a]\ntype = \"openai\"\nmodel = \"gpt-4o\"\n\n\
+             [providers.f]\ntype = \"failover\"\nmodel = \"x\"\nproviders = [\"a\"]\nmax_retries = 1\n",
+        );
+        let err = failover.validate().unwrap_err();
+        assert!(err.to_string().contains("failover child"));
+    }
+
+    #[test]
+    fn validate_commit_fallback_failover_only() {
+        let parse = |toml: &str| toml::from_str::<ProvidersConfig>(toml).expect("should parse");
+        let failover = parse(
+            "[providers.a]\ntype = \"openai\"\nmodel = \"gpt-4o\"\n\n\
+             [providers.f]\ntype = \"failover\"\nmodel = \"x\"\nproviders = [\"a\"]\ncommit_fallback = false\n",
+        );
+        assert!(failover.validate().is_ok());
+        assert_eq!(failover.providers["f"].commit_fallback, Some(false));
+
+        let single = parse(
+            "[providers.a]\ntype = \"openai\"\nmodel = \"gpt-4o\"\ncommit_fallback = false\n",
+        );
+        let err = single.validate().unwrap_err();
+        assert!(err.to_string().contains("only valid for failover"));
+    }
+
+    #[test]
+    fn parse_connection_settings() {
+        let config = ProvidersConfig::parse(
+            r#"
+[providers.default]
+type = "openai"
+model = "gpt-4o"
+base_url = "https://gateway.example.com/v1"
+proxy = "http://proxy.example.com:3128"
+headers = { "X-Gateway-Team" = "infra" }
+
+[providers.claude]
+type = "anthropic"
+model = "claude-sonnet-4-20250514"
+base_url = "https://gateway.example.com/anthropic"
+"#,
+        )
+        .expect("should parse");
+        let def = &config.providers["default"];
+        assert_eq!(def.proxy.as_deref(), Some("http://proxy.example.com:3128"));
+        assert_eq!(def.headers["X-Gateway-Team"], "infra");
+        let provider = config.instantiate_default().expect("should instantiate");
+        assert_eq!(provider.model_name(), "gpt-4o");
+
+        let on_failover = ProvidersConfig::parse(
+            "[providers.a]\ntype = \"openai\"\nmodel = \"gpt-4o\"\n\n\
+             [providers.default]\ntype = \"failover\"\nmodel = \"x\"\nproviders = [\"a\"]\nproxy = \"http://p:1\"\n",
+        );
+        let Err(err) = on_failover else {
+            panic!("connection settings on a failover must be rejected");
+        };
+        assert!(err.to_string().contains("each failover child"));
+    }
+
+    #[test]
+    fn parse_openrouter_routing() {
+        let config = ProvidersConfig::parse(
+            r#"
+[providers.default]
+type = "openrouter"
+model = "anthropic/claude-sonnet-4"
+fallback_models = ["openai/gpt-4o"]
+provider_preferences = { order = ["anthropic", "amazon-bedrock"], data_collection = "deny", sort = "latency" }
+"#,
+        )
+        .expect("should parse");
+        let def = &config.providers["default"];
+        assert_eq!(def.provider_type, ProviderType::Openrouter);
+        assert_eq!(def.fallback_models, ["openai/gpt-4o"]);
+        let preferences = def.provider_preferences.as_ref().unwrap();
+        assert_eq!(preferences.order, ["anthropic", "amazon-bedrock"]);
+        assert_eq!(
+            preferences.data_collection,
+            Some(super::super::openrouter::DataCollection::Deny)
+        );
+
+        let unknown = ProvidersConfig::parse(
+            "[providers.default]\ntype = \"openrouter\"\nmodel = \"x\"\nprovider_preferences = { fastest = true }\n",
+        );
+        assert!(unknown.is_err(), "unknown preference keys are rejected");
+
+        let on_openai = ProvidersConfig::parse(
+            "[providers.default]\ntype = \"openai\"\nmodel = \"gpt-4o\"\nfallback_models = [\"gpt-4o-mini\"]\n",
+        );
+        let Err(err) = on_openai else {
+            panic!("fallback_models outside openrouter must be rejected");
+        };
+        assert!(err.to_string().contains("only valid for openrouter"));
+    }
+
+    #[test]
+    fn parse_pricing_section() {
+        let config = ProvidersConfig::parse(
+            "[providers.default]\ntype = \"ollama\"\nmodel = \"llama3\"\n\n\
+             [pricing.claude-sonnet-4]\ninput_per_mtok = 3.0\noutput_per_mtok = 15.0\ncache_read_per_mtok = 0.3\n",
+        )
+        .expect("should parse");
+        let rates = config.pricing["claude-sonnet-4"];
+        assert!((rates.output_per_mtok - 15.0).abs() < 1e-10);
+        assert!((rates.cache_read_per_mtok - 0.3).abs() < 1e-10);
+        assert!(
+            rates.cache_write_per_mtok.abs() < 1e-10,
+            "omitted rates are zero"
+        );
+
+        let negative = ProvidersConfig::parse(
+            "[providers.default]\ntype = \"ollama\"\nmodel = \"llama3\"\n\n\
+             [pricing.llama3]\ninput_per_mtok = -1.0\noutput_per_mtok = 0.0\n",
+        );
+        let Err(err) = negative else {
+            panic!("negative rate must be rejected");
+        };
+        assert!(err.to_string().contains("non-negative"));
+    }
+
+    #[test]
+    fn parse_validates_and_instantiates_default() {
+        let config = ProvidersConfig::parse(
+            "[providers.default]\ntype = \"ollama\"\nmodel = \"llama3\"\nmax_tokens = 512\n",
+        )
+        .expect("should parse");
+        let provider = config.instantiate_default().expect("should instantiate");
+        assert_eq!(provider.model_name(), "llama3");
+        assert_eq!(provider.max_output_tokens(), 512);
+
+        let invalid = ProvidersConfig::parse(
+            "[providers.default]\ntype = \"ollama\"\nmodel = \"llama3\"\ntemperature = 0.5\n",
+        );
+        assert!(invalid.is_err(), "parse must validate");
+
+        let no_default =
+            ProvidersConfig::parse("[providers.local]\ntype = \"ollama\"\nmodel = \"llama3\"\n")
+                .expect("should parse");
+        let err = no_default
+            .instantiate_default()
+            .err()
+            .expect("no default provider");
+        assert!(err.to_string().contains("[providers.default]"));
+    }
+
     #[test]
     fn validate_agent_references_unknown_provider() {
         let toml = r#"
@@ -491,7 +952,16 @@ system_prompt = "test"
             model: "llama3".to_owned(),
             api_key_env: None,
             base_url: Some("http://localhost:11434/v1".to_owned()),
+            proxy: None,
+            ca_bundle: None,
+            headers: HashMap::new(),
             max_tokens: 2048,
+            region: None,
+            temperature: None,
+            max_retries: None,
+            fallback_models: Vec::new(),
+            provider_preferences: None,
+            commit_fallback: None,
             providers: None,
         };
         let provider = instantiate_provider(&def).expect("should succeed without API key");
@@ -506,7 +976,16 @@ system_prompt = "test"
             model: "claude-sonnet-4-20250514".to_owned(),
             api_key_env: Some("CHERUB_TEST_NONEXISTENT_KEY_12345".to_owned()),
             base_url: None,
+            proxy: None,
+            ca_bundle: None,
+            headers: HashMap::new(),
             max_tokens: 4096,
+            region: None,
+            temperature: None,
+            max_retries: None,
+            fallback_models: Vec::new(),
+            provider_preferences: None,
+            commit_fallback: None,
             providers: None,
         };
         match instantiate_provider(&def) {
@@ -1,16 +1,23 @@
 //! Failover provider: wraps multiple providers and tries them in order.
 //!
-//! On `CherubError::Provider`, the next provider is attempted. Non-Provider
+//! On `CherubError::Provider` or `RetriesExhausted`, the next provider is attempted. Non-Provider
 //! errors (e.g., `NotPermitted`, `InvalidInvocation`) propagate immediately.
-//! A circuit breaker per provider avoids hammering providers that are down.
+//! A circuit breaker per provider avoids hammering providers that are down;
+//! a failed health check trips it straight away.
+//!
+//! Once the conversation has run a Commit-tier action, falling back can be
+//! disabled (`with_commit_fallback(false)`): the conversation then stays on the
+//! provider that was serving it, and its errors surface instead of a different
+//! model silently taking over.
 
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use tracing::{Instrument, info, info_span, warn};
 
-use super::{ApiUsage, Message, Provider, ToolDefinition};
+use super::{ApiUsage, CompletionRequest, Message, MessageDelta, Provider};
 use crate::error::CherubError;
 
 const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
@@ -55,6 +62,11 @@ impl CircuitState {
         self.consecutive_failures = 0;
         self.opened_at = None;
     }
+
+    /// Trip the circuit regardless of the failure count.
+    fn trip(&mut self) {
+        self.opened_at = Some(Instant::now());
+    }
 }
 
 /// Provider that tries multiple child providers in order, with circuit breaker
@@ -70,6 +82,8 @@ pub struct FailoverProvider {
     last_success_idx: Mutex<usize>,
     failure_threshold: u32,
     cooldown: Duration,
+    commit_fallback: bool,
+    commit_tier: AtomicBool,
 }
 
 impl FailoverProvider {
@@ -96,6 +110,8 @@ impl FailoverProvider {
             last_success_idx: Mutex::new(0),
             failure_threshold: DEFAULT_FAILURE_THRESHOLD,
             cooldown: DEFAULT_COOLDOWN,
+            commit_fallback: true,
+            commit_tier: AtomicBool::new(false),
         }
     }
 
@@ -110,79 +126,109 @@ impl FailoverProvider {
         self.cooldown = cooldown;
         self
     }
+
+    /// Whether to keep falling back after the conversation has run a
+    /// Commit-tier action (default true). When false, such a conversation
+    /// stays on the provider that last answered it.
+    pub fn with_commit_fallback(mut self, allowed: bool) -> Self {
+        self.commit_fallback = allowed;
+        self
+    }
+
+    /// Indices of the providers to try for the next request, in order.
+    fn candidates(&self) -> Vec<usize> {
+        let last = *self
+            .last_success_idx
+            .lock()
+            .expect("last_success mutex poisoned");
+        if !self.commit_fallback && self.commit_tier.load(Ordering::Relaxed) {
+            // Pinned: the circuit is ignored, since there is nowhere else to go.
+            return vec![last];
+        }
+
+        let circuits = self.circuits.lock().expect("circuit mutex poisoned");
+        (0..self.providers.len())
+            .filter(|&idx| {
+                let open = circuits[idx].is_open(self.cooldown);
+                if open {
+                    info!(
+                        provider = %self.provider_names[idx],
+                        "skipping provider (circuit open)"
+                    );
+                }
+                !open
+            })
+            .collect()
+    }
+
+    fn record_success(&self, idx: usize) {
+        self.circuits.lock().expect("circuit mutex poisoned")[idx].record_success();
+        *self
+            .last_success_idx
+            .lock()
+            .expect("last_success mutex poisoned") = idx;
+        info!(
+            provider = %self.provider_names[idx],
+            "provider succeeded"
+        );
+    }
+
+    fn record_failure(&self, idx: usize, error: &CherubError) {
+        warn!(
+            provider = %self.provider_names[idx],
+            error = %error,
+            "provider failed"
+        );
+        let mut circuits = self.circuits.lock().expect("circuit mutex poisoned");
+        circuits[idx].record_failure(self.failure_threshold);
+        if circuits[idx].opened_at.is_some() {
+            warn!(
+                provider = %self.provider_names[idx],
+                threshold = self.failure_threshold,
+                "circuit breaker tripped"
+            );
+        }
+    }
+}
+
+/// Errors that mean "this provider is unavailable right now" — worth trying the next one.
+fn is_transient(error: &CherubError) -> bool {
+    matches!(
+        error,
+        CherubError::Provider(_) | CherubError::RetriesExhausted { .. }
+    )
+}
+
+/// Final error once every candidate has failed (or none was tried).
+fn exhausted(last_error: Option<CherubError>) -> CherubError {
+    last_error.unwrap_or_else(|| CherubError::Provider("all providers circuit-broken".to_owned()))
 }
 
 #[async_trait]
 impl Provider for FailoverProvider {
     async fn complete(
         &self,
-        system: &str,
-        messages: &[Message],
-        tools: &[ToolDefinition],
+        request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError> {
         async {
             let mut last_error = None;
 
-            for (idx, provider) in self.providers.iter().enumerate() {
-                // Check circuit breaker — skip if open.
-                {
-                    let circuits = self.circuits.lock().expect("circuit mutex poisoned");
-                    if circuits[idx].is_open(self.cooldown) {
-                        info!(
-                            provider = %self.provider_names[idx],
-                            "skipping provider (circuit open)"
-                        );
-                        continue;
-                    }
-                }
-
+            for idx in self.candidates() {
                 info!(
                     provider = %self.provider_names[idx],
                     idx,
                     "attempting provider"
                 );
 
-                match provider.complete(system, messages, tools).await {
+                match self.providers[idx].complete(request).await {
                     Ok(result) => {
-                        // Record success.
-                        {
-                            let mut circuits =
-                                self.circuits.lock().expect("circuit mutex poisoned");
-                            circuits[idx].record_success();
-                        }
-                        {
-                            let mut last = self
-                                .last_success_idx
-                                .lock()
-                                .expect("last_success mutex poisoned");
-                            *last = idx;
-                        }
-                        info!(
-                            provider = %self.provider_names[idx],
-                            "provider succeeded"
-                        );
+                        self.record_success(idx);
                         return Ok(result);
                     }
-                    Err(CherubError::Provider(ref msg)) => {
+                    Err(e) if is_transient(&e) => {
                         // Transient provider error — record failure, try next.
-                        warn!(
-                            provider = %self.provider_names[idx],
-                            error = %msg,
-                            "provider failed, trying next"
-                        );
-                        {
-                            let mut circuits =
-                                self.circuits.lock().expect("circuit mutex poisoned");
-                            circuits[idx].record_failure(self.failure_threshold);
-                            if circuits[idx].opened_at.is_some() {
-                                warn!(
-                                    provider = %self.provider_names[idx],
-                                    threshold = self.failure_threshold,
-                                    "circuit breaker tripped"
-                                );
-                            }
-                        }
-                        last_error = Some(CherubError::Provider(msg.clone()));
+                        self.record_failure(idx, &e);
+                        last_error = Some(e);
                     }
                     Err(e) => {
                         // Non-Provider error — propagate immediately (e.g., NotPermitted).
@@ -192,14 +238,100 @@ impl Provider for FailoverProvider {
             }
 
             // All providers failed (or were circuit-broken).
-            Err(last_error.unwrap_or_else(|| {
-                CherubError::Provider("all providers circuit-broken".to_owned())
-            }))
+            Err(exhausted(last_error))
         }
         .instrument(info_span!("failover_complete"))
         .await
     }
 
+    /// Falls back only while nothing has been streamed: once a provider has
+    /// reported a delta, its failure is final.
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest<'_>,
+        on_delta: &mut (dyn FnMut(MessageDelta) + Send),
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        async {
+            let mut last_error = None;
+
+            for idx in self.candidates() {
+                info!(
+                    provider = %self.provider_names[idx],
+                    idx,
+                    "attempting provider (streaming)"
+                );
+
+                let mut started = false;
+                let result = {
+                    let mut forward = |delta| {
+                        started = true;
+                        on_delta(delta);
+                    };
+                    self.providers[idx]
+                        .complete_streaming(request, &mut forward)
+                        .await
+                };
+
+                match result {
+                    Ok(result) => {
+                        self.record_success(idx);
+                        return Ok(result);
+                    }
+                    Err(e) if is_transient(&e) => {
+                        self.record_failure(idx, &e);
+                        if started {
+                            return Err(e);
+                        }
+                        last_error = Some(e);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            Err(exhausted(last_error))
+        }
+        .instrument(info_span!("failover_complete_streaming"))
+        .await
+    }
+
+    /// Checks every child, tripping the circuit of each one that fails so the
+    /// first requests skip it. Passes if any child is healthy.
+    async fn health_check(&self) -> Result<(), CherubError> {
+        let mut last_error = None;
+        let mut healthy = false;
+        for (idx, provider) in self.providers.iter().enumerate() {
+            match provider.health_check().await {
+                Ok(()) => healthy = true,
+                Err(e) => {
+                    warn!(
+                        provider = %self.provider_names[idx],
+                        error = %e,
+                        "provider failed health check"
+                    );
+                    self.circuits.lock().expect("circuit mutex poisoned")[idx].trip();
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            Some(e) if !healthy => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn enter_commit_tier(&self) {
+        if !self.commit_tier.swap(true, Ordering::Relaxed) && !self.commit_fallback {
+            let idx = *self
+                .last_success_idx
+                .lock()
+                .expect("last_success mutex poisoned");
+            info!(
+                provider = %self.provider_names[idx],
+                "commit-tier conversation pinned to provider"
+            );
+        }
+    }
+
     fn model_name(&self) -> &str {
         let idx = *self
             .last_success_idx
@@ -221,11 +353,14 @@ impl Provider for FailoverProvider {
 mod tests {
     use super::*;
 
+    type MockResult = Result<(Message, Option<ApiUsage>), CherubError>;
+
     /// A mock provider that returns a configurable result.
     struct MockProvider {
         name: String,
         max_tokens: u32,
-        result: Mutex<Vec<Result<(Message, Option<ApiUsage>), CherubError>>>,
+        result: Mutex<Vec<MockResult>>,
+        healthy: bool,
     }
 
     impl MockProvider {
@@ -234,6 +369,7 @@ mod tests {
                 name: name.to_owned(),
                 max_tokens,
                 result: Mutex::new(Vec::new()),
+                healthy: true,
             }
         }
 
@@ -249,17 +385,21 @@ mod tests {
                 name: name.to_owned(),
                 max_tokens,
                 result: Mutex::new(reversed),
+                healthy: true,
             }
         }
+
+        fn unhealthy(mut self) -> Self {
+            self.healthy = false;
+            self
+        }
     }
 
     #[async_trait]
     impl Provider for MockProvider {
         async fn complete(
             &self,
-            _system: &str,
-            _messages: &[Message],
-            _tools: &[ToolDefinition],
+            _request: &CompletionRequest<'_>,
         ) -> Result<(Message, Option<ApiUsage>), CherubError> {
             let mut results = self.result.lock().unwrap();
             if let Some(result) = results.pop() {
@@ -278,6 +418,14 @@ mod tests {
             }
         }
 
+        async fn health_check(&self) -> Result<(), CherubError> {
+            if self.healthy {
+                Ok(())
+            } else {
+                Err(CherubError::Provider(format!("{} unreachable", self.name)))
+            }
+        }
+
         fn model_name(&self) -> &str {
             &self.name
         }
@@ -287,6 +435,36 @@ mod tests {
         }
     }
 
+    /// Streams one text delta, then fails.
+    struct BrokenStream;
+
+    #[async_trait]
+    impl Provider for BrokenStream {
+        async fn complete(
+            &self,
+            _request: &CompletionRequest<'_>,
+        ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+            provider_err("connection reset")
+        }
+
+        async fn complete_streaming(
+            &self,
+            _request: &CompletionRequest<'_>,
+            on_delta: &mut (dyn FnMut(MessageDelta) + Send),
+        ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+            on_delta(MessageDelta::Text("partial".to_owned()));
+            provider_err("connection reset")
+        }
+
+        fn model_name(&self) -> &str {
+            "broken"
+        }
+
+        fn max_output_tokens(&self) -> u32 {
+            4096
+        }
+    }
+
     fn ok_result(name: &str) -> Result<(Message, Option<ApiUsage>), CherubError> {
         Ok((
             Message::Assistant {
@@ -309,7 +487,9 @@ mod tests {
         let failover = FailoverProvider::new(vec![Box::new(p)], vec!["primary".to_owned()]);
 
         let messages = vec![Message::user_text("hello")];
-        let result = failover.complete("system", &messages, &[]).await;
+        let result = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(result.is_ok());
     }
 
@@ -323,13 +503,38 @@ mod tests {
         );
 
         let messages = vec![Message::user_text("hello")];
-        let result = failover.complete("system", &messages, &[]).await;
+        let result = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(result.is_ok());
 
         // Verify last_success_idx points to the second provider.
         assert_eq!(failover.model_name(), "model-b");
     }
 
+    #[tokio::test]
+    async fn exhausted_retries_fail_over() {
+        let exhausted = Err(CherubError::RetriesExhausted {
+            retries: 3,
+            last_error: "rate limited (429)".to_owned(),
+        });
+        let p1 = MockProvider::with_results("model-a", 4096, vec![exhausted]);
+        let p2 = MockProvider::with_results("model-b", 4096, vec![ok_result("model-b")]);
+        let failover = FailoverProvider::new(
+            vec![Box::new(p1), Box::new(p2)],
+            vec!["primary".to_owned(), "secondary".to_owned()],
+        );
+
+        let messages = vec![Message::user_text("hello")];
+        assert!(
+            failover
+                .complete(&CompletionRequest::new("system", &messages, &[]))
+                .await
+                .is_ok()
+        );
+        assert_eq!(failover.model_name(), "model-b");
+    }
+
     #[tokio::test]
     async fn all_providers_fail() {
         let p1 = MockProvider::with_results("model-a", 4096, vec![provider_err("down-a")]);
@@ -340,7 +545,9 @@ mod tests {
         );
 
         let messages = vec![Message::user_text("hello")];
-        let result = failover.complete("system", &messages, &[]).await;
+        let result = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("down-b"), "should contain last error: {err}");
@@ -360,7 +567,9 @@ mod tests {
         );
 
         let messages = vec![Message::user_text("hello")];
-        let result = failover.complete("system", &messages, &[]).await;
+        let result = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         // Should be InvalidInvocation, not Provider.
@@ -394,15 +603,21 @@ mod tests {
         let messages = vec![Message::user_text("hello")];
 
         // First call: p1 fails, p2 succeeds. p1 has 1 failure.
-        let r1 = failover.complete("system", &messages, &[]).await;
+        let r1 = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(r1.is_ok());
 
         // Second call: p1 fails again, circuit trips (2 failures >= threshold 2). p2 succeeds.
-        let r2 = failover.complete("system", &messages, &[]).await;
+        let r2 = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(r2.is_ok());
 
         // Third call: p1 should be skipped (circuit open). p2 succeeds directly.
-        let r3 = failover.complete("system", &messages, &[]).await;
+        let r3 = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(r3.is_ok());
         assert_eq!(failover.model_name(), "model-b");
     }
@@ -430,14 +645,20 @@ mod tests {
         let messages = vec![Message::user_text("hello")];
 
         // First two calls trip the circuit on p1.
-        let _ = failover.complete("system", &messages, &[]).await;
-        let _ = failover.complete("system", &messages, &[]).await;
+        let _ = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
+        let _ = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
 
         // Wait for cooldown to expire.
         tokio::time::sleep(Duration::from_millis(100)).await;
 
         // Now p1 should be tried again (half-open). It returns ok_result.
-        let r = failover.complete("system", &messages, &[]).await;
+        let r = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(r.is_ok());
         assert_eq!(failover.model_name(), "model-a");
     }
@@ -474,11 +695,15 @@ mod tests {
         assert_eq!(failover.model_name(), "model-a");
 
         // First call: p1 fails, p2 succeeds → last_success_idx = 1.
-        let _ = failover.complete("system", &messages, &[]).await;
+        let _ = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert_eq!(failover.model_name(), "model-b");
 
         // Second call: p1 succeeds → last_success_idx = 0.
-        let _ = failover.complete("system", &messages, &[]).await;
+        let _ = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert_eq!(failover.model_name(), "model-a");
     }
 
@@ -508,16 +733,22 @@ mod tests {
         let messages = vec![Message::user_text("hello")];
 
         // Call 1: p1 fails (count=1), p2 succeeds.
-        let _ = failover.complete("system", &messages, &[]).await;
+        let _ = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
 
         // Call 2: p1 succeeds (count resets to 0).
-        let r = failover.complete("system", &messages, &[]).await;
+        let r = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         assert!(r.is_ok());
         assert_eq!(failover.model_name(), "model-a");
 
         // Verify circuit is not tripped: p1 had 1 failure then 1 success,
         // so count is 0. Now p1 fails again (count=1), still below threshold (2).
-        let _ = failover.complete("system", &messages, &[]).await;
+        let _ = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await;
         // p1 failed, p2 succeeded. But circuit should NOT be open (only 1 failure).
         {
             let circuits = failover.circuits.lock().unwrap();
@@ -527,4 +758,137 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn commit_tier_keeps_fallback_by_default() {
+        let p1 = MockProvider::with_results("model-a", 4096, vec![provider_err("down")]);
+        let p2 = MockProvider::succeeding("model-b", 4096);
+        let failover = FailoverProvider::new(
+            vec![Box::new(p1), Box::new(p2)],
+            vec!["primary".to_owned(), "secondary".to_owned()],
+        );
+
+        failover.enter_commit_tier();
+        let messages = vec![Message::user_text("hello")];
+        assert!(
+            failover
+                .complete(&CompletionRequest::new("system", &messages, &[]))
+                .await
+                .is_ok()
+        );
+        assert_eq!(failover.model_name(), "model-b");
+    }
+
+    #[tokio::test]
+    async fn commit_tier_pins_provider_when_fallback_disabled() {
+        let p1 = MockProvider::with_results(
+            "model-a",
+            4096,
+            vec![ok_result("model-a"), provider_err("rate limited")],
+        );
+        let p2 = MockProvider::succeeding("model-b", 4096);
+        let failover = FailoverProvider::new(
+            vec![Box::new(p1), Box::new(p2)],
+            vec!["primary".to_owned(), "secondary".to_owned()],
+        )
+        .with_commit_fallback(false);
+
+        let messages = vec![Message::user_text("hello")];
+        assert!(
+            failover
+                .complete(&CompletionRequest::new("system", &messages, &[]))
+                .await
+                .is_ok()
+        );
+
+        failover.enter_commit_tier();
+        let err = failover
+            .complete(&CompletionRequest::new("system", &messages, &[]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("rate limited"),
+            "pinned provider's error should surface: {err}"
+        );
+        assert_eq!(failover.model_name(), "model-a");
+    }
+
+    #[tokio::test]
+    async fn failed_health_check_trips_circuit() {
+        let p1 = MockProvider::succeeding("model-a", 4096).unhealthy();
+        let p2 = MockProvider::succeeding("model-b", 4096);
+        let failover = FailoverProvider::new(
+            vec![Box::new(p1), Box::new(p2)],
+            vec!["primary".to_owned(), "secondary".to_owned()],
+        );
+
+        assert!(failover.health_check().await.is_ok());
+
+        // The unhealthy primary is skipped without being asked.
+        let messages = vec![Message::user_text("hello")];
+        assert!(
+            failover
+                .complete(&CompletionRequest::new("system", &messages, &[]))
+                .await
+                .is_ok()
+        );
+        assert_eq!(failover.model_name(), "model-b");
+    }
+
+    #[tokio::test]
+    async fn health_check_fails_when_no_child_is_healthy() {
+        let p1 = MockProvider::succeeding("model-a", 4096).unhealthy();
+        let p2 = MockProvider::succeeding("model-b", 4096).unhealthy();
+        let failover = FailoverProvider::new(
+            vec![Box::new(p1), Box::new(p2)],
+            vec!["primary".to_owned(), "secondary".to_owned()],
+        );
+
+        let err = failover.health_check().await.unwrap_err();
+        assert!(err.to_string().contains("model-b unreachable"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn streaming_falls_back_before_first_delta() {
+        let p1 = MockProvider::with_results("model-a", 4096, vec![provider_err("down")]);
+        let p2 = MockProvider::succeeding("model-b", 4096);
+        let failover = FailoverProvider::new(
+            vec![Box::new(p1), Box::new(p2)],
+            vec!["primary".to_owned(), "secondary".to_owned()],
+        );
+
+        let mut deltas = Vec::new();
+        let messages = vec![Message::user_text("hello")];
+        let result = failover
+            .complete_streaming(
+                &CompletionRequest::new("system", &messages, &[]),
+                &mut |d| deltas.push(d),
+            )
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(
+            deltas,
+            vec![MessageDelta::Text("ok from model-b".to_owned())]
+        );
+    }
+
+    #[tokio::test]
+    async fn streaming_failure_after_delta_is_final() {
+        let p2 = MockProvider::succeeding("model-b", 4096);
+        let failover = FailoverProvider::new(
+            vec![Box::new(BrokenStream), Box::new(p2)],
+            vec!["primary".to_owned(), "secondary".to_owned()],
+        );
+
+        let mut deltas = Vec::new();
+        let messages = vec![Message::user_text("hello")];
+        let result = failover
+            .complete_streaming(
+                &CompletionRequest::new("system", &messages, &[]),
+                &mut |d| deltas.push(d),
+            )
+            .await;
+        assert!(result.is_err(), "output already shown must not be replaced");
+        assert_eq!(deltas, vec![MessageDelta::Text("partial".to_owned())]);
+    }
 }
@@ -0,0 +1,184 @@
+//! Timeouts and cancellation for provider calls.
+//!
+//! A streaming connection that stops sending without closing would otherwise
+//! hold the agent loop until the HTTP client's own limits fire, if ever.
+//! [`complete_guarded`] streams the call and races it against the request's
+//! `timeout` and `cancel` token. Dropping the call's future closes the
+//! connection; the text already streamed is returned in the error so the
+//! caller can show it. Tool calls that had started are discarded — a
+//! half-received invocation must never execute.
+
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use super::{ApiUsage, CompletionRequest, Message, MessageDelta, Provider};
+use crate::error::CherubError;
+
+/// Complete `request` through `provider.complete_streaming`, honoring its
+/// `timeout` and `cancel` token. Deltas are forwarded to `on_delta` as they
+/// arrive. Fails with `ProviderTimeout` or `Cancelled`, carrying the partial
+/// text, when the call is cut off.
+pub async fn complete_guarded(
+    provider: &dyn Provider,
+    request: &CompletionRequest<'_>,
+    on_delta: &mut (dyn FnMut(MessageDelta) + Send),
+) -> Result<(Message, Option<ApiUsage>), CherubError> {
+    if request.timeout.is_none() && request.cancel.is_none() {
+        return provider.complete_streaming(request, on_delta).await;
+    }
+
+    let mut partial = String::new();
+    let mut recording = |delta: MessageDelta| {
+        if let MessageDelta::Text(text) = &delta {
+            partial.push_str(text);
+        }
+        on_delta(delta);
+    };
+    // A missing token never fires; a missing timeout never elapses.
+    let never = CancellationToken::new();
+    let cancel = request.cancel.unwrap_or(&never);
+    let deadline = async {
+        match request.timeout {
+            Some(timeout) => tokio::time::sleep(timeout).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let interruption = tokio::select! {
+        result = provider.complete_streaming(request, &mut recording) => return result,
+        () = cancel.cancelled() => None,
+        () = deadline => request.timeout,
+    };
+    match interruption {
+        Some(after) => {
+            warn!(
+                model = %provider.model_name(),
+                timeout_ms = after.as_millis() as u64,
+                partial_len = partial.len(),
+                "provider call timed out"
+            );
+            Err(CherubError::ProviderTimeout { after, partial })
+        }
+        None => {
+            warn!(
+                model = %provider.model_name(),
+                partial_len = partial.len(),
+                "provider call cancelled"
+            );
+            Err(CherubError::Cancelled { partial })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ContentBlock, StopReason};
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Streams one text delta and, if `hang`, then never finishes.
+    struct Stalling {
+        hang: bool,
+    }
+
+    #[async_trait]
+    impl Provider for Stalling {
+        async fn complete(
+            &self,
+            _request: &CompletionRequest<'_>,
+        ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+            unreachable!("complete_guarded streams")
+        }
+
+        async fn complete_streaming(
+            &self,
+            _request: &CompletionRequest<'_>,
+            on_delta: &mut (dyn FnMut(MessageDelta) + Send),
+        ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+            on_delta(MessageDelta::Text("Partial ans".to_owned()));
+            on_delta(MessageDelta::ToolUseStart {
+                id: "t1".to_owned(),
+                name: "bash".to_owned(),
+            });
+            on_delta(MessageDelta::ToolInput("{\"command\": \"rm".to_owned()));
+            if self.hang {
+                std::future::pending::<()>().await;
+            }
+            Ok((
+                Message::Assistant {
+                    content: vec![ContentBlock::Text {
+                        text: "Partial answer".to_owned(),
+                    }],
+                    stop_reason: StopReason::EndTurn,
+                },
+                None,
+            ))
+        }
+
+        fn model_name(&self) -> &str {
+            "stalling"
+        }
+
+        fn max_output_tokens(&self) -> u32 {
+            1024
+        }
+    }
+
+    #[tokio::test]
+    async fn timeout_returns_partial_text() {
+        let provider = Stalling { hang: true };
+        let messages = [Message::user_text("hi")];
+        let request =
+            CompletionRequest::new("", &messages, &[]).with_timeout(Duration::from_millis(20));
+
+        let mut deltas = 0;
+        let err = complete_guarded(&provider, &request, &mut |_| deltas += 1)
+            .await
+            .unwrap_err();
+        match err {
+            CherubError::ProviderTimeout { after, partial } => {
+                assert_eq!(after, Duration::from_millis(20));
+                assert_eq!(partial, "Partial ans", "tool input is not part of it");
+            }
+            other => panic!("expected a timeout: {other:?}"),
+        }
+        assert_eq!(deltas, 3, "deltas still reach the caller");
+    }
+
+    #[tokio::test]
+    async fn cancel_token_interrupts() {
+        let provider = Stalling { hang: true };
+        let messages = [Message::user_text("hi")];
+        let token = CancellationToken::new();
+        let request = CompletionRequest::new("", &messages, &[]).with_cancel(&token);
+
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+        let err = complete_guarded(&provider, &request, &mut |_| {})
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, CherubError::Cancelled { partial } if partial == "Partial ans"),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn finished_call_is_returned() {
+        let provider = Stalling { hang: false };
+        let messages = [Message::user_text("hi")];
+        let token = CancellationToken::new();
+        let request = CompletionRequest::new("", &messages, &[])
+            .with_timeout(Duration::from_secs(5))
+            .with_cancel(&token);
+
+        let (message, _) = complete_guarded(&provider, &request, &mut |_| {})
+            .await
+            .unwrap();
+        assert!(matches!(message, Message::Assistant { .. }));
+    }
+}
@@ -0,0 +1,146 @@
+//! HTTP client settings shared by the API providers, for corporate proxies
+//! and gateway deployments.
+//!
+//! Without a configured `proxy`, clients honor `HTTP_PROXY`, `HTTPS_PROXY`,
+//! `ALL_PROXY`, and `NO_PROXY` from the environment. An explicit `proxy`
+//! replaces the environment's proxies but still respects `NO_PROXY`.
+//! `ca_bundle` adds trusted roots (a TLS-intercepting proxy's CA) on top of
+//! the built-in ones; `headers` are sent with every request (gateway keys,
+//! routing tags).
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+
+use crate::error::CherubError;
+
+/// Limit for each request a health check makes: a backend that is down or
+/// unreachable should fail startup quickly.
+pub(crate) const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Network settings for a provider's HTTP client. The default is a direct
+/// client with environment proxies and no extra headers.
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    /// Proxy URL for every request (`http://`, `https://`, or `socks5://`).
+    pub proxy: Option<String>,
+    /// PEM file of additional CA certificates to trust.
+    pub ca_bundle: Option<PathBuf>,
+    /// Headers added to every request.
+    pub headers: HashMap<String, String>,
+}
+
+/// A client builder with `config` applied and no timeouts set.
+pub(crate) fn client_builder(config: &HttpConfig) -> Result<ClientBuilder, CherubError> {
+    let mut builder = Client::builder();
+
+    if let Some(ref url) = config.proxy {
+        let proxy = Proxy::all(url)
+            .map_err(|e| CherubError::Config(format!("invalid proxy '{url}': {e}")))?
+            .no_proxy(NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ref path) = config.ca_bundle {
+        let pem = std::fs::read(path).map_err(|e| {
+            CherubError::Config(format!("cannot read CA bundle {}: {e}", path.display()))
+        })?;
+        let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| {
+            CherubError::Config(format!("invalid CA bundle {}: {e}", path.display()))
+        })?;
+        if certificates.is_empty() {
+            return Err(CherubError::Config(format!(
+                "CA bundle {} contains no certificates",
+                path.display()
+            )));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if !config.headers.is_empty() {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            let header = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| CherubError::Config(format!("invalid header name '{name}'")))?;
+            // Values may be credentials: never echo them in the error.
+            let mut value = HeaderValue::from_str(value)
+                .map_err(|_| CherubError::Config(format!("invalid value for header '{name}'")))?;
+            value.set_sensitive(true);
+            headers.insert(header, value);
+        }
+        builder = builder.default_headers(headers);
+    }
+
+    Ok(builder)
+}
+
+/// The client every API provider uses: `config` plus the standard timeouts.
+pub(crate) fn api_client(config: &HttpConfig) -> Result<Client, CherubError> {
+    client_builder(config)?
+        .connect_timeout(Duration::from_secs(10))
+        .read_timeout(Duration::from_secs(30))
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| CherubError::Provider(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_builds() {
+        assert!(api_client(&HttpConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn invalid_settings_rejected() {
+        let proxy = HttpConfig {
+            proxy: Some("not a url".to_owned()),
+            ..HttpConfig::default()
+        };
+        assert!(matches!(api_client(&proxy), Err(CherubError::Config(_))));
+
+        let header = HttpConfig {
+            headers: HashMap::from([("bad header".to_owned(), "v".to_owned())]),
+            ..HttpConfig::default()
+        };
+        assert!(matches!(api_client(&header), Err(CherubError::Config(_))));
+
+        let secret = HttpConfig {
+            headers: HashMap::from([("x-api-key".to_owned(), "line\nbreak-secret".to_owned())]),
+            ..HttpConfig::default()
+        };
+        let err = api_client(&secret).unwrap_err().to_string();
+        assert!(!err.contains("secret"), "{err}");
+    }
+
+    #[test]
+    fn ca_bundle_must_hold_certificates() {
+        let path = std::env::temp_dir().join(format!("cherub-ca-{}.pem", uuid::Uuid::now_v7()));
+        std::fs::write(&path, "no certificates here\n").unwrap();
+        let config = HttpConfig {
+            ca_bundle: Some(path.clone()),
+            ..HttpConfig::default()
+        };
+        let err = api_client(&config).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.to_string().contains("no certificates"), "{err}");
+
+        let missing = HttpConfig {
+            ca_bundle: Some(path),
+            ..HttpConfig::default()
+        };
+        assert!(
+            api_client(&missing)
+                .unwrap_err()
+                .to_string()
+                .contains("cannot read")
+        );
+    }
+}
@@ -1,18 +1,39 @@
 pub mod anthropic;
+pub mod batch;
+pub mod bedrock;
+pub(crate) mod bedrock_wire;
+pub mod cache;
 pub mod config;
 pub mod failover;
+pub mod guard;
+pub mod http;
+pub mod layer;
+pub mod ollama;
 pub mod openai;
 pub(crate) mod openai_wire;
+pub mod openrouter;
 pub mod pricing;
+pub mod sigv4;
+pub(crate) mod sse;
+pub mod structured;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub(crate) mod wire;
 
+use std::path::Path;
+use std::time::Duration;
+
 use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
 
 use crate::error::CherubError;
 
-/// Token usage reported by the API after a completion call.
-#[derive(Debug, Clone, Copy)]
+/// Token usage reported by the API after a completion call. Sums with `+=`
+/// (see `AgentLoop::session_usage`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ApiUsage {
     pub input_tokens: u32,
     pub output_tokens: u32,
@@ -34,6 +55,19 @@ impl ApiUsage {
     }
 }
 
+impl std::ops::AddAssign for ApiUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+        self.cache_creation_tokens = self
+            .cache_creation_tokens
+            .saturating_add(other.cache_creation_tokens);
+        self.cache_read_tokens = self
+            .cache_read_tokens
+            .saturating_add(other.cache_read_tokens);
+    }
+}
+
 /// Abstraction over LLM providers. Object-safe via `async_trait` to enable
 /// `Box<dyn Provider>` (composite providers, failover, runtime selection).
 /// `dyn Provider` is a legitimate extension boundary per project convention.
@@ -41,11 +75,53 @@ impl ApiUsage {
 pub trait Provider: Send + Sync {
     async fn complete(
         &self,
-        system: &str,
-        messages: &[Message],
-        tools: &[ToolDefinition],
+        request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError>;
 
+    /// Like `complete`, but reports the message to `on_delta` as it is
+    /// generated, so a UI can render text early and see tool calls start.
+    /// Returns the same assembled message as `complete`.
+    ///
+    /// The default replays a finished `complete` as deltas; providers that
+    /// can stream override it. Deltas already reported are never retracted,
+    /// so a streaming override does not retry once output has started.
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest<'_>,
+        on_delta: &mut (dyn FnMut(MessageDelta) + Send),
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        let (message, usage) = self.complete(request).await?;
+        replay_deltas(&message, on_delta);
+        Ok((message, usage))
+    }
+
+    /// Verify that the backend is reachable, accepts the credentials, and
+    /// serves the configured model, so a bad key or a mistyped model fails
+    /// at startup rather than on the first turn. The default checks
+    /// `model_name` against `list_models` when the backend can list them.
+    async fn health_check(&self) -> Result<(), CherubError> {
+        let Some(models) = self.list_models().await? else {
+            return Ok(());
+        };
+        let model = self.model_name();
+        if models.iter().any(|m| m == model) {
+            Ok(())
+        } else {
+            Err(model_unavailable(model, &models))
+        }
+    }
+
+    /// Models these credentials can use, or `None` when the backend cannot
+    /// list them.
+    async fn list_models(&self) -> Result<Option<Vec<String>>, CherubError> {
+        Ok(None)
+    }
+
+    /// Called by the runtime once the conversation has executed a
+    /// Commit-tier action. Composite providers use it to stop switching
+    /// models mid-conversation; the default ignores it.
+    fn enter_commit_tier(&self) {}
+
     /// The model identifier string (e.g. "claude-sonnet-4-20250514").
     fn model_name(&self) -> &str;
 
@@ -53,7 +129,125 @@ pub trait Provider: Send + Sync {
     fn max_output_tokens(&self) -> u32;
 }
 
-/// Content within a user message. Supports text and images for multimodal input.
+/// One completion call: the conversation so far, plus generation settings
+/// that override the provider's configuration for this call only. Lets the
+/// caller vary sampling per turn, e.g. a low temperature for tool selection.
+#[derive(Debug, Clone, Copy)]
+pub struct CompletionRequest<'a> {
+    pub system: &'a str,
+    pub messages: &'a [Message],
+    pub tools: &'a [ToolDefinition],
+    /// Model to use instead of the provider's configured one.
+    pub model: Option<&'a str>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    /// Strings that end generation when produced. Empty leaves the API default.
+    pub stop_sequences: &'a [String],
+    /// Output token limit instead of the provider's configured one.
+    pub max_tokens: Option<u32>,
+    /// Give up on the call after this long. Enforced by
+    /// [`guard::complete_guarded`], which surfaces any partial output.
+    pub timeout: Option<Duration>,
+    /// Abandon the call when this token is cancelled. Enforced by
+    /// [`guard::complete_guarded`], like `timeout`.
+    pub cancel: Option<&'a CancellationToken>,
+}
+
+impl<'a> CompletionRequest<'a> {
+    /// A request with no overrides: the provider's configuration applies.
+    pub fn new(system: &'a str, messages: &'a [Message], tools: &'a [ToolDefinition]) -> Self {
+        Self {
+            system,
+            messages,
+            tools,
+            model: None,
+            temperature: None,
+            top_p: None,
+            stop_sequences: &[],
+            max_tokens: None,
+            timeout: None,
+            cancel: None,
+        }
+    }
+
+    pub fn with_model(mut self, model: &'a str) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    pub fn with_stop_sequences(mut self, stop_sequences: &'a [String]) -> Self {
+        self.stop_sequences = stop_sequences;
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: &'a CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+}
+
+/// How many model names a health-check error lists before eliding the rest.
+const LISTED_MODELS: usize = 10;
+
+/// The health-check error for a model the backend does not serve. Long lists
+/// (OpenRouter serves hundreds) are cut short.
+pub(crate) fn model_unavailable(model: &str, available: &[String]) -> CherubError {
+    let mut listed = available
+        .iter()
+        .take(LISTED_MODELS)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if available.len() > LISTED_MODELS {
+        listed.push_str(&format!(", and {} more", available.len() - LISTED_MODELS));
+    }
+    CherubError::Provider(format!(
+        "model '{model}' is not available with these credentials (available: {listed})"
+    ))
+}
+
+/// Report a finished assistant message to `on_delta` as if it had streamed:
+/// each text block whole, each tool call as a start plus its full input.
+pub(crate) fn replay_deltas(message: &Message, on_delta: &mut (dyn FnMut(MessageDelta) + Send)) {
+    if let Message::Assistant { content, .. } = message {
+        for block in content {
+            match block {
+                ContentBlock::Text { text } => on_delta(MessageDelta::Text(text.clone())),
+                ContentBlock::ToolUse { id, name, input } => {
+                    on_delta(MessageDelta::ToolUseStart {
+                        id: id.clone(),
+                        name: name.clone(),
+                    });
+                    on_delta(MessageDelta::ToolInput(input.to_string()));
+                }
+            }
+        }
+    }
+}
+
+/// Content within a user message. Supports text, images, and documents for
+/// multimodal input. Binary data is held base64-encoded, as every provider
+/// sends it; `from_path` loads a file from disk.
 ///
 /// Uses adjacent tagging (`tag` + `content`) because `Text(String)` is a newtype
 /// variant — internal tagging can't serialize a newtype containing a scalar.
@@ -62,7 +256,66 @@ pub trait Provider: Send + Sync {
 #[serde(tag = "type", content = "content", rename_all = "snake_case")]
 pub enum UserContent {
     Text(String),
-    Image { media_type: String, data: String },
+    Image {
+        media_type: String,
+        data: String,
+    },
+    /// A PDF. `name` is shown to the model where the provider supports it.
+    Document {
+        media_type: String,
+        data: String,
+        name: Option<String>,
+    },
+}
+
+/// Largest image accepted from disk (the Anthropic API's per-image limit).
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+/// Largest document accepted from disk (the Anthropic API's request limit).
+const MAX_DOCUMENT_BYTES: u64 = 32 * 1024 * 1024;
+
+impl UserContent {
+    /// Load an image (`png`, `jpg`/`jpeg`, `gif`, `webp`) or PDF document
+    /// from disk, typed by extension. Blocking read; files are size-capped.
+    pub fn from_path(path: &Path) -> Result<Self, CherubError> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        let (media_type, limit) = match ext.as_str() {
+            "png" => ("image/png", MAX_IMAGE_BYTES),
+            "jpg" | "jpeg" => ("image/jpeg", MAX_IMAGE_BYTES),
+            "gif" => ("image/gif", MAX_IMAGE_BYTES),
+            "webp" => ("image/webp", MAX_IMAGE_BYTES),
+            "pdf" => ("application/pdf", MAX_DOCUMENT_BYTES),
+            _ => {
+                return Err(CherubError::Attachment(format!(
+                    "unsupported file type: {}",
+                    path.display()
+                )));
+            }
+        };
+        let unreadable =
+            |e: std::io::Error| CherubError::Attachment(format!("{}: {e}", path.display()));
+        let size = std::fs::metadata(path).map_err(unreadable)?.len();
+        if size > limit {
+            return Err(CherubError::Attachment(format!(
+                "{} is {size} bytes, over the {limit}-byte limit",
+                path.display()
+            )));
+        }
+        let data = BASE64.encode(std::fs::read(path).map_err(unreadable)?);
+        let media_type = media_type.to_owned();
+        Ok(if media_type.starts_with("image/") {
+            UserContent::Image { media_type, data }
+        } else {
+            UserContent::Document {
+                media_type,
+                data,
+                name: path.file_name().map(|n| n.to_string_lossy().into_owned()),
+            }
+        })
+    }
 }
 
 /// Content blocks within an assistant message.
@@ -79,6 +332,18 @@ pub enum ContentBlock {
     },
 }
 
+/// An increment of an assistant message, reported while it streams in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageDelta {
+    /// Text appended to the current text block.
+    Text(String),
+    /// A tool-use block started. Its input follows as `ToolInput` fragments.
+    ToolUseStart { id: String, name: String },
+    /// A fragment of the current tool call's JSON input; valid JSON only once
+    /// all fragments are joined.
+    ToolInput(String),
+}
+
 /// Messages exchanged between the runtime and LLM providers.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "role", rename_all = "snake_case")]
@@ -116,8 +381,51 @@ pub enum StopReason {
 }
 
 /// Schema definition for a tool, sent to the provider so the model knows what tools are available.
+#[derive(Debug)]
 pub struct ToolDefinition {
     pub(crate) name: String,
     pub(crate) description: String,
     pub(crate) input_schema: serde_json::Value,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_path_types_files_by_extension() {
+        let dir = std::env::temp_dir().join(format!("cherub-attach-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = dir.join("shot.PNG");
+        let pdf = dir.join("spec.pdf");
+        let txt = dir.join("notes.txt");
+        std::fs::write(&png, b"\x89PNG").unwrap();
+        std::fs::write(&pdf, b"%PDF-1.7").unwrap();
+        std::fs::write(&txt, b"hi").unwrap();
+
+        assert_eq!(
+            UserContent::from_path(&png).unwrap(),
+            UserContent::Image {
+                media_type: "image/png".to_owned(),
+                data: "iVBORw==".to_owned(),
+            }
+        );
+        assert_eq!(
+            UserContent::from_path(&pdf).unwrap(),
+            UserContent::Document {
+                media_type: "application/pdf".to_owned(),
+                data: "JVBERi0xLjc=".to_owned(),
+                name: Some("spec.pdf".to_owned()),
+            }
+        );
+        assert!(matches!(
+            UserContent::from_path(&txt),
+            Err(CherubError::Attachment(_))
+        ));
+        assert!(matches!(
+            UserContent::from_path(&dir.join("missing.png")),
+            Err(CherubError::Attachment(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+//! Local model provider: an Ollama or llama.cpp server, for running offline.
+//!
+//! Both servers speak the OpenAI Chat Completions API under `/v1`, so
+//! completions go through `OpenAiProvider` with no API key. What this adds is
+//! knowing the server is local: `list_models` reads `/v1/models`, and
+//! `health_check` fails fast at startup when the server is down or the model
+//! has not been pulled, instead of after a round of connection retries on the
+//! first turn.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use tracing::info;
+
+use super::http::{self, HttpConfig};
+use super::openai::OpenAiProvider;
+use super::openai_wire::ModelList;
+use super::{ApiUsage, CompletionRequest, Message, Provider};
+use crate::error::CherubError;
+use crate::retry::RetryConfig;
+
+/// Ollama's default address. llama.cpp's server listens on :8080.
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434/v1";
+
+/// A local server is either up or not; don't wait long to find out.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct OllamaProvider {
+    inner: OpenAiProvider,
+    client: Client,
+    base_url: String,
+}
+
+impl OllamaProvider {
+    pub fn new(model: &str, max_tokens: u32) -> Result<Self, CherubError> {
+        let client = health_client(&HttpConfig::default())?;
+        Ok(Self {
+            inner: OpenAiProvider::new(None, model, max_tokens)?
+                .with_base_url(DEFAULT_BASE_URL.to_owned()),
+            client,
+            base_url: DEFAULT_BASE_URL.to_owned(),
+        })
+    }
+
+    /// Override the server URL, including the `/v1` suffix.
+    pub fn with_base_url(mut self, url: String) -> Self {
+        self.inner = self.inner.with_base_url(url.clone());
+        self.base_url = url;
+        self
+    }
+
+    /// Override the retry behavior (attempts, backoff bounds).
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.inner = self.inner.with_retry_config(config);
+        self
+    }
+
+    /// Rebuild the HTTP clients with proxy, CA bundle, and header settings.
+    pub fn with_http_config(mut self, config: &HttpConfig) -> Result<Self, CherubError> {
+        self.inner = self.inner.with_http_config(config)?;
+        self.client = health_client(config)?;
+        Ok(self)
+    }
+
+    /// Models the server can run (Ollama: pulled models, as `name:tag`).
+    async fn fetch_models(&self) -> Result<Vec<String>, CherubError> {
+        let unreachable = |e: reqwest::Error| {
+            CherubError::Provider(format!(
+                "local model server at {} is not reachable: {e}",
+                self.base_url
+            ))
+        };
+        let list: ModelList = self
+            .client
+            .get(format!("{}/models", self.base_url))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(unreachable)?
+            .json()
+            .await
+            .map_err(|e| CherubError::Provider(format!("invalid model list: {e}")))?;
+        Ok(list.data.into_iter().map(|m| m.id).collect())
+    }
+}
+
+fn health_client(config: &HttpConfig) -> Result<Client, CherubError> {
+    http::client_builder(config)?
+        .timeout(HEALTH_TIMEOUT)
+        .build()
+        .map_err(|e| CherubError::Provider(e.to_string()))
+}
+
+/// Ollama lists `llama3` as `llama3:latest`.
+fn model_listed(model: &str, listed: &[String]) -> bool {
+    listed
+        .iter()
+        .any(|id| id == model || id.strip_suffix(":latest") == Some(model))
+}
+
+#[async_trait]
+impl Provider for OllamaProvider {
+    async fn complete(
+        &self,
+        request: &CompletionRequest<'_>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        self.inner.complete(request).await
+    }
+
+    /// The server answers and lists the configured model.
+    async fn health_check(&self) -> Result<(), CherubError> {
+        let models = self.fetch_models().await?;
+        let model = self.model_name();
+        if !model_listed(model, &models) {
+            return Err(CherubError::Provider(format!(
+                "model '{model}' is not available on the local server (available: {})",
+                models.join(", ")
+            )));
+        }
+        info!(url = %self.base_url, models = models.len(), "local model server healthy");
+        Ok(())
+    }
+
+    async fn list_models(&self) -> Result<Option<Vec<String>>, CherubError> {
+        self.fetch_models().await.map(Some)
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn max_output_tokens(&self) -> u32 {
+        self.inner.max_output_tokens()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_matches_with_implicit_latest_tag() {
+        let listed = ["llama3:latest".to_owned(), "qwen2.5:7b".to_owned()];
+        assert!(model_listed("llama3", &listed));
+        assert!(model_listed("llama3:latest", &listed));
+        assert!(model_listed("qwen2.5:7b", &listed));
+        assert!(!model_listed("qwen2.5", &listed));
+    }
+}
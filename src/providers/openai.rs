@@ -1,17 +1,24 @@
-use std::time::Duration;
-
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use secrecy::{ExposeSecret, SecretString};
-use tracing::{Instrument, info, info_span, warn};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use tracing::{Instrument, info_span, warn};
 
 use async_trait::async_trait;
 
-use super::openai_wire::{self, ChatCompletionRequest, ChatCompletionResponse, OaiTool};
-use super::{ApiUsage, Message, Provider, ToolDefinition};
+use super::batch::{BatchItem, BatchJob, BatchResult, BatchState};
+use super::http::{self, HttpConfig};
+use super::openai_wire::{
+    self, Batch, BatchCreate, BatchErrorDetail, BatchInputLine, BatchOutputLine, BatchResponse,
+    ChatCompletionRequest, ChatCompletionResponse, FileObject, ModelList, OaiTool,
+};
+use super::{ApiUsage, CompletionRequest, Message, Provider, model_unavailable};
 use crate::error::CherubError;
-use crate::retry::{RetryConfig, RetryVerdict, classify_status, compute_delay};
+use crate::retry::{RetryConfig, send_with_retry};
 
 const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+/// The endpoint batch requests target.
+const BATCH_ENDPOINT: &str = "/v1/chat/completions";
 
 /// OpenAI Chat Completions API provider. Covers any compatible endpoint:
 /// OpenAI, Azure OpenAI, Gemini, Ollama, vLLM, LM Studio, Groq.
@@ -30,12 +37,7 @@ impl OpenAiProvider {
         model: &str,
         max_tokens: u32,
     ) -> Result<Self, CherubError> {
-        let client = Client::builder()
-            .connect_timeout(Duration::from_secs(10))
-            .read_timeout(Duration::from_secs(30))
-            .timeout(Duration::from_secs(120))
-            .build()
-            .map_err(|e| CherubError::Provider(e.to_string()))?;
+        let client = http::api_client(&HttpConfig::default())?;
 
         Ok(Self {
             client,
@@ -47,123 +49,333 @@ impl OpenAiProvider {
         })
     }
 
+    /// Override the retry behavior (attempts, backoff bounds).
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry_config = config;
+        self
+    }
+
+    /// Rebuild the HTTP client with proxy, CA bundle, and header settings.
+    pub fn with_http_config(mut self, config: &HttpConfig) -> Result<Self, CherubError> {
+        self.client = http::api_client(config)?;
+        Ok(self)
+    }
+
     /// Override the base URL. For Ollama, vLLM, LM Studio, Groq, Azure, etc.
     pub fn with_base_url(mut self, url: String) -> Self {
         self.base_url = url;
         self
     }
-}
 
-#[async_trait]
-impl Provider for OpenAiProvider {
-    /// Send a non-streaming completion request to an OpenAI-compatible API.
-    /// Retries on transient errors (429, 5xx) with exponential backoff.
-    async fn complete(
+    /// Add the auth header, only when an API key is present.
+    fn authorized(&self, builder: RequestBuilder) -> RequestBuilder {
+        match self.api_key {
+            Some(ref key) => {
+                builder.header("authorization", format!("Bearer {}", key.expose_secret()))
+            }
+            None => builder,
+        }
+    }
+
+    /// GET `{base_url}/{path}` with the API key, for health checks. `None`
+    /// when the endpoint does not offer it (404/405).
+    pub(crate) async fn get_json<T: DeserializeOwned>(
         &self,
-        system: &str,
-        messages: &[Message],
-        tools: &[ToolDefinition],
-    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
-        async {
-            let wire_messages = openai_wire::messages_to_openai_wire(system, messages);
-            let wire_tools: Vec<OaiTool> = tools.iter().map(OaiTool::from).collect();
-
-            let body = ChatCompletionRequest {
-                model: &self.model,
-                max_tokens: self.max_tokens,
-                messages: wire_messages,
-                tools: wire_tools,
+        path: &str,
+    ) -> Result<Option<T>, CherubError> {
+        let response = self
+            .authorized(self.client.get(format!("{}/{path}", self.base_url)))
+            .timeout(http::HEALTH_CHECK_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| {
+                CherubError::Provider(format!("API at {} unreachable: {e}", self.base_url))
+            })?;
+        let status = response.status();
+        if matches!(status.as_u16(), 404 | 405) {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CherubError::Provider(format!("API error {status}: {body}")));
+        }
+        response
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))
+    }
+
+    fn wire_request<'a>(
+        &'a self,
+        request: &CompletionRequest<'a>,
+        extra: &'a Map<String, Value>,
+    ) -> ChatCompletionRequest<'a> {
+        ChatCompletionRequest {
+            model: request.model.unwrap_or(&self.model),
+            max_tokens: request.max_tokens.unwrap_or(self.max_tokens),
+            temperature: request.temperature,
+            top_p: request.top_p,
+            stop: request.stop_sequences,
+            messages: openai_wire::messages_to_openai_wire(request.system, request.messages),
+            tools: request.tools.iter().map(OaiTool::from).collect(),
+            extra,
+        }
+    }
+
+    /// Send a request through the shared retry layer.
+    async fn send_with(
+        &self,
+        build: impl FnMut() -> RequestBuilder,
+    ) -> Result<reqwest::Response, CherubError> {
+        match send_with_retry(&self.retry_config, build).await {
+            Ok(response) => Ok(response),
+            Err(e) => Err(e
+                .into_error(|status, _, body| format!("API error {status}: {body}"))
+                .await),
+        }
+    }
+
+    // --- Batch API (see `batch`) ---
+
+    /// Upload the requests as a JSONL file, then create a batch over it.
+    pub(crate) async fn submit_batch(
+        &self,
+        items: &[BatchItem<'_>],
+    ) -> Result<BatchJob, CherubError> {
+        let no_extra = Map::new();
+        let mut jsonl = Vec::new();
+        for item in items {
+            let line = BatchInputLine {
+                custom_id: item.custom_id,
+                method: "POST",
+                url: BATCH_ENDPOINT,
+                body: self.wire_request(&item.request, &no_extra),
             };
+            serde_json::to_writer(&mut jsonl, &line)
+                .map_err(|e| CherubError::Provider(format!("JSON serialize error: {e}")))?;
+            jsonl.push(b'\n');
+        }
+
+        // multipart/form-data by hand: two fields don't justify the
+        // `multipart` feature. The boundary is a fresh UUID, so the file cannot contain it.
+        let boundary = format!("cherub-{}", uuid::Uuid::now_v7().simple());
+        let mut form = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"purpose\"\r\n\r\nbatch\r\n\
+             --{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"batch.jsonl\"\r\n\
+             Content-Type: application/jsonl\r\n\r\n"
+        )
+        .into_bytes();
+        form.extend_from_slice(&jsonl);
+        form.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        let files_url = format!("{}/files", self.base_url);
+        let file: FileObject = self
+            .send_with(|| {
+                self.authorized(self.client.post(&files_url))
+                    .header(
+                        "content-type",
+                        format!("multipart/form-data; boundary={boundary}"),
+                    )
+                    .body(form.clone())
+            })
+            .await?
+            .json()
+            .await
+            .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))?;
+
+        let create = serde_json::to_vec(&BatchCreate {
+            input_file_id: &file.id,
+            endpoint: BATCH_ENDPOINT,
+            completion_window: "24h",
+        })
+        .map_err(|e| CherubError::Provider(format!("JSON serialize error: {e}")))?;
+        let batches_url = format!("{}/batches", self.base_url);
+        let response = self
+            .send_with(|| {
+                self.authorized(self.client.post(&batches_url))
+                    .header("content-type", "application/json")
+                    .body(create.clone())
+            })
+            .await?;
+        batch_job(response).await
+    }
+
+    pub(crate) async fn batch_status(&self, id: &str) -> Result<BatchJob, CherubError> {
+        let url = format!("{}/batches/{id}", self.base_url);
+        let response = self
+            .send_with(|| self.authorized(self.client.get(&url)))
+            .await?;
+        batch_job(response).await
+    }
+
+    pub(crate) async fn cancel_batch(&self, id: &str) -> Result<BatchJob, CherubError> {
+        let url = format!("{}/batches/{id}/cancel", self.base_url);
+        let response = self
+            .send_with(|| self.authorized(self.client.post(&url)))
+            .await?;
+        batch_job(response).await
+    }
+
+    /// Read the output file (successes) and the error file (failures).
+    pub(crate) async fn batch_results(
+        &self,
+        job: &BatchJob,
+    ) -> Result<Vec<BatchResult>, CherubError> {
+        let mut results = Vec::new();
+        for file_id in &job.result_locations {
+            let url = format!("{}/files/{file_id}/content", self.base_url);
+            let body = self
+                .send_with(|| self.authorized(self.client.get(&url)))
+                .await?
+                .text()
+                .await
+                .map_err(|e| CherubError::Provider(format!("failed to read batch results: {e}")))?;
+            for line in body.lines().filter(|line| !line.trim().is_empty()) {
+                let line: BatchOutputLine = serde_json::from_str(line).map_err(|e| {
+                    CherubError::Provider(format!("invalid batch result line: {e}"))
+                })?;
+                results.push(BatchResult {
+                    custom_id: line.custom_id,
+                    outcome: batch_outcome(line.response, line.error),
+                });
+            }
+        }
+        Ok(results)
+    }
 
+    /// `complete` with `extra` merged into the top level of the request body.
+    pub(crate) async fn complete_with_extra(
+        &self,
+        request: &CompletionRequest<'_>,
+        extra: &Map<String, Value>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        async {
+            let body = self.wire_request(request, extra);
             let json_body = serde_json::to_vec(&body)
                 .map_err(|e| CherubError::Provider(format!("JSON serialize error: {e}")))?;
 
             let url = format!("{}/chat/completions", self.base_url);
 
-            for attempt in 0..=self.retry_config.max_retries {
-                let mut req = self
-                    .client
-                    .post(&url)
+            let request = || {
+                self.authorized(self.client.post(&url))
                     .header("content-type", "application/json")
-                    .body(json_body.clone());
-
-                // Add auth header only when an API key is present.
-                if let Some(ref key) = self.api_key {
-                    req = req.header("authorization", format!("Bearer {}", key.expose_secret()));
+                    .body(json_body.clone())
+            };
+            let response = match send_with_retry(&self.retry_config, request).await {
+                Ok(response) => response,
+                Err(e) => {
+                    return Err(e
+                        .into_error(|status, _, body| format!("API error {status}: {body}"))
+                        .await);
                 }
+            };
 
-                let result = req.send().await;
-
-                let response = match result {
-                    Ok(r) => r,
-                    Err(e)
-                        if (e.is_connect() || e.is_timeout())
-                            && attempt < self.retry_config.max_retries =>
-                    {
-                        let delay = compute_delay(&self.retry_config, attempt);
-                        warn!(
-                            error = %e,
-                            attempt,
-                            delay_ms = delay.as_millis() as u64,
-                            "retrying API call (connection/timeout error)"
-                        );
-                        tokio::time::sleep(delay).await;
-                        continue;
-                    }
-                    Err(e) => {
-                        let retries = attempt;
-                        return Err(CherubError::Provider(format!(
-                            "connection error: {e} (after {retries} retries)"
-                        )));
-                    }
-                };
-
-                let status = response.status().as_u16();
-                info!(status);
-
-                match classify_status(status) {
-                    RetryVerdict::Success => {
-                        let resp: ChatCompletionResponse = response
-                            .json()
-                            .await
-                            .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))?;
-
-                        return Ok(openai_wire::openai_response_to_message(resp));
-                    }
-                    RetryVerdict::Transient(_) if attempt < self.retry_config.max_retries => {
-                        let retry_after = response
-                            .headers()
-                            .get("retry-after")
-                            .and_then(|v| v.to_str().ok())
-                            .and_then(|s| s.parse::<u64>().ok())
-                            .map(Duration::from_secs);
-
-                        let delay = retry_after
-                            .unwrap_or_else(|| compute_delay(&self.retry_config, attempt));
-                        warn!(
-                            status,
-                            attempt,
-                            delay_ms = delay.as_millis() as u64,
-                            "retrying API call"
-                        );
-                        tokio::time::sleep(delay).await;
-                    }
-                    RetryVerdict::Transient(_) | RetryVerdict::Permanent => {
-                        let body_text = response.text().await.unwrap_or_default();
-                        let retries = attempt;
-                        warn!(status, "API error response");
-                        return Err(CherubError::Provider(format!(
-                            "API error {status}: {body_text} (after {retries} retries)"
-                        )));
-                    }
-                }
-            }
+            let resp: ChatCompletionResponse = response
+                .json()
+                .await
+                .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))?;
 
-            unreachable!("retry loop exhausted without returning")
+            Ok(openai_wire::openai_response_to_message(resp))
         }
         .instrument(info_span!("api_call", model = %self.model))
         .await
     }
+}
+
+/// Parse a Batch object.
+async fn batch_job(response: reqwest::Response) -> Result<BatchJob, CherubError> {
+    let batch: Batch = response
+        .json()
+        .await
+        .map_err(|e| CherubError::Provider(format!("JSON parse error: {e}")))?;
+    let state = match batch.status.as_str() {
+        "completed" | "expired" | "cancelled" => BatchState::Ended,
+        "failed" => BatchState::Failed(
+            batch
+                .errors
+                .map(|errors| {
+                    errors
+                        .data
+                        .into_iter()
+                        .map(|e| e.message)
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                })
+                .unwrap_or_else(|| "validation failed".to_owned()),
+        ),
+        _ => BatchState::InProgress,
+    };
+    let (total, succeeded, failed) = batch
+        .request_counts
+        .map_or((0, 0, 0), |c| (c.total, c.completed, c.failed));
+    Ok(BatchJob {
+        id: batch.id,
+        state,
+        succeeded,
+        failed,
+        pending: total.saturating_sub(succeeded + failed),
+        result_locations: batch
+            .output_file_id
+            .into_iter()
+            .chain(batch.error_file_id)
+            .collect(),
+    })
+}
+
+/// One output-file line as a result: a 200 response parses as a completion;
+/// anything else is the error it carries.
+fn batch_outcome(
+    response: Option<BatchResponse>,
+    error: Option<BatchErrorDetail>,
+) -> Result<(Message, Option<ApiUsage>), String> {
+    match (response, error) {
+        (Some(response), _) if response.status_code == 200 => {
+            serde_json::from_value::<ChatCompletionResponse>(response.body)
+                .map(openai_wire::openai_response_to_message)
+                .map_err(|e| format!("JSON parse error: {e}"))
+        }
+        (Some(response), _) => Err(format!(
+            "API error {}: {}",
+            response.status_code, response.body
+        )),
+        (None, Some(error)) => Err(error.message),
+        (None, None) => Err("no response".to_owned()),
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAiProvider {
+    /// Send a non-streaming completion request to an OpenAI-compatible API.
+    /// Retries on transient errors (429, 5xx) with exponential backoff.
+    async fn complete(
+        &self,
+        request: &CompletionRequest<'_>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        self.complete_with_extra(request, &Map::new()).await
+    }
+
+    /// Compatible servers name models their own way (Gemini's `models/`
+    /// prefix, Azure deployment names), so only OpenAI's own list is
+    /// authoritative; elsewhere an unlisted model is a warning.
+    async fn health_check(&self) -> Result<(), CherubError> {
+        let Some(models) = self.list_models().await? else {
+            return Ok(());
+        };
+        if models.contains(&self.model) {
+            return Ok(());
+        }
+        if self.base_url == DEFAULT_BASE_URL {
+            return Err(model_unavailable(&self.model, &models));
+        }
+        warn!(model = %self.model, url = %self.base_url, "model not in the endpoint's model list");
+        Ok(())
+    }
+
+    async fn list_models(&self) -> Result<Option<Vec<String>>, CherubError> {
+        let list: Option<ModelList> = self.get_json("models").await?;
+        Ok(list.map(|list| list.data.into_iter().map(|m| m.id).collect()))
+    }
 
     fn model_name(&self) -> &str {
         &self.model
@@ -177,13 +389,14 @@ impl Provider for OpenAiProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::ToolDefinition;
 
     #[test]
     fn request_body_structure() {
         use serde_json::json;
 
         let messages = vec![Message::user_text("hello")];
-        let tools = vec![ToolDefinition {
+        let tools = [ToolDefinition {
             name: "bash".to_owned(),
             description: "Execute bash commands".to_owned(),
             input_schema: json!({
@@ -198,16 +411,24 @@ mod tests {
         let wire_messages = openai_wire::messages_to_openai_wire("You are helpful.", &messages);
         let wire_tools: Vec<_> = tools.iter().map(OaiTool::from).collect();
 
+        let stop = ["END".to_owned()];
         let body = ChatCompletionRequest {
             model: "gpt-4o",
             max_tokens: 4096,
+            temperature: Some(0.0),
+            top_p: Some(0.9),
+            stop: &stop,
             messages: wire_messages,
             tools: wire_tools,
+            extra: &serde_json::Map::new(),
         };
 
         let json = serde_json::to_value(&body).unwrap();
         assert_eq!(json["model"], "gpt-4o");
         assert_eq!(json["max_tokens"], 4096);
+        assert_eq!(json["temperature"], 0.0);
+        assert!((json["top_p"].as_f64().unwrap() - 0.9).abs() < 1e-6);
+        assert_eq!(json["stop"], json!(["END"]));
         assert_eq!(json["messages"][0]["role"], "system");
         assert!(json["tools"].is_array());
         assert_eq!(json["tools"][0]["type"], "function");
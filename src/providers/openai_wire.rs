@@ -12,9 +12,18 @@ use super::{ApiUsage, ContentBlock, Message, StopReason, ToolDefinition, UserCon
 pub(crate) struct ChatCompletionRequest<'a> {
     pub model: &'a str,
     pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    pub stop: &'a [String],
     pub messages: Vec<OaiMessage>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<OaiTool>,
+    /// Endpoint-specific top-level fields (OpenRouter routing).
+    #[serde(flatten)]
+    pub extra: &'a serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Serialize, Debug)]
@@ -43,6 +52,8 @@ pub(crate) enum OaiContentPart {
     Text { text: String },
     #[serde(rename = "image_url")]
     ImageUrl { image_url: OaiImageUrl },
+    #[serde(rename = "file")]
+    File { file: OaiFile },
 }
 
 #[derive(Serialize, Debug)]
@@ -50,6 +61,14 @@ pub(crate) struct OaiImageUrl {
     pub url: String,
 }
 
+#[derive(Serialize, Debug)]
+pub(crate) struct OaiFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// A `data:` URL.
+    pub file_data: String,
+}
+
 #[derive(Serialize, Debug)]
 pub(crate) struct OaiToolCall {
     pub id: String,
@@ -78,6 +97,76 @@ pub(crate) struct OaiToolFunction {
     pub parameters: serde_json::Value,
 }
 
+// --- Batch API ---
+
+/// One line of a batch input file.
+#[derive(Serialize)]
+pub(crate) struct BatchInputLine<'a> {
+    pub custom_id: &'a str,
+    pub method: &'static str,
+    pub url: &'static str,
+    pub body: ChatCompletionRequest<'a>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct BatchCreate<'a> {
+    pub input_file_id: &'a str,
+    pub endpoint: &'static str,
+    pub completion_window: &'static str,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct FileObject {
+    pub id: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Batch {
+    pub id: String,
+    /// `validating`, `in_progress`, `finalizing`, `completed`, `failed`,
+    /// `expired`, `cancelling`, or `cancelled`.
+    pub status: String,
+    #[serde(default)]
+    pub request_counts: Option<BatchRequestCounts>,
+    #[serde(default)]
+    pub output_file_id: Option<String>,
+    #[serde(default)]
+    pub error_file_id: Option<String>,
+    #[serde(default)]
+    pub errors: Option<BatchErrors>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BatchRequestCounts {
+    pub total: u32,
+    pub completed: u32,
+    pub failed: u32,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BatchErrors {
+    pub data: Vec<BatchErrorDetail>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BatchErrorDetail {
+    pub message: String,
+}
+
+/// One line of a batch output or error file.
+#[derive(Deserialize)]
+pub(crate) struct BatchOutputLine {
+    pub custom_id: String,
+    pub response: Option<BatchResponse>,
+    pub error: Option<BatchErrorDetail>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BatchResponse {
+    pub status_code: u16,
+    pub body: serde_json::Value,
+}
+
 // --- Response types ---
 
 #[derive(Deserialize)]
@@ -114,6 +203,40 @@ pub(crate) struct OaiResponseFunction {
 pub(crate) struct OaiUsage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
+    #[serde(default)]
+    pub prompt_tokens_details: Option<OaiPromptTokensDetails>,
+}
+
+/// `GET /models`: the models the endpoint serves.
+#[derive(Deserialize)]
+pub(crate) struct ModelList {
+    pub data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ModelEntry {
+    pub id: String,
+}
+
+/// Automatic prompt caching (OpenAI). Absent on most compatible servers.
+#[derive(Deserialize, Clone, Copy)]
+pub(crate) struct OaiPromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: u32,
+}
+
+impl From<OaiUsage> for ApiUsage {
+    /// `prompt_tokens` includes cache hits; `ApiUsage` counts them apart, as
+    /// they bill at a discount.
+    fn from(u: OaiUsage) -> Self {
+        let cached = u.prompt_tokens_details.map_or(0, |d| d.cached_tokens);
+        ApiUsage {
+            input_tokens: u.prompt_tokens.saturating_sub(cached),
+            output_tokens: u.completion_tokens,
+            cache_creation_tokens: 0,
+            cache_read_tokens: cached,
+        }
+    }
 }
 
 // --- Conversions ---
@@ -132,18 +255,20 @@ impl From<&ToolDefinition> for OaiTool {
 }
 
 /// Convert internal messages to OpenAI wire format.
-/// System prompt is prepended as a `{"role": "system"}` message.
+/// System prompt is prepended as a `{"role": "system"}` message, unless empty.
 /// Tool results become individual `{"role": "tool"}` messages (no merging needed).
 pub(crate) fn messages_to_openai_wire(system: &str, messages: &[Message]) -> Vec<OaiMessage> {
     let mut wire = Vec::with_capacity(messages.len() + 1);
 
     // System prompt is a system message in the OpenAI format.
-    wire.push(OaiMessage {
-        role: "system",
-        content: OaiContent::Text(system.to_owned()),
-        tool_calls: None,
-        tool_call_id: None,
-    });
+    if !system.is_empty() {
+        wire.push(OaiMessage {
+            role: "system",
+            content: OaiContent::Text(system.to_owned()),
+            tool_calls: None,
+            tool_call_id: None,
+        });
+    }
 
     for msg in messages {
         match msg {
@@ -204,6 +329,16 @@ fn user_content_to_oai(content: &[UserContent]) -> OaiContent {
                     url: format!("data:{media_type};base64,{data}"),
                 },
             },
+            UserContent::Document {
+                media_type,
+                data,
+                name,
+            } => OaiContentPart::File {
+                file: OaiFile {
+                    filename: name.clone(),
+                    file_data: format!("data:{media_type};base64,{data}"),
+                },
+            },
         })
         .collect();
 
@@ -246,9 +381,7 @@ fn assistant_content_to_oai(content: &[ContentBlock]) -> (OaiContent, Vec<OaiToo
 pub(crate) fn openai_response_to_message(
     resp: ChatCompletionResponse,
 ) -> (Message, Option<ApiUsage>) {
-    let usage = resp
-        .usage
-        .map(|u| ApiUsage::new(u.prompt_tokens, u.completion_tokens));
+    let usage = resp.usage.map(ApiUsage::from);
 
     let choice = match resp.choices.into_iter().next() {
         Some(c) => c,
@@ -282,8 +415,9 @@ pub(crate) fn openai_response_to_message(
     // Add tool calls if present.
     if let Some(tool_calls) = choice.message.tool_calls {
         for tc in tool_calls {
-            let input: serde_json::Value =
-                serde_json::from_str(&tc.function.arguments).unwrap_or(serde_json::Value::Null);
+            // Unparseable arguments stay a raw string, for the runtime to refuse.
+            let input = serde_json::from_str(&tc.function.arguments)
+                .unwrap_or(serde_json::Value::String(tc.function.arguments));
             content.push(ContentBlock::ToolUse {
                 id: tc.id,
                 name: tc.function.name,
@@ -314,6 +448,13 @@ mod tests {
         assert_eq!(json["content"], "You are helpful.");
     }
 
+    #[test]
+    fn empty_system_prompt_omitted() {
+        let wire = messages_to_openai_wire("", &[Message::user_text("hello")]);
+        assert_eq!(wire.len(), 1);
+        assert_eq!(wire[0].role, "user");
+    }
+
     #[test]
     fn user_text_message_serializes() {
         let wire = messages_to_openai_wire("sys", &[Message::user_text("hello")]);
@@ -344,6 +485,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn user_document_message_serializes() {
+        let wire = messages_to_openai_wire(
+            "",
+            &[Message::User {
+                content: vec![UserContent::Document {
+                    media_type: "application/pdf".to_owned(),
+                    data: "JVBERi0...".to_owned(),
+                    name: Some("spec.pdf".to_owned()),
+                }],
+            }],
+        );
+        let json = serde_json::to_value(&wire[0]).unwrap();
+        let part = &json["content"][0];
+        assert_eq!(part["type"], "file");
+        assert_eq!(part["file"]["filename"], "spec.pdf");
+        assert_eq!(
+            part["file"]["file_data"],
+            "data:application/pdf;base64,JVBERi0..."
+        );
+    }
+
     #[test]
     fn user_mixed_content_serializes() {
         let wire = messages_to_openai_wire(
@@ -409,6 +572,20 @@ mod tests {
         assert_eq!(json["content"], "file1.txt\nfile2.txt");
     }
 
+    #[test]
+    fn cached_prompt_tokens_split_out() {
+        let usage: OaiUsage = serde_json::from_value(json!({
+            "prompt_tokens": 1200,
+            "completion_tokens": 40,
+            "prompt_tokens_details": {"cached_tokens": 1024}
+        }))
+        .unwrap();
+        let usage = ApiUsage::from(usage);
+        assert_eq!(usage.input_tokens, 176);
+        assert_eq!(usage.cache_read_tokens, 1024);
+        assert_eq!(usage.output_tokens, 40);
+    }
+
     #[test]
     fn response_parsing_text_only() {
         let json_str = r#"{
@@ -559,7 +736,7 @@ mod tests {
     }
 
     #[test]
-    fn malformed_tool_arguments_become_null() {
+    fn malformed_tool_arguments_kept_as_raw_string() {
         let json_str = r#"{
             "choices": [{
                 "message": {
@@ -578,7 +755,7 @@ mod tests {
         match msg {
             Message::Assistant { content, .. } => match &content[0] {
                 ContentBlock::ToolUse { input, .. } => {
-                    assert!(input.is_null());
+                    assert_eq!(input, &json!("not valid json!!!"));
                 }
                 _ => panic!("expected ToolUse"),
             },
@@ -604,7 +781,7 @@ mod tests {
     #[test]
     fn request_body_structure() {
         let messages = vec![Message::user_text("hello")];
-        let tools = vec![ToolDefinition {
+        let tools = [ToolDefinition {
             name: "bash".to_owned(),
             description: "Run bash".to_owned(),
             input_schema: json!({"type": "object"}),
@@ -616,8 +793,12 @@ mod tests {
         let body = ChatCompletionRequest {
             model: "gpt-4o",
             max_tokens: 4096,
+            temperature: None,
+            top_p: None,
+            stop: &[],
             messages: wire_messages,
             tools: wire_tools,
+            extra: &serde_json::Map::new(),
         };
 
         let json = serde_json::to_value(&body).unwrap();
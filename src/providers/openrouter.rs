@@ -0,0 +1,222 @@
+//! OpenRouter provider: many models from many vendors behind one API key.
+//!
+//! OpenRouter speaks the OpenAI Chat Completions API, so requests go through
+//! `OpenAiProvider`. What this adds is routing: `models` lists fallbacks tried
+//! in order when the primary model errors or is unavailable, and `provider`
+//! preferences choose which upstream vendors may serve the request. Both ride
+//! along on every request; [`OpenRouterProvider::complete_routed`] overrides
+//! them for a single call.
+
+use async_trait::async_trait;
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::http::HttpConfig;
+use super::openai::OpenAiProvider;
+use super::{ApiUsage, CompletionRequest, Message, Provider, model_unavailable};
+use crate::error::CherubError;
+use crate::retry::RetryConfig;
+
+pub const DEFAULT_BASE_URL: &str = "https://openrouter.ai/api/v1";
+
+/// Which upstream providers may serve a request, and how to pick among them.
+/// Unset fields leave OpenRouter's defaults (load-balanced, fallbacks allowed).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderPreferences {
+    /// Providers to try first, in order (e.g. `["anthropic", "amazon-bedrock"]`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+    /// Only these providers may serve the request.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+    /// These providers are never used.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// Whether providers outside `order` may be used when those in it fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,
+    /// Only use providers that support every parameter in the request
+    /// (tools, stop sequences).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_parameters: Option<bool>,
+    /// `deny` excludes providers that may store or train on prompts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<DataCollection>,
+    /// Rank providers by this instead of load-balancing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ProviderSort>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataCollection {
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderSort {
+    Price,
+    Throughput,
+    Latency,
+}
+
+/// Routing parameters sent with a request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Routing {
+    /// Models to fall back to, in order, after the request's own model.
+    pub fallback_models: Vec<String>,
+    pub preferences: ProviderPreferences,
+}
+
+impl Routing {
+    /// The top-level request fields for this routing, given the primary model.
+    fn to_body(&self, model: &str) -> Map<String, Value> {
+        let mut body = Map::new();
+        if !self.fallback_models.is_empty() {
+            let models: Vec<&str> = std::iter::once(model)
+                .chain(self.fallback_models.iter().map(String::as_str))
+                .collect();
+            body.insert("models".to_owned(), Value::from(models));
+        }
+        if self.preferences != ProviderPreferences::default() {
+            body.insert(
+                "provider".to_owned(),
+                serde_json::to_value(&self.preferences)
+                    .expect("provider preferences serialize to JSON"),
+            );
+        }
+        body
+    }
+}
+
+pub struct OpenRouterProvider {
+    inner: OpenAiProvider,
+    routing: Routing,
+}
+
+impl OpenRouterProvider {
+    /// `model` is an OpenRouter model slug, e.g. `anthropic/claude-sonnet-4`.
+    pub fn new(api_key: SecretString, model: &str, max_tokens: u32) -> Result<Self, CherubError> {
+        Ok(Self {
+            inner: OpenAiProvider::new(Some(api_key), model, max_tokens)?
+                .with_base_url(DEFAULT_BASE_URL.to_owned()),
+            routing: Routing::default(),
+        })
+    }
+
+    /// Routing sent with every request unless a call overrides it.
+    pub fn with_routing(mut self, routing: Routing) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    /// Override the API URL, including the `/api/v1` suffix.
+    pub fn with_base_url(mut self, url: String) -> Self {
+        self.inner = self.inner.with_base_url(url);
+        self
+    }
+
+    /// Override the retry behavior (attempts, backoff bounds).
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.inner = self.inner.with_retry_config(config);
+        self
+    }
+
+    /// Rebuild the HTTP client with proxy, CA bundle, and header settings.
+    pub fn with_http_config(mut self, config: &HttpConfig) -> Result<Self, CherubError> {
+        self.inner = self.inner.with_http_config(config)?;
+        Ok(self)
+    }
+
+    /// Complete `request` with `routing` in place of the configured routing.
+    pub async fn complete_routed(
+        &self,
+        request: &CompletionRequest<'_>,
+        routing: &Routing,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        let model = request.model.unwrap_or(self.inner.model_name());
+        self.inner
+            .complete_with_extra(request, &routing.to_body(model))
+            .await
+    }
+}
+
+#[async_trait]
+impl Provider for OpenRouterProvider {
+    async fn complete(
+        &self,
+        request: &CompletionRequest<'_>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        self.complete_routed(request, &self.routing).await
+    }
+
+    /// The model list is public, so the key is checked separately.
+    async fn health_check(&self) -> Result<(), CherubError> {
+        self.inner.get_json::<Value>("key").await?;
+        let Some(models) = self.list_models().await? else {
+            return Ok(());
+        };
+        // `:free`, `:nitro` and the like select a variant of a listed model.
+        let model = self.model_name();
+        let base = model.split_once(':').map_or(model, |(base, _)| base);
+        if models.iter().any(|m| m == model || m == base) {
+            Ok(())
+        } else {
+            Err(model_unavailable(model, &models))
+        }
+    }
+
+    async fn list_models(&self) -> Result<Option<Vec<String>>, CherubError> {
+        self.inner.list_models().await
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn max_output_tokens(&self) -> u32 {
+        self.inner.max_output_tokens()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn default_routing_adds_nothing() {
+        assert!(Routing::default().to_body("openai/gpt-4o").is_empty());
+    }
+
+    #[test]
+    fn routing_body_lists_primary_model_first() {
+        let routing = Routing {
+            fallback_models: vec!["openai/gpt-4o".to_owned()],
+            preferences: ProviderPreferences {
+                order: vec!["anthropic".to_owned()],
+                allow_fallbacks: Some(false),
+                data_collection: Some(DataCollection::Deny),
+                sort: Some(ProviderSort::Price),
+                ..ProviderPreferences::default()
+            },
+        };
+        let body = Value::Object(routing.to_body("anthropic/claude-sonnet-4"));
+        assert_eq!(
+            body,
+            json!({
+                "models": ["anthropic/claude-sonnet-4", "openai/gpt-4o"],
+                "provider": {
+                    "order": ["anthropic"],
+                    "allow_fallbacks": false,
+                    "data_collection": "deny",
+                    "sort": "price"
+                }
+            })
+        );
+    }
+}
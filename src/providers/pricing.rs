@@ -1,26 +1,32 @@
 //! Model pricing and cost computation (M12).
 //!
 //! Pricing is a billing concern, decoupled from the `Provider` trait.
-//! Rates live in a DB-backed `model_pricing` table, loaded into an in-memory
-//! `PricingTable` at startup. `lookup_pricing()` finds the best match using
+//! Rates live in a DB-backed `model_pricing` table and/or the providers
+//! config's `[pricing]` section, loaded into an in-memory `PricingTable` at
+//! startup. `lookup_pricing()` finds the best match using
 //! longest-prefix matching against the model name.
 
 use std::collections::HashMap;
 
+use serde::Deserialize;
+
 use super::ApiUsage;
 
 /// Per-model cost rates in USD per million tokens.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ModelPricing {
     pub input_per_mtok: f64,
     pub output_per_mtok: f64,
     /// Cache write rate (e.g. Anthropic: 125% of input rate). 0.0 if no caching.
+    #[serde(default)]
     pub cache_write_per_mtok: f64,
     /// Cache read rate (e.g. Anthropic: 10% of input rate). 0.0 if no caching.
+    #[serde(default)]
     pub cache_read_per_mtok: f64,
 }
 
-/// In-memory pricing table loaded from DB at startup.
+/// In-memory pricing table loaded at startup, keyed by model-name prefix.
 pub type PricingTable = HashMap<String, ModelPricing>;
 
 /// Look up pricing for a model name using longest-prefix match.
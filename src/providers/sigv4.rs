@@ -0,0 +1,206 @@
+//! AWS Signature Version 4 request signing, for the Bedrock provider.
+//!
+//! Hand-rolled over `sha2`/`hmac` rather than pulling in the AWS SDK: a
+//! provider signs one kind of request (a JSON POST with no query string).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Static AWS credentials. Read from the standard environment variables;
+/// profiles, SSO, and instance roles are not resolved.
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: SecretString,
+    /// Present for temporary (STS) credentials.
+    pub session_token: Option<SecretString>,
+}
+
+impl AwsCredentials {
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, and `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+        Some(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: SecretString::from(var("AWS_SECRET_ACCESS_KEY")?),
+            session_token: var("AWS_SESSION_TOKEN").map(SecretString::from),
+        })
+    }
+}
+
+/// `AWS_REGION`, falling back to `AWS_DEFAULT_REGION`.
+pub fn region_from_env() -> Option<String> {
+    ["AWS_REGION", "AWS_DEFAULT_REGION"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+}
+
+/// What is being signed. `path` is as sent on the wire (already
+/// percent-encoded); `headers` must include `host` and `x-amz-date`.
+pub(crate) struct SigningRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub headers: &'a [(&'a str, &'a str)],
+    pub payload: &'a [u8],
+    pub region: &'a str,
+    pub service: &'a str,
+    /// `YYYYMMDDTHHMMSSZ`, matching the `x-amz-date` header.
+    pub amz_date: &'a str,
+}
+
+/// The headers to add to `req`: `authorization`, plus `x-amz-security-token`
+/// (itself signed) for temporary credentials.
+pub(crate) fn sign(
+    req: &SigningRequest<'_>,
+    credentials: &AwsCredentials,
+) -> Vec<(&'static str, String)> {
+    let token = credentials
+        .session_token
+        .as_ref()
+        .map(|token| token.expose_secret().to_owned());
+    let mut headers: Vec<(String, &str)> = req
+        .headers
+        .iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.trim()))
+        .chain(
+            token
+                .as_deref()
+                .map(|token| ("x-amz-security-token".to_owned(), token)),
+        )
+        .collect();
+    headers.sort();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    // Services other than S3 sign each path segment encoded a second time.
+    let canonical_uri = req.path.replace('%', "%25");
+    let canonical_request = format!(
+        "{}\n{canonical_uri}\n\n{canonical_headers}\n{signed_headers}\n{}",
+        req.method,
+        hex(&Sha256::digest(req.payload)),
+    );
+
+    let date = &req.amz_date[..8];
+    let scope = format!("{date}/{}/{}/aws4_request", req.region, req.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{scope}\n{}",
+        req.amz_date,
+        hex(&Sha256::digest(canonical_request.as_bytes())),
+    );
+
+    let secret = format!("AWS4{}", credentials.secret_access_key.expose_secret());
+    let key = [date, req.region, req.service, "aws4_request"]
+        .iter()
+        .fold(secret.into_bytes(), |key, part| hmac(&key, part.as_bytes()));
+    let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        credentials.access_key_id
+    );
+    let mut signed = vec![("authorization", authorization)];
+    if let Some(token) = token {
+        signed.push(("x-amz-security-token", token));
+    }
+    signed
+}
+
+/// The current time as `YYYYMMDDTHHMMSSZ`.
+pub(crate) fn amz_date(now: SystemTime) -> String {
+    let secs = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since the epoch (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let (era, doe) = (z / 146_097, z % 146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn matches_aws_test_suite_get_vanilla() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_owned(),
+            secret_access_key: SecretString::from("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+            session_token: None,
+        };
+        let req = SigningRequest {
+            method: "GET",
+            path: "/",
+            headers: &[
+                ("Host", "example.amazonaws.com"),
+                ("X-Amz-Date", "20150830T123600Z"),
+            ],
+            payload: b"",
+            region: "us-east-1",
+            service: "service",
+            amz_date: "20150830T123600Z",
+        };
+        assert_eq!(
+            sign(&req, &credentials),
+            [(
+                "authorization",
+                "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+                 SignedHeaders=host;x-amz-date, \
+                 Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+                    .to_owned()
+            )]
+        );
+
+        let temporary = AwsCredentials {
+            session_token: Some(SecretString::from("session")),
+            ..credentials
+        };
+        let signed = sign(&req, &temporary);
+        assert!(
+            signed[0]
+                .1
+                .contains("SignedHeaders=host;x-amz-date;x-amz-security-token,")
+        );
+        assert_eq!(signed[1], ("x-amz-security-token", "session".to_owned()));
+    }
+
+    #[test]
+    fn formats_amz_date() {
+        let at = UNIX_EPOCH + Duration::from_secs(1_440_938_160); // 2015-08-30 12:36:00 UTC
+        assert_eq!(amz_date(at), "20150830T123600Z");
+        assert_eq!(amz_date(UNIX_EPOCH), "19700101T000000Z");
+        let leap = UNIX_EPOCH + Duration::from_secs(1_709_164_799); // 2024-02-28 23:59:59 UTC
+        assert_eq!(amz_date(leap), "20240228T235959Z");
+    }
+}
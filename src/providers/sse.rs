@@ -0,0 +1,85 @@
+//! Server-sent events framing for streaming responses (`text/event-stream`).
+//!
+//! Only the `event` and `data` fields are used; comments, `id`, and `retry`
+//! are ignored. Events may be split across network chunks arbitrarily.
+
+/// One dispatched event.
+#[derive(Debug, PartialEq)]
+pub(crate) struct SseEvent {
+    pub event: Option<String>,
+    /// `data` lines, joined with `\n`.
+    pub data: String,
+}
+
+/// Incremental decoder: feed body chunks as they arrive, get whole events.
+#[derive(Default)]
+pub(crate) struct SseDecoder {
+    buf: Vec<u8>,
+}
+
+impl SseDecoder {
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        // CRLF line endings are normalized away; a lone CR is not supported.
+        self.buf
+            .extend(chunk.iter().copied().filter(|&b| b != b'\r'));
+        let mut events = Vec::new();
+        while let Some(end) = self.buf.windows(2).position(|w| w == b"\n\n") {
+            let frame: Vec<u8> = self.buf.drain(..end + 2).collect();
+            if let Some(event) = parse_frame(&String::from_utf8_lossy(&frame[..end])) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+/// An event with no `data` lines is not dispatched.
+fn parse_frame(frame: &str) -> Option<SseEvent> {
+    let mut event = None;
+    let mut data: Option<String> = None;
+    for line in frame.lines() {
+        if line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event = Some(value.to_owned()),
+            "data" => match &mut data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.to_owned()),
+            },
+            _ => {}
+        }
+    }
+    Some(SseEvent { event, data: data? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_reassembled_across_chunks() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(b"event: ping\r\ndata: {\"ty").is_empty());
+        let events = decoder.push(b"pe\":\"ping\"}\r\n\r\n: keepalive\n\ndata: a\ndata: b\n\n");
+        assert_eq!(
+            events,
+            [
+                SseEvent {
+                    event: Some("ping".to_owned()),
+                    data: r#"{"type":"ping"}"#.to_owned(),
+                },
+                SseEvent {
+                    event: None,
+                    data: "a\nb".to_owned(),
+                },
+            ]
+        );
+        assert!(decoder.push(b"data: partial\n").is_empty());
+    }
+}
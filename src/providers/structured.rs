@@ -0,0 +1,336 @@
+//! Structured output: ask a provider for JSON matching a schema and parse it.
+//!
+//! Works on every backend through a schema-constrained tool call: the model
+//! answers by calling a `respond` tool whose input schema is the caller's
+//! schema. A model that answers in plain text instead is accepted too, as long
+//! as the text is the JSON value (optionally in a code fence). When the answer
+//! does not parse as `T`, the parse error is sent back and the model tries
+//! again, up to `max_retries` times.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::warn;
+
+use super::{ApiUsage, CompletionRequest, ContentBlock, Message, Provider, ToolDefinition};
+use crate::error::CherubError;
+
+/// Name of the tool the model answers through.
+const RESPOND_TOOL: &str = "respond";
+
+/// Wrapper key for schemas that are not objects (tool inputs must be objects).
+const WRAPPED_KEY: &str = "value";
+
+/// Complete `request` and parse the answer as `T`, which `schema` (JSON
+/// Schema) describes. The request's tools are replaced by the `respond` tool;
+/// its system prompt gets an instruction to use it appended.
+///
+/// Returns the value and the usage summed over every attempt. Fails with
+/// `StructuredOutput` once `max_retries` reprompts have not produced a valid
+/// answer; provider errors propagate as they are.
+pub async fn complete_structured<T: DeserializeOwned>(
+    provider: &dyn Provider,
+    request: &CompletionRequest<'_>,
+    schema: &Value,
+    max_retries: u32,
+) -> Result<(T, ApiUsage), CherubError> {
+    let wrapped = schema.get("type").and_then(Value::as_str) != Some("object");
+    let tools = [ToolDefinition {
+        name: RESPOND_TOOL.to_owned(),
+        description: "Give your final answer. The input is the answer itself.".to_owned(),
+        input_schema: if wrapped {
+            serde_json::json!({
+                "type": "object",
+                "properties": { WRAPPED_KEY: schema },
+                "required": [WRAPPED_KEY],
+            })
+        } else {
+            schema.clone()
+        },
+    }];
+    let instruction = format!(
+        "Answer by calling the `{RESPOND_TOOL}` tool exactly once, with input that matches its schema."
+    );
+    let system = if request.system.is_empty() {
+        instruction
+    } else {
+        format!("{}\n\n{instruction}", request.system)
+    };
+
+    let mut messages = request.messages.to_vec();
+    let mut usage = ApiUsage::default();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let attempt = CompletionRequest {
+            system: &system,
+            messages: &messages,
+            tools: &tools,
+            ..*request
+        };
+        let (response, attempt_usage) = provider.complete(&attempt).await?;
+        if let Some(u) = attempt_usage {
+            usage += u;
+        }
+
+        let error = match parse_answer::<T>(&response, wrapped) {
+            Ok(value) => return Ok((value, usage)),
+            Err(error) => error,
+        };
+        if attempts > max_retries {
+            return Err(CherubError::StructuredOutput {
+                attempts,
+                last_error: error,
+            });
+        }
+        warn!(attempt = attempts, error = %error, "structured output invalid, reprompting");
+        let feedback = format!(
+            "That answer was invalid: {error}. Call `{RESPOND_TOOL}` again with input that matches its schema."
+        );
+        let tool_uses: Vec<String> = match &response {
+            Message::Assistant { content, .. } => content
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::ToolUse { id, .. } => Some(id.clone()),
+                    ContentBlock::Text { .. } => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        messages.push(response);
+        if tool_uses.is_empty() {
+            messages.push(Message::user_text(&feedback));
+        } else {
+            // Every tool call needs a result before the next user turn.
+            for tool_use_id in tool_uses {
+                messages.push(Message::ToolResult {
+                    tool_use_id,
+                    content: feedback.clone(),
+                    is_error: true,
+                });
+            }
+        }
+    }
+}
+
+/// Pull the answer out of a response: the `respond` call's input if there is
+/// one, else the text as JSON.
+fn parse_answer<T: DeserializeOwned>(response: &Message, wrapped: bool) -> Result<T, String> {
+    let Message::Assistant { content, .. } = response else {
+        return Err("expected an assistant message".to_owned());
+    };
+
+    let call = content.iter().find_map(|block| match block {
+        ContentBlock::ToolUse { name, input, .. } if name == RESPOND_TOOL => Some(input),
+        _ => None,
+    });
+    let value = match call {
+        Some(input) if wrapped => input
+            .get(WRAPPED_KEY)
+            .cloned()
+            .ok_or_else(|| format!("input has no `{WRAPPED_KEY}` field"))?,
+        Some(input) => input.clone(),
+        None => {
+            let text: String = content
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    ContentBlock::ToolUse { .. } => None,
+                })
+                .collect();
+            let text = text.trim();
+            let json = text
+                .strip_prefix("```json")
+                .or_else(|| text.strip_prefix("```"))
+                .and_then(|s| s.strip_suffix("```"))
+                .unwrap_or(text);
+            serde_json::from_str(json)
+                .map_err(|e| format!("no `{RESPOND_TOOL}` call and the text is not JSON ({e})"))?
+        }
+    };
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::StopReason;
+    use async_trait::async_trait;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Replies from a script and records every request's messages.
+    struct Scripted {
+        replies: Mutex<Vec<Message>>,
+        seen: Mutex<Vec<Vec<Message>>>,
+    }
+
+    impl Scripted {
+        fn new(mut replies: Vec<Message>) -> Self {
+            replies.reverse();
+            Self {
+                replies: Mutex::new(replies),
+                seen: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for Scripted {
+        async fn complete(
+            &self,
+            request: &CompletionRequest<'_>,
+        ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+            assert_eq!(request.tools.len(), 1);
+            assert!(request.system.contains("`respond`"));
+            self.seen.lock().unwrap().push(request.messages.to_vec());
+            let reply = self.replies.lock().unwrap().pop().expect("script ran out");
+            Ok((reply, Some(ApiUsage::new(10, 5))))
+        }
+
+        fn model_name(&self) -> &str {
+            "scripted"
+        }
+
+        fn max_output_tokens(&self) -> u32 {
+            1024
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Verdict {
+        safe: bool,
+        reason: String,
+    }
+
+    fn verdict_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "safe": { "type": "boolean" },
+                "reason": { "type": "string" }
+            },
+            "required": ["safe", "reason"]
+        })
+    }
+
+    fn respond(id: &str, input: Value) -> Message {
+        Message::Assistant {
+            content: vec![ContentBlock::ToolUse {
+                id: id.to_owned(),
+                name: RESPOND_TOOL.to_owned(),
+                input,
+            }],
+            stop_reason: StopReason::ToolUse,
+        }
+    }
+
+    fn text(text: &str) -> Message {
+        Message::Assistant {
+            content: vec![ContentBlock::Text {
+                text: text.to_owned(),
+            }],
+            stop_reason: StopReason::EndTurn,
+        }
+    }
+
+    #[tokio::test]
+    async fn parses_respond_tool_call() {
+        let provider = Scripted::new(vec![respond(
+            "t1",
+            json!({"safe": true, "reason": "read-only"}),
+        )]);
+        let messages = [Message::user_text("is `ls` safe?")];
+        let request = CompletionRequest::new("", &messages, &[]);
+
+        let (verdict, usage): (Verdict, _) =
+            complete_structured(&provider, &request, &verdict_schema(), 2)
+                .await
+                .unwrap();
+        assert_eq!(
+            verdict,
+            Verdict {
+                safe: true,
+                reason: "read-only".to_owned()
+            }
+        );
+        assert_eq!(usage, ApiUsage::new(10, 5));
+    }
+
+    #[tokio::test]
+    async fn accepts_fenced_json_text() {
+        let provider = Scripted::new(vec![text(
+            "```json\n{\"safe\": false, \"reason\": \"deletes files\"}\n```",
+        )]);
+        let messages = [Message::user_text("is `rm -rf /` safe?")];
+        let request = CompletionRequest::new("Be terse.", &messages, &[]);
+
+        let (verdict, _): (Verdict, _) =
+            complete_structured(&provider, &request, &verdict_schema(), 0)
+                .await
+                .unwrap();
+        assert!(!verdict.safe);
+    }
+
+    #[tokio::test]
+    async fn reprompts_with_parse_error() {
+        let provider = Scripted::new(vec![
+            respond("t1", json!({"safe": "yes"})),
+            text("sure!"),
+            respond("t2", json!({"safe": true, "reason": "fine"})),
+        ]);
+        let messages = [Message::user_text("is `ls` safe?")];
+        let request = CompletionRequest::new("", &messages, &[]);
+
+        let (verdict, usage): (Verdict, _) =
+            complete_structured(&provider, &request, &verdict_schema(), 2)
+                .await
+                .unwrap();
+        assert!(verdict.safe);
+        assert_eq!(usage, ApiUsage::new(30, 15), "usage sums every attempt");
+
+        let seen = provider.seen.lock().unwrap();
+        // The bad tool call is answered with an error result...
+        let Message::ToolResult {
+            tool_use_id,
+            content,
+            is_error,
+        } = &seen[1][2]
+        else {
+            panic!("expected a tool result: {:?}", seen[1]);
+        };
+        assert_eq!(tool_use_id, "t1");
+        assert!(is_error);
+        assert!(content.contains("invalid type"), "{content}");
+        // ...and the plain-text answer with a user message.
+        assert!(matches!(&seen[2][4], Message::User { .. }));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_retry_limit() {
+        let provider = Scripted::new(vec![text("no"), text("still no")]);
+        let messages = [Message::user_text("is `ls` safe?")];
+        let request = CompletionRequest::new("", &messages, &[]);
+
+        let err = complete_structured::<Verdict>(&provider, &request, &verdict_schema(), 1)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, CherubError::StructuredOutput { attempts: 2, .. }),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn non_object_schema_is_wrapped() {
+        let provider = Scripted::new(vec![respond("t1", json!({"value": ["a", "b"]}))]);
+        let messages = [Message::user_text("list two letters")];
+        let request = CompletionRequest::new("", &messages, &[]);
+        let schema = json!({"type": "array", "items": {"type": "string"}});
+
+        let (letters, _): (Vec<String>, _) = complete_structured(&provider, &request, &schema, 0)
+            .await
+            .unwrap();
+        assert_eq!(letters, ["a", "b"]);
+    }
+}
@@ -0,0 +1,557 @@
+//! Scripted provider for tests (feature `testing`).
+//!
+//! `MockProvider` answers each completion call with the next turn of a script
+//! instead of calling a model, so the agent loop — tool calls, enforcement,
+//! approval, and the results fed back — can be driven end to end in a unit
+//! test. Pair it with the tool doubles in `tools::testing`. Tool-use IDs are
+//! assigned from a counter, so runs are identical. Streaming replays each turn
+//! as deltas.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::{ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason};
+use crate::error::CherubError;
+
+/// One scripted reply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockTurn {
+    /// Answer with text and end the turn.
+    Text(String),
+    /// Call these tools, as `(name, input)`, in one message.
+    ToolCalls(Vec<(String, Value)>),
+    /// Reply with this message as given.
+    Message(Message),
+    /// Fail the call (`CherubError::Provider`).
+    Error(String),
+}
+
+impl MockTurn {
+    pub fn text(text: &str) -> Self {
+        Self::Text(text.to_owned())
+    }
+
+    /// A single tool call.
+    pub fn tool_call(name: &str, input: Value) -> Self {
+        Self::ToolCalls(vec![(name.to_owned(), input)])
+    }
+}
+
+/// What the provider was sent on one call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockRequest {
+    pub system: String,
+    pub messages: Vec<Message>,
+    /// Names of the tools offered.
+    pub tools: Vec<String>,
+}
+
+#[derive(Default)]
+struct State {
+    script: VecDeque<MockTurn>,
+    requests: Vec<MockRequest>,
+    next_tool_id: u32,
+}
+
+/// Replies from a script, in order, and records every request. Fails once
+/// the script runs out. Clones share the script and the record, so keep one
+/// to inspect after handing the other to the agent loop.
+#[derive(Clone)]
+pub struct MockProvider {
+    state: Arc<Mutex<State>>,
+    model: String,
+    usage: Option<ApiUsage>,
+}
+
+impl MockProvider {
+    pub fn new(script: impl IntoIterator<Item = MockTurn>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                script: script.into_iter().collect(),
+                ..State::default()
+            })),
+            model: "mock".to_owned(),
+            usage: None,
+        }
+    }
+
+    /// Report this model name (for pricing and logs). Defaults to `mock`.
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_owned();
+        self
+    }
+
+    /// Report `usage` for every successful call. Defaults to none.
+    pub fn with_usage(mut self, usage: ApiUsage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// The requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .requests
+            .clone()
+    }
+}
+
+#[async_trait]
+impl Provider for MockProvider {
+    async fn complete(
+        &self,
+        request: &CompletionRequest<'_>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.requests.push(MockRequest {
+            system: request.system.to_owned(),
+            messages: request.messages.to_vec(),
+            tools: request.tools.iter().map(|t| t.name.clone()).collect(),
+        });
+        let message = match state.script.pop_front() {
+            Some(MockTurn::Text(text)) => Message::Assistant {
+                content: vec![ContentBlock::Text { text }],
+                stop_reason: StopReason::EndTurn,
+            },
+            Some(MockTurn::ToolCalls(calls)) => Message::Assistant {
+                content: calls
+                    .into_iter()
+                    .map(|(name, input)| {
+                        state.next_tool_id += 1;
+                        ContentBlock::ToolUse {
+                            id: format!("toolu_mock_{}", state.next_tool_id),
+                            name,
+                            input,
+                        }
+                    })
+                    .collect(),
+                stop_reason: StopReason::ToolUse,
+            },
+            Some(MockTurn::Message(message)) => message,
+            Some(MockTurn::Error(message)) => return Err(CherubError::Provider(message)),
+            None => {
+                return Err(CherubError::Provider(
+                    "mock provider: script exhausted".to_owned(),
+                ));
+            }
+        };
+        Ok((message, self.usage))
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn max_output_tokens(&self) -> u32 {
+        4096
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+    use tokio_util::sync::CancellationToken;
+
+    use super::*;
+    use crate::enforcement::policy::Policy;
+    use crate::enforcement::tier::Tier;
+    use crate::runtime::AgentLoop;
+    use crate::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
+    use crate::runtime::output::NullSink;
+    use crate::runtime::session::InvocationOutcome;
+    use crate::tools::ToolRegistry;
+    use crate::tools::testing::RecordingTool;
+
+    const POLICY: &str = r#"
+[tools.bash]
+enabled = true
+
+[tools.bash.actions.read]
+tier = "observe"
+patterns = ["^ls"]
+
+[tools.bash.actions.delete]
+tier = "commit"
+patterns = ["^rm "]
+"#;
+
+    struct Deny;
+
+    impl ApprovalGate for Deny {
+        async fn request_approval(&self, _context: &EscalationContext<'_>) -> ApprovalResult {
+            ApprovalResult::Denied
+        }
+    }
+
+    fn agent(provider: &MockProvider, bash: &RecordingTool) -> AgentLoop<Deny, NullSink> {
+        AgentLoop::new(
+            Policy::from_str(POLICY).unwrap(),
+            Box::new(provider.clone()),
+            ToolRegistry::new().with_recording(bash.clone()),
+            "test".to_owned(),
+            Deny,
+            NullSink,
+            "test",
+        )
+    }
+
+    fn tool_result(message: &Message) -> (&str, &str, bool) {
+        match message {
+            Message::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => (tool_use_id, content, *is_error),
+            other => panic!("expected a tool result: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn scripted_tool_calls_run_through_enforcement() {
+        let provider = MockProvider::new([
+            MockTurn::tool_call("bash", json!({"command": "ls -la"})),
+            MockTurn::ToolCalls(vec![
+                ("bash".to_owned(), json!({"command": "rm notes.txt"})),
+                ("bash".to_owned(), json!({"command": "curl example.com"})),
+            ]),
+            MockTurn::text("Done."),
+        ])
+        .with_usage(ApiUsage::new(10, 5));
+        let bash = RecordingTool::new("bash", "file.txt");
+        let mut agent = agent(&provider, &bash);
+
+        agent.run_turn_text("clean up").await.unwrap();
+
+        // Only the observe-tier call ran: the commit-tier one was denied at
+        // approval and the unmatched one rejected.
+        let calls = bash.recorded();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].tier, Tier::Observe);
+
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].tools.contains(&"bash".to_owned()));
+        assert_eq!(
+            tool_result(&requests[1].messages[2]),
+            ("toolu_mock_1", "file.txt", false)
+        );
+        let (id, _, denied) = tool_result(&requests[2].messages[4]);
+        assert_eq!(id, "toolu_mock_2");
+        assert!(denied);
+        let (id, _, rejected) = tool_result(&requests[2].messages[5]);
+        assert_eq!(id, "toolu_mock_3");
+        assert!(rejected);
+
+        // The session logs every proposed call with its outcome.
+        let session = agent.session();
+        let outcomes: Vec<_> = session
+            .invocations()
+            .iter()
+            .map(|r| (r.tool_use_id.as_str(), r.outcome))
+            .collect();
+        assert!(matches!(
+            outcomes[..],
+            [
+                (
+                    "toolu_mock_1",
+                    InvocationOutcome::Executed {
+                        tier: Tier::Observe,
+                        approved: false,
+                        is_error: false,
+                        ..
+                    }
+                ),
+                ("toolu_mock_2", InvocationOutcome::Denied),
+                ("toolu_mock_3", InvocationOutcome::Rejected),
+            ]
+        ));
+        assert_eq!(session.usage(), ApiUsage::new(30, 15));
+    }
+
+    #[tokio::test]
+    async fn session_file_resumes_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let bash = RecordingTool::new("bash", "file.txt");
+
+        let provider = MockProvider::new([
+            MockTurn::tool_call("bash", json!({"command": "ls"})),
+            MockTurn::text("One file."),
+        ]);
+        let mut first = agent(&provider, &bash);
+        first.with_session_file(&path).await.unwrap();
+        first.run_turn_text("what's here?").await.unwrap();
+
+        // A fresh process picks up the history and the invocation log.
+        let provider = MockProvider::new([MockTurn::text("Still one.")]);
+        let mut second = agent(&provider, &bash);
+        second.with_session_file(&path).await.unwrap();
+        assert_eq!(second.session_id(), first.session_id());
+        assert_eq!(second.session().invocations().len(), 1);
+        second.run_turn_text("and now?").await.unwrap();
+
+        let requests = provider.requests();
+        assert_eq!(requests[0].messages.len(), 5, "history was sent");
+        assert_eq!(second.session_messages().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn loop_budget_stops_the_turn() {
+        use crate::runtime::budget::{BudgetLimit, LoopBudget};
+
+        // Out of tool calls: the second call is answered but never run.
+        let provider = MockProvider::new([MockTurn::ToolCalls(vec![
+            ("bash".to_owned(), json!({"command": "ls a"})),
+            ("bash".to_owned(), json!({"command": "ls b"})),
+        ])]);
+        let bash = RecordingTool::new("bash", "file.txt");
+        let mut capped = agent(&provider, &bash);
+        capped.with_loop_budget(LoopBudget {
+            max_tool_calls: Some(1),
+            ..LoopBudget::default()
+        });
+        let err = capped.run_turn_text("look").await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                CherubError::BudgetExceeded {
+                    limit: BudgetLimit::ToolCalls(1)
+                }
+            ),
+            "{err:?}"
+        );
+        assert_eq!(bash.recorded().len(), 1);
+        let (id, content, is_error) = tool_result(&capped.session_messages()[3]);
+        assert_eq!(id, "toolu_mock_2");
+        assert!(content.contains("budget"));
+        assert!(is_error);
+
+        // Out of model calls while the model keeps asking for tools.
+        let provider = MockProvider::new([
+            MockTurn::tool_call("bash", json!({"command": "ls"})),
+            MockTurn::tool_call("bash", json!({"command": "ls"})),
+            MockTurn::text("never reached"),
+        ]);
+        let mut looping = agent(&provider, &bash);
+        looping.with_loop_budget(LoopBudget {
+            max_iterations: Some(2),
+            ..LoopBudget::default()
+        });
+        let err = looping.run_turn_text("look").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "turn stopped: loop budget of 2 model calls exhausted"
+        );
+        assert_eq!(provider.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn lifecycle_events_follow_the_run() {
+        use crate::runtime::events::{LifecycleEvent, RunOutcome};
+
+        let provider = MockProvider::new([
+            MockTurn::ToolCalls(vec![
+                ("bash".to_owned(), json!({"command": "ls"})),
+                ("bash".to_owned(), json!({"command": "rm -rf build"})),
+                ("bash".to_owned(), json!({"command": "curl example.com"})),
+            ]),
+            MockTurn::text("Done."),
+        ]);
+        let bash = RecordingTool::new("bash", "file.txt");
+        let mut agent = agent(&provider, &bash);
+        let mut events = agent.subscribe();
+        agent.run_turn_text("look").await.unwrap();
+
+        let mut seen = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            seen.push(match event {
+                LifecycleEvent::TurnStarted { .. } => "started".to_owned(),
+                LifecycleEvent::ToolProposed { action, .. } => format!("proposed {action}"),
+                LifecycleEvent::DecisionMade { decision, tier, .. } => {
+                    format!("{decision:?} {tier:?}")
+                }
+                LifecycleEvent::EscalationRequested { tier, .. } => format!("escalation {tier:?}"),
+                LifecycleEvent::ToolCompleted(record) => format!("completed {}", record.action),
+                LifecycleEvent::RunFinished { outcome, .. } => {
+                    assert_eq!(outcome, RunOutcome::Completed);
+                    "finished".to_owned()
+                }
+            });
+        }
+        assert_eq!(
+            seen,
+            [
+                "started",
+                "proposed ls",
+                "Allowed Some(Observe)",
+                "completed ls",
+                "proposed rm -rf build",
+                "Escalated Some(Commit)",
+                "escalation Commit",
+                "Denied None",
+                "completed rm -rf build",
+                "proposed curl example.com",
+                "Rejected None",
+                "completed curl example.com",
+                "finished",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn hooks_veto_rewrite_and_annotate_under_enforcement() {
+        use crate::providers::CompletionRequest;
+        use crate::runtime::hooks::{BeforeCompletion, BeforeTool, Hooks, ToolCall};
+
+        struct Guard;
+
+        impl Hooks for Guard {
+            async fn on_before_tool(&self, call: &ToolCall<'_>) -> BeforeTool {
+                match call.params["command"].as_str() {
+                    Some("rm -rf build") => BeforeTool::Veto("not today".to_owned()),
+                    Some("ls old") => BeforeTool::Rewrite(json!({"command": "ls new"})),
+                    // A rewrite cannot launder an action past the policy.
+                    Some("ls sneaky") => BeforeTool::Rewrite(json!({"command": "curl x"})),
+                    _ => BeforeTool::Proceed,
+                }
+            }
+
+            async fn on_after_tool(
+                &self,
+                call: &ToolCall<'_>,
+                _output: &str,
+                is_error: bool,
+            ) -> Option<String> {
+                (!is_error).then(|| format!("(checked {})", call.tool_use_id))
+            }
+
+            async fn on_before_completion(
+                &self,
+                request: &CompletionRequest<'_>,
+            ) -> BeforeCompletion {
+                if request.messages.len() > 1 {
+                    BeforeCompletion::Veto("one round only".to_owned())
+                } else {
+                    BeforeCompletion::Proceed
+                }
+            }
+        }
+
+        let provider = MockProvider::new([
+            MockTurn::ToolCalls(vec![
+                ("bash".to_owned(), json!({"command": "rm -rf build"})),
+                ("bash".to_owned(), json!({"command": "ls old"})),
+                ("bash".to_owned(), json!({"command": "ls sneaky"})),
+            ]),
+            MockTurn::text("never reached"),
+        ]);
+        let bash = RecordingTool::new("bash", "file.txt");
+        let mut agent = agent(&provider, &bash).with_hooks(Guard);
+        let err = agent.run_turn_text("tidy").await.unwrap_err();
+        assert!(
+            matches!(&err, CherubError::HookVeto(r) if r == "one round only"),
+            "{err:?}"
+        );
+        assert_eq!(provider.requests().len(), 1);
+
+        let recorded = bash.recorded();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].params["command"], "ls new");
+
+        let messages = agent.session_messages();
+        assert_eq!(
+            tool_result(&messages[2]),
+            ("toolu_mock_1", "not today", true)
+        );
+        assert_eq!(
+            tool_result(&messages[3]),
+            ("toolu_mock_2", "file.txt\n\n(checked toolu_mock_2)", false)
+        );
+        assert_eq!(
+            tool_result(&messages[4]),
+            ("toolu_mock_3", "action not permitted", true)
+        );
+
+        let outcomes: Vec<_> = agent
+            .session()
+            .invocations()
+            .iter()
+            .map(|r| r.outcome)
+            .collect();
+        assert_eq!(outcomes[0], InvocationOutcome::Vetoed);
+        assert!(matches!(outcomes[1], InvocationOutcome::Executed { .. }));
+        assert_eq!(outcomes[2], InvocationOutcome::Rejected);
+    }
+
+    /// Cancels the run while asked for approval, like a Ctrl-C at the prompt.
+    struct CancelOnAsk(CancellationToken);
+
+    impl ApprovalGate for CancelOnAsk {
+        async fn request_approval(&self, _context: &EscalationContext<'_>) -> ApprovalResult {
+            self.0.cancel();
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn cancellation_denies_escalation_and_stops_the_turn() {
+        let provider = MockProvider::new([
+            MockTurn::ToolCalls(vec![
+                ("bash".to_owned(), json!({"command": "rm -rf build"})),
+                ("bash".to_owned(), json!({"command": "ls"})),
+            ]),
+            MockTurn::text("never reached"),
+        ]);
+        let bash = RecordingTool::new("bash", "file.txt");
+        let cancel = CancellationToken::new();
+        let mut agent = AgentLoop::new(
+            Policy::from_str(POLICY).unwrap(),
+            Box::new(provider.clone()),
+            ToolRegistry::new().with_recording(bash.clone()),
+            "test".to_owned(),
+            CancelOnAsk(cancel.clone()),
+            NullSink,
+            "test",
+        );
+        agent.with_cancellation(cancel);
+
+        let err = agent.run_turn_text("clean up").await.unwrap_err();
+        assert!(matches!(err, CherubError::Cancelled { .. }), "{err:?}");
+        assert!(bash.recorded().is_empty(), "nothing ran");
+        assert_eq!(provider.requests().len(), 1);
+
+        // Both calls are answered, so the history stays valid.
+        let messages = agent.session_messages();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            tool_result(&messages[2]),
+            ("toolu_mock_1", "action not permitted", true)
+        );
+        let (id, content, _) = tool_result(&messages[3]);
+        assert_eq!(id, "toolu_mock_2");
+        assert!(content.contains("cancelled"));
+        assert_eq!(
+            agent.session().invocations()[0].outcome,
+            InvocationOutcome::Denied
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_and_exhaustion_fail_the_call() {
+        let provider = MockProvider::new([MockTurn::Error("overloaded".to_owned())]);
+        let messages = [Message::user_text("hi")];
+        let request = CompletionRequest::new("", &messages, &[]);
+
+        let err = provider.complete(&request).await.unwrap_err();
+        assert!(matches!(err, CherubError::Provider(m) if m == "overloaded"));
+        let err = provider.complete(&request).await.unwrap_err();
+        assert!(matches!(err, CherubError::Provider(m) if m.contains("exhausted")));
+        assert_eq!(provider.requests().len(), 2);
+    }
+}
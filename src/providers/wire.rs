@@ -3,7 +3,9 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::{ApiUsage, ContentBlock, Message, StopReason, ToolDefinition, UserContent};
+use super::{
+    ApiUsage, ContentBlock, Message, MessageDelta, StopReason, ToolDefinition, UserContent,
+};
 
 // --- Request types ---
 
@@ -11,6 +13,13 @@ use super::{ApiUsage, ContentBlock, Message, StopReason, ToolDefinition, UserCon
 pub(crate) struct RequestBody<'a> {
     pub model: &'a str,
     pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    pub stop_sequences: &'a [String],
+    #[serde(skip_serializing_if = "str::is_empty")]
     pub system: &'a str,
     pub messages: Vec<WireMessage>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -50,8 +59,15 @@ pub(crate) enum WireContentBlock {
     },
     #[serde(rename = "image")]
     Image { source: WireImageSource },
+    #[serde(rename = "document")]
+    Document {
+        source: WireImageSource,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+    },
 }
 
+/// Base64 source of an image or document block.
 #[derive(Serialize)]
 pub(crate) struct WireImageSource {
     #[serde(rename = "type")]
@@ -67,6 +83,53 @@ pub(crate) struct WireTool {
     pub input_schema: serde_json::Value,
 }
 
+// --- Message Batches ---
+
+#[derive(Serialize)]
+pub(crate) struct BatchCreate<'a> {
+    pub requests: Vec<BatchEntry<'a>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct BatchEntry<'a> {
+    pub custom_id: &'a str,
+    pub params: RequestBody<'a>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MessageBatch {
+    pub id: String,
+    /// `in_progress`, `canceling`, or `ended`.
+    pub processing_status: String,
+    pub request_counts: BatchRequestCounts,
+    pub results_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BatchRequestCounts {
+    pub processing: u32,
+    pub succeeded: u32,
+    pub errored: u32,
+    pub canceled: u32,
+    pub expired: u32,
+}
+
+/// One line of a batch's JSONL results.
+#[derive(Deserialize)]
+pub(crate) struct BatchResultLine {
+    pub custom_id: String,
+    pub result: BatchOutcome,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum BatchOutcome {
+    Succeeded { message: ResponseBody },
+    Errored { error: ErrorBody },
+    Canceled,
+    Expired,
+}
+
 // --- Response types ---
 
 #[derive(Deserialize)]
@@ -86,6 +149,32 @@ pub(crate) struct WireUsage {
     pub cache_read_input_tokens: u32,
 }
 
+/// Body of a non-2xx response: `{"type":"error","error":{"type":..,"message":..}}`.
+#[derive(Deserialize)]
+pub(crate) struct ErrorBody {
+    pub error: WireError,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WireError {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+}
+
+/// One page of `GET /v1/models`.
+#[derive(Deserialize)]
+pub(crate) struct ModelPage {
+    pub data: Vec<ModelInfo>,
+    pub has_more: bool,
+    pub last_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ModelInfo {
+    pub id: String,
+}
+
 #[derive(Deserialize)]
 #[serde(tag = "type")]
 pub(crate) enum ResponseContentBlock {
@@ -99,6 +188,154 @@ pub(crate) enum ResponseContentBlock {
     },
 }
 
+// --- Streaming types ---
+
+/// The `data` of one event in a streaming response.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockStart {
+        content_block: ResponseContentBlock,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: BlockDelta,
+    },
+    MessageDelta {
+        delta: StopDelta,
+        usage: Option<DeltaUsage>,
+    },
+    MessageStop,
+    Error {
+        error: WireError,
+    },
+    /// `ping`, `content_block_stop`, and event types added later.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct StreamMessage {
+    pub usage: Option<WireUsage>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum BlockDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct StopDelta {
+    pub stop_reason: Option<String>,
+}
+
+/// Cumulative output tokens, sent with the final `message_delta`.
+#[derive(Deserialize)]
+pub(crate) struct DeltaUsage {
+    pub output_tokens: u32,
+}
+
+/// Assembles a `ResponseBody` from stream events, yielding each delta.
+#[derive(Default)]
+pub(crate) struct StreamAccumulator {
+    /// Blocks in index order, each with its tool input JSON so far.
+    blocks: Vec<(ResponseContentBlock, String)>,
+    stop_reason: Option<String>,
+    usage: Option<WireUsage>,
+    stopped: bool,
+}
+
+impl StreamAccumulator {
+    /// Apply one event. `Error` events are the caller's to handle first.
+    pub(crate) fn apply(&mut self, event: StreamEvent) -> Option<MessageDelta> {
+        match event {
+            StreamEvent::MessageStart { message } => {
+                self.usage = message.usage;
+                None
+            }
+            StreamEvent::ContentBlockStart { content_block } => {
+                let delta = match &content_block {
+                    ResponseContentBlock::Text { text } if text.is_empty() => None,
+                    ResponseContentBlock::Text { text } => Some(MessageDelta::Text(text.clone())),
+                    ResponseContentBlock::ToolUse { id, name, .. } => {
+                        Some(MessageDelta::ToolUseStart {
+                            id: id.clone(),
+                            name: name.clone(),
+                        })
+                    }
+                };
+                self.blocks.push((content_block, String::new()));
+                delta
+            }
+            StreamEvent::ContentBlockDelta { index, delta } => {
+                let (block, input) = self.blocks.get_mut(index)?;
+                match (block, delta) {
+                    (ResponseContentBlock::Text { text }, BlockDelta::TextDelta { text: more }) => {
+                        text.push_str(&more);
+                        Some(MessageDelta::Text(more))
+                    }
+                    (
+                        ResponseContentBlock::ToolUse { .. },
+                        BlockDelta::InputJsonDelta { partial_json },
+                    ) => {
+                        input.push_str(&partial_json);
+                        Some(MessageDelta::ToolInput(partial_json))
+                    }
+                    _ => None,
+                }
+            }
+            StreamEvent::MessageDelta { delta, usage } => {
+                self.stop_reason = delta.stop_reason.or(self.stop_reason.take());
+                if let (Some(total), Some(usage)) = (&mut self.usage, usage) {
+                    total.output_tokens = usage.output_tokens;
+                }
+                None
+            }
+            StreamEvent::MessageStop => {
+                self.stopped = true;
+                None
+            }
+            StreamEvent::Error { .. } | StreamEvent::Other => None,
+        }
+    }
+
+    /// The complete response, once `message_stop` has arrived.
+    pub(crate) fn finish(self) -> Result<ResponseBody, String> {
+        if !self.stopped {
+            return Err("stream ended before message_stop".to_owned());
+        }
+        let content = self
+            .blocks
+            .into_iter()
+            .map(|(block, json)| match block {
+                ResponseContentBlock::ToolUse { id, name, .. } if !json.is_empty() => {
+                    // Unparseable input stays a raw string, for the runtime to refuse.
+                    let input =
+                        serde_json::from_str(&json).unwrap_or(serde_json::Value::String(json));
+                    ResponseContentBlock::ToolUse { id, name, input }
+                }
+                block => block,
+            })
+            .collect();
+        Ok(ResponseBody {
+            content,
+            stop_reason: self.stop_reason.unwrap_or_default(),
+            usage: self.usage,
+        })
+    }
+}
+
 // --- Conversions ---
 
 impl From<&ToolDefinition> for WireTool {
@@ -133,6 +370,18 @@ fn user_content_to_wire(content: &[UserContent]) -> WireContent {
                     data: data.clone(),
                 },
             },
+            UserContent::Document {
+                media_type,
+                data,
+                name,
+            } => WireContentBlock::Document {
+                source: WireImageSource {
+                    source_type: "base64",
+                    media_type: media_type.clone(),
+                    data: data.clone(),
+                },
+                title: name.clone(),
+            },
         })
         .collect();
 
@@ -269,6 +518,23 @@ mod tests {
         assert_eq!(blocks[0]["source"]["data"], "iVBOR...");
     }
 
+    #[test]
+    fn user_document_message_serializes() {
+        let wire = messages_to_wire(&[Message::User {
+            content: vec![UserContent::Document {
+                media_type: "application/pdf".to_owned(),
+                data: "JVBERi0...".to_owned(),
+                name: Some("spec.pdf".to_owned()),
+            }],
+        }]);
+        let json = serde_json::to_value(&wire[0]).unwrap();
+        let block = &json["content"][0];
+        assert_eq!(block["type"], "document");
+        assert_eq!(block["source"]["type"], "base64");
+        assert_eq!(block["source"]["media_type"], "application/pdf");
+        assert_eq!(block["title"], "spec.pdf");
+    }
+
     #[test]
     fn user_mixed_content_serializes() {
         let wire = messages_to_wire(&[Message::User {
@@ -322,6 +588,9 @@ mod tests {
         let body = RequestBody {
             model: "claude-sonnet-4-20250514",
             max_tokens: 4096,
+            temperature: None,
+            top_p: None,
+            stop_sequences: &[],
             system: "You are helpful.",
             messages: vec![WireMessage {
                 role: "user",
@@ -338,6 +607,66 @@ mod tests {
         assert_eq!(json["model"], "claude-sonnet-4-20250514");
         assert_eq!(json["stream"], false);
         assert_eq!(json["tools"][0]["name"], "bash");
+        assert!(json.get("temperature").is_none());
+    }
+
+    #[test]
+    fn stream_events_accumulate_into_response() {
+        let events = [
+            json!({"type": "message_start", "message": {"usage": {"input_tokens": 12, "output_tokens": 1}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "ping"}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Listing"}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": " files"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "t1", "name": "bash", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{\"command\":"}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": " \"ls\"}"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 30}}),
+        ];
+        let mut acc = StreamAccumulator::default();
+        let deltas: Vec<_> = events
+            .into_iter()
+            .filter_map(|e| acc.apply(serde_json::from_value(e).unwrap()))
+            .collect();
+        assert_eq!(
+            deltas,
+            [
+                MessageDelta::Text("Listing".to_owned()),
+                MessageDelta::Text(" files".to_owned()),
+                MessageDelta::ToolUseStart {
+                    id: "t1".to_owned(),
+                    name: "bash".to_owned()
+                },
+                MessageDelta::ToolInput("{\"command\":".to_owned()),
+                MessageDelta::ToolInput(" \"ls\"}".to_owned()),
+            ]
+        );
+
+        // Truncated before message_stop.
+        assert!(StreamAccumulator::default().finish().is_err());
+
+        acc.apply(serde_json::from_value(json!({"type": "message_stop"})).unwrap());
+        let (msg, usage) = response_to_message(acc.finish().unwrap());
+        let usage = usage.unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (12, 30));
+        assert_eq!(
+            msg,
+            Message::Assistant {
+                content: vec![
+                    ContentBlock::Text {
+                        text: "Listing files".to_owned()
+                    },
+                    ContentBlock::ToolUse {
+                        id: "t1".to_owned(),
+                        name: "bash".to_owned(),
+                        input: json!({"command": "ls"}),
+                    },
+                ],
+                stop_reason: StopReason::ToolUse,
+            }
+        );
     }
 
     #[test]
@@ -557,9 +886,7 @@ mod tests {
     #[test]
     fn error_message_does_not_contain_secrets() {
         // Verify that our error format doesn't accidentally include API key patterns.
-        let error_msg = format!(
-            "API error 401 Unauthorized: {{\"type\":\"error\",\"error\":{{\"type\":\"authentication_error\",\"message\":\"invalid x-api-key\"}}}}"
-        );
+        let error_msg = "API error 401 Unauthorized: {\"type\":\"error\",\"error\":{\"type\":\"authentication_error\",\"message\":\"invalid x-api-key\"}}";
         assert!(!error_msg.contains("sk-ant-"));
         assert!(!error_msg.contains("sk-"));
     }
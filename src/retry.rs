@@ -1,11 +1,26 @@
-//! Retry logic with exponential backoff for transient API errors.
+//! Retry logic with exponential backoff for transient API errors, and the
+//! per-tool `[tools.<name>.retry]` policy for transient execution failures.
+//!
+//! Providers send through `send_with_retry`, which owns the backoff loop and
+//! `Retry-After` handling; each provider only builds requests and words its
+//! API's error responses (`SendError::into_error`).
 //!
 //! Hand-rolled, no external dependencies. Jitter uses `SystemTime` nanoseconds
 //! to avoid adding `rand` as a non-optional dependency.
 
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-/// Configuration for retry behavior.
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::CherubError;
+use crate::tools::ToolResult;
+
+/// Configuration for retry behavior. `max_retries` is retries after the
+/// first attempt; 0 disables retrying.
+#[derive(Debug, Clone)]
 pub struct RetryConfig {
     pub max_retries: u32,
     pub base_delay: Duration,
@@ -67,6 +82,202 @@ pub fn compute_delay(config: &RetryConfig, attempt: u32) -> Duration {
     with_jitter.min(config.max_delay + Duration::from_millis(999))
 }
 
+/// The server's requested wait: `retry-after-ms` (OpenAI, Azure), else
+/// `retry-after` in seconds. The HTTP-date form of `retry-after` is ignored.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+    };
+    header("retry-after-ms")
+        .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+        .or_else(|| header("retry-after").map(Duration::from_secs_f64))
+}
+
+/// Why `send_with_retry` gave up.
+pub(crate) enum SendFailure {
+    /// No response: connection refused, timeout, ...
+    Connection(reqwest::Error),
+    /// A non-2xx response, body unread.
+    Status(Response),
+}
+
+pub(crate) struct SendError {
+    pub failure: SendFailure,
+    /// Retries made before giving up.
+    pub retries: u32,
+    /// The failure was transient, but `max_retries` was used up.
+    pub exhausted: bool,
+}
+
+impl SendError {
+    /// The error to surface. `describe(status, headers, body)` words an error
+    /// response in the API's own terms. Exhausted retries are
+    /// `CherubError::RetriesExhausted`; anything else is `Provider`.
+    pub(crate) async fn into_error(
+        self,
+        describe: impl FnOnce(u16, &HeaderMap, &str) -> String,
+    ) -> CherubError {
+        let message = match self.failure {
+            SendFailure::Connection(e) => format!("connection error: {e}"),
+            SendFailure::Status(response) => {
+                let status = response.status().as_u16();
+                let headers = response.headers().clone();
+                let body = response.text().await.unwrap_or_default();
+                warn!(status, "API error response");
+                describe(status, &headers, &body)
+            }
+        };
+        if self.exhausted {
+            CherubError::RetriesExhausted {
+                retries: self.retries,
+                last_error: message,
+            }
+        } else {
+            CherubError::Provider(format!("{message} (after {} retries)", self.retries))
+        }
+    }
+}
+
+/// Send the request `build` makes, rebuilding it for each attempt (so a
+/// signature or timestamp can be fresh). Connection errors, timeouts, 429,
+/// and 5xx are retried with exponential backoff and jitter, or after the
+/// server's `Retry-After` when it sends one. A `Retry-After` longer than
+/// `max_delay` is not waited out: the response is returned as the error.
+pub(crate) async fn send_with_retry(
+    config: &RetryConfig,
+    mut build: impl FnMut() -> RequestBuilder,
+) -> Result<Response, SendError> {
+    let mut attempt = 0;
+    loop {
+        let can_retry = attempt < config.max_retries;
+        let give_up = |failure, exhausted| SendError {
+            failure,
+            retries: attempt,
+            exhausted,
+        };
+        let response = match build().send().await {
+            Ok(r) => r,
+            Err(e) if e.is_connect() || e.is_timeout() => {
+                if !can_retry {
+                    return Err(give_up(SendFailure::Connection(e), true));
+                }
+                let delay = compute_delay(config, attempt);
+                warn!(
+                    error = %e,
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    "retrying API call (connection/timeout error)"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+            Err(e) => return Err(give_up(SendFailure::Connection(e), false)),
+        };
+
+        let status = response.status().as_u16();
+        info!(status);
+
+        match classify_status(status) {
+            RetryVerdict::Success => return Ok(response),
+            RetryVerdict::Permanent => return Err(give_up(SendFailure::Status(response), false)),
+            RetryVerdict::Transient(_) if !can_retry => {
+                return Err(give_up(SendFailure::Status(response), true));
+            }
+            RetryVerdict::Transient(_) => {
+                let delay = match retry_after(response.headers()) {
+                    Some(wait) if wait > config.max_delay => {
+                        warn!(
+                            status,
+                            retry_after_ms = wait.as_millis() as u64,
+                            "Retry-After exceeds max delay, not retrying"
+                        );
+                        return Err(give_up(SendFailure::Status(response), false));
+                    }
+                    Some(wait) => wait,
+                    None => compute_delay(config, attempt),
+                };
+                warn!(
+                    status,
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    "retrying API call"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// `[tools.<name>.retry]`: re-run a tool call that failed transiently. Carried
+/// in capability tokens; the executor retries, re-checking the token's TTL and
+/// caveats before each attempt. At least one condition must be set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first (2–10).
+    pub(crate) max_attempts: u32,
+    /// Delay before the first retry; doubled for each later one, plus jitter.
+    #[serde(default = "default_backoff_ms")]
+    pub(crate) backoff_ms: u64,
+    /// Cap on the doubled delay.
+    #[serde(default = "default_max_backoff_ms")]
+    pub(crate) max_backoff_ms: u64,
+    /// Retry when the process exits with one of these codes.
+    #[serde(default)]
+    pub(crate) exit_codes: Vec<i32>,
+    /// Retry when the process is killed at its time limit.
+    #[serde(default)]
+    pub(crate) timeout: bool,
+    /// Retry when the tool fails to run (spawn failure, connection error).
+    /// Policy refusals are never retried.
+    #[serde(default)]
+    pub(crate) errors: bool,
+}
+
+fn default_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_backoff_ms() -> u64 {
+    10_000
+}
+
+impl RetryPolicy {
+    /// Whether `result` is a failure this policy retries.
+    pub(crate) fn should_retry(&self, result: &Result<ToolResult, CherubError>) -> bool {
+        match result {
+            Ok(ToolResult {
+                status: Some(status),
+                ..
+            }) => {
+                (self.timeout && status.timed_out)
+                    || status
+                        .exit_code
+                        .is_some_and(|code| self.exit_codes.contains(&code))
+            }
+            Ok(_) => false,
+            Err(CherubError::ToolExecution(_)) => self.errors,
+            Err(_) => false,
+        }
+    }
+
+    /// Delay before retry number `retry` (0 for the first retry).
+    pub(crate) fn delay(&self, retry: u32) -> Duration {
+        let config = RetryConfig {
+            max_retries: self.max_attempts.saturating_sub(1),
+            base_delay: Duration::from_millis(self.backoff_ms),
+            max_delay: Duration::from_millis(self.max_backoff_ms),
+        };
+        compute_delay(&config, retry)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +349,58 @@ mod tests {
         // nanosecond resolution this will always pass.
         assert!(!all_exactly_base || cfg!(miri));
     }
+
+    #[test]
+    fn retry_after_prefers_milliseconds_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert("retry-after", "2".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(2)));
+        headers.insert("retry-after-ms", "250".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(250)));
+
+        let mut date = HeaderMap::new();
+        date.insert(
+            "retry-after",
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&date), None);
+    }
+
+    fn retry_policy(toml: &str) -> RetryPolicy {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn exited(exit_code: Option<i32>, timed_out: bool) -> Result<ToolResult, CherubError> {
+        Ok(ToolResult {
+            output: String::new(),
+            status: Some(crate::tools::ProcessStatus {
+                exit_code,
+                timed_out,
+                stdout: String::new(),
+                stderr: String::new(),
+                stdout_discarded: 0,
+                stderr_discarded: 0,
+                discarded_bytes: 0,
+                duration: Duration::ZERO,
+                cwd: std::path::PathBuf::from("/"),
+            }),
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn retry_policy_matches_configured_failures() {
+        let policy = retry_policy("max_attempts = 3\nexit_codes = [75]\ntimeout = true\n");
+        assert!(policy.should_retry(&exited(Some(75), false)));
+        assert!(policy.should_retry(&exited(None, true)));
+        assert!(!policy.should_retry(&exited(Some(1), false)));
+        assert!(!policy.should_retry(&exited(Some(0), false)));
+        assert!(!policy.should_retry(&Err(CherubError::ToolExecution("spawn".to_owned()))));
+
+        let errors = retry_policy("max_attempts = 2\nerrors = true\n");
+        assert!(errors.should_retry(&Err(CherubError::ToolExecution("spawn".to_owned()))));
+        assert!(!errors.should_retry(&Err(CherubError::NotPermitted)));
+    }
 }
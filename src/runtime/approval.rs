@@ -2,6 +2,11 @@ use std::future::Future;
 use std::time::Duration;
 
 use tokio::io::AsyncBufReadExt;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::enforcement::policy::Policy;
+use crate::enforcement::tier::Tier;
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
 
@@ -14,6 +19,9 @@ pub struct EscalationContext<'a> {
 pub enum ApprovalResult {
     Approved,
     Denied,
+    /// No human responded in time. Resolved by the policy's `[escalation]`
+    /// fallback in `request_with_policy`; treated as `Denied` without one.
+    TimedOut,
 }
 
 /// Abstraction over approval gates. Allows mock gates for testing.
@@ -24,6 +32,70 @@ pub trait ApprovalGate: Send + Sync {
     ) -> impl Future<Output = ApprovalResult> + Send;
 }
 
+/// Ask `gate` for approval of an escalation at `tier`, bounded by the policy's
+/// `[escalation] timeout_secs` (if configured).
+///
+/// The timeout is enforced here, not by the gate, so a gate without its own
+/// deadline cannot hang a headless run. A timed-out request resolves to the
+/// policy's `on_timeout` fallback; without an `[escalation]` section, or when
+/// a security stage flagged the call (`guarded`, see
+/// `enforcement::flagged_by_guards`), it is denied. Never returns `TimedOut`.
+///
+/// Cancelling `cancel` while the request is pending denies it, whatever the
+/// fallback: nobody is left to act on an approval.
+pub(crate) async fn request_with_policy<A: ApprovalGate>(
+    gate: &A,
+    context: &EscalationContext<'_>,
+    tier: Tier,
+    guarded: bool,
+    policy: &Policy,
+    cancel: Option<&CancellationToken>,
+) -> ApprovalResult {
+    let never = CancellationToken::new();
+    let cancel = cancel.unwrap_or(&never);
+    let request = async {
+        match policy.escalation {
+            Some(ref escalation) => {
+                tokio::time::timeout(escalation.timeout, gate.request_approval(context))
+                    .await
+                    .unwrap_or(ApprovalResult::TimedOut)
+            }
+            None => gate.request_approval(context).await,
+        }
+    };
+    let result = tokio::select! {
+        biased;
+        () = cancel.cancelled() => {
+            info!(tool = context.tool, "escalation cancelled, denying");
+            return ApprovalResult::Denied;
+        }
+        result = request => result,
+    };
+
+    match result {
+        ApprovalResult::TimedOut => {
+            let approved = !guarded
+                && policy
+                    .escalation
+                    .as_ref()
+                    .is_some_and(|e| e.on_timeout.approves(tier));
+            info!(
+                tool = context.tool,
+                tier = tier.as_str(),
+                guarded,
+                approved,
+                "escalation timed out, applying fallback"
+            );
+            if approved {
+                ApprovalResult::Approved
+            } else {
+                ApprovalResult::Denied
+            }
+        }
+        other => other,
+    }
+}
+
 pub struct CliApprovalGate {
     pub(crate) timeout: Duration,
 }
@@ -46,7 +118,7 @@ impl ApprovalGate for CliApprovalGate {
     ///
     /// Prints to stderr (not stdout — stdout is for tool output).
     /// Only `y` or `yes` (case-insensitive) → Approved.
-    /// Everything else (empty, `n`, garbage, timeout, EOF) → Denied.
+    /// Everything else (empty, `n`, garbage, EOF) → Denied. Timeout → TimedOut.
     async fn request_approval(&self, context: &EscalationContext<'_>) -> ApprovalResult {
         eprintln!(
             "\n[ESCALATION] {} wants to execute: {}",
@@ -68,8 +140,13 @@ impl ApprovalGate for CliApprovalGate {
                     ApprovalResult::Denied
                 }
             }
-            // Timeout, EOF, or I/O error → Denied
-            _ => {
+            // Timeout → fallback decided by request_with_policy
+            Err(_) => {
+                eprintln!();
+                ApprovalResult::TimedOut
+            }
+            // EOF or I/O error → Denied
+            Ok(_) => {
                 eprintln!();
                 ApprovalResult::Denied
             }
@@ -129,4 +206,152 @@ mod tests {
     fn whitespace_only_denies() {
         assert!(matches!(parse_input("  \t  "), ApprovalResult::Denied));
     }
+
+    // --- request_with_policy ---
+
+    use std::str::FromStr;
+
+    /// Gate that never answers — a headless run with nobody watching.
+    struct SilentGate;
+
+    impl ApprovalGate for SilentGate {
+        async fn request_approval(&self, _context: &EscalationContext<'_>) -> ApprovalResult {
+            std::future::pending().await
+        }
+    }
+
+    struct FixedGate(fn() -> ApprovalResult);
+
+    impl ApprovalGate for FixedGate {
+        async fn request_approval(&self, _context: &EscalationContext<'_>) -> ApprovalResult {
+            (self.0)()
+        }
+    }
+
+    fn context(params: &serde_json::Value) -> EscalationContext<'_> {
+        EscalationContext {
+            tool: "bash",
+            command: "rm -rf /tmp/x",
+            params,
+        }
+    }
+
+    fn policy(on_timeout: &str) -> Policy {
+        Policy::from_str(&format!(
+            "[tools]\n\n[escalation]\ntimeout_secs = 1\non_timeout = \"{on_timeout}\"\n"
+        ))
+        .expect("should parse")
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn silent_gate_times_out_to_deny() {
+        let params = serde_json::json!({});
+        let result = request_with_policy(
+            &SilentGate,
+            &context(&params),
+            Tier::Act,
+            false,
+            &policy("deny"),
+            None,
+        )
+        .await;
+        assert!(matches!(result, ApprovalResult::Denied));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn observe_fallback_approves_observe_tier() {
+        let params = serde_json::json!({});
+        let result = request_with_policy(
+            &SilentGate,
+            &context(&params),
+            Tier::Observe,
+            false,
+            &policy("observe"),
+            None,
+        )
+        .await;
+        assert!(matches!(result, ApprovalResult::Approved));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn observe_fallback_denies_guarded_escalations() {
+        let params = serde_json::json!({"command": "cat ~/.ssh/id_rsa"});
+        let result = request_with_policy(
+            &SilentGate,
+            &context(&params),
+            Tier::Observe,
+            true,
+            &policy("observe"),
+            None,
+        )
+        .await;
+        assert!(matches!(result, ApprovalResult::Denied));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn observe_fallback_denies_higher_tiers() {
+        let params = serde_json::json!({});
+        for tier in [Tier::Act, Tier::Commit] {
+            let result = request_with_policy(
+                &SilentGate,
+                &context(&params),
+                tier,
+                false,
+                &policy("observe"),
+                None,
+            )
+            .await;
+            assert!(matches!(result, ApprovalResult::Denied));
+        }
+    }
+
+    #[tokio::test]
+    async fn gate_timeout_without_section_denies() {
+        let params = serde_json::json!({});
+        let no_section = Policy::from_str("[tools]\n").expect("should parse");
+        let gate = FixedGate(|| ApprovalResult::TimedOut);
+        let result = request_with_policy(
+            &gate,
+            &context(&params),
+            Tier::Observe,
+            false,
+            &no_section,
+            None,
+        )
+        .await;
+        assert!(matches!(result, ApprovalResult::Denied));
+    }
+
+    #[tokio::test]
+    async fn cancellation_denies_pending_request() {
+        let params = serde_json::json!({});
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = request_with_policy(
+            &SilentGate,
+            &context(&params),
+            Tier::Observe,
+            false,
+            &policy("observe"),
+            Some(&token),
+        )
+        .await;
+        assert!(matches!(result, ApprovalResult::Denied));
+    }
+
+    #[tokio::test]
+    async fn human_answer_passes_through() {
+        let params = serde_json::json!({});
+        let gate = FixedGate(|| ApprovalResult::Approved);
+        let result = request_with_policy(
+            &gate,
+            &context(&params),
+            Tier::Commit,
+            false,
+            &policy("deny"),
+            None,
+        )
+        .await;
+        assert!(matches!(result, ApprovalResult::Approved));
+    }
 }
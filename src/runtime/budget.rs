@@ -0,0 +1,153 @@
+//! Per-turn limits on the agent loop.
+//!
+//! A confused model can keep calling tools without ever finishing. A
+//! [`LoopBudget`] caps one turn (one `run_turn` call) by model calls, tool
+//! calls, elapsed time, and tokens. Limits are checked between steps, never
+//! mid-call: when one runs out the turn ends with `BudgetExceeded`, and any
+//! tool calls the model had already proposed get an error result so the
+//! history stays valid.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::MAX_ITERATIONS;
+
+/// Limits for one turn. `None` means unlimited, except `max_iterations`,
+/// which defaults to the loop's built-in cap of 25 model calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoopBudget {
+    /// Model calls per turn.
+    pub max_iterations: Option<u32>,
+    /// Tool calls per turn, counting every call the model proposes.
+    pub max_tool_calls: Option<u32>,
+    /// Wall-clock time per turn.
+    pub max_duration: Option<Duration>,
+    /// Input plus output tokens over the turn's inference calls.
+    pub max_tokens: Option<u64>,
+}
+
+/// The limit a turn ran into. Displays as the amount allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    Iterations(u32),
+    ToolCalls(u32),
+    Duration(Duration),
+    Tokens(u64),
+}
+
+impl fmt::Display for BudgetLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetLimit::Iterations(n) => write!(f, "{n} model calls"),
+            BudgetLimit::ToolCalls(n) => write!(f, "{n} tool calls"),
+            BudgetLimit::Duration(d) => write!(f, "{}s", d.as_secs_f32()),
+            BudgetLimit::Tokens(n) => write!(f, "{n} tokens"),
+        }
+    }
+}
+
+/// What one turn has used so far. Model calls are counted by the loop itself.
+pub(crate) struct TurnUsage {
+    started: Instant,
+    pub(crate) tool_calls: u32,
+    pub(crate) tokens: u64,
+}
+
+impl TurnUsage {
+    pub(crate) fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            tool_calls: 0,
+            tokens: 0,
+        }
+    }
+}
+
+impl LoopBudget {
+    /// Model calls allowed per turn.
+    pub(crate) fn iterations(&self) -> u32 {
+        self.max_iterations.unwrap_or(MAX_ITERATIONS as u32)
+    }
+
+    /// The limit, other than model calls, that forbids another model call.
+    pub(crate) fn before_completion(&self, used: &TurnUsage) -> Option<BudgetLimit> {
+        if let Some(max) = self.max_tokens
+            && used.tokens >= max
+        {
+            return Some(BudgetLimit::Tokens(max));
+        }
+        self.elapsed(used)
+    }
+
+    /// The limit that forbids another tool call, if any.
+    pub(crate) fn before_tool_call(&self, used: &TurnUsage) -> Option<BudgetLimit> {
+        if let Some(max) = self.max_tool_calls
+            && used.tool_calls >= max
+        {
+            return Some(BudgetLimit::ToolCalls(max));
+        }
+        self.elapsed(used)
+    }
+
+    fn elapsed(&self, used: &TurnUsage) -> Option<BudgetLimit> {
+        self.max_duration
+            .filter(|max| used.started.elapsed() >= *max)
+            .map(BudgetLimit::Duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_by_default() {
+        let budget = LoopBudget::default();
+        let used = TurnUsage {
+            started: Instant::now(),
+            tool_calls: 1_000,
+            tokens: u64::MAX,
+        };
+        assert_eq!(budget.before_completion(&used), None);
+        assert_eq!(budget.before_tool_call(&used), None);
+        assert_eq!(budget.iterations(), MAX_ITERATIONS as u32);
+    }
+
+    #[test]
+    fn each_limit_trips() {
+        let budget = LoopBudget {
+            max_iterations: Some(3),
+            max_tool_calls: Some(5),
+            max_duration: None,
+            max_tokens: Some(1_000),
+        };
+        let mut used = TurnUsage::start();
+        assert_eq!(budget.before_completion(&used), None);
+
+        used.tokens = 1_000;
+        assert_eq!(
+            budget.before_completion(&used),
+            Some(BudgetLimit::Tokens(1_000))
+        );
+        assert_eq!(budget.iterations(), 3);
+
+        used.tool_calls = 5;
+        assert_eq!(
+            budget.before_tool_call(&used),
+            Some(BudgetLimit::ToolCalls(5))
+        );
+        assert_eq!(BudgetLimit::ToolCalls(5).to_string(), "5 tool calls");
+    }
+
+    #[test]
+    fn elapsed_time_trips_both_checks() {
+        let budget = LoopBudget {
+            max_duration: Some(Duration::ZERO),
+            ..LoopBudget::default()
+        };
+        let used = TurnUsage::start();
+        let limit = Some(BudgetLimit::Duration(Duration::ZERO));
+        assert_eq!(budget.before_completion(&used), limit);
+        assert_eq!(budget.before_tool_call(&used), limit);
+    }
+}
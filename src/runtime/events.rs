@@ -0,0 +1,165 @@
+//! Lifecycle events for observers of the agent loop.
+//!
+//! `OutputSink` renders a run for the user; the event bus reports it to
+//! anything else watching — a UI's progress view, a logger, a metrics
+//! collector — without changes to the loop. Events are owned values on a
+//! broadcast channel: each subscriber receives every event sent after it
+//! subscribed, and one that falls more than `CAPACITY` events behind misses
+//! the oldest (`RecvError::Lagged`) instead of slowing the loop down.
+//!
+//! Events carry what the audit log records (tool, action, tier, decision),
+//! never which policy rule matched.
+
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::budget::BudgetLimit;
+use super::session::InvocationRecord;
+use crate::enforcement::tier::Tier;
+use crate::error::CherubError;
+
+/// Events a subscriber can fall behind by before it starts missing some.
+const CAPACITY: usize = 256;
+
+/// One step of a run, in the order the loop takes them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    /// A `run_turn` call began. `turn_number` is the session's `next_ordinal`.
+    TurnStarted { session_id: Uuid, turn_number: i32 },
+    /// The model proposed a tool call, before enforcement sees it.
+    ToolProposed {
+        tool_use_id: String,
+        tool: String,
+        action: String,
+    },
+    /// Enforcement decided a call, the approval gate resolved an escalation,
+    /// or a hook vetoed the call.
+    DecisionMade {
+        tool_use_id: String,
+        decision: DecisionKind,
+        /// `None` for rejections, denials, and vetoes.
+        tier: Option<Tier>,
+    },
+    /// An escalated call is waiting on the approval gate.
+    EscalationRequested {
+        tool_use_id: String,
+        tool: String,
+        action: String,
+        tier: Tier,
+    },
+    /// A proposed call reached its final outcome, run or not.
+    ToolCompleted(InvocationRecord),
+    /// The `run_turn` call returned.
+    RunFinished {
+        session_id: Uuid,
+        turn_number: i32,
+        outcome: RunOutcome,
+    },
+}
+
+/// What became of a call at a decision point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionKind {
+    Allowed,
+    Rejected,
+    Escalated,
+    Approved,
+    Denied,
+    /// An `on_before_tool` hook stopped the call before enforcement.
+    Vetoed,
+}
+
+/// How a turn ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutcome {
+    Completed,
+    Cancelled,
+    BudgetExceeded(BudgetLimit),
+    /// Any other error, as displayed.
+    Failed(String),
+}
+
+impl RunOutcome {
+    pub(crate) fn of(result: &Result<(), CherubError>) -> Self {
+        match result {
+            Ok(()) => RunOutcome::Completed,
+            Err(CherubError::Cancelled { .. }) => RunOutcome::Cancelled,
+            Err(CherubError::BudgetExceeded { limit }) => RunOutcome::BudgetExceeded(*limit),
+            Err(e) => RunOutcome::Failed(e.to_string()),
+        }
+    }
+}
+
+/// The sending side of the bus, owned by `AgentLoop`.
+pub(crate) struct EventBus {
+    sender: broadcast::Sender<LifecycleEvent>,
+}
+
+impl EventBus {
+    pub(crate) fn new() -> Self {
+        Self {
+            sender: broadcast::channel(CAPACITY).0,
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<LifecycleEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Send `event` to every subscriber. Built only if someone is listening.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> LifecycleEvent) {
+        if self.sender.receiver_count() > 0 {
+            // Fails only when the last subscriber dropped since the check.
+            let _ = self.sender.send(event());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscribers_see_events_sent_after_subscribing() {
+        let bus = EventBus::new();
+        bus.emit(|| panic!("built with nobody listening"));
+
+        let mut first = bus.subscribe();
+        let started = LifecycleEvent::TurnStarted {
+            session_id: Uuid::nil(),
+            turn_number: 0,
+        };
+        bus.emit(|| started.clone());
+        let mut second = bus.subscribe();
+        bus.emit(|| LifecycleEvent::RunFinished {
+            session_id: Uuid::nil(),
+            turn_number: 0,
+            outcome: RunOutcome::Completed,
+        });
+
+        assert_eq!(first.try_recv().unwrap(), started);
+        assert!(matches!(
+            first.try_recv().unwrap(),
+            LifecycleEvent::RunFinished { .. }
+        ));
+        assert!(matches!(
+            second.try_recv().unwrap(),
+            LifecycleEvent::RunFinished { .. }
+        ));
+        assert!(second.try_recv().is_err());
+    }
+
+    #[test]
+    fn outcome_classifies_errors() {
+        assert_eq!(
+            RunOutcome::of(&Err(CherubError::Cancelled {
+                partial: String::new()
+            })),
+            RunOutcome::Cancelled
+        );
+        assert_eq!(
+            RunOutcome::of(&Err(CherubError::Provider("down".to_owned()))),
+            RunOutcome::Failed("provider error: down".to_owned())
+        );
+    }
+}
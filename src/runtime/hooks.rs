@@ -0,0 +1,73 @@
+//! User callbacks around tool calls and inference.
+//!
+//! A [`Hooks`] implementation sits between the model and enforcement, never
+//! in place of it. `on_before_tool` sees each call the model proposes and
+//! may let it through, veto it, or rewrite its parameters; a rewritten call
+//! is evaluated by the policy like any other, so a hook cannot turn a
+//! rejected action into an allowed one. `on_after_tool` may annotate the
+//! result the model sees, and `on_before_completion` may stop the turn
+//! before the next inference call.
+//!
+//! Every method defaults to doing nothing; implement the ones you need.
+
+use std::future::Future;
+
+use crate::providers::CompletionRequest;
+
+/// A tool call as a hook sees it.
+pub struct ToolCall<'a> {
+    pub tool_use_id: &'a str,
+    pub tool: &'a str,
+    pub params: &'a serde_json::Value,
+}
+
+/// What `on_before_tool` decided for a call.
+pub enum BeforeTool {
+    /// Send the call to enforcement unchanged.
+    Proceed,
+    /// Send the call to enforcement with these parameters instead.
+    Rewrite(serde_json::Value),
+    /// Do not run the call. The reason is the tool result the model sees.
+    Veto(String),
+}
+
+/// What `on_before_completion` decided for an inference call.
+pub enum BeforeCompletion {
+    Proceed,
+    /// End the turn with `HookVeto` before the call is made.
+    Veto(String),
+}
+
+/// Callbacks the agent loop runs at fixed points of a turn. Generic parameter
+/// on `AgentLoop`, following the same pattern as `ApprovalGate` and
+/// `OutputSink`.
+pub trait Hooks: Send + Sync {
+    /// Before enforcement evaluates a proposed call.
+    fn on_before_tool(&self, _call: &ToolCall<'_>) -> impl Future<Output = BeforeTool> + Send {
+        async { BeforeTool::Proceed }
+    }
+
+    /// After a call ran, with the result the model is about to see. Returned
+    /// text is appended to that result.
+    fn on_after_tool(
+        &self,
+        _call: &ToolCall<'_>,
+        _output: &str,
+        _is_error: bool,
+    ) -> impl Future<Output = Option<String>> + Send {
+        async { None }
+    }
+
+    /// Before each inference call of a turn.
+    fn on_before_completion(
+        &self,
+        _request: &CompletionRequest<'_>,
+    ) -> impl Future<Output = BeforeCompletion> + Send {
+        async { BeforeCompletion::Proceed }
+    }
+}
+
+/// Runs no callbacks. The default for `AgentLoop`.
+pub struct NoHooks;
+
+impl Hooks for NoHooks {}
@@ -1,31 +1,48 @@
 pub mod approval;
+pub mod budget;
+pub mod events;
+pub mod hooks;
 pub mod output;
 pub mod prompt;
 pub mod session;
 pub mod tokens;
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use tokio_util::sync::CancellationToken;
 use tracing::{info, info_span, warn};
 
 use crate::enforcement::policy::Policy;
-use crate::enforcement::{self, Decision};
+use crate::enforcement::tier::Tier;
+use crate::enforcement::{self, Decision, SessionContext};
 use crate::error::CherubError;
 use crate::providers::{
-    ApiUsage, ContentBlock, Message, Provider, StopReason, ToolDefinition, UserContent,
+    ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason, ToolDefinition,
+    UserContent, guard,
 };
-use crate::tools::{Proposed, ToolContext, ToolInvocation, ToolRegistry};
+use crate::tools::artifacts::Artifact;
+use crate::tools::attachment::Attachment;
+use crate::tools::{Executed, Proposed, Provenance, ToolContext, ToolInvocation, ToolRegistry};
 
 use approval::{ApprovalGate, ApprovalResult, EscalationContext};
+use budget::{BudgetLimit, LoopBudget, TurnUsage};
+use events::{DecisionKind, EventBus, LifecycleEvent, RunOutcome};
+use hooks::{BeforeCompletion, BeforeTool, Hooks, NoHooks, ToolCall};
 use output::{OutputEvent, OutputSink};
-use session::Session;
+use session::{InvocationOutcome, Session};
 
+#[cfg(any(feature = "postgres", feature = "sqlite-store"))]
+use crate::storage::{AuditDecision, AuditStore, NewAuditEvent};
 #[cfg(feature = "postgres")]
-use crate::storage::{
-    AuditDecision, AuditStore, CallType, CostStore, NewAuditEvent, NewTokenUsage,
-};
+use crate::storage::{CallType, CostStore, NewTokenUsage};
+
+pub(crate) const MAX_ITERATIONS: usize = 25;
 
-const MAX_ITERATIONS: usize = 25;
+/// Result given to tool calls left unrun when a turn's loop budget runs out.
+const BUDGET_SKIPPED: &str = "not run: this turn's budget is exhausted";
+
+/// Tool result for calls left unrun because the turn was cancelled.
+const CANCEL_SKIPPED: &str = "not run: the turn was cancelled";
 
 /// Compact when estimated tokens exceed this fraction of the context window.
 const COMPACTION_THRESHOLD_RATIO: f32 = 0.75;
@@ -38,6 +55,10 @@ const HARD_STOP_RATIO: f32 = 0.95;
 /// Number of recent messages to preserve across compaction (3 turn pairs).
 const COMPACTION_PRESERVE_RECENT: usize = 6;
 
+/// Tool results estimated at or above this many tokens are elided (outside the
+/// preserved recent messages) before falling back to summarization.
+const ELIDE_MIN_TOKENS: u32 = 500;
+
 /// Minimum message count before compaction is even considered.
 const COMPACTION_MIN_MESSAGES: usize = 10;
 
@@ -66,10 +87,26 @@ fn extract_user_text(content: &[UserContent]) -> String {
         .join(" ")
 }
 
+/// Tool output as the model sees it: with a line naming the files the call
+/// changed, when artifact tracking found any, and one per attachment.
+fn model_content(output: String, artifacts: &[Artifact], attachments: &[Attachment]) -> String {
+    let mut content = output;
+    if !artifacts.is_empty() {
+        content.push_str(&format!(
+            "\n[files changed: {}]",
+            Artifact::summary(artifacts)
+        ));
+    }
+    for attachment in attachments {
+        content.push_str(&format!("\n[attachment: {attachment}]"));
+    }
+    content
+}
+
 /// The agent loop. Owns session state and orchestrates model <-> tool interaction.
-/// Generic over approval gate and output sink for testability. Provider is
-/// `Box<dyn Provider>` — object-safe via `async_trait` (M13-prep).
-pub struct AgentLoop<A: ApprovalGate, O: OutputSink> {
+/// Generic over approval gate, output sink, and hooks for testability. Provider
+/// is `Box<dyn Provider>` — object-safe via `async_trait` (M13-prep).
+pub struct AgentLoop<A: ApprovalGate, O: OutputSink, H: Hooks = NoHooks> {
     session: Session,
     policy: Policy,
     provider: Box<dyn Provider>,
@@ -78,6 +115,7 @@ pub struct AgentLoop<A: ApprovalGate, O: OutputSink> {
     tool_definitions: Vec<ToolDefinition>,
     approval_gate: A,
     output: O,
+    hooks: H,
     /// Last API-reported input token count, used for smarter compaction triggering.
     last_usage: Option<ApiUsage>,
     /// Optional shared memory store for proactive injection (M6d).
@@ -88,18 +126,27 @@ pub struct AgentLoop<A: ApprovalGate, O: OutputSink> {
     /// Optional audit log store (M10).
     /// When set, every enforcement decision and execution outcome is appended.
     /// Failures are non-fatal — logged and skipped; they never block tool execution.
-    #[cfg(feature = "postgres")]
+    #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
     audit_store: Option<std::sync::Arc<dyn AuditStore>>,
     /// Optional cost tracking store (M12).
     /// When set, every LLM API call is recorded with token counts and cost.
     /// Failures are non-fatal — logged and skipped; they never block inference.
     #[cfg(feature = "postgres")]
     cost_store: Option<std::sync::Arc<dyn CostStore>>,
-    /// In-memory pricing table loaded from DB at startup.
-    /// Used by `record_cost()` to look up per-model rates.
-    /// Empty map = all costs recorded as $0.00 (no DB or no pricing configured).
-    #[cfg(feature = "postgres")]
+    /// Per-model rates, from the DB and/or the providers config `[pricing]`.
+    /// Empty map = every call costs $0.00 (no pricing configured).
     pricing_table: crate::providers::pricing::PricingTable,
+    /// Refuse further provider calls once the session's cost reaches this.
+    cost_ceiling_usd: Option<f64>,
+    /// Per-turn limits on model calls, tool calls, time, and tokens.
+    loop_budget: LoopBudget,
+    /// Longest an inference call may take before it is abandoned.
+    provider_timeout: Option<Duration>,
+    /// Stops the turn when triggered: the in-flight inference call, tool
+    /// execution, or escalation.
+    cancel: Option<CancellationToken>,
+    /// Lifecycle events for observers; see `subscribe`.
+    events: EventBus,
 }
 
 impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
@@ -122,18 +169,54 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
             tool_definitions,
             approval_gate,
             output,
+            hooks: NoHooks,
             last_usage: None,
             #[cfg(feature = "memory")]
             memory_store: None,
-            #[cfg(feature = "postgres")]
+            #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
             audit_store: None,
             #[cfg(feature = "postgres")]
             cost_store: None,
-            #[cfg(feature = "postgres")]
             pricing_table: std::collections::HashMap::new(),
+            cost_ceiling_usd: None,
+            loop_budget: LoopBudget::default(),
+            provider_timeout: None,
+            cancel: None,
+            events: EventBus::new(),
+        }
+    }
+
+    /// Run `hooks` around every tool call and inference call. See
+    /// [`hooks`] for what they may change; enforcement still decides.
+    pub fn with_hooks<H: Hooks>(self, hooks: H) -> AgentLoop<A, O, H> {
+        AgentLoop {
+            session: self.session,
+            policy: self.policy,
+            provider: self.provider,
+            registry: self.registry,
+            system_prompt: self.system_prompt,
+            tool_definitions: self.tool_definitions,
+            approval_gate: self.approval_gate,
+            output: self.output,
+            hooks,
+            last_usage: self.last_usage,
+            #[cfg(feature = "memory")]
+            memory_store: self.memory_store,
+            #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
+            audit_store: self.audit_store,
+            #[cfg(feature = "postgres")]
+            cost_store: self.cost_store,
+            pricing_table: self.pricing_table,
+            cost_ceiling_usd: self.cost_ceiling_usd,
+            loop_budget: self.loop_budget,
+            provider_timeout: self.provider_timeout,
+            cancel: self.cancel,
+            events: self.events,
         }
     }
+}
 
+impl<A: ApprovalGate, O: OutputSink, H: Hooks> AgentLoop<A, O, H> {
     /// Attach a memory store for proactive injection.
     ///
     /// When attached, the runtime embeds the user message and queries for relevant
@@ -156,7 +239,7 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
     /// they are logged and execution continues normally.
     ///
     /// Call this once after `new()` and before the first `run_turn()`.
-    #[cfg(feature = "postgres")]
+    #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
     pub fn with_audit_log(&mut self, store: std::sync::Arc<dyn AuditStore>) {
         self.audit_store = Some(store);
     }
@@ -175,21 +258,49 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
 
     /// Set the in-memory pricing table for cost computation.
     ///
-    /// The table is loaded from `model_pricing` at startup. If empty (no DB or
-    /// no pricing rows), all costs are recorded as $0.00.
-    #[cfg(feature = "postgres")]
+    /// The table is loaded from `model_pricing` and the providers config at
+    /// startup. If empty (no pricing configured), all costs are $0.00.
     pub fn with_pricing_table(&mut self, table: crate::providers::pricing::PricingTable) {
         self.pricing_table = table;
     }
 
-    /// Attach a PostgreSQL session store. Resumes the previous session for the given
+    /// Abort with `CostCeilingExceeded` instead of making another provider
+    /// call once this loop's estimated cost reaches `usd`. Needs a pricing
+    /// table entry for the model; unpriced calls cost nothing.
+    pub fn with_cost_ceiling(&mut self, usd: f64) {
+        self.cost_ceiling_usd = Some(usd);
+    }
+
+    /// Limit every turn by `budget`. A turn that runs out stops between
+    /// steps with `BudgetExceeded` instead of looping on.
+    pub fn with_loop_budget(&mut self, budget: LoopBudget) {
+        self.loop_budget = budget;
+    }
+
+    /// Abandon an inference call that takes longer than `timeout`, failing
+    /// the turn with `ProviderTimeout`. Text streamed before then is kept.
+    pub fn with_provider_timeout(&mut self, timeout: Duration) {
+        self.provider_timeout = Some(timeout);
+    }
+
+    /// Cancelling `token` (from another task or a Ctrl-C handler) stops the
+    /// turn, which fails with `Cancelled`. An inference call is abandoned, a
+    /// running tool is dropped (killing any commands it spawned), a pending
+    /// escalation is denied, and calls not yet run get an error result. A
+    /// cancelled token stays cancelled: attach a fresh one before the next
+    /// turn.
+    pub fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = Some(token);
+    }
+
+    /// Attach a session store (PostgreSQL or SQLite). Resumes the previous session for the given
     /// connector channel, or creates a new one.
     ///
     /// Call this once after `new()` and before the first `run_turn()`.
-    #[cfg(feature = "sessions")]
+    #[cfg(any(feature = "sessions", feature = "sqlite-store"))]
     pub async fn with_persistence(
         &mut self,
-        store: Box<dyn crate::storage::SessionStore>, // storage module gated by postgres
+        store: Box<dyn crate::storage::SessionStore>,
         connector: &str,
         connector_id: &str,
     ) -> Result<(), CherubError> {
@@ -207,6 +318,39 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
         Ok(())
     }
 
+    /// Keep the session in a JSON file at `path`, rewritten when the model
+    /// proposes tool calls and when each turn ends, so a run killed mid-task
+    /// can pick up where it stopped. The file carries the risk score and
+    /// circuit-breaker state too, so resuming does not reset either. An
+    /// existing file is resumed (see `Session::resume`); otherwise the current
+    /// session is written there.
+    ///
+    /// Call this once after `new()` and before the first `run_turn()`.
+    pub async fn with_session_file(&mut self, path: &std::path::Path) -> Result<(), CherubError> {
+        if tokio::fs::try_exists(path).await.unwrap_or(false) {
+            let resumed = Session::resume(path).await?;
+            #[cfg(any(feature = "sessions", feature = "sqlite-store"))]
+            if self.session.store.is_some() {
+                warn!("session file replaces the stored session; the store is detached");
+            }
+            tracing::info!(
+                session_id = %resumed.id,
+                message_count = resumed.messages.len(),
+                path = %path.display(),
+                "session resumed from file"
+            );
+            self.session = resumed;
+        }
+        self.session.file = Some(path.to_owned());
+        self.session.save(path).await
+    }
+
+    /// Undo the agent's most recent workspace change (see
+    /// `ToolRegistry::with_snapshots`). `None` if there is nothing to undo.
+    pub async fn rollback(&self) -> Result<Option<crate::tools::snapshot::Snapshot>, CherubError> {
+        self.registry.rollback().await
+    }
+
     /// Read-only view of the conversation history.
     pub fn session_messages(&self) -> &[Message] {
         &self.session.messages
@@ -217,9 +361,131 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
         self.session.id
     }
 
+    /// Token usage summed over every provider call since this loop was created:
+    /// inference, compaction summaries, and memory extraction. A resumed
+    /// session starts from zero; the cost store has the persisted history.
+    pub fn session_usage(&self) -> ApiUsage {
+        self.session.usage
+    }
+
+    /// Estimated USD cost of the calls counted in `session_usage`, at the
+    /// pricing table's rates.
+    pub fn session_cost_usd(&self) -> f64 {
+        self.session.cost_usd
+    }
+
+    /// The session this loop drives: history, proposed tool calls and their
+    /// outcomes, and usage totals.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Receive lifecycle events for every turn from now on: turns starting
+    /// and finishing, tool calls proposed, decided, escalated, and completed.
+    /// Observing never slows the loop; a subscriber that falls behind misses
+    /// events instead.
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<LifecycleEvent> {
+        self.events.subscribe()
+    }
+
+    /// Log a tool call's outcome on the session and report it to subscribers.
+    fn record_invocation(
+        &mut self,
+        turn_number: i32,
+        tool_use_id: &str,
+        tool: &str,
+        action: &str,
+        outcome: InvocationOutcome,
+    ) {
+        self.session
+            .record_invocation(turn_number, tool_use_id, tool, action, outcome);
+        if let Some(record) = self.session.invocations().last() {
+            self.events
+                .emit(|| LifecycleEvent::ToolCompleted(record.clone()));
+        }
+    }
+
+    /// `content` with any note `on_after_tool` adds for the call.
+    async fn annotated(&self, call: &ToolCall<'_>, content: String, is_error: bool) -> String {
+        match self.hooks.on_after_tool(call, &content, is_error).await {
+            Some(note) => format!("{content}\n\n{note}"),
+            None => content,
+        }
+    }
+
+    fn decided(&self, tool_use_id: &str, decision: DecisionKind, tier: Option<Tier>) {
+        self.events.emit(|| LifecycleEvent::DecisionMade {
+            tool_use_id: tool_use_id.to_owned(),
+            decision,
+            tier,
+        });
+    }
+
+    /// Add one provider call to the session totals.
+    fn add_usage(&mut self, usage: ApiUsage) {
+        use crate::providers::pricing;
+
+        let cost_usd = pricing::lookup_pricing(&self.pricing_table, self.provider.model_name())
+            .map_or(0.0, |p| pricing::compute_cost(&usage, &p));
+        self.session.add_usage(usage, cost_usd);
+    }
+
+    /// Whether the run's cancellation token (if any) has been cancelled.
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Fail if the cost ceiling has been reached. Checked before each
+    /// inference call, so a session ends between turns of the loop, never
+    /// mid-response.
+    fn check_cost_ceiling(&self) -> Result<(), CherubError> {
+        match self.cost_ceiling_usd {
+            Some(ceiling_usd) if self.session.cost_usd >= ceiling_usd => {
+                warn!(
+                    spent_usd = self.session.cost_usd,
+                    ceiling_usd, "cost ceiling reached"
+                );
+                Err(CherubError::CostCeilingExceeded {
+                    spent_usd: self.session.cost_usd,
+                    ceiling_usd,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Show and keep the text an interrupted inference call had streamed, so
+    /// the user sees it and the history does not lose it. Nothing to do if
+    /// none had arrived.
+    async fn keep_partial(&mut self, error: &CherubError) {
+        let (CherubError::ProviderTimeout { partial, .. } | CherubError::Cancelled { partial }) =
+            error
+        else {
+            return;
+        };
+        if partial.is_empty() {
+            return;
+        }
+        self.output.emit(OutputEvent::Text(partial)).await;
+        self.output
+            .emit(OutputEvent::Warning(
+                "Model response was interrupted — output is incomplete.",
+            ))
+            .await;
+        self.session.push(Message::Assistant {
+            content: vec![ContentBlock::Text {
+                text: partial.clone(),
+            }],
+            stop_reason: StopReason::EndTurn,
+        });
+        self.session.persist_last().await;
+    }
+
     /// Append an audit event non-fatally. Logs a warning on failure; never panics.
     /// Audit failures must never block tool execution — the runtime continues regardless.
-    #[cfg(feature = "postgres")]
+    #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
     async fn audit(&self, event: NewAuditEvent) {
         if let Some(ref store) = self.audit_store
             && let Err(e) = store.append(event).await
@@ -257,6 +523,9 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
 
     /// Check whether the session exceeds the context window threshold and compact if so.
     ///
+    /// Old tool outputs are elided first; the older conversation is summarized
+    /// only if that does not bring the estimate back under the threshold.
+    ///
     /// Called once per turn, after pushing the user message and building the effective
     /// system prompt, but **before** the iteration loop. Mid-turn compaction would
     /// break tool_use/tool_result pairing.
@@ -288,6 +557,26 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
             "context window threshold exceeded, compacting"
         );
 
+        // First, the cheap step: drop the bodies of old, large tool outputs.
+        let freed = self
+            .session
+            .elide_tool_outputs(COMPACTION_PRESERVE_RECENT, ELIDE_MIN_TOKENS);
+        if freed > 0 {
+            self.session.persist_compacted().await;
+            self.last_usage = None;
+
+            let remaining = input_tokens.saturating_sub(freed);
+            info!(freed, remaining, "elided old tool outputs");
+            if remaining < threshold {
+                self.output
+                    .emit(OutputEvent::Warning(
+                        "Context trimmed — older tool outputs were elided.",
+                    ))
+                    .await;
+                return Ok(());
+            }
+        }
+
         let Some((old, recent)) = self
             .session
             .split_for_compaction(COMPACTION_PRESERVE_RECENT)
@@ -317,7 +606,6 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
         self.session
             .apply_compaction(summary_user, summary_ack, recent);
 
-        #[cfg(feature = "sessions")]
         self.session.persist_compacted().await;
 
         // Clear last usage since the message list changed dramatically.
@@ -343,7 +631,7 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
     /// Uses a summarization-only prompt (no tools, no enforcement) — this is a
     /// runtime operation, not an agent tool call.
     async fn summarize(
-        &self,
+        &mut self,
         messages: &[Message],
         _effective_system: &str,
     ) -> Result<String, CherubError> {
@@ -366,17 +654,18 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
 
         let summary_messages = vec![Message::user_text(&summarize_prompt)];
 
-        let (response, _usage) = self
+        let (response, usage) = self
             .provider
-            .complete(
+            .complete(&CompletionRequest::new(
                 "You are a concise summarizer.",
                 &summary_messages,
                 &[], // No tools for summarization
-            )
+            ))
             .await?;
 
-        #[cfg(feature = "postgres")]
-        if let Some(u) = _usage {
+        if let Some(u) = usage {
+            self.add_usage(u);
+            #[cfg(feature = "postgres")]
             self.record_cost(u, CallType::Summarization).await;
         }
 
@@ -416,8 +705,8 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
     /// Non-fatal throughout: any failure at any step logs a warning and proceeds.
     /// Compaction must succeed regardless of memory flush outcomes.
     #[cfg(feature = "memory")]
-    async fn flush_to_memory(&self, messages: &[Message], effective_system: &str) {
-        let Some(ref store) = self.memory_store else {
+    async fn flush_to_memory(&mut self, messages: &[Message], effective_system: &str) {
+        let Some(store) = self.memory_store.clone() else {
             return;
         };
 
@@ -440,7 +729,11 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
         let extraction_messages = vec![Message::user_text(&extraction_prompt)];
         let result = self
             .provider
-            .complete(effective_system, &extraction_messages, &[])
+            .complete(&CompletionRequest::new(
+                effective_system,
+                &extraction_messages,
+                &[],
+            ))
             .await;
 
         let (response, extraction_usage) = match result {
@@ -451,8 +744,9 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
             }
         };
 
-        #[cfg(feature = "postgres")]
         if let Some(u) = extraction_usage {
+            self.add_usage(u);
+            #[cfg(feature = "postgres")]
             self.record_cost(u, CallType::Extraction).await;
         }
 
@@ -552,7 +846,13 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                 "confidence": 0.8
             });
 
-            let proposal = ToolInvocation::<Proposed>::new("memory", "execute", params);
+            let proposal = ToolInvocation::<Proposed>::new("memory", "execute", params)
+                .with_provenance(Provenance {
+                    session_id: Some(self.session.id),
+                    turn_number: Some(self.session.next_ordinal),
+                    model: Some(self.provider.model_name().to_owned()),
+                    tool_use_id: None,
+                });
             let (evaluated, decision) = enforcement::evaluate(proposal, &self.policy, None);
 
             match decision {
@@ -562,7 +862,11 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                         session_id: self.session.id,
                         turn_number: self.session.next_ordinal,
                     };
-                    match evaluated.execute(token, &self.registry, &ctx).await {
+                    match evaluated
+                        .execute(token, &self.registry, &ctx)
+                        .await
+                        .and_then(|r| enforcement::inspect_output(r.into_result(), &self.policy))
+                    {
                         Ok(_) => promoted_count += 1,
                         Err(e) => {
                             warn!(error = %e, "memory flush user promotion failed (non-fatal)");
@@ -572,7 +876,7 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                 Decision::Escalate { .. } => {
                     // User not available during compaction — fact stays in Working scope.
                 }
-                Decision::Reject => {
+                Decision::Reject { .. } => {
                     // Policy doesn't allow this write — fact stays in Working scope.
                 }
             }
@@ -594,6 +898,22 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
 
     /// Run one user turn: push user message, call model, handle tool calls in a loop.
     pub async fn run_turn(&mut self, content: Vec<UserContent>) -> Result<(), CherubError> {
+        let (session_id, turn_number) = (self.session.id, self.session.next_ordinal);
+        self.events.emit(|| LifecycleEvent::TurnStarted {
+            session_id,
+            turn_number,
+        });
+        let result = self.turn(content).await;
+        self.session.save_file().await;
+        self.events.emit(|| LifecycleEvent::RunFinished {
+            session_id,
+            turn_number,
+            outcome: RunOutcome::of(&result),
+        });
+        result
+    }
+
+    async fn turn(&mut self, content: Vec<UserContent>) -> Result<(), CherubError> {
         // Note: we don't use entered() spans because EnteredSpan is !Send,
         // which prevents this future from being spawned on tokio. Structured
         // fields on info!() calls carry the same context.
@@ -604,7 +924,6 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
         let user_query = extract_user_text(&content);
 
         self.session.push(Message::User { content });
-        #[cfg(feature = "sessions")]
         self.session.persist_last().await;
 
         // Build effective system prompt — may include injected memories (M6d).
@@ -662,7 +981,9 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
         // Runs before the iteration loop — mid-turn compaction would break tool_use/tool_result.
         self.maybe_compact(&effective_system).await?;
 
-        for iteration in 0..MAX_ITERATIONS {
+        let mut used = TurnUsage::start();
+        let max_iterations = self.loop_budget.iterations();
+        for iteration in 0..max_iterations {
             let _iter_span = info_span!("iteration", n = iteration);
 
             // Hard-stop safety net: if mid-turn tool results pushed us past 95%
@@ -688,17 +1009,41 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                 }
             }
 
-            let (assistant_msg, usage) = self
-                .provider
-                .complete(
-                    &effective_system,
-                    &self.session.messages,
-                    &self.tool_definitions,
-                )
-                .await?;
+            self.check_cost_ceiling()?;
+            if self.is_cancelled() {
+                return Err(cancelled());
+            }
+            if let Some(limit) = self.loop_budget.before_completion(&used) {
+                return Err(budget_exceeded(limit));
+            }
+            let mut request = CompletionRequest::new(
+                &effective_system,
+                &self.session.messages,
+                &self.tool_definitions,
+            );
+            request.timeout = self.provider_timeout;
+            request.cancel = self.cancel.as_ref();
+            if let BeforeCompletion::Veto(reason) = self.hooks.on_before_completion(&request).await
+            {
+                info!(iteration, "hook stopped the turn before inference");
+                return Err(CherubError::HookVeto(reason));
+            }
+            let (assistant_msg, usage) =
+                match guard::complete_guarded(&*self.provider, &request, &mut |_| {}).await {
+                    Ok(completion) => completion,
+                    Err(
+                        e @ (CherubError::ProviderTimeout { .. } | CherubError::Cancelled { .. }),
+                    ) => {
+                        self.keep_partial(&e).await;
+                        return Err(e);
+                    }
+                    Err(e) => return Err(e),
+                };
 
             if let Some(u) = usage {
                 self.last_usage = Some(u);
+                self.add_usage(u);
+                used.tokens += u64::from(u.input_tokens) + u64::from(u.output_tokens);
                 #[cfg(feature = "postgres")]
                 self.record_cost(u, CallType::Inference).await;
             }
@@ -740,12 +1085,14 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                 content,
                 stop_reason,
             });
-            #[cfg(feature = "sessions")]
             self.session.persist_last().await;
 
             if stop_reason != StopReason::ToolUse || tool_uses.is_empty() {
                 return Ok(());
             }
+            // Record the proposed calls before any runs: a crash mid-call
+            // leaves them for `Session::resume` to mark interrupted.
+            self.session.save_file().await;
 
             // Construct context for this tool execution cycle.
             let ctx = ToolContext {
@@ -795,8 +1142,53 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
             #[cfg(not(feature = "postgres"))]
             let budget_ctx: Option<crate::enforcement::BudgetContext> = None;
 
+            let mut session_ctx = SessionContext {
+                budget: budget_ctx,
+                risk_score: self.session.risk_score,
+                failures: std::mem::take(&mut self.session.tool_failures),
+            };
+
             // Process tool calls through enforcement
+            let mut exhausted = None;
             for (tool_use_id, name, input) in tool_uses {
+                if self.is_cancelled() {
+                    self.session.push(Message::ToolResult {
+                        tool_use_id,
+                        content: CANCEL_SKIPPED.to_owned(),
+                        is_error: true,
+                    });
+                    self.session.persist_last().await;
+                    continue;
+                }
+                // Out of budget: the remaining calls are answered, not run.
+                exhausted = exhausted.or_else(|| self.loop_budget.before_tool_call(&used));
+                if exhausted.is_some() {
+                    self.session.push(Message::ToolResult {
+                        tool_use_id,
+                        content: BUDGET_SKIPPED.to_owned(),
+                        is_error: true,
+                    });
+                    self.session.persist_last().await;
+                    continue;
+                }
+                used.tool_calls += 1;
+
+                // Hooks see the call first; what they let through is evaluated
+                // like any other call, rewritten or not.
+                let proposed = ToolCall {
+                    tool_use_id: &tool_use_id,
+                    tool: &name,
+                    params: &input,
+                };
+                let (input, veto) = match self.hooks.on_before_tool(&proposed).await {
+                    BeforeTool::Proceed => (input, None),
+                    BeforeTool::Rewrite(params) => {
+                        info!(tool = %name, "hook rewrote tool parameters");
+                        (params, None)
+                    }
+                    BeforeTool::Veto(reason) => (input, Some(reason)),
+                };
+
                 // Map composite tool name → enforcement policy name (MCP: server name).
                 let enforcement_name = self.registry.enforcement_name(&name);
                 // Enrich params with MCP metadata for McpStructured extraction.
@@ -811,18 +1203,85 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                     .unwrap_or("<no action>")
                     .to_owned();
                 let display_str = display_str.as_str();
+                self.events.emit(|| LifecycleEvent::ToolProposed {
+                    tool_use_id: tool_use_id.clone(),
+                    tool: name.clone(),
+                    action: display_str.to_owned(),
+                });
+
+                if let Some(reason) = veto {
+                    info!(decision = "VETOED", tool = %name, action = %display_str);
+                    self.decided(&tool_use_id, DecisionKind::Vetoed, None);
+                    self.output
+                        .emit(OutputEvent::ToolRejected {
+                            tool: &name,
+                            command: display_str,
+                        })
+                        .await;
+                    self.record_invocation(
+                        ctx.turn_number,
+                        &tool_use_id,
+                        &name,
+                        display_str,
+                        InvocationOutcome::Vetoed,
+                    );
+                    self.session.push(Message::ToolResult {
+                        tool_use_id,
+                        content: reason,
+                        is_error: true,
+                    });
+                    self.session.persist_last().await;
+                    continue;
+                }
+                let call = ToolCall {
+                    tool_use_id: &tool_use_id,
+                    tool: &name,
+                    params: &input,
+                };
 
                 let proposal =
-                    ToolInvocation::<Proposed>::new(enforcement_name, "execute", enriched);
+                    match ToolInvocation::<Proposed>::from_tool_call(enforcement_name, enriched) {
+                        Ok(proposal) => proposal.with_provenance(Provenance {
+                            session_id: Some(ctx.session_id),
+                            turn_number: Some(ctx.turn_number),
+                            model: Some(self.provider.model_name().to_owned()),
+                            tool_use_id: Some(tool_use_id.clone()),
+                        }),
+                        Err(e) => {
+                            // Malformed, not a policy decision: the model is told
+                            // what to fix.
+                            let err_msg = e.to_string();
+                            warn!(tool = %name, error = %err_msg, "malformed tool call");
+                            self.output.emit(OutputEvent::ToolError(&err_msg)).await;
+                            self.record_invocation(
+                                ctx.turn_number,
+                                &tool_use_id,
+                                &name,
+                                display_str,
+                                InvocationOutcome::Malformed,
+                            );
+                            self.session.push(Message::ToolResult {
+                                tool_use_id,
+                                content: err_msg,
+                                is_error: true,
+                            });
+                            self.session.persist_last().await;
+                            continue;
+                        }
+                    };
                 let (mut evaluated, decision) =
-                    enforcement::evaluate(proposal, &self.policy, budget_ctx.as_ref());
-                // Restore original composite name for registry lookup.
-                evaluated.tool = name.clone();
+                    enforcement::evaluate(proposal, &self.policy, Some(&session_ctx));
+                #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
+                let invocation_id = evaluated.id;
 
                 match decision {
                     Decision::Allow(token) => {
-                        #[cfg(feature = "postgres")]
-                        let tier_str = token.tier.as_str().to_owned();
+                        let tier = token.tier;
+                        self.decided(&tool_use_id, DecisionKind::Allowed, Some(tier));
+                        #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
+                        let tier_str = tier.as_str().to_owned();
+                        #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
+                        let issuance = token.issuance.clone();
                         info!(decision = "ALLOWED", tool = %name, action = %display_str);
                         self.output
                             .emit(OutputEvent::ToolAllowed {
@@ -831,12 +1290,51 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                             })
                             .await;
 
+                        session_ctx.record_execution(&self.policy, tier);
+                        if tier == Tier::Commit {
+                            self.provider.enter_commit_tier();
+                        }
                         let exec_start = Instant::now();
-                        match evaluated.execute(token, &self.registry, &ctx).await {
-                            Ok(result) => {
-                                let duration_ms = exec_start.elapsed().as_millis() as i64;
-                                info!(duration_ms = %duration_ms, "tool execution complete");
-                                #[cfg(feature = "postgres")]
+                        // Restore original composite name for registry lookup.
+                        evaluated.tool = name.clone();
+                        let exec_result = unless_cancelled(
+                            self.cancel.as_ref(),
+                            evaluated.execute(token, &self.registry, &ctx),
+                        )
+                        .await;
+                        // A cancelled call is not a tool failure.
+                        if !matches!(exec_result, Err(CherubError::Cancelled { .. })) {
+                            session_ctx.record_outcome(
+                                &self.policy,
+                                enforcement_name,
+                                exec_result.is_ok(),
+                            );
+                        }
+                        match exec_result.and_then(|executed| {
+                            executed
+                                .try_map_result(|r| enforcement::inspect_output(r, &self.policy))
+                        }) {
+                            Ok(executed) => {
+                                let Executed {
+                                    result,
+                                    tier,
+                                    issuance,
+                                    duration,
+                                    attempts,
+                                } = executed.state;
+                                let duration_ms = duration.as_millis() as i64;
+                                info!(
+                                    decision_id = %issuance.decision_id,
+                                    tier = tier.as_str(),
+                                    duration_ms = %duration_ms,
+                                    attempts,
+                                    exit_code = result.status.as_ref().and_then(|s| s.exit_code),
+                                    timed_out = result.status.as_ref().is_some_and(|s| s.timed_out),
+                                    artifacts = result.artifacts.len(),
+                                    attachments = result.attachments.len(),
+                                    "tool execution complete"
+                                );
+                                #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
                                 self.audit(NewAuditEvent {
                                     session_id: Some(ctx.session_id),
                                     user_id: ctx.user_id.clone(),
@@ -847,6 +1345,9 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                     tier: Some(tier_str),
                                     duration_ms: Some(duration_ms),
                                     is_error: Some(false),
+                                    invocation_id: Some(invocation_id),
+                                    model: Some(self.provider.model_name().to_owned()),
+                                    issuance: Some(issuance),
                                 })
                                 .await;
                                 if !result.output.is_empty() {
@@ -854,19 +1355,44 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                         .emit(OutputEvent::ToolOutput(&result.output))
                                         .await;
                                 }
+                                if !result.artifacts.is_empty() {
+                                    self.output
+                                        .emit(OutputEvent::FilesChanged(&result.artifacts))
+                                        .await;
+                                }
+                                for attachment in &result.attachments {
+                                    self.output.emit(OutputEvent::Attachment(attachment)).await;
+                                }
+                                self.record_invocation(
+                                    ctx.turn_number,
+                                    &tool_use_id,
+                                    &name,
+                                    display_str,
+                                    InvocationOutcome::Executed {
+                                        tier,
+                                        approved: false,
+                                        is_error: false,
+                                        duration,
+                                    },
+                                );
+                                let content = model_content(
+                                    result.output,
+                                    &result.artifacts,
+                                    &result.attachments,
+                                );
+                                let content = self.annotated(&call, content, false).await;
                                 self.session.push(Message::ToolResult {
                                     tool_use_id,
-                                    content: result.output,
+                                    content,
                                     is_error: false,
                                 });
-                                #[cfg(feature = "sessions")]
                                 self.session.persist_last().await;
                             }
                             Err(e) => {
                                 let duration_ms = exec_start.elapsed().as_millis() as i64;
                                 let err_msg = e.to_string();
                                 warn!(duration_ms = %duration_ms, error = %err_msg, "tool execution failed");
-                                #[cfg(feature = "postgres")]
+                                #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
                                 self.audit(NewAuditEvent {
                                     session_id: Some(ctx.session_id),
                                     user_id: ctx.user_id.clone(),
@@ -877,22 +1403,38 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                     tier: Some(tier_str),
                                     duration_ms: Some(duration_ms),
                                     is_error: Some(true),
+                                    invocation_id: Some(invocation_id),
+                                    model: Some(self.provider.model_name().to_owned()),
+                                    issuance: Some(issuance),
                                 })
                                 .await;
                                 self.output.emit(OutputEvent::ToolError(&err_msg)).await;
+                                self.record_invocation(
+                                    ctx.turn_number,
+                                    &tool_use_id,
+                                    &name,
+                                    display_str,
+                                    InvocationOutcome::Executed {
+                                        tier,
+                                        approved: false,
+                                        is_error: true,
+                                        duration: exec_start.elapsed(),
+                                    },
+                                );
+                                let content = self.annotated(&call, err_msg, true).await;
                                 self.session.push(Message::ToolResult {
                                     tool_use_id,
-                                    content: err_msg,
+                                    content,
                                     is_error: true,
                                 });
-                                #[cfg(feature = "sessions")]
                                 self.session.persist_last().await;
                             }
                         }
                     }
-                    Decision::Reject => {
-                        info!(decision = "REJECTED", tool = %name, action = %display_str);
-                        #[cfg(feature = "postgres")]
+                    Decision::Reject { reason, suggestion } => {
+                        self.decided(&tool_use_id, DecisionKind::Rejected, None);
+                        info!(decision = "REJECTED", tool = %name, action = %display_str, reason = reason.as_str());
+                        #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
                         self.audit(NewAuditEvent {
                             session_id: Some(ctx.session_id),
                             user_id: ctx.user_id.clone(),
@@ -903,6 +1445,9 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                             tier: None,
                             duration_ms: None,
                             is_error: None,
+                            invocation_id: Some(invocation_id),
+                            model: Some(self.provider.model_name().to_owned()),
+                            issuance: None,
                         })
                         .await;
                         self.output
@@ -911,19 +1456,32 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                 command: display_str,
                             })
                             .await;
+                        self.record_invocation(
+                            ctx.turn_number,
+                            &tool_use_id,
+                            &name,
+                            display_str,
+                            InvocationOutcome::Rejected,
+                        );
+                        // Displays as "action not permitted" whatever the reason.
+                        let err = CherubError::Rejected(reason);
+                        let content = match suggestion {
+                            Some(hint) => format!("{err}: {hint}"),
+                            None => err.to_string(),
+                        };
                         self.session.push(Message::ToolResult {
                             tool_use_id,
-                            content: "action not permitted".to_owned(),
+                            content,
                             is_error: true,
                         });
-                        #[cfg(feature = "sessions")]
                         self.session.persist_last().await;
                     }
                     Decision::Escalate { tier } => {
-                        #[cfg(feature = "postgres")]
+                        self.decided(&tool_use_id, DecisionKind::Escalated, Some(tier));
+                        #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
                         let tier_str = tier.as_str().to_owned();
                         info!(decision = "ESCALATED", tool = %name, action = %display_str);
-                        #[cfg(feature = "postgres")]
+                        #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
                         self.audit(NewAuditEvent {
                             session_id: Some(ctx.session_id),
                             user_id: ctx.user_id.clone(),
@@ -934,17 +1492,39 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                             tier: Some(tier_str.clone()),
                             duration_ms: None,
                             is_error: None,
+                            invocation_id: Some(invocation_id),
+                            model: Some(self.provider.model_name().to_owned()),
+                            issuance: None,
                         })
                         .await;
 
+                        self.events.emit(|| LifecycleEvent::EscalationRequested {
+                            tool_use_id: tool_use_id.clone(),
+                            tool: name.clone(),
+                            action: display_str.to_owned(),
+                            tier,
+                        });
                         let context = EscalationContext {
                             tool: &name,
                             command: display_str,
                             params: &input,
                         };
-                        match self.approval_gate.request_approval(&context).await {
+                        match approval::request_with_policy(
+                            &self.approval_gate,
+                            &context,
+                            tier,
+                            enforcement::flagged_by_guards(&self.policy, &evaluated),
+                            &self.policy,
+                            self.cancel.as_ref(),
+                        )
+                        .await
+                        {
                             ApprovalResult::Approved => {
-                                let token = enforcement::approve_escalation(tier);
+                                self.decided(&tool_use_id, DecisionKind::Approved, Some(tier));
+                                let token =
+                                    enforcement::approve_escalation(tier, &evaluated, &self.policy);
+                                #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
+                                let issuance = token.issuance.clone();
                                 info!(decision = "APPROVED", tool = %name, action = %display_str);
                                 self.output
                                     .emit(OutputEvent::ToolApproved {
@@ -953,12 +1533,53 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                     })
                                     .await;
 
+                                session_ctx.record_execution(&self.policy, tier);
+                                if tier == Tier::Commit {
+                                    self.provider.enter_commit_tier();
+                                }
                                 let exec_start = Instant::now();
-                                match evaluated.execute(token, &self.registry, &ctx).await {
-                                    Ok(result) => {
-                                        let duration_ms = exec_start.elapsed().as_millis() as i64;
-                                        info!(duration_ms = %duration_ms, "tool execution complete");
-                                        #[cfg(feature = "postgres")]
+                                evaluated.tool = name.clone();
+                                let exec_result = unless_cancelled(
+                                    self.cancel.as_ref(),
+                                    evaluated.execute(token, &self.registry, &ctx),
+                                )
+                                .await;
+                                if !matches!(exec_result, Err(CherubError::Cancelled { .. })) {
+                                    session_ctx.record_outcome(
+                                        &self.policy,
+                                        enforcement_name,
+                                        exec_result.is_ok(),
+                                    );
+                                }
+                                match exec_result.and_then(|executed| {
+                                    executed.try_map_result(|r| {
+                                        enforcement::inspect_output(r, &self.policy)
+                                    })
+                                }) {
+                                    Ok(executed) => {
+                                        let Executed {
+                                            result,
+                                            tier,
+                                            issuance,
+                                            duration,
+                                            attempts,
+                                        } = executed.state;
+                                        let duration_ms = duration.as_millis() as i64;
+                                        info!(
+                                            decision_id = %issuance.decision_id,
+                                            tier = tier.as_str(),
+                                            duration_ms = %duration_ms,
+                                            attempts,
+                                            exit_code = result.status.as_ref().and_then(|s| s.exit_code),
+                                            timed_out = result.status.as_ref().is_some_and(|s| s.timed_out),
+                                            artifacts = result.artifacts.len(),
+                                            attachments = result.attachments.len(),
+                                            "tool execution complete"
+                                        );
+                                        #[cfg(any(
+                                            feature = "postgres",
+                                            feature = "sqlite-store"
+                                        ))]
                                         self.audit(NewAuditEvent {
                                             session_id: Some(ctx.session_id),
                                             user_id: ctx.user_id.clone(),
@@ -969,6 +1590,9 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                             tier: Some(tier_str.clone()),
                                             duration_ms: Some(duration_ms),
                                             is_error: Some(false),
+                                            invocation_id: Some(invocation_id),
+                                            model: Some(self.provider.model_name().to_owned()),
+                                            issuance: Some(issuance),
                                         })
                                         .await;
                                         if !result.output.is_empty() {
@@ -976,19 +1600,49 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                                 .emit(OutputEvent::ToolOutput(&result.output))
                                                 .await;
                                         }
+                                        if !result.artifacts.is_empty() {
+                                            self.output
+                                                .emit(OutputEvent::FilesChanged(&result.artifacts))
+                                                .await;
+                                        }
+                                        for attachment in &result.attachments {
+                                            self.output
+                                                .emit(OutputEvent::Attachment(attachment))
+                                                .await;
+                                        }
+                                        self.record_invocation(
+                                            ctx.turn_number,
+                                            &tool_use_id,
+                                            &name,
+                                            display_str,
+                                            InvocationOutcome::Executed {
+                                                tier,
+                                                approved: true,
+                                                is_error: false,
+                                                duration,
+                                            },
+                                        );
+                                        let content = model_content(
+                                            result.output,
+                                            &result.artifacts,
+                                            &result.attachments,
+                                        );
+                                        let content = self.annotated(&call, content, false).await;
                                         self.session.push(Message::ToolResult {
                                             tool_use_id,
-                                            content: result.output,
+                                            content,
                                             is_error: false,
                                         });
-                                        #[cfg(feature = "sessions")]
                                         self.session.persist_last().await;
                                     }
                                     Err(e) => {
                                         let duration_ms = exec_start.elapsed().as_millis() as i64;
                                         let err_msg = e.to_string();
                                         warn!(duration_ms = %duration_ms, error = %err_msg, "tool execution failed");
-                                        #[cfg(feature = "postgres")]
+                                        #[cfg(any(
+                                            feature = "postgres",
+                                            feature = "sqlite-store"
+                                        ))]
                                         self.audit(NewAuditEvent {
                                             session_id: Some(ctx.session_id),
                                             user_id: ctx.user_id.clone(),
@@ -999,22 +1653,38 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                             tier: Some(tier_str.clone()),
                                             duration_ms: Some(duration_ms),
                                             is_error: Some(true),
+                                            invocation_id: Some(invocation_id),
+                                            model: Some(self.provider.model_name().to_owned()),
+                                            issuance: Some(issuance),
                                         })
                                         .await;
                                         self.output.emit(OutputEvent::ToolError(&err_msg)).await;
+                                        self.record_invocation(
+                                            ctx.turn_number,
+                                            &tool_use_id,
+                                            &name,
+                                            display_str,
+                                            InvocationOutcome::Executed {
+                                                tier,
+                                                approved: true,
+                                                is_error: true,
+                                                duration: exec_start.elapsed(),
+                                            },
+                                        );
+                                        let content = self.annotated(&call, err_msg, true).await;
                                         self.session.push(Message::ToolResult {
                                             tool_use_id,
-                                            content: err_msg,
+                                            content,
                                             is_error: true,
                                         });
-                                        #[cfg(feature = "sessions")]
                                         self.session.persist_last().await;
                                     }
                                 }
                             }
-                            ApprovalResult::Denied => {
+                            ApprovalResult::Denied | ApprovalResult::TimedOut => {
+                                self.decided(&tool_use_id, DecisionKind::Denied, None);
                                 info!(decision = "DENIED", tool = %name, action = %display_str);
-                                #[cfg(feature = "postgres")]
+                                #[cfg(any(feature = "postgres", feature = "sqlite-store"))]
                                 self.audit(NewAuditEvent {
                                     session_id: Some(ctx.session_id),
                                     user_id: ctx.user_id.clone(),
@@ -1025,6 +1695,9 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                     tier: Some(tier_str),
                                     duration_ms: None,
                                     is_error: None,
+                                    invocation_id: Some(invocation_id),
+                                    model: Some(self.provider.model_name().to_owned()),
+                                    issuance: None,
                                 })
                                 .await;
                                 self.output
@@ -1033,13 +1706,19 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                                         command: display_str,
                                     })
                                     .await;
+                                self.record_invocation(
+                                    ctx.turn_number,
+                                    &tool_use_id,
+                                    &name,
+                                    display_str,
+                                    InvocationOutcome::Denied,
+                                );
                                 // Policy opacity: identical message to Reject
                                 self.session.push(Message::ToolResult {
                                     tool_use_id,
                                     content: "action not permitted".to_owned(),
                                     is_error: true,
                                 });
-                                #[cfg(feature = "sessions")]
                                 self.session.persist_last().await;
                             }
                         }
@@ -1047,27 +1726,76 @@ impl<A: ApprovalGate, O: OutputSink> AgentLoop<A, O> {
                 }
             }
 
-            if iteration == MAX_ITERATIONS - 1 {
-                warn!(
-                    max_iterations = MAX_ITERATIONS,
-                    "reached max iterations, stopping turn"
-                );
-                self.output
-                    .emit(OutputEvent::Warning(
-                        "Reached maximum iterations, stopping.",
-                    ))
-                    .await;
+            self.session.risk_score = session_ctx.risk_score;
+            self.session.tool_failures = session_ctx.failures;
+
+            if self.is_cancelled() {
+                return Err(cancelled());
+            }
+            if let Some(limit) = exhausted {
+                return Err(budget_exceeded(limit));
             }
         }
 
+        if let Some(max) = self.loop_budget.max_iterations {
+            return Err(budget_exceeded(BudgetLimit::Iterations(max)));
+        }
+        warn!(max_iterations, "reached max iterations, stopping turn");
+        self.output
+            .emit(OutputEvent::Warning(
+                "Reached maximum iterations, stopping.",
+            ))
+            .await;
         Ok(())
     }
 }
 
+fn cancelled() -> CherubError {
+    info!("turn cancelled");
+    CherubError::Cancelled {
+        partial: String::new(),
+    }
+}
+
+/// Await `execution` unless `cancel` fires first. Dropping the future is what
+/// stops the tool: a command's process group is killed with it.
+async fn unless_cancelled<T>(
+    cancel: Option<&CancellationToken>,
+    execution: impl Future<Output = Result<T, CherubError>>,
+) -> Result<T, CherubError> {
+    let Some(cancel) = cancel else {
+        return execution.await;
+    };
+    tokio::select! {
+        biased;
+        () = cancel.cancelled() => Err(cancelled()),
+        result = execution => result,
+    }
+}
+
+fn budget_exceeded(limit: BudgetLimit) -> CherubError {
+    warn!(%limit, "loop budget exhausted, stopping turn");
+    CherubError::BudgetExceeded { limit }
+}
+
 #[cfg(all(test, feature = "memory"))]
 mod tests {
     use super::*;
 
+    #[test]
+    fn model_content_notes_files_and_attachments() {
+        let artifacts = [Artifact {
+            path: "out.png".into(),
+            change: crate::tools::artifacts::ArtifactChange::Created,
+        }];
+        let attachments = [Attachment::bytes("image/png", vec![0; 3]).with_name("out.png")];
+        assert_eq!(
+            model_content("rendered".to_owned(), &artifacts, &attachments),
+            "rendered\n[files changed: created out.png]\n[attachment: out.png (image/png, 3 bytes)]"
+        );
+        assert_eq!(model_content("ok".to_owned(), &[], &[]), "ok");
+    }
+
     #[test]
     fn extract_user_text_single_text() {
         let content = vec![UserContent::Text("hello world".to_owned())];
@@ -1,5 +1,8 @@
 use std::future::Future;
 
+use crate::tools::artifacts::Artifact;
+use crate::tools::attachment::Attachment;
+
 /// Events emitted by the agent loop during execution.
 pub enum OutputEvent<'a> {
     /// Text content from the model's response.
@@ -14,6 +17,10 @@ pub enum OutputEvent<'a> {
     ToolDenied { tool: &'a str, command: &'a str },
     /// Successful output from tool execution.
     ToolOutput(&'a str),
+    /// Files a tool execution created, modified, or deleted (artifact tracking).
+    FilesChanged(&'a [Artifact]),
+    /// A binary payload returned by a tool execution.
+    Attachment(&'a Attachment),
     /// Error output from tool execution.
     ToolError(&'a str),
     /// Runtime warning (e.g., max iterations reached).
@@ -46,6 +53,10 @@ impl OutputSink for StdoutSink {
                 println!("[DENIED] {tool}: {command}");
             }
             OutputEvent::ToolOutput(output) => println!("{output}"),
+            OutputEvent::FilesChanged(artifacts) => {
+                println!("[FILES] {}", Artifact::summary(artifacts));
+            }
+            OutputEvent::Attachment(attachment) => println!("[ATTACHMENT] {attachment}"),
             OutputEvent::ToolError(err) => println!("[ERROR] {err}"),
             OutputEvent::Warning(msg) => println!("[WARNING] {msg}"),
         }
@@ -1,3 +1,6 @@
+use crate::enforcement::policy::{Policy, PolicySummary};
+use crate::providers::ToolDefinition;
+
 /// Format the memory injection section appended to the system prompt before each turn.
 ///
 /// Splits memories into **Verified** (Explicit/Confirmed) and **Inferred** subsections.
@@ -67,6 +70,10 @@ pub fn serialize_messages_for_prompt(messages: &[crate::providers::Message]) ->
                     match c {
                         UserContent::Text(text) => out.push_str(text),
                         UserContent::Image { .. } => out.push_str("[image]"),
+                        UserContent::Document { name, .. } => match name {
+                            Some(name) => out.push_str(&format!("[document: {name}]")),
+                            None => out.push_str("[document]"),
+                        },
                     }
                 }
                 out.push('\n');
@@ -97,7 +104,67 @@ pub fn serialize_messages_for_prompt(messages: &[crate::providers::Message]) ->
     out
 }
 
-/// Build the system prompt for the agent.
+/// Assembles the system prompt: workspace info, then the tool manifest and a
+/// policy summary when given. Facts only — no safety guardrails (the
+/// enforcement layer handles that).
+pub struct PromptBuilder<'a> {
+    cwd: &'a str,
+    tools: Vec<ToolDefinition>,
+    policy: Option<PolicySummary>,
+}
+
+impl<'a> PromptBuilder<'a> {
+    pub fn new(cwd: &'a str) -> Self {
+        Self {
+            cwd,
+            tools: Vec::new(),
+            policy: None,
+        }
+    }
+
+    /// List the registered tools, one line each. Full schemas go to the
+    /// provider separately.
+    pub fn with_tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Say which tools are enabled and which wait for approval. Tool names
+    /// only — see `Policy::summary()`.
+    pub fn with_policy(mut self, policy: &Policy) -> Self {
+        self.policy = Some(policy.summary());
+        self
+    }
+
+    pub fn build(self) -> String {
+        let mut p = build_system_prompt(self.cwd);
+
+        if !self.tools.is_empty() {
+            p.push_str("\n\n## Available Tools\n\n");
+            for tool in &self.tools {
+                let summary = tool.description.lines().next().unwrap_or_default();
+                p.push_str(&format!("- `{}`: {summary}\n", tool.name));
+            }
+        }
+
+        if let Some(policy) = self.policy {
+            p.push_str("\n\n## Policy\n\n");
+            let enabled: Vec<String> = policy.enabled.iter().map(|t| format!("`{t}`")).collect();
+            p.push_str(&format!("Enabled tools: {}.\n", enabled.join(", ")));
+            for tool in &policy.approval_required {
+                p.push_str(&format!(
+                    "Some `{tool}` operations need user approval before they run.\n"
+                ));
+            }
+            p.push_str("Calls the policy does not permit return 'action not permitted'.");
+        }
+
+        p
+    }
+}
+
+/// Build the workspace section of the system prompt. `PromptBuilder` adds
+/// the tool manifest and policy summary on top.
 ///
 /// Minimal prompt — no safety guardrails (enforcement layer handles that).
 /// Sections are appended based on which features are compiled in.
@@ -187,6 +254,48 @@ mod tests {
         assert!(prompt.contains("/home/user/project"));
     }
 
+    #[test]
+    fn builder_adds_tool_manifest_and_policy_summary() {
+        use std::str::FromStr;
+
+        let policy = Policy::from_str(
+            r#"
+[tools.bash]
+enabled = true
+
+[tools.bash.actions.destructive]
+tier = "commit"
+patterns = ["^rm "]
+"#,
+        )
+        .unwrap();
+        let prompt = PromptBuilder::new("/work")
+            .with_tools(vec![ToolDefinition {
+                name: "bash".to_owned(),
+                description: "Execute a bash command.\nMore detail.".to_owned(),
+                input_schema: serde_json::json!({"type": "object"}),
+            }])
+            .with_policy(&policy)
+            .build();
+        assert!(prompt.starts_with(&build_system_prompt("/work")));
+        assert!(prompt.contains("- `bash`: Execute a bash command.\n"));
+        assert!(!prompt.contains("More detail."));
+        assert!(prompt.contains("Enabled tools: `bash`."));
+        assert!(prompt.contains("Some `bash` operations need user approval"));
+        // Policy opacity: no action names, tiers, or patterns.
+        for secret in ["destructive", "commit", "^rm "] {
+            assert!(!prompt.contains(secret), "prompt leaks {secret:?}");
+        }
+    }
+
+    #[test]
+    fn builder_without_extras_matches_base_prompt() {
+        assert_eq!(
+            PromptBuilder::new("/work").build(),
+            build_system_prompt("/work")
+        );
+    }
+
     #[test]
     fn serialize_messages_user_and_assistant() {
         use crate::providers::{ContentBlock, Message, StopReason};
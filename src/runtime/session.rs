@@ -1,11 +1,64 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::providers::Message;
-#[cfg(feature = "sessions")]
+use super::tokens;
+use crate::enforcement::breaker::ToolFailures;
+use crate::enforcement::tier::Tier;
+use crate::error::CherubError;
+use crate::providers::{ApiUsage, ContentBlock, Message};
+#[cfg(any(feature = "sessions", feature = "sqlite-store"))]
 use crate::storage::SessionStore;
 
+/// Version of the session file layout written by [`Session::save`].
+const SESSION_FILE_VERSION: u32 = 1;
+
+/// Result given to a tool call whose outcome a session file does not record.
+const INTERRUPTED_CALL: &str = "interrupted: the session ended before this call's result was recorded. It may or may not have taken effect; check before retrying.";
+
+/// What became of one tool call the model proposed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum InvocationOutcome {
+    /// The call could not be parsed; it never reached enforcement.
+    Malformed,
+    /// Enforcement rejected the call.
+    Rejected,
+    /// An `on_before_tool` hook vetoed the call before enforcement saw it.
+    Vetoed,
+    /// The call was escalated and the approval gate denied it (or timed out).
+    Denied,
+    /// The call ran, either allowed outright or `approved` after escalation.
+    Executed {
+        tier: Tier,
+        approved: bool,
+        is_error: bool,
+        duration: Duration,
+    },
+}
+
+/// One proposed tool call and its outcome, in the order the model made them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvocationRecord {
+    /// `next_ordinal` when the call was proposed (same as `ToolContext::turn_number`).
+    pub turn_number: i32,
+    pub tool_use_id: String,
+    pub tool: String,
+    /// The command, action, or MCP tool name the call carried.
+    pub action: String,
+    pub outcome: InvocationOutcome,
+}
+
 /// In-memory conversation history with optional PostgreSQL persistence.
 ///
+/// Besides the messages, a session keeps a log of every tool call the model
+/// proposed and the usage of every provider call made for it. A session file
+/// (`save`/`resume`) carries both; a session restored from a `SessionStore`
+/// starts them empty (the audit log and cost store hold that history).
+///
 /// Session owns its store — AgentLoop stays at 3 type params forever.
 /// Persistence is Session's concern, not AgentLoop's.
 pub struct Session {
@@ -15,8 +68,44 @@ pub struct Session {
     pub(crate) next_ordinal: i32,
     /// How many times this session has been compacted.
     pub(crate) compaction_count: u32,
-    #[cfg(feature = "sessions")]
-    store: Option<Box<dyn SessionStore>>,
+    pub(crate) invocations: Vec<InvocationRecord>,
+    /// Sum of API-reported usage over every provider call.
+    pub(crate) usage: ApiUsage,
+    /// Estimated USD cost of the calls counted in `usage`.
+    pub(crate) cost_usd: f64,
+    /// Cumulative risk of actions executed in this session (`[risk]` policy section).
+    /// Carried across turns; fed to enforcement via `SessionContext`.
+    pub(crate) risk_score: u32,
+    /// Recent per-tool execution failures (`[circuit_breaker]` policy section).
+    /// Carried across turns like `risk_score`.
+    pub(crate) tool_failures: ToolFailures,
+    /// Session file rewritten by `save_file`, if set: when the model proposes
+    /// tool calls and when a turn ends.
+    pub(crate) file: Option<PathBuf>,
+    #[cfg(any(feature = "sessions", feature = "sqlite-store"))]
+    pub(crate) store: Option<Box<dyn SessionStore>>,
+}
+
+/// On-disk form of a session (JSON).
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SessionFile {
+    version: u32,
+    id: Uuid,
+    user_id: String,
+    /// Directory the session was running in, to flag a resume elsewhere.
+    working_dir: Option<PathBuf>,
+    compaction_count: u32,
+    usage: ApiUsage,
+    cost_usd: f64,
+    /// Enforcement state, so a resume keeps its risk budget spent and its
+    /// circuit breakers open. Absent in files from before it was recorded.
+    #[serde(default)]
+    risk_score: u32,
+    #[serde(default)]
+    tool_failures: HashMap<String, Vec<SystemTime>>,
+    invocations: Vec<InvocationRecord>,
+    messages: Vec<Message>,
 }
 
 impl Session {
@@ -29,13 +118,19 @@ impl Session {
             messages: Vec::new(),
             next_ordinal: 0,
             compaction_count: 0,
-            #[cfg(feature = "sessions")]
+            invocations: Vec::new(),
+            usage: ApiUsage::default(),
+            cost_usd: 0.0,
+            risk_score: 0,
+            tool_failures: ToolFailures::default(),
+            file: None,
+            #[cfg(any(feature = "sessions", feature = "sqlite-store"))]
             store: None,
         }
     }
 
     /// Restore a session from persisted state with an attached store.
-    #[cfg(feature = "sessions")]
+    #[cfg(any(feature = "sessions", feature = "sqlite-store"))]
     pub fn from_persisted(
         id: Uuid,
         messages: Vec<Message>,
@@ -49,6 +144,12 @@ impl Session {
             messages,
             next_ordinal,
             compaction_count: 0,
+            invocations: Vec::new(),
+            usage: ApiUsage::default(),
+            cost_usd: 0.0,
+            risk_score: 0,
+            tool_failures: ToolFailures::default(),
+            file: None,
             store: Some(store),
         }
     }
@@ -61,26 +162,166 @@ impl Session {
         ordinal
     }
 
-    /// Persist the last pushed message to the store (non-fatal: logs warning on failure).
-    /// Called by AgentLoop after each `push()`.
-    #[cfg(feature = "sessions")]
+    /// Persist the last pushed message to the store (non-fatal: logs warning
+    /// on failure). Called by AgentLoop after each `push()`.
     pub async fn persist_last(&self) {
-        let Some(ref store) = self.store else {
-            return;
+        #[cfg(any(feature = "sessions", feature = "sqlite-store"))]
+        {
+            let Some(ref store) = self.store else {
+                return;
+            };
+            let Some(msg) = self.messages.last() else {
+                return;
+            };
+            let ordinal = self.next_ordinal - 1;
+            if let Err(e) = store.push_message(self.id, ordinal, msg).await {
+                tracing::warn!(
+                    error = %e,
+                    session_id = %self.id,
+                    ordinal,
+                    "failed to persist message (non-fatal)"
+                );
+            }
+        }
+    }
+
+    /// Write the whole session to `path` as JSON, replacing it atomically.
+    pub async fn save(&self, path: &Path) -> Result<(), CherubError> {
+        let file = SessionFile {
+            version: SESSION_FILE_VERSION,
+            id: self.id,
+            user_id: self.user_id.clone(),
+            working_dir: std::env::current_dir().ok(),
+            compaction_count: self.compaction_count,
+            usage: self.usage,
+            cost_usd: self.cost_usd,
+            risk_score: self.risk_score,
+            tool_failures: self.tool_failures.to_wall_clock(),
+            invocations: self.invocations.clone(),
+            messages: self.messages.clone(),
+        };
+        let json = serde_json::to_vec_pretty(&file)
+            .map_err(|e| CherubError::Storage(format!("cannot encode session: {e}")))?;
+        let tmp = path.with_extension(format!("tmp-{}", Uuid::now_v7()));
+        let write = async {
+            tokio::fs::write(&tmp, json).await?;
+            tokio::fs::rename(&tmp, path).await
+        };
+        write.await.map_err(|e| {
+            CherubError::Storage(format!("cannot write session {}: {e}", path.display()))
+        })
+    }
+
+    /// Load a session written by [`save`](Self::save). Tool calls left
+    /// without a result (the process died mid-turn) get an error result
+    /// saying so, since they may or may not have run; the model sees that
+    /// instead of a broken history. The restored session has no store and
+    /// no autosave file.
+    pub async fn resume(path: &Path) -> Result<Self, CherubError> {
+        let bytes = tokio::fs::read(path).await.map_err(|e| {
+            CherubError::Storage(format!("cannot read session {}: {e}", path.display()))
+        })?;
+        let file: SessionFile = serde_json::from_slice(&bytes).map_err(|e| {
+            CherubError::Storage(format!("invalid session file {}: {e}", path.display()))
+        })?;
+        if file.version != SESSION_FILE_VERSION {
+            return Err(CherubError::Storage(format!(
+                "session file {} has version {}, expected {SESSION_FILE_VERSION}",
+                path.display(),
+                file.version
+            )));
+        }
+        let cwd = std::env::current_dir().ok();
+        if file.working_dir.is_some() && file.working_dir != cwd {
+            tracing::warn!(
+                session_id = %file.id,
+                saved_dir = ?file.working_dir,
+                current_dir = ?cwd,
+                "resuming session in a different directory"
+            );
+        }
+
+        let mut session = Self {
+            id: file.id,
+            user_id: file.user_id,
+            next_ordinal: file.messages.len() as i32,
+            messages: file.messages,
+            compaction_count: file.compaction_count,
+            invocations: file.invocations,
+            usage: file.usage,
+            cost_usd: file.cost_usd,
+            risk_score: file.risk_score,
+            tool_failures: ToolFailures::from_wall_clock(file.tool_failures),
+            file: None,
+            #[cfg(any(feature = "sessions", feature = "sqlite-store"))]
+            store: None,
+        };
+        let interrupted = session.close_interrupted_calls();
+        if interrupted > 0 {
+            tracing::warn!(
+                session_id = %session.id,
+                interrupted,
+                "session ended mid-turn; unfinished tool calls marked interrupted"
+            );
+        }
+        Ok(session)
+    }
+
+    /// Give every tool call in the last assistant message that has no result
+    /// an error result. Returns how many were added.
+    fn close_interrupted_calls(&mut self) -> usize {
+        let Some(last) = self
+            .messages
+            .iter()
+            .rposition(|m| matches!(m, Message::Assistant { .. }))
+        else {
+            return 0;
+        };
+        let answered: HashSet<&str> = self.messages[last + 1..]
+            .iter()
+            .filter_map(|m| match m {
+                Message::ToolResult { tool_use_id, .. } => Some(tool_use_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        let Message::Assistant { content, .. } = &self.messages[last] else {
+            return 0;
         };
-        let Some(msg) = self.messages.last() else {
+        let missing: Vec<String> = content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, .. } if !answered.contains(id.as_str()) => {
+                    Some(id.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        for tool_use_id in &missing {
+            self.push(Message::ToolResult {
+                tool_use_id: tool_use_id.clone(),
+                content: INTERRUPTED_CALL.to_owned(),
+                is_error: true,
+            });
+        }
+        missing.len()
+    }
+
+    /// Rewrite the session file, if one is set. Non-fatal. Called by
+    /// AgentLoop at turn boundaries rather than per message: each call
+    /// writes the whole history.
+    pub(crate) async fn save_file(&self) {
+        let Some(ref path) = self.file else {
             return;
         };
-        let ordinal = self.next_ordinal - 1;
-        if let Err(e) = store.push_message(self.id, ordinal, msg).await {
+        if let Err(e) = self.save(path).await {
             tracing::warn!(
                 error = %e,
                 session_id = %self.id,
-                ordinal,
-                "failed to persist message (non-fatal)"
+                "failed to save session file (non-fatal)"
             );
         }
     }
+
     /// Read-only view of the session messages.
     pub fn messages(&self) -> &[Message] {
         &self.messages
@@ -91,6 +332,46 @@ impl Session {
         self.compaction_count
     }
 
+    /// Every tool call proposed in this session, oldest first. Compaction
+    /// does not touch it.
+    pub fn invocations(&self) -> &[InvocationRecord] {
+        &self.invocations
+    }
+
+    /// Token usage summed over every provider call made for this session.
+    pub fn usage(&self) -> ApiUsage {
+        self.usage
+    }
+
+    /// Estimated USD cost of the calls counted in `usage`.
+    pub fn cost_usd(&self) -> f64 {
+        self.cost_usd
+    }
+
+    /// Record the outcome of a tool call proposed at `turn_number`.
+    pub fn record_invocation(
+        &mut self,
+        turn_number: i32,
+        tool_use_id: &str,
+        tool: &str,
+        action: &str,
+        outcome: InvocationOutcome,
+    ) {
+        self.invocations.push(InvocationRecord {
+            turn_number,
+            tool_use_id: tool_use_id.to_owned(),
+            tool: tool.to_owned(),
+            action: action.to_owned(),
+            outcome,
+        });
+    }
+
+    /// Add one provider call's usage and estimated cost to the totals.
+    pub fn add_usage(&mut self, usage: ApiUsage, cost_usd: f64) {
+        self.usage += usage;
+        self.cost_usd += cost_usd;
+    }
+
     /// Split messages for compaction, preserving the most recent `preserve_recent` messages.
     ///
     /// Finds a clean split boundary by walking backward from the split point to the
@@ -135,6 +416,28 @@ impl Session {
         Some((old, recent))
     }
 
+    /// Replace the bodies of tool results estimated above `min_tokens` with a
+    /// short placeholder, except in the most recent `preserve_recent` messages.
+    /// Cheaper than summarizing (no provider call) and keeps every
+    /// tool_use→tool_result pair intact, so it is safe mid-turn.
+    ///
+    /// Returns the estimated tokens freed.
+    pub fn elide_tool_outputs(&mut self, preserve_recent: usize, min_tokens: u32) -> u32 {
+        let end = self.messages.len().saturating_sub(preserve_recent);
+        let mut freed = 0u32;
+        for msg in &mut self.messages[..end] {
+            let before = tokens::estimate_message_tokens(msg);
+            if before < min_tokens {
+                continue;
+            }
+            if let Message::ToolResult { content, .. } = msg {
+                *content = format!("[tool output elided to save context: ~{before} tokens]");
+                freed += before.saturating_sub(tokens::estimate_message_tokens(msg));
+            }
+        }
+        freed
+    }
+
     /// Replace session messages after compaction.
     ///
     /// The new message list is: [summary_user, summary_ack, ...recent].
@@ -156,17 +459,19 @@ impl Session {
 
     /// Persist the full compacted message list via `replace_messages`.
     /// Non-fatal: logs warning on failure.
-    #[cfg(feature = "sessions")]
     pub async fn persist_compacted(&self) {
-        let Some(ref store) = self.store else {
-            return;
-        };
-        if let Err(e) = store.replace_messages(self.id, &self.messages).await {
-            tracing::warn!(
-                error = %e,
-                session_id = %self.id,
-                "failed to persist compacted session (non-fatal)"
-            );
+        #[cfg(any(feature = "sessions", feature = "sqlite-store"))]
+        {
+            let Some(ref store) = self.store else {
+                return;
+            };
+            if let Err(e) = store.replace_messages(self.id, &self.messages).await {
+                tracing::warn!(
+                    error = %e,
+                    session_id = %self.id,
+                    "failed to persist compacted session (non-fatal)"
+                );
+            }
         }
     }
 }
@@ -212,6 +517,136 @@ mod tests {
         assert_eq!(s.compaction_count, 0);
     }
 
+    #[test]
+    fn invocations_and_usage_survive_compaction() {
+        let mut session = Session::new("test");
+        session.push(Message::user_text("clean up"));
+        session.record_invocation(1, "t1", "bash", "ls", InvocationOutcome::Rejected);
+        session.record_invocation(
+            1,
+            "t2",
+            "bash",
+            "rm -rf build",
+            InvocationOutcome::Executed {
+                tier: Tier::Commit,
+                approved: true,
+                is_error: false,
+                duration: Duration::from_millis(5),
+            },
+        );
+        session.add_usage(ApiUsage::new(100, 10), 0.01);
+        session.add_usage(ApiUsage::new(50, 5), 0.02);
+
+        session.apply_compaction(
+            Message::user_text("[Context Summary]"),
+            Message::user_text("ack"),
+            Vec::new(),
+        );
+        let tools: Vec<&str> = session
+            .invocations()
+            .iter()
+            .map(|r| r.tool_use_id.as_str())
+            .collect();
+        assert_eq!(tools, ["t1", "t2"]);
+        assert_eq!(session.usage(), ApiUsage::new(150, 15));
+        assert!((session.cost_usd() - 0.03).abs() < 1e-10);
+    }
+
+    fn tool_use(ids: &[&str]) -> Message {
+        Message::Assistant {
+            content: ids
+                .iter()
+                .map(|id| ContentBlock::ToolUse {
+                    id: (*id).to_owned(),
+                    name: "bash".to_owned(),
+                    input: serde_json::json!({"command": "make"}),
+                })
+                .collect(),
+            stop_reason: StopReason::ToolUse,
+        }
+    }
+
+    #[tokio::test]
+    async fn save_and_resume_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let mut session = Session::new("alice");
+        session.push(Message::user_text("build it"));
+        session.push(tool_use(&["t1"]));
+        session.push(Message::ToolResult {
+            tool_use_id: "t1".to_owned(),
+            content: "ok".to_owned(),
+            is_error: false,
+        });
+        session.record_invocation(1, "t1", "bash", "make", InvocationOutcome::Rejected);
+        session.add_usage(ApiUsage::new(100, 10), 0.5);
+        session.risk_score = 7;
+        session.tool_failures = ToolFailures::from_wall_clock(HashMap::from([(
+            "bash".to_owned(),
+            vec![SystemTime::now()],
+        )]));
+        session.save(&path).await.unwrap();
+
+        let resumed = Session::resume(&path).await.unwrap();
+        assert_eq!(resumed.id, session.id);
+        assert_eq!(resumed.user_id, "alice");
+        assert_eq!(resumed.messages.len(), 3);
+        assert_eq!(resumed.next_ordinal, 3);
+        assert_eq!(resumed.invocations, session.invocations);
+        assert_eq!(resumed.usage(), ApiUsage::new(100, 10));
+        assert_eq!(resumed.risk_score, 7);
+        assert_eq!(resumed.tool_failures.to_wall_clock()["bash"].len(), 1);
+        assert!(resumed.file.is_none());
+    }
+
+    #[tokio::test]
+    async fn resume_closes_interrupted_tool_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let mut session = Session::new("test");
+        session.push(Message::user_text("deploy"));
+        session.push(tool_use(&["t1", "t2"]));
+        session.push(Message::ToolResult {
+            tool_use_id: "t1".to_owned(),
+            content: "built".to_owned(),
+            is_error: false,
+        });
+        session.save(&path).await.unwrap();
+
+        let resumed = Session::resume(&path).await.unwrap();
+        assert_eq!(resumed.messages.len(), 4);
+        match &resumed.messages[3] {
+            Message::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => {
+                assert_eq!(tool_use_id, "t2");
+                assert!(content.starts_with("interrupted"));
+                assert!(is_error);
+            }
+            other => panic!("expected a tool result: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn resume_rejects_bad_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = Session::resume(&dir.path().join("none.json")).await;
+        assert!(matches!(missing, Err(CherubError::Storage(_))));
+
+        let path = dir.path().join("session.json");
+        Session::new("test").save(&path).await.unwrap();
+        let json = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("\"version\": 1", "\"version\": 99");
+        std::fs::write(&path, json).unwrap();
+        let Err(err) = Session::resume(&path).await else {
+            panic!("resumed an unknown version");
+        };
+        assert!(err.to_string().contains("version 99"), "{err}");
+    }
+
     #[test]
     fn split_for_compaction_too_few_messages_returns_none() {
         let mut session = Session::new("test");
@@ -288,6 +723,44 @@ mod tests {
         assert_eq!(old.len() + recent.len(), 6);
     }
 
+    #[test]
+    fn elide_tool_outputs_spares_recent_and_small_results() {
+        let mut session = Session::new("test");
+        let big = "x".repeat(3_000);
+        for (id, content) in [("t1", big.as_str()), ("t2", "ok"), ("t3", big.as_str())] {
+            session.push(Message::Assistant {
+                content: vec![ContentBlock::ToolUse {
+                    id: id.to_owned(),
+                    name: "bash".to_owned(),
+                    input: serde_json::json!({"command": "cat log"}),
+                }],
+                stop_reason: StopReason::ToolUse,
+            });
+            session.push(Message::ToolResult {
+                tool_use_id: id.to_owned(),
+                content: content.to_owned(),
+                is_error: false,
+            });
+        }
+
+        let freed = session.elide_tool_outputs(2, 500);
+        assert!(freed > 900, "freed {freed}");
+        let results: Vec<&str> = session
+            .messages()
+            .iter()
+            .filter_map(|m| match m {
+                Message::ToolResult { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(results[0].starts_with("[tool output elided"));
+        assert_eq!(results[1], "ok");
+        assert_eq!(results[2], big, "recent results are kept");
+
+        // Already elided: nothing more to free.
+        assert_eq!(session.elide_tool_outputs(2, 500), 0);
+    }
+
     #[test]
     fn apply_compaction_replaces_messages() {
         let mut session = Session::new("test");
@@ -329,6 +802,11 @@ mod tests {
                         media_type: "image/png".to_owned(),
                         data: "base64data==".to_owned(),
                     },
+                    UserContent::Document {
+                        media_type: "application/pdf".to_owned(),
+                        data: "JVBERi0=".to_owned(),
+                        name: Some("spec.pdf".to_owned()),
+                    },
                 ],
             },
             Message::Assistant {
@@ -37,41 +37,52 @@ pub fn estimate_tokens(system: &str, messages: &[Message], tools: &[ToolDefiniti
 
     // Messages
     for msg in messages {
-        total += PER_MESSAGE_OVERHEAD;
-        match msg {
-            Message::User { content } => {
-                for c in content {
-                    match c {
-                        UserContent::Text(text) => {
-                            total += (text.len() as u32) / CHARS_PER_TOKEN_TEXT;
-                        }
-                        UserContent::Image { .. } => {
-                            // Images are billed separately by the API; estimate a fixed cost.
-                            total += 1000;
-                        }
+        total += estimate_message_tokens(msg);
+    }
+
+    total
+}
+
+/// Estimate one message's share of the input, including role framing.
+pub fn estimate_message_tokens(msg: &Message) -> u32 {
+    let mut total = PER_MESSAGE_OVERHEAD;
+    match msg {
+        Message::User { content } => {
+            for c in content {
+                match c {
+                    UserContent::Text(text) => {
+                        total += (text.len() as u32) / CHARS_PER_TOKEN_TEXT;
+                    }
+                    UserContent::Image { .. } => {
+                        // Images are billed separately by the API; estimate a fixed cost.
+                        total += 1000;
+                    }
+                    UserContent::Document { data, .. } => {
+                        // PDFs are billed per page as text plus a page image;
+                        // scale with size, at least one image's worth.
+                        total += ((data.len() as u32) / CHARS_PER_TOKEN_TEXT).max(1000);
                     }
                 }
             }
-            Message::Assistant { content, .. } => {
-                for block in content {
-                    match block {
-                        ContentBlock::Text { text } => {
-                            total += (text.len() as u32) / CHARS_PER_TOKEN_TEXT;
-                        }
-                        ContentBlock::ToolUse { name, input, .. } => {
-                            total += (name.len() as u32) / CHARS_PER_TOKEN_TEXT;
-                            let input_len = input.to_string().len() as u32;
-                            total += input_len / CHARS_PER_TOKEN_JSON;
-                        }
+        }
+        Message::Assistant { content, .. } => {
+            for block in content {
+                match block {
+                    ContentBlock::Text { text } => {
+                        total += (text.len() as u32) / CHARS_PER_TOKEN_TEXT;
+                    }
+                    ContentBlock::ToolUse { name, input, .. } => {
+                        total += (name.len() as u32) / CHARS_PER_TOKEN_TEXT;
+                        let input_len = input.to_string().len() as u32;
+                        total += input_len / CHARS_PER_TOKEN_JSON;
                     }
                 }
             }
-            Message::ToolResult { content, .. } => {
-                total += (content.len() as u32) / CHARS_PER_TOKEN_JSON;
-            }
+        }
+        Message::ToolResult { content, .. } => {
+            total += (content.len() as u32) / CHARS_PER_TOKEN_JSON;
         }
     }
-
     total
 }
 
@@ -165,6 +176,19 @@ mod tests {
         assert_eq!(tokens, 1004);
     }
 
+    #[test]
+    fn document_scales_with_size() {
+        let document = |len: usize| Message::User {
+            content: vec![UserContent::Document {
+                media_type: "application/pdf".to_owned(),
+                data: "A".repeat(len),
+                name: None,
+            }],
+        };
+        assert_eq!(estimate_tokens("", &[document(10)], &[]), 1004);
+        assert_eq!(estimate_tokens("", &[document(40_000)], &[]), 10_004);
+    }
+
     #[test]
     fn context_window_claude_models() {
         assert_eq!(context_window_size("claude-sonnet-4-20250514"), 200_000);
@@ -1,13 +1,17 @@
-//! Embedding provider abstraction and OpenAI implementation (M6c).
+//! Embedding provider abstraction and an OpenAI-compatible implementation (M6c).
 //!
 //! `EmbeddingProvider` is a true `dyn Trait` boundary — the backend is selected at
-//! runtime based on environment configuration (`OPENAI_API_KEY`).
+//! runtime based on environment configuration (`OPENAI_API_KEY`). The one
+//! implementation covers OpenAI, Voyage AI, and local Ollama/llama.cpp servers,
+//! which share the `/embeddings` wire format.
 
 use async_trait::async_trait;
 use secrecy::{ExposeSecret, SecretString};
 use tracing::Instrument;
 
 use crate::error::CherubError;
+use crate::providers::http::{self, HttpConfig};
+use crate::providers::ollama;
 
 /// Produces dense vector embeddings from text.
 ///
@@ -36,49 +40,101 @@ pub trait EmbeddingProvider: Send + Sync {
     }
 }
 
-// ─── OpenAI implementation ────────────────────────────────────────────────────
+// ─── OpenAI-compatible implementation ─────────────────────────────────────────
 
-const OPENAI_EMBED_URL: &str = "https://api.openai.com/v1/embeddings";
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
 const OPENAI_MODEL: &str = "text-embedding-3-small";
 const OPENAI_DIMENSION: usize = 1536;
 
-/// Calls the OpenAI Embeddings API (`text-embedding-3-small`, 1536 dimensions).
+/// Voyage AI, the embedding provider Anthropic recommends (Anthropic has no
+/// embeddings endpoint of its own). Speaks the OpenAI request/response shape.
+const VOYAGE_BASE_URL: &str = "https://api.voyageai.com/v1";
+
+/// Texts per request. Below every supported backend's limit (OpenAI 2048,
+/// Voyage 1000); larger batches are split.
+const MAX_BATCH: usize = 128;
+
+/// Calls an OpenAI-compatible `/embeddings` endpoint: OpenAI itself
+/// (`text-embedding-3-small`, 1536 dimensions, by default), Voyage AI, or a
+/// local Ollama/llama.cpp server.
 ///
-/// The API key is kept in a `SecretString`. `expose_secret()` is called only
-/// inside this module at the point where the HTTP request is built.
+/// The API key, if any, is kept in a `SecretString`. `expose_secret()` is
+/// called only inside this module at the point where the HTTP request is built.
 pub struct OpenAiEmbeddingProvider {
     client: reqwest::Client,
     // CREDENTIAL: SecretString wraps the API key — Debug impl auto-redacts.
-    api_key: SecretString,
+    api_key: Option<SecretString>,
+    url: String,
+    model: String,
+    dimension: usize,
 }
 
 impl OpenAiEmbeddingProvider {
-    /// Create a new provider. Builds a `reqwest::Client` with standard timeouts.
+    /// OpenAI's `text-embedding-3-small`. Builds a `reqwest::Client` with
+    /// standard timeouts.
     pub fn new(api_key: SecretString) -> Result<Self, CherubError> {
-        let client = reqwest::Client::builder()
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .read_timeout(std::time::Duration::from_secs(30))
-            .timeout(std::time::Duration::from_secs(120))
-            .build()
+        Self::build(
+            Some(api_key),
+            OPENAI_BASE_URL,
+            OPENAI_MODEL,
+            OPENAI_DIMENSION,
+        )
+    }
+
+    /// A Voyage AI model (e.g. `voyage-3`, 1024 dimensions).
+    pub fn voyage(
+        api_key: SecretString,
+        model: &str,
+        dimension: usize,
+    ) -> Result<Self, CherubError> {
+        Self::build(Some(api_key), VOYAGE_BASE_URL, model, dimension)
+    }
+
+    /// A model on a local Ollama server (e.g. `nomic-embed-text`, 768
+    /// dimensions). No API key; `with_base_url` points at llama.cpp instead.
+    pub fn local(model: &str, dimension: usize) -> Result<Self, CherubError> {
+        Self::build(None, ollama::DEFAULT_BASE_URL, model, dimension)
+    }
+
+    fn build(
+        api_key: Option<SecretString>,
+        base_url: &str,
+        model: &str,
+        dimension: usize,
+    ) -> Result<Self, CherubError> {
+        let client = http::api_client(&HttpConfig::default())
             .map_err(|e| CherubError::Config(format!("failed to build HTTP client: {e}")))?;
 
-        Ok(Self { client, api_key })
+        Ok(Self {
+            client,
+            api_key,
+            url: format!("{base_url}/embeddings"),
+            model: model.to_owned(),
+            dimension,
+        })
+    }
+
+    /// Override the base URL, including any `/v1` suffix (a gateway, or
+    /// wiremock in tests). Requests go to `{url}/embeddings`.
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.url = format!("{}/embeddings", url.trim_end_matches('/'));
+        self
     }
 
     /// POST a batch of texts to the Embeddings API and return all vectors.
-    async fn call_api(&self, input: Vec<String>) -> Result<Vec<Vec<f32>>, CherubError> {
-        let span = tracing::info_span!("openai_embed", count = input.len(), model = OPENAI_MODEL);
+    async fn call_api(&self, input: &[String]) -> Result<Vec<Vec<f32>>, CherubError> {
+        let span = tracing::info_span!("openai_embed", count = input.len(), model = %self.model);
 
         async {
-            // CREDENTIAL: expose_secret() is the only access point — used only here.
-            let resp = self
-                .client
-                .post(OPENAI_EMBED_URL)
-                .bearer_auth(self.api_key.expose_secret())
-                .json(&serde_json::json!({
-                    "input": input,
-                    "model": OPENAI_MODEL,
-                }))
+            let mut request = self.client.post(&self.url).json(&serde_json::json!({
+                "input": input,
+                "model": self.model,
+            }));
+            if let Some(ref api_key) = self.api_key {
+                // CREDENTIAL: expose_secret() is the only access point — used only here.
+                request = request.bearer_auth(api_key.expose_secret());
+            }
+            let resp = request
                 .send()
                 .await
                 .map_err(|e| CherubError::Provider(format!("embedding request failed: {e}")))?;
@@ -98,10 +154,21 @@ impl OpenAiEmbeddingProvider {
             let data = body["data"].as_array().ok_or_else(|| {
                 CherubError::Provider("embedding response missing 'data' array".into())
             })?;
+            if data.len() != input.len() {
+                return Err(CherubError::Provider(format!(
+                    "embedding API returned {} vectors for {} inputs",
+                    data.len(),
+                    input.len()
+                )));
+            }
 
-            // The API returns objects in the same order as the input.
-            data.iter()
-                .map(|item| {
+            // Items carry their input's `index`; order by it rather than
+            // trusting the response order.
+            let mut vectors: Vec<(u64, Vec<f32>)> = data
+                .iter()
+                .enumerate()
+                .map(|(position, item)| {
+                    let index = item["index"].as_u64().unwrap_or(position as u64);
                     item["embedding"]
                         .as_array()
                         .ok_or_else(|| {
@@ -118,6 +185,24 @@ impl OpenAiEmbeddingProvider {
                                 })
                                 .collect::<Result<Vec<f32>, _>>()
                         })
+                        .map(|vector| (index, vector))
+                })
+                .collect::<Result<_, _>>()?;
+            vectors.sort_by_key(|(index, _)| *index);
+
+            vectors
+                .into_iter()
+                .map(|(_, vector)| {
+                    if vector.len() == self.dimension {
+                        Ok(vector)
+                    } else {
+                        Err(CherubError::Provider(format!(
+                            "embedding model '{}' returned {} dimensions, expected {}",
+                            self.model,
+                            vector.len(),
+                            self.dimension
+                        )))
+                    }
                 })
                 .collect()
         }
@@ -129,25 +214,26 @@ impl OpenAiEmbeddingProvider {
 #[async_trait]
 impl EmbeddingProvider for OpenAiEmbeddingProvider {
     fn dimension(&self) -> usize {
-        OPENAI_DIMENSION
+        self.dimension
     }
 
     fn model_name(&self) -> &str {
-        OPENAI_MODEL
+        &self.model
     }
 
     async fn embed(&self, text: &str) -> Result<Vec<f32>, CherubError> {
-        let mut results = self.call_api(vec![text.to_owned()]).await?;
+        let mut results = self.call_api(&[text.to_owned()]).await?;
         results
             .pop()
             .ok_or_else(|| CherubError::Provider("embedding API returned empty data".into()))
     }
 
-    /// Batched: single HTTP call for all texts.
+    /// Batched: one HTTP call per `MAX_BATCH` texts.
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, CherubError> {
-        if texts.is_empty() {
-            return Ok(Vec::new());
+        let mut out = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(MAX_BATCH) {
+            out.extend(self.call_api(chunk).await?);
         }
-        self.call_api(texts.to_vec()).await
+        Ok(out)
     }
 }
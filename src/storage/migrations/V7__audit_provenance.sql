@@ -0,0 +1,13 @@
+-- Invocation provenance on audit events.
+--
+-- Every ToolInvocation carries a UUIDv7 correlation ID from the moment it is
+-- proposed. Recording it (plus the proposing model) lets a rejected or executed
+-- action be traced back to the exact model turn that produced it.
+--
+-- Both columns are nullable: rows written before this migration, and
+-- out-of-session events, have neither.
+
+ALTER TABLE audit_events ADD COLUMN IF NOT EXISTS invocation_id UUID;
+ALTER TABLE audit_events ADD COLUMN IF NOT EXISTS model TEXT;
+
+CREATE INDEX IF NOT EXISTS audit_invocation_idx ON audit_events (invocation_id) WHERE invocation_id IS NOT NULL;
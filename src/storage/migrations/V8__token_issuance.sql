@@ -0,0 +1,12 @@
+-- Capability token issuance on audit events.
+--
+-- Every token minted by the enforcement layer records a decision ID, the hash
+-- of the policy that allowed it, and the rule that matched. Storing them with
+-- the allow/approve rows answers "which rule authorized this?" after the fact.
+--
+-- All columns are nullable: rejects and denials mint no token, human approvals
+-- have no policy hash, and rows written before this migration have none.
+
+ALTER TABLE audit_events ADD COLUMN IF NOT EXISTS decision_id UUID;
+ALTER TABLE audit_events ADD COLUMN IF NOT EXISTS policy_hash TEXT;
+ALTER TABLE audit_events ADD COLUMN IF NOT EXISTS rule TEXT;
@@ -1,10 +1,17 @@
 pub mod embedding;
+#[cfg(feature = "postgres")]
 pub mod pg_audit_store;
+#[cfg(feature = "postgres")]
 pub mod pg_cost_store;
+#[cfg(feature = "postgres")]
 pub mod pg_memory_store;
+#[cfg(feature = "postgres")]
 pub mod pg_pricing_store;
+#[cfg(feature = "postgres")]
 pub mod pg_session_store;
 pub mod search;
+#[cfg(feature = "sqlite-store")]
+pub mod sqlite_store;
 
 #[cfg(feature = "credentials")]
 pub mod credential_types;
@@ -15,15 +22,20 @@ pub mod pg_credential_store;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+#[cfg(feature = "postgres")]
 use deadpool_postgres::{Config, Pool, Runtime};
+#[cfg(feature = "postgres")]
 use secrecy::{ExposeSecret, SecretString};
+#[cfg(feature = "postgres")]
 use tokio_postgres::NoTls;
 use uuid::Uuid;
 
+use crate::enforcement::capability::Issuance;
 use crate::error::CherubError;
 use crate::providers::Message;
 
 // Embed migrations at compile time so the binary is self-contained.
+#[cfg(feature = "postgres")]
 mod embedded {
     refinery::embed_migrations!("src/storage/migrations");
 }
@@ -403,6 +415,12 @@ pub struct NewAuditEvent {
     pub duration_ms: Option<i64>,
     /// Whether the executed tool returned an error. None if not executed.
     pub is_error: Option<bool>,
+    /// Correlation ID of the `ToolInvocation` this event describes.
+    pub invocation_id: Option<Uuid>,
+    /// Model that proposed the invocation (from `Provenance::model`).
+    pub model: Option<String>,
+    /// Issuance record of the capability token, for allow/approve events.
+    pub issuance: Option<Issuance>,
 }
 
 /// A fully-loaded audit event row.
@@ -418,6 +436,11 @@ pub struct AuditEvent {
     pub tier: Option<String>,
     pub duration_ms: Option<i64>,
     pub is_error: Option<bool>,
+    pub invocation_id: Option<Uuid>,
+    pub model: Option<String>,
+    pub decision_id: Option<Uuid>,
+    pub policy_hash: Option<String>,
+    pub rule: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -428,13 +451,14 @@ pub struct AuditFilter {
     pub decision: Option<AuditDecision>,
     pub user_id: Option<String>,
     pub session_id: Option<Uuid>,
+    pub invocation_id: Option<Uuid>,
     pub since: Option<DateTime<Utc>>,
     pub limit: Option<i64>,
 }
 
 /// Append-only event log for enforcement decisions and tool executions.
 ///
-/// Implementations: `PgAuditStore` (PostgreSQL, M10).
+/// Implementations: `PgAuditStore` (PostgreSQL, M10), `SqliteStore` (SQLite).
 /// This is a true `dyn Trait` boundary — backend selected at runtime.
 #[async_trait]
 pub trait AuditStore: Send + Sync {
@@ -575,6 +599,7 @@ pub trait PricingStore: Send + Sync {
 /// exposed at the single point where deadpool-postgres requires a plain `String`.
 /// The binary is self-contained: migrations are embedded at compile time via
 /// `refinery::embed_migrations!`. No migration files need to be deployed.
+#[cfg(feature = "postgres")]
 pub async fn connect(database_url: SecretString) -> Result<Pool, CherubError> {
     let mut cfg = Config::new();
     // CREDENTIAL: expose_secret() is the only access point for the DB password.
@@ -30,11 +30,13 @@ impl AuditStore for PgAuditStore {
                 CherubError::Storage(format!("audit: failed to get connection: {e}"))
             })?;
 
+        let issuance = event.issuance.as_ref();
         let row = conn
             .query_one(
                 "INSERT INTO audit_events \
-                 (session_id, user_id, turn_number, tool, action, decision, tier, duration_ms, is_error) \
-                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+                 (session_id, user_id, turn_number, tool, action, decision, tier, duration_ms, is_error, \
+                  invocation_id, model, decision_id, policy_hash, rule) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) \
                  RETURNING id",
                 &[
                     &event.session_id,
@@ -46,6 +48,11 @@ impl AuditStore for PgAuditStore {
                     &event.tier,
                     &event.duration_ms,
                     &event.is_error,
+                    &event.invocation_id,
+                    &event.model,
+                    &issuance.map(|i| i.decision_id),
+                    &issuance.and_then(|i| i.policy_hash.as_deref()),
+                    &issuance.map(|i| i.rule.as_str()),
                 ],
             )
             .await
@@ -89,6 +96,11 @@ impl AuditStore for PgAuditStore {
             binds.push(Box::new(session_id));
             idx += 1;
         }
+        if let Some(invocation_id) = filter.invocation_id {
+            clauses.push(format!("invocation_id = ${idx}"));
+            binds.push(Box::new(invocation_id));
+            idx += 1;
+        }
         if let Some(since) = filter.since {
             clauses.push(format!("created_at >= ${idx}"));
             binds.push(Box::new(since));
@@ -106,7 +118,8 @@ impl AuditStore for PgAuditStore {
 
         let sql = format!(
             "SELECT id, session_id, user_id, turn_number, tool, action, decision, tier, \
-             duration_ms, is_error, created_at \
+             duration_ms, is_error, invocation_id, model, decision_id, policy_hash, rule, \
+             created_at \
              FROM audit_events \
              {where_clause} \
              ORDER BY created_at DESC \
@@ -139,7 +152,12 @@ impl AuditStore for PgAuditStore {
                     tier: row.get(7),
                     duration_ms: row.get(8),
                     is_error: row.get(9),
-                    created_at: row.get(10),
+                    invocation_id: row.get(10),
+                    model: row.get(11),
+                    decision_id: row.get(12),
+                    policy_hash: row.get(13),
+                    rule: row.get(14),
+                    created_at: row.get(15),
                 })
             })
             .collect()
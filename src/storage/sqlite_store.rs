@@ -0,0 +1,569 @@
+//! SQLite implementation of `SessionStore` and `AuditStore`.
+//!
+//! One database file holds session history and the audit log, for a single
+//! machine without PostgreSQL. The schema mirrors the PostgreSQL one (UUIDs
+//! and timestamps as text) and is created on open. rusqlite is synchronous,
+//! so every call runs on the blocking pool against one shared connection.
+//!
+//! Like `audit_events` in PostgreSQL, the audit table is append-only: rows
+//! are never updated or deleted.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, Row, params, params_from_iter};
+use uuid::Uuid;
+
+use crate::error::CherubError;
+use crate::providers::Message;
+
+use super::{AuditDecision, AuditEvent, AuditFilter, AuditStore, NewAuditEvent, SessionStore};
+
+/// Schema version stored in `PRAGMA user_version`.
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id           TEXT PRIMARY KEY,
+    connector    TEXT NOT NULL,
+    connector_id TEXT NOT NULL,
+    created_at   TEXT NOT NULL,
+    updated_at   TEXT NOT NULL,
+    UNIQUE (connector, connector_id)
+);
+
+CREATE TABLE IF NOT EXISTS session_messages (
+    session_id   TEXT    NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    ordinal      INTEGER NOT NULL,
+    role         TEXT    NOT NULL,
+    message_json TEXT    NOT NULL,
+    PRIMARY KEY (session_id, ordinal)
+);
+
+CREATE TABLE IF NOT EXISTS audit_events (
+    id            TEXT PRIMARY KEY,
+    session_id    TEXT,
+    user_id       TEXT NOT NULL,
+    turn_number   INTEGER,
+    tool          TEXT NOT NULL,
+    action        TEXT,
+    decision      TEXT NOT NULL,
+    tier          TEXT,
+    duration_ms   INTEGER,
+    is_error      INTEGER,
+    invocation_id TEXT,
+    model         TEXT,
+    decision_id   TEXT,
+    policy_hash   TEXT,
+    rule          TEXT,
+    -- RFC 3339, UTC, fixed precision: sorts lexically in time order.
+    created_at    TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS audit_session_idx    ON audit_events (session_id) WHERE session_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS audit_user_idx       ON audit_events (user_id);
+CREATE INDEX IF NOT EXISTS audit_tool_idx       ON audit_events (tool);
+CREATE INDEX IF NOT EXISTS audit_decision_idx   ON audit_events (decision);
+CREATE INDEX IF NOT EXISTS audit_time_idx       ON audit_events (created_at DESC);
+CREATE INDEX IF NOT EXISTS audit_invocation_idx ON audit_events (invocation_id) WHERE invocation_id IS NOT NULL;
+";
+
+/// SQLite-backed session and audit store. Clone-cheap: clones share the
+/// connection, so one open file can back both `with_persistence` and
+/// `with_audit_log`.
+#[derive(Clone)]
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Open (or create) the database at `path` and bring its schema up to date.
+    pub async fn open(path: &Path) -> Result<Self, CherubError> {
+        let path = path.to_owned();
+        let conn = tokio::task::spawn_blocking(move || -> Result<Connection, CherubError> {
+            let conn = Connection::open(&path).map_err(|e| {
+                CherubError::Storage(format!("cannot open {}: {e}", path.display()))
+            })?;
+            init(&conn)?;
+            Ok(conn)
+        })
+        .await
+        .map_err(|e| CherubError::Storage(format!("sqlite: open task failed: {e}")))??;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Run `f` against the connection on the blocking pool.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, CherubError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, CherubError> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            // A panic mid-call rolls back its transaction; the connection is still usable.
+            let mut conn = conn.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| CherubError::Storage(format!("sqlite: task failed: {e}")))?
+    }
+}
+
+/// Apply pragmas and create the schema if this is a new file.
+fn init(conn: &Connection) -> Result<(), CherubError> {
+    conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+        .map_err(query_err)?;
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(query_err)?;
+    if version > SCHEMA_VERSION {
+        return Err(CherubError::Storage(format!(
+            "database schema version {version} is newer than this build supports ({SCHEMA_VERSION})"
+        )));
+    }
+    conn.execute_batch(SCHEMA).map_err(query_err)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .map_err(query_err)?;
+    Ok(())
+}
+
+fn query_err(e: rusqlite::Error) -> CherubError {
+    CherubError::Storage(format!("sqlite: {e}"))
+}
+
+fn serde_err(e: impl std::fmt::Display) -> CherubError {
+    CherubError::Storage(format!("serde error: {e}"))
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Extract the role string from a message for the denormalized `role` column.
+fn message_role_str(msg: &Message) -> &'static str {
+    match msg {
+        Message::User { .. } => "user",
+        Message::Assistant { .. } => "assistant",
+        Message::ToolResult { .. } => "tool_result",
+    }
+}
+
+fn load_messages(conn: &Connection, session_id: Uuid) -> Result<Vec<Message>, CherubError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT message_json FROM session_messages \
+             WHERE session_id = ?1 ORDER BY ordinal ASC",
+        )
+        .map_err(query_err)?;
+    let rows = stmt
+        .query_map([session_id.to_string()], |row| row.get::<_, String>(0))
+        .map_err(query_err)?;
+    rows.map(|json| serde_json::from_str(&json.map_err(query_err)?).map_err(serde_err))
+        .collect()
+}
+
+#[async_trait]
+impl SessionStore for SqliteStore {
+    async fn get_or_create_session(
+        &self,
+        connector: &str,
+        connector_id: &str,
+    ) -> Result<(Uuid, Vec<Message>), CherubError> {
+        let connector = connector.to_owned();
+        let connector_id = connector_id.to_owned();
+        self.with_conn(move |conn| {
+            let existing: Option<String> = conn
+                .query_row(
+                    "SELECT id FROM sessions WHERE connector = ?1 AND connector_id = ?2",
+                    params![connector, connector_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(query_err)?;
+
+            let session_id = match existing {
+                Some(id) => parse_uuid(&id)?,
+                None => {
+                    let new_id = Uuid::now_v7();
+                    let ts = now();
+                    conn.execute(
+                        "INSERT INTO sessions (id, connector, connector_id, created_at, updated_at) \
+                         VALUES (?1, ?2, ?3, ?4, ?4)",
+                        params![new_id.to_string(), connector, connector_id, ts],
+                    )
+                    .map_err(query_err)?;
+                    tracing::info!(
+                        session_id = %new_id,
+                        connector,
+                        connector_id,
+                        "created new session"
+                    );
+                    new_id
+                }
+            };
+            Ok((session_id, load_messages(conn, session_id)?))
+        })
+        .await
+    }
+
+    async fn push_message(
+        &self,
+        session_id: Uuid,
+        ordinal: i32,
+        message: &Message,
+    ) -> Result<(), CherubError> {
+        let message_json = serde_json::to_string(message).map_err(serde_err)?;
+        let role = message_role_str(message);
+        self.with_conn(move |conn| {
+            let tx = conn.transaction().map_err(query_err)?;
+            tx.execute(
+                "INSERT INTO session_messages (session_id, ordinal, role, message_json) \
+                 VALUES (?1, ?2, ?3, ?4) \
+                 ON CONFLICT (session_id, ordinal) DO UPDATE \
+                   SET message_json = excluded.message_json, role = excluded.role",
+                params![session_id.to_string(), ordinal, role, message_json],
+            )
+            .map_err(query_err)?;
+            tx.execute(
+                "UPDATE sessions SET updated_at = ?1 WHERE id = ?2",
+                params![now(), session_id.to_string()],
+            )
+            .map_err(query_err)?;
+            tx.commit().map_err(query_err)
+        })
+        .await
+    }
+
+    async fn load_messages(&self, session_id: Uuid) -> Result<Vec<Message>, CherubError> {
+        self.with_conn(move |conn| load_messages(conn, session_id))
+            .await
+    }
+
+    async fn replace_messages(
+        &self,
+        session_id: Uuid,
+        messages: &[Message],
+    ) -> Result<(), CherubError> {
+        let rows = messages
+            .iter()
+            .map(|m| {
+                Ok((
+                    message_role_str(m),
+                    serde_json::to_string(m).map_err(serde_err)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, CherubError>>()?;
+        let count = rows.len();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction().map_err(query_err)?;
+            let id = session_id.to_string();
+            tx.execute("DELETE FROM session_messages WHERE session_id = ?1", [&id])
+                .map_err(query_err)?;
+            {
+                let mut insert = tx
+                    .prepare(
+                        "INSERT INTO session_messages (session_id, ordinal, role, message_json) \
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )
+                    .map_err(query_err)?;
+                for (ordinal, (role, json)) in rows.iter().enumerate() {
+                    insert
+                        .execute(params![id, ordinal as i64, role, json])
+                        .map_err(query_err)?;
+                }
+            }
+            tx.execute(
+                "UPDATE sessions SET updated_at = ?1 WHERE id = ?2",
+                params![now(), id],
+            )
+            .map_err(query_err)?;
+            tx.commit().map_err(query_err)
+        })
+        .await?;
+
+        tracing::info!(
+            session_id = %session_id,
+            message_count = count,
+            "replaced session messages after compaction"
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditStore for SqliteStore {
+    async fn append(&self, event: NewAuditEvent) -> Result<Uuid, CherubError> {
+        let id = Uuid::now_v7();
+        self.with_conn(move |conn| {
+            let issuance = event.issuance.as_ref();
+            conn.execute(
+                "INSERT INTO audit_events \
+                 (id, session_id, user_id, turn_number, tool, action, decision, tier, duration_ms, \
+                  is_error, invocation_id, model, decision_id, policy_hash, rule, created_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+                params![
+                    id.to_string(),
+                    event.session_id.map(|u| u.to_string()),
+                    event.user_id,
+                    event.turn_number,
+                    event.tool,
+                    event.action,
+                    event.decision.as_str(),
+                    event.tier,
+                    event.duration_ms,
+                    event.is_error,
+                    event.invocation_id.map(|u| u.to_string()),
+                    event.model,
+                    issuance.map(|i| i.decision_id.to_string()),
+                    issuance.and_then(|i| i.policy_hash.as_deref()),
+                    issuance.map(|i| i.rule.as_str()),
+                    now(),
+                ],
+            )
+            .map_err(|e| CherubError::Storage(format!("audit: insert failed: {e}")))?;
+            Ok(id)
+        })
+        .await
+    }
+
+    async fn list(&self, filter: AuditFilter) -> Result<Vec<AuditEvent>, CherubError> {
+        self.with_conn(move |conn| {
+            let mut clauses: Vec<&str> = Vec::new();
+            let mut binds: Vec<Value> = Vec::new();
+
+            if let Some(tool) = filter.tool {
+                clauses.push("tool = ?");
+                binds.push(Value::Text(tool));
+            }
+            if let Some(decision) = filter.decision {
+                clauses.push("decision = ?");
+                binds.push(Value::Text(decision.as_str().to_owned()));
+            }
+            if let Some(user_id) = filter.user_id {
+                clauses.push("user_id = ?");
+                binds.push(Value::Text(user_id));
+            }
+            if let Some(session_id) = filter.session_id {
+                clauses.push("session_id = ?");
+                binds.push(Value::Text(session_id.to_string()));
+            }
+            if let Some(invocation_id) = filter.invocation_id {
+                clauses.push("invocation_id = ?");
+                binds.push(Value::Text(invocation_id.to_string()));
+            }
+            if let Some(since) = filter.since {
+                clauses.push("created_at >= ?");
+                binds.push(Value::Text(
+                    since.to_rfc3339_opts(SecondsFormat::Micros, true),
+                ));
+            }
+            binds.push(Value::Integer(filter.limit.unwrap_or(100)));
+
+            let where_clause = if clauses.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", clauses.join(" AND "))
+            };
+            let sql = format!(
+                "SELECT id, session_id, user_id, turn_number, tool, action, decision, tier, \
+                 duration_ms, is_error, invocation_id, model, decision_id, policy_hash, rule, \
+                 created_at \
+                 FROM audit_events \
+                 {where_clause} \
+                 ORDER BY created_at DESC, rowid DESC \
+                 LIMIT ?"
+            );
+
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|e| CherubError::Storage(format!("audit: list query failed: {e}")))?;
+            let mut rows = stmt
+                .query(params_from_iter(binds))
+                .map_err(|e| CherubError::Storage(format!("audit: list query failed: {e}")))?;
+            let mut events = Vec::new();
+            while let Some(row) = rows.next().map_err(query_err)? {
+                events.push(audit_event(row)?);
+            }
+            Ok(events)
+        })
+        .await
+    }
+}
+
+fn audit_event(row: &Row<'_>) -> Result<AuditEvent, CherubError> {
+    let text = |idx: usize| row.get::<_, Option<String>>(idx).map_err(query_err);
+    let uuid = |idx: usize| text(idx)?.as_deref().map(parse_uuid).transpose();
+    let id = text(0)?.unwrap_or_default();
+    let created_at = text(15)?.unwrap_or_default();
+    Ok(AuditEvent {
+        id: parse_uuid(&id)?,
+        session_id: uuid(1)?,
+        user_id: text(2)?.unwrap_or_default(),
+        turn_number: row.get(3).map_err(query_err)?,
+        tool: text(4)?.unwrap_or_default(),
+        action: text(5)?,
+        decision: text(6)?.unwrap_or_default().parse::<AuditDecision>()?,
+        tier: text(7)?,
+        duration_ms: row.get(8).map_err(query_err)?,
+        is_error: row.get(9).map_err(query_err)?,
+        invocation_id: uuid(10)?,
+        model: text(11)?,
+        decision_id: uuid(12)?,
+        policy_hash: text(13)?,
+        rule: text(14)?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)
+            .map_err(|e| CherubError::Storage(format!("invalid timestamp '{created_at}': {e}")))?
+            .with_timezone(&Utc),
+    })
+}
+
+fn parse_uuid(s: &str) -> Result<Uuid, CherubError> {
+    Uuid::parse_str(s).map_err(|e| CherubError::Storage(format!("invalid uuid '{s}': {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ContentBlock, StopReason};
+
+    async fn store() -> (tempfile::TempDir, SqliteStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteStore::open(&dir.path().join("cherub.db"))
+            .await
+            .unwrap();
+        (dir, store)
+    }
+
+    fn event(session_id: Uuid, tool: &str, decision: AuditDecision) -> NewAuditEvent {
+        NewAuditEvent {
+            session_id: Some(session_id),
+            user_id: "alice".to_owned(),
+            turn_number: Some(1),
+            tool: tool.to_owned(),
+            action: Some("ls".to_owned()),
+            decision,
+            tier: None,
+            duration_ms: None,
+            is_error: None,
+            invocation_id: Some(Uuid::now_v7()),
+            model: Some("claude-test".to_owned()),
+            issuance: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn sessions_persist_across_reopen() {
+        let (dir, store) = store().await;
+        let (id, messages) = store.get_or_create_session("cli", "default").await.unwrap();
+        assert!(messages.is_empty());
+        store
+            .push_message(id, 0, &Message::user_text("hello"))
+            .await
+            .unwrap();
+        store
+            .push_message(
+                id,
+                1,
+                &Message::Assistant {
+                    content: vec![ContentBlock::Text {
+                        text: "hi".to_owned(),
+                    }],
+                    stop_reason: StopReason::EndTurn,
+                },
+            )
+            .await
+            .unwrap();
+        drop(store);
+
+        let store = SqliteStore::open(&dir.path().join("cherub.db"))
+            .await
+            .unwrap();
+        let (again, messages) = store.get_or_create_session("cli", "default").await.unwrap();
+        assert_eq!(again, id);
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(messages[1], Message::Assistant { .. }));
+
+        let (other, _) = store.get_or_create_session("telegram", "42").await.unwrap();
+        assert_ne!(other, id);
+    }
+
+    #[tokio::test]
+    async fn replace_messages_rewrites_history() {
+        let (_dir, store) = store().await;
+        let (id, _) = store.get_or_create_session("cli", "default").await.unwrap();
+        for i in 0..5 {
+            store
+                .push_message(id, i, &Message::user_text(&format!("msg {i}")))
+                .await
+                .unwrap();
+        }
+        store
+            .replace_messages(id, &[Message::user_text("[Context Summary]")])
+            .await
+            .unwrap();
+        assert_eq!(store.load_messages(id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn audit_events_filter_and_order() {
+        let (_dir, store) = store().await;
+        let session = Uuid::now_v7();
+        let first = store
+            .append(event(session, "bash", AuditDecision::Allow))
+            .await
+            .unwrap();
+        store
+            .append(event(session, "bash", AuditDecision::Reject))
+            .await
+            .unwrap();
+        store
+            .append(event(Uuid::now_v7(), "http", AuditDecision::Allow))
+            .await
+            .unwrap();
+
+        let all = store.list(AuditFilter::default()).await.unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].tool, "http", "most recent first");
+        assert_eq!(all[2].id, first);
+
+        let rejects = store
+            .list(AuditFilter {
+                session_id: Some(session),
+                decision: Some(AuditDecision::Reject),
+                ..AuditFilter::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(rejects.len(), 1);
+        assert_eq!(rejects[0].session_id, Some(session));
+        assert_eq!(rejects[0].model.as_deref(), Some("claude-test"));
+
+        let limited = store
+            .list(AuditFilter {
+                limit: Some(1),
+                ..AuditFilter::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn newer_schema_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cherub.db");
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        let Err(err) = SqliteStore::open(&path).await else {
+            panic!("opened a newer schema");
+        };
+        assert!(err.to_string().contains("newer"), "{err}");
+    }
+}
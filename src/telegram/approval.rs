@@ -110,7 +110,8 @@ impl ApprovalGate for TelegramApprovalGate {
         // Wait for approval response with timeout.
         match tokio::time::timeout(self.timeout, rx).await {
             Ok(Ok(true)) => ApprovalResult::Approved,
-            _ => ApprovalResult::Denied,
+            Ok(_) => ApprovalResult::Denied,
+            Err(_) => ApprovalResult::TimedOut,
         }
     }
 }
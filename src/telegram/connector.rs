@@ -11,7 +11,7 @@ use crate::providers::UserContent;
 use super::approval::parse_callback_data;
 use super::session::{InboundMessage, SessionCommand};
 
-/// Handle an incoming Telegram message. Extracts text and photos,
+/// Handle an incoming Telegram message. Extracts text, photos, and PDFs,
 /// routes to the session manager.
 pub async fn handle_message(
     bot: Bot,
@@ -35,7 +35,7 @@ pub async fn handle_message(
     if let Some(photos) = msg.photo()
         && let Some(largest) = photos.last()
     {
-        match download_photo(&bot, largest.file.id.clone()).await {
+        match download_file(&bot, largest.file.id.clone()).await {
             Ok(data) => {
                 content.push(UserContent::Image {
                     media_type: "image/jpeg".to_owned(),
@@ -55,13 +55,38 @@ pub async fn handle_message(
         content.push(UserContent::Text(text.to_owned()));
     }
 
-    // Handle documents: forward as text description (full processing deferred).
+    // Handle documents: PDFs are downloaded for the model to read; anything
+    // else (or a failed download) is forwarded as a text description.
     if let Some(doc) = msg.document() {
-        let desc = match &doc.file_name {
-            Some(name) => format!("[Document: {name}]"),
-            None => "[Document attached]".to_owned(),
+        let is_pdf = doc
+            .mime_type
+            .as_ref()
+            .is_some_and(|m| m.essence_str() == "application/pdf");
+        let downloaded = if is_pdf {
+            match download_file(&bot, doc.file.id.clone()).await {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    warn!(chat_id = %chat_id, error = %e, "failed to download document");
+                    None
+                }
+            }
+        } else {
+            None
         };
-        content.push(UserContent::Text(desc));
+        match downloaded {
+            Some(data) => content.push(UserContent::Document {
+                media_type: "application/pdf".to_owned(),
+                data,
+                name: doc.file_name.clone(),
+            }),
+            None => {
+                let desc = match &doc.file_name {
+                    Some(name) => format!("[Document: {name}]"),
+                    None => "[Document attached]".to_owned(),
+                };
+                content.push(UserContent::Text(desc));
+            }
+        }
     }
 
     if content.is_empty() {
@@ -102,8 +127,8 @@ pub async fn handle_callback(
     Ok(())
 }
 
-/// Download a photo from Telegram and return it as base64-encoded data.
-async fn download_photo(
+/// Download a photo or document from Telegram and return it as base64-encoded data.
+async fn download_file(
     bot: &Bot,
     file_id: FileId,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
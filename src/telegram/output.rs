@@ -1,7 +1,9 @@
 use teloxide::prelude::*;
-use teloxide::types::ParseMode;
+use teloxide::types::{InputFile, ParseMode};
 
 use crate::runtime::output::{OutputEvent, OutputSink};
+use crate::tools::artifacts::Artifact;
+use crate::tools::attachment::{Attachment, Payload};
 
 /// Maximum Telegram message length (API limit).
 const MAX_MESSAGE_LEN: usize = 4096;
@@ -47,6 +49,19 @@ impl TelegramSink {
             let _ = self.bot.send_message(self.chat_id, &chunk).await;
         }
     }
+
+    /// Send an attachment as a document, falling back to its description if
+    /// the upload fails.
+    async fn send_attachment(&self, attachment: &Attachment) {
+        let file = match &attachment.payload {
+            Payload::Bytes(data) => InputFile::memory(data.clone()),
+            Payload::File(path) => InputFile::file(path.clone()),
+        }
+        .file_name(attachment.display_name());
+        if self.bot.send_document(self.chat_id, file).await.is_err() {
+            self.send_plain(&format!("[ATTACHMENT] {attachment}")).await;
+        }
+    }
 }
 
 impl OutputSink for TelegramSink {
@@ -74,6 +89,11 @@ impl OutputSink for TelegramSink {
                 let msg = format!("```\n{output}\n```");
                 self.send(&msg).await;
             }
+            OutputEvent::FilesChanged(artifacts) => {
+                let msg = format!("[FILES] {}", Artifact::summary(artifacts));
+                self.send_plain(&msg).await;
+            }
+            OutputEvent::Attachment(attachment) => self.send_attachment(attachment).await,
             OutputEvent::ToolError(err) => {
                 let msg = format!("[ERROR] {err}");
                 self.send_plain(&msg).await;
@@ -9,10 +9,13 @@ use tracing::{info, warn};
 use crate::enforcement::policy::Policy;
 use crate::providers::UserContent;
 use crate::providers::anthropic::AnthropicProvider;
+use crate::providers::bedrock::BedrockProvider;
 use crate::providers::config::ProvidersConfig;
+use crate::providers::ollama::OllamaProvider;
 use crate::providers::openai::OpenAiProvider;
+use crate::providers::sigv4::{self, AwsCredentials};
 use crate::runtime::AgentLoop;
-use crate::runtime::prompt::build_system_prompt;
+use crate::runtime::prompt::PromptBuilder;
 use crate::tools::ToolRegistry;
 
 use super::approval::{ApprovalMessage, TelegramApprovalGate};
@@ -129,15 +132,7 @@ async fn chat_session(
         config.providers_config
     {
         // Use config file — instantiate the "default" provider (supports failover).
-        if !providers_config.providers.contains_key("default") {
-            warn!(chat_id = %chat_id, "providers config missing [providers.default]");
-            return;
-        }
-        match crate::providers::config::instantiate_named_provider(
-            providers_config,
-            "default",
-            &mut Vec::new(),
-        ) {
+        match providers_config.instantiate_default() {
             Ok(p) => p,
             Err(e) => {
                 warn!(chat_id = %chat_id, error = %e, "failed to create provider from config");
@@ -160,6 +155,40 @@ async fn chat_session(
                     }
                 }
             }
+            "ollama" => match OllamaProvider::new(&config.model, config.max_tokens) {
+                Ok(mut p) => {
+                    if let Some(url) = config.base_url {
+                        p = p.with_base_url(url);
+                    }
+                    Box::new(p)
+                }
+                Err(e) => {
+                    warn!(chat_id = %chat_id, error = %e, "failed to create Ollama provider");
+                    return;
+                }
+            },
+            "bedrock" => {
+                let Some(credentials) = AwsCredentials::from_env() else {
+                    warn!(chat_id = %chat_id, "AWS credentials required for bedrock provider");
+                    return;
+                };
+                let Some(region) = sigv4::region_from_env() else {
+                    warn!(chat_id = %chat_id, "AWS_REGION required for bedrock provider");
+                    return;
+                };
+                match BedrockProvider::new(credentials, &region, &config.model, config.max_tokens) {
+                    Ok(mut p) => {
+                        if let Some(url) = config.base_url {
+                            p = p.with_endpoint(url);
+                        }
+                        Box::new(p)
+                    }
+                    Err(e) => {
+                        warn!(chat_id = %chat_id, error = %e, "failed to create Bedrock provider");
+                        return;
+                    }
+                }
+            }
             _ => {
                 // Default to Anthropic. api_key is required for Anthropic.
                 let api_key = match config.api_key {
@@ -220,6 +249,13 @@ async fn chat_session(
         ToolRegistry::new()
     };
 
+    // PowerShell is the native shell on Windows hosts.
+    let registry = if cfg!(windows) && !skip_builtin_bash {
+        registry.with_powershell()
+    } else {
+        registry
+    };
+
     // Add container-sandboxed bash if runtime is available.
     #[cfg(feature = "container")]
     let (registry, _sandbox_bash_ipc_dir) = {
@@ -242,7 +278,10 @@ async fn chat_session(
     let cwd = std::env::current_dir()
         .map(|p| p.display().to_string())
         .unwrap_or_else(|_| ".".to_owned());
-    let system_prompt = build_system_prompt(&cwd);
+    let system_prompt = PromptBuilder::new(&cwd)
+        .with_tools(registry.definitions())
+        .with_policy(&config.policy)
+        .build();
 
     let output = TelegramSink::new(config.bot.clone(), chat_id);
     let approval_gate = TelegramApprovalGate::new(config.bot, chat_id, approval_tx);
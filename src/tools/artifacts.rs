@@ -0,0 +1,202 @@
+//! Artifact tracking: which files a tool invocation created, modified, or
+//! deleted.
+//!
+//! With tracking enabled (`ToolRegistry::with_artifact_tracking`,
+//! `--track-files`), the executor records every file's size and modification
+//! time under the workspace root before and after each execution and attaches
+//! the difference to the `ToolResult`. Works for every tool, including
+//! processes that write through the shell.
+//!
+//! `.git` and cache directories (those holding a `CACHEDIR.TAG`, such as
+//! cargo's `target/`) are not scanned, symlinks are not followed, and a
+//! workspace with more than `MAX_FILES` files is not tracked at all. A rewrite
+//! that keeps both size and modification time is not seen.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Files scanned per workspace before tracking gives up.
+const MAX_FILES: usize = 50_000;
+/// Paths listed by `Artifact::summary` before the rest are counted.
+const MAX_LISTED: usize = 20;
+
+/// One file an invocation changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Relative to the workspace root.
+    pub path: PathBuf,
+    pub change: ArtifactChange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactChange {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl ArtifactChange {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+impl fmt::Display for Artifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.change.as_str(), self.path.display())
+    }
+}
+
+impl Artifact {
+    /// One line naming the changed files, e.g. `created a.txt, modified
+    /// src/lib.rs`, with any beyond `MAX_LISTED` counted.
+    pub fn summary(artifacts: &[Artifact]) -> String {
+        let mut listed: Vec<String> = artifacts
+            .iter()
+            .take(MAX_LISTED)
+            .map(ToString::to_string)
+            .collect();
+        if artifacts.len() > MAX_LISTED {
+            listed.push(format!("and {} more", artifacts.len() - MAX_LISTED));
+        }
+        listed.join(", ")
+    }
+}
+
+/// Size and modification time of every file under a root.
+pub(crate) struct Scan(HashMap<PathBuf, (u64, Option<SystemTime>)>);
+
+impl Scan {
+    /// Scan `root` on the blocking pool. `None` if the workspace is too large
+    /// to track (or the scan could not run).
+    pub(crate) async fn take(root: &Path) -> Option<Self> {
+        let root = root.to_path_buf();
+        let scan = tokio::task::spawn_blocking(move || {
+            let mut files = HashMap::new();
+            walk(&root, &root, &mut files).then_some(files)
+        })
+        .await
+        .ok()
+        .flatten();
+        if scan.is_none() {
+            warn!(
+                max_files = MAX_FILES,
+                "workspace too large, file changes not tracked"
+            );
+        }
+        scan.map(Self)
+    }
+
+    /// What changed between `self` (before) and `after`, sorted by path.
+    pub(crate) fn diff(&self, after: &Scan) -> Vec<Artifact> {
+        let mut artifacts: Vec<Artifact> = after
+            .0
+            .iter()
+            .filter_map(|(path, state)| {
+                let change = match self.0.get(path) {
+                    None => ArtifactChange::Created,
+                    Some(before) if before != state => ArtifactChange::Modified,
+                    Some(_) => return None,
+                };
+                Some(Artifact {
+                    path: path.clone(),
+                    change,
+                })
+            })
+            .chain(
+                self.0
+                    .keys()
+                    .filter(|path| !after.0.contains_key(*path))
+                    .map(|path| Artifact {
+                        path: path.clone(),
+                        change: ArtifactChange::Deleted,
+                    }),
+            )
+            .collect();
+        artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+        artifacts
+    }
+}
+
+/// Record the files under `dir`; false once more than `MAX_FILES` are seen.
+/// Unreadable entries are skipped.
+fn walk(root: &Path, dir: &Path, files: &mut HashMap<PathBuf, (u64, Option<SystemTime>)>) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return true;
+    };
+    if dir != root && dir.join("CACHEDIR.TAG").exists() {
+        return true;
+    }
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.path().symlink_metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        if meta.is_dir() {
+            if entry.file_name() != ".git" && !walk(root, &path, files) {
+                return false;
+            }
+            continue;
+        }
+        if files.len() == MAX_FILES {
+            return false;
+        }
+        if let Ok(relative) = path.strip_prefix(root) {
+            files.insert(relative.to_path_buf(), (meta.len(), meta.modified().ok()));
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn diff_reports_created_modified_and_deleted_files() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "a").unwrap();
+        std::fs::write(root.join("old.txt"), "x").unwrap();
+        std::fs::write(root.join("same.txt"), "x").unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::write(root.join("target/CACHEDIR.TAG"), "").unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+
+        let before = Scan::take(root).await.unwrap();
+        std::fs::write(root.join("src/lib.rs"), "ab").unwrap();
+        std::fs::remove_file(root.join("old.txt")).unwrap();
+        std::fs::write(root.join("new.txt"), "n").unwrap();
+        std::fs::write(root.join("target/debug/out"), "bin").unwrap();
+        std::fs::write(root.join(".git/index"), "i").unwrap();
+        let after = Scan::take(root).await.unwrap();
+
+        let artifacts = before.diff(&after);
+        assert_eq!(
+            Artifact::summary(&artifacts),
+            "created new.txt, deleted old.txt, modified src/lib.rs"
+        );
+    }
+
+    #[test]
+    fn summary_counts_beyond_limit() {
+        let artifacts: Vec<Artifact> = (0..MAX_LISTED + 3)
+            .map(|i| Artifact {
+                path: PathBuf::from(format!("f{i}")),
+                change: ArtifactChange::Created,
+            })
+            .collect();
+        assert!(Artifact::summary(&artifacts).ends_with(", and 3 more"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Binary payloads a tool returns alongside its text output.
+//!
+//! `ToolResult.output` is what the model reads. A screenshot, a generated
+//! image, or a build tarball travels beside it as an `Attachment` — raw bytes
+//! or a reference to a file the tool wrote, with a MIME type — instead of
+//! being base64-encoded into the text. The agent loop tells the model what was
+//! attached (one line per attachment, see `Display`) and hands the payload to
+//! the output sink, which delivers it to humans.
+//!
+//! The `[output]` policy rules apply to text only; attachments pass them
+//! uninspected.
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// A binary payload attached to a `ToolResult`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// File name to present it under; defaults to the file's own name for
+    /// `Payload::File`.
+    pub name: Option<String>,
+    /// MIME type, e.g. `image/png`.
+    pub mime_type: String,
+    pub payload: Payload,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Payload {
+    /// Held in memory.
+    Bytes(Vec<u8>),
+    /// A file the tool wrote; read when delivered.
+    File(PathBuf),
+}
+
+impl Attachment {
+    pub fn bytes(mime_type: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            name: None,
+            mime_type: mime_type.into(),
+            payload: Payload::Bytes(data),
+        }
+    }
+
+    pub fn file(mime_type: impl Into<String>, path: PathBuf) -> Self {
+        Self {
+            name: None,
+            mime_type: mime_type.into(),
+            payload: Payload::File(path),
+        }
+    }
+
+    /// Set the name to present the attachment under (builder pattern).
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The given name, else the file's name, else `attachment`.
+    pub fn display_name(&self) -> String {
+        match (&self.name, &self.payload) {
+            (Some(name), _) => name.clone(),
+            (None, Payload::File(path)) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "attachment".to_owned()),
+            (None, Payload::Bytes(_)) => "attachment".to_owned(),
+        }
+    }
+
+    /// Payload size in bytes; `None` for a file that can no longer be read.
+    pub fn size(&self) -> Option<u64> {
+        match &self.payload {
+            Payload::Bytes(data) => Some(data.len() as u64),
+            Payload::File(path) => std::fs::metadata(path).ok().map(|meta| meta.len()),
+        }
+    }
+}
+
+/// `shot.png (image/png, 2048 bytes)`.
+impl fmt::Display for Attachment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}", self.display_name(), self.mime_type)?;
+        match self.size() {
+            Some(size) => write!(f, ", {size} bytes)"),
+            None => write!(f, ", missing)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_names_type_and_size() {
+        let shot =
+            Attachment::bytes("image/png", vec![0x89, b'P', b'N', b'G']).with_name("shot.png");
+        assert_eq!(shot.to_string(), "shot.png (image/png, 4 bytes)");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("build.tar.gz");
+        std::fs::write(&path, [0u8; 10]).unwrap();
+        let tarball = Attachment::file("application/gzip", path.clone());
+        assert_eq!(
+            tarball.to_string(),
+            "build.tar.gz (application/gzip, 10 bytes)"
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            tarball.to_string(),
+            "build.tar.gz (application/gzip, missing)"
+        );
+    }
+}
@@ -1,17 +1,136 @@
+use std::path::Path;
+use std::process::Stdio;
 use std::time::{Duration, Instant};
 
-use tokio::process::Command;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{ChildStdin, Command};
 use tracing::{info, info_span, warn};
 
 use crate::enforcement::capability::CapabilityToken;
 use crate::error::CherubError;
 
-use super::ToolResult;
+use super::process_group::{self, ProcessGroup};
+use super::sandbox::{Sandbox, SandboxProfile};
+use super::scratch::{self, ScratchDir};
+use super::{ProcessStatus, ToolResult, env, interactive, seccomp};
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
 const DEFAULT_MAX_OUTPUT: usize = 256 * 1024; // 256 KiB
 
-/// Bash command execution tool.
+/// Shell program a command string is run with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Sh,
+    #[default]
+    Bash,
+    Zsh,
+    Fish,
+    /// PowerShell 7+.
+    Pwsh,
+    /// Windows PowerShell 5.1 (`powershell.exe`).
+    Powershell,
+    Cmd,
+}
+
+impl Shell {
+    fn program(self) -> &'static str {
+        match self {
+            Shell::Sh => "sh",
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::Pwsh => "pwsh",
+            Shell::Powershell => "powershell",
+            Shell::Cmd => "cmd",
+        }
+    }
+
+    /// Whether this is one of the Windows shells: backslash is a path
+    /// separator there, not an escape.
+    pub(crate) fn is_windows(self) -> bool {
+        matches!(self, Shell::Pwsh | Shell::Powershell | Shell::Cmd)
+    }
+}
+
+/// `[tools.<name>.shell]`: which shell runs the command, and whether as a login
+/// shell (profile sourced first). Carried in capability tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShellConfig {
+    #[serde(default)]
+    pub(crate) program: Shell,
+    #[serde(default)]
+    pub(crate) login: bool,
+}
+
+impl ShellConfig {
+    /// The process that runs `command` in this shell, built by `command_for`.
+    /// "Login" means the shell's startup files run first: `-l` for POSIX
+    /// shells, the profile for PowerShell, AutoRun for cmd.
+    pub(super) fn command(
+        self,
+        token: &CapabilityToken,
+        root: &Path,
+        scratch: &Path,
+        command: &str,
+    ) -> Command {
+        let program = self.program.program();
+        match self.program {
+            Shell::Sh | Shell::Bash | Shell::Zsh | Shell::Fish => {
+                let flag = if self.login { "-lc" } else { "-c" };
+                command_for(token, root, None, scratch, program, &[flag, command])
+            }
+            Shell::Pwsh | Shell::Powershell => {
+                let mut args = vec!["-NoLogo", "-NonInteractive"];
+                if !self.login {
+                    args.push("-NoProfile");
+                }
+                args.extend(["-Command", command]);
+                command_for(token, root, None, scratch, program, &args)
+            }
+            Shell::Cmd => {
+                // `/v:off` keeps `!` literal whatever the registry default.
+                let mut args = vec!["/v:off", "/s", "/c"];
+                if !self.login {
+                    args.insert(0, "/d");
+                }
+                cmd_command(token, root, scratch, args, command)
+            }
+        }
+    }
+}
+
+/// cmd does not parse its command line by the MSVC rules std quotes for, so
+/// on Windows the command goes verbatim after `/s /c`, in one pair of quotes.
+#[cfg(windows)]
+fn cmd_command(
+    token: &CapabilityToken,
+    root: &Path,
+    scratch: &Path,
+    args: Vec<&str>,
+    command: &str,
+) -> Command {
+    let mut cmd = command_for(token, root, None, scratch, "cmd", &args);
+    cmd.raw_arg(format!("\"{command}\""));
+    cmd
+}
+
+#[cfg(not(windows))]
+fn cmd_command<'a>(
+    token: &CapabilityToken,
+    root: &Path,
+    scratch: &Path,
+    mut args: Vec<&'a str>,
+    command: &'a str,
+) -> Command {
+    args.push(command);
+    command_for(token, root, None, scratch, "cmd", &args)
+}
+
+/// Shell command execution tool. Runs bash unless the policy names another
+/// shell in `[tools.<name>.shell]`.
 pub struct BashTool {
     pub(crate) timeout: Duration,
     pub(crate) max_output: usize,
@@ -45,79 +164,311 @@ impl BashTool {
     pub async fn execute(
         &self,
         params: &serde_json::Value,
-        _token: CapabilityToken,
+        token: CapabilityToken,
     ) -> Result<ToolResult, CherubError> {
+        token.check_tool("bash")?;
         let command = params
             .get("command")
             .and_then(|v| v.as_str())
             .ok_or_else(|| {
                 CherubError::InvalidInvocation("missing 'command' parameter".to_owned())
             })?;
+        let stdin = params.get("stdin").and_then(|v| v.as_str());
 
         let _span = info_span!("bash_exec", command = %command);
-        let start = Instant::now();
+        // Fail fast: without a terminal these would hang until the timeout.
+        if let Some(reason) = interactive::interactive_reason(command, stdin.is_some()) {
+            info!(reason = %reason, "interactive command refused");
+            return Err(CherubError::ToolExecution(reason));
+        }
+        // The policy's per-tier limit, if any, overrides the tool default.
+        let timeout = token.limits.timeout.unwrap_or(self.timeout);
+        let max_output = token.limits.max_output_bytes.unwrap_or(self.max_output);
 
-        let result = tokio::time::timeout(self.timeout, async {
-            Command::new("bash")
-                .arg("-c")
-                .arg(command)
-                .kill_on_drop(true)
-                .output()
-                .await
-        })
-        .await;
+        let root = token
+            .workspace_root
+            .clone()
+            .unwrap_or_else(super::workspace_root);
+        let scratch = ScratchDir::create()?;
+        let cmd = token
+            .shell
+            .unwrap_or_default()
+            .command(&token, &root, &scratch.path, command);
+        run(cmd, &token, &root, scratch, stdin, timeout, max_output).await
+    }
+}
 
-        match result {
-            Err(_) => {
-                let duration_ms = start.elapsed().as_millis();
-                warn!(duration_ms = %duration_ms, "command timed out");
-                Err(CherubError::ToolExecution(format!(
-                    "command timed out after {}s",
-                    self.timeout.as_secs()
-                )))
-            }
-            Ok(Err(e)) => {
-                warn!(error = %e, "failed to spawn");
-                Err(CherubError::ToolExecution(format!("failed to spawn: {e}")))
-            }
-            Ok(Ok(output)) => {
-                let duration_ms = start.elapsed().as_millis();
-                let exit_code = output.status.code().unwrap_or(-1);
-                let stdout_bytes = output.stdout.len();
-                let stderr_bytes = output.stderr.len();
-                info!(exit_code, stdout_bytes, stderr_bytes, duration_ms = %duration_ms);
-
-                let mut stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-                let stderr = String::from_utf8_lossy(&output.stderr);
-
-                if !stderr.is_empty() {
-                    if !stdout.is_empty() && !stdout.ends_with('\n') {
-                        stdout.push('\n');
-                    }
-                    stdout.push_str(&stderr);
-                }
+/// `program` with `args`, wrapped in the token's sandbox backend if its tier
+/// has one. `input` is a host path the command must be able to read;
+/// `scratch` is the invocation's scratch directory, which it may write.
+pub(super) fn command_for(
+    token: &CapabilityToken,
+    root: &Path,
+    input: Option<&Path>,
+    scratch: &Path,
+    program: &str,
+    args: &[&str],
+) -> Command {
+    let sandbox = token
+        .limits
+        .sandbox
+        .as_ref()
+        .zip(SandboxProfile::for_tier(token.tier, root));
+    match sandbox {
+        Some((backend, profile)) => {
+            let profile = SandboxProfile {
+                input,
+                scratch: Some(scratch),
+                ..profile
+            };
+            backend.command(&profile, program, args)
+        }
+        None => {
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+    }
+}
 
-                if !output.status.success() {
-                    let code = output
-                        .status
-                        .code()
-                        .map_or("unknown".to_owned(), |c| c.to_string());
-                    if !stdout.is_empty() && !stdout.ends_with('\n') {
-                        stdout.push('\n');
-                    }
-                    stdout.push_str(&format!("[exit code: {code}]"));
-                }
+/// Spawn `cmd` the way every command tool runs: allowlisted environment plus
+/// `$CHERUB_TMPDIR`, workspace cwd, the token's seccomp filter, its own process
+/// group, and output capped at `max_output` within `timeout`. The scratch
+/// directory is removed when this returns or is cancelled; outgrowing the
+/// tier's `tmp_quota_bytes` kills the process group and fails the call. `stdin` is written to the child and
+/// then closed; without it stdin is `/dev/null`. A non-zero exit or timeout is
+/// reported in the result, not as an error. The result's `ProcessStatus` keeps
+/// the separate streams, timing, and working directory behind the merged output.
+pub(super) async fn run(
+    mut cmd: Command,
+    token: &CapabilityToken,
+    root: &Path,
+    scratch: ScratchDir,
+    stdin: Option<&str>,
+    timeout: Duration,
+    max_output: usize,
+) -> Result<ToolResult, CherubError> {
+    let start = Instant::now();
+    // Never inherit the agent's environment (API keys live there).
+    cmd.env_clear()
+        .envs(env::allowlisted(&token.env))
+        .env(scratch::ENV_VAR, &scratch.path);
+    // With a policy `[workspace]`, relative paths resolve inside it.
+    if token.workspace_root.is_some() {
+        cmd.current_dir(root);
+    }
+    let cwd = match cmd.as_std().get_current_dir() {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir().unwrap_or_else(|_| root.to_path_buf()),
+    };
+    seccomp::apply(&mut cmd, &token.seccomp)?;
+    cmd.stdin(if stdin.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    })
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true);
+    process_group::isolate(&mut cmd);
+    let mut child = cmd.spawn().map_err(|e| {
+        warn!(error = %e, "failed to spawn");
+        CherubError::ToolExecution(format!("failed to spawn: {e}"))
+    })?;
+    // Killed on every path but a normal exit, including this future being
+    // dropped mid-run.
+    let group = ProcessGroup::track(&child);
 
-                // Truncate at byte boundary (safe: we truncate the String, not raw bytes)
-                if stdout.len() > self.max_output {
-                    stdout.truncate(self.max_output);
-                    stdout.push_str("\n[output truncated]");
-                }
+    let mut stdout_buf = Vec::new();
+    let mut stderr_buf = Vec::new();
+    let mut discarded = [0u64; 2];
+    let (stdin_pipe, stdout_pipe, stderr_pipe) =
+        (child.stdin.take(), child.stdout.take(), child.stderr.take());
+    // Buffers live outside the future, so output read before a timeout is kept.
+    let quota = token.limits.tmp_quota_bytes;
+    let finished = tokio::time::timeout(timeout, async {
+        let [stdout_discarded, stderr_discarded] = &mut discarded;
+        let process = async {
+            let ((), out, err, status) = tokio::join!(
+                feed(stdin_pipe, stdin.unwrap_or_default()),
+                drain(stdout_pipe, &mut stdout_buf, max_output, stdout_discarded),
+                drain(stderr_pipe, &mut stderr_buf, max_output, stderr_discarded),
+                child.wait(),
+            );
+            out.and(err).and(status)
+        };
+        // `None` once the scratch directory outgrows its quota.
+        tokio::select! {
+            finished = process => Some(finished),
+            () = scratch.exceeded(quota) => None,
+        }
+    })
+    .await;
 
-                Ok(ToolResult { output: stdout })
-            }
+    let (exit_code, timed_out) = match finished {
+        Ok(Some(Ok(status))) => {
+            group.release();
+            (status.code(), false)
+        }
+        Ok(None) => {
+            warn!(quota, "scratch directory over quota, command killed");
+            group.kill(&mut child).await;
+            return Err(CherubError::ToolExecution(format!(
+                "scratch directory exceeded its quota of {} bytes",
+                quota.unwrap_or_default()
+            )));
         }
+        Ok(Some(Err(e))) => {
+            warn!(error = %e, "failed to collect output");
+            return Err(CherubError::ToolExecution(format!(
+                "failed to collect output: {e}"
+            )));
+        }
+        Err(_) => {
+            warn!(
+                duration_ms = %start.elapsed().as_millis(),
+                "command timed out"
+            );
+            group.kill(&mut child).await;
+            (None, true)
+        }
+    };
+
+    let duration = start.elapsed();
+    info!(
+        exit_code,
+        timed_out,
+        stdout_bytes = stdout_buf.len(),
+        stderr_bytes = stderr_buf.len(),
+        duration_ms = %duration.as_millis()
+    );
+
+    trim_partial_char(&mut stdout_buf, &mut discarded[0]);
+    trim_partial_char(&mut stderr_buf, &mut discarded[1]);
+    let mut status = ProcessStatus {
+        exit_code,
+        timed_out,
+        stdout: String::from_utf8_lossy(&stdout_buf).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr_buf).into_owned(),
+        stdout_discarded: discarded[0],
+        stderr_discarded: discarded[1],
+        discarded_bytes: 0,
+        duration,
+        cwd,
+    };
+
+    let mut output = status.stdout.clone();
+    if !status.stderr.is_empty() {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(&status.stderr);
+    }
+
+    // Each stream was capped while reading; the merged text gets the same cap.
+    let kept = floor_char_boundary(&output, max_output);
+    status.discarded_bytes = discarded[0] + discarded[1] + (output.len() - kept) as u64;
+    output.truncate(kept);
+    if status.truncated() {
+        warn!(
+            discarded_bytes = status.discarded_bytes,
+            max_output, "output truncated"
+        );
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(&format!(
+            "[output truncated: {} bytes discarded]",
+            status.discarded_bytes
+        ));
+    }
+
+    let trailer = if status.timed_out {
+        Some(format!("[timed out after {}s]", timeout.as_secs_f32()))
+    } else if status.exit_code != Some(0) {
+        let code = status
+            .exit_code
+            .map_or("unknown".to_owned(), |c| c.to_string());
+        Some(format!("[exit code: {code}]"))
+    } else {
+        None
+    };
+    if let Some(trailer) = trailer {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(&trailer);
+    }
+
+    Ok(ToolResult {
+        output,
+        status: Some(status),
+        artifacts: Vec::new(),
+        attachments: Vec::new(),
+    })
+}
+
+/// Write `input` to the child's stdin, then close it so the child sees EOF.
+/// A child that exits without reading it all is not an error.
+async fn feed(pipe: Option<ChildStdin>, input: &str) {
+    let Some(mut pipe) = pipe else {
+        return;
+    };
+    if let Err(e) = pipe.write_all(input.as_bytes()).await
+        && e.kind() != std::io::ErrorKind::BrokenPipe
+    {
+        warn!(error = %e, "failed to write stdin");
+    }
+}
+
+/// Read a child's pipe to EOF, keeping at most `limit` bytes in `buf` and
+/// counting the rest in `discarded`. Reading continues past the limit so the
+/// child never blocks on a full pipe, but nothing more is buffered.
+async fn drain<R: AsyncRead + Unpin>(
+    pipe: Option<R>,
+    buf: &mut Vec<u8>,
+    limit: usize,
+    discarded: &mut u64,
+) -> std::io::Result<()> {
+    let Some(mut pipe) = pipe else {
+        return Ok(());
+    };
+    let mut chunk = [0u8; 8192];
+    loop {
+        let n = pipe.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        let keep = n.min(limit.saturating_sub(buf.len()));
+        buf.extend_from_slice(&chunk[..keep]);
+        *discarded += (n - keep) as u64;
+    }
+}
+
+/// If the cap split a multi-byte character at the end of `buf`, drop its
+/// leading bytes (counted as discarded) rather than render a replacement char.
+fn trim_partial_char(buf: &mut Vec<u8>, discarded: &mut u64) {
+    if *discarded == 0 {
+        return;
+    }
+    if let Err(e) = std::str::from_utf8(buf)
+        && e.error_len().is_none()
+    {
+        *discarded += (buf.len() - e.valid_up_to()) as u64;
+        buf.truncate(e.valid_up_to());
+    }
+}
+
+/// Largest char boundary in `text` at or below `max`.
+pub(crate) fn floor_char_boundary(text: &str, max: usize) -> usize {
+    if text.len() <= max {
+        return text.len();
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
     }
+    end
 }
 
 #[cfg(test)]
@@ -158,6 +509,7 @@ patterns = ["^echo ", "^false$", "^sh "]
             .await
             .unwrap();
         assert_eq!(result.output.trim(), "hello");
+        assert_eq!(result.status.unwrap().exit_code, Some(0));
     }
 
     #[tokio::test]
@@ -174,6 +526,24 @@ patterns = ["^echo ", "^false$", "^sh "]
         assert!(result.output.contains("err"));
     }
 
+    #[tokio::test]
+    async fn status_keeps_streams_timing_and_cwd() {
+        let result = BashTool::new()
+            .execute(
+                &json!({"command": "echo out && echo err >&2 && false"}),
+                allow_token(),
+            )
+            .await
+            .unwrap();
+        let status = result.status.unwrap();
+        assert_eq!(status.stdout, "out\n");
+        assert_eq!(status.stderr, "err\n");
+        assert!(!status.success());
+        assert!(!status.stdout_truncated() && !status.stderr_truncated());
+        assert!(status.duration > Duration::ZERO);
+        assert_eq!(status.cwd, std::env::current_dir().unwrap());
+    }
+
     #[tokio::test]
     async fn nonzero_exit_code() {
         let tool = BashTool::new();
@@ -184,6 +554,17 @@ patterns = ["^echo ", "^false$", "^sh "]
         assert!(result.output.contains("[exit code: 1]"));
     }
 
+    #[tokio::test]
+    async fn killed_by_signal_has_no_exit_code() {
+        let tool = BashTool::new();
+        let result = tool
+            .execute(&json!({"command": "sh -c 'kill -9 $$'"}), allow_token())
+            .await
+            .unwrap();
+        assert_eq!(result.status.unwrap().exit_code, None);
+        assert!(result.output.contains("[exit code: unknown]"));
+    }
+
     #[tokio::test]
     async fn missing_command_param() {
         let tool = BashTool::new();
@@ -194,6 +575,41 @@ patterns = ["^echo ", "^false$", "^sh "]
         assert!(matches!(err, CherubError::InvalidInvocation(_)));
     }
 
+    #[tokio::test]
+    async fn stdin_is_piped_to_command() {
+        let tool = BashTool::new();
+        let result = tool
+            .execute(
+                &json!({"command": "tr a-z A-Z", "stdin": "hello"}),
+                allow_token(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output, "HELLO");
+
+        // A command that ignores its stdin is not an error.
+        let result = tool
+            .execute(
+                &json!({"command": "true", "stdin": "x".repeat(1 << 20)}),
+                allow_token(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.status.unwrap().exit_code, Some(0));
+    }
+
+    #[tokio::test]
+    async fn interactive_command_fails_fast() {
+        let tool = BashTool::new();
+        for command in ["vim notes.txt", "git rebase -i HEAD~2", "python3"] {
+            let err = tool
+                .execute(&json!({"command": command}), allow_token())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, CherubError::ToolExecution(_)), "{command}");
+        }
+    }
+
     #[tokio::test]
     async fn exit_code_included() {
         let tool = BashTool::new();
@@ -202,21 +618,224 @@ patterns = ["^echo ", "^false$", "^sh "]
             .await
             .unwrap();
         assert!(result.output.contains("[exit code: 42]"));
+        assert_eq!(result.status.unwrap().exit_code, Some(42));
     }
 
     // --- Step 6: Error handling tests ---
 
     #[tokio::test]
-    async fn command_timeout_returns_error() {
+    async fn command_timeout_reported_distinctly() {
         let tool = BashTool::with_timeout(Duration::from_millis(100));
-        let err = tool
-            .execute(&json!({"command": "sleep 10"}), allow_token())
+        let result = tool
+            .execute(&json!({"command": "echo started; sleep 10"}), allow_token())
             .await
-            .unwrap_err();
-        match err {
-            CherubError::ToolExecution(msg) => assert!(msg.contains("timed out")),
-            other => panic!("expected ToolExecution timeout, got {other:?}"),
-        }
+            .unwrap();
+        let status = result.status.unwrap();
+        assert!(status.timed_out);
+        assert_eq!(status.exit_code, None);
+        assert!(result.output.starts_with("started\n"));
+        assert!(result.output.contains("[timed out after 0.1s]"));
+    }
+
+    #[tokio::test]
+    async fn timeout_kills_process_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("leaked");
+        let command = format!("(sleep 1; touch {}) & sleep 10", marker.display());
+        let tool = BashTool::with_timeout(Duration::from_millis(100));
+        let result = tool
+            .execute(&json!({ "command": command }), allow_token())
+            .await
+            .unwrap();
+        assert!(result.status.unwrap().timed_out);
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists(), "background job survived the timeout");
+    }
+
+    #[tokio::test]
+    async fn cancellation_kills_process_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("leaked");
+        let command = format!("(sleep 1; touch {}) & sleep 10", marker.display());
+        let tool = BashTool::new();
+        // Dropping the execution future mid-run, as an aborted turn does.
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(200),
+            tool.execute(&json!({ "command": command }), allow_token()),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists(), "background job survived cancellation");
+    }
+
+    #[tokio::test]
+    async fn policy_timeout_overrides_default() {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::ToolInvocation;
+        use std::str::FromStr;
+
+        let policy = Policy::from_str(
+            "[tools.bash]\nenabled = true\n\n[tools.bash.actions.read]\ntier = \"observe\"\npatterns = [\"^sleep \"]\n\n[execution.observe]\ntimeout_secs = 1\n",
+        )
+        .unwrap();
+        let params = json!({"command": "sleep 10"});
+        let proposal = ToolInvocation::new("bash", "execute", params.clone());
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        let enforcement::Decision::Allow(token) = decision else {
+            panic!("expected Allow");
+        };
+        let start = Instant::now();
+        let result = BashTool::new().execute(&params, token).await.unwrap();
+        assert!(result.status.unwrap().timed_out);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn scratch_dir_exported_removed_and_quota_limited() {
+        let result = BashTool::new()
+            .execute(
+                &json!({"command": "echo \"$CHERUB_TMPDIR\" && echo x > \"$CHERUB_TMPDIR/f\""}),
+                allow_token(),
+            )
+            .await
+            .unwrap();
+        let scratch = std::path::PathBuf::from(result.output.trim());
+        assert!(scratch.starts_with(std::env::temp_dir()), "{scratch:?}");
+        assert!(!scratch.exists());
+
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::ToolInvocation;
+        use std::str::FromStr;
+
+        let policy = Policy::from_str(
+            "[tools.bash]\nenabled = true\n\n[tools.bash.actions.read]\ntier = \"observe\"\npatterns = [\"^head \", \"^sleep \"]\n\n[execution.observe]\ntmp_quota_bytes = 4096\n",
+        )
+        .unwrap();
+        let params =
+            json!({"command": "head -c 1000000 /dev/zero > \"$CHERUB_TMPDIR/big\" && sleep 10"});
+        let proposal = ToolInvocation::new("bash", "execute", params.clone());
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        let enforcement::Decision::Allow(token) = decision else {
+            panic!("expected Allow");
+        };
+        let start = Instant::now();
+        let err = BashTool::new().execute(&params, token).await.unwrap_err();
+        assert!(err.to_string().contains("quota"), "{err}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn runs_policy_shell() {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::ToolInvocation;
+        use std::str::FromStr;
+
+        let run = |shell: &'static str, command: &'static str| async move {
+            let policy = Policy::from_str(&format!(
+                "[tools.bash]\nenabled = true\n\n[tools.bash.shell]\n{shell}\n\n[tools.bash.actions.read]\ntier = \"observe\"\npatterns = [\"^echo \", \"^shopt \"]\n"
+            ))
+            .unwrap();
+            let params = json!({"command": command});
+            let proposal = ToolInvocation::new("bash", "execute", params.clone());
+            let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+            let enforcement::Decision::Allow(token) = decision else {
+                panic!("expected Allow");
+            };
+            BashTool::new()
+                .execute(&params, token)
+                .await
+                .unwrap()
+                .output
+        };
+        assert_eq!(run("program = \"sh\"", "echo \"$0\"").await.trim(), "sh");
+        assert_eq!(run("", "echo \"$0\"").await.trim(), "bash");
+        let login = "shopt -q login_shell && echo login || echo plain";
+        // A login shell sources the profile, which may print its own output.
+        let out = run("login = true", login).await;
+        assert!(out.lines().any(|l| l == "login"), "{out:?}");
+        assert_eq!(run("login = false", login).await.trim(), "plain");
+    }
+
+    #[tokio::test]
+    async fn runs_in_policy_workspace() {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::ToolInvocation;
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let policy = Policy::from_str(&format!(
+            "[tools.bash]\nenabled = true\n\n[tools.bash.actions.read]\ntier = \"observe\"\npatterns = [\"^pwd$\"]\n\n[workspace]\nroot = \"{}\"\n",
+            dir.path().display()
+        ))
+        .unwrap();
+        let params = json!({"command": "pwd"});
+        let proposal = ToolInvocation::new("bash", "execute", params.clone());
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        let enforcement::Decision::Allow(token) = decision else {
+            panic!("expected Allow");
+        };
+        let result = BashTool::new().execute(&params, token).await.unwrap();
+        assert_eq!(
+            result.output.lines().next().map(std::path::Path::new),
+            Some(dir.path().canonicalize().unwrap().as_path())
+        );
+    }
+
+    #[tokio::test]
+    async fn environment_is_allowlisted() {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::ToolInvocation;
+        use std::str::FromStr;
+
+        // Cargo sets both for the test process; only the declared one passes.
+        let params =
+            json!({"command": "echo ${CARGO_PKG_NAME:-unset} ${CARGO_MANIFEST_DIR:-unset}"});
+        let result = BashTool::new()
+            .execute(&params, allow_token())
+            .await
+            .unwrap();
+        assert_eq!(result.output.lines().next(), Some("unset unset"));
+
+        let policy = Policy::from_str(
+            "[tools.bash]\nenabled = true\n\n[tools.bash.actions.read]\ntier = \"observe\"\npatterns = [\"^echo \"]\n\n[execution]\nenv = [\"CARGO_PKG_NAME\"]\n",
+        )
+        .unwrap();
+        let proposal = ToolInvocation::new("bash", "execute", params.clone());
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        let enforcement::Decision::Allow(token) = decision else {
+            panic!("expected Allow");
+        };
+        let result = BashTool::new().execute(&params, token).await.unwrap();
+        assert_eq!(
+            result.output.lines().next(),
+            Some(format!("{} unset", env!("CARGO_PKG_NAME")).as_str())
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn seccomp_profile_blocks_deletion_at_observe() {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::ToolInvocation;
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("keep");
+        std::fs::write(&file, "x").unwrap();
+        let policy = Policy::from_str(
+            "[tools.bash]\nenabled = true\n\n[tools.bash.actions.read]\ntier = \"observe\"\npatterns = [\"^rm \"]\n\n[execution.observe]\nseccomp = true\n",
+        )
+        .unwrap();
+        let params = json!({"command": format!("rm {}", file.display())});
+        let proposal = ToolInvocation::new("bash", "execute", params.clone());
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        let enforcement::Decision::Allow(token) = decision else {
+            panic!("expected Allow");
+        };
+        let result = BashTool::new().execute(&params, token).await.unwrap();
+        assert_ne!(result.status.unwrap().exit_code, Some(0));
+        assert!(file.exists());
     }
 
     #[tokio::test]
@@ -225,12 +844,34 @@ patterns = ["^echo ", "^false$", "^sh "]
         let tool = BashTool::with_max_output(1024);
         let result = tool
             .execute(
-                &json!({"command": "head -c 4096 /dev/urandom | base64"}),
+                &json!({"command": "head -c 5000 /dev/zero | tr '\\0' a"}),
                 allow_token(),
             )
             .await
             .unwrap();
-        assert!(result.output.contains("[output truncated]"));
+        let status = result.status.unwrap();
+        assert!(status.truncated());
+        assert_eq!(status.discarded_bytes, 5000 - 1024);
+        assert!(result.output.starts_with(&"a".repeat(1024)));
+        assert!(
+            result
+                .output
+                .ends_with("[output truncated: 3976 bytes discarded]")
+        );
+    }
+
+    #[tokio::test]
+    async fn truncation_respects_char_boundaries() {
+        let tool = BashTool::with_max_output(5);
+        let result = tool
+            .execute(&json!({"command": "printf 'ééé'"}), allow_token())
+            .await
+            .unwrap();
+        assert!(
+            result
+                .output
+                .starts_with("éé\n[output truncated: 2 bytes discarded]")
+        );
     }
 
     #[tokio::test]
@@ -168,9 +168,10 @@ impl ContainerTool {
     pub async fn execute(
         &self,
         params: &serde_json::Value,
-        _token: CapabilityToken, // consumed — proves enforcement ran
+        token: CapabilityToken, // consumed — proves enforcement ran
         ctx: &ToolContext,
     ) -> Result<ToolResult, CherubError> {
+        token.check_tool(&self.metadata.name)?;
         let span = tracing::info_span!(
             "container_execute",
             tool = %self.metadata.name,
@@ -242,6 +243,9 @@ impl ContainerTool {
                         }
                         return Ok(ToolResult {
                             output: output.unwrap_or_default(),
+                            status: None,
+                            artifacts: Vec::new(),
+                            attachments: Vec::new(),
                         });
                     }
                     ToolMessage::HostCall { id, function, args } => {
@@ -43,8 +43,9 @@ impl DevEnvironmentTool {
     pub async fn execute(
         &self,
         params: &serde_json::Value,
-        _token: CapabilityToken,
+        token: CapabilityToken,
     ) -> Result<ToolResult, CherubError> {
+        token.check_tool("dev_environment")?;
         let action = params.get("action").and_then(|v| v.as_str()).unwrap_or("");
 
         if action != "setup" {
@@ -77,6 +78,9 @@ impl DevEnvironmentTool {
                 "Dev environment ready. Image: {tag}\nInstalled: {lang_list}\n\
                  Python 3 is always included. The sandbox bash tool will use this image."
             ),
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
         })
     }
 }
@@ -0,0 +1,52 @@
+//! Environment allowlist for spawned tool processes.
+//!
+//! Tool processes never inherit the agent's environment: provider API keys and
+//! other secrets in it would otherwise be one `env` away from the model. They
+//! get `BASE_ENV` plus whatever names the policy's `[execution] env` declares.
+
+use std::ffi::OsString;
+
+/// Variables every tool process receives, when set in the agent's environment.
+#[cfg(not(windows))]
+pub(crate) const BASE_ENV: &[&str] = &["PATH", "HOME", "LANG"];
+
+/// Windows processes (cmd and PowerShell among them) fail to start or resolve
+/// programs without the system locations.
+#[cfg(windows)]
+pub(crate) const BASE_ENV: &[&str] = &[
+    "PATH",
+    "PATHEXT",
+    "SystemRoot",
+    "SystemDrive",
+    "windir",
+    "ComSpec",
+    "USERPROFILE",
+    "TEMP",
+    "TMP",
+];
+
+/// The agent's values for `BASE_ENV` and `extra`. Unset names are skipped.
+pub(crate) fn allowlisted(extra: &[String]) -> Vec<(String, OsString)> {
+    BASE_ENV
+        .iter()
+        .copied()
+        .chain(extra.iter().map(String::as_str))
+        .filter_map(|name| std::env::var_os(name).map(|value| (name.to_owned(), value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_allowlisted_names_pass() {
+        // Cargo sets these for every test process.
+        let env = allowlisted(&["CARGO_PKG_NAME".to_owned(), "CHERUB_UNSET_VAR".to_owned()]);
+        let names: Vec<&str> = env.iter().map(|(k, _)| k.as_str()).collect();
+        assert!(names.contains(&"PATH"));
+        assert!(names.contains(&"CARGO_PKG_NAME"));
+        assert!(!names.contains(&"CARGO_MANIFEST_DIR"));
+        assert!(!names.contains(&"CHERUB_UNSET_VAR"));
+    }
+}
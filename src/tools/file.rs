@@ -1,14 +1,18 @@
 //! File tool: structured file operations for the agent.
 //!
-//! Provides read, edit, glob, and grep actions with workspace containment.
+//! Provides read, write, append, edit, delete, list, glob, and grep actions with
+//! workspace containment.
 //! All paths are relative to the workspace root. Absolute paths and directory
 //! traversal are rejected. Symlinks are resolved and re-checked.
 //!
 //! Uses `MatchSource::Structured` for enforcement — the action string is
-//! `"{action}:{path}"` or `"{action}"`, matching the memory tool pattern.
+//! `"{action}:{path}"` or `"{action}"`, matching the memory tool pattern. Policy
+//! tiers operations by path prefix (`^write:src/`) and, through a `max_len`
+//! constraint on `content`, by size — no shell command parsing involved.
 
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::time::SystemTime;
 
 use regex::RegexBuilder;
@@ -16,8 +20,8 @@ use tracing::{info_span, warn};
 
 use crate::enforcement::capability::CapabilityToken;
 use crate::error::CherubError;
-use crate::tools::ToolResult;
 use crate::tools::path::{is_binary_content, resolve_workspace_path};
+use crate::tools::{ToolResult, check_cancelled, spawn_cancellable};
 
 /// Maximum lines returned by `read` before truncation.
 const READ_MAX_LINES: usize = 2_000;
@@ -31,6 +35,8 @@ const GREP_MAX_MATCHES: usize = 100;
 const GREP_MAX_OUTPUT_BYTES: usize = 256 * 1024;
 /// Maximum entries returned by `glob` before truncation.
 const GLOB_MAX_ENTRIES: usize = 1_000;
+/// Maximum entries returned by `list` before truncation.
+const LIST_MAX_ENTRIES: usize = 1_000;
 
 /// UTF-8 BOM (byte order mark).
 const UTF8_BOM: &str = "\u{FEFF}";
@@ -47,8 +53,9 @@ impl FileTool {
     pub async fn execute(
         &self,
         params: &serde_json::Value,
-        _token: CapabilityToken,
+        token: CapabilityToken,
     ) -> Result<ToolResult, CherubError> {
+        token.check_tool("file")?;
         let action = params
             .get("action")
             .and_then(|v| v.as_str())
@@ -56,15 +63,31 @@ impl FileTool {
                 CherubError::InvalidInvocation("file tool requires 'action'".to_owned())
             })?;
 
-        match action {
-            "read" => self.op_read(params),
-            "edit" => self.op_edit(params),
-            "glob" => self.op_glob(params),
-            "grep" => self.op_grep(params),
+        // The operations are synchronous filesystem walks (a grep over a large
+        // tree can take seconds); run them on the blocking pool so they don't
+        // stall the agent loop or provider streaming on the async runtime.
+        // A policy `[workspace]` root on the token takes precedence.
+        let root = token
+            .workspace_root
+            .clone()
+            .unwrap_or_else(|| self.workspace_root.clone());
+        let tool = FileTool::new(root);
+        let action = action.to_owned();
+        let params = params.clone();
+        spawn_cancellable("file operation", move |cancelled| match action.as_str() {
+            "read" => tool.op_read(&params),
+            "write" => tool.op_write(&params, false, cancelled),
+            "append" => tool.op_write(&params, true, cancelled),
+            "edit" => tool.op_edit(&params, cancelled),
+            "delete" => tool.op_delete(&params, cancelled),
+            "list" => tool.op_list(&params),
+            "glob" => tool.op_glob(&params),
+            "grep" => tool.op_grep(&params),
             other => Err(CherubError::InvalidInvocation(format!(
                 "unknown file action: {other}"
             ))),
-        }
+        })
+        .await
     }
 
     fn op_read(&self, params: &serde_json::Value) -> Result<ToolResult, CherubError> {
@@ -134,10 +157,137 @@ impl FileTool {
             output = "[Empty file]".to_owned();
         }
 
-        Ok(ToolResult { output })
+        Ok(ToolResult {
+            output,
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
     }
 
-    fn op_edit(&self, params: &serde_json::Value) -> Result<ToolResult, CherubError> {
+    /// Create or replace (`append = false`) or extend (`append = true`) a file
+    /// with `content`. The parent directory must exist.
+    fn op_write(
+        &self,
+        params: &serde_json::Value,
+        append: bool,
+        cancelled: &AtomicBool,
+    ) -> Result<ToolResult, CherubError> {
+        let action = if append { "append" } else { "write" };
+        let path_str = require_str(params, "path", action)?;
+        let content = require_str(params, "content", action)?;
+        let _span = info_span!("file_write", path = %path_str, append, bytes = content.len());
+
+        let resolved = resolve_workspace_path(&self.workspace_root, path_str)?;
+        if resolved.is_dir() {
+            return Err(CherubError::ToolExecution(format!(
+                "'{path_str}' is a directory"
+            )));
+        }
+
+        check_cancelled(cancelled)?;
+        let written = if append {
+            use std::io::Write;
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&resolved)
+                .and_then(|mut f| f.write_all(content.as_bytes()))
+        } else {
+            fs::write(&resolved, content.as_bytes())
+        };
+        written
+            .map_err(|e| CherubError::ToolExecution(format!("cannot write '{path_str}': {e}")))?;
+
+        let verb = if append { "appended to" } else { "wrote" };
+        Ok(ToolResult {
+            output: format!("{verb} '{path_str}' ({} bytes)", content.len()),
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
+    }
+
+    /// Remove a single file. Directories are refused.
+    fn op_delete(
+        &self,
+        params: &serde_json::Value,
+        cancelled: &AtomicBool,
+    ) -> Result<ToolResult, CherubError> {
+        let path_str = require_str(params, "path", "delete")?;
+        let _span = info_span!("file_delete", path = %path_str);
+
+        let resolved = resolve_workspace_path(&self.workspace_root, path_str)?;
+        if resolved.is_dir() {
+            return Err(CherubError::ToolExecution(format!(
+                "'{path_str}' is a directory; only files can be deleted"
+            )));
+        }
+        check_cancelled(cancelled)?;
+        fs::remove_file(&resolved)
+            .map_err(|e| CherubError::ToolExecution(format!("cannot delete '{path_str}': {e}")))?;
+
+        Ok(ToolResult {
+            output: format!("deleted '{path_str}'"),
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
+    }
+
+    /// List a directory's entries (directories suffixed with `/`), sorted by name.
+    fn op_list(&self, params: &serde_json::Value) -> Result<ToolResult, CherubError> {
+        let dir_str = params.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let _span = info_span!("file_list", path = %dir_str);
+
+        let resolved = if dir_str == "." {
+            self.workspace_root.clone()
+        } else {
+            resolve_workspace_path(&self.workspace_root, dir_str)?
+        };
+        let entries = fs::read_dir(&resolved)
+            .map_err(|e| CherubError::ToolExecution(format!("cannot list '{dir_str}': {e}")))?;
+
+        let mut names: Vec<String> = entries
+            .filter_map(Result::ok)
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                match entry.file_type() {
+                    Ok(t) if t.is_dir() => format!("{name}/"),
+                    _ => name,
+                }
+            })
+            .collect();
+        names.sort_unstable();
+
+        let total = names.len();
+        let mut output = names
+            .into_iter()
+            .take(LIST_MAX_ENTRIES)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if total > LIST_MAX_ENTRIES {
+            output.push_str(&format!(
+                "\n[Showing {LIST_MAX_ENTRIES} of {total} entries.]"
+            ));
+        }
+        if output.is_empty() {
+            output = "[Empty directory]".to_owned();
+        }
+
+        Ok(ToolResult {
+            output,
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
+    }
+
+    fn op_edit(
+        &self,
+        params: &serde_json::Value,
+        cancelled: &AtomicBool,
+    ) -> Result<ToolResult, CherubError> {
         let path_str = require_str(params, "path", "edit")?;
         let old_string = require_str(params, "old_string", "edit")?;
         let new_string = require_str(params, "new_string", "edit")?;
@@ -251,6 +401,7 @@ impl FileTool {
             final_content
         };
 
+        check_cancelled(cancelled)?;
         fs::write(&resolved, to_write.as_bytes())
             .map_err(|e| CherubError::ToolExecution(format!("cannot write '{path_str}': {e}")))?;
 
@@ -259,7 +410,12 @@ impl FileTool {
         } else {
             format!("edited '{path_str}' ({applied} replacements)")
         };
-        Ok(ToolResult { output: msg })
+        Ok(ToolResult {
+            output: msg,
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
     }
 
     fn op_glob(&self, params: &serde_json::Value) -> Result<ToolResult, CherubError> {
@@ -312,7 +468,7 @@ impl FileTool {
         }
 
         // Sort by mtime, newest first.
-        valid_entries.sort_by(|a, b| b.1.cmp(&a.1));
+        valid_entries.sort_by_key(|e| std::cmp::Reverse(e.1));
 
         let total = valid_entries.len();
         let truncated = total > GLOB_MAX_ENTRIES;
@@ -335,7 +491,12 @@ impl FileTool {
             output = "no matches".to_owned();
         }
 
-        Ok(ToolResult { output })
+        Ok(ToolResult {
+            output,
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
     }
 
     fn op_grep(&self, params: &serde_json::Value) -> Result<ToolResult, CherubError> {
@@ -449,7 +610,12 @@ impl FileTool {
             output = "no matches".to_owned();
         }
 
-        Ok(ToolResult { output })
+        Ok(ToolResult {
+            output,
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
     }
 }
 
@@ -1040,6 +1206,92 @@ patterns = ["^edit:", "^edit$"]
         assert!(err.to_string().contains("relative"));
     }
 
+    // --- write / append / delete / list ---
+
+    #[tokio::test]
+    async fn write_creates_and_replaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = make_tool(dir.path());
+        for content in ["first\n", "second\n"] {
+            let result = tool
+                .execute(
+                    &json!({"action": "write", "path": "out.txt", "content": content}),
+                    allow_token(),
+                )
+                .await
+                .unwrap();
+            assert!(result.output.contains("wrote 'out.txt'"));
+            assert_eq!(
+                fs::read_to_string(dir.path().join("out.txt")).unwrap(),
+                content
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn write_requires_existing_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = make_tool(dir.path())
+            .execute(
+                &json!({"action": "write", "path": "missing/out.txt", "content": "x"}),
+                allow_token(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("parent directory does not exist"));
+    }
+
+    #[tokio::test]
+    async fn append_extends_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("log.txt"), "one\n").unwrap();
+        make_tool(dir.path())
+            .execute(
+                &json!({"action": "append", "path": "log.txt", "content": "two\n"}),
+                allow_token(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("log.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_removes_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("gone.txt"), "x").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let tool = make_tool(dir.path());
+        tool.execute(
+            &json!({"action": "delete", "path": "gone.txt"}),
+            allow_token(),
+        )
+        .await
+        .unwrap();
+        assert!(!dir.path().join("gone.txt").exists());
+
+        let err = tool
+            .execute(&json!({"action": "delete", "path": "sub"}), allow_token())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("directory"));
+        assert!(dir.path().join("sub").exists());
+    }
+
+    #[tokio::test]
+    async fn list_marks_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b.txt"), "x").unwrap();
+        fs::create_dir(dir.path().join("a")).unwrap();
+        let result = make_tool(dir.path())
+            .execute(&json!({"action": "list"}), allow_token())
+            .await
+            .unwrap();
+        assert_eq!(result.output, "a/\nb.txt");
+    }
+
     // --- unknown action ---
 
     #[tokio::test]
@@ -1047,7 +1299,7 @@ patterns = ["^edit:", "^edit$"]
         let dir = tempfile::tempdir().unwrap();
         let tool = make_tool(dir.path());
         let err = tool
-            .execute(&json!({"action": "delete"}), allow_token())
+            .execute(&json!({"action": "chmod"}), allow_token())
             .await
             .unwrap_err();
         assert!(err.to_string().contains("unknown file action"));
@@ -0,0 +1,393 @@
+//! Git tool: typed repository operations for the agent.
+//!
+//! Provides status, diff, add, commit, push, and reset actions. Each action is
+//! built into a fixed `git` argv (never a shell string), so policy tiers repo
+//! operations by action name instead of regexes over free-form bash.
+//!
+//! Uses `MatchSource::Structured` for enforcement — the action string is
+//! `"{action}"`. Options that change what an action can destroy are typed
+//! params (`force` for push, `mode` for reset) that policy constraints can
+//! check.
+//!
+//! Every invocation runs with:
+//! - `--literal-pathspecs`, so a path is never read as pathspec magic;
+//! - repository hooks and fsmonitor disabled — both run programs named by files
+//!   the agent can write;
+//! - `diff` without external diff drivers or textconv filters, for the same reason.
+//!
+//! Paths must be relative to the workspace root without `..`; ref names may not
+//! start with `-` (option injection) or contain `:` (refspecs).
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tracing::info_span;
+
+use crate::enforcement::capability::CapabilityToken;
+use crate::error::CherubError;
+use crate::tools::ToolResult;
+use crate::tools::path::is_safe_relative_path;
+
+use super::bash;
+use super::scratch::ScratchDir;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_MAX_OUTPUT: usize = 256 * 1024; // 256 KiB
+
+/// Global options placed before every subcommand.
+const GLOBAL_ARGS: &[&str] = &[
+    "--no-pager",
+    "--literal-pathspecs",
+    "-c",
+    "core.hooksPath=/dev/null",
+    "-c",
+    "core.fsmonitor=false",
+];
+
+pub struct GitTool {
+    workspace_root: PathBuf,
+}
+
+impl GitTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self { workspace_root }
+    }
+
+    pub async fn execute(
+        &self,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError> {
+        token.check_tool("git")?;
+        let action = params
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                CherubError::InvalidInvocation("git tool requires 'action'".to_owned())
+            })?;
+        let args = subcommand_args(action, params)?;
+
+        let _span = info_span!("git_exec", action = %action);
+        let timeout = token.limits.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        let max_output = token.limits.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT);
+        // A policy `[workspace]` root on the token takes precedence.
+        let root = token
+            .workspace_root
+            .clone()
+            .unwrap_or_else(|| self.workspace_root.clone());
+
+        let argv: Vec<&str> = GLOBAL_ARGS
+            .iter()
+            .copied()
+            .chain(args.iter().map(String::as_str))
+            .collect();
+        let scratch = ScratchDir::create()?;
+        let mut cmd = bash::command_for(&token, &root, None, &scratch.path, "git", &argv);
+        cmd.current_dir(&root);
+        bash::run(cmd, &token, &root, scratch, None, timeout, max_output).await
+    }
+}
+
+/// The subcommand and its arguments for `action`.
+fn subcommand_args(action: &str, params: &serde_json::Value) -> Result<Vec<String>, CherubError> {
+    let mut args: Vec<String> = Vec::new();
+    match action {
+        "status" => args.extend(["status", "--short", "--branch"].map(String::from)),
+        "diff" => {
+            args.extend(["diff", "--no-ext-diff", "--no-textconv"].map(String::from));
+            if flag(params, "staged") {
+                args.push("--cached".to_owned());
+            }
+            args.push("--".to_owned());
+            args.extend(paths(params, action, false)?);
+        }
+        "add" => {
+            args.extend(["add", "--"].map(String::from));
+            args.extend(paths(params, action, true)?);
+        }
+        "commit" => {
+            let message = params
+                .get("message")
+                .and_then(|v| v.as_str())
+                .filter(|m| !m.trim().is_empty())
+                .ok_or_else(|| {
+                    CherubError::InvalidInvocation("git commit requires 'message'".to_owned())
+                })?;
+            args.extend(["commit", "-m", message].map(String::from));
+        }
+        "push" => {
+            args.push("push".to_owned());
+            if flag(params, "force") {
+                args.push("--force-with-lease".to_owned());
+            }
+            args.push(ref_arg(params, "remote")?.unwrap_or("origin").to_owned());
+            args.extend(ref_arg(params, "branch")?.map(str::to_owned));
+        }
+        "reset" => {
+            let mode = params.get("mode").and_then(|v| v.as_str()).ok_or_else(|| {
+                CherubError::InvalidInvocation("git reset requires 'mode'".to_owned())
+            })?;
+            args.extend(["reset", "-q"].map(String::from));
+            match mode {
+                "unstage" => {
+                    args.push("--".to_owned());
+                    args.extend(paths(params, action, false)?);
+                }
+                "soft" | "mixed" | "hard" => {
+                    args.push(format!("--{mode}"));
+                    args.push(ref_arg(params, "target")?.unwrap_or("HEAD").to_owned());
+                }
+                other => {
+                    return Err(CherubError::InvalidInvocation(format!(
+                        "unknown git reset mode: {other}"
+                    )));
+                }
+            }
+        }
+        other => {
+            return Err(CherubError::InvalidInvocation(format!(
+                "unknown git action: {other}"
+            )));
+        }
+    }
+    Ok(args)
+}
+
+fn flag(params: &serde_json::Value, field: &str) -> bool {
+    params.get(field).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// The `paths` param: workspace-relative paths without `..`.
+fn paths(
+    params: &serde_json::Value,
+    action: &str,
+    required: bool,
+) -> Result<Vec<String>, CherubError> {
+    let invalid = || {
+        CherubError::InvalidInvocation(format!(
+            "git {action}: 'paths' must be an array of relative paths without '..'"
+        ))
+    };
+    let Some(value) = params.get("paths") else {
+        return if required {
+            Err(invalid())
+        } else {
+            Ok(Vec::new())
+        };
+    };
+    let paths = value
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|p| {
+            p.as_str()
+                .filter(|p| is_safe_relative_path(p))
+                .map(str::to_owned)
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if required && paths.is_empty() {
+        return Err(invalid());
+    }
+    Ok(paths)
+}
+
+/// An optional remote, branch, or revision param. Rejects anything git could
+/// read as an option or a refspec (`src:dst`, or `+branch`, which force-pushes
+/// without the `force` param).
+fn ref_arg<'a>(params: &'a serde_json::Value, field: &str) -> Result<Option<&'a str>, CherubError> {
+    let Some(value) = params.get(field) else {
+        return Ok(None);
+    };
+    match value.as_str() {
+        Some(s)
+            if !s.is_empty()
+                && !s.starts_with(['-', '+'])
+                && !s.contains(':')
+                && !s.chars().any(|c| c.is_whitespace() || c.is_control()) =>
+        {
+            Ok(Some(s))
+        }
+        _ => Err(CherubError::InvalidInvocation(format!(
+            "git: invalid '{field}'"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    fn allow_token() -> CapabilityToken {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::{Proposed, ToolInvocation};
+        use std::str::FromStr;
+
+        let policy_str = r#"
+[tools.git]
+enabled = true
+match_source = "structured"
+
+[tools.git.actions.all]
+tier = "act"
+patterns = ["^(status|diff|add|commit)$"]
+"#;
+        let policy = Policy::from_str(policy_str).unwrap();
+        let proposal =
+            ToolInvocation::<Proposed>::new("git", "execute", json!({"action": "status"}));
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        match decision {
+            enforcement::Decision::Allow(token) => token,
+            _ => panic!("expected Allow"),
+        }
+    }
+
+    fn args(params: serde_json::Value) -> Result<Vec<String>, CherubError> {
+        let action = params["action"].as_str().unwrap().to_owned();
+        subcommand_args(&action, &params)
+    }
+
+    #[test]
+    fn actions_build_fixed_argv() {
+        assert_eq!(
+            args(json!({"action": "diff", "staged": true, "paths": ["src/main.rs"]})).unwrap(),
+            [
+                "diff",
+                "--no-ext-diff",
+                "--no-textconv",
+                "--cached",
+                "--",
+                "src/main.rs"
+            ]
+        );
+        assert_eq!(
+            args(json!({"action": "commit", "message": "--amend"})).unwrap(),
+            ["commit", "-m", "--amend"]
+        );
+        assert_eq!(
+            args(json!({"action": "push", "force": true, "branch": "feature/x"})).unwrap(),
+            ["push", "--force-with-lease", "origin", "feature/x"]
+        );
+        assert_eq!(
+            args(json!({"action": "reset", "mode": "hard"})).unwrap(),
+            ["reset", "-q", "--hard", "HEAD"]
+        );
+        assert_eq!(
+            args(json!({"action": "reset", "mode": "unstage", "paths": ["a.txt"]})).unwrap(),
+            ["reset", "-q", "--", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn unsafe_params_rejected() {
+        for params in [
+            json!({"action": "add"}),
+            json!({"action": "add", "paths": []}),
+            json!({"action": "add", "paths": ["../outside"]}),
+            json!({"action": "add", "paths": ["/etc/passwd"]}),
+            json!({"action": "add", "paths": "a.txt"}),
+            json!({"action": "commit", "message": "  "}),
+            json!({"action": "push", "remote": "--upload-pack=evil"}),
+            json!({"action": "push", "branch": "HEAD:main"}),
+            json!({"action": "push", "branch": "+main"}),
+            json!({"action": "push", "remote": "+origin"}),
+            json!({"action": "push", "branch": "a b"}),
+            json!({"action": "reset"}),
+            json!({"action": "reset", "mode": "merge"}),
+            json!({"action": "reset", "mode": "hard", "target": "-p"}),
+            json!({"action": "rebase"}),
+        ] {
+            assert!(
+                matches!(args(params.clone()), Err(CherubError::InvalidInvocation(_))),
+                "{params}"
+            );
+        }
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    #[tokio::test]
+    async fn add_commit_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["config", "user.name", "Test"]);
+        git(dir.path(), &["config", "user.email", "test@example.com"]);
+        std::fs::write(dir.path().join("a.txt"), "hello\n").unwrap();
+        let tool = GitTool::new(dir.path().to_path_buf());
+
+        let result = tool
+            .execute(&json!({"action": "add", "paths": ["a.txt"]}), allow_token())
+            .await
+            .unwrap();
+        assert_eq!(
+            result.status.unwrap().exit_code,
+            Some(0),
+            "{}",
+            result.output
+        );
+
+        let result = tool
+            .execute(&json!({"action": "status"}), allow_token())
+            .await
+            .unwrap();
+        assert!(result.output.contains("A  a.txt"), "{}", result.output);
+
+        let result = tool
+            .execute(
+                &json!({"action": "commit", "message": "add a"}),
+                allow_token(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.status.unwrap().exit_code,
+            Some(0),
+            "{}",
+            result.output
+        );
+
+        let result = tool
+            .execute(&json!({"action": "status"}), allow_token())
+            .await
+            .unwrap();
+        assert!(!result.output.contains("a.txt"), "{}", result.output);
+    }
+
+    #[tokio::test]
+    async fn repository_hooks_do_not_run() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["config", "user.name", "Test"]);
+        git(dir.path(), &["config", "user.email", "test@example.com"]);
+        let hook = dir.path().join(".git/hooks/pre-commit");
+        std::fs::write(&hook, "#!/bin/sh\ntouch hooked\nexit 1\n").unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "x").unwrap();
+        let tool = GitTool::new(dir.path().to_path_buf());
+
+        tool.execute(&json!({"action": "add", "paths": ["a.txt"]}), allow_token())
+            .await
+            .unwrap();
+        let result = tool
+            .execute(&json!({"action": "commit", "message": "m"}), allow_token())
+            .await
+            .unwrap();
+        assert_eq!(
+            result.status.unwrap().exit_code,
+            Some(0),
+            "{}",
+            result.output
+        );
+        assert!(!dir.path().join("hooked").exists());
+    }
+}
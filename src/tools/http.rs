@@ -8,8 +8,15 @@
 //!
 //! # Security hardening (M10)
 //!
-//! - **No redirects**: `reqwest` is configured with `redirect::Policy::none()`.
-//!   An injected redirect could exfiltrate credentials to an attacker-controlled host.
+//! - **Redirects are re-validated**: `reqwest` never follows redirects itself
+//!   (`redirect::Policy::none()`). With `[tools.http.destinations] max_redirects`
+//!   set, the tool follows up to that many hops, and only to hosts the destinations
+//!   `allow` list admits outright; each hop gets the same DNS rebinding check.
+//!   Headers (including injected credentials) are only re-sent to the original
+//!   origin. Any other redirect is returned to the agent with its `Location`, so
+//!   the target must be proposed as a fresh invocation and evaluated like any URL.
+//! - **Response size cap**: the body is read in chunks and cut off at the tier's
+//!   `[execution.<tier>] max_output_bytes` (default 256 KiB); the rest is never buffered.
 //! - **DNS rebinding defense**: the hostname is resolved before sending.
 //!   Any resolved IP in a private/loopback/link-local range is rejected.
 //!   This prevents SSRF attacks where the agent is tricked into hitting internal services
@@ -22,10 +29,12 @@
 //!
 //! 1. Agent proposes `http` tool call with `action: "get"`, `url: "https://api.stripe.com/v1/..."`.
 //! 2. `HttpStructured` extractor produces `"get:api.stripe.com"`.
-//! 3. Enforcement evaluates against `[tools.http.actions.*]` patterns → Allow/Escalate/Reject.
+//! 3. Enforcement evaluates against `[tools.http.actions.*]` patterns → Allow/Escalate/Reject,
+//!    then applies `[tools.http.destinations]` (if configured) to the parsed URL host.
 //! 4. On Allow: `HttpTool::execute()` is called with a `CapabilityToken`.
 //! 5. DNS is resolved; any private IP rejects the call.
-//! 6. Broker injects credential (if specified), request is sent without redirect following.
+//! 6. Broker injects credential (if specified), request is sent; permitted redirects
+//!    are followed hop by hop (see above).
 //! 7. Response body (any status) is scanned by `LeakDetector` before returning.
 
 use std::net::IpAddr;
@@ -49,7 +58,8 @@ const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 const READ_TIMEOUT: Duration = Duration::from_secs(30);
 /// Total request timeout (connect + transfer).
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
-/// Maximum response body size to return (truncate beyond this).
+/// Maximum response body size to return (truncate beyond this) when the tier
+/// sets no `max_output_bytes`.
 const MAX_BODY_BYTES: usize = 256 * 1024; // 256 KiB
 
 /// HTTP tool implementation. One instance shared across the AgentLoop lifecycle.
@@ -62,7 +72,8 @@ impl HttpTool {
     /// Create an `HttpTool` with pre-configured timeouts and security policy.
     ///
     /// Security configuration applied here:
-    /// - Redirects disabled (`Policy::none()`) — prevents credential exfiltration via redirect.
+    /// - Redirects not followed by the client (`Policy::none()`) — `execute()` follows
+    ///   them itself, re-validating each hop against the token's destinations.
     /// - Timeouts enforced at connect, read, and total-request levels.
     /// - DNS rebinding check enforced per-request (see `check_dns_rebinding()`).
     pub fn new(broker: Arc<CredentialBroker>) -> Self {
@@ -81,9 +92,10 @@ impl HttpTool {
     pub async fn execute(
         &self,
         params: &serde_json::Value,
-        _token: CapabilityToken, // Consumed — proves enforcement cleared this call.
+        token: CapabilityToken, // Consumed — proves enforcement cleared this call.
         ctx: &ToolContext,
     ) -> Result<ToolResult, CherubError> {
+        token.check_tool("http")?;
         let action = params
             .get("action")
             .and_then(|v| v.as_str())
@@ -105,6 +117,17 @@ impl HttpTool {
             ));
         }
 
+        // Re-check the host as reqwest parsed it, so a URL enforcement read
+        // differently cannot reach a host the destinations never cleared.
+        if token
+            .redirects
+            .as_ref()
+            .is_some_and(|d| !d.permits_request(url.as_str()))
+        {
+            warn!(url = %url, "http: parsed url is outside the permitted destinations");
+            return Err(CherubError::NotPermitted);
+        }
+
         // DNS rebinding defense (M10): resolve the hostname and reject if any resolved
         // address is in a private/loopback/link-local range. This prevents SSRF attacks
         // where the agent is tricked into targeting internal services.
@@ -129,76 +152,156 @@ impl HttpTool {
                 (url, vec![], LeakDetector::new())
             };
 
-        let mut builder = self.client.request(method.clone(), final_url.clone());
-
-        // Apply any extra headers from params.
-        if let Some(headers) = params.get("headers").and_then(|v| v.as_object()) {
-            for (k, v) in headers {
-                if let Some(val_str) = v.as_str() {
-                    builder = builder.header(k.as_str(), val_str);
+        let headers: Vec<(&str, &str)> = params
+            .get("headers")
+            .and_then(|v| v.as_object())
+            .into_iter()
+            .flatten()
+            .filter_map(|(k, v)| Some((k.as_str(), v.as_str()?)))
+            .collect();
+        let origin = final_url.origin();
+        let mut body = params.get("body").and_then(|v| v.as_str());
+        let mut method = method;
+        let mut url = final_url;
+        let mut hops = 0u8;
+
+        let (mut response, unfollowed) = loop {
+            let mut builder = self.client.request(method.clone(), url.clone());
+            // Headers from params and the broker (Authorization, API-Key, etc.)
+            // only ever go to the origin the agent proposed.
+            if url.origin() == origin {
+                for (k, v) in &headers {
+                    builder = builder.header(*k, *v);
+                }
+                for (k, v) in &credential_headers {
+                    builder = builder.header(k.as_str(), v.as_str());
                 }
             }
-        }
-
-        // Apply credential headers returned by the broker (Authorization, API-Key, etc.).
-        for (k, v) in &credential_headers {
-            builder = builder.header(k.as_str(), v.as_str());
-        }
+            // Apply request body (for POST/PUT/PATCH).
+            if let Some(body) = body {
+                builder = builder.body(body.to_owned());
+            }
 
-        // Apply request body (for POST/PUT/PATCH).
-        if let Some(body) = params.get("body").and_then(|v| v.as_str()) {
-            builder = builder.body(body.to_owned());
-        }
+            // Send the request.
+            let response = builder.send().await.map_err(|e| {
+                let msg = format!("http request failed: {e}");
+                // Scan error message for credential leakage before returning.
+                let safe_msg = leak_detector.redact(&msg);
+                CherubError::Http(safe_msg)
+            })?;
 
-        // Send the request.
-        let response = builder.send().await.map_err(|e| {
-            let msg = format!("http request failed: {e}");
-            // Scan error message for credential leakage before returning.
-            let safe_msg = leak_detector.redact(&msg);
-            CherubError::Http(safe_msg)
-        })?;
+            let Some(next) = redirect_target(&response, &url) else {
+                break (response, None);
+            };
+            let permitted = token.redirects.as_ref().is_some_and(|d| {
+                hops < d.max_redirects
+                    && matches!(next.scheme(), "http" | "https")
+                    && d.allows_redirect(next.as_str())
+            });
+            if !permitted {
+                info!(
+                    url = %url,
+                    status = response.status().as_u16(),
+                    hops,
+                    "http: redirect not followed"
+                );
+                break (response, Some(next));
+            }
+            check_dns_rebinding(&next).await?;
+            hops += 1;
+            info!(from = %url, to = %next, hop = hops, "http: following redirect");
+            // 303, and 301/302 after a POST, switch to GET without a body
+            // (RFC 9110 §15.4); 307/308 repeat the request as-is.
+            let status = response.status();
+            if (status == reqwest::StatusCode::SEE_OTHER && method != reqwest::Method::HEAD)
+                || (matches!(status.as_u16(), 301 | 302) && method == reqwest::Method::POST)
+            {
+                method = reqwest::Method::GET;
+                body = None;
+            }
+            url = next;
+        };
 
         let status = response.status();
         let status_code = status.as_u16();
 
-        // Read body with size limit.
-        let body_bytes = response.bytes().await.map_err(|e| {
+        // Read the body in chunks, stopping at the cap.
+        let max_body = token.limits.max_output_bytes.unwrap_or(MAX_BODY_BYTES);
+        let (body_bytes, truncated) = read_capped(&mut response, max_body).await.map_err(|e| {
             let msg = format!("failed to read response body: {e}");
             let safe_msg = leak_detector.redact(&msg);
             CherubError::Http(safe_msg)
         })?;
 
-        let body_str = if body_bytes.len() > MAX_BODY_BYTES {
-            let truncated = &body_bytes[..MAX_BODY_BYTES];
-            let lossy = String::from_utf8_lossy(truncated);
-            format!("{lossy}\n[... truncated at {MAX_BODY_BYTES} bytes]")
-        } else {
-            String::from_utf8_lossy(&body_bytes).into_owned()
-        };
+        let mut body_str = String::from_utf8_lossy(&body_bytes).into_owned();
+        if truncated {
+            body_str.push_str(&format!("\n[... truncated at {max_body} bytes]"));
+        }
+        if let Some(location) = unfollowed {
+            body_str = format!("Location: {location}\n\n{body_str}");
+        }
 
         // Scan for leaked credential values before returning to session history.
         let safe_body = leak_detector.redact(&body_str);
 
         if leak_detector.contains_secret(&body_str) {
             warn!(
-                url = %final_url,
+                url = %url,
                 status = status_code,
                 "credential value detected in HTTP response body — redacted before returning to agent"
             );
         }
 
         info!(
-            method = %action.to_uppercase(),
-            url = %final_url,
+            method = %method,
+            url = %url,
             status = status_code,
+            hops,
             "http request complete"
         );
 
         let output = format!("HTTP {status_code}\n\n{safe_body}");
-        Ok(ToolResult { output })
+        Ok(ToolResult {
+            output,
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
     }
 }
 
+/// Read at most `max` bytes of the body. The flag is set if more was sent;
+/// the remainder is left unread.
+async fn read_capped(
+    response: &mut reqwest::Response,
+    max: usize,
+) -> Result<(Vec<u8>, bool), reqwest::Error> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let room = max - bytes.len();
+        if chunk.len() > room {
+            bytes.extend_from_slice(&chunk[..room]);
+            return Ok((bytes, true));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok((bytes, false))
+}
+
+/// The absolute URL a redirect response points to, if it is one with a usable
+/// `Location` header.
+fn redirect_target(response: &reqwest::Response, current: &Url) -> Option<Url> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let location = response
+        .headers()
+        .get(reqwest::header::LOCATION)?
+        .to_str()
+        .ok()?;
+    current.join(location).ok()
+}
+
 /// Resolve the URL hostname and reject if any IP is in a private/reserved range.
 ///
 /// Blocks:
@@ -300,7 +403,7 @@ pub fn http_tool_definition() -> crate::providers::ToolDefinition {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["get", "post", "put", "patch", "delete"],
+                    "enum": ["get", "head", "post", "put", "patch", "delete"],
                     "description": "HTTP method"
                 },
                 "url": {
@@ -416,6 +519,66 @@ mod tests {
         assert!(!is_private_ip("::ffff:8.8.8.8".parse().unwrap()));
     }
 
+    // ─── redirect_target / read_capped tests ─────────────────────────────────
+    //
+    // Served from loopback, so these call the helpers directly rather than
+    // `execute()` (which rejects private addresses).
+
+    fn no_redirect_client() -> reqwest::Client {
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn redirect_target_resolves_relative_location() {
+        use wiremock::matchers::path;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(path("/old"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/new?x=1"))
+            .mount(&server)
+            .await;
+        Mock::given(path("/plain"))
+            .respond_with(ResponseTemplate::new(200).insert_header("location", "/ignored"))
+            .mount(&server)
+            .await;
+
+        let url = Url::parse(&format!("{}/old", server.uri())).unwrap();
+        let response = no_redirect_client().get(url.clone()).send().await.unwrap();
+        assert_eq!(
+            redirect_target(&response, &url).map(|u| u.to_string()),
+            Some(format!("{}/new?x=1", server.uri()))
+        );
+
+        let url = Url::parse(&format!("{}/plain", server.uri())).unwrap();
+        let response = no_redirect_client().get(url.clone()).send().await.unwrap();
+        assert_eq!(redirect_target(&response, &url), None);
+    }
+
+    #[tokio::test]
+    async fn read_capped_truncates_at_limit() {
+        use wiremock::{Mock, MockServer, ResponseTemplate, matchers::any};
+
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(10_000)))
+            .mount(&server)
+            .await;
+
+        let mut response = no_redirect_client().get(server.uri()).send().await.unwrap();
+        let (bytes, truncated) = read_capped(&mut response, 100).await.unwrap();
+        assert_eq!(bytes.len(), 100);
+        assert!(truncated);
+
+        let mut response = no_redirect_client().get(server.uri()).send().await.unwrap();
+        let (bytes, truncated) = read_capped(&mut response, 10_000).await.unwrap();
+        assert_eq!(bytes.len(), 10_000);
+        assert!(!truncated);
+    }
+
     // ─── check_dns_rebinding tests ────────────────────────────────────────────
 
     #[tokio::test]
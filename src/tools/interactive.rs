@@ -0,0 +1,178 @@
+//! Interactive-command detection for the bash tool.
+//!
+//! Spawned commands have no terminal of their own: stdin is a pipe or
+//! `/dev/null`, and the process runs in a background process group. Editors,
+//! pagers, and full-screen programs that open the terminal anyway stop or spin
+//! until the timeout kills them. This check spots the common cases before
+//! spawning so the agent gets an immediate, actionable error instead.
+//!
+//! Best effort: it looks at the leading words of each sub-command. A miss costs
+//! one timeout, nothing more.
+
+use crate::enforcement::shell;
+
+/// Programs that always take over the terminal.
+const TERMINAL_PROGRAMS: &[&str] = &[
+    "vi", "vim", "nvim", "view", "vimdiff", "nano", "pico", "emacs", "less", "more", "most", "man",
+    "top", "htop", "btop", "watch", "tmux", "screen", "mc", "notepad",
+];
+
+/// Interpreters that start a REPL when run bare with nothing on stdin.
+const REPLS: &[&str] = &[
+    "python",
+    "python3",
+    "node",
+    "irb",
+    "ghci",
+    "bash",
+    "sh",
+    "zsh",
+    "fish",
+    "pwsh",
+    "powershell",
+    "cmd",
+    "psql",
+    "mysql",
+    "sqlite3",
+];
+
+/// Leading words that run the rest of the segment as a command.
+const WRAPPERS: &[&str] = &["env", "command", "exec", "nice", "nohup", "time"];
+
+/// Git global options that take a separate value.
+const GIT_OPTS_WITH_VALUE: &[&str] = &["-C", "-c", "--git-dir", "--work-tree", "--namespace"];
+
+/// Why `command` would wait for a terminal, if it would. `has_stdin` is whether
+/// the invocation supplies stdin content (a bare REPL then reads it and exits).
+pub(crate) fn interactive_reason(command: &str, has_stdin: bool) -> Option<String> {
+    let segments = shell::parse_commands(command)?;
+    let single = segments.len() == 1;
+    segments.into_iter().find_map(|segment| {
+        let words: Vec<&str> = segment
+            .split_whitespace()
+            .map(|w| w.trim_matches(|c| c == '\'' || c == '"'))
+            .skip_while(|w| is_assignment(w) || WRAPPERS.contains(w))
+            .collect();
+        let (&program, args) = words.split_first()?;
+        let program = program.rsplit('/').next().unwrap_or(program);
+        if program == "emacs" && args.iter().any(|a| a.ends_with("-batch") || *a == "--script") {
+            return None;
+        }
+        if TERMINAL_PROGRAMS.contains(&program) {
+            return Some(format!("'{program}' needs a terminal; use a non-interactive command"));
+        }
+        if single && args.is_empty() && !has_stdin && REPLS.contains(&program) {
+            return Some(format!(
+                "'{program}' with no arguments starts an interactive session; pass a script or supply stdin"
+            ));
+        }
+        match program {
+            "git" => git_reason(args),
+            _ => None,
+        }
+    })
+}
+
+fn git_reason(args: &[&str]) -> Option<String> {
+    let mut words = args.iter().copied();
+    let subcommand = loop {
+        let word = words.next()?;
+        if GIT_OPTS_WITH_VALUE.contains(&word) {
+            words.next();
+        } else if !word.starts_with('-') {
+            break word;
+        }
+    };
+    let args: Vec<&str> = words.collect();
+    let has = |flags: &[&str]| args.iter().any(|a| flags.contains(a));
+    let interactive = match subcommand {
+        "rebase" => has(&["-i", "--interactive", "--edit-todo"]),
+        "add" => has(&["-i", "--interactive", "-p", "--patch", "-e", "--edit"]),
+        "checkout" | "reset" | "restore" | "stash" | "commit" => has(&["-p", "--patch"]),
+        _ => false,
+    };
+    if interactive {
+        return Some(format!(
+            "'git {subcommand}' in interactive mode needs a terminal; use the non-interactive form"
+        ));
+    }
+    if subcommand == "commit" && !commit_has_message(&args) {
+        return Some(
+            "'git commit' without a message opens an editor; pass -m, -F, or --no-edit".to_owned(),
+        );
+    }
+    None
+}
+
+/// Whether `git commit` args supply the message (so no editor opens).
+fn commit_has_message(args: &[&str]) -> bool {
+    args.iter().any(|a| {
+        ["--message", "--file", "--reuse-message", "--no-edit", "--fixup"]
+            .iter()
+            .any(|long| a == long || a.starts_with(&format!("{long}=")))
+            // Short bundles: `-m`, `-am`, `-mfix`, `-F`, `-C`.
+            || (a.starts_with('-') && !a.starts_with("--") && a[1..].contains(['m', 'F', 'C']))
+    })
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_programs_detected() {
+        for command in [
+            "vim src/main.rs",
+            "cat log | less",
+            "EDITOR=x /usr/bin/nano notes",
+            "env TERM=xterm top",
+            "ls && man ls",
+        ] {
+            assert!(interactive_reason(command, false).is_some(), "{command}");
+        }
+        assert!(interactive_reason("emacs --batch -l build.el", false).is_none());
+    }
+
+    #[test]
+    fn git_interactive_modes_detected() {
+        for command in [
+            "git rebase -i HEAD~3",
+            "git -C repo add -p",
+            "git commit",
+            "git commit --amend",
+        ] {
+            assert!(interactive_reason(command, false).is_some(), "{command}");
+        }
+        for command in [
+            "git rebase main",
+            "git commit -m 'fix'",
+            "git commit -am fix",
+            "git commit --amend --no-edit",
+            "git commit --file=msg.txt",
+            "git status",
+        ] {
+            assert_eq!(interactive_reason(command, false), None, "{command}");
+        }
+    }
+
+    #[test]
+    fn bare_repl_needs_stdin() {
+        assert!(interactive_reason("python3", false).is_some());
+        assert_eq!(interactive_reason("python3", true), None);
+        assert_eq!(interactive_reason("python3 script.py", false), None);
+        assert_eq!(interactive_reason("echo 1 | python3", false), None);
+    }
+
+    #[test]
+    fn ordinary_commands_pass() {
+        for command in ["ls -la", "cargo test", "grep -r vim src", "echo less"] {
+            assert_eq!(interactive_reason(command, false), None, "{command}");
+        }
+    }
+}
@@ -0,0 +1,394 @@
+//! Kubectl tool: typed Kubernetes operations for the agent.
+//!
+//! Provides get, describe, logs, apply, scale, delete, and drain. Each action
+//! is built into a fixed `kubectl` argv (never a shell string), so policy tiers
+//! cluster operations by verb instead of regexes over free-form bash.
+//!
+//! Uses `MatchSource::Kubectl` for enforcement — the action string is
+//! `"{verb}:{namespace}"`, or just `"drain"` for the cluster-scoped node drain,
+//! so patterns both tier the verb and allowlist the namespaces it may touch.
+//! Every namespaced action passes `--namespace` explicitly; the kubeconfig's
+//! default namespace is never used.
+//!
+//! `get` prints kubectl's default table, never `-o yaml`, so secret values do
+//! not reach the output. `apply` reads the manifest from stdin. kubectl would
+//! create cluster-scoped objects (ClusterRole, Namespace, ...) whatever
+//! `--namespace` says, so every object in the manifest must set
+//! `metadata.namespace` to the call's namespace, and built-in cluster-scoped
+//! kinds are refused outright. A cluster-scoped custom resource carrying a
+//! namespace is not caught; allowlist `apply` only where the cluster's CRDs
+//! are namespaced.
+//!
+//! The cluster is whatever the agent's kubeconfig selects (`~/.kube/config`,
+//! or `KUBECONFIG` when the policy's `[execution] env` passes it through).
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tracing::info_span;
+
+use crate::enforcement::capability::CapabilityToken;
+use crate::error::CherubError;
+use crate::tools::ToolResult;
+
+use super::bash;
+use super::scratch::ScratchDir;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_MAX_OUTPUT: usize = 256 * 1024; // 256 KiB
+/// Log lines fetched when `tail` is not given.
+const DEFAULT_TAIL: u64 = 200;
+/// Longest Kubernetes object name (DNS subdomain).
+const MAX_NAME_LEN: usize = 253;
+/// Longest namespace name (DNS label).
+const MAX_NAMESPACE_LEN: usize = 63;
+/// Built-in kinds that are not namespaced.
+const CLUSTER_SCOPED_KINDS: &[&str] = &[
+    "APIService",
+    "CertificateSigningRequest",
+    "ClusterRole",
+    "ClusterRoleBinding",
+    "CSIDriver",
+    "CSINode",
+    "CustomResourceDefinition",
+    "FlowSchema",
+    "IngressClass",
+    "MutatingWebhookConfiguration",
+    "Namespace",
+    "Node",
+    "PersistentVolume",
+    "PriorityClass",
+    "PriorityLevelConfiguration",
+    "RuntimeClass",
+    "StorageClass",
+    "ValidatingAdmissionPolicy",
+    "ValidatingAdmissionPolicyBinding",
+    "ValidatingWebhookConfiguration",
+    "VolumeAttachment",
+];
+
+pub struct KubectlTool {
+    workspace_root: PathBuf,
+}
+
+impl KubectlTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self { workspace_root }
+    }
+
+    pub async fn execute(
+        &self,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError> {
+        token.check_tool("kubectl")?;
+        let action = params
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                CherubError::InvalidInvocation("kubectl tool requires 'action'".to_owned())
+            })?;
+        let args = subcommand_args(action, params)?;
+        let manifest = match action {
+            "apply" => {
+                let manifest = required(params, action, "manifest")?;
+                // `subcommand_args` has already required a valid namespace.
+                check_manifest(manifest, namespace(action, params).unwrap_or_default())?;
+                Some(manifest)
+            }
+            _ => None,
+        };
+
+        let _span = info_span!("kubectl_exec", action = %action);
+        let timeout = token.limits.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        let max_output = token.limits.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT);
+        // A policy `[workspace]` root on the token takes precedence.
+        let root = token
+            .workspace_root
+            .clone()
+            .unwrap_or_else(|| self.workspace_root.clone());
+
+        let argv: Vec<&str> = args.iter().map(String::as_str).collect();
+        let scratch = ScratchDir::create()?;
+        let mut cmd = bash::command_for(&token, &root, None, &scratch.path, "kubectl", &argv);
+        cmd.current_dir(&root);
+        bash::run(cmd, &token, &root, scratch, manifest, timeout, max_output).await
+    }
+}
+
+/// The namespace an action runs in: `None` for the cluster-scoped `drain`.
+/// Shared with enforcement, so the action string names exactly the namespace
+/// the argv passes.
+pub(crate) fn namespace<'a>(action: &str, params: &'a serde_json::Value) -> Option<&'a str> {
+    match action {
+        "drain" => None,
+        _ => params
+            .get("namespace")
+            .and_then(|v| v.as_str())
+            .filter(|ns| is_namespace(ns)),
+    }
+}
+
+/// The kubectl arguments for `action`.
+fn subcommand_args(action: &str, params: &serde_json::Value) -> Result<Vec<String>, CherubError> {
+    let mut args: Vec<String> = Vec::new();
+    match action {
+        "get" | "describe" => {
+            args.push(action.to_owned());
+            args.push(name_param(params, action, "resource")?.to_owned());
+            if params.get("name").is_some() {
+                args.push(name_param(params, action, "name")?.to_owned());
+            }
+            if let Some(selector) = params.get("selector") {
+                let selector = selector
+                    .as_str()
+                    .filter(|s| is_selector(s))
+                    .ok_or_else(|| invalid(action, "'selector' is not a label selector"))?;
+                args.push(format!("--selector={selector}"));
+            }
+        }
+        "logs" => {
+            let name = name_param(params, action, "name")?;
+            args.push("logs".to_owned());
+            args.push(match params.get("resource") {
+                Some(_) => format!("{}/{name}", name_param(params, action, "resource")?),
+                None => name.to_owned(),
+            });
+            if params.get("container").is_some() {
+                let container = name_param(params, action, "container")?;
+                args.push(format!("--container={container}"));
+            }
+            let tail = match params.get("tail") {
+                Some(tail) => tail
+                    .as_u64()
+                    .ok_or_else(|| invalid(action, "'tail' must be a non-negative integer"))?,
+                None => DEFAULT_TAIL,
+            };
+            args.push(format!("--tail={tail}"));
+            if params
+                .get("previous")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+            {
+                args.push("--previous".to_owned());
+            }
+        }
+        "apply" => args.extend(["apply", "--filename=-"].map(String::from)),
+        "scale" => {
+            let resource = name_param(params, action, "resource")?;
+            let name = name_param(params, action, "name")?;
+            let replicas = params
+                .get("replicas")
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| invalid(action, "requires 'replicas', a non-negative integer"))?;
+            args.extend([
+                "scale".to_owned(),
+                format!("{resource}/{name}"),
+                format!("--replicas={replicas}"),
+            ]);
+        }
+        "delete" => {
+            // A name is required: no `--all`, no selector-wide deletes.
+            let resource = name_param(params, action, "resource")?;
+            let name = name_param(params, action, "name")?;
+            args.extend(["delete", resource, name].map(String::from));
+        }
+        "drain" => {
+            let node = name_param(params, action, "node")?;
+            args.extend(["drain", node, "--ignore-daemonsets"].map(String::from));
+            return Ok(args);
+        }
+        other => {
+            return Err(CherubError::InvalidInvocation(format!(
+                "unknown kubectl action: {other}"
+            )));
+        }
+    }
+    let namespace = namespace(action, params)
+        .ok_or_else(|| invalid(action, "requires 'namespace', a Kubernetes namespace name"))?;
+    args.push(format!("--namespace={namespace}"));
+    Ok(args)
+}
+
+/// Refuse a manifest with an object outside `namespace`: one of a built-in
+/// cluster-scoped kind, or one whose `metadata.namespace` is not `namespace`.
+/// `List` objects are checked item by item.
+fn check_manifest(manifest: &str, namespace: &str) -> Result<(), CherubError> {
+    for document in serde_yaml::Deserializer::from_str(manifest) {
+        let object = serde_json::Value::deserialize(document)
+            .map_err(|e| invalid("apply", &format!("manifest is not valid YAML: {e}")))?;
+        check_object(&object, namespace)?;
+    }
+    Ok(())
+}
+
+fn check_object(object: &serde_json::Value, namespace: &str) -> Result<(), CherubError> {
+    if object.is_null() {
+        return Ok(()); // An empty document (`---` with nothing after it)
+    }
+    let kind = object
+        .get("kind")
+        .and_then(|k| k.as_str())
+        .ok_or_else(|| invalid("apply", "every manifest object needs a 'kind'"))?;
+    if kind.ends_with("List") {
+        let items = object
+            .get("items")
+            .and_then(|i| i.as_array())
+            .ok_or_else(|| invalid("apply", &format!("'{kind}' has no 'items'")))?;
+        return items
+            .iter()
+            .try_for_each(|item| check_object(item, namespace));
+    }
+    if CLUSTER_SCOPED_KINDS.contains(&kind) {
+        return Err(invalid(
+            "apply",
+            &format!("'{kind}' is cluster-scoped; only namespaced objects can be applied"),
+        ));
+    }
+    if object
+        .pointer("/metadata/namespace")
+        .and_then(|n| n.as_str())
+        != Some(namespace)
+    {
+        return Err(invalid(
+            "apply",
+            &format!("every object must set metadata.namespace to '{namespace}'"),
+        ));
+    }
+    Ok(())
+}
+
+fn invalid(action: &str, msg: &str) -> CherubError {
+    CherubError::InvalidInvocation(format!("kubectl {action}: {msg}"))
+}
+
+fn required<'a>(
+    params: &'a serde_json::Value,
+    action: &str,
+    field: &str,
+) -> Result<&'a str, CherubError> {
+    params
+        .get(field)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| invalid(action, &format!("requires '{field}'")))
+}
+
+/// A resource type, object, container, or node name param.
+fn name_param<'a>(
+    params: &'a serde_json::Value,
+    action: &str,
+    field: &str,
+) -> Result<&'a str, CherubError> {
+    let value = required(params, action, field)?;
+    if !is_name(value, MAX_NAME_LEN, true) {
+        return Err(invalid(
+            action,
+            &format!("'{field}' is not a Kubernetes name"),
+        ));
+    }
+    Ok(value)
+}
+
+/// A namespace: a DNS label.
+pub(crate) fn is_namespace(name: &str) -> bool {
+    is_name(name, MAX_NAMESPACE_LEN, false)
+}
+
+/// Lowercase letters, digits, `-` (and `.` if `dots`), starting and ending
+/// with a letter or digit. Never readable as an option.
+fn is_name(name: &str, max_len: usize, dots: bool) -> bool {
+    let edge = |c: Option<char>| c.is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+    name.len() <= max_len
+        && edge(name.chars().next())
+        && edge(name.chars().last())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || (dots && c == '.'))
+}
+
+/// A label selector such as `app=web,tier!=cache` or `env in (dev, qa)`.
+fn is_selector(selector: &str) -> bool {
+    !selector.trim().is_empty()
+        && selector
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " =!,._-/()".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(params: serde_json::Value) -> Result<Vec<String>, CherubError> {
+        let action = params["action"].as_str().unwrap().to_owned();
+        subcommand_args(&action, &params)
+    }
+
+    #[test]
+    fn builds_fixed_argv_with_explicit_namespace() {
+        assert_eq!(
+            args(json!({"action": "get", "resource": "pods", "namespace": "dev", "selector": "app=web"}))
+                .unwrap(),
+            ["get", "pods", "--selector=app=web", "--namespace=dev"]
+        );
+        assert_eq!(
+            args(json!({"action": "logs", "name": "web", "resource": "deployment", "namespace": "dev"}))
+                .unwrap(),
+            ["logs", "deployment/web", "--tail=200", "--namespace=dev"]
+        );
+        assert_eq!(
+            args(json!({"action": "scale", "resource": "deployment", "name": "web", "replicas": 3, "namespace": "prod"}))
+                .unwrap(),
+            ["scale", "deployment/web", "--replicas=3", "--namespace=prod"]
+        );
+        assert_eq!(
+            args(json!({"action": "drain", "node": "node-1.internal"})).unwrap(),
+            ["drain", "node-1.internal", "--ignore-daemonsets"]
+        );
+    }
+
+    #[test]
+    fn rejects_missing_namespace_and_option_injection() {
+        // Without a namespace kubectl would use the kubeconfig default.
+        assert!(args(json!({"action": "get", "resource": "pods"})).is_err());
+        assert!(args(json!({"action": "get", "resource": "pods", "namespace": "Dev"})).is_err());
+        assert!(args(json!({"action": "get", "resource": "-o=yaml", "namespace": "dev"})).is_err());
+        assert!(
+            args(
+                json!({"action": "delete", "resource": "pods", "name": "--all", "namespace": "dev"})
+            )
+            .is_err()
+        );
+        // Delete always names one object.
+        assert!(args(json!({"action": "delete", "resource": "pods", "namespace": "dev"})).is_err());
+        assert!(args(json!({"action": "exec", "namespace": "dev"})).is_err());
+    }
+
+    #[test]
+    fn manifest_objects_must_stay_in_the_namespace() {
+        let deployment =
+            "apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: web\n  namespace: dev\n";
+        assert!(check_manifest(deployment, "dev").is_ok());
+        assert!(check_manifest(&format!("---\n{deployment}---\n"), "dev").is_ok());
+        assert!(check_manifest(deployment, "prod").is_err());
+
+        let unnamespaced = "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: cfg\n";
+        assert!(check_manifest(unnamespaced, "dev").is_err());
+
+        let escalation = format!(
+            "{deployment}---\napiVersion: rbac.authorization.k8s.io/v1\nkind: ClusterRoleBinding\n\
+             metadata:\n  name: pwn\n  namespace: dev\nroleRef:\n  kind: ClusterRole\n  name: cluster-admin\n"
+        );
+        assert!(check_manifest(&escalation, "dev").is_err());
+
+        let list = r#"{"apiVersion": "v1", "kind": "List", "items": [
+            {"kind": "ConfigMap", "metadata": {"name": "a", "namespace": "dev"}},
+            {"kind": "Namespace", "metadata": {"name": "x", "namespace": "dev"}}]}"#;
+        assert!(check_manifest(list, "dev").is_err());
+
+        assert!(check_manifest("kind: [unclosed", "dev").is_err());
+        assert!(check_manifest("metadata:\n  namespace: dev\n", "dev").is_err());
+    }
+}
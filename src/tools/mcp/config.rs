@@ -1,8 +1,11 @@
 //! MCP server configuration.
 //!
-//! Parsed from a TOML file (`--mcp-config`). Each server entry specifies the
-//! command to spawn, arguments, environment variables, and optional credential
-//! references for env-var injection at spawn time.
+//! Parsed from a TOML file (`--mcp-config`). Each server entry is either a
+//! local process (`command`, spoken to over stdio) or a remote endpoint (`url`,
+//! spoken to over streamable HTTP with SSE responses). Local servers take
+//! arguments, environment variables, and credential references for env-var
+//! injection at spawn time; remote servers take an optional credential sent as
+//! a bearer token.
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -20,12 +23,15 @@ pub struct McpConfig {
     pub servers: HashMap<String, McpServerConfig>,
 }
 
-/// Configuration for a single MCP server process.
+/// Configuration for a single MCP server. Exactly one of `command` and `url`.
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct McpServerConfig {
     /// Command to spawn (e.g., "npx", "uvx", "node").
-    pub command: String,
+    pub command: Option<String>,
+    /// Remote endpoint (e.g., "https://mcp.example.com/mcp"). Plain `http` is
+    /// accepted only for loopback hosts.
+    pub url: Option<String>,
     /// Arguments to the command.
     #[serde(default)]
     pub args: Vec<String>,
@@ -36,6 +42,64 @@ pub struct McpServerConfig {
     /// Decrypted at spawn time and injected as env vars.
     #[serde(default)]
     pub credential_env: HashMap<String, String>,
+    /// Credential name in the vault, sent as a bearer token to a `url` server.
+    pub auth_credential: Option<String>,
+}
+
+/// How to reach a server, resolved from a validated [`McpServerConfig`].
+pub enum McpTransport<'a> {
+    Stdio { command: &'a str },
+    Http { url: &'a str },
+}
+
+impl McpServerConfig {
+    /// Check the field combination and return the transport to use.
+    pub fn transport(&self) -> Result<McpTransport<'_>, CherubError> {
+        match (&self.command, &self.url) {
+            (Some(command), None) => {
+                if self.auth_credential.is_some() {
+                    return Err(CherubError::Mcp(
+                        "auth_credential applies only to url servers".to_owned(),
+                    ));
+                }
+                Ok(McpTransport::Stdio { command })
+            }
+            (None, Some(url)) => {
+                if !self.args.is_empty() || !self.env.is_empty() || !self.credential_env.is_empty()
+                {
+                    return Err(CherubError::Mcp(
+                        "args, env, and credential_env apply only to command servers".to_owned(),
+                    ));
+                }
+                check_url(url)?;
+                Ok(McpTransport::Http { url })
+            }
+            _ => Err(CherubError::Mcp(
+                "exactly one of 'command' and 'url' is required".to_owned(),
+            )),
+        }
+    }
+}
+
+/// Require `https`, or `http` to a loopback host (a local server or tunnel).
+fn check_url(url: &str) -> Result<(), CherubError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| CherubError::Mcp(format!("invalid url '{url}': {e}")))?;
+    let loopback = parsed.host_str().is_some_and(|host| {
+        host == "localhost"
+            || host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    });
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" if loopback => Ok(()),
+        scheme => Err(CherubError::Mcp(format!(
+            "url '{url}': scheme '{scheme}' not allowed (use https, or http to localhost)"
+        ))),
+    }
 }
 
 impl McpConfig {
@@ -53,7 +117,14 @@ impl McpConfig {
         let content = std::fs::read_to_string(path)
             .map_err(|e| CherubError::Mcp(format!("cannot read {}: {e}", path.display())))?;
 
-        toml::from_str(&content).map_err(|e| CherubError::Mcp(format!("invalid MCP config: {e}")))
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| CherubError::Mcp(format!("invalid MCP config: {e}")))?;
+        for (name, server) in &config.servers {
+            server
+                .transport()
+                .map_err(|e| CherubError::Mcp(format!("server '{name}': {e}")))?;
+        }
+        Ok(config)
     }
 }
 
@@ -77,7 +148,7 @@ args = ["mcp-server-fireflies"]
         assert_eq!(config.servers.len(), 2);
 
         let gw = &config.servers["google-workspace"];
-        assert_eq!(gw.command, "npx");
+        assert_eq!(gw.command.as_deref(), Some("npx"));
         assert_eq!(
             gw.args,
             vec!["-y", "@anthropic/mcp-server-google-workspace"]
@@ -89,7 +160,7 @@ args = ["mcp-server-fireflies"]
         assert!(gw.credential_env.is_empty());
 
         let ff = &config.servers["fireflies"];
-        assert_eq!(ff.command, "uvx");
+        assert_eq!(ff.command.as_deref(), Some("uvx"));
         assert!(ff.env.is_empty());
     }
 
@@ -120,6 +191,38 @@ unknown = "bad"
         assert!(err.is_err());
     }
 
+    #[test]
+    fn url_server_transport() {
+        let toml = r#"
+[servers.remote]
+url = "https://mcp.example.com/mcp"
+auth_credential = "remote_token"
+
+[servers.local]
+url = "http://127.0.0.1:8000/mcp"
+"#;
+        let config: McpConfig = toml::from_str(toml).expect("url config should parse");
+        for server in config.servers.values() {
+            assert!(matches!(server.transport(), Ok(McpTransport::Http { .. })));
+        }
+    }
+
+    #[test]
+    fn invalid_server_combinations_rejected() {
+        for server in [
+            "",
+            "command = \"echo\"\nurl = \"https://x.example/mcp\"",
+            "command = \"echo\"\nauth_credential = \"k\"",
+            "url = \"https://x.example/mcp\"\nargs = [\"-v\"]",
+            "url = \"http://x.example/mcp\"",
+            "url = \"file:///tmp/sock\"",
+        ] {
+            let config: McpConfig =
+                toml::from_str(&format!("[servers.s]\n{server}\n")).expect("should parse");
+            assert!(config.servers["s"].transport().is_err(), "{server}");
+        }
+    }
+
     #[test]
     fn empty_servers_valid() {
         let toml = "[servers]\n";
@@ -1,20 +1,23 @@
 //! MCP server loader: reads config, spawns servers, discovers tools.
 //!
 //! Entry point: `load_from_config()` — reads the config file, spawns each
-//! server process, runs MCP initialization, discovers tools, and returns
-//! a list of `McpToolProxy` instances ready for registration.
+//! local server process or connects to each remote endpoint, runs MCP
+//! initialization, discovers tools, and returns a list of `McpToolProxy`
+//! instances ready for registration.
 
 use std::path::Path;
 use std::sync::Arc;
 
-use rmcp::service::ServiceExt;
-use rmcp::transport::{ConfigureCommandExt, TokioChildProcess};
+use rmcp::RoleClient;
+use rmcp::service::{RunningService, ServiceExt};
+use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
+use rmcp::transport::{ConfigureCommandExt, StreamableHttpClientTransport, TokioChildProcess};
 use tokio::process::Command;
 use tokio::sync::Mutex;
 use tracing::{info, warn};
 
 use super::client::McpClient;
-use super::config::{McpConfig, McpServerConfig};
+use super::config::{McpConfig, McpServerConfig, McpTransport};
 use super::proxy::McpToolProxy;
 use crate::error::CherubError;
 
@@ -29,7 +32,8 @@ pub struct McpLoadResult {
 /// Load MCP servers from a config file, spawn processes, discover tools.
 ///
 /// If `credential_store` is provided and a server has `credential_env` entries,
-/// credentials are decrypted and injected as env vars at spawn time.
+/// credentials are decrypted and injected as env vars at spawn time; a remote
+/// server's `auth_credential` is decrypted and sent as its bearer token.
 pub async fn load_from_config(
     config_path: &Path,
     #[cfg(feature = "credentials")] credential_store: Option<&dyn crate::storage::CredentialStore>,
@@ -49,7 +53,7 @@ pub async fn load_from_config(
     let mut errors = Vec::new();
 
     for (server_name, server_config) in &config.servers {
-        match spawn_server(
+        match connect_server(
             server_name,
             server_config,
             #[cfg(feature = "credentials")]
@@ -78,13 +82,75 @@ pub async fn load_from_config(
     McpLoadResult { tools, errors }
 }
 
-/// Spawn a single MCP server and discover its tools.
-async fn spawn_server(
+/// Connect to a single MCP server and discover its tools.
+async fn connect_server(
     server_name: &str,
     config: &McpServerConfig,
     #[cfg(feature = "credentials")] credential_store: Option<&dyn crate::storage::CredentialStore>,
     #[cfg(feature = "credentials")] user_id: &str,
 ) -> Result<Vec<McpToolProxy>, CherubError> {
+    let service = match config.transport()? {
+        McpTransport::Stdio { command } => {
+            spawn_stdio(
+                command,
+                config,
+                #[cfg(feature = "credentials")]
+                credential_store,
+                #[cfg(feature = "credentials")]
+                user_id,
+            )
+            .await?
+        }
+        McpTransport::Http { url } => {
+            connect_http(
+                url,
+                config,
+                #[cfg(feature = "credentials")]
+                credential_store,
+                #[cfg(feature = "credentials")]
+                user_id,
+            )
+            .await?
+        }
+    };
+
+    let client = McpClient::new(service, server_name);
+
+    // Discover tools (handles pagination automatically).
+    let discovered_tools = client.list_all_tools().await?;
+    let client = Arc::new(Mutex::new(client));
+
+    let proxies: Vec<McpToolProxy> = discovered_tools
+        .into_iter()
+        .map(|tool| {
+            let tool_name = tool.name.to_string();
+            let composite = format!("{server_name}__{tool_name}");
+            let description = tool.description.as_deref().unwrap_or("MCP tool").to_owned();
+
+            // Convert Arc<JsonObject> to serde_json::Value::Object.
+            let input_schema = serde_json::Value::Object(tool.input_schema.as_ref().clone());
+
+            McpToolProxy {
+                server_name: server_name.to_owned(),
+                tool_name,
+                composite_name: composite,
+                description,
+                input_schema,
+                client: Arc::clone(&client),
+            }
+        })
+        .collect();
+
+    Ok(proxies)
+}
+
+/// Spawn a local server process and run the MCP handshake over its stdio.
+async fn spawn_stdio(
+    command: &str,
+    config: &McpServerConfig,
+    #[cfg(feature = "credentials")] credential_store: Option<&dyn crate::storage::CredentialStore>,
+    #[cfg(feature = "credentials")] user_id: &str,
+) -> Result<RunningService<RoleClient, ()>, CherubError> {
     // Build environment: static env + decrypted credential_env.
     #[allow(unused_mut)]
     let mut env_vars = config.env.clone();
@@ -99,19 +165,10 @@ async fn spawn_server(
         let mut cred_vars = Vec::new();
         if let Some(store) = credential_store {
             for (env_key, cred_name) in &config.credential_env {
-                let encrypted = store.get(user_id, cred_name).await.map_err(|e| {
-                    CherubError::Mcp(format!("credential '{cred_name}' for env '{env_key}': {e}"))
-                })?;
-                let decrypted = store.decrypt(&encrypted).await.map_err(|e| {
-                    CherubError::Mcp(format!(
-                        "credential '{cred_name}' decrypt failed for env '{env_key}': {e}"
-                    ))
-                })?;
-                // decrypted.expose() is the 4th call site (same as credential_broker).
-                cred_vars.push((
-                    env_key.clone(),
-                    secrecy::SecretString::from(decrypted.expose().to_owned()),
-                ));
+                let secret =
+                    decrypt_credential(store, user_id, cred_name, &format!("env '{env_key}'"))
+                        .await?;
+                cred_vars.push((env_key.clone(), secret));
             }
         } else if !config.credential_env.is_empty() {
             return Err(CherubError::Mcp(
@@ -136,8 +193,11 @@ async fn spawn_server(
     let env_for_closure = env_vars.clone();
     #[cfg(feature = "credentials")]
     let cred_env_for_closure = credential_env_vars;
-    let transport = TokioChildProcess::new(Command::new(&config.command).configure(move |cmd| {
+    let transport = TokioChildProcess::new(Command::new(command).configure(move |cmd| {
         cmd.args(&args);
+        // Only the base allowlist and the server's configured env — never the
+        // agent's full environment.
+        cmd.env_clear().envs(crate::tools::env::allowlisted(&[]));
         for (k, v) in &env_for_closure {
             cmd.env(k, v);
         }
@@ -151,40 +211,69 @@ async fn spawn_server(
         }
         cmd.kill_on_drop(true);
     }))
-    .map_err(|e| CherubError::Mcp(format!("failed to spawn '{}': {e}", config.command)))?;
+    .map_err(|e| CherubError::Mcp(format!("failed to spawn '{command}': {e}")))?;
 
     // Initialize MCP session.
-    let service = ()
-        .serve(transport)
+    ().serve(transport)
         .await
-        .map_err(|e| CherubError::Mcp(format!("MCP init handshake failed: {e}")))?;
-
-    let client = McpClient::new(service, server_name);
+        .map_err(|e| CherubError::Mcp(format!("MCP init handshake failed: {e}")))
+}
 
-    // Discover tools (handles pagination automatically).
-    let discovered_tools = client.list_all_tools().await?;
-    let client = Arc::new(Mutex::new(client));
+/// Connect to a remote server over streamable HTTP (POST requests, SSE
+/// responses) and run the MCP handshake.
+async fn connect_http(
+    url: &str,
+    config: &McpServerConfig,
+    #[cfg(feature = "credentials")] credential_store: Option<&dyn crate::storage::CredentialStore>,
+    #[cfg(feature = "credentials")] user_id: &str,
+) -> Result<RunningService<RoleClient, ()>, CherubError> {
+    #[allow(unused_mut)]
+    let mut transport_config = StreamableHttpClientTransportConfig::with_uri(url);
 
-    let proxies: Vec<McpToolProxy> = discovered_tools
-        .into_iter()
-        .map(|tool| {
-            let tool_name = tool.name.to_string();
-            let composite = format!("{server_name}__{tool_name}");
-            let description = tool.description.as_deref().unwrap_or("MCP tool").to_owned();
+    #[cfg(feature = "credentials")]
+    if let Some(cred_name) = &config.auth_credential {
+        let store = credential_store.ok_or_else(|| {
+            CherubError::Mcp(
+                "auth_credential requires credential store (CHERUB_MASTER_KEY + DATABASE_URL)"
+                    .to_owned(),
+            )
+        })?;
+        let secret = decrypt_credential(store, user_id, cred_name, "auth").await?;
+        // The transport holds the token for the session's lifetime.
+        use secrecy::ExposeSecret;
+        transport_config = transport_config.auth_header(secret.expose_secret());
+    }
 
-            // Convert Arc<JsonObject> to serde_json::Value::Object.
-            let input_schema = serde_json::Value::Object(tool.input_schema.as_ref().clone());
+    #[cfg(not(feature = "credentials"))]
+    if config.auth_credential.is_some() {
+        return Err(CherubError::Mcp(
+            "auth_credential requires the 'credentials' feature".to_owned(),
+        ));
+    }
 
-            McpToolProxy {
-                server_name: server_name.to_owned(),
-                tool_name,
-                composite_name: composite,
-                description,
-                input_schema,
-                client: Arc::clone(&client),
-            }
-        })
-        .collect();
+    let transport = StreamableHttpClientTransport::from_config(transport_config);
+    ().serve(transport)
+        .await
+        .map_err(|e| CherubError::Mcp(format!("MCP init handshake with '{url}' failed: {e}")))
+}
 
-    Ok(proxies)
+/// Fetch and decrypt a vault credential. `purpose` names its use in errors.
+#[cfg(feature = "credentials")]
+async fn decrypt_credential(
+    store: &dyn crate::storage::CredentialStore,
+    user_id: &str,
+    cred_name: &str,
+    purpose: &str,
+) -> Result<secrecy::SecretString, CherubError> {
+    let encrypted = store
+        .get(user_id, cred_name)
+        .await
+        .map_err(|e| CherubError::Mcp(format!("credential '{cred_name}' for {purpose}: {e}")))?;
+    let decrypted = store.decrypt(&encrypted).await.map_err(|e| {
+        CherubError::Mcp(format!(
+            "credential '{cred_name}' decrypt failed for {purpose}: {e}"
+        ))
+    })?;
+    // decrypted.expose() is the 4th call site (same as credential_broker).
+    Ok(secrecy::SecretString::from(decrypted.expose().to_owned()))
 }
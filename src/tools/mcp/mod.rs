@@ -1,6 +1,7 @@
 //! MCP (Model Context Protocol) server support.
 //!
-//! Spawns MCP server processes over stdio, discovers tools via `tools/list`,
+//! Spawns MCP server processes over stdio or connects to remote servers over
+//! streamable HTTP (SSE responses), discovers tools via `tools/list`,
 //! and registers each as a `ToolImpl::Mcp` variant. All calls are routed
 //! through the enforcement layer with `McpStructured` match source.
 
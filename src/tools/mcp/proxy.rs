@@ -6,13 +6,17 @@
 use std::ops::Deref;
 use std::sync::Arc;
 
-use rmcp::model::RawContent;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use rmcp::model::{RawContent, ResourceContents};
 use tokio::sync::Mutex;
+use tracing::warn;
 
 use super::client::McpClient;
 use crate::error::CherubError;
 use crate::providers::ToolDefinition;
 use crate::tools::ToolResult;
+use crate::tools::attachment::Attachment;
 
 /// Proxy for a single tool discovered from an MCP server.
 pub struct McpToolProxy {
@@ -70,6 +74,11 @@ impl McpToolProxy {
             })
             .collect::<Vec<_>>()
             .join("\n");
+        let attachments = result
+            .content
+            .iter()
+            .filter_map(|c| self.attachment(c.deref()))
+            .collect();
 
         let is_error = result.is_error.unwrap_or(false);
         if is_error {
@@ -78,10 +87,49 @@ impl McpToolProxy {
                 self.server_name, self.tool_name
             )))
         } else {
-            Ok(ToolResult { output })
+            Ok(ToolResult {
+                output,
+                status: None,
+                artifacts: Vec::new(),
+                attachments,
+            })
         }
     }
 
+    /// Decode an image, audio, or binary resource block into an attachment.
+    /// Text blocks are part of the output; undecodable blocks are dropped.
+    fn attachment(&self, content: &RawContent) -> Option<Attachment> {
+        let (data, mime_type, name) = match content {
+            RawContent::Image(image) => (&image.data, image.mime_type.clone(), None),
+            RawContent::Audio(audio) => (&audio.data, audio.mime_type.clone(), None),
+            RawContent::Resource(embedded) => match &embedded.resource {
+                ResourceContents::BlobResourceContents {
+                    uri,
+                    mime_type,
+                    blob,
+                    ..
+                } => (
+                    blob,
+                    mime_type
+                        .clone()
+                        .unwrap_or_else(|| "application/octet-stream".to_owned()),
+                    uri.rsplit('/').next().filter(|name| !name.is_empty()),
+                ),
+                ResourceContents::TextResourceContents { .. } => return None,
+            },
+            RawContent::Text(_) | RawContent::ResourceLink(_) => return None,
+        };
+        let Ok(bytes) = BASE64.decode(data) else {
+            warn!(server = %self.server_name, tool = %self.tool_name, "undecodable MCP content dropped");
+            return None;
+        };
+        let attachment = Attachment::bytes(mime_type, bytes);
+        Some(match name {
+            Some(name) => attachment.with_name(name),
+            None => attachment,
+        })
+    }
+
     /// Build a `ToolDefinition` for the LLM.
     pub fn definition(&self) -> ToolDefinition {
         ToolDefinition {
@@ -32,9 +32,10 @@ impl MemoryTool {
     pub async fn execute(
         &self,
         params: &serde_json::Value,
-        _token: CapabilityToken,
+        token: CapabilityToken,
         ctx: &ToolContext,
     ) -> Result<ToolResult, CherubError> {
+        token.check_tool("memory")?;
         let action = params
             .get("action")
             .and_then(|v| v.as_str())
@@ -113,6 +114,9 @@ impl MemoryTool {
 
         Ok(ToolResult {
             output: format!("stored: {id}"),
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
         })
     }
 
@@ -157,6 +161,9 @@ impl MemoryTool {
         if memories.is_empty() {
             return Ok(ToolResult {
                 output: "no memories found".to_owned(),
+                status: None,
+                artifacts: Vec::new(),
+                attachments: Vec::new(),
             });
         }
 
@@ -171,7 +178,12 @@ impl MemoryTool {
             .collect::<Vec<_>>()
             .join("\n");
 
-        Ok(ToolResult { output })
+        Ok(ToolResult {
+            output,
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
     }
 
     async fn op_search(
@@ -200,6 +212,9 @@ impl MemoryTool {
         if memories.is_empty() {
             return Ok(ToolResult {
                 output: "no results".to_owned(),
+                status: None,
+                artifacts: Vec::new(),
+                attachments: Vec::new(),
             });
         }
 
@@ -209,7 +224,12 @@ impl MemoryTool {
             .collect::<Vec<_>>()
             .join("\n");
 
-        Ok(ToolResult { output })
+        Ok(ToolResult {
+            output,
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
     }
 
     async fn op_update(&self, params: &serde_json::Value) -> Result<ToolResult, CherubError> {
@@ -237,6 +257,9 @@ impl MemoryTool {
         let new_id = self.store.update(id, changes).await?;
         Ok(ToolResult {
             output: format!("updated: {new_id} (supersedes {id})"),
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
         })
     }
 
@@ -253,6 +276,9 @@ impl MemoryTool {
         self.store.forget(id).await?;
         Ok(ToolResult {
             output: format!("forgotten: {id}"),
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
         })
     }
 }
@@ -1,3 +1,5 @@
+pub mod artifacts;
+pub mod attachment;
 pub mod bash;
 #[cfg(feature = "container")]
 pub mod container;
@@ -7,42 +9,76 @@ pub mod container_bash;
 pub mod credential_broker;
 #[cfg(feature = "container")]
 pub mod dev_environment;
+pub(crate) mod env;
 pub mod file;
+pub mod git;
 #[cfg(feature = "credentials")]
 pub mod http;
+pub(crate) mod interactive;
+pub mod kubectl;
 #[cfg(feature = "credentials")]
 pub(crate) mod leak_detector;
 #[cfg(feature = "mcp")]
 pub mod mcp;
 #[cfg(feature = "memory")]
 pub mod memory;
+pub mod patch;
 pub(crate) mod path;
+pub mod powershell;
+pub mod process_group;
+pub mod sandbox;
+pub(crate) mod scratch;
+pub mod script;
+pub mod search;
+pub mod seccomp;
+pub mod snapshot;
+pub mod sql;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
-use std::marker::PhantomData;
-#[cfg(feature = "container")]
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tokio::sync::Semaphore;
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use crate::enforcement::capability::CapabilityToken;
+use crate::enforcement::capability::{CapabilityToken, InvocationBinding, Issuance, SpentTokens};
+use crate::enforcement::tier::Tier;
 use crate::error::CherubError;
 use crate::providers::ToolDefinition;
 
+use artifacts::{Artifact, Scan};
+use attachment::Attachment;
 use bash::BashTool;
 #[cfg(feature = "container")]
 use container::ContainerTool;
 #[cfg(feature = "container")]
 use dev_environment::DevEnvironmentTool;
 use file::FileTool;
+use git::GitTool;
 #[cfg(feature = "credentials")]
 use http::HttpTool;
+use kubectl::KubectlTool;
 #[cfg(feature = "mcp")]
 use mcp::proxy::McpToolProxy;
 #[cfg(feature = "memory")]
 use memory::MemoryTool;
+use patch::PatchTool;
+use powershell::PowerShellTool;
+use script::ScriptTool;
+use search::SearchTool;
+use snapshot::{Snapshot, Snapshots};
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+use sql::SqlTool;
 #[cfg(feature = "wasm")]
 use wasm::WasmTool;
 
@@ -52,35 +88,144 @@ pub struct Proposed;
 /// Typestate: enforcement layer has evaluated this invocation.
 pub struct Evaluated;
 
+/// Typestate: the tool has run. Carries the result and how it was authorized
+/// and run, so audit, transcript, and provider feedback read one object.
+#[derive(Debug)]
+pub struct Executed {
+    pub(crate) result: ToolResult,
+    /// Tier of the capability token the tool ran under.
+    pub(crate) tier: Tier,
+    /// Issuance record of that token: decision id, rule, policy hash.
+    pub(crate) issuance: Issuance,
+    /// Wall-clock time of execution, including retries and their backoff.
+    pub(crate) duration: Duration,
+    /// Runs of the tool: 1 plus retries; 0 when a dry run simulated it.
+    pub(crate) attempts: u32,
+}
+
 /// A tool invocation progressing through the enforcement pipeline.
 ///
 /// `ToolInvocation<Proposed>` → enforcement evaluates → `ToolInvocation<Evaluated>`
+/// → `execute()` → `ToolInvocation<Executed>`
 ///
 /// `execute()` only exists on `Evaluated` — the compiler rejects calls on `Proposed`.
+///
+/// `id` (UUID v7) and `provenance` are assigned at proposal time and carried across
+/// the transition unchanged, so every audit row can be traced to the model turn
+/// that proposed it.
+///
+/// The transition also records a SHA-256 digest of `params`. `execute()` recomputes
+/// it and fails closed on mismatch, so params mutated after evaluation (fields are
+/// `pub(crate)`) never reach a tool under a decision made for different params.
+///
+/// Serializes (in either state) as `{id, tool, action, params, provenance}`; the
+/// digest and state are not included. Only `ToolInvocation<Proposed>` implements
+/// `Deserialize` — an invocation shipped over IPC must be evaluated again.
+#[derive(Serialize)]
 pub struct ToolInvocation<State> {
+    pub(crate) id: Uuid,
     pub(crate) tool: String,
     pub(crate) action: String,
     pub(crate) params: serde_json::Value,
-    _state: PhantomData<State>,
+    pub(crate) provenance: Option<Provenance>,
+    #[serde(skip)]
+    params_digest: Option<[u8; 32]>, // Set by transition(); None while Proposed
+    #[serde(skip)]
+    pub(crate) state: State,
+}
+
+impl<'de> Deserialize<'de> for ToolInvocation<Proposed> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Wire {
+            id: Uuid,
+            tool: String,
+            action: String,
+            params: serde_json::Value,
+            #[serde(default)]
+            provenance: Option<Provenance>,
+        }
+
+        let wire = Wire::deserialize(deserializer)?;
+        Ok(Self {
+            id: wire.id,
+            tool: wire.tool,
+            action: wire.action,
+            params: wire.params,
+            provenance: wire.provenance,
+            params_digest: None,
+            state: Proposed,
+        })
+    }
+}
+
+/// SHA-256 over the canonical JSON serialization of `params`.
+fn params_digest(params: &serde_json::Value) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+    // Serializing a `Value` cannot fail (no non-string keys, no custom Serialize).
+    let bytes = serde_json::to_vec(params).unwrap_or_default();
+    Sha256::digest(&bytes).into()
+}
+
+/// Where a proposed invocation came from. All fields are optional — runtime
+/// operations (e.g. the pre-compaction memory flush) may only know some of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub session_id: Option<Uuid>,
+    pub turn_number: Option<i32>,
+    /// Model that proposed the invocation, as reported by `Provider::model_name()`.
+    pub model: Option<String>,
+    /// The provider-assigned `tool_use` block ID.
+    pub tool_use_id: Option<String>,
 }
 
 impl ToolInvocation<Proposed> {
     pub fn new(tool: &str, action: &str, params: serde_json::Value) -> Self {
         Self {
+            id: Uuid::now_v7(),
             tool: tool.to_owned(),
             action: action.to_owned(),
             params,
-            _state: PhantomData,
+            provenance: None,
+            params_digest: None,
+            state: Proposed,
         }
     }
 
+    /// Propose a model's tool call. Params must be a JSON object: a provider
+    /// passes arguments it could not parse through as the raw string, and
+    /// those are refused here, before enforcement ever sees them.
+    pub fn from_tool_call(tool: &str, params: serde_json::Value) -> Result<Self, CherubError> {
+        match params {
+            serde_json::Value::Object(_) => Ok(Self::new(tool, "execute", params)),
+            serde_json::Value::String(_) => Err(CherubError::InvalidInvocation(
+                "tool arguments are not valid JSON".to_owned(),
+            )),
+            _ => Err(CherubError::InvalidInvocation(
+                "tool arguments must be a JSON object".to_owned(),
+            )),
+        }
+    }
+
+    /// Attach provenance to a freshly proposed invocation (builder pattern).
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
     /// Transition to Evaluated state. Only callable within the crate (by enforcement).
+    /// Binds the evaluation to the current params via their digest.
     pub(crate) fn transition(self) -> ToolInvocation<Evaluated> {
+        let digest = params_digest(&self.params);
         ToolInvocation {
+            id: self.id,
             tool: self.tool,
             action: self.action,
             params: self.params,
-            _state: PhantomData,
+            provenance: self.provenance,
+            params_digest: Some(digest),
+            state: Evaluated,
         }
     }
 }
@@ -95,29 +240,250 @@ pub struct ToolContext {
 }
 
 impl ToolInvocation<Evaluated> {
+    /// The id and params digest a capability token for this invocation is bound to.
+    pub(crate) fn binding(&self) -> InvocationBinding {
+        (self.id, self.params_digest)
+    }
+
     /// Execute the tool invocation via the registry. Requires a `CapabilityToken` (consumed on use).
+    ///
+    /// Fails with `NotPermitted` if `params` no longer match the digest taken at evaluation,
+    /// if the token's TTL has elapsed, if the token was minted for another invocation,
+    /// tool, or action, if it violates one of its caveats, or if it was already presented
+    /// to this registry (single use).
+    ///
+    /// A token carrying a `[tools.<name>.retry]` policy re-runs the tool after a
+    /// matching transient failure, with backoff, re-checking the token's TTL and
+    /// caveats before each retry. The last attempt's result is returned.
+    ///
+    /// With `ToolRegistry::with_snapshots`, Act and Commit calls first record
+    /// the workspace (once, not per retry) so `ToolRegistry::rollback` can undo them.
+    /// With `ToolRegistry::with_artifact_tracking`, the files the call changed
+    /// are attached to its result.
     pub async fn execute(
         self,
         token: CapabilityToken,
         registry: &ToolRegistry,
         ctx: &ToolContext,
-    ) -> Result<ToolResult, CherubError> {
+    ) -> Result<ToolInvocation<Executed>, CherubError> {
+        if token.is_expired() {
+            error!(invocation_id = %self.id, tool = %self.tool, "capability token expired");
+            return Err(CherubError::NotPermitted);
+        }
+        if !token.is_bound_to(self.binding()) {
+            error!(invocation_id = %self.id, tool = %self.tool, "capability token minted for another invocation");
+            return Err(CherubError::NotPermitted);
+        }
+        if !token.is_scoped_to(registry.enforcement_name(&self.tool), &self.action) {
+            error!(invocation_id = %self.id, tool = %self.tool, "capability token scoped to another tool or action");
+            return Err(CherubError::NotPermitted);
+        }
+        if self.params_digest != Some(params_digest(&self.params)) {
+            error!(invocation_id = %self.id, tool = %self.tool, "params modified after evaluation");
+            return Err(CherubError::NotPermitted);
+        }
+        token.check_caveats(&self.params)?;
+        registry.spent.spend(&token)?;
+        let started = Instant::now();
+        let (tier, issuance) = (token.tier, token.issuance.clone());
+        if let Some(output) = &registry.dry_run
+            && tier > Tier::Observe
+        {
+            info!(
+                invocation_id = %self.id,
+                tool = %self.tool,
+                action = %self.action,
+                tier = tier.as_str(),
+                "dry run: execution simulated"
+            );
+            let result = ToolResult {
+                output: output.clone(),
+                status: None,
+                artifacts: Vec::new(),
+                attachments: Vec::new(),
+            };
+            return Ok(self.executed(Executed {
+                result,
+                tier,
+                issuance,
+                duration: started.elapsed(),
+                attempts: 0,
+            }));
+        }
+        let root = token.workspace_root.clone().unwrap_or_else(workspace_root);
+        if let Some(snapshots) = &registry.snapshots
+            && token.tier > Tier::Observe
+        {
+            snapshots.snapshot(&root, self.id, &self.tool).await?;
+        }
+        let before = if registry.track_artifacts {
+            Scan::take(&root).await
+        } else {
+            None
+        };
         let tool = registry.find(&self.tool).ok_or_else(|| {
             CherubError::InvalidInvocation(format!("unknown tool: {}", self.tool))
         })?;
-        tool.execute(&self.params, token, ctx).await
+        let mut token = token;
+        // Cap capture up front: by the time output is measured, an Act or
+        // Commit call has already had its effect, and refusing it then would
+        // report a side effect that happened as a denial.
+        let max_output_bytes = token.max_output_bytes();
+        if let Some(max) = max_output_bytes {
+            let limit = &mut token.limits.max_output_bytes;
+            *limit = Some(limit.map_or(max, |l| l.min(max)));
+        }
+        let mut retry = 0;
+        let mut result = loop {
+            // Taken before the attempt consumes the token; `None` once the
+            // policy's attempts are used up (or the tool has no retry policy).
+            let next = token.for_retry();
+            let result = tool.execute(&self.params, token, ctx).await;
+            let Some(next) = next else { break result };
+            let Some(policy) = next.retry.as_ref().filter(|p| p.should_retry(&result)) else {
+                break result;
+            };
+            let delay = policy.delay(retry);
+            warn!(
+                invocation_id = %self.id,
+                tool = %self.tool,
+                attempt = retry + 1,
+                delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+                "transient tool failure, retrying"
+            );
+            tokio::time::sleep(delay).await;
+            // The grant may have lapsed during the attempt or the backoff.
+            if next.is_expired() || next.check_caveats(&self.params).is_err() {
+                error!(invocation_id = %self.id, tool = %self.tool, "capability token no longer fresh, not retrying");
+                break result;
+            }
+            token = next;
+            retry += 1;
+        }?;
+        if let Some(before) = before
+            && let Some(after) = Scan::take(&root).await
+        {
+            result.artifacts = before.diff(&after);
+        }
+        if let Some(max) = max_output_bytes
+            && result.output.len() > max
+        {
+            // In-process tools don't read the limit, and capped tools append
+            // trailers past it.
+            warn!(invocation_id = %self.id, tool = %self.tool, bytes = result.output.len(), max, "tool output truncated to token caveat");
+            truncate_output(&mut result.output, max);
+        }
+        Ok(self.executed(Executed {
+            result,
+            tier,
+            issuance,
+            duration: started.elapsed(),
+            attempts: retry + 1,
+        }))
+    }
+
+    fn executed(self, state: Executed) -> ToolInvocation<Executed> {
+        ToolInvocation {
+            id: self.id,
+            tool: self.tool,
+            action: self.action,
+            params: self.params,
+            provenance: self.provenance,
+            params_digest: self.params_digest,
+            state,
+        }
+    }
+}
+
+impl ToolInvocation<Executed> {
+    /// The tool's result, dropping the invocation and execution metadata.
+    pub fn into_result(self) -> ToolResult {
+        self.state.result
+    }
+
+    /// Pass the result through `f` (e.g. output enforcement), keeping the rest.
+    pub(crate) fn try_map_result(
+        mut self,
+        f: impl FnOnce(ToolResult) -> Result<ToolResult, CherubError>,
+    ) -> Result<Self, CherubError> {
+        self.state.result = f(self.state.result)?;
+        Ok(self)
     }
 }
 
 #[derive(Debug)]
 pub struct ToolResult {
     pub output: String,
+    /// How the spawned process ended, for tools that run one (bash). `None`
+    /// for in-process tools.
+    pub status: Option<ProcessStatus>,
+    /// Files the invocation created, modified, or deleted under the workspace
+    /// root. Filled in by the executor when the registry tracks artifacts;
+    /// empty otherwise.
+    pub artifacts: Vec<Artifact>,
+    /// Binary payloads (images, archives) returned beside `output` rather
+    /// than encoded into it.
+    pub attachments: Vec<Attachment>,
+}
+
+/// Outcome of a process spawned by a tool. `ToolResult::output` is what the
+/// model sees (streams merged, capped, with trailers); this keeps the parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessStatus {
+    /// Exit code; `None` if the process was killed by a signal (including on timeout).
+    pub exit_code: Option<i32>,
+    /// The process exceeded its time limit and its process group was killed.
+    pub timed_out: bool,
+    /// Captured stdout, capped at the tool's output limit.
+    pub stdout: String,
+    /// Captured stderr, capped at the tool's output limit.
+    pub stderr: String,
+    /// Bytes dropped from stdout by the size limit.
+    pub stdout_discarded: u64,
+    /// Bytes dropped from stderr by the size limit.
+    pub stderr_discarded: u64,
+    /// Output bytes dropped by the size limit; the output ends with a marker when nonzero.
+    pub discarded_bytes: u64,
+    /// Wall-clock time from spawn until the process exited or was killed.
+    pub duration: Duration,
+    /// Directory the process ran in.
+    pub cwd: PathBuf,
+}
+
+impl ProcessStatus {
+    /// Whether output was cut at the size limit.
+    pub fn truncated(&self) -> bool {
+        self.discarded_bytes > 0
+    }
+
+    /// Whether stdout was cut at the size limit.
+    pub fn stdout_truncated(&self) -> bool {
+        self.stdout_discarded > 0
+    }
+
+    /// Whether stderr was cut at the size limit.
+    pub fn stderr_truncated(&self) -> bool {
+        self.stderr_discarded > 0
+    }
+
+    /// Exited with code 0 within its time limit.
+    pub fn success(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
 }
 
 /// Enum dispatch for tool implementations. Known variants at compile time.
 pub(crate) enum ToolImpl {
     Bash(BashTool),
     File(FileTool),
+    Git(GitTool),
+    Kubectl(KubectlTool),
+    Patch(PatchTool),
+    PowerShell(PowerShellTool),
+    Script(ScriptTool),
+    Search(SearchTool),
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    Sql(SqlTool),
     #[cfg(feature = "memory")]
     Memory(MemoryTool),
     #[cfg(feature = "credentials")]
@@ -130,6 +496,10 @@ pub(crate) enum ToolImpl {
     DevEnvironment(DevEnvironmentTool),
     #[cfg(feature = "mcp")]
     Mcp(McpToolProxy),
+    #[cfg(any(test, feature = "testing"))]
+    Recording(testing::RecordingTool),
+    #[cfg(any(test, feature = "testing"))]
+    Fake(testing::FakeTool),
 }
 
 impl ToolImpl {
@@ -137,6 +507,14 @@ impl ToolImpl {
         match self {
             Self::Bash(_) => "bash",
             Self::File(_) => "file",
+            Self::Git(_) => "git",
+            Self::Kubectl(_) => "kubectl",
+            Self::Patch(_) => "patch",
+            Self::PowerShell(_) => "powershell",
+            Self::Script(_) => "script",
+            Self::Search(_) => "search",
+            #[cfg(any(feature = "sqlite", feature = "postgres"))]
+            Self::Sql(_) => "sql",
             #[cfg(feature = "memory")]
             Self::Memory(_) => "memory",
             #[cfg(feature = "credentials")]
@@ -149,6 +527,30 @@ impl ToolImpl {
             Self::DevEnvironment(_) => "dev_environment",
             #[cfg(feature = "mcp")]
             Self::Mcp(t) => &t.composite_name,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Recording(t) => &t.name,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Fake(t) => &t.name,
+        }
+    }
+
+    /// How many invocations of this tool `execute_all` runs at once; `None` is
+    /// unlimited. Git serializes on the repository's index lock, and patch
+    /// stages and renames files that a concurrent patch may also touch.
+    fn max_concurrency(&self) -> Option<usize> {
+        match self {
+            Self::Git(_) | Self::Patch(_) => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Policy name the tool is evaluated (and its tokens scoped) under: the
+    /// server name for MCP tools, the tool name otherwise.
+    fn enforcement_name(&self) -> &str {
+        match self {
+            #[cfg(feature = "mcp")]
+            Self::Mcp(t) => &t.server_name,
+            _ => self.name(),
         }
     }
 
@@ -160,9 +562,18 @@ impl ToolImpl {
         // Memory arms use it for provenance; http uses it for user_id; bash ignores it.
         _ctx: &ToolContext,
     ) -> Result<ToolResult, CherubError> {
+        token.check_tool(self.enforcement_name())?;
         match self {
             Self::Bash(tool) => tool.execute(params, token).await,
             Self::File(tool) => tool.execute(params, token).await,
+            Self::Git(tool) => tool.execute(params, token).await,
+            Self::Kubectl(tool) => tool.execute(params, token).await,
+            Self::Patch(tool) => tool.execute(params, token).await,
+            Self::PowerShell(tool) => tool.execute(params, token).await,
+            Self::Script(tool) => tool.execute(params, token).await,
+            Self::Search(tool) => tool.execute(params, token).await,
+            #[cfg(any(feature = "sqlite", feature = "postgres"))]
+            Self::Sql(tool) => tool.execute(params, token).await,
             #[cfg(feature = "memory")]
             Self::Memory(tool) => tool.execute(params, token, _ctx).await,
             #[cfg(feature = "credentials")]
@@ -178,6 +589,10 @@ impl ToolImpl {
                 let _ = token; // Consume the capability token.
                 tool.execute(params).await
             }
+            #[cfg(any(test, feature = "testing"))]
+            Self::Recording(tool) => tool.execute(params, token),
+            #[cfg(any(test, feature = "testing"))]
+            Self::Fake(tool) => tool.execute(),
         }
     }
 
@@ -185,7 +600,8 @@ impl ToolImpl {
         match self {
             Self::Bash(_) => ToolDefinition {
                 name: "bash".to_owned(),
-                description: "Execute a bash command. The command is passed to `bash -c`."
+                description: "Execute a bash command. The command is passed to `bash -c`. \
+                    There is no terminal: editors, pagers, and interactive modes are refused."
                     .to_owned(),
                 input_schema: json!({
                     "type": "object",
@@ -193,6 +609,10 @@ impl ToolImpl {
                         "command": {
                             "type": "string",
                             "description": "The bash command to execute"
+                        },
+                        "stdin": {
+                            "type": "string",
+                            "description": "Text to pipe to the command's stdin (default: none)"
                         }
                     },
                     "required": ["command"]
@@ -200,7 +620,8 @@ impl ToolImpl {
             },
             Self::File(_) => ToolDefinition {
                 name: "file".to_owned(),
-                description: "Read, edit, search, and find files in the workspace. \
+                description: "Read, write, append to, edit, delete, list, search, and find \
+                    files in the workspace. \
                     All paths are relative to the workspace root. \
                     Use this instead of bash for file operations."
                     .to_owned(),
@@ -209,12 +630,16 @@ impl ToolImpl {
                     "properties": {
                         "action": {
                             "type": "string",
-                            "enum": ["read", "edit", "glob", "grep"],
+                            "enum": ["read", "write", "append", "edit", "delete", "list", "glob", "grep"],
                             "description": "Operation to perform"
                         },
                         "path": {
                             "type": "string",
-                            "description": "Relative file path (required for read/edit; optional base dir for glob/grep)"
+                            "description": "Relative file path (required for read/write/append/edit/delete; optional directory for list/glob/grep)"
+                        },
+                        "content": {
+                            "type": "string",
+                            "description": "File content (for write and append)"
                         },
                         "offset": {
                             "type": "integer",
@@ -252,6 +677,235 @@ impl ToolImpl {
                     "required": ["action"]
                 }),
             },
+            Self::Git(_) => ToolDefinition {
+                name: "git".to_owned(),
+                description: "Run git operations on the workspace repository. \
+                    Use this instead of bash for status, diff, add, commit, push, and reset. \
+                    Repository hooks do not run."
+                    .to_owned(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "action": {
+                            "type": "string",
+                            "enum": ["status", "diff", "add", "commit", "push", "reset"],
+                            "description": "Operation to perform"
+                        },
+                        "paths": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Relative paths (required for add; optional filter for diff and reset mode 'unstage')"
+                        },
+                        "staged": {
+                            "type": "boolean",
+                            "description": "Diff the index instead of the working tree (for diff, default false)"
+                        },
+                        "message": {
+                            "type": "string",
+                            "description": "Commit message (required for commit)"
+                        },
+                        "remote": {
+                            "type": "string",
+                            "description": "Remote name (for push, default 'origin')"
+                        },
+                        "branch": {
+                            "type": "string",
+                            "description": "Branch to push (for push, default: git's push.default)"
+                        },
+                        "force": {
+                            "type": "boolean",
+                            "description": "Push with --force-with-lease (for push, default false)"
+                        },
+                        "mode": {
+                            "type": "string",
+                            "enum": ["unstage", "soft", "mixed", "hard"],
+                            "description": "Reset mode (required for reset). 'unstage' removes paths from the index; the others move HEAD to 'target'"
+                        },
+                        "target": {
+                            "type": "string",
+                            "description": "Revision to reset to (for reset, default 'HEAD')"
+                        }
+                    },
+                    "required": ["action"]
+                }),
+            },
+            Self::Patch(_) => ToolDefinition {
+                name: "patch".to_owned(),
+                description: "Apply a unified diff (as produced by `diff -u` or `git diff`) to \
+                    files in the workspace. Files can be modified, created (--- /dev/null), \
+                    deleted (+++ /dev/null), or renamed. Either every hunk applies or nothing \
+                    is changed."
+                    .to_owned(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "patch": {
+                            "type": "string",
+                            "description": "Unified diff; paths relative to the workspace root, optionally prefixed a/ and b/"
+                        }
+                    },
+                    "required": ["patch"]
+                }),
+            },
+            Self::PowerShell(_) => ToolDefinition {
+                name: "powershell".to_owned(),
+                description: "Execute a PowerShell command (or a cmd command, if the host is \
+                    configured for cmd). There is no terminal: editors, pagers, and \
+                    interactive modes are refused."
+                    .to_owned(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "command": {
+                            "type": "string",
+                            "description": "The PowerShell command to execute"
+                        },
+                        "stdin": {
+                            "type": "string",
+                            "description": "Text to pipe to the command's stdin (default: none)"
+                        }
+                    },
+                    "required": ["command"]
+                }),
+            },
+            Self::Script(_) => ToolDefinition {
+                name: "script".to_owned(),
+                description: "Run a short Python or Node.js script in the workspace. \
+                    Output is stdout followed by stderr. \
+                    Scripts that import os, subprocess, or network modules need approval."
+                    .to_owned(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "language": {
+                            "type": "string",
+                            "enum": ["python", "node"],
+                            "description": "Interpreter to run the script with"
+                        },
+                        "code": {
+                            "type": "string",
+                            "description": "Script source"
+                        },
+                        "stdin": {
+                            "type": "string",
+                            "description": "Text to pipe to the script's stdin (default: none)"
+                        }
+                    },
+                    "required": ["language", "code"]
+                }),
+            },
+            Self::Search(_) => ToolDefinition {
+                name: "search".to_owned(),
+                description: "Search file contents in the workspace with a regular expression. \
+                    Returns 'path:line:text' for each match, up to max_results."
+                    .to_owned(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "pattern": {
+                            "type": "string",
+                            "description": "Regular expression to search for"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "Relative file or directory to search (default: the workspace root)"
+                        },
+                        "include": {
+                            "type": "string",
+                            "description": "File name glob filter, e.g. '*.rs'"
+                        },
+                        "ignore_case": {
+                            "type": "boolean",
+                            "description": "Match case-insensitively (default false)"
+                        },
+                        "max_results": {
+                            "type": "integer",
+                            "description": "Max matches to return (default 100, at most 1000)"
+                        }
+                    },
+                    "required": ["pattern"]
+                }),
+            },
+            Self::Kubectl(_) => ToolDefinition {
+                name: "kubectl".to_owned(),
+                description: "Inspect and change Kubernetes resources. \
+                    Use this instead of bash for kubectl. Every action except drain \
+                    runs in the given namespace."
+                    .to_owned(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "action": {
+                            "type": "string",
+                            "enum": ["get", "describe", "logs", "apply", "scale", "delete", "drain"],
+                            "description": "Operation to perform"
+                        },
+                        "namespace": {
+                            "type": "string",
+                            "description": "Namespace (required for all actions except drain)"
+                        },
+                        "resource": {
+                            "type": "string",
+                            "description": "Resource type, e.g. 'pods' or 'deployment' (required for get, describe, scale, delete; optional for logs, default pod)"
+                        },
+                        "name": {
+                            "type": "string",
+                            "description": "Object name (required for logs, scale, delete; optional for get and describe)"
+                        },
+                        "selector": {
+                            "type": "string",
+                            "description": "Label selector, e.g. 'app=web' (for get and describe)"
+                        },
+                        "container": {
+                            "type": "string",
+                            "description": "Container name (for logs)"
+                        },
+                        "tail": {
+                            "type": "integer",
+                            "description": "Log lines to fetch (for logs, default 200)"
+                        },
+                        "previous": {
+                            "type": "boolean",
+                            "description": "Logs of the previous container instance (for logs, default false)"
+                        },
+                        "manifest": {
+                            "type": "string",
+                            "description": "YAML or JSON manifest (required for apply)"
+                        },
+                        "replicas": {
+                            "type": "integer",
+                            "description": "Replica count (required for scale)"
+                        },
+                        "node": {
+                            "type": "string",
+                            "description": "Node to drain (required for drain)"
+                        }
+                    },
+                    "required": ["action"]
+                }),
+            },
+            #[cfg(any(feature = "sqlite", feature = "postgres"))]
+            Self::Sql(_) => ToolDefinition {
+                name: "sql".to_owned(),
+                description: "Run SQL against a database configured by the operator. \
+                    Several statements separated by ';' run in one transaction. \
+                    Results are tab-separated with a header row."
+                    .to_owned(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "database": {
+                            "type": "string",
+                            "description": "Name of the configured database"
+                        },
+                        "query": {
+                            "type": "string",
+                            "description": "SQL to run; no dollar-quoted strings"
+                        }
+                    },
+                    "required": ["database", "query"]
+                }),
+            },
             #[cfg(feature = "memory")]
             Self::Memory(_) => ToolDefinition {
                 name: "memory".to_owned(),
@@ -339,6 +993,13 @@ impl ToolImpl {
             Self::DevEnvironment(_) => dev_environment::tool_definition(),
             #[cfg(feature = "mcp")]
             Self::Mcp(t) => t.definition(),
+            #[cfg(any(test, feature = "testing"))]
+            Self::Recording(testing::RecordingTool { name, .. })
+            | Self::Fake(testing::FakeTool { name, .. }) => ToolDefinition {
+                name: name.clone(),
+                description: "Test double; accepts any parameters.".to_owned(),
+                input_schema: json!({ "type": "object" }),
+            },
         }
     }
 }
@@ -346,8 +1007,15 @@ impl ToolImpl {
 /// Registry of available tools. Provides lookup and schema definitions.
 pub struct ToolRegistry {
     tools: Vec<ToolImpl>,
+    spent: SpentTokens,      // Nonces of tokens already presented to `execute()`
+    dry_run: Option<String>, // Synthetic output for Act/Commit executions, if set
+    snapshots: Option<Snapshots>, // Workspace states recorded before Act/Commit executions
+    track_artifacts: bool,   // Diff the workspace around every execution
 }
 
+/// Default synthetic output for `ToolRegistry::with_dry_run`.
+pub const DRY_RUN_OUTPUT: &str = "[dry run: action recorded but not executed]";
+
 /// Returns the workspace root directory (current working directory).
 fn workspace_root() -> std::path::PathBuf {
     std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
@@ -360,7 +1028,15 @@ impl ToolRegistry {
             tools: vec![
                 ToolImpl::Bash(BashTool::new()),
                 ToolImpl::File(FileTool::new(workspace_root())),
+                ToolImpl::Git(GitTool::new(workspace_root())),
+                ToolImpl::Patch(PatchTool::new(workspace_root())),
+                ToolImpl::Script(ScriptTool::new(workspace_root())),
+                ToolImpl::Search(SearchTool::new(workspace_root())),
             ],
+            spent: SpentTokens::default(),
+            dry_run: None,
+            snapshots: None,
+            track_artifacts: false,
         }
     }
 
@@ -370,7 +1046,17 @@ impl ToolRegistry {
     /// (registered later via `with_container()`).
     pub fn new_without_bash() -> Self {
         Self {
-            tools: vec![ToolImpl::File(FileTool::new(workspace_root()))],
+            tools: vec![
+                ToolImpl::File(FileTool::new(workspace_root())),
+                ToolImpl::Git(GitTool::new(workspace_root())),
+                ToolImpl::Patch(PatchTool::new(workspace_root())),
+                ToolImpl::Script(ScriptTool::new(workspace_root())),
+                ToolImpl::Search(SearchTool::new(workspace_root())),
+            ],
+            spent: SpentTokens::default(),
+            dry_run: None,
+            snapshots: None,
+            track_artifacts: false,
         }
     }
 
@@ -381,8 +1067,16 @@ impl ToolRegistry {
             tools: vec![
                 ToolImpl::Bash(BashTool::new()),
                 ToolImpl::File(FileTool::new(workspace_root())),
+                ToolImpl::Git(GitTool::new(workspace_root())),
+                ToolImpl::Patch(PatchTool::new(workspace_root())),
+                ToolImpl::Script(ScriptTool::new(workspace_root())),
+                ToolImpl::Search(SearchTool::new(workspace_root())),
                 ToolImpl::Memory(MemoryTool::new(store)),
             ],
+            spent: SpentTokens::default(),
+            dry_run: None,
+            snapshots: None,
+            track_artifacts: false,
         }
     }
 
@@ -394,11 +1088,26 @@ impl ToolRegistry {
         Self {
             tools: vec![
                 ToolImpl::File(FileTool::new(workspace_root())),
+                ToolImpl::Git(GitTool::new(workspace_root())),
+                ToolImpl::Patch(PatchTool::new(workspace_root())),
+                ToolImpl::Script(ScriptTool::new(workspace_root())),
+                ToolImpl::Search(SearchTool::new(workspace_root())),
                 ToolImpl::Memory(MemoryTool::new(store)),
             ],
+            spent: SpentTokens::default(),
+            dry_run: None,
+            snapshots: None,
+            track_artifacts: false,
         }
     }
 
+    /// Add the PowerShell tool (consumes and returns self). Registered on
+    /// Windows hosts, where it is the native shell.
+    pub fn with_powershell(mut self) -> Self {
+        self.tools.push(ToolImpl::PowerShell(PowerShellTool::new()));
+        self
+    }
+
     /// Add the HTTP tool to an existing registry (consumes and returns self).
     ///
     /// The `CredentialBroker` is shared between the tool and the registry.
@@ -447,6 +1156,109 @@ impl ToolRegistry {
         self
     }
 
+    /// Register a `RecordingTool`, replacing any tool of the same name
+    /// (builder pattern).
+    #[cfg(any(test, feature = "testing"))]
+    pub fn with_recording(mut self, tool: testing::RecordingTool) -> Self {
+        self.tools.retain(|t| t.name() != tool.name);
+        self.tools.push(ToolImpl::Recording(tool));
+        self
+    }
+
+    /// Register a `FakeTool`, replacing any tool of the same name (builder
+    /// pattern).
+    #[cfg(any(test, feature = "testing"))]
+    pub fn with_fake(mut self, tool: testing::FakeTool) -> Self {
+        self.tools.retain(|t| t.name() != tool.name);
+        self.tools.push(ToolImpl::Fake(tool));
+        self
+    }
+
+    /// Add the kubectl tool to the registry (builder pattern). It talks to the
+    /// cluster the agent's kubeconfig selects.
+    pub fn with_kubectl(mut self) -> Self {
+        self.tools
+            .push(ToolImpl::Kubectl(KubectlTool::new(workspace_root())));
+        self
+    }
+
+    /// Add the SQL tool to the registry (builder pattern). Its databases come
+    /// from the policy's `[tools.sql.databases]`.
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    pub fn with_sql(mut self) -> Self {
+        self.tools
+            .push(ToolImpl::Sql(SqlTool::new(workspace_root())));
+        self
+    }
+
+    /// Simulate Act and Commit executions (builder pattern).
+    ///
+    /// Invocations are still evaluated, approved, and token-checked as usual,
+    /// but instead of running, the tool call is logged and `output` returned
+    /// as its result. Observe-tier calls run normally, so the agent can keep
+    /// reading while a new policy is tried against it.
+    pub fn with_dry_run(mut self, output: impl Into<String>) -> Self {
+        self.dry_run = Some(output.into());
+        self
+    }
+
+    /// Snapshot the workspace before every Act or Commit execution, keeping the
+    /// snapshots in `store` (a directory outside the workspace), so that
+    /// `rollback()` can undo the agent's file changes. A failed snapshot
+    /// fails the call rather than run it unprotected.
+    pub fn with_snapshots(mut self, store: PathBuf) -> Self {
+        self.snapshots = Some(Snapshots::new(store));
+        self
+    }
+
+    /// Report the files each execution created, modified, or deleted under the
+    /// workspace root in `ToolResult.artifacts` (see `artifacts`).
+    pub fn with_artifact_tracking(mut self) -> Self {
+        self.track_artifacts = true;
+        self
+    }
+
+    /// Restore the workspace to the newest snapshot that differs from its
+    /// current state. `None` if snapshots are off or there is nothing to undo.
+    pub async fn rollback(&self) -> Result<Option<Snapshot>, CherubError> {
+        match &self.snapshots {
+            Some(snapshots) => snapshots.rollback().await,
+            None => Ok(None),
+        }
+    }
+
+    /// Execute independent invocations concurrently; results come back in
+    /// input order.
+    ///
+    /// Each call goes through `ToolInvocation::execute` with its own token, so
+    /// every check still applies and one failure does not affect the others.
+    /// Calls to a tool with a concurrency limit wait for a free slot. Whether
+    /// the calls are independent (no call reads what another writes) is the
+    /// caller's judgement.
+    pub async fn execute_all(
+        &self,
+        calls: Vec<(ToolInvocation<Evaluated>, CapabilityToken)>,
+        ctx: &ToolContext,
+    ) -> Vec<Result<ToolInvocation<Executed>, CherubError>> {
+        let limits: HashMap<&str, Semaphore> = self
+            .tools
+            .iter()
+            .filter_map(|t| Some((t.name(), Semaphore::new(t.max_concurrency()?))))
+            .collect();
+        let runs = calls.into_iter().map(|(invocation, token)| {
+            let limit = limits.get(invocation.tool.as_str());
+            async move {
+                // The semaphores are never closed, so acquiring cannot fail.
+                let _permit = match limit {
+                    Some(slots) => slots.acquire().await.ok(),
+                    None => None,
+                };
+                invocation.execute(token, self, ctx).await
+            }
+        });
+        join_all(runs).await
+    }
+
     pub(crate) fn find(&self, name: &str) -> Option<&ToolImpl> {
         self.tools.iter().find(|t| t.name() == name)
     }
@@ -456,11 +1268,8 @@ impl ToolRegistry {
     /// For MCP tools, returns the server name (e.g., "google-workspace").
     /// For all other tools, returns the tool name as-is.
     pub fn enforcement_name<'a>(&'a self, tool_name: &'a str) -> &'a str {
-        match self.find(tool_name) {
-            #[cfg(feature = "mcp")]
-            Some(ToolImpl::Mcp(t)) => &t.server_name,
-            _ => tool_name,
-        }
+        self.find(tool_name)
+            .map_or(tool_name, |tool| tool.enforcement_name())
     }
 
     /// Enrich params with MCP enforcement metadata.
@@ -494,9 +1303,461 @@ impl ToolRegistry {
     }
 }
 
+/// Extension point for tool implementations. Not used for known variants —
+/// enum dispatch via `ToolImpl` is preferred. Reserved for future external plugins.
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn execute(
+        &self,
+        action: &str,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError>;
+}
+
+/// Cut `output` to at most `max` bytes, ending in a truncation marker when
+/// there is room for one.
+fn truncate_output(output: &mut String, max: usize) {
+    const MARKER: &str = "\n[output truncated]";
+    let keep = if max > MARKER.len() {
+        max - MARKER.len()
+    } else {
+        max
+    };
+    output.truncate(bash::floor_char_boundary(output, keep));
+    if output.len() + MARKER.len() <= max {
+        output.push_str(MARKER);
+    }
+}
+
+/// Run `f` on the blocking pool. Dropping the returned future (a cancelled
+/// turn) does not stop the task, so it raises the flag `f` is handed; `f`
+/// calls [`check_cancelled`] before it changes anything on disk.
+pub(crate) async fn spawn_cancellable<T, F>(what: &str, f: F) -> Result<T, CherubError>
+where
+    T: Send + 'static,
+    F: FnOnce(&AtomicBool) -> Result<T, CherubError> + Send + 'static,
+{
+    struct RaiseOnDrop(Arc<AtomicBool>);
+    impl Drop for RaiseOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _raise = RaiseOnDrop(Arc::clone(&cancelled));
+    tokio::task::spawn_blocking(move || f(&cancelled))
+        .await
+        .map_err(|e| CherubError::ToolExecution(format!("{what} failed: {e}")))?
+}
+
+/// `Err(Cancelled)` once the future awaiting a [`spawn_cancellable`] task
+/// has been dropped.
+pub(crate) fn check_cancelled(cancelled: &AtomicBool) -> Result<(), CherubError> {
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(CherubError::Cancelled {
+            partial: String::new(),
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
+    use crate::enforcement::policy::Policy;
+
+    fn approve(tier: Tier, evaluated: &ToolInvocation<Evaluated>) -> CapabilityToken {
+        let policy = Policy::from_str("[tools]\n").unwrap();
+        crate::enforcement::approve_escalation(tier, evaluated, &policy)
+    }
+
+    fn test_ctx() -> ToolContext {
+        ToolContext {
+            user_id: "test".to_owned(),
+            session_id: Uuid::now_v7(),
+            turn_number: 0,
+        }
+    }
+
+    #[test]
+    fn transition_preserves_id_and_provenance() {
+        let provenance = Provenance {
+            session_id: Some(Uuid::now_v7()),
+            turn_number: Some(3),
+            model: Some("claude-sonnet-4-20250514".to_owned()),
+            tool_use_id: Some("toolu_01".to_owned()),
+        };
+        let proposal = ToolInvocation::new("bash", "execute", json!({"command": "ls"}))
+            .with_provenance(provenance.clone());
+        let id = proposal.id;
+
+        let evaluated = proposal.transition();
+        assert_eq!(evaluated.id, id);
+        assert_eq!(evaluated.provenance, Some(provenance));
+    }
+
+    #[tokio::test]
+    async fn execute_rejects_params_mutated_after_evaluation() {
+        let registry = ToolRegistry::new();
+        let ctx = test_ctx();
+        let mut evaluated =
+            ToolInvocation::new("bash", "execute", json!({"command": "echo hi"})).transition();
+
+        let token = approve(Tier::Observe, &evaluated);
+        evaluated.params = json!({"command": "rm -rf /tmp/x"});
+        let result = evaluated.execute(token, &registry, &ctx).await;
+        assert!(matches!(result, Err(CherubError::NotPermitted)));
+    }
+
+    #[tokio::test]
+    async fn execute_rejects_token_for_other_invocation() {
+        let ctx = test_ctx();
+        let approved =
+            ToolInvocation::new("bash", "execute", json!({"command": "ls /tmp"})).transition();
+        let other = ToolInvocation::new("bash", "execute", json!({"command": "rm -rf /tmp/x"}))
+            .transition();
+
+        let token = approve(Tier::Commit, &approved);
+        let result = other.execute(token, &ToolRegistry::new(), &ctx).await;
+        assert!(matches!(result, Err(CherubError::NotPermitted)));
+    }
+
+    #[tokio::test]
+    async fn execute_rejects_expired_token() {
+        use crate::enforcement::{self, Decision, policy::Policy};
+        use std::str::FromStr;
+
+        let policy = Policy::from_str(
+            "token_ttl_secs = 1\n\n[tools.bash]\nenabled = true\n\n[tools.bash.actions.read]\ntier = \"observe\"\npatterns = [\"^echo \"]\n",
+        )
+        .unwrap();
+        let proposal = ToolInvocation::new("bash", "execute", json!({"command": "echo hi"}));
+        let (evaluated, decision) = enforcement::evaluate(proposal, &policy, None);
+        let Decision::Allow(token) = decision else {
+            panic!("expected Allow");
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+        let ctx = test_ctx();
+        let result = evaluated.execute(token, &ToolRegistry::new(), &ctx).await;
+        assert!(matches!(result, Err(CherubError::NotPermitted)));
+    }
+
+    #[tokio::test]
+    async fn execute_rejects_token_scoped_to_another_tool() {
+        let ctx = test_ctx();
+        let mut evaluated =
+            ToolInvocation::new("bash", "execute", json!({"action": "read", "path": "x"}))
+                .transition();
+        let token = approve(Tier::Observe, &evaluated);
+        // Same id and params, retargeted at another tool.
+        evaluated.tool = "file".to_owned();
+        let result = evaluated.execute(token, &ToolRegistry::new(), &ctx).await;
+        assert!(matches!(result, Err(CherubError::NotPermitted)));
+    }
+
+    #[tokio::test]
+    async fn execute_enforces_output_caveat() {
+        use crate::enforcement::capability::Caveat;
+        let ctx = test_ctx();
+        let registry = ToolRegistry::new();
+        let run = |max: usize| {
+            let evaluated =
+                ToolInvocation::new("bash", "execute", json!({"command": "printf '%064d' 0"}))
+                    .transition();
+            let token = approve(Tier::Observe, &evaluated).with_caveat(Caveat::MaxOutputBytes(max));
+            (evaluated, token)
+        };
+        let (evaluated, token) = run(1024);
+        let output = evaluated
+            .execute(token, &registry, &ctx)
+            .await
+            .unwrap()
+            .into_result()
+            .output;
+        assert_eq!(output, "0".repeat(64));
+
+        // Over the cap the call still succeeds, with its output cut short.
+        let (evaluated, token) = run(32);
+        let output = evaluated
+            .execute(token, &registry, &ctx)
+            .await
+            .unwrap()
+            .into_result()
+            .output;
+        assert!(output.len() <= 32, "{output:?}");
+        assert!(output.ends_with("[output truncated]"), "{output:?}");
+        let (evaluated, token) = run(2);
+        let output = evaluated
+            .execute(token, &registry, &ctx)
+            .await
+            .unwrap()
+            .into_result()
+            .output;
+        assert_eq!(output, "00");
+    }
+
+    #[tokio::test]
+    async fn execute_retries_transient_exit_codes() {
+        let ctx = test_ctx();
+        let dir = tempfile::tempdir().unwrap();
+        let count = dir.path().join("count");
+        // Exits 75 (EX_TEMPFAIL) until its third run.
+        let command = format!(
+            "n=$(cat {0} 2>/dev/null || echo 0); n=$((n+1)); echo $n > {0}; \
+             [ $n -ge 3 ] || exit 75; echo done",
+            count.display()
+        );
+        let run = |max_attempts: u32| {
+            let policy = Policy::from_str(&format!(
+                "[tools.bash]\nenabled = true\n\n[tools.bash.retry]\n\
+                 max_attempts = {max_attempts}\nbackoff_ms = 0\nexit_codes = [75]\n"
+            ))
+            .unwrap();
+            let evaluated =
+                ToolInvocation::new("bash", "execute", json!({"command": command})).transition();
+            let token = crate::enforcement::approve_escalation(Tier::Act, &evaluated, &policy);
+            (evaluated, token)
+        };
+        let registry = ToolRegistry::new();
+
+        let (evaluated, token) = run(2);
+        let result = evaluated
+            .execute(token, &registry, &ctx)
+            .await
+            .unwrap()
+            .into_result();
+        assert_eq!(result.status.unwrap().exit_code, Some(75));
+        assert_eq!(std::fs::read_to_string(&count).unwrap().trim(), "2");
+
+        std::fs::remove_file(&count).unwrap();
+        let (evaluated, token) = run(3);
+        let executed = evaluated.execute(token, &registry, &ctx).await.unwrap();
+        assert_eq!(executed.state.attempts, 3);
+        assert_eq!(executed.state.tier, Tier::Act);
+        let result = executed.into_result();
+        assert!(result.output.contains("done"), "{}", result.output);
+        assert_eq!(std::fs::read_to_string(&count).unwrap().trim(), "3");
+    }
+
+    #[tokio::test]
+    async fn act_execution_can_be_rolled_back() {
+        let ctx = test_ctx();
+        let store = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let policy = Policy::from_str(&format!(
+            "[tools.bash]\nenabled = true\n\n[workspace]\nroot = \"{}\"\n",
+            dir.path().display()
+        ))
+        .unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "draft").unwrap();
+        let registry = ToolRegistry::new().with_snapshots(store.path().join("snapshots"));
+
+        let run = |tier: Tier, command: &str| {
+            let evaluated =
+                ToolInvocation::new("bash", "execute", json!({"command": command})).transition();
+            let token = crate::enforcement::approve_escalation(tier, &evaluated, &policy);
+            (evaluated, token)
+        };
+        let (evaluated, token) = run(Tier::Act, "echo gone > notes.txt && touch extra");
+        let id = evaluated.id;
+        evaluated.execute(token, &registry, &ctx).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "gone\n");
+
+        // Observe calls are not snapshotted.
+        let (evaluated, token) = run(Tier::Observe, "cat notes.txt");
+        evaluated.execute(token, &registry, &ctx).await.unwrap();
+
+        let snapshot = registry.rollback().await.unwrap().unwrap();
+        assert_eq!(snapshot.invocation_id, id);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "draft");
+        assert!(!dir.path().join("extra").exists());
+        assert_eq!(registry.rollback().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn tracked_execution_reports_changed_files() {
+        let ctx = test_ctx();
+        let dir = tempfile::tempdir().unwrap();
+        let policy = Policy::from_str(&format!(
+            "[tools.bash]\nenabled = true\n\n[workspace]\nroot = \"{}\"\n",
+            dir.path().display()
+        ))
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "draft").unwrap();
+        std::fs::write(dir.path().join("old.txt"), "x").unwrap();
+        let registry = ToolRegistry::new().with_artifact_tracking();
+
+        let evaluated = ToolInvocation::new(
+            "bash",
+            "execute",
+            json!({"command": "echo longer draft > notes.txt && rm old.txt && touch new.txt"}),
+        )
+        .transition();
+        let token = crate::enforcement::approve_escalation(Tier::Act, &evaluated, &policy);
+        let result = evaluated
+            .execute(token, &registry, &ctx)
+            .await
+            .unwrap()
+            .into_result();
+        assert_eq!(
+            Artifact::summary(&result.artifacts),
+            "created new.txt, modified notes.txt, deleted old.txt"
+        );
+
+        // Untracked registries report nothing.
+        let evaluated =
+            ToolInvocation::new("bash", "execute", json!({"command": "touch other.txt"}))
+                .transition();
+        let token = crate::enforcement::approve_escalation(Tier::Act, &evaluated, &policy);
+        let result = evaluated
+            .execute(token, &ToolRegistry::new(), &ctx)
+            .await
+            .unwrap()
+            .into_result();
+        assert!(result.artifacts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn dry_run_simulates_act_and_commit() {
+        let ctx = test_ctx();
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("marker");
+        let registry = ToolRegistry::new().with_dry_run(DRY_RUN_OUTPUT);
+        let command = format!("echo hi > {}", marker.display());
+        for tier in [Tier::Act, Tier::Commit] {
+            let evaluated =
+                ToolInvocation::new("bash", "execute", json!({"command": command})).transition();
+            let token = approve(tier, &evaluated);
+            let result = evaluated
+                .execute(token, &registry, &ctx)
+                .await
+                .unwrap()
+                .into_result();
+            assert_eq!(result.output, DRY_RUN_OUTPUT);
+            assert!(!marker.exists());
+        }
+
+        // Observe-tier calls still run.
+        let evaluated =
+            ToolInvocation::new("bash", "execute", json!({"command": "echo hi"})).transition();
+        let token = approve(Tier::Observe, &evaluated);
+        let result = evaluated
+            .execute(token, &registry, &ctx)
+            .await
+            .unwrap()
+            .into_result();
+        assert_eq!(result.output.trim(), "hi");
+    }
+
+    #[tokio::test]
+    async fn execute_all_runs_concurrently_in_order() {
+        let ctx = test_ctx();
+        let registry = ToolRegistry::new();
+        let mut calls: Vec<_> = (0..3)
+            .map(|i| {
+                let command = format!("sleep 0.5; echo {i}");
+                let evaluated = ToolInvocation::new("bash", "execute", json!({"command": command}))
+                    .transition();
+                let token = approve(Tier::Observe, &evaluated);
+                (evaluated, token)
+            })
+            .collect();
+        // A token for another invocation fails on its own.
+        let (_, stray) = calls.remove(1);
+        let other = ToolInvocation::new("bash", "execute", json!({"command": "true"})).transition();
+        calls.insert(1, (other, stray));
+
+        let start = std::time::Instant::now();
+        let results = registry.execute_all(calls, &ctx).await;
+        assert!(start.elapsed() < Duration::from_millis(1200));
+        assert_eq!(results[0].as_ref().unwrap().state.result.output.trim(), "0");
+        assert!(matches!(results[1], Err(CherubError::NotPermitted)));
+        assert_eq!(results[2].as_ref().unwrap().state.result.output.trim(), "2");
+    }
+
+    #[tokio::test]
+    async fn execute_rejects_replayed_token() {
+        let ctx = test_ctx();
+        let registry = ToolRegistry::new();
+        let key = [3; 32];
+        let evaluated =
+            ToolInvocation::new("bash", "execute", json!({"command": "true"})).transition();
+        let sealed = approve(Tier::Observe, &evaluated).seal(&key);
+
+        // The same invocation presented again, e.g. by a tool re-entering dispatch.
+        let replay = ToolInvocation {
+            id: evaluated.id,
+            tool: evaluated.tool.clone(),
+            action: evaluated.action.clone(),
+            params: evaluated.params.clone(),
+            provenance: None,
+            params_digest: evaluated.params_digest,
+            state: Evaluated,
+        };
+        let first = CapabilityToken::unseal(&sealed, &key).unwrap();
+        let second = CapabilityToken::unseal(&sealed, &key).unwrap();
+        assert!(evaluated.execute(first, &registry, &ctx).await.is_ok());
+        let result = replay.execute(second, &registry, &ctx).await;
+        assert!(matches!(result, Err(CherubError::NotPermitted)));
+    }
+
+    #[test]
+    fn transition_binds_params_digest() {
+        let params = json!({"command": "ls", "args": ["-la"]});
+        let evaluated = ToolInvocation::new("bash", "execute", params.clone()).transition();
+        assert_eq!(evaluated.params_digest, Some(params_digest(&params)));
+        assert_ne!(
+            params_digest(&params),
+            params_digest(&json!({"command": "ls", "args": ["-l"]}))
+        );
+    }
+
+    #[test]
+    fn invocation_serde_round_trip_yields_proposal() {
+        let evaluated = ToolInvocation::new("bash", "execute", json!({"command": "ls"}))
+            .with_provenance(Provenance {
+                turn_number: Some(2),
+                ..Provenance::default()
+            })
+            .transition();
+        let json = serde_json::to_value(&evaluated).unwrap();
+        assert!(json.get("params_digest").is_none());
+
+        let proposal: ToolInvocation<Proposed> = serde_json::from_value(json).unwrap();
+        assert_eq!(proposal.id, evaluated.id);
+        assert_eq!(proposal.params, evaluated.params);
+        assert_eq!(proposal.provenance, evaluated.provenance);
+        assert!(proposal.params_digest.is_none());
+    }
+
+    #[test]
+    fn tool_call_params_must_be_object() {
+        let proposal = ToolInvocation::from_tool_call("bash", json!({"command": "ls"})).unwrap();
+        assert_eq!(proposal.action, "execute");
+        assert!(matches!(
+            ToolInvocation::from_tool_call("bash", json!("{\"command\":")),
+            Err(CherubError::InvalidInvocation(msg)) if msg.contains("not valid JSON")
+        ));
+        assert!(matches!(
+            ToolInvocation::from_tool_call("bash", serde_json::Value::Null),
+            Err(CherubError::InvalidInvocation(_))
+        ));
+    }
+
+    #[test]
+    fn invocation_ids_are_unique() {
+        let a = ToolInvocation::new("bash", "execute", json!({}));
+        let b = ToolInvocation::new("bash", "execute", json!({}));
+        assert_ne!(a.id, b.id);
+        assert!(a.provenance.is_none());
+    }
 
     #[test]
     fn enforcement_name_non_mcp_returns_tool_name() {
@@ -521,17 +1782,25 @@ mod tests {
         assert!(enriched.get("__mcp_server").is_none());
         assert!(enriched.get("__mcp_tool").is_none());
     }
-}
 
-/// Extension point for tool implementations. Not used for known variants —
-/// enum dispatch via `ToolImpl` is preferred. Reserved for future external plugins.
-pub trait Tool: Send + Sync {
-    fn name(&self) -> &str;
-
-    fn execute(
-        &self,
-        action: &str,
-        params: &serde_json::Value,
-        token: CapabilityToken,
-    ) -> Result<ToolResult, CherubError>;
+    #[tokio::test]
+    async fn dropped_blocking_task_sees_cancellation() {
+        let (start_tx, start_rx) = std::sync::mpsc::channel();
+        let (seen_tx, seen_rx) = std::sync::mpsc::channel();
+        let task = spawn_cancellable("test task", move |cancelled| {
+            start_rx.recv().unwrap();
+            seen_tx.send(check_cancelled(cancelled).is_err()).unwrap();
+            Ok(())
+        });
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), task)
+                .await
+                .is_err()
+        );
+        start_tx.send(()).unwrap();
+        let seen = tokio::task::spawn_blocking(move || seen_rx.recv().unwrap())
+            .await
+            .unwrap();
+        assert!(seen);
+    }
 }
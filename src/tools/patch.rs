@@ -0,0 +1,620 @@
+//! Patch tool: applies a unified diff to files in the workspace.
+//!
+//! Accepts the output of `diff -u` or `git diff` in `params["patch"]`. Each
+//! file section may modify, create (`--- /dev/null`), delete (`+++ /dev/null`),
+//! or rename a file; `a/` and `b/` prefixes are stripped as git writes them.
+//! Every path must resolve inside the workspace.
+//!
+//! Application is all-or-nothing: every hunk of every file is matched in memory
+//! first (at its stated line, else the nearest exact match), then the new
+//! contents are staged next to their targets and renamed into place. A patch
+//! that does not apply cleanly changes nothing.
+//!
+//! Uses `MatchSource::Patch` for enforcement — one `"{op}:{path}"` action string
+//! per touched file (`create`, `modify`, or `delete`; a rename is a delete plus
+//! a create), so policy tiers patches by path prefix like the file tool.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+use tracing::{info_span, warn};
+use uuid::Uuid;
+
+use crate::enforcement::capability::CapabilityToken;
+use crate::error::CherubError;
+use crate::tools::path::{is_binary_content, is_safe_relative_path, resolve_workspace_path};
+use crate::tools::{ToolResult, check_cancelled, spawn_cancellable};
+
+pub struct PatchTool {
+    workspace_root: PathBuf,
+}
+
+/// One file section of a unified diff.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct FilePatch {
+    old_path: Option<String>, // None for `/dev/null` (creation)
+    new_path: Option<String>, // None for `/dev/null` (deletion)
+    hunks: Vec<Hunk>,
+}
+
+/// One `@@` hunk. Lines keep their `\n` unless the diff marks them
+/// `\ No newline at end of file`.
+#[derive(Debug, PartialEq, Eq)]
+struct Hunk {
+    old_start: usize,
+    old: Vec<String>, // Context and removed lines
+    new: Vec<String>, // Context and added lines
+}
+
+/// A file's new state, computed before anything is written.
+struct Change {
+    write: Option<(PathBuf, String)>,
+    remove: Option<PathBuf>,
+    summary: String,
+}
+
+impl PatchTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self { workspace_root }
+    }
+
+    pub async fn execute(
+        &self,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError> {
+        token.check_tool("patch")?;
+        let diff = params
+            .get("patch")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                CherubError::InvalidInvocation("patch tool requires 'patch'".to_owned())
+            })?;
+        let files = parse(diff).map_err(CherubError::InvalidInvocation)?;
+
+        // A policy `[workspace]` root on the token takes precedence.
+        let root = token
+            .workspace_root
+            .clone()
+            .unwrap_or_else(|| self.workspace_root.clone());
+        spawn_cancellable("patch task", move |cancelled| {
+            apply_all(&root, &files, cancelled)
+        })
+        .await
+    }
+}
+
+impl FilePatch {
+    /// Action strings for enforcement: `"{op}:{path}"` per touched path.
+    pub(crate) fn actions(&self) -> Vec<String> {
+        match (&self.old_path, &self.new_path) {
+            (None, Some(new)) => vec![format!("create:{new}")],
+            (Some(old), None) => vec![format!("delete:{old}")],
+            (Some(old), Some(new)) if old == new => vec![format!("modify:{new}")],
+            (Some(old), Some(new)) => vec![format!("delete:{old}"), format!("create:{new}")],
+            (None, None) => Vec::new(), // Rejected by `parse`
+        }
+    }
+
+    fn display_path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// Parse a unified diff into its file sections. Text outside file sections
+/// (`diff --git`, `index`, commit messages) is ignored.
+pub(crate) fn parse(diff: &str) -> Result<Vec<FilePatch>, String> {
+    // Split on '\n' only: a CRLF file's diff lines keep their '\r'.
+    let mut lines = diff
+        .strip_suffix('\n')
+        .unwrap_or(diff)
+        .split('\n')
+        .peekable();
+    let mut files = Vec::new();
+    while let Some(line) = lines.next() {
+        let Some(old) = line.strip_prefix("--- ") else {
+            continue;
+        };
+        let new = lines
+            .next()
+            .and_then(|l| l.strip_prefix("+++ "))
+            .ok_or_else(|| "expected a '+++' line after '---'".to_owned())?;
+        let (old_path, new_path) = header_paths(old, new)?;
+
+        let mut hunks = Vec::new();
+        while let Some(header) = lines.next_if(|l| l.starts_with("@@ ")) {
+            hunks.push(parse_hunk(header, &mut lines)?);
+        }
+        let patch = FilePatch {
+            old_path,
+            new_path,
+            hunks,
+        };
+        if patch.hunks.is_empty() {
+            return Err(format!("no hunks for '{}'", patch.display_path()));
+        }
+        files.push(patch);
+    }
+    if files.is_empty() {
+        return Err("patch contains no file changes".to_owned());
+    }
+    Ok(files)
+}
+
+/// Paths from the `---`/`+++` headers, with timestamps and git's `a/`/`b/`
+/// prefixes removed. `/dev/null` is `None`.
+fn header_paths(old: &str, new: &str) -> Result<(Option<String>, Option<String>), String> {
+    let path = |header: &str| {
+        let path = header.split('\t').next().unwrap_or_default().trim_end();
+        (path != "/dev/null").then(|| path.to_owned())
+    };
+    let (mut old, mut new) = (path(old), path(new));
+    let prefixed =
+        |p: &Option<String>, prefix: &str| p.as_ref().is_none_or(|p| p.starts_with(prefix));
+    if (old.is_some() || new.is_some()) && prefixed(&old, "a/") && prefixed(&new, "b/") {
+        old = old.map(|p| p[2..].to_owned());
+        new = new.map(|p| p[2..].to_owned());
+    }
+    if old.is_none() && new.is_none() {
+        return Err("file header names no file".to_owned());
+    }
+    if let Some(p) = [&old, &new]
+        .into_iter()
+        .flatten()
+        .find(|p| !is_safe_relative_path(p))
+    {
+        return Err(format!(
+            "'{p}': path must be relative and must not contain '..' or null bytes"
+        ));
+    }
+    Ok((old, new))
+}
+
+/// Parse one hunk, consuming its body lines from `lines`.
+fn parse_hunk<'a>(
+    header: &str,
+    lines: &mut std::iter::Peekable<impl Iterator<Item = &'a str>>,
+) -> Result<Hunk, String> {
+    let (old_start, old_count, new_count) =
+        parse_range(header).ok_or_else(|| format!("malformed hunk header '{header}'"))?;
+    let mut hunk = Hunk {
+        old_start,
+        old: Vec::new(),
+        new: Vec::new(),
+    };
+    // Which side(s) the previous line went to, for `\ No newline at end of file`.
+    let mut last = (false, false);
+    while hunk.old.len() < old_count || hunk.new.len() < new_count {
+        let line = lines
+            .next()
+            .ok_or_else(|| format!("hunk '{header}' ends early"))?;
+        // Some editors strip the space from blank context lines.
+        let (tag, text) = match line {
+            "" => (" ", ""),
+            _ => line.split_at_checked(1).unwrap_or(("", line)),
+        };
+        last = match tag {
+            " " => (true, true),
+            "-" => (true, false),
+            "+" => (false, true),
+            "\\" => {
+                strip_newline(&mut hunk, last);
+                continue;
+            }
+            _ => return Err(format!("unexpected line in hunk '{header}': '{line}'")),
+        };
+        if last.0 {
+            hunk.old.push(format!("{text}\n"));
+        }
+        if last.1 {
+            hunk.new.push(format!("{text}\n"));
+        }
+    }
+    if lines.next_if(|l| l.starts_with('\\')).is_some() {
+        strip_newline(&mut hunk, last);
+    }
+    if hunk.old.len() != old_count || hunk.new.len() != new_count {
+        return Err(format!("hunk '{header}' line counts do not match its body"));
+    }
+    Ok(hunk)
+}
+
+fn strip_newline(hunk: &mut Hunk, (old, new): (bool, bool)) {
+    for (side, lines) in [(old, &mut hunk.old), (new, &mut hunk.new)] {
+        if let Some(line) = lines.last_mut().filter(|_| side) {
+            line.pop();
+        }
+    }
+}
+
+/// `@@ -start,count +start,count @@` → (old start, old count, new count).
+/// An omitted count is 1.
+fn parse_range(header: &str) -> Option<(usize, usize, usize)> {
+    let (ranges, _) = header.strip_prefix("@@ -")?.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |s: &str| -> Option<(usize, usize)> {
+        match s.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((s.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = range(old)?;
+    let (_, new_count) = range(new)?;
+    Some((old_start, old_count, new_count))
+}
+
+/// Apply `hunks` in order to `content`. Each hunk is placed at its stated line
+/// if the old lines match there, else at the nearest exact match after the
+/// previous hunk.
+fn apply_hunks(content: &str, hunks: &[Hunk]) -> Result<String, usize> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(content.len());
+    let mut pos = 0;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let at = locate(&lines, pos, hunk).ok_or(i + 1)?;
+        out.extend(lines[pos..at].iter().copied());
+        out.extend(hunk.new.iter().map(String::as_str));
+        pos = at + hunk.old.len();
+    }
+    out.extend(lines[pos..].iter().copied());
+    Ok(out)
+}
+
+fn locate(lines: &[&str], from: usize, hunk: &Hunk) -> Option<usize> {
+    let fits = |at: usize| {
+        lines
+            .get(at..at + hunk.old.len())
+            .is_some_and(|window| window.iter().zip(&hunk.old).all(|(a, b)| *a == b))
+    };
+    // A pure insertion (`-n,0`) goes after line n; otherwise the hunk starts at n.
+    let stated = if hunk.old.is_empty() {
+        hunk.old_start
+    } else {
+        hunk.old_start.saturating_sub(1)
+    };
+    let stated = stated.max(from);
+    (0..=lines.len()).find_map(|d| {
+        [
+            Some(stated + d),
+            stated.checked_sub(d).filter(|&at| at >= from),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|&at| fits(at))
+    })
+}
+
+/// Compute every file's new contents, then stage and move them into place.
+fn apply_all(
+    root: &Path,
+    files: &[FilePatch],
+    cancelled: &AtomicBool,
+) -> Result<ToolResult, CherubError> {
+    let _span = info_span!("patch_apply", files = files.len());
+    let mut targets = HashSet::new();
+    let mut changes = Vec::with_capacity(files.len());
+    for file in files {
+        let renamed = file
+            .new_path
+            .as_ref()
+            .filter(|&new| file.old_path.as_ref() != Some(new));
+        for path in file.old_path.iter().chain(renamed) {
+            if !targets.insert(path.as_str()) {
+                return Err(CherubError::ToolExecution(format!(
+                    "'{path}' appears more than once in the patch"
+                )));
+            }
+        }
+        changes.push(plan(root, file)?);
+    }
+
+    // Stage every write before touching any target.
+    let mut staged: Vec<(PathBuf, &Path)> = Vec::new();
+    for (target, content) in changes.iter().filter_map(|c| c.write.as_ref()) {
+        let name = target.file_name().unwrap_or_default().to_string_lossy();
+        let temp = target.with_file_name(format!(".{name}.cherub-patch-{}", Uuid::now_v7()));
+        if let Err(e) = fs::write(&temp, content) {
+            discard(&staged);
+            let _ = fs::remove_file(&temp);
+            return Err(CherubError::ToolExecution(format!(
+                "cannot write '{}': {e}",
+                target.display()
+            )));
+        }
+        staged.push((temp, target));
+    }
+    if let Err(e) = check_cancelled(cancelled) {
+        discard(&staged);
+        return Err(e);
+    }
+    for (i, (temp, target)) in staged.iter().enumerate() {
+        if let Err(e) = fs::rename(temp, target) {
+            discard(&staged[i..]);
+            return Err(CherubError::ToolExecution(format!(
+                "cannot replace '{}': {e}",
+                target.display()
+            )));
+        }
+    }
+    for path in changes.iter().filter_map(|c| c.remove.as_ref()) {
+        fs::remove_file(path).map_err(|e| {
+            CherubError::ToolExecution(format!("cannot delete '{}': {e}", path.display()))
+        })?;
+    }
+
+    let hunks: usize = files.iter().map(|f| f.hunks.len()).sum();
+    let mut output = format!("applied {hunks} hunk(s) to {} file(s):", files.len());
+    for change in &changes {
+        output.push_str("\n  ");
+        output.push_str(&change.summary);
+    }
+    Ok(ToolResult {
+        output,
+        status: None,
+        artifacts: Vec::new(),
+        attachments: Vec::new(),
+    })
+}
+
+/// Resolve one file section's paths and compute its new contents.
+fn plan(root: &Path, file: &FilePatch) -> Result<Change, CherubError> {
+    let old = file
+        .old_path
+        .as_deref()
+        .map(|p| resolve_workspace_path(root, p).map(|resolved| (p, resolved)))
+        .transpose()?;
+    let new = file
+        .new_path
+        .as_deref()
+        .map(|p| resolve_workspace_path(root, p).map(|resolved| (p, resolved)))
+        .transpose()?;
+
+    let original = match &old {
+        Some((path, resolved)) => {
+            let bytes = fs::read(resolved)
+                .map_err(|e| CherubError::ToolExecution(format!("cannot read '{path}': {e}")))?;
+            if is_binary_content(&bytes) {
+                return Err(CherubError::ToolExecution(format!(
+                    "'{path}' is a binary file"
+                )));
+            }
+            String::from_utf8(bytes)
+                .map_err(|_| CherubError::ToolExecution(format!("'{path}' is not valid UTF-8")))?
+        }
+        None => String::new(),
+    };
+    if let Some((path, resolved)) = &new
+        && old.as_ref().is_none_or(|(old, _)| old != path)
+        && resolved.exists()
+    {
+        return Err(CherubError::ToolExecution(format!(
+            "'{path}' already exists"
+        )));
+    }
+
+    let display = file.display_path();
+    let content = apply_hunks(&original, &file.hunks).map_err(|hunk| {
+        CherubError::ToolExecution(format!(
+            "hunk {hunk} of '{display}' does not apply; re-read the file and regenerate the patch"
+        ))
+    })?;
+    let added: usize = file.hunks.iter().map(|h| h.new.len()).sum();
+    let removed: usize = file.hunks.iter().map(|h| h.old.len()).sum();
+    let counts = format!("{} hunk(s), +{added} -{removed}", file.hunks.len());
+
+    Ok(match (old, new) {
+        (Some((path, resolved)), None) => {
+            if !content.is_empty() {
+                return Err(CherubError::ToolExecution(format!(
+                    "deleting '{path}' leaves content behind"
+                )));
+            }
+            Change {
+                write: None,
+                remove: Some(resolved),
+                summary: format!("deleted {path}"),
+            }
+        }
+        (None, Some((path, resolved))) => Change {
+            write: Some((resolved, content)),
+            remove: None,
+            summary: format!("created {path} ({counts})"),
+        },
+        (Some((old, old_resolved)), Some((path, resolved))) if old != path => Change {
+            write: Some((resolved, content)),
+            remove: Some(old_resolved),
+            summary: format!("renamed {old} -> {path} ({counts})"),
+        },
+        (_, Some((path, resolved))) => Change {
+            write: Some((resolved, content)),
+            remove: None,
+            summary: format!("modified {path} ({counts})"),
+        },
+        (None, None) => {
+            return Err(CherubError::InvalidInvocation(
+                "file header names no file".to_owned(),
+            ));
+        }
+    })
+}
+
+/// Remove staged files that were not moved into place.
+fn discard(staged: &[(PathBuf, &Path)]) {
+    for (temp, _) in staged {
+        if let Err(e) = fs::remove_file(temp) {
+            warn!(error = %e, path = %temp.display(), "failed to remove staged patch file");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn allow_token() -> CapabilityToken {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::{Proposed, ToolInvocation};
+        use std::str::FromStr;
+
+        let policy_str = r#"
+[tools.patch]
+enabled = true
+match_source = "patch"
+
+[tools.patch.actions.any]
+tier = "observe"
+patterns = ["^(create|modify|delete):"]
+"#;
+        let policy = Policy::from_str(policy_str).unwrap();
+        let proposal = ToolInvocation::<Proposed>::new(
+            "patch",
+            "execute",
+            json!({"patch": "--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n"}),
+        );
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        match decision {
+            enforcement::Decision::Allow(token) => token,
+            _ => panic!("expected Allow"),
+        }
+    }
+
+    async fn apply(dir: &Path, diff: &str) -> Result<ToolResult, CherubError> {
+        PatchTool::new(dir.to_path_buf())
+            .execute(&json!({"patch": diff}), allow_token())
+            .await
+    }
+
+    const SOURCE: &str = "fn one() {}\nfn two() {}\nfn three() {}\n";
+
+    #[test]
+    fn parses_git_headers() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\nindex 1..2 100644\n\
+            --- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -2 +2,2 @@\n-fn two() {}\n+fn two() {}\n+fn four() {}\n";
+        let files = parse(diff).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].actions(), ["modify:src/lib.rs"]);
+        assert_eq!(files[0].hunks[0].old, ["fn two() {}\n"]);
+        assert_eq!(files[0].hunks[0].new, ["fn two() {}\n", "fn four() {}\n"]);
+    }
+
+    #[test]
+    fn actions_name_every_touched_path() {
+        let diff = "--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+x\n\
+            --- a/gone.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-x\n\
+            --- a/old.rs\n+++ b/moved.rs\n@@ -1 +1 @@\n-x\n+y\n";
+        let actions: Vec<String> = parse(diff)
+            .unwrap()
+            .iter()
+            .flat_map(FilePatch::actions)
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                "create:new.rs",
+                "delete:gone.rs",
+                "delete:old.rs",
+                "create:moved.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn unsafe_or_malformed_patches_rejected() {
+        for diff in [
+            "--- a/../etc/passwd\n+++ b/../etc/passwd\n@@ -1 +1 @@\n-a\n+b\n",
+            "--- /etc/passwd\n+++ /etc/passwd\n@@ -1 +1 @@\n-a\n+b\n",
+            "--- a/x\n+++ b/x\n",
+            "--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n-a\n+b\n",
+            "--- a/x\n@@ -1 +1 @@\n-a\n+b\n",
+            "just some text",
+        ] {
+            assert!(parse(diff).is_err(), "{diff}");
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_hunks_with_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), format!("// header\n{SOURCE}")).unwrap();
+        // Stated line 2 is off by one; the hunk still applies at its match.
+        let diff = "--- a/lib.rs\n+++ b/lib.rs\n@@ -2,2 +2,2 @@\n fn two() {}\n-fn three() {}\n+fn three() -> u8 { 3 }\n";
+        let result = apply(dir.path(), diff).await.unwrap();
+        assert_eq!(
+            result.output,
+            "applied 1 hunk(s) to 1 file(s):\n  modified lib.rs (1 hunk(s), +2 -2)"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("lib.rs")).unwrap(),
+            "// header\nfn one() {}\nfn two() {}\nfn three() -> u8 { 3 }\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_hunk_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), SOURCE).unwrap();
+        fs::write(dir.path().join("b.rs"), SOURCE).unwrap();
+        let diff = "--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-fn one() {}\n+fn uno() {}\n\
+            --- a/b.rs\n+++ b/b.rs\n@@ -1 +1 @@\n-fn missing() {}\n+fn uno() {}\n";
+        let err = apply(dir.path(), diff).await.unwrap_err();
+        assert!(
+            err.to_string().contains("hunk 1 of 'b.rs' does not apply"),
+            "{err}"
+        );
+        assert_eq!(fs::read_to_string(dir.path().join("a.rs")).unwrap(), SOURCE);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn creates_renames_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("old.rs"), SOURCE).unwrap();
+        fs::write(dir.path().join("gone.rs"), "bye\n").unwrap();
+        let diff = "--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1,2 @@\n+line one\n+line two\n\\ No newline at end of file\n\
+            --- a/gone.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-bye\n\
+            --- a/old.rs\n+++ b/moved.rs\n@@ -3 +3 @@\n-fn three() {}\n+fn drei() {}\n";
+        apply(dir.path(), diff).await.unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("new.rs")).unwrap(),
+            "line one\nline two"
+        );
+        assert!(!dir.path().join("gone.rs").exists());
+        assert!(!dir.path().join("old.rs").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("moved.rs")).unwrap(),
+            "fn one() {}\nfn two() {}\nfn drei() {}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn create_over_existing_file_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("new.rs"), SOURCE).unwrap();
+        let diff = "--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+x\n";
+        assert!(apply(dir.path(), diff).await.is_err());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("new.rs")).unwrap(),
+            SOURCE
+        );
+    }
+
+    #[tokio::test]
+    async fn symlink_escape_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret"), "a\n").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+        let diff = "--- a/link/secret\n+++ b/link/secret\n@@ -1 +1 @@\n-a\n+b\n";
+        assert!(apply(dir.path(), diff).await.is_err());
+        assert_eq!(
+            fs::read_to_string(outside.path().join("secret")).unwrap(),
+            "a\n"
+        );
+    }
+}
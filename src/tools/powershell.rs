@@ -0,0 +1,136 @@
+//! PowerShell tool: the Windows counterpart of the bash tool.
+//!
+//! Runs the command with the shell named in `[tools.powershell.shell]`
+//! (`pwsh`, `powershell`, or `cmd`), through the same process runner as bash:
+//! allowlisted environment, workspace cwd, timeout, and output cap.
+//!
+//! The shell must be configured. Enforcement reads a command by the tool's
+//! shell (backslashes are path separators, and syntax the POSIX parser would
+//! misread is rejected), so running PowerShell under a tool the policy treats
+//! as bash would evaluate one language and execute another.
+
+use std::time::Duration;
+
+use tracing::{info, info_span};
+
+use crate::enforcement::capability::CapabilityToken;
+use crate::error::CherubError;
+
+use super::bash::{self, ShellConfig};
+use super::scratch::ScratchDir;
+use super::{ToolResult, interactive};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_MAX_OUTPUT: usize = 256 * 1024; // 256 KiB
+
+pub struct PowerShellTool {
+    pub(crate) timeout: Duration,
+    pub(crate) max_output: usize,
+}
+
+impl PowerShellTool {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            max_output: DEFAULT_MAX_OUTPUT,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError> {
+        token.check_tool("powershell")?;
+        let command = params
+            .get("command")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                CherubError::InvalidInvocation("missing 'command' parameter".to_owned())
+            })?;
+        let stdin = params.get("stdin").and_then(|v| v.as_str());
+        let shell = windows_shell(&token)?;
+
+        let _span = info_span!("powershell_exec", command = %command);
+        if let Some(reason) = interactive::interactive_reason(command, stdin.is_some()) {
+            info!(reason = %reason, "interactive command refused");
+            return Err(CherubError::ToolExecution(reason));
+        }
+        let timeout = token.limits.timeout.unwrap_or(self.timeout);
+        let max_output = token.limits.max_output_bytes.unwrap_or(self.max_output);
+
+        let root = token
+            .workspace_root
+            .clone()
+            .unwrap_or_else(super::workspace_root);
+        let scratch = ScratchDir::create()?;
+        let cmd = shell.command(&token, &root, &scratch.path, command);
+        bash::run(cmd, &token, &root, scratch, stdin, timeout, max_output).await
+    }
+}
+
+/// The token's shell, which must be a Windows one (see the module docs).
+fn windows_shell(token: &CapabilityToken) -> Result<ShellConfig, CherubError> {
+    token
+        .shell
+        .filter(|s| s.program.is_windows())
+        .ok_or_else(|| {
+            CherubError::ToolExecution(
+                "powershell tool needs [tools.powershell.shell] with program \"pwsh\", \
+                 \"powershell\", or \"cmd\""
+                    .to_owned(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::enforcement::{self, policy::Policy};
+    use crate::tools::ToolInvocation;
+    use std::str::FromStr;
+
+    fn token(shell: &str) -> CapabilityToken {
+        let policy = Policy::from_str(&format!(
+            "[tools.powershell]\nenabled = true\n\n{shell}\n\n[tools.powershell.actions.read]\ntier = \"observe\"\npatterns = [\"(?i)^get-location$\"]\n"
+        ))
+        .unwrap();
+        let proposal =
+            ToolInvocation::new("powershell", "execute", json!({"command": "Get-Location"}));
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        match decision {
+            enforcement::Decision::Allow(token) => token,
+            _ => panic!("expected Allow"),
+        }
+    }
+
+    #[tokio::test]
+    async fn requires_a_windows_shell() {
+        for shell in ["", "[tools.powershell.shell]\nprogram = \"bash\""] {
+            let err = PowerShellTool::new()
+                .execute(&json!({"command": "Get-Location"}), token(shell))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, CherubError::ToolExecution(_)), "{shell}");
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_through_shared_runner() {
+        // Whether or not pwsh is installed, the command goes through the
+        // shared runner: an exit status or a spawn error, never a hang.
+        let result = PowerShellTool::new()
+            .execute(
+                &json!({"command": "Get-Location"}),
+                token("[tools.powershell.shell]\nprogram = \"pwsh\""),
+            )
+            .await;
+        match result {
+            Ok(result) => assert!(result.status.is_some()),
+            Err(e) => assert!(matches!(e, CherubError::ToolExecution(_))),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Process-group lifetime for spawned commands.
+//!
+//! Every command runs as the leader of its own process group (a new console
+//! process group on Windows), so killing the group reaches everything the
+//! command started: `npm install`'s children, backgrounded jobs, and so on.
+//!
+//! A `ProcessGroup` guard kills the group unless the command finished on its
+//! own: on timeout, on a failed read, when the executing future is dropped
+//! (cancellation), or on panic. Live groups are also tracked process-wide,
+//! because a terminal's Ctrl-C only reaches the agent's own group:
+//! `reap_on_shutdown` kills them all when the agent is interrupted or
+//! terminated, and `kill_all` does the same on an orderly exit.
+//!
+//! Kills shell out to `kill(1)` (`taskkill /T` on Windows) rather than
+//! `libc::killpg`, to stay free of `unsafe`.
+
+use std::collections::BTreeSet;
+use std::sync::{Mutex, PoisonError};
+
+use tokio::process::{Child, Command};
+use tracing::{info, warn};
+
+/// Process groups of commands that are still running.
+static LIVE: Groups = Groups::new();
+
+struct Groups(Mutex<BTreeSet<u32>>);
+
+impl Groups {
+    const fn new() -> Self {
+        Self(Mutex::new(BTreeSet::new()))
+    }
+
+    fn insert(&self, pgid: u32) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(pgid);
+    }
+
+    fn remove(&self, pgid: u32) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&pgid);
+    }
+
+    fn kill_all(&self) -> usize {
+        let groups = std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner));
+        for &pgid in &groups {
+            kill_sync(pgid);
+        }
+        groups.len()
+    }
+}
+
+/// Start the child in its own process group, so killing the group kills
+/// everything the command started. On Windows, a new console process group.
+pub(crate) fn isolate(cmd: &mut Command) {
+    #[cfg(unix)]
+    cmd.process_group(0);
+    #[cfg(windows)]
+    {
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// The process group led by a child spawned with `isolate`. Killed on drop
+/// unless `release`d.
+pub(crate) struct ProcessGroup {
+    pgid: Option<u32>, // None once released or killed, or if the child already exited
+}
+
+impl ProcessGroup {
+    pub(crate) fn track(child: &Child) -> Self {
+        let pgid = child.id();
+        if let Some(pgid) = pgid {
+            LIVE.insert(pgid);
+        }
+        Self { pgid }
+    }
+
+    /// The leader exited on its own: stop tracking the group without killing
+    /// it.
+    pub(crate) fn release(mut self) {
+        if let Some(pgid) = self.pgid.take() {
+            LIVE.remove(pgid);
+        }
+    }
+
+    /// SIGKILL the group, then reap the leader. Falls back to killing just the
+    /// leader if the group kill failed.
+    pub(crate) async fn kill(mut self, child: &mut Child) {
+        if let Some(pgid) = self.pgid.take() {
+            let (program, args) = kill_argv(pgid);
+            if let Err(e) = Command::new(program).args(&args).status().await {
+                warn!(error = %e, "failed to kill process group");
+            }
+            LIVE.remove(pgid);
+        }
+        let _ = child.kill().await;
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Some(pgid) = self.pgid.take() {
+            kill_sync(pgid);
+            LIVE.remove(pgid);
+        }
+    }
+}
+
+/// Kill every tracked process group. Returns how many there were.
+pub fn kill_all() -> usize {
+    LIVE.kill_all()
+}
+
+/// Wait for Ctrl-C (or SIGTERM on Unix), kill every tracked process group,
+/// and exit. Spawn once at startup.
+///
+/// A Ctrl-C for which `interrupt` returns true was handled by the caller
+/// (it cancelled a running turn, whose commands die with it), and waiting
+/// resumes.
+pub async fn reap_on_shutdown(interrupt: fn() -> bool) {
+    #[cfg(unix)]
+    let mut term = {
+        use tokio::signal::unix::{SignalKind, signal};
+        signal(SignalKind::terminate()).ok()
+    };
+    loop {
+        #[cfg(unix)]
+        let interrupted = match term.as_mut() {
+            Some(term) => tokio::select! {
+                result = tokio::signal::ctrl_c() => result.is_ok(),
+                _ = term.recv() => false,
+            },
+            None => tokio::signal::ctrl_c().await.is_ok(),
+        };
+        #[cfg(not(unix))]
+        let interrupted = tokio::signal::ctrl_c().await.is_ok();
+        if !(interrupted && interrupt()) {
+            break;
+        }
+    }
+    let killed = kill_all();
+    info!(process_groups = killed, "shutting down");
+    std::process::exit(130);
+}
+
+fn kill_argv(pgid: u32) -> (&'static str, Vec<String>) {
+    #[cfg(unix)]
+    return (
+        "kill",
+        vec!["-KILL".to_owned(), "--".to_owned(), format!("-{pgid}")],
+    );
+    #[cfg(windows)]
+    return (
+        "taskkill",
+        vec![
+            "/F".to_owned(),
+            "/T".to_owned(),
+            "/PID".to_owned(),
+            pgid.to_string(),
+        ],
+    );
+}
+
+/// Blocking kill, for `Drop` and shutdown where nothing can be awaited.
+fn kill_sync(pgid: u32) {
+    let (program, args) = kill_argv(pgid);
+    let killed = std::process::Command::new(program)
+        .args(&args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    if let Err(e) = killed {
+        warn!(error = %e, pgid, "failed to kill process group");
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn spawn(script: &str) -> Child {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", script]).kill_on_drop(true);
+        isolate(&mut cmd);
+        cmd.spawn().unwrap()
+    }
+
+    #[tokio::test]
+    async fn dropped_guard_kills_background_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("marker");
+        let mut child = spawn(&format!("(sleep 1; touch {}) & sleep 30", marker.display()));
+        let group = ProcessGroup::track(&child);
+        drop(group);
+        let status = child.wait().await.unwrap();
+        assert_eq!(status.code(), None);
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists(), "background job survived the guard");
+    }
+
+    #[tokio::test]
+    async fn kill_all_reaps_tracked_groups() {
+        let groups = Groups::new();
+        let mut child = spawn("sleep 30");
+        groups.insert(child.id().unwrap());
+        assert_eq!(groups.kill_all(), 1);
+        assert_eq!(child.wait().await.unwrap().code(), None);
+        assert_eq!(groups.kill_all(), 0);
+    }
+}
@@ -0,0 +1,359 @@
+//! Optional OS-level confinement for spawned commands.
+//!
+//! A second layer under the policy: a command the policy allowed still runs in
+//! a sandbox when `[execution.<tier>] sandbox` names a backend. The profile is
+//! fixed by the token's tier:
+//!
+//! | Tier    | Network | Filesystem                                      |
+//! |---------|---------|-------------------------------------------------|
+//! | Observe | none    | read-only (private `/tmp`)                      |
+//! | Act     | host    | read-only except the workspace (private `/tmp`) |
+//! | Commit  | host    | unconfined — a human approved it                |
+//!
+//! Backends: `bubblewrap` (namespaces, no daemon) and `container` (an
+//! ephemeral Docker/Podman container, for hosts where unprivileged namespaces
+//! are unavailable).
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::enforcement::tier::Tier;
+
+/// What a sandboxed command may touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandboxProfile<'a> {
+    /// Whether the command shares the host network. `false` → loopback only.
+    pub network: bool,
+    /// The one directory mounted writable, if any.
+    pub writable: Option<&'a Path>,
+    /// The workspace, mounted read-only and used as the working directory by
+    /// backends that must mount it explicitly.
+    pub workspace: Option<&'a Path>,
+    /// A host file or directory the command reads from (e.g. a script written
+    /// to the host's temp dir), mounted read-only at the same path.
+    pub input: Option<&'a Path>,
+    /// The invocation's scratch directory (`tools::scratch`), mounted writable
+    /// at the same path.
+    pub scratch: Option<&'a Path>,
+}
+
+impl<'a> SandboxProfile<'a> {
+    /// The profile for `tier`, or `None` if the tier runs unconfined.
+    pub fn for_tier(tier: Tier, workspace: &'a Path) -> Option<Self> {
+        match tier {
+            Tier::Observe => Some(Self {
+                network: false,
+                writable: None,
+                workspace: Some(workspace),
+                input: None,
+                scratch: None,
+            }),
+            Tier::Act => Some(Self {
+                network: true,
+                writable: Some(workspace),
+                workspace: Some(workspace),
+                input: None,
+                scratch: None,
+            }),
+            Tier::Commit => None,
+        }
+    }
+}
+
+/// Extension point for sandbox backends. Known backends dispatch through the
+/// `SandboxBackend` enum; the trait is reserved for external ones.
+pub trait Sandbox {
+    /// The command that runs `program` with `args` confined to `profile`.
+    fn command(&self, profile: &SandboxProfile<'_>, program: &str, args: &[&str]) -> Command;
+}
+
+/// Sandbox backend selected by `[execution.<tier>] sandbox`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxBackend {
+    /// `bwrap` (bubblewrap): unprivileged user, mount, pid, and net namespaces.
+    /// Must be on `PATH`; a missing binary fails the spawn rather than running
+    /// the command unconfined.
+    Bubblewrap,
+    /// An ephemeral container per invocation, configured by `[execution.container]`.
+    Container(ContainerSandbox),
+}
+
+impl Sandbox for SandboxBackend {
+    fn command(&self, profile: &SandboxProfile<'_>, program: &str, args: &[&str]) -> Command {
+        match self {
+            SandboxBackend::Bubblewrap => bubblewrap(profile, program, args),
+            SandboxBackend::Container(c) => c.command(profile, program, args),
+        }
+    }
+}
+
+/// Container runtime CLI used by `ContainerSandbox`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    fn program(self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Podman => "podman",
+        }
+    }
+}
+
+/// Extra bind mount into a sandbox container.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerMount {
+    pub host: PathBuf,
+    pub container: PathBuf,
+    pub read_only: bool,
+}
+
+/// Runs each command in a fresh `--rm` container from `image`, with the
+/// workspace bind-mounted at the same path (read-only unless the profile makes
+/// it writable) and a read-only root filesystem.
+///
+/// The network is attached only when both the profile and `network` allow it.
+/// Nothing from the agent's environment is forwarded; the command sees the
+/// image's environment. A timeout kills the runtime client; `--init` makes
+/// the container exit with it when the runtime propagates the signal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerSandbox {
+    pub runtime: ContainerRuntime,
+    pub image: String,
+    pub network: bool,
+    pub mounts: Vec<ContainerMount>,
+}
+
+impl Sandbox for ContainerSandbox {
+    fn command(&self, profile: &SandboxProfile<'_>, program: &str, args: &[&str]) -> Command {
+        let mut cmd = Command::new(self.runtime.program());
+        cmd.args(["run", "--rm", "--interactive", "--init", "--read-only"])
+            .args(["--tmpfs", "/tmp", "--cap-drop", "ALL"])
+            .args(["--security-opt", "no-new-privileges"]);
+        if !(profile.network && self.network) {
+            cmd.args(["--network", "none"]);
+        }
+        if let Some(dir) = profile.workspace {
+            let mode = if profile.writable.is_some() {
+                "rw"
+            } else {
+                "ro"
+            };
+            cmd.arg("--volume")
+                .arg(format!("{}:{}:{mode}", dir.display(), dir.display()))
+                .arg("--workdir")
+                .arg(dir);
+        }
+        if let Some(input) = profile.input {
+            cmd.arg("--volume")
+                .arg(format!("{}:{}:ro", input.display(), input.display()));
+        }
+        // The container sees none of the host environment; pass its name on.
+        if let Some(scratch) = profile.scratch {
+            cmd.arg("--volume")
+                .arg(format!("{}:{}:rw", scratch.display(), scratch.display()))
+                .arg("--env")
+                .arg(format!("{}={}", super::scratch::ENV_VAR, scratch.display()));
+        }
+        for m in &self.mounts {
+            let mode = if m.read_only { "ro" } else { "rw" };
+            cmd.arg("--volume").arg(format!(
+                "{}:{}:{mode}",
+                m.host.display(),
+                m.container.display()
+            ));
+        }
+        cmd.arg(&self.image).arg(program).args(args);
+        cmd
+    }
+}
+
+fn bubblewrap(profile: &SandboxProfile<'_>, program: &str, args: &[&str]) -> Command {
+    let mut cmd = Command::new("bwrap");
+    cmd.args(["--ro-bind", "/", "/"])
+        .args(["--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"])
+        .args(["--unshare-pid", "--die-with-parent"]);
+    if !profile.network {
+        cmd.arg("--unshare-net");
+    }
+    // After `--tmpfs /tmp`, so a workspace under /tmp stays visible; before
+    // the writable bind, which must win when both name the workspace.
+    if let Some(workspace) = profile.workspace {
+        cmd.arg("--ro-bind").arg(workspace).arg(workspace);
+        cmd.arg("--chdir").arg(workspace);
+    }
+    if let Some(dir) = profile.writable {
+        cmd.arg("--bind").arg(dir).arg(dir);
+    }
+    // After `--tmpfs /tmp`, so an input under the host's /tmp stays visible.
+    if let Some(input) = profile.input {
+        cmd.arg("--ro-bind").arg(input).arg(input);
+    }
+    if let Some(scratch) = profile.scratch {
+        cmd.arg("--bind").arg(scratch).arg(scratch);
+    }
+    cmd.arg("--").arg(program).args(args);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(cmd: &Command) -> Vec<String> {
+        cmd.as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn observe_has_no_network_or_writable_mounts() {
+        let profile = SandboxProfile::for_tier(Tier::Observe, Path::new("/work")).unwrap();
+        let cmd = SandboxBackend::Bubblewrap.command(&profile, "bash", &["-c", "ls"]);
+        assert_eq!(cmd.as_std().get_program(), "bwrap");
+        let args = argv(&cmd);
+        assert!(args.contains(&"--unshare-net".to_owned()));
+        assert!(!args.contains(&"--bind".to_owned()));
+        assert_eq!(args[args.len() - 4..], ["--", "bash", "-c", "ls"]);
+    }
+
+    #[test]
+    fn act_binds_workspace_writable() {
+        let profile = SandboxProfile::for_tier(Tier::Act, Path::new("/work")).unwrap();
+        let args = argv(&SandboxBackend::Bubblewrap.command(&profile, "bash", &[]));
+        assert!(!args.contains(&"--unshare-net".to_owned()));
+        assert!(args.windows(3).any(|w| w == ["--bind", "/work", "/work"]));
+    }
+
+    fn container() -> ContainerSandbox {
+        ContainerSandbox {
+            runtime: ContainerRuntime::Podman,
+            image: "debian:stable-slim".to_owned(),
+            network: true,
+            mounts: vec![ContainerMount {
+                host: PathBuf::from("/opt/cache"),
+                container: PathBuf::from("/cache"),
+                read_only: true,
+            }],
+        }
+    }
+
+    #[test]
+    fn container_observe_is_offline_with_readonly_workspace() {
+        let profile = SandboxProfile::for_tier(Tier::Observe, Path::new("/work")).unwrap();
+        let cmd = SandboxBackend::Container(container()).command(&profile, "bash", &["-c", "ls"]);
+        assert_eq!(cmd.as_std().get_program(), "podman");
+        let args = argv(&cmd);
+        assert_eq!(args[..2], ["run", "--rm"]);
+        assert!(args.windows(2).any(|w| w == ["--network", "none"]));
+        assert!(args.windows(2).any(|w| w == ["--volume", "/work:/work:ro"]));
+        assert!(
+            args.windows(2)
+                .any(|w| w == ["--volume", "/opt/cache:/cache:ro"])
+        );
+        assert!(args.windows(2).any(|w| w == ["--workdir", "/work"]));
+        assert_eq!(
+            args[args.len() - 4..],
+            ["debian:stable-slim", "bash", "-c", "ls"]
+        );
+    }
+
+    #[test]
+    fn container_act_network_follows_config() {
+        let profile = SandboxProfile::for_tier(Tier::Act, Path::new("/work")).unwrap();
+        let args = argv(&container().command(&profile, "bash", &[]));
+        assert!(!args.contains(&"none".to_owned()));
+        assert!(args.windows(2).any(|w| w == ["--volume", "/work:/work:rw"]));
+
+        let offline = ContainerSandbox {
+            network: false,
+            ..container()
+        };
+        let offline_args = argv(&offline.command(&profile, "bash", &[]));
+        assert!(offline_args.windows(2).any(|w| w == ["--network", "none"]));
+    }
+
+    #[test]
+    fn workspace_is_mounted_and_entered() {
+        let profile = SandboxProfile::for_tier(Tier::Observe, Path::new("/tmp/work")).unwrap();
+        let args = argv(&SandboxBackend::Bubblewrap.command(&profile, "bash", &[]));
+        let tmpfs = args.iter().position(|a| a == "--tmpfs").unwrap();
+        let bind = args
+            .windows(3)
+            .position(|w| w == ["--ro-bind", "/tmp/work", "/tmp/work"])
+            .unwrap();
+        assert!(bind > tmpfs);
+        assert!(args.windows(2).any(|w| w == ["--chdir", "/tmp/work"]));
+
+        // On Act the writable bind comes last, so it wins.
+        let profile = SandboxProfile::for_tier(Tier::Act, Path::new("/tmp/work")).unwrap();
+        let args = argv(&SandboxBackend::Bubblewrap.command(&profile, "bash", &[]));
+        let read_only = args
+            .windows(3)
+            .position(|w| w == ["--ro-bind", "/tmp/work", "/tmp/work"])
+            .unwrap();
+        let writable = args
+            .windows(3)
+            .position(|w| w == ["--bind", "/tmp/work", "/tmp/work"])
+            .unwrap();
+        assert!(writable > read_only);
+    }
+
+    #[test]
+    fn input_is_mounted_read_only() {
+        let mut profile = SandboxProfile::for_tier(Tier::Observe, Path::new("/work")).unwrap();
+        profile.input = Some(Path::new("/tmp/cherub-script"));
+        let args = argv(&SandboxBackend::Bubblewrap.command(&profile, "python3", &[]));
+        let tmpfs = args.iter().position(|a| a == "--tmpfs").unwrap();
+        let bind = args
+            .windows(3)
+            .position(|w| w == ["--ro-bind", "/tmp/cherub-script", "/tmp/cherub-script"])
+            .unwrap();
+        assert!(bind > tmpfs);
+
+        let args = argv(&container().command(&profile, "python3", &[]));
+        assert!(
+            args.windows(2)
+                .any(|w| w == ["--volume", "/tmp/cherub-script:/tmp/cherub-script:ro"])
+        );
+    }
+
+    #[test]
+    fn scratch_is_mounted_writable_even_on_observe() {
+        let mut profile = SandboxProfile::for_tier(Tier::Observe, Path::new("/work")).unwrap();
+        profile.scratch = Some(Path::new("/tmp/cherub-tmp-1"));
+        let args = argv(&SandboxBackend::Bubblewrap.command(&profile, "bash", &[]));
+        let tmpfs = args.iter().position(|a| a == "--tmpfs").unwrap();
+        let bind = args
+            .windows(3)
+            .position(|w| w == ["--bind", "/tmp/cherub-tmp-1", "/tmp/cherub-tmp-1"])
+            .unwrap();
+        assert!(bind > tmpfs);
+
+        let args = argv(&container().command(&profile, "bash", &[]));
+        assert!(
+            args.windows(2)
+                .any(|w| w == ["--volume", "/tmp/cherub-tmp-1:/tmp/cherub-tmp-1:rw"])
+        );
+        assert!(
+            args.windows(2)
+                .any(|w| w == ["--env", "CHERUB_TMPDIR=/tmp/cherub-tmp-1"])
+        );
+    }
+
+    #[test]
+    fn commit_is_unconfined() {
+        assert_eq!(
+            SandboxProfile::for_tier(Tier::Commit, Path::new("/work")),
+            None
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Per-invocation scratch directories for spawned commands.
+//!
+//! Every command a tool spawns gets a fresh, empty directory of its own under
+//! the host's temp dir, named in `$CHERUB_TMPDIR` and mounted writable when
+//! the command is sandboxed. It is removed when the execution finishes, and on
+//! cancellation too: removal runs when the `ScratchDir` is dropped.
+//!
+//! `[execution.<tier>] tmp_quota_bytes` caps its size. The directory is
+//! measured while the command runs; one that grows past the quota gets the
+//! process group killed and the call fails.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::error::CherubError;
+
+/// Environment variable naming the scratch directory.
+pub(crate) const ENV_VAR: &str = "CHERUB_TMPDIR";
+
+/// How often a quota-limited directory is measured.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A scratch directory, deleted with everything in it on drop.
+pub(crate) struct ScratchDir {
+    pub(crate) path: PathBuf,
+}
+
+impl ScratchDir {
+    pub(crate) fn create() -> Result<Self, CherubError> {
+        let path = std::env::temp_dir().join(format!("cherub-tmp-{}", Uuid::now_v7()));
+        std::fs::create_dir(&path).map_err(|e| {
+            CherubError::ToolExecution(format!("cannot create scratch directory: {e}"))
+        })?;
+        Ok(Self { path })
+    }
+
+    /// Completes once the directory holds more than `quota` bytes; never
+    /// without a quota.
+    pub(crate) async fn exceeded(&self, quota: Option<u64>) {
+        let Some(quota) = quota else {
+            return std::future::pending().await;
+        };
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let path = self.path.clone();
+            let size = tokio::task::spawn_blocking(move || dir_size(&path))
+                .await
+                .unwrap_or(0);
+            if size > quota {
+                return;
+            }
+        }
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.path) {
+            warn!(error = %e, dir = %self.path.display(), "failed to remove scratch dir");
+        }
+    }
+}
+
+/// Bytes in the regular files under `dir`. Symlinks are not followed.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.path().symlink_metadata().ok()?;
+            Some(if meta.is_dir() {
+                dir_size(&entry.path())
+            } else {
+                meta.len()
+            })
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn removed_on_drop_and_measured_against_quota() {
+        let scratch = ScratchDir::create().unwrap();
+        let path = scratch.path.clone();
+        std::fs::create_dir(path.join("nested")).unwrap();
+        std::fs::write(path.join("nested/blob"), [0u8; 2048]).unwrap();
+        assert_eq!(dir_size(&path), 2048);
+
+        let quota = tokio::time::timeout(Duration::from_secs(5), scratch.exceeded(Some(1024)));
+        assert!(quota.await.is_ok());
+        let none = tokio::time::timeout(POLL_INTERVAL * 2, scratch.exceeded(None));
+        assert!(none.await.is_err());
+
+        drop(scratch);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,185 @@
+//! Script tool: runs short Python or Node.js snippets.
+//!
+//! The snippet is written to a fresh file in the host temp dir and run by the
+//! interpreter under the same environment allowlist, workspace, sandbox, and
+//! seccomp rules as bash. Under a sandbox the file is mounted read-only.
+//!
+//! Uses `MatchSource::Field("language")` for enforcement — the action string is
+//! the language name, so policy decides which interpreters each tier may run.
+//! The body is screened with `not_matches` constraints on `code` (imports of
+//! `os`, `subprocess`, or network modules escalate; see the default policy).
+//! Screening is a heuristic; the sandbox is what confines the process.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tracing::{info_span, warn};
+use uuid::Uuid;
+
+use crate::enforcement::capability::CapabilityToken;
+use crate::error::CherubError;
+use crate::tools::ToolResult;
+
+use super::bash;
+use super::scratch::ScratchDir;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_OUTPUT: usize = 256 * 1024; // 256 KiB
+
+/// Interpreter and script file extension for each supported language.
+const LANGUAGES: &[(&str, &str, &str)] = &[("python", "python3", "py"), ("node", "node", "js")];
+
+pub struct ScriptTool {
+    workspace_root: PathBuf,
+}
+
+impl ScriptTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self { workspace_root }
+    }
+
+    pub async fn execute(
+        &self,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError> {
+        token.check_tool("script")?;
+        let language = params
+            .get("language")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                CherubError::InvalidInvocation("script tool requires 'language'".to_owned())
+            })?;
+        let code = params.get("code").and_then(|v| v.as_str()).ok_or_else(|| {
+            CherubError::InvalidInvocation("script tool requires 'code'".to_owned())
+        })?;
+        let stdin = params.get("stdin").and_then(|v| v.as_str());
+        let (_, interpreter, extension) = LANGUAGES
+            .iter()
+            .find(|(name, _, _)| *name == language)
+            .ok_or_else(|| {
+            CherubError::InvalidInvocation(format!("unsupported script language: {language}"))
+        })?;
+
+        let _span = info_span!("script_exec", language = %language, code_bytes = code.len());
+        let timeout = token.limits.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        let max_output = token.limits.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT);
+        // A policy `[workspace]` root on the token takes precedence.
+        let root = token
+            .workspace_root
+            .clone()
+            .unwrap_or_else(|| self.workspace_root.clone());
+
+        let scratch = ScratchDir::create()?;
+        // One directory per run, so concurrent scripts never share a file.
+        let dir = std::env::temp_dir().join(format!("cherub-script-{}", Uuid::now_v7()));
+        let script = dir.join(format!("script.{extension}"));
+        tokio::fs::create_dir(&dir)
+            .await
+            .map_err(|e| CherubError::ToolExecution(format!("cannot write script: {e}")))?;
+        let result = match tokio::fs::write(&script, code).await {
+            Ok(()) => {
+                let script_arg = script.to_string_lossy();
+                let mut cmd = bash::command_for(
+                    &token,
+                    &root,
+                    Some(&dir),
+                    &scratch.path,
+                    interpreter,
+                    &[&script_arg],
+                );
+                cmd.current_dir(&root);
+                bash::run(cmd, &token, &root, scratch, stdin, timeout, max_output).await
+            }
+            Err(e) => Err(CherubError::ToolExecution(format!(
+                "cannot write script: {e}"
+            ))),
+        };
+
+        if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+            warn!(error = %e, dir = %dir.display(), "failed to remove script dir");
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn allow_token() -> CapabilityToken {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::{Proposed, ToolInvocation};
+        use std::str::FromStr;
+
+        let policy_str = r#"
+[tools.script]
+enabled = true
+match_source = "field"
+match_field = "language"
+
+[tools.script.actions.run]
+tier = "observe"
+patterns = ["^python$", "^node$"]
+"#;
+        let policy = Policy::from_str(policy_str).unwrap();
+        let proposal = ToolInvocation::<Proposed>::new(
+            "script",
+            "execute",
+            json!({"language": "python", "code": "print(1)"}),
+        );
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        match decision {
+            enforcement::Decision::Allow(token) => token,
+            _ => panic!("expected Allow"),
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_python_in_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("data.txt"), "42").unwrap();
+        let tool = ScriptTool::new(dir.path().to_path_buf());
+        let params = json!({
+            "language": "python",
+            "code": "print(open('data.txt').read().strip())",
+        });
+        let result = tool.execute(&params, allow_token()).await.unwrap();
+        assert_eq!(result.output.trim(), "42");
+        assert_eq!(result.status.unwrap().exit_code, Some(0));
+    }
+
+    #[tokio::test]
+    async fn script_file_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = ScriptTool::new(dir.path().to_path_buf());
+        let params = json!({"language": "python", "code": "import sys; print(sys.argv[0])"});
+        let result = tool.execute(&params, allow_token()).await.unwrap();
+        let script = PathBuf::from(result.output.trim());
+        assert!(script.ends_with("script.py"), "{}", result.output);
+        assert!(!script.exists());
+    }
+
+    #[tokio::test]
+    async fn stdin_is_passed_to_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = ScriptTool::new(dir.path().to_path_buf());
+        let params = json!({
+            "language": "python",
+            "code": "import sys; print(sys.stdin.read().upper())",
+            "stdin": "hello",
+        });
+        let result = tool.execute(&params, allow_token()).await.unwrap();
+        assert_eq!(result.output.trim(), "HELLO");
+    }
+
+    #[tokio::test]
+    async fn unsupported_language_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = ScriptTool::new(dir.path().to_path_buf());
+        let params = json!({"language": "ruby", "code": "puts 1"});
+        let result = tool.execute(&params, allow_token()).await;
+        assert!(matches!(result, Err(CherubError::InvalidInvocation(_))));
+    }
+}
@@ -0,0 +1,334 @@
+//! Search tool: read-only content search over the workspace.
+//!
+//! Runs ripgrep (`rg`) when it is on `PATH`, else `grep -rn`, as a fixed argv
+//! under the same environment allowlist, workspace, sandbox, and seccomp rules
+//! as bash. The pattern is always passed with `-e`, so it is never read as an
+//! option, and the search path must resolve inside the workspace.
+//!
+//! Output is one `path:line:text` entry per match, at most `max_results`
+//! entries, with long lines cut short.
+//!
+//! Uses `MatchSource::Field("pattern")` for enforcement; the default policy
+//! allows every pattern at Observe.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tracing::info_span;
+
+use crate::enforcement::capability::CapabilityToken;
+use crate::error::CherubError;
+use crate::tools::ToolResult;
+use crate::tools::path::resolve_workspace_path;
+
+use super::bash;
+use super::scratch::ScratchDir;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_OUTPUT: usize = 256 * 1024; // 256 KiB
+/// Matches returned when `max_results` is not given.
+const DEFAULT_MAX_RESULTS: usize = 100;
+/// Upper bound on `max_results`.
+const MAX_RESULTS_LIMIT: usize = 1_000;
+/// Characters kept per match line.
+const MAX_LINE_CHARS: usize = 500;
+
+/// The search program, picked once when the tool is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchProgram {
+    Ripgrep,
+    Grep,
+}
+
+pub struct SearchTool {
+    workspace_root: PathBuf,
+    program: SearchProgram,
+}
+
+impl SearchTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        let program = if on_path("rg") {
+            SearchProgram::Ripgrep
+        } else {
+            SearchProgram::Grep
+        };
+        Self {
+            workspace_root,
+            program,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError> {
+        token.check_tool("search")?;
+        let pattern = params
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| {
+                CherubError::InvalidInvocation("search tool requires 'pattern'".to_owned())
+            })?;
+        let path = params.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let include = params.get("include").and_then(|v| v.as_str());
+        let ignore_case = params
+            .get("ignore_case")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let max_results = params
+            .get("max_results")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_MAX_RESULTS, |n| {
+                usize::try_from(n).unwrap_or(MAX_RESULTS_LIMIT)
+            })
+            .clamp(1, MAX_RESULTS_LIMIT);
+
+        let _span = info_span!("search", pattern = %pattern, path = %path);
+        let timeout = token.limits.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        let max_output = token.limits.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT);
+        // A policy `[workspace]` root on the token takes precedence.
+        let root = token
+            .workspace_root
+            .clone()
+            .unwrap_or_else(|| self.workspace_root.clone());
+        let path = match path {
+            "." => None,
+            path => {
+                resolve_workspace_path(&root, path)?;
+                Some(path)
+            }
+        };
+
+        let (program, args) = self.program.argv(pattern, path, include, ignore_case);
+        let argv: Vec<&str> = args.iter().map(String::as_str).collect();
+        let scratch = ScratchDir::create()?;
+        let mut cmd = bash::command_for(&token, &root, None, &scratch.path, program, &argv);
+        cmd.current_dir(&root);
+        let result = bash::run(cmd, &token, &root, scratch, None, timeout, max_output).await?;
+
+        // Both programs exit 1 for "no matches" and 2 for errors.
+        let output = match result.status.as_ref().map(|s| (s.exit_code, s.timed_out)) {
+            Some((Some(0), false)) => limit_results(&result.output, max_results),
+            Some((Some(1), false)) => "No matches found.".to_owned(),
+            _ => return Ok(result),
+        };
+        Ok(ToolResult { output, ..result })
+    }
+}
+
+impl SearchProgram {
+    /// Program name and arguments for one search. Without a path both search
+    /// the working directory and print paths relative to it.
+    fn argv(
+        self,
+        pattern: &str,
+        path: Option<&str>,
+        include: Option<&str>,
+        ignore_case: bool,
+    ) -> (&'static str, Vec<String>) {
+        let mut args: Vec<String> = match self {
+            SearchProgram::Ripgrep => ["--line-number", "--no-heading", "--color=never"]
+                .map(String::from)
+                .to_vec(),
+            // -I skips binary files; -r does not follow symlinks.
+            SearchProgram::Grep => ["-rnI", "-E", "--color=never"].map(String::from).to_vec(),
+        };
+        if ignore_case {
+            args.push("-i".to_owned());
+        }
+        if let Some(glob) = include {
+            args.push(match self {
+                SearchProgram::Ripgrep => format!("--glob={glob}"),
+                SearchProgram::Grep => format!("--include={glob}"),
+            });
+        }
+        args.extend(["-e".to_owned(), pattern.to_owned(), "--".to_owned()]);
+        args.extend(path.map(str::to_owned));
+        let program = match self {
+            SearchProgram::Ripgrep => "rg",
+            SearchProgram::Grep => "grep",
+        };
+        (program, args)
+    }
+}
+
+/// Keep the first `max` match lines, each cut to `MAX_LINE_CHARS`, and note
+/// how many were dropped.
+fn limit_results(output: &str, max: usize) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    let mut out: Vec<String> = lines
+        .iter()
+        .take(max)
+        .map(|line| match line.char_indices().nth(MAX_LINE_CHARS) {
+            Some((end, _)) => format!("{}...", &line[..end]),
+            None => (*line).to_owned(),
+        })
+        .collect();
+    if lines.len() > max {
+        out.push(format!(
+            "[... {} more matches truncated; narrow the pattern or path]",
+            lines.len() - max
+        ));
+    }
+    out.join("\n")
+}
+
+/// Whether an executable named `program` (`program.exe` on Windows) is in one
+/// of the `PATH` directories.
+fn on_path(program: &str) -> bool {
+    let file = format!("{program}{}", std::env::consts::EXE_SUFFIX);
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| is_executable(&dir.join(&file)))
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+/// Windows has no execute bit; the `.exe` name is what makes it runnable.
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn allow_token() -> CapabilityToken {
+        use crate::enforcement::{self, policy::Policy};
+        use crate::tools::{Proposed, ToolInvocation};
+        use std::str::FromStr;
+
+        let policy_str = r#"
+[tools.search]
+enabled = true
+match_source = "field"
+match_field = "pattern"
+
+[tools.search.actions.any]
+tier = "observe"
+patterns = ["^"]
+"#;
+        let policy = Policy::from_str(policy_str).unwrap();
+        let proposal =
+            ToolInvocation::<Proposed>::new("search", "execute", json!({"pattern": "x"}));
+        let (_, decision) = enforcement::evaluate(proposal, &policy, None);
+        match decision {
+            enforcement::Decision::Allow(token) => token,
+            _ => panic!("expected Allow"),
+        }
+    }
+
+    fn grep_tool(dir: &Path) -> SearchTool {
+        SearchTool {
+            workspace_root: dir.to_path_buf(),
+            program: SearchProgram::Grep,
+        }
+    }
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "fn alpha() {}\nfn beta() {}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.md"), "alpha notes\n").unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn finds_matches_under_path() {
+        let dir = workspace();
+        let params = json!({"pattern": "fn (alpha|beta)", "path": "src"});
+        let result = grep_tool(dir.path())
+            .execute(&params, allow_token())
+            .await
+            .unwrap();
+        assert_eq!(
+            result.output,
+            "src/lib.rs:1:fn alpha() {}\nsrc/lib.rs:2:fn beta() {}"
+        );
+    }
+
+    #[tokio::test]
+    async fn results_are_limited() {
+        let dir = workspace();
+        let params = json!({"pattern": "alpha", "include": "*.rs", "max_results": 1});
+        let result = grep_tool(dir.path())
+            .execute(&params, allow_token())
+            .await
+            .unwrap();
+        assert_eq!(result.output, "src/lib.rs:1:fn alpha() {}");
+
+        let params = json!({"pattern": "fn", "max_results": 1});
+        let result = grep_tool(dir.path())
+            .execute(&params, allow_token())
+            .await
+            .unwrap();
+        assert!(
+            result
+                .output
+                .ends_with("[... 1 more matches truncated; narrow the pattern or path]")
+        );
+    }
+
+    #[tokio::test]
+    async fn no_matches_is_not_an_error() {
+        let dir = workspace();
+        let params = json!({"pattern": "gamma"});
+        let result = grep_tool(dir.path())
+            .execute(&params, allow_token())
+            .await
+            .unwrap();
+        assert_eq!(result.output, "No matches found.");
+    }
+
+    #[tokio::test]
+    async fn path_outside_workspace_rejected() {
+        let dir = workspace();
+        for path in ["../", "/etc"] {
+            let params = json!({"pattern": "root", "path": path});
+            let result = grep_tool(dir.path()).execute(&params, allow_token()).await;
+            assert!(result.is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn pattern_is_never_an_option() {
+        let (program, args) =
+            SearchProgram::Ripgrep.argv("--pre=sh", Some("src"), Some("*.rs"), true);
+        assert_eq!(program, "rg");
+        assert_eq!(
+            args,
+            [
+                "--line-number",
+                "--no-heading",
+                "--color=never",
+                "-i",
+                "--glob=*.rs",
+                "-e",
+                "--pre=sh",
+                "--",
+                "src"
+            ]
+        );
+    }
+
+    #[test]
+    fn long_lines_are_cut() {
+        let line = format!("a.rs:1:{}", "x".repeat(MAX_LINE_CHARS + 10));
+        let out = limit_results(&line, 10);
+        assert_eq!(out.chars().count(), MAX_LINE_CHARS + 3);
+        assert!(out.ends_with("..."));
+    }
+}
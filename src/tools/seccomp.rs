@@ -0,0 +1,268 @@
+//! seccomp-bpf syscall filters for spawned commands.
+//!
+//! Defense in depth behind the regex policy: even if a command slips past the
+//! patterns (an interpreter, an alias, a compiled helper), the kernel refuses
+//! the syscalls its tier should never need. Denied syscalls fail with `EPERM`;
+//! the process keeps running and sees an ordinary "Operation not permitted".
+//!
+//! Enabled per tier with `[execution.<tier>] seccomp = true`. The shipped
+//! profiles (`default_denied`) can be replaced per tool with
+//! `[tools.<name>.seccomp]`. Commit is never filtered.
+
+use serde::{Deserialize, Serialize};
+
+use crate::enforcement::tier::Tier;
+use crate::error::CherubError;
+
+/// A syscall a profile can deny. Names match the kernel's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Syscall {
+    Connect,
+    Bind,
+    Listen,
+    Accept,
+    Accept4,
+    Unlink,
+    Unlinkat,
+    Rmdir,
+    Rename,
+    Renameat,
+    Renameat2,
+    Truncate,
+    Ftruncate,
+    Chmod,
+    Fchmod,
+    Fchmodat,
+    Chown,
+    Fchown,
+    Fchownat,
+    Lchown,
+    Ptrace,
+    Mount,
+    Umount2,
+}
+
+/// Shipped profile for `tier`: Observe may not touch the network or modify
+/// files, Act may not delete files or change permissions. Commit is unfiltered.
+pub fn default_denied(tier: Tier) -> &'static [Syscall] {
+    use Syscall::*;
+    const ACT: &[Syscall] = &[
+        Unlink, Unlinkat, Rmdir, Chmod, Fchmod, Fchmodat, Chown, Fchown, Fchownat, Lchown, Ptrace,
+        Mount, Umount2,
+    ];
+    const OBSERVE: &[Syscall] = &[
+        Connect, Bind, Listen, Accept, Accept4, Unlink, Unlinkat, Rmdir, Rename, Renameat,
+        Renameat2, Truncate, Ftruncate, Chmod, Fchmod, Fchmodat, Chown, Fchown, Fchownat, Lchown,
+        Ptrace, Mount, Umount2,
+    ];
+    match tier {
+        Tier::Observe => OBSERVE,
+        Tier::Act => ACT,
+        Tier::Commit => &[],
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod filter {
+    use super::Syscall;
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xC000_003E; // AUDIT_ARCH_X86_64
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xC000_00B7; // AUDIT_ARCH_AARCH64
+
+    // Offsets into `struct seccomp_data`.
+    const NR_OFFSET: u32 = 0;
+    const ARCH_OFFSET: u32 = 4;
+    // x32 syscalls on x86_64 carry this bit; they would bypass the numbers below.
+    const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+    impl Syscall {
+        /// The syscall number on this architecture, or `None` if it has no such
+        /// syscall (aarch64 only has the `*at` forms).
+        fn nr(self) -> Option<libc::c_long> {
+            Some(match self {
+                Syscall::Connect => libc::SYS_connect,
+                Syscall::Bind => libc::SYS_bind,
+                Syscall::Listen => libc::SYS_listen,
+                #[cfg(target_arch = "x86_64")]
+                Syscall::Accept => libc::SYS_accept,
+                Syscall::Accept4 => libc::SYS_accept4,
+                #[cfg(target_arch = "x86_64")]
+                Syscall::Unlink => libc::SYS_unlink,
+                Syscall::Unlinkat => libc::SYS_unlinkat,
+                #[cfg(target_arch = "x86_64")]
+                Syscall::Rmdir => libc::SYS_rmdir,
+                #[cfg(target_arch = "x86_64")]
+                Syscall::Rename => libc::SYS_rename,
+                #[cfg(target_arch = "x86_64")]
+                Syscall::Renameat => libc::SYS_renameat,
+                Syscall::Renameat2 => libc::SYS_renameat2,
+                Syscall::Truncate => libc::SYS_truncate,
+                Syscall::Ftruncate => libc::SYS_ftruncate,
+                #[cfg(target_arch = "x86_64")]
+                Syscall::Chmod => libc::SYS_chmod,
+                Syscall::Fchmod => libc::SYS_fchmod,
+                Syscall::Fchmodat => libc::SYS_fchmodat,
+                #[cfg(target_arch = "x86_64")]
+                Syscall::Chown => libc::SYS_chown,
+                Syscall::Fchown => libc::SYS_fchown,
+                Syscall::Fchownat => libc::SYS_fchownat,
+                #[cfg(target_arch = "x86_64")]
+                Syscall::Lchown => libc::SYS_lchown,
+                Syscall::Ptrace => libc::SYS_ptrace,
+                Syscall::Mount => libc::SYS_mount,
+                Syscall::Umount2 => libc::SYS_umount2,
+                #[allow(unreachable_patterns)]
+                _ => return None,
+            })
+        }
+    }
+
+    fn stmt(code: u32, k: u32) -> libc::sock_filter {
+        libc::sock_filter {
+            code: code as u16,
+            jt: 0,
+            jf: 0,
+            k,
+        }
+    }
+
+    fn jump(code: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+        libc::sock_filter {
+            code: code as u16,
+            jt,
+            jf,
+            k,
+        }
+    }
+
+    /// BPF program returning `EPERM` for `denied` and allowing everything else.
+    /// Any other architecture's syscall ABI kills the process.
+    pub(super) fn program(denied: &[Syscall]) -> Vec<libc::sock_filter> {
+        use libc::{BPF_ABS, BPF_JEQ, BPF_JGE, BPF_JMP, BPF_K, BPF_LD, BPF_RET, BPF_W};
+        let errno = libc::SECCOMP_RET_ERRNO | (libc::EPERM as u32 & libc::SECCOMP_RET_DATA);
+
+        let mut numbers: Vec<u32> = denied
+            .iter()
+            .filter_map(|s| s.nr())
+            .filter_map(|nr| u32::try_from(nr).ok())
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+
+        let mut prog = vec![
+            stmt(BPF_LD | BPF_W | BPF_ABS, ARCH_OFFSET),
+            jump(BPF_JMP | BPF_JEQ | BPF_K, AUDIT_ARCH, 1, 0),
+            stmt(BPF_RET | BPF_K, libc::SECCOMP_RET_KILL_PROCESS),
+            stmt(BPF_LD | BPF_W | BPF_ABS, NR_OFFSET),
+            jump(BPF_JMP | BPF_JGE | BPF_K, X32_SYSCALL_BIT, 0, 1),
+            stmt(BPF_RET | BPF_K, errno),
+        ];
+        for nr in numbers {
+            prog.push(jump(BPF_JMP | BPF_JEQ | BPF_K, nr, 0, 1));
+            prog.push(stmt(BPF_RET | BPF_K, errno));
+        }
+        prog.push(stmt(BPF_RET | BPF_K, libc::SECCOMP_RET_ALLOW));
+        prog
+    }
+
+    /// Install `prog` on the calling thread. Runs in the forked child between
+    /// `fork` and `exec`, so it only makes raw syscalls and never allocates.
+    pub(super) fn install(prog: &mut [libc::sock_filter]) -> std::io::Result<()> {
+        let fprog = libc::sock_fprog {
+            len: prog.len() as libc::c_ushort,
+            filter: prog.as_mut_ptr(),
+        };
+        // SAFETY: `prctl` with these options reads only `fprog`, which points at
+        // `prog` and outlives both calls. No_new_privs is required for an
+        // unprivileged process to install a filter.
+        let installed = unsafe {
+            libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) == 0
+                && libc::prctl(
+                    libc::PR_SET_SECCOMP,
+                    libc::SECCOMP_MODE_FILTER,
+                    &fprog as *const libc::sock_fprog,
+                ) == 0
+        };
+        if installed {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+}
+
+/// Make `cmd` install a filter denying `denied` just before it execs.
+/// No-op for an empty list.
+pub(crate) fn apply(
+    cmd: &mut tokio::process::Command,
+    denied: &[Syscall],
+) -> Result<(), CherubError> {
+    if denied.is_empty() {
+        return Ok(());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // Built here, before the fork: the child must not allocate.
+        let mut prog = filter::program(denied);
+        // SAFETY: the closure runs in the child after `fork`. It only calls
+        // `prctl` on a buffer allocated before the fork (see `filter::install`).
+        unsafe {
+            cmd.pre_exec(move || filter::install(&mut prog));
+        }
+        Ok(())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = cmd;
+        Err(CherubError::ToolExecution(
+            "seccomp filtering requires Linux".to_owned(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commit_is_unfiltered_and_act_is_narrower_than_observe() {
+        assert!(default_denied(Tier::Commit).is_empty());
+        let observe = default_denied(Tier::Observe);
+        assert!(observe.contains(&Syscall::Connect));
+        assert!(!default_denied(Tier::Act).contains(&Syscall::Connect));
+        assert!(
+            default_denied(Tier::Act)
+                .iter()
+                .all(|s| observe.contains(s))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn denied_syscalls_fail_with_eperm() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("keep");
+        std::fs::write(&file, "x").unwrap();
+
+        let mut cmd = tokio::process::Command::new("rm");
+        cmd.arg(&file);
+        apply(&mut cmd, &[Syscall::Unlink, Syscall::Unlinkat]).unwrap();
+        let output = cmd.output().await.unwrap();
+        assert!(!output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("Operation not permitted"),
+            "{output:?}"
+        );
+        assert!(file.exists());
+
+        // Without a filter the same command succeeds.
+        let mut cmd = tokio::process::Command::new("rm");
+        cmd.arg(&file);
+        apply(&mut cmd, &[]).unwrap();
+        assert!(cmd.status().await.unwrap().success());
+        assert!(!file.exists());
+    }
+}
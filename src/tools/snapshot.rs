@@ -0,0 +1,235 @@
+//! Workspace snapshots around Act/Commit executions.
+//!
+//! With snapshots enabled (`ToolRegistry::with_snapshots`, `--snapshots`), the
+//! executor records the workspace before every Act or Commit tool call, and
+//! `rollback()` restores the most recent state that differs from the current
+//! one: modified and deleted files come back, files created since are removed.
+//!
+//! Snapshots are git trees in a private store outside the workspace
+//! (`git --git-dir <store> --work-tree <root>`), so the user's own repository,
+//! index, and stash are never touched. Files matched by `.gitignore` are
+//! neither recorded nor removed, and the contents of nested repositories are
+//! not recorded. Only files in the workspace are restored: network requests,
+//! processes, and paths outside the root are not undone.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::error::CherubError;
+
+/// Options placed before every git subcommand: no hooks, no fsmonitor, and no
+/// user or system config (which could name filters or other programs).
+const GIT_ARGS: &[&str] = &[
+    "-c",
+    "core.hooksPath=/dev/null",
+    "-c",
+    "core.fsmonitor=false",
+];
+
+/// Recorded workspace states, newest last.
+pub struct Snapshots {
+    store: PathBuf,
+    taken: Mutex<Vec<Snapshot>>,
+}
+
+/// One recorded state of a workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Git tree id of the recorded files.
+    pub tree: String,
+    /// Workspace root the tree was recorded from.
+    pub root: PathBuf,
+    /// The invocation the snapshot was taken before.
+    pub invocation_id: Uuid,
+    pub tool: String,
+}
+
+impl Snapshots {
+    /// Keep snapshots in `store`, a directory outside the workspace. Created
+    /// on first use.
+    pub fn new(store: PathBuf) -> Self {
+        Self {
+            store,
+            taken: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record `root` before `tool` runs for `invocation_id`. A state identical
+    /// to the newest snapshot of the same root is not recorded twice.
+    pub(crate) async fn snapshot(
+        &self,
+        root: &Path,
+        invocation_id: Uuid,
+        tool: &str,
+    ) -> Result<(), CherubError> {
+        let mut taken = self.taken.lock().await;
+        let tree = self.record(root).await?;
+        if taken
+            .last()
+            .is_some_and(|s| s.tree == tree && s.root == root)
+        {
+            return Ok(());
+        }
+        info!(%invocation_id, tool, tree = %tree, root = %root.display(), "workspace snapshot taken");
+        taken.push(Snapshot {
+            tree,
+            root: root.to_path_buf(),
+            invocation_id,
+            tool: tool.to_owned(),
+        });
+        Ok(())
+    }
+
+    /// Restore the newest snapshot that differs from the workspace as it is
+    /// now, discarding it and any newer ones. `None` if there is nothing to
+    /// roll back to.
+    pub async fn rollback(&self) -> Result<Option<Snapshot>, CherubError> {
+        let mut taken = self.taken.lock().await;
+        while let Some(snapshot) = taken.pop() {
+            // Records the current state too, so files created since the
+            // snapshot are in the index and get removed by the reset.
+            let current = self.record(&snapshot.root).await?;
+            if current == snapshot.tree {
+                continue;
+            }
+            self.git(
+                &snapshot.root,
+                &["read-tree", "-u", "--reset", &snapshot.tree],
+            )
+            .await?;
+            info!(
+                invocation_id = %snapshot.invocation_id,
+                tool = %snapshot.tool,
+                tree = %snapshot.tree,
+                root = %snapshot.root.display(),
+                "workspace rolled back"
+            );
+            return Ok(Some(snapshot));
+        }
+        Ok(None)
+    }
+
+    /// Stage every non-ignored file under `root` and return the tree id.
+    async fn record(&self, root: &Path) -> Result<String, CherubError> {
+        if !self.store.join("HEAD").exists() {
+            let mut init = git();
+            init.args(["init", "-q", "--bare"]).arg(&self.store);
+            run(init, "init").await?;
+        }
+        self.git(root, &["add", "-A"]).await?;
+        self.git(root, &["write-tree"]).await
+    }
+
+    /// Run git against the store with `root` as the work tree; trimmed stdout.
+    async fn git(&self, root: &Path, args: &[&str]) -> Result<String, CherubError> {
+        let mut cmd = git();
+        cmd.arg("--git-dir")
+            .arg(&self.store)
+            .arg("--work-tree")
+            .arg(root)
+            .args(args)
+            .current_dir(root);
+        run(cmd, args.first().copied().unwrap_or_default()).await
+    }
+}
+
+/// `git` with the base environment only, no user or system config, and
+/// `GIT_ARGS`.
+fn git() -> Command {
+    let mut cmd = Command::new("git");
+    cmd.env_clear()
+        .envs(super::env::allowlisted(&[]))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .args(GIT_ARGS)
+        .stdin(Stdio::null());
+    cmd
+}
+
+/// Run `cmd` (the `subcommand` step) to completion; trimmed stdout.
+async fn run(mut cmd: Command, subcommand: &str) -> Result<String, CherubError> {
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| CherubError::ToolExecution(format!("workspace snapshot: {e}")))?;
+    if !output.status.success() {
+        return Err(CherubError::ToolExecution(format!(
+            "workspace snapshot: git {subcommand} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rollback_restores_modified_deleted_and_created_files() {
+        let store = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("a.txt"), "original").unwrap();
+        std::fs::write(root.join("src/b.txt"), "keep me").unwrap();
+        std::fs::write(root.join(".gitignore"), "build/\n").unwrap();
+        std::fs::create_dir(root.join("build")).unwrap();
+        std::fs::write(root.join("build/out"), "artifact").unwrap();
+
+        let snapshots = Snapshots::new(store.path().join("snapshots"));
+        let id = Uuid::now_v7();
+        snapshots.snapshot(root, id, "bash").await.unwrap();
+
+        std::fs::write(root.join("a.txt"), "clobbered").unwrap();
+        std::fs::remove_file(root.join("src/b.txt")).unwrap();
+        std::fs::create_dir(root.join("new")).unwrap();
+        std::fs::write(root.join("new/c.txt"), "stray").unwrap();
+        std::fs::write(root.join("build/out"), "rebuilt").unwrap();
+
+        let restored = snapshots.rollback().await.unwrap().unwrap();
+        assert_eq!(restored.invocation_id, id);
+        assert_eq!(
+            std::fs::read_to_string(root.join("a.txt")).unwrap(),
+            "original"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/b.txt")).unwrap(),
+            "keep me"
+        );
+        assert!(!root.join("new").exists());
+        // Ignored files are left as they are.
+        assert_eq!(
+            std::fs::read_to_string(root.join("build/out")).unwrap(),
+            "rebuilt"
+        );
+        assert_eq!(snapshots.rollback().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn rollback_skips_snapshots_equal_to_current_state() {
+        let store = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        std::fs::write(root.join("a.txt"), "v1").unwrap();
+
+        let snapshots = Snapshots::new(store.path().join("snapshots"));
+        let first = Uuid::now_v7();
+        snapshots.snapshot(root, first, "patch").await.unwrap();
+        std::fs::write(root.join("a.txt"), "v2").unwrap();
+        // A call that changes nothing: its snapshot equals the current state.
+        snapshots
+            .snapshot(root, Uuid::now_v7(), "http")
+            .await
+            .unwrap();
+
+        let restored = snapshots.rollback().await.unwrap().unwrap();
+        assert_eq!(restored.invocation_id, first);
+        assert_eq!(std::fs::read_to_string(root.join("a.txt")).unwrap(), "v1");
+    }
+}
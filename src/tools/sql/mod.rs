@@ -0,0 +1,46 @@
+//! SQL tool: run queries against databases named in policy.
+//!
+//! Databases are configured per tool under `[tools.<name>.databases]` and
+//! carried in the capability token, so the agent only ever names one:
+//! `{"database": "app", "query": "SELECT ..."}`. Backends are feature-gated:
+//! SQLite with `sqlite`, PostgreSQL with `postgres`.
+//!
+//! Uses `MatchSource::Sql` for enforcement: each statement is classified by
+//! its verb (`enforcement::sql`), so the default patterns tier `select` as
+//! Observe, `insert`/`update` as Act, and `drop`/`truncate`/`alter` as Commit.
+//! As a backstop for side effects a verb does not show (`SELECT ... INTO`,
+//! functions that write), Observe-tier queries run in a read-only transaction.
+//!
+//! All statements of one call run in a single transaction: it commits only if
+//! every statement succeeds. Results are tab-separated with a header row, at
+//! most `MAX_ROWS` rows per statement.
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+mod tool;
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub use tool::SqlTool;
+
+/// A database the SQL tool may connect to, from `[tools.<name>.databases]`.
+/// Carried in capability tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Database {
+    /// SQLite file; a relative path is resolved against the workspace root.
+    Sqlite { path: PathBuf },
+    /// PostgreSQL connection string (`host=... user=... dbname=...` or a
+    /// `postgresql://` URL), with the password read from `password_env` at
+    /// connection time so it never sits in the policy file.
+    Postgres {
+        config: String,
+        password_env: Option<String>,
+    },
+}
@@ -0,0 +1,100 @@
+//! PostgreSQL backend. Each call opens its own connection, runs the query
+//! with the simple query protocol inside one transaction, and closes it.
+//! Connections are unencrypted: use a local socket or a tunnel for remote
+//! servers.
+
+use std::time::Duration;
+
+use tokio_postgres::{NoTls, SimpleQueryMessage};
+use tracing::warn;
+
+use crate::error::CherubError;
+
+use super::tool::{MAX_ROWS, StatementResult};
+
+/// Run `query` in one transaction, read-only if `read_only`. The password, if
+/// any, is read from the `password_env` environment variable.
+pub(super) async fn run(
+    config: &str,
+    password_env: Option<&str>,
+    query: &str,
+    read_only: bool,
+    timeout: Duration,
+) -> Result<Vec<StatementResult>, CherubError> {
+    let mut config: tokio_postgres::Config = config
+        .parse()
+        .map_err(|e| CherubError::ToolExecution(format!("sql: invalid connection config: {e}")))?;
+    if let Some(var) = password_env {
+        let password = std::env::var(var).map_err(|_| {
+            CherubError::ToolExecution(format!("sql: password variable {var} is not set"))
+        })?;
+        config.password(password);
+    }
+    config.connect_timeout(timeout);
+
+    let run = async {
+        let (mut client, connection) = config.connect(NoTls).await.map_err(sql_error)?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                warn!(error = %e, "sql: postgres connection error");
+            }
+        });
+        let tx = client
+            .build_transaction()
+            .read_only(read_only)
+            .start()
+            .await
+            .map_err(sql_error)?;
+        tx.batch_execute(&format!(
+            "SET LOCAL statement_timeout = {}",
+            timeout.as_millis()
+        ))
+        .await
+        .map_err(sql_error)?;
+        let messages = tx.simple_query(query).await.map_err(sql_error)?;
+        tx.commit().await.map_err(sql_error)?;
+        Ok(collect(messages))
+    };
+    tokio::time::timeout(timeout, run).await.map_err(|_| {
+        CherubError::ToolExecution(format!("sql: query timed out after {}s", timeout.as_secs()))
+    })?
+}
+
+/// Group simple-query messages into one result per statement.
+fn collect(messages: Vec<SimpleQueryMessage>) -> Vec<StatementResult> {
+    let mut results = Vec::new();
+    let mut current: Option<StatementResult> = None;
+    for message in messages {
+        match message {
+            SimpleQueryMessage::RowDescription(columns) => {
+                current = Some(StatementResult::Rows {
+                    columns: columns.iter().map(|c| c.name().to_owned()).collect(),
+                    rows: Vec::new(),
+                    omitted: 0,
+                });
+            }
+            SimpleQueryMessage::Row(row) => {
+                if let Some(StatementResult::Rows { rows, omitted, .. }) = current.as_mut() {
+                    if rows.len() == MAX_ROWS {
+                        *omitted += 1;
+                    } else {
+                        rows.push(
+                            (0..row.len())
+                                .map(|i| row.get(i).map(str::to_owned))
+                                .collect(),
+                        );
+                    }
+                }
+            }
+            SimpleQueryMessage::CommandComplete(n) => {
+                results.push(current.take().unwrap_or(StatementResult::Affected(n)));
+            }
+            _ => {}
+        }
+    }
+    results
+}
+
+fn sql_error(e: tokio_postgres::Error) -> CherubError {
+    CherubError::ToolExecution(format!("sql: {e}"))
+}
@@ -0,0 +1,221 @@
+//! SQLite backend. rusqlite is synchronous, so queries run on the blocking
+//! pool; the timeout, or dropping the future when the turn is cancelled,
+//! interrupts the running statement.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rusqlite::fallible_iterator::FallibleIterator;
+use rusqlite::limits::Limit;
+use rusqlite::types::ValueRef;
+use rusqlite::{Batch, Connection, InterruptHandle, OpenFlags};
+
+use crate::error::CherubError;
+
+use super::tool::{MAX_ROWS, StatementResult};
+
+/// Run `query` against the database at `path` in one transaction. With
+/// `read_only` the file is opened read-only; otherwise it is created if missing.
+pub(super) async fn run(
+    path: PathBuf,
+    query: &str,
+    read_only: bool,
+    timeout: Duration,
+) -> Result<Vec<StatementResult>, CherubError> {
+    let flags = if read_only {
+        OpenFlags::SQLITE_OPEN_READ_ONLY
+    } else {
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE
+    };
+    let conn = Connection::open_with_flags(&path, flags | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(sql_error)?;
+    // ATTACH would reach files the policy never named.
+    conn.set_limit(Limit::SQLITE_LIMIT_ATTACHED, 0)
+        .map_err(sql_error)?;
+    // Dropping the task's handle does not stop it. Interrupt the statement
+    // instead, on timeout and when this future is dropped mid-query: it fails
+    // and its transaction rolls back. Once the task has finished the
+    // connection is closed and the interrupt is a no-op.
+    let _interrupt = InterruptOnDrop(conn.get_interrupt_handle());
+    let query = query.to_owned();
+    let task = tokio::task::spawn_blocking(move || run_blocking(conn, &query));
+    match tokio::time::timeout(timeout, task).await {
+        Ok(joined) => joined
+            .map_err(|e| CherubError::ToolExecution(format!("sql: query task failed: {e}")))?,
+        Err(_) => Err(CherubError::ToolExecution(format!(
+            "sql: query timed out after {}s",
+            timeout.as_secs()
+        ))),
+    }
+}
+
+/// Interrupts the connection's running statement when dropped.
+struct InterruptOnDrop(InterruptHandle);
+
+impl Drop for InterruptOnDrop {
+    fn drop(&mut self) {
+        self.0.interrupt();
+    }
+}
+
+fn run_blocking(mut conn: Connection, query: &str) -> Result<Vec<StatementResult>, CherubError> {
+    let tx = conn.transaction().map_err(sql_error)?;
+    let mut results = Vec::new();
+    {
+        let mut batch = Batch::new(&tx, query);
+        while let Some(mut stmt) = batch.next().map_err(sql_error)? {
+            if stmt.column_count() == 0 {
+                results.push(StatementResult::Affected(
+                    stmt.raw_execute().map_err(sql_error)? as u64,
+                ));
+                continue;
+            }
+            let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+            let mut rows = Vec::new();
+            let mut omitted = 0;
+            let mut cursor = stmt.raw_query();
+            while let Some(row) = cursor.next().map_err(sql_error)? {
+                if rows.len() == MAX_ROWS {
+                    omitted += 1;
+                    continue;
+                }
+                let values = (0..columns.len())
+                    .map(|i| row.get_ref(i).map(value_text))
+                    .collect::<Result<_, _>>()
+                    .map_err(sql_error)?;
+                rows.push(values);
+            }
+            results.push(StatementResult::Rows {
+                columns,
+                rows,
+                omitted,
+            });
+        }
+    }
+    tx.commit().map_err(sql_error)?;
+    Ok(results)
+}
+
+fn value_text(value: ValueRef<'_>) -> Option<String> {
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(i) => Some(i.to_string()),
+        ValueRef::Real(f) => Some(f.to_string()),
+        ValueRef::Text(t) => Some(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => Some(format!("<blob, {} bytes>", b.len())),
+    }
+}
+
+fn sql_error(e: rusqlite::Error) -> CherubError {
+    CherubError::ToolExecution(format!("sql: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[tokio::test]
+    async fn statements_run_in_one_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        let results = run(
+            path.clone(),
+            "CREATE TABLE t (id INTEGER, name TEXT); \
+             INSERT INTO t VALUES (1, 'a'), (2, NULL); \
+             SELECT * FROM t ORDER BY id",
+            false,
+            TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results[1], StatementResult::Affected(2));
+        assert_eq!(
+            results[2],
+            StatementResult::Rows {
+                columns: vec!["id".to_owned(), "name".to_owned()],
+                rows: vec![
+                    vec![Some("1".to_owned()), Some("a".to_owned())],
+                    vec![Some("2".to_owned()), None],
+                ],
+                omitted: 0,
+            }
+        );
+
+        // A failing statement rolls back the ones before it.
+        let failed = run(
+            path.clone(),
+            "INSERT INTO t VALUES (3, 'c'); INSERT INTO missing VALUES (1)",
+            false,
+            TIMEOUT,
+        )
+        .await;
+        assert!(failed.is_err());
+        let count = run(path, "SELECT count(*) FROM t", true, TIMEOUT)
+            .await
+            .unwrap();
+        assert!(
+            matches!(&count[0], StatementResult::Rows { rows, .. } if rows[0][0].as_deref() == Some("2"))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_only_refuses_writes_and_attach() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        run(path.clone(), "CREATE TABLE t (id INTEGER)", false, TIMEOUT)
+            .await
+            .unwrap();
+
+        assert!(
+            run(path.clone(), "INSERT INTO t VALUES (1)", true, TIMEOUT)
+                .await
+                .is_err()
+        );
+        let other = dir.path().join("other.db");
+        let attach = format!("ATTACH DATABASE '{}' AS other", other.display());
+        assert!(run(path.clone(), &attach, false, TIMEOUT).await.is_err());
+        // Read-only never creates a database.
+        assert!(
+            run(dir.path().join("new.db"), "SELECT 1", true, TIMEOUT)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn dropping_the_future_interrupts_and_rolls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        run(path.clone(), "CREATE TABLE t (id INTEGER)", false, TIMEOUT)
+            .await
+            .unwrap();
+
+        let endless = "INSERT INTO t VALUES (1); \
+             WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) \
+             SELECT count(*) FROM c";
+        let dropped = tokio::time::timeout(
+            Duration::from_millis(200),
+            run(path.clone(), endless, false, TIMEOUT),
+        )
+        .await;
+        assert!(dropped.is_err());
+
+        // The abandoned transaction holds the write lock until it rolls back.
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while run(path.clone(), "INSERT INTO t VALUES (2)", false, TIMEOUT)
+            .await
+            .is_err()
+        {
+            assert!(std::time::Instant::now() < deadline, "query never stopped");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let ids = run(path, "SELECT group_concat(id) FROM t", true, TIMEOUT)
+            .await
+            .unwrap();
+        assert!(
+            matches!(&ids[0], StatementResult::Rows { rows, .. } if rows[0][0].as_deref() == Some("2"))
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! `SqlTool`: runs a query against a token's database and formats the results.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tracing::{Instrument, info_span};
+
+use crate::enforcement::capability::CapabilityToken;
+use crate::enforcement::tier::Tier;
+use crate::error::CherubError;
+use crate::tools::ToolResult;
+
+use super::Database;
+#[cfg(feature = "postgres")]
+use super::postgres;
+#[cfg(feature = "sqlite")]
+use super::sqlite;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Rows returned per statement; the rest are counted but not shown.
+pub(super) const MAX_ROWS: usize = 500;
+/// Characters kept per value.
+const MAX_VALUE_CHARS: usize = 200;
+
+/// What one statement produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum StatementResult {
+    /// A statement that returns rows; `None` values are SQL NULL. `omitted`
+    /// rows beyond `MAX_ROWS` were not kept.
+    Rows {
+        columns: Vec<String>,
+        rows: Vec<Vec<Option<String>>>,
+        omitted: u64,
+    },
+    /// A statement without a result set, with the rows it changed.
+    Affected(u64),
+}
+
+pub struct SqlTool {
+    /// Where relative SQLite paths resolve when the token carries no root.
+    #[cfg(feature = "sqlite")]
+    workspace_root: PathBuf,
+}
+
+impl SqlTool {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        #[cfg(not(feature = "sqlite"))]
+        let _ = workspace_root; // Only SQLite databases are file paths.
+        Self {
+            #[cfg(feature = "sqlite")]
+            workspace_root,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError> {
+        token.check_tool("sql")?;
+        let name = params
+            .get("database")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                CherubError::InvalidInvocation("sql tool requires 'database'".to_owned())
+            })?;
+        let query = params
+            .get("query")
+            .and_then(|v| v.as_str())
+            .filter(|q| !q.trim().is_empty())
+            .ok_or_else(|| {
+                CherubError::InvalidInvocation("sql tool requires 'query'".to_owned())
+            })?;
+        let database = token.databases.get(name).ok_or_else(|| {
+            CherubError::InvalidInvocation(format!("sql tool: unknown database '{name}'"))
+        })?;
+
+        // Instrument, not entered(): EnteredSpan is !Send.
+        let span = info_span!("sql", database = %name, tier = ?token.tier);
+        let read_only = token.tier == Tier::Observe;
+        let timeout = token.limits.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        let results = async {
+            Ok::<_, CherubError>(match database {
+                #[cfg(feature = "sqlite")]
+                Database::Sqlite { path } => {
+                    // A policy `[workspace]` root on the token takes precedence.
+                    let root = token
+                        .workspace_root
+                        .as_ref()
+                        .unwrap_or(&self.workspace_root);
+                    sqlite::run(root.join(path), query, read_only, timeout).await?
+                }
+                #[cfg(feature = "postgres")]
+                Database::Postgres {
+                    config,
+                    password_env,
+                } => {
+                    postgres::run(config, password_env.as_deref(), query, read_only, timeout)
+                        .await?
+                }
+                #[allow(unreachable_patterns)]
+                _ => {
+                    return Err(CherubError::ToolExecution(format!(
+                        "sql tool: database '{name}' needs a backend this build does not include"
+                    )));
+                }
+            })
+        }
+        .instrument(span)
+        .await?;
+        Ok(ToolResult {
+            output: format_results(&results),
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
+    }
+}
+
+/// Tab-separated rows under a header per result set, results separated by a
+/// blank line.
+fn format_results(results: &[StatementResult]) -> String {
+    let blocks: Vec<String> = results
+        .iter()
+        .map(|result| match result {
+            StatementResult::Affected(n) => format!("{n} row(s) affected"),
+            StatementResult::Rows {
+                columns,
+                rows,
+                omitted,
+            } => {
+                let mut lines = vec![columns.join("\t")];
+                lines.extend(rows.iter().map(|row| {
+                    row.iter()
+                        .map(|v| v.as_deref().map_or("NULL".to_owned(), format_value))
+                        .collect::<Vec<_>>()
+                        .join("\t")
+                }));
+                let shown = rows.len() as u64;
+                lines.push(match omitted {
+                    0 => format!("({shown} row(s))"),
+                    n => format!("({} row(s), {shown} shown)", shown + n),
+                });
+                lines.join("\n")
+            }
+        })
+        .collect();
+    blocks.join("\n\n")
+}
+
+/// One value on one line: tabs and newlines escaped, long values cut short.
+fn format_value(value: &str) -> String {
+    let mut out: String = value
+        .chars()
+        .take(MAX_VALUE_CHARS)
+        .flat_map(|c| match c {
+            '\t' => vec!['\\', 't'],
+            '\n' => vec!['\\', 'n'],
+            '\r' => vec!['\\', 'r'],
+            c => vec![c],
+        })
+        .collect();
+    if value.chars().nth(MAX_VALUE_CHARS).is_some() {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_are_tab_separated_with_counts() {
+        let results = [
+            StatementResult::Rows {
+                columns: vec!["id".to_owned(), "name".to_owned()],
+                rows: vec![
+                    vec![Some("1".to_owned()), Some("a\tb".to_owned())],
+                    vec![Some("2".to_owned()), None],
+                ],
+                omitted: 3,
+            },
+            StatementResult::Affected(4),
+        ];
+        assert_eq!(
+            format_results(&results),
+            "id\tname\n1\ta\\tb\n2\tNULL\n(5 row(s), 2 shown)\n\n4 row(s) affected"
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn statements_tiered_by_verb_and_observe_is_read_only() {
+        use std::str::FromStr;
+
+        use serde_json::json;
+
+        use crate::enforcement::policy::Policy;
+        use crate::enforcement::{self, Decision};
+        use crate::tools::{ToolContext, ToolInvocation, ToolRegistry};
+
+        let dir = tempfile::tempdir().unwrap();
+        let policy = Policy::from_str(&format!(
+            r#"
+[tools.sql]
+enabled = true
+match_source = "sql"
+
+[tools.sql.databases.app]
+sqlite = "{}"
+
+[tools.sql.actions.read]
+tier = "observe"
+patterns = ["^select:app$"]
+
+[tools.sql.actions.write]
+tier = "act"
+patterns = ["^(insert|update|create):app$"]
+
+[tools.sql.actions.destructive]
+tier = "commit"
+patterns = ["^(delete|drop):app$"]
+"#,
+            dir.path().join("app.db").display()
+        ))
+        .unwrap();
+        let registry = ToolRegistry::new().with_sql();
+        let ctx = ToolContext {
+            user_id: "test".to_owned(),
+            session_id: uuid::Uuid::now_v7(),
+            turn_number: 0,
+        };
+        let evaluate = |query: &str| {
+            enforcement::evaluate(
+                ToolInvocation::new("sql", "execute", json!({"database": "app", "query": query})),
+                &policy,
+                None,
+            )
+        };
+
+        let (evaluated, decision) =
+            evaluate("CREATE TABLE t (id INTEGER); INSERT INTO t VALUES (1)");
+        let Decision::Allow(token) = decision else {
+            panic!("create/insert should be allowed");
+        };
+        assert_eq!(token.tier, Tier::Act);
+        evaluated.execute(token, &registry, &ctx).await.unwrap();
+
+        assert!(matches!(
+            evaluate("SELECT 1; DROP TABLE t").1,
+            Decision::Escalate { tier: Tier::Commit }
+        ));
+        assert!(matches!(
+            evaluate("PRAGMA writable_schema = 1").1,
+            Decision::Reject { .. }
+        ));
+
+        let (evaluated, decision) = evaluate("SELECT id FROM t");
+        let Decision::Allow(token) = decision else {
+            panic!("select should be allowed");
+        };
+        assert_eq!(token.tier, Tier::Observe);
+        let result = evaluated.execute(token, &registry, &ctx).await.unwrap();
+        assert_eq!(result.into_result().output, "id\n1\n(1 row(s))");
+
+        // A write the verb does not show still fails at Observe.
+        let (_, decision) = evaluate("SELECT id FROM t");
+        let Decision::Allow(token) = decision else {
+            panic!("select should be allowed");
+        };
+        let params = json!({"database": "app", "query": "INSERT INTO t VALUES (2)"});
+        assert!(
+            SqlTool::new(dir.path().to_path_buf())
+                .execute(&params, token)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Test doubles for embedders (feature `testing`).
+//!
+//! Both run in-process and never spawn anything, so an agent loop can be driven
+//! end to end in a unit test. Register them with
+//! `ToolRegistry::with_recording` / `with_fake`; a double replaces a built-in
+//! tool of the same name, so `"bash"` can be faked. Calls still pass through
+//! enforcement: the policy needs a `[tools.<name>]` section like any tool.
+//!
+//! - `RecordingTool` captures every call it receives (action, params, tier)
+//!   and answers with a fixed output.
+//! - `FakeTool` answers from a script of canned responses, in order.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::enforcement::capability::CapabilityToken;
+use crate::enforcement::tier::Tier;
+use crate::error::CherubError;
+
+use super::{ProcessStatus, ToolResult};
+
+/// One call received by a `RecordingTool`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedCall {
+    pub action: String,
+    pub params: serde_json::Value,
+    /// Tier of the capability token the call ran under.
+    pub tier: Tier,
+}
+
+/// Captures every call and returns `output`. Clones share the record, so keep
+/// one to inspect after handing the other to the registry.
+#[derive(Clone)]
+pub struct RecordingTool {
+    pub(crate) name: String,
+    output: String,
+    calls: Arc<Mutex<Vec<RecordedCall>>>,
+}
+
+impl RecordingTool {
+    /// A tool registered as `name` that answers every call with `output`.
+    pub fn new(name: &str, output: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            output: output.to_owned(),
+            calls: Arc::default(),
+        }
+    }
+
+    /// The calls received so far, oldest first.
+    pub fn recorded(&self) -> Vec<RecordedCall> {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub(super) fn execute(
+        &self,
+        params: &serde_json::Value,
+        token: CapabilityToken,
+    ) -> Result<ToolResult, CherubError> {
+        let call = RecordedCall {
+            action: token.inspect().action,
+            params: params.clone(),
+            tier: token.tier,
+        };
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(call);
+        Ok(ToolResult {
+            output: self.output.clone(),
+            status: None,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        })
+    }
+}
+
+/// A canned `FakeTool` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FakeResponse {
+    /// In-process success with this output.
+    Output(String),
+    /// A process that exited with `code`, with this output on stdout.
+    Exit { output: String, code: i32 },
+    /// A process killed at its time limit.
+    Timeout,
+    /// The tool failed to run (`CherubError::ToolExecution`).
+    Error(String),
+}
+
+/// Answers each call with the next scripted response; fails once the script
+/// runs out.
+pub struct FakeTool {
+    pub(crate) name: String,
+    script: Mutex<VecDeque<FakeResponse>>,
+}
+
+impl FakeTool {
+    /// A tool registered as `name` that replays `script` in order.
+    pub fn new(name: &str, script: impl IntoIterator<Item = FakeResponse>) -> Self {
+        Self {
+            name: name.to_owned(),
+            script: Mutex::new(script.into_iter().collect()),
+        }
+    }
+
+    pub(super) fn execute(&self) -> Result<ToolResult, CherubError> {
+        let next = self
+            .script
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front();
+        let process = |output: String, exit_code: Option<i32>, timed_out: bool| ToolResult {
+            status: Some(ProcessStatus {
+                exit_code,
+                timed_out,
+                stdout: output.clone(),
+                stderr: String::new(),
+                stdout_discarded: 0,
+                stderr_discarded: 0,
+                discarded_bytes: 0,
+                duration: Duration::ZERO,
+                cwd: PathBuf::new(),
+            }),
+            output,
+            artifacts: Vec::new(),
+            attachments: Vec::new(),
+        };
+        match next {
+            Some(FakeResponse::Output(output)) => Ok(ToolResult {
+                output,
+                status: None,
+                artifacts: Vec::new(),
+                attachments: Vec::new(),
+            }),
+            Some(FakeResponse::Exit { output, code }) => Ok(process(output, Some(code), false)),
+            Some(FakeResponse::Timeout) => Ok(process(String::new(), None, true)),
+            Some(FakeResponse::Error(message)) => Err(CherubError::ToolExecution(message)),
+            None => Err(CherubError::ToolExecution(format!(
+                "fake tool '{}': script exhausted",
+                self.name
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::enforcement::policy::Policy;
+    use crate::enforcement::{self, Decision};
+    use crate::tools::{ToolContext, ToolInvocation, ToolRegistry};
+
+    const POLICY: &str = r#"
+[tools.bash]
+enabled = true
+
+[tools.bash.actions.read]
+tier = "observe"
+patterns = ["^ls"]
+
+[tools.bash.actions.write]
+tier = "act"
+patterns = ["^touch "]
+"#;
+
+    fn ctx() -> ToolContext {
+        ToolContext {
+            user_id: "test".to_owned(),
+            session_id: uuid::Uuid::now_v7(),
+            turn_number: 0,
+        }
+    }
+
+    async fn run(registry: &ToolRegistry, command: &str) -> Result<ToolResult, CherubError> {
+        let policy = Policy::from_str(POLICY).unwrap();
+        let proposal = ToolInvocation::new("bash", "execute", json!({"command": command}));
+        let (evaluated, decision) = enforcement::evaluate(proposal, &policy, None);
+        let token = match decision {
+            Decision::Allow(token) => token,
+            Decision::Escalate { tier, .. } => {
+                enforcement::approve_escalation(tier, &evaluated, &policy)
+            }
+            Decision::Reject { reason, .. } => return Err(CherubError::Rejected(reason)),
+        };
+        evaluated
+            .execute(token, registry, &ctx())
+            .await
+            .map(|e| e.into_result())
+    }
+
+    #[tokio::test]
+    async fn recording_tool_replaces_bash_and_records_tiers() {
+        let recorder = RecordingTool::new("bash", "ok");
+        let registry = ToolRegistry::new().with_recording(recorder.clone());
+
+        assert_eq!(run(&registry, "ls -la").await.unwrap().output, "ok");
+        run(&registry, "touch marker").await.unwrap();
+        assert!(run(&registry, "rm -rf /").await.is_err());
+
+        let calls = recorder.recorded();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].params["command"], "ls -la");
+        assert_eq!(calls[0].tier, Tier::Observe);
+        assert_eq!(calls[1].tier, Tier::Act);
+        // Nothing actually ran.
+        assert!(!std::path::Path::new("marker").exists());
+    }
+
+    #[tokio::test]
+    async fn fake_tool_replays_script_then_fails() {
+        let registry = ToolRegistry::new().with_fake(FakeTool::new(
+            "bash",
+            [
+                FakeResponse::Output("file.txt".to_owned()),
+                FakeResponse::Exit {
+                    output: "no such file".to_owned(),
+                    code: 2,
+                },
+                FakeResponse::Error("spawn failed".to_owned()),
+            ],
+        ));
+
+        assert_eq!(run(&registry, "ls").await.unwrap().output, "file.txt");
+        let exited = run(&registry, "ls x").await.unwrap();
+        assert_eq!(exited.status.unwrap().exit_code, Some(2));
+        assert!(matches!(
+            run(&registry, "ls").await,
+            Err(CherubError::ToolExecution(m)) if m == "spawn failed"
+        ));
+        assert!(matches!(
+            run(&registry, "ls").await,
+            Err(CherubError::ToolExecution(m)) if m.contains("exhausted")
+        ));
+    }
+}
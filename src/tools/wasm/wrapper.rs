@@ -343,9 +343,10 @@ impl WasmTool {
     pub async fn execute(
         &self,
         params: &serde_json::Value,
-        _token: CapabilityToken, // consumed — proves enforcement ran
+        token: CapabilityToken, // consumed — proves enforcement ran
         user_id: &str,
     ) -> Result<ToolResult, CherubError> {
+        token.check_tool(&self.module.name)?;
         let module = Arc::clone(&self.module);
         let engine = self.runtime.engine.clone();
         let user_id = user_id.to_owned();
@@ -452,6 +453,9 @@ fn execute_in_sandbox(
     }
     Ok(ToolResult {
         output: response.output.unwrap_or_default(),
+        status: None,
+        artifacts: Vec::new(),
+        attachments: Vec::new(),
     })
 }
 
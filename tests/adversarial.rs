@@ -14,7 +14,7 @@ use async_trait::async_trait;
 
 use cherub::enforcement::policy::Policy;
 use cherub::error::CherubError;
-use cherub::providers::{ApiUsage, ContentBlock, Message, Provider, StopReason, ToolDefinition};
+use cherub::providers::{ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason};
 use cherub::runtime::AgentLoop;
 use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
 use cherub::runtime::output::NullSink;
@@ -40,9 +40,7 @@ impl MockProvider {
 impl Provider for MockProvider {
     async fn complete(
         &self,
-        _system: &str,
-        _messages: &[Message],
-        _tools: &[ToolDefinition],
+        _request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError> {
         let mut queue = self.responses.lock().unwrap();
         Ok((queue.pop_front().unwrap_or_else(end_turn), None))
@@ -366,6 +364,31 @@ async fn unknown_tool_rejected() {
     assert!(results[0].2);
 }
 
+#[tokio::test]
+async fn malformed_arguments_rejected_before_enforcement() {
+    // Provider could not parse the arguments and passed the raw string on.
+    let mut agent = make_agent(
+        vec![
+            tool_use_msg_raw("1", "bash", json!("{\"command\": \"ls")),
+            tool_use_msg_raw("2", "bash", json!(["ls"])),
+        ],
+        MockApprovalPolicy::AlwaysDeny,
+    );
+    agent.run_turn_text("test").await.unwrap();
+
+    let results = find_tool_results(agent.session_messages());
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[0].1,
+        "invalid tool invocation: tool arguments are not valid JSON"
+    );
+    assert_eq!(
+        results[1].1,
+        "invalid tool invocation: tool arguments must be a JSON object"
+    );
+    assert!(results.iter().all(|r| r.2));
+}
+
 // ===========================================================================
 // Multi-tool batching
 // ===========================================================================
@@ -925,7 +948,7 @@ mod compaction_memory {
     use cherub::enforcement::policy::Policy;
     use cherub::error::CherubError;
     use cherub::providers::{
-        ApiUsage, ContentBlock, Message, Provider, StopReason, ToolDefinition,
+        ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason,
     };
     use cherub::runtime::AgentLoop;
     use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
@@ -960,14 +983,12 @@ mod compaction_memory {
     impl Provider for CompactionProvider {
         async fn complete(
             &self,
-            _system: &str,
-            messages: &[Message],
-            _tools: &[ToolDefinition],
+            request: &CompletionRequest<'_>,
         ) -> Result<(Message, Option<ApiUsage>), CherubError> {
             let mut queue = self.responses.lock().unwrap();
             let msg = queue.pop_front().unwrap_or_else(end_turn);
 
-            let usage = if messages.len() > 10 {
+            let usage = if request.messages.len() > 10 {
                 Some(ApiUsage::new(160_000, 100))
             } else {
                 Some(ApiUsage::new(1_000, 100))
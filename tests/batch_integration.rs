@@ -0,0 +1,267 @@
+//! Integration tests for batch submission, polling, and result retrieval
+//! against the Anthropic Message Batches and OpenAI Batch APIs (wiremock).
+
+use std::time::Duration;
+
+use secrecy::SecretString;
+use serde_json::{Value, json};
+use wiremock::matchers::{body_partial_json, body_string_contains, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cherub::providers::anthropic::AnthropicProvider;
+use cherub::providers::batch::{BatchItem, BatchProvider, BatchState};
+use cherub::providers::openai::OpenAiProvider;
+use cherub::providers::{CompletionRequest, ContentBlock, Message};
+
+fn text_of(message: &Message) -> &str {
+    match message {
+        Message::Assistant { content, .. } => match &content[0] {
+            ContentBlock::Text { text } => text,
+            other => panic!("unexpected block: {other:?}"),
+        },
+        other => panic!("unexpected message: {other:?}"),
+    }
+}
+
+fn anthropic_batch(status: &str, processing: u32, results_url: Option<String>) -> Value {
+    json!({
+        "id": "msgbatch_01",
+        "type": "message_batch",
+        "processing_status": status,
+        "request_counts": {
+            "processing": processing,
+            "succeeded": 2 - processing.min(2),
+            "errored": 0,
+            "canceled": 0,
+            "expired": 0
+        },
+        "results_url": results_url
+    })
+}
+
+#[tokio::test]
+async fn anthropic_batch_round_trip() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/batches"))
+        .and(header("x-api-key", "test-key"))
+        .and(body_partial_json(json!({
+            "requests": [
+                {"custom_id": "case-1", "params": {"model": "claude-test", "max_tokens": 1024}},
+                {"custom_id": "case-2", "params": {"model": "claude-test", "max_tokens": 64}}
+            ]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(anthropic_batch(
+            "in_progress",
+            2,
+            None,
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(anthropic_batch(
+            "in_progress",
+            1,
+            None,
+        )))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    let results_url = format!("{}/v1/messages/batches/msgbatch_01/results", server.uri());
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(anthropic_batch(
+            "ended",
+            0,
+            Some(results_url),
+        )))
+        .mount(&server)
+        .await;
+    let results = [
+        json!({"custom_id": "case-2", "result": {"type": "errored", "error": {
+            "type": "error",
+            "error": {"type": "invalid_request_error", "message": "max_tokens too small"}
+        }}}),
+        json!({"custom_id": "case-1", "result": {"type": "succeeded", "message": {
+            "content": [{"type": "text", "text": "allowed"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 12, "output_tokens": 3}
+        }}}),
+        json!({"custom_id": "case-3", "result": {"type": "expired"}}),
+    ]
+    .iter()
+    .map(Value::to_string)
+    .collect::<Vec<_>>()
+    .join("\n");
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01/results"))
+        .and(header("x-api-key", "test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(results))
+        .mount(&server)
+        .await;
+
+    let provider = BatchProvider::Anthropic(
+        AnthropicProvider::new(SecretString::from("test-key"), "claude-test", 1024)
+            .unwrap()
+            .with_base_url(&server.uri()),
+    );
+    let first = [Message::user_text("ls -la")];
+    let second = [Message::user_text("rm -rf /")];
+    let items = [
+        BatchItem {
+            custom_id: "case-1",
+            request: CompletionRequest::new("", &first, &[]),
+        },
+        BatchItem {
+            custom_id: "case-2",
+            request: CompletionRequest::new("", &second, &[]).with_max_tokens(64),
+        },
+    ];
+
+    let job = provider.submit(&items).await.unwrap();
+    assert_eq!(job.state, BatchState::InProgress);
+    assert_eq!(job.pending, 2);
+
+    let job = provider
+        .wait(&job.id, Duration::from_millis(10))
+        .await
+        .unwrap();
+    assert_eq!(job.state, BatchState::Ended);
+    assert_eq!(job.succeeded, 2);
+
+    let results = provider.results(&job).await.unwrap();
+    assert_eq!(results.len(), 3);
+    let (message, usage) = results[1].outcome.as_ref().unwrap();
+    assert_eq!(results[1].custom_id, "case-1");
+    assert_eq!(text_of(message), "allowed");
+    assert_eq!(usage.unwrap().input_tokens, 12);
+    assert_eq!(
+        results[0].outcome.as_ref().unwrap_err(),
+        "API error: max_tokens too small"
+    );
+    assert_eq!(results[2].outcome.as_ref().unwrap_err(), "expired");
+}
+
+#[tokio::test]
+async fn openai_batch_round_trip() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/files"))
+        .and(header("authorization", "Bearer test-key"))
+        .and(body_string_contains("name=\"purpose\"\r\n\r\nbatch"))
+        .and(body_string_contains(
+            r#"{"custom_id":"case-1","method":"POST","url":"/v1/chat/completions","body":{"model":"gpt-test""#,
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "file-in"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/batches"))
+        .and(body_partial_json(json!({
+            "input_file_id": "file-in",
+            "endpoint": "/v1/chat/completions",
+            "completion_window": "24h"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "batch_01",
+            "status": "validating",
+            "request_counts": {"total": 0, "completed": 0, "failed": 0}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/batches/batch_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "batch_01",
+            "status": "completed",
+            "request_counts": {"total": 2, "completed": 1, "failed": 1},
+            "output_file_id": "file-out",
+            "error_file_id": "file-err"
+        })))
+        .mount(&server)
+        .await;
+    let output = json!({"custom_id": "case-1", "response": {"status_code": 200, "body": {
+        "choices": [{"message": {"content": "allowed", "tool_calls": null}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 12, "completion_tokens": 3}
+    }}, "error": null});
+    Mock::given(method("GET"))
+        .and(path("/files/file-out/content"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(format!("{output}\n")))
+        .mount(&server)
+        .await;
+    let failed = json!({"custom_id": "case-2", "response": {"status_code": 400, "body": {
+        "error": {"message": "bad request"}
+    }}, "error": null});
+    Mock::given(method("GET"))
+        .and(path("/files/file-err/content"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(format!("{failed}\n")))
+        .mount(&server)
+        .await;
+
+    let provider = BatchProvider::OpenAi(
+        OpenAiProvider::new(Some(SecretString::from("test-key")), "gpt-test", 1024)
+            .unwrap()
+            .with_base_url(server.uri()),
+    );
+    let first = [Message::user_text("ls -la")];
+    let second = [Message::user_text("rm -rf /")];
+    let items = [
+        BatchItem {
+            custom_id: "case-1",
+            request: CompletionRequest::new("", &first, &[]),
+        },
+        BatchItem {
+            custom_id: "case-2",
+            request: CompletionRequest::new("", &second, &[]),
+        },
+    ];
+
+    let job = provider.submit(&items).await.unwrap();
+    assert_eq!(job.state, BatchState::InProgress);
+    assert!(
+        provider.results(&job).await.is_err(),
+        "no results while in progress"
+    );
+
+    let job = provider
+        .wait(&job.id, Duration::from_millis(10))
+        .await
+        .unwrap();
+    assert_eq!((job.succeeded, job.failed, job.pending), (1, 1, 0));
+    let results = provider.results(&job).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(text_of(&results[0].outcome.as_ref().unwrap().0), "allowed");
+    let err = results[1].outcome.as_ref().unwrap_err();
+    assert!(err.starts_with("API error 400"), "{err}");
+}
+
+#[tokio::test]
+async fn failed_openai_batch_reports_why() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/batches/batch_02"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "batch_02",
+            "status": "failed",
+            "errors": {"data": [{"code": "invalid_json_line", "message": "line 3 is not JSON"}]}
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = BatchProvider::OpenAi(
+        OpenAiProvider::new(Some(SecretString::from("test-key")), "gpt-test", 1024)
+            .unwrap()
+            .with_base_url(server.uri()),
+    );
+    let job = provider.status("batch_02").await.unwrap();
+    assert_eq!(
+        job.state,
+        BatchState::Failed("line 3 is not JSON".to_owned())
+    );
+    let err = provider.results(&job).await.unwrap_err().to_string();
+    assert!(err.contains("line 3 is not JSON"), "{err}");
+}
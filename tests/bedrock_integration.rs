@@ -0,0 +1,130 @@
+//! Integration tests for the Bedrock Converse provider.
+//!
+//! Uses wiremock as the Bedrock runtime endpoint and checks the request is
+//! SigV4-signed and shaped for Converse.
+
+use secrecy::SecretString;
+use wiremock::matchers::{body_partial_json, header_regex, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cherub::providers::bedrock::BedrockProvider;
+use cherub::providers::sigv4::AwsCredentials;
+use cherub::providers::{CompletionRequest, ContentBlock, Message, Provider};
+
+fn test_provider(url: &str) -> BedrockProvider {
+    let credentials = AwsCredentials {
+        access_key_id: "AKIDTEST".to_owned(),
+        secret_access_key: SecretString::from("secret"),
+        session_token: None,
+    };
+    BedrockProvider::new(
+        credentials,
+        "us-east-1",
+        "anthropic.claude-3-haiku-20240307-v1:0",
+        1024,
+    )
+    .unwrap()
+    .with_endpoint(url.to_owned())
+}
+
+#[tokio::test]
+async fn converse_request_is_signed_and_parsed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse"))
+        .and(header_regex(
+            "authorization",
+            r"^AWS4-HMAC-SHA256 Credential=AKIDTEST/\d{8}/us-east-1/bedrock/aws4_request, SignedHeaders=host;x-amz-date, Signature=[0-9a-f]{64}$",
+        ))
+        .and(header_regex("x-amz-date", r"^\d{8}T\d{6}Z$"))
+        .and(body_partial_json(serde_json::json!({
+            "system": [{"text": "system"}],
+            "inferenceConfig": {"maxTokens": 1024},
+            "messages": [{"role": "user", "content": [{"text": "hello"}]}],
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"output":{"message":{"role":"assistant","content":[{"text":"hi"}]}},"stopReason":"end_turn","usage":{"inputTokens":5,"outputTokens":1,"totalTokens":6}}"#,
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (message, usage) = test_provider(&server.uri())
+        .complete(&CompletionRequest::new(
+            "system",
+            &[Message::user_text("hello")],
+            &[],
+        ))
+        .await
+        .unwrap();
+    let Message::Assistant { content, .. } = message else {
+        panic!("expected assistant message");
+    };
+    assert_eq!(
+        content,
+        [ContentBlock::Text {
+            text: "hi".to_owned()
+        }]
+    );
+    assert_eq!(usage.unwrap().input_tokens, 5);
+}
+
+#[tokio::test]
+async fn request_overrides_model_and_inference_config() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/model/amazon.nova-lite-v1%3A0/converse"))
+        .and(body_partial_json(serde_json::json!({
+            "inferenceConfig": {"maxTokens": 64, "temperature": 0.0, "stopSequences": ["END"]},
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"output":{"message":{"role":"assistant","content":[{"text":"hi"}]}},"stopReason":"end_turn"}"#,
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let messages = [Message::user_text("hello")];
+    let stop = ["END".to_owned()];
+    let request = CompletionRequest::new("system", &messages, &[])
+        .with_model("amazon.nova-lite-v1:0")
+        .with_max_tokens(64)
+        .with_temperature(0.0)
+        .with_stop_sequences(&stop);
+    let provider = test_provider(&server.uri());
+    provider.complete(&request).await.unwrap();
+    // The override is per call: the configured model is unchanged.
+    assert_eq!(
+        provider.model_name(),
+        "anthropic.claude-3-haiku-20240307-v1:0"
+    );
+}
+
+#[tokio::test]
+async fn access_denied_names_the_error_type() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(403)
+                .insert_header(
+                    "x-amzn-errortype",
+                    "AccessDeniedException:http://internal.amazon.com/",
+                )
+                .set_body_string(r#"{"message":"not authorized"}"#),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = test_provider(&server.uri())
+        .complete(&CompletionRequest::new(
+            "system",
+            &[Message::user_text("hello")],
+            &[],
+        ))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("403 AccessDeniedException"), "{err}");
+    assert!(err.contains("0 retries"), "{err}");
+}
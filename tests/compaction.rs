@@ -15,11 +15,12 @@ use async_trait::async_trait;
 
 use cherub::enforcement::policy::Policy;
 use cherub::error::CherubError;
-use cherub::providers::{ApiUsage, ContentBlock, Message, Provider, StopReason, ToolDefinition};
+use cherub::providers::{ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason};
 use cherub::runtime::AgentLoop;
 use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
 use cherub::runtime::output::NullSink;
 use cherub::runtime::session::Session;
+use cherub::runtime::tokens::estimate_tokens;
 use cherub::tools::ToolRegistry;
 
 // ---------------------------------------------------------------------------
@@ -42,9 +43,7 @@ impl MockProvider {
 impl Provider for MockProvider {
     async fn complete(
         &self,
-        _system: &str,
-        _messages: &[Message],
-        _tools: &[ToolDefinition],
+        _request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError> {
         let mut queue = self.responses.lock().unwrap();
         Ok((queue.pop_front().unwrap_or_else(end_turn), None))
@@ -76,15 +75,13 @@ impl HighUsageProvider {
 impl Provider for HighUsageProvider {
     async fn complete(
         &self,
-        _system: &str,
-        messages: &[Message],
-        _tools: &[ToolDefinition],
+        request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError> {
         let mut queue = self.responses.lock().unwrap();
         let msg = queue.pop_front().unwrap_or_else(end_turn);
 
         // Report high usage when conversation is long enough to trigger compaction.
-        let usage = if messages.len() > 10 {
+        let usage = if request.messages.len() > 10 {
             Some(ApiUsage::new(160_000, 100)) // Above 75% of 200k
         } else {
             Some(ApiUsage::new(1_000, 100))
@@ -500,6 +497,185 @@ async fn compaction_triggers_with_high_usage() {
     // Either way, no crash.
 }
 
+/// Usage reported by every call accumulates on the loop.
+#[tokio::test]
+async fn session_usage_sums_provider_calls() {
+    let provider = HighUsageProvider::new(Vec::new());
+    let policy = Policy::from_str(POLICY).unwrap();
+    let mut agent = AgentLoop::new(
+        policy,
+        Box::new(provider),
+        ToolRegistry::new(),
+        "test".to_owned(),
+        AutoApprove,
+        NullSink,
+        "test",
+    );
+    assert_eq!(agent.session_usage(), ApiUsage::default());
+
+    for i in 0..3 {
+        agent.run_turn_text(&format!("turn {i}")).await.unwrap();
+    }
+
+    assert_eq!(agent.session_usage(), ApiUsage::new(3_000, 300));
+}
+
+/// Cost accumulates at the pricing table's rates, and the ceiling stops the
+/// loop before the call that would go past it.
+#[tokio::test]
+async fn cost_ceiling_stops_further_calls() {
+    use cherub::providers::pricing::{ModelPricing, PricingTable};
+
+    let provider = HighUsageProvider::new(Vec::new());
+    let policy = Policy::from_str(POLICY).unwrap();
+    let mut agent = AgentLoop::new(
+        policy,
+        Box::new(provider),
+        ToolRegistry::new(),
+        "test".to_owned(),
+        AutoApprove,
+        NullSink,
+        "test",
+    );
+    let mut table = PricingTable::new();
+    table.insert(
+        "claude-test".to_owned(),
+        ModelPricing {
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+            cache_write_per_mtok: 0.0,
+            cache_read_per_mtok: 0.0,
+        },
+    );
+    agent.with_pricing_table(table);
+    agent.with_cost_ceiling(0.01);
+
+    // 1000 input + 100 output tokens per call = $0.0045.
+    for i in 0..3 {
+        agent.run_turn_text(&format!("turn {i}")).await.unwrap();
+    }
+    assert!((agent.session_cost_usd() - 0.0135).abs() < 1e-10);
+
+    let messages_before = agent.session_messages().len();
+    let err = agent.run_turn_text("turn 3").await.unwrap_err();
+    assert!(
+        matches!(err, CherubError::CostCeilingExceeded { ceiling_usd, .. } if ceiling_usd == 0.01),
+        "{err:?}"
+    );
+    assert_eq!(
+        agent.session_usage(),
+        ApiUsage::new(3_000, 300),
+        "no call was made"
+    );
+    assert_eq!(agent.session_messages().len(), messages_before + 1);
+}
+
+/// Reports the estimated input size as usage, so compaction sees the effect
+/// of eliding.
+struct EstimatingProvider {
+    responses: Mutex<VecDeque<Message>>,
+}
+
+#[async_trait]
+impl Provider for EstimatingProvider {
+    async fn complete(
+        &self,
+        request: &CompletionRequest<'_>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        let input = estimate_tokens(request.system, request.messages, request.tools);
+        let msg = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(end_turn);
+        Ok((msg, Some(ApiUsage::new(input, 10))))
+    }
+
+    fn model_name(&self) -> &str {
+        "test-model" // 100k window: compaction at 75k
+    }
+
+    fn max_output_tokens(&self) -> u32 {
+        4096
+    }
+}
+
+/// Large old tool outputs are elided before anything is summarized.
+#[tokio::test]
+async fn old_tool_outputs_elided_before_summarizing() {
+    // Each turn: one `seq` call (~16k tokens of output), then a final answer.
+    let mut responses = Vec::new();
+    for i in 0..6 {
+        responses.push(Message::Assistant {
+            content: vec![ContentBlock::ToolUse {
+                id: format!("t{i}"),
+                name: "bash".to_owned(),
+                input: json!({"command": "seq 1 10000"}),
+            }],
+            stop_reason: StopReason::ToolUse,
+        });
+        responses.push(end_turn());
+    }
+    let provider = EstimatingProvider {
+        responses: Mutex::new(VecDeque::from(responses)),
+    };
+    let policy = Policy::from_str(
+        r#"
+[tools.bash]
+enabled = true
+[tools.bash.actions.read]
+tier = "observe"
+patterns = ["^seq "]
+"#,
+    )
+    .unwrap();
+    let mut agent = AgentLoop::new(
+        policy,
+        Box::new(provider),
+        ToolRegistry::new(),
+        "test".to_owned(),
+        AutoApprove,
+        NullSink,
+        "test",
+    );
+
+    for i in 0..6 {
+        agent
+            .run_turn_text(&format!("count, round {i}"))
+            .await
+            .unwrap();
+    }
+
+    let messages = agent.session_messages();
+    let elided = messages
+        .iter()
+        .filter(|m| matches!(m, Message::ToolResult { content, .. } if content.starts_with("[tool output elided")))
+        .count();
+    assert!(elided > 0, "old tool outputs should be elided");
+    assert!(
+        !messages.iter().any(
+            |m| matches!(m, Message::User { content } if content.iter().any(|c|
+            matches!(c, cherub::providers::UserContent::Text(t) if t.contains("[Context Summary"))))
+        ),
+        "eliding was enough; nothing should be summarized"
+    );
+    // The most recent tool output is intact.
+    let last_result = messages
+        .iter()
+        .rev()
+        .find_map(|m| match m {
+            Message::ToolResult { content, .. } => Some(content),
+            _ => None,
+        })
+        .unwrap();
+    assert!(last_result.ends_with("10000\n") || last_result.ends_with("10000"));
+    assert!(
+        estimate_tokens("", messages, &[]) < 75_000,
+        "session should be back under the threshold"
+    );
+}
+
 // ===========================================================================
 // Tests: Memory flush during compaction (feature = "memory")
 // ===========================================================================
@@ -516,7 +692,7 @@ mod memory_flush {
     use cherub::enforcement::policy::Policy;
     use cherub::error::CherubError;
     use cherub::providers::{
-        ApiUsage, ContentBlock, Message, Provider, StopReason, ToolDefinition,
+        ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason,
     };
     use cherub::runtime::AgentLoop;
     use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
@@ -554,15 +730,13 @@ mod memory_flush {
     impl Provider for CompactionProvider {
         async fn complete(
             &self,
-            _system: &str,
-            messages: &[Message],
-            _tools: &[ToolDefinition],
+            request: &CompletionRequest<'_>,
         ) -> Result<(Message, Option<ApiUsage>), CherubError> {
             let mut queue = self.responses.lock().unwrap();
             let msg = queue.pop_front().unwrap_or_else(end_turn);
 
             // Report high usage when conversation is long enough to trigger compaction.
-            let usage = if messages.len() > 10 {
+            let usage = if request.messages.len() > 10 {
                 Some(ApiUsage::new(160_000, 100))
             } else {
                 Some(ApiUsage::new(1_000, 100))
@@ -23,6 +23,20 @@ use cherub::tools::container::{ContainerConfig, ContainerRuntime};
 
 // ─── Helpers ──────────────────────────────────────────────────────────────────
 
+/// A token for calling `tool` directly (bypassing `ToolInvocation::execute`).
+/// Tokens are always bound to an evaluated invocation; this one is bound to a
+/// throwaway proposal for `tool` that the empty policy rejects.
+fn approved_token(
+    tool: &str,
+    tier: cherub::enforcement::tier::Tier,
+) -> cherub::enforcement::capability::CapabilityToken {
+    use std::str::FromStr as _;
+    let policy = cherub::enforcement::policy::Policy::from_str("[tools]\n").expect("empty policy");
+    let proposal = cherub::tools::ToolInvocation::new(tool, "execute", serde_json::json!({}));
+    let (evaluated, _) = cherub::enforcement::evaluate(proposal, &policy, None);
+    cherub::enforcement::approve_escalation(tier, &evaluated, &policy)
+}
+
 fn python3_available() -> bool {
     std::process::Command::new("python3")
         .arg("--version")
@@ -445,7 +459,7 @@ fn registry_new_vs_no_bash() {
 #[tokio::test]
 #[ignore]
 async fn container_bash_docker_e2e() {
-    use cherub::enforcement::{self, tier::Tier};
+    use cherub::enforcement::tier::Tier;
     use cherub::tools::ToolContext;
     use cherub::tools::container::BollardRuntime;
     use uuid::Uuid;
@@ -467,7 +481,7 @@ async fn container_bash_docker_e2e() {
     let (bash_tool, _ipc_dir) =
         cherub::tools::container_bash::build(runtime as Arc<dyn ContainerRuntime>, workspace);
 
-    let token = enforcement::approve_escalation(Tier::Observe);
+    let token = approved_token("bash", Tier::Observe);
     let ctx = ToolContext {
         user_id: "test-user".to_owned(),
         session_id: Uuid::now_v7(),
@@ -485,7 +499,7 @@ async fn container_bash_docker_e2e() {
     assert_eq!(result.output.trim(), "hello");
 
     // Test 2: env should only show container-internal vars (not host secrets)
-    let token2 = enforcement::approve_escalation(Tier::Observe);
+    let token2 = approved_token("bash", Tier::Observe);
     let result2 = timeout(
         Duration::from_secs(30),
         bash_tool.execute(&serde_json::json!({"command": "env"}), token2, &ctx),
@@ -515,7 +529,7 @@ async fn container_bash_docker_e2e() {
     );
 
     // Test 3: policy file should not exist
-    let token3 = enforcement::approve_escalation(Tier::Observe);
+    let token3 = approved_token("bash", Tier::Observe);
     let result3 = timeout(
         Duration::from_secs(30),
         bash_tool.execute(
@@ -534,7 +548,7 @@ async fn container_bash_docker_e2e() {
     );
 
     // Test 4: workspace is visible (Cargo.toml should exist)
-    let token4 = enforcement::approve_escalation(Tier::Observe);
+    let token4 = approved_token("bash", Tier::Observe);
     let result4 = timeout(
         Duration::from_secs(30),
         bash_tool.execute(
@@ -553,7 +567,7 @@ async fn container_bash_docker_e2e() {
     );
 
     // Test 5: host filesystem not visible (host /etc/hostname differs from container)
-    let token5 = enforcement::approve_escalation(Tier::Observe);
+    let token5 = approved_token("bash", Tier::Observe);
     let result5 = timeout(
         Duration::from_secs(30),
         bash_tool.execute(
@@ -573,7 +587,7 @@ async fn container_bash_docker_e2e() {
     );
 
     // Test 6: build tools work (if image built with LANGUAGES=rust)
-    let token6 = enforcement::approve_escalation(Tier::Observe);
+    let token6 = approved_token("bash", Tier::Observe);
     let result6 = timeout(
         Duration::from_secs(30),
         bash_tool.execute(
@@ -22,7 +22,7 @@ use tokio::net::UnixListener;
 use tokio::process::Command;
 use tokio::time::{Duration, timeout};
 
-use cherub::enforcement::{self, tier::Tier};
+use cherub::enforcement::tier::Tier;
 use cherub::error::CherubError;
 use cherub::tools::ToolContext;
 use cherub::tools::container::ipc::{IpcTransport, RuntimeMessage, ToolMessage};
@@ -33,6 +33,20 @@ use uuid::Uuid;
 
 // ─── Helpers ──────────────────────────────────────────────────────────────────
 
+/// A token for calling `tool` directly (bypassing `ToolInvocation::execute`).
+/// Tokens are always bound to an evaluated invocation; this one is bound to a
+/// throwaway proposal for `tool` that the empty policy rejects.
+fn approved_token(
+    tool: &str,
+    tier: cherub::enforcement::tier::Tier,
+) -> cherub::enforcement::capability::CapabilityToken {
+    use std::str::FromStr as _;
+    let policy = cherub::enforcement::policy::Policy::from_str("[tools]\n").expect("empty policy");
+    let proposal = cherub::tools::ToolInvocation::new(tool, "execute", serde_json::json!({}));
+    let (evaluated, _) = cherub::enforcement::evaluate(proposal, &policy, None);
+    cherub::enforcement::approve_escalation(tier, &evaluated, &policy)
+}
+
 /// Returns true if `python3` is available on PATH.
 fn python3_available() -> bool {
     std::process::Command::new("python3")
@@ -373,7 +387,7 @@ async fn container_tool_execute_via_mock_runtime() {
 
     // Build a CapabilityToken via approve_escalation — the public enforcement API
     // for creating tokens in test/approval contexts.
-    let token = enforcement::approve_escalation(Tier::Observe);
+    let token = approved_token("test-tool", Tier::Observe);
     let ctx = ToolContext {
         user_id: "test-user".to_owned(),
         session_id: Uuid::now_v7(),
@@ -443,7 +457,7 @@ async fn container_tool_docker_text_analysis() {
     let caps = ContainerCapabilities::default();
     let tool = ContainerTool::new(metadata, runtime, caps, ipc_dir);
 
-    let token = enforcement::approve_escalation(Tier::Observe);
+    let token = approved_token("text-analysis", Tier::Observe);
     let ctx = ToolContext {
         user_id: "test-user".to_owned(),
         session_id: Uuid::now_v7(),
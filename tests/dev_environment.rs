@@ -19,7 +19,9 @@ use async_trait::async_trait;
 
 use cherub::enforcement::policy::Policy;
 use cherub::error::CherubError;
-use cherub::providers::{ApiUsage, ContentBlock, Message, Provider, StopReason, ToolDefinition};
+use cherub::providers::{
+    ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason, ToolDefinition,
+};
 use cherub::runtime::AgentLoop;
 use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
 use cherub::runtime::output::NullSink;
@@ -30,6 +32,20 @@ use cherub::tools::dev_environment::{
 
 // ─── Unit tests: language validation ────────────────────────────────────────
 
+/// A token for calling `tool` directly (bypassing `ToolInvocation::execute`).
+/// Tokens are always bound to an evaluated invocation; this one is bound to a
+/// throwaway proposal for `tool` that the empty policy rejects.
+fn approved_token(
+    tool: &str,
+    tier: cherub::enforcement::tier::Tier,
+) -> cherub::enforcement::capability::CapabilityToken {
+    use std::str::FromStr as _;
+    let policy = cherub::enforcement::policy::Policy::from_str("[tools]\n").expect("empty policy");
+    let proposal = cherub::tools::ToolInvocation::new(tool, "execute", serde_json::json!({}));
+    let (evaluated, _) = cherub::enforcement::evaluate(proposal, &policy, None);
+    cherub::enforcement::approve_escalation(tier, &evaluated, &policy)
+}
+
 #[test]
 fn validate_languages_accepts_known() {
     for lang in ALLOWED_LANGUAGES {
@@ -160,9 +176,7 @@ impl MockProvider {
 impl Provider for MockProvider {
     async fn complete(
         &self,
-        _system: &str,
-        _messages: &[Message],
-        _tools: &[ToolDefinition],
+        _request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError> {
         let mut queue = self.responses.lock().unwrap();
         Ok((queue.pop_front().unwrap_or_else(end_turn), None))
@@ -319,7 +333,7 @@ async fn enforcement_unknown_action_rejected() {
 #[tokio::test]
 #[ignore]
 async fn docker_build_and_verify() {
-    use cherub::enforcement::{self, tier::Tier};
+    use cherub::enforcement::tier::Tier;
     use cherub::tools::ToolContext;
     use cherub::tools::container::BollardRuntime;
     use cherub::tools::container::ContainerRuntime;
@@ -347,7 +361,7 @@ async fn docker_build_and_verify() {
     let dev_env = cherub::tools::dev_environment::DevEnvironmentTool::new(Arc::clone(&bash_tool));
 
     // Build with rust.
-    let token = enforcement::approve_escalation(Tier::Act);
+    let token = approved_token("dev_environment", Tier::Act);
     let result = timeout(
         Duration::from_secs(600),
         dev_env.execute(&json!({"action": "setup", "languages": ["rust"]}), token),
@@ -362,7 +376,7 @@ async fn docker_build_and_verify() {
     );
 
     // Verify the bash tool now uses the new image by running cargo --version.
-    let token2 = enforcement::approve_escalation(Tier::Observe);
+    let token2 = approved_token("bash", Tier::Observe);
     let ctx = ToolContext {
         user_id: "test-user".to_owned(),
         session_id: Uuid::now_v7(),
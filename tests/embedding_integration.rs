@@ -0,0 +1,96 @@
+//! Wiremock tests for the OpenAI-compatible embedding provider: batching,
+//! response ordering, dimension checks, and keyless local servers.
+
+#![cfg(feature = "memory")]
+
+use secrecy::SecretString;
+use serde_json::{Value, json};
+use wiremock::matchers::{header, header_exists, method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use cherub::error::CherubError;
+use cherub::storage::embedding::{EmbeddingProvider, OpenAiEmbeddingProvider};
+
+/// Answers with one `[i, len]` vector per input, in reverse order.
+struct Echo;
+
+impl Respond for Echo {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        let input = body["input"].as_array().unwrap();
+        let data: Vec<Value> = input
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, text)| {
+                let len = text.as_str().unwrap().len();
+                json!({"object": "embedding", "index": i, "embedding": [i as f32, len as f32]})
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(json!({"object": "list", "data": data}))
+    }
+}
+
+#[tokio::test]
+async fn batches_are_chunked_and_ordered() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(header("authorization", "Bearer test-key"))
+        .respond_with(Echo)
+        .expect(2)
+        .mount(&server)
+        .await;
+    let provider = OpenAiEmbeddingProvider::voyage(SecretString::from("test-key"), "voyage-3", 2)
+        .unwrap()
+        .with_base_url(&format!("{}/v1", server.uri()));
+    assert_eq!(provider.model_name(), "voyage-3");
+    assert_eq!(provider.dimension(), 2);
+
+    let texts: Vec<String> = (0..200).map(|i| "x".repeat(i)).collect();
+    let vectors = provider.embed_batch(&texts).await.unwrap();
+    assert_eq!(vectors.len(), 200);
+    for (i, vector) in vectors.iter().enumerate() {
+        assert_eq!(vector[1], i as f32, "vector {i} is out of order");
+    }
+}
+
+#[tokio::test]
+async fn wrong_dimension_is_rejected() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(Echo)
+        .mount(&server)
+        .await;
+    let provider = OpenAiEmbeddingProvider::new(SecretString::from("test-key"))
+        .unwrap()
+        .with_base_url(&server.uri());
+
+    let err = provider.embed("hello").await.unwrap_err();
+    assert!(
+        matches!(&err, CherubError::Provider(msg) if msg.contains("returned 2 dimensions, expected 1536")),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn local_server_needs_no_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header_exists("authorization"))
+        .respond_with(ResponseTemplate::new(401))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/embeddings"))
+        .respond_with(Echo)
+        .mount(&server)
+        .await;
+    let provider = OpenAiEmbeddingProvider::local("nomic-embed-text", 2)
+        .unwrap()
+        .with_base_url(&server.uri());
+
+    let vector = provider.embed("hello").await.unwrap();
+    assert_eq!(vector, [0.0, 5.0]);
+}
@@ -8,9 +8,9 @@ use secrecy::SecretString;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
-use cherub::providers::Provider;
 use cherub::providers::anthropic::AnthropicProvider;
 use cherub::providers::failover::FailoverProvider;
+use cherub::providers::{CompletionRequest, Provider};
 
 /// Valid mock 200 response body matching the Anthropic API wire format.
 const MOCK_SUCCESS_BODY: &str = r#"{"content":[{"type":"text","text":"ok"}],"stop_reason":"end_turn","usage":{"input_tokens":10,"output_tokens":5}}"#;
@@ -53,7 +53,9 @@ async fn first_down_second_succeeds() {
     );
 
     let messages = vec![cherub::providers::Message::user_text("hello")];
-    let result = failover.complete("system", &messages, &[]).await;
+    let result = failover
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_ok(), "should succeed via fallback: {result:?}");
     assert_eq!(failover.model_name(), "claude-test");
 }
@@ -87,7 +89,9 @@ async fn both_servers_down() {
     );
 
     let messages = vec![cherub::providers::Message::user_text("hello")];
-    let result = failover.complete("system", &messages, &[]).await;
+    let result = failover
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_err(), "should fail when all providers are down");
     let err = result.unwrap_err().to_string();
     assert!(
@@ -128,17 +132,23 @@ async fn circuit_breaker_activation() {
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
     // Call 1: server1 fails (1 failure), server2 succeeds.
-    let r1 = failover.complete("system", &messages, &[]).await;
+    let r1 = failover
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(r1.is_ok());
 
     // Call 2: server1 fails again (2 failures = threshold), circuit trips. server2 succeeds.
-    let r2 = failover.complete("system", &messages, &[]).await;
+    let r2 = failover
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(r2.is_ok());
 
     // Call 3: server1 is skipped (circuit open). server2 succeeds without trying server1.
     // We can verify by checking that server1 received exactly 2 requests (from calls 1 and 2),
     // not 3.
-    let r3 = failover.complete("system", &messages, &[]).await;
+    let r3 = failover
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(r3.is_ok());
 
     let s1_requests = server1.received_requests().await.unwrap();
@@ -4,8 +4,11 @@
 //! based on the structured match_source and the configured patterns.
 //!
 //! Does not require a database or filesystem operations — uses the mock provider
-//! + in-memory enforcement. The tool will error on execute (missing file) but
+//! and in-memory enforcement. The tool will error on execute (missing file) but
 //! the enforcement decision is what we're testing.
+//!
+//! Path- and size-based tiering of write/append/delete is checked with
+//! `Policy::check` against `TIERED_POLICY`, which mirrors the shipped config.
 
 use std::collections::VecDeque;
 use std::str::FromStr;
@@ -15,9 +18,11 @@ use serde_json::json;
 
 use async_trait::async_trait;
 
+use cherub::enforcement::DecisionKind;
 use cherub::enforcement::policy::Policy;
+use cherub::enforcement::tier::Tier;
 use cherub::error::CherubError;
-use cherub::providers::{ApiUsage, ContentBlock, Message, Provider, StopReason, ToolDefinition};
+use cherub::providers::{ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason};
 use cherub::runtime::AgentLoop;
 use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
 use cherub::runtime::output::NullSink;
@@ -43,9 +48,7 @@ impl MockProvider {
 impl Provider for MockProvider {
     async fn complete(
         &self,
-        _system: &str,
-        _messages: &[Message],
-        _tools: &[ToolDefinition],
+        _request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError> {
         let mut queue = self.responses.lock().unwrap();
         Ok((queue.pop_front().unwrap_or_else(end_turn), None))
@@ -248,7 +251,7 @@ async fn edit_is_allowed_at_act_tier() {
 
 #[tokio::test]
 async fn unknown_action_rejected() {
-    let responses = vec![file_tool_msg("t5", "delete", None), end_turn()];
+    let responses = vec![file_tool_msg("t5", "chmod", None), end_turn()];
     let mut agent = make_agent(responses, MockApprovalPolicy::AlwaysApprove);
     agent.run_turn_text("test").await.unwrap();
     let msgs = agent.session_messages();
@@ -330,3 +333,81 @@ async fn injection_in_action_field_rejected() {
         assert_eq!(content, "action not permitted");
     }
 }
+
+// ---------------------------------------------------------------------------
+// Tests: path-prefix and size tiering (mirrors config/default_policy.toml)
+// ---------------------------------------------------------------------------
+
+const TIERED_POLICY: &str = r#"
+[tools.file]
+enabled = true
+match_source = "structured"
+
+[tools.file.actions.read_ops]
+tier = "observe"
+patterns = ["^read:", "^list:", "^list$"]
+
+[tools.file.actions.sensitive_writes]
+tier = "commit"
+patterns = ["^(write|append|edit|delete):\\.env", "^(write|append|edit|delete):\\.github/"]
+
+[tools.file.actions.delete_ops]
+tier = "commit"
+patterns = ["^delete:"]
+
+[tools.file.actions.create_ops]
+tier = "act"
+patterns = ["^write:", "^append:"]
+constraints = [{ field = "content", op = "max_len", value = 16 }]
+on_constraint_failure = "escalate"
+"#;
+
+fn check(params: serde_json::Value) -> DecisionKind {
+    Policy::from_str(TIERED_POLICY)
+        .unwrap()
+        .check("file", &params)
+}
+
+#[test]
+fn list_is_observe() {
+    assert_eq!(
+        check(json!({"action": "list", "path": "src"})),
+        DecisionKind::Allow {
+            tier: Tier::Observe
+        }
+    );
+}
+
+#[test]
+fn small_write_is_act() {
+    for action in ["write", "append"] {
+        assert_eq!(
+            check(json!({"action": action, "path": "notes.md", "content": "hello"})),
+            DecisionKind::Allow { tier: Tier::Act },
+            "{action}"
+        );
+    }
+}
+
+#[test]
+fn oversized_write_escalates() {
+    assert_eq!(
+        check(json!({"action": "write", "path": "notes.md", "content": "x".repeat(17)})),
+        DecisionKind::Escalate { tier: Tier::Act }
+    );
+}
+
+#[test]
+fn sensitive_paths_and_deletes_are_commit() {
+    for params in [
+        json!({"action": "write", "path": ".env", "content": "A=1"}),
+        json!({"action": "append", "path": ".github/workflows/ci.yml", "content": "x"}),
+        json!({"action": "delete", "path": "notes.md"}),
+    ] {
+        assert_eq!(
+            check(params.clone()),
+            DecisionKind::Escalate { tier: Tier::Commit },
+            "{params}"
+        );
+    }
+}
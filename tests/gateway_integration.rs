@@ -0,0 +1,93 @@
+//! Integration tests for gateway and proxy deployments.
+//!
+//! Uses wiremock as the gateway (base URL) or as the HTTP proxy itself.
+
+use std::collections::HashMap;
+
+use secrecy::SecretString;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cherub::providers::anthropic::AnthropicProvider;
+use cherub::providers::http::HttpConfig;
+use cherub::providers::openai::OpenAiProvider;
+use cherub::providers::{CompletionRequest, Message, Provider};
+
+fn anthropic_reply() -> serde_json::Value {
+    serde_json::json!({
+        "content": [{"type": "text", "text": "via gateway"}],
+        "stop_reason": "end_turn",
+        "usage": {"input_tokens": 5, "output_tokens": 3}
+    })
+}
+
+fn openai_reply() -> serde_json::Value {
+    serde_json::json!({
+        "choices": [{
+            "message": {"role": "assistant", "content": "via proxy"},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 5, "completion_tokens": 3}
+    })
+}
+
+fn gateway_headers() -> HttpConfig {
+    HttpConfig {
+        headers: HashMap::from([("x-gateway-team".to_owned(), "infra".to_owned())]),
+        ..HttpConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn anthropic_base_url_and_default_headers() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/anthropic/v1/messages"))
+        .and(header("x-gateway-team", "infra"))
+        .and(header("x-api-key", "test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(anthropic_reply()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = AnthropicProvider::new(SecretString::from("test-key"), "claude-test", 1024)
+        .unwrap()
+        .with_base_url(&format!("{}/anthropic/", server.uri()))
+        .with_http_config(&gateway_headers())
+        .unwrap();
+    let messages = [Message::user_text("hello")];
+    provider
+        .complete(&CompletionRequest::new("", &messages, &[]))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn explicit_proxy_receives_requests() {
+    // The wiremock server plays the proxy: a plain-HTTP request to any host
+    // goes to it in absolute form.
+    let proxy = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("host", "api.gateway.invalid"))
+        .and(header("x-gateway-team", "infra"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(openai_reply()))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+
+    let config = HttpConfig {
+        proxy: Some(proxy.uri()),
+        ..gateway_headers()
+    };
+    let provider = OpenAiProvider::new(None, "gpt-test", 1024)
+        .unwrap()
+        .with_base_url("http://api.gateway.invalid/v1".to_owned())
+        .with_http_config(&config)
+        .unwrap();
+    let messages = [Message::user_text("hello")];
+    provider
+        .complete(&CompletionRequest::new("", &messages, &[]))
+        .await
+        .unwrap();
+}
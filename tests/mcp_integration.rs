@@ -225,7 +225,7 @@ patterns = ["^ls "]
     let proposal = ToolInvocation::new(enforcement_name, "execute", enriched);
     let (_, decision) = enforcement::evaluate(proposal, &policy, None);
     match decision {
-        enforcement::Decision::Reject => {} // expected
+        enforcement::Decision::Reject { .. } => {} // expected
         _ => panic!("expected Reject for unregistered server"),
     }
 }
@@ -339,3 +339,31 @@ command = "/nonexistent/binary/that/doesnt/exist"
     assert!(!result.errors.is_empty());
     assert!(result.tools.is_empty());
 }
+
+#[tokio::test]
+async fn unreachable_url_returns_error() {
+    // Bind then drop a listener so the port is closed.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("mcp_config.toml");
+    std::fs::write(
+        &config_path,
+        format!("[servers.remote]\nurl = \"http://127.0.0.1:{port}/mcp\"\n"),
+    )
+    .unwrap();
+
+    let result = loader::load_from_config(
+        &config_path,
+        #[cfg(feature = "credentials")]
+        None,
+        #[cfg(feature = "credentials")]
+        "test",
+    )
+    .await;
+    assert_eq!(result.errors.len(), 1, "errors: {:?}", result.errors);
+    assert!(result.tools.is_empty());
+}
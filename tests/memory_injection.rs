@@ -21,7 +21,7 @@ use uuid::{NoContext, Timestamp, Uuid};
 
 use cherub::enforcement::policy::Policy;
 use cherub::error::CherubError;
-use cherub::providers::{ApiUsage, ContentBlock, Message, Provider, StopReason, ToolDefinition};
+use cherub::providers::{ApiUsage, CompletionRequest, ContentBlock, Message, Provider, StopReason};
 use cherub::runtime::AgentLoop;
 use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
 use cherub::runtime::output::NullSink;
@@ -58,11 +58,12 @@ impl CapturingProvider {
 impl Provider for CapturingProvider {
     async fn complete(
         &self,
-        system: &str,
-        _messages: &[Message],
-        _tools: &[ToolDefinition],
+        request: &CompletionRequest<'_>,
     ) -> Result<(Message, Option<ApiUsage>), CherubError> {
-        self.captures.lock().unwrap().push(system.to_owned());
+        self.captures
+            .lock()
+            .unwrap()
+            .push(request.system.to_owned());
         let mut queue = self.responses.lock().unwrap();
         Ok((queue.pop_front().unwrap_or_else(end_turn), None))
     }
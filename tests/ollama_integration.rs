@@ -0,0 +1,63 @@
+//! Integration tests for the local model provider's health check.
+//!
+//! Uses wiremock to stand in for an Ollama server's `/v1/models`.
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cherub::providers::Provider;
+use cherub::providers::ollama::OllamaProvider;
+
+async fn server_with_models() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"object":"list","data":[{"id":"llama3.2:latest","object":"model"},{"id":"qwen2.5:7b","object":"model"}]}"#,
+        ))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn provider(model: &str, url: &str) -> OllamaProvider {
+    OllamaProvider::new(model, 1024)
+        .unwrap()
+        .with_base_url(format!("{url}/v1"))
+}
+
+#[tokio::test]
+async fn lists_models_and_passes_health_check() {
+    let server = server_with_models().await;
+    let local = provider("llama3.2", &server.uri());
+    assert_eq!(
+        local.list_models().await.unwrap().unwrap(),
+        ["llama3.2:latest", "qwen2.5:7b"]
+    );
+    assert!(local.health_check().await.is_ok());
+}
+
+#[tokio::test]
+async fn health_check_fails_for_unpulled_model() {
+    let server = server_with_models().await;
+    let err = provider("mistral", &server.uri())
+        .health_check()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("'mistral' is not available"), "{err}");
+    assert!(err.contains("qwen2.5:7b"), "{err}");
+}
+
+#[tokio::test]
+async fn health_check_fails_fast_when_server_down() {
+    let server = MockServer::start().await;
+    let url = server.uri();
+    drop(server);
+    let err = provider("llama3.2", &url)
+        .health_check()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not reachable"), "{err}");
+}
@@ -7,8 +7,8 @@ use secrecy::SecretString;
 use wiremock::matchers::{header_exists, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
-use cherub::providers::Provider;
 use cherub::providers::openai::OpenAiProvider;
+use cherub::providers::{CompletionRequest, Provider};
 
 /// Valid mock 200 response body that matches the OpenAI Chat Completions wire format.
 const MOCK_SUCCESS_BODY: &str = r#"{"choices":[{"message":{"content":"ok","tool_calls":null},"finish_reason":"stop"}],"usage":{"prompt_tokens":10,"completion_tokens":5}}"#;
@@ -41,7 +41,9 @@ async fn retry_succeeds_after_transient_429() {
     let provider = test_provider(&server.uri(), Some(SecretString::from("test-key")));
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_ok(), "should succeed after retry: {result:?}");
 }
 
@@ -67,7 +69,9 @@ async fn retry_succeeds_after_server_error() {
     let provider = test_provider(&server.uri(), Some(SecretString::from("test-key")));
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_ok(), "should succeed after retry: {result:?}");
 }
 
@@ -85,7 +89,9 @@ async fn no_retry_on_client_error() {
     let provider = test_provider(&server.uri(), Some(SecretString::from("test-key")));
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_err());
     let err = result.unwrap_err().to_string();
     assert!(err.contains("400"), "error should mention status: {err}");
@@ -106,7 +112,9 @@ async fn max_retries_exhausted() {
     let provider = test_provider(&server.uri(), Some(SecretString::from("test-key")));
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_err());
     let err = result.unwrap_err().to_string();
     assert!(err.contains("503"), "error should mention status: {err}");
@@ -140,7 +148,9 @@ async fn retry_after_header_respected() {
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
     let start = std::time::Instant::now();
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     let elapsed = start.elapsed();
 
     assert!(result.is_ok(), "should succeed after retry: {result:?}");
@@ -165,7 +175,9 @@ async fn no_auth_header_when_key_is_none() {
     let provider = test_provider(&server.uri(), None);
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_ok(), "should succeed without auth: {result:?}");
 
     // Verify that no authorization header was sent.
@@ -192,7 +204,9 @@ async fn auth_header_present_when_key_is_set() {
     let provider = test_provider(&server.uri(), Some(SecretString::from("sk-test-key")));
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_ok(), "should succeed with auth: {result:?}");
 
     let requests = server.received_requests().await.unwrap();
@@ -237,7 +251,10 @@ async fn tool_calls_roundtrip() {
     let provider = test_provider(&server.uri(), Some(SecretString::from("test-key")));
     let messages = vec![cherub::providers::Message::user_text("list files")];
 
-    let (msg, usage) = provider.complete("system", &messages, &[]).await.unwrap();
+    let (msg, usage) = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await
+        .unwrap();
 
     // Verify usage.
     let usage = usage.expect("usage should be present");
@@ -0,0 +1,96 @@
+//! Integration tests for the OpenRouter provider's routing parameters.
+//!
+//! Uses wiremock to check that fallback models and provider preferences reach
+//! the request body, and that a per-call routing replaces the configured one.
+
+use secrecy::SecretString;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cherub::providers::openrouter::{
+    OpenRouterProvider, ProviderPreferences, ProviderSort, Routing,
+};
+use cherub::providers::{CompletionRequest, Message, Provider};
+
+const MOCK_SUCCESS_BODY: &str = r#"{"choices":[{"message":{"content":"ok","tool_calls":null},"finish_reason":"stop"}],"usage":{"prompt_tokens":10,"completion_tokens":5}}"#;
+
+fn provider(server: &MockServer) -> OpenRouterProvider {
+    OpenRouterProvider::new(
+        SecretString::from("or-test-key"),
+        "anthropic/claude-sonnet-4",
+        1024,
+    )
+    .unwrap()
+    .with_base_url(format!("{}/api/v1", server.uri()))
+    .with_routing(Routing {
+        fallback_models: vec!["openai/gpt-4o".to_owned()],
+        preferences: ProviderPreferences {
+            order: vec!["anthropic".to_owned()],
+            allow_fallbacks: Some(false),
+            ..ProviderPreferences::default()
+        },
+    })
+}
+
+#[tokio::test]
+async fn configured_routing_is_sent() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/chat/completions"))
+        .and(header("authorization", "Bearer or-test-key"))
+        .and(body_partial_json(json!({
+            "model": "anthropic/claude-sonnet-4",
+            "models": ["anthropic/claude-sonnet-4", "openai/gpt-4o"],
+            "provider": { "order": ["anthropic"], "allow_fallbacks": false }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_string(MOCK_SUCCESS_BODY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let messages = [Message::user_text("hello")];
+    let (message, usage) = provider(&server)
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await
+        .unwrap();
+    assert!(matches!(message, Message::Assistant { .. }));
+    assert_eq!(usage.unwrap().input_tokens, 10);
+}
+
+#[tokio::test]
+async fn per_call_routing_overrides() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({
+            "model": "google/gemini-2.5-pro",
+            "models": ["google/gemini-2.5-pro", "anthropic/claude-sonnet-4"],
+            "provider": { "sort": "throughput" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_string(MOCK_SUCCESS_BODY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let routing = Routing {
+        fallback_models: vec!["anthropic/claude-sonnet-4".to_owned()],
+        preferences: ProviderPreferences {
+            sort: Some(ProviderSort::Throughput),
+            ..ProviderPreferences::default()
+        },
+    };
+    let messages = [Message::user_text("hello")];
+    let request =
+        CompletionRequest::new("system", &messages, &[]).with_model("google/gemini-2.5-pro");
+    provider(&server)
+        .complete_routed(&request, &routing)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&received[0].body).unwrap();
+    assert!(
+        body["provider"].get("order").is_none(),
+        "the configured preferences are replaced, not merged: {body}"
+    );
+}
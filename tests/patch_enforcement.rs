@@ -0,0 +1,92 @@
+//! Enforcement tests for the patch tool.
+//!
+//! A diff produces one `"{op}:{path}"` action string per touched file; every
+//! one must match and the most restrictive tier wins. Checked with
+//! `Policy::check` against `PATCH_POLICY`, which mirrors the shipped config.
+
+use std::str::FromStr;
+
+use serde_json::json;
+
+use cherub::enforcement::policy::Policy;
+use cherub::enforcement::tier::Tier;
+use cherub::enforcement::{DecisionKind, RejectReason};
+
+const PATCH_POLICY: &str = r#"
+[tools.patch]
+enabled = true
+match_source = "patch"
+
+[tools.patch.actions.sensitive_writes]
+tier = "commit"
+patterns = [
+    "^(create|modify|delete):\\.env",
+    "^(create|modify|delete):\\.git/",
+    "^(create|modify|delete):\\.github/",
+]
+
+[tools.patch.actions.delete_ops]
+tier = "commit"
+patterns = ["^delete:"]
+
+[tools.patch.actions.write_ops]
+tier = "act"
+patterns = ["^create:", "^modify:"]
+"#;
+
+const MODIFY_SRC: &str = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-a\n+b\n";
+
+fn check(diff: &str) -> DecisionKind {
+    Policy::from_str(PATCH_POLICY)
+        .unwrap()
+        .check("patch", &json!({"patch": diff}))
+}
+
+#[test]
+fn modify_and_create_are_act() {
+    let create = "--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1 @@\n+x\n";
+    assert_eq!(
+        check(&format!("{MODIFY_SRC}{create}")),
+        DecisionKind::Allow { tier: Tier::Act }
+    );
+}
+
+#[test]
+fn one_sensitive_file_escalates_the_whole_patch() {
+    let env = "--- /dev/null\n+++ b/.env\n@@ -0,0 +1 @@\n+KEY=1\n";
+    assert_eq!(
+        check(&format!("{MODIFY_SRC}{env}")),
+        DecisionKind::Escalate { tier: Tier::Commit }
+    );
+}
+
+#[test]
+fn deletes_and_renames_are_commit() {
+    for diff in [
+        "--- a/notes.md\n+++ /dev/null\n@@ -1 +0,0 @@\n-x\n",
+        "--- a/old.rs\n+++ b/new.rs\n@@ -1 +1 @@\n-a\n+b\n",
+    ] {
+        assert_eq!(
+            check(diff),
+            DecisionKind::Escalate { tier: Tier::Commit },
+            "{diff}"
+        );
+    }
+}
+
+#[test]
+fn malformed_or_escaping_patch_rejected() {
+    for diff in [
+        "not a diff",
+        "--- a/../x\n+++ b/../x\n@@ -1 +1 @@\n-a\n+b\n",
+        "--- /etc/passwd\n+++ /etc/passwd\n@@ -1 +1 @@\n-a\n+b\n",
+    ] {
+        assert_eq!(
+            check(diff),
+            DecisionKind::Reject {
+                reason: RejectReason::MissingParam
+            },
+            "{diff}"
+        );
+    }
+}
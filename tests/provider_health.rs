@@ -0,0 +1,220 @@
+//! Integration tests for startup health checks and model listing.
+//!
+//! Uses wiremock as each API: a bad key or an unknown model must fail
+//! `health_check` before any completion is attempted.
+
+use secrecy::SecretString;
+use serde_json::json;
+use wiremock::matchers::{header, method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cherub::providers::Provider;
+use cherub::providers::anthropic::AnthropicProvider;
+use cherub::providers::openai::OpenAiProvider;
+use cherub::providers::openrouter::OpenRouterProvider;
+
+fn anthropic(server: &MockServer, model: &str) -> AnthropicProvider {
+    AnthropicProvider::new(SecretString::from("test-key"), model, 1024)
+        .unwrap()
+        .with_base_url(&server.uri())
+}
+
+#[tokio::test]
+async fn anthropic_health_check_resolves_the_model() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models/claude-sonnet-4-0"))
+        .and(header("x-api-key", "test-key"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "type": "model", "id": "claude-sonnet-4-20250514"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "type": "error",
+            "error": {"type": "not_found_error", "message": "model: claude-sonet-4"}
+        })))
+        .mount(&server)
+        .await;
+
+    assert!(
+        anthropic(&server, "claude-sonnet-4-0")
+            .health_check()
+            .await
+            .is_ok()
+    );
+    let err = anthropic(&server, "claude-sonet-4")
+        .health_check()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("model 'claude-sonet-4' not found"), "{err}");
+}
+
+#[tokio::test]
+async fn anthropic_health_check_rejects_bad_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "type": "error",
+            "error": {"type": "authentication_error", "message": "invalid x-api-key"}
+        })))
+        .mount(&server)
+        .await;
+
+    let err = anthropic(&server, "claude-sonnet-4-0")
+        .health_check()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("authentication failed (401)"), "{err}");
+}
+
+#[tokio::test]
+async fn anthropic_lists_every_page() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(query_param_is_missing("after_id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [{"id": "claude-opus-4-1-20250805"}, {"id": "claude-sonnet-4-20250514"}],
+            "has_more": true,
+            "last_id": "claude-sonnet-4-20250514"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(query_param("after_id", "claude-sonnet-4-20250514"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [{"id": "claude-3-5-haiku-20241022"}],
+            "has_more": false,
+            "last_id": "claude-3-5-haiku-20241022"
+        })))
+        .mount(&server)
+        .await;
+
+    let models = anthropic(&server, "claude-sonnet-4-0")
+        .list_models()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        models,
+        [
+            "claude-opus-4-1-20250805",
+            "claude-sonnet-4-20250514",
+            "claude-3-5-haiku-20241022"
+        ]
+    );
+}
+
+fn openai(server: &MockServer, model: &str) -> OpenAiProvider {
+    OpenAiProvider::new(Some(SecretString::from("test-key")), model, 1024)
+        .unwrap()
+        .with_base_url(server.uri())
+}
+
+#[tokio::test]
+async fn openai_compatible_endpoint_checks_key_and_tolerates_naming() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .and(header("authorization", "Bearer test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [{"id": "models/gemini-2.5-flash"}]
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = openai(&server, "gemini-2.5-flash");
+    assert_eq!(
+        provider.list_models().await.unwrap().unwrap(),
+        ["models/gemini-2.5-flash"]
+    );
+    // Not the OpenAI API itself: an unlisted name is only a warning.
+    assert!(provider.health_check().await.is_ok());
+
+    let rejected = OpenAiProvider::new(Some(SecretString::from("wrong")), "gpt-4o", 1024)
+        .unwrap()
+        .with_base_url(server.uri());
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("bad key"))
+        .mount(&server)
+        .await;
+    let err = rejected.health_check().await.unwrap_err().to_string();
+    assert!(err.contains("401"), "{err}");
+}
+
+#[tokio::test]
+async fn endpoint_without_model_list_passes() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let provider = openai(&server, "my-deployment");
+    assert_eq!(provider.list_models().await.unwrap(), None);
+    assert!(provider.health_check().await.is_ok());
+}
+
+fn openrouter(server: &MockServer, model: &str) -> OpenRouterProvider {
+    OpenRouterProvider::new(SecretString::from("or-key"), model, 1024)
+        .unwrap()
+        .with_base_url(server.uri())
+}
+
+async fn openrouter_server(key_status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/key"))
+        .and(header("authorization", "Bearer or-key"))
+        .respond_with(
+            ResponseTemplate::new(key_status).set_body_json(json!({"data": {"label": "test"}})),
+        )
+        .mount(&server)
+        .await;
+    let models: Vec<_> = (0..30)
+        .map(|i| json!({"id": format!("vendor/model-{i}")}))
+        .chain([json!({"id": "anthropic/claude-sonnet-4"})])
+        .collect();
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"data": models})))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn openrouter_checks_key_and_model() {
+    let server = openrouter_server(200).await;
+    assert!(
+        openrouter(&server, "anthropic/claude-sonnet-4:nitro")
+            .health_check()
+            .await
+            .is_ok()
+    );
+
+    let err = openrouter(&server, "anthropic/claude-sonet-4")
+        .health_check()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("'anthropic/claude-sonet-4' is not available"),
+        "{err}"
+    );
+    assert!(err.ends_with("and 21 more)"), "long lists are cut: {err}");
+
+    let revoked = openrouter_server(401).await;
+    let err = openrouter(&revoked, "anthropic/claude-sonnet-4")
+        .health_check()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("401"), "{err}");
+}
@@ -0,0 +1,175 @@
+//! Integration tests for provider call timeouts and cancellation.
+//!
+//! A stalled stream must not hold the agent loop: the turn fails with
+//! `ProviderTimeout`/`Cancelled`, and text that had streamed is kept.
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use secrecy::SecretString;
+use tokio_util::sync::CancellationToken;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cherub::enforcement::policy::Policy;
+use cherub::error::CherubError;
+use cherub::providers::anthropic::AnthropicProvider;
+use cherub::providers::guard::complete_guarded;
+use cherub::providers::{
+    ApiUsage, CompletionRequest, ContentBlock, Message, MessageDelta, Provider, StopReason,
+};
+use cherub::runtime::AgentLoop;
+use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
+use cherub::runtime::output::NullSink;
+use cherub::tools::ToolRegistry;
+
+const POLICY: &str = r#"
+[tools.bash]
+enabled = true
+[tools.bash.actions.read]
+tier = "observe"
+patterns = ["^ls "]
+"#;
+
+struct AutoApprove;
+
+impl ApprovalGate for AutoApprove {
+    async fn request_approval(&self, _context: &EscalationContext<'_>) -> ApprovalResult {
+        ApprovalResult::Approved
+    }
+}
+
+/// Streams some text and then stalls on the first call; answers normally after.
+struct StallOnce {
+    stalled: AtomicBool,
+}
+
+#[async_trait]
+impl Provider for StallOnce {
+    async fn complete(
+        &self,
+        _request: &CompletionRequest<'_>,
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        Ok((
+            Message::Assistant {
+                content: vec![ContentBlock::Text {
+                    text: "Done.".to_owned(),
+                }],
+                stop_reason: StopReason::EndTurn,
+            },
+            None,
+        ))
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest<'_>,
+        on_delta: &mut (dyn FnMut(MessageDelta) + Send),
+    ) -> Result<(Message, Option<ApiUsage>), CherubError> {
+        if !self.stalled.swap(true, Ordering::SeqCst) {
+            on_delta(MessageDelta::Text("I was about to".to_owned()));
+            std::future::pending::<()>().await;
+        }
+        self.complete(request).await
+    }
+
+    fn model_name(&self) -> &str {
+        "claude-test"
+    }
+
+    fn max_output_tokens(&self) -> u32 {
+        4096
+    }
+}
+
+fn agent() -> AgentLoop<AutoApprove, NullSink> {
+    AgentLoop::new(
+        Policy::from_str(POLICY).unwrap(),
+        Box::new(StallOnce {
+            stalled: AtomicBool::new(false),
+        }),
+        ToolRegistry::new(),
+        "test".to_owned(),
+        AutoApprove,
+        NullSink,
+        "test",
+    )
+}
+
+fn assistant_text(message: &Message) -> &str {
+    match message {
+        Message::Assistant { content, .. } => match &content[0] {
+            ContentBlock::Text { text } => text,
+            other => panic!("unexpected block: {other:?}"),
+        },
+        other => panic!("unexpected message: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn stalled_stream_times_out_and_keeps_partial_text() {
+    let mut agent = agent();
+    agent.with_provider_timeout(Duration::from_millis(50));
+
+    let err = agent.run_turn_text("hello").await.unwrap_err();
+    assert!(
+        matches!(&err, CherubError::ProviderTimeout { partial, .. } if partial == "I was about to"),
+        "{err:?}"
+    );
+    let messages = agent.session_messages();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(assistant_text(&messages[1]), "I was about to");
+
+    // The loop is not stuck: the next turn completes.
+    agent.run_turn_text("try again").await.unwrap();
+    assert_eq!(
+        assistant_text(agent.session_messages().last().unwrap()),
+        "Done."
+    );
+}
+
+#[tokio::test]
+async fn cancellation_interrupts_the_turn() {
+    let mut agent = agent();
+    let token = CancellationToken::new();
+    agent.with_cancellation(token.clone());
+
+    let canceller = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        token.cancel();
+    });
+    let err = agent.run_turn_text("hello").await.unwrap_err();
+    canceller.await.unwrap();
+    assert!(
+        matches!(&err, CherubError::Cancelled { partial } if partial == "I was about to"),
+        "{err:?}"
+    );
+}
+
+/// A real HTTP provider whose server never answers in time.
+#[tokio::test]
+async fn slow_server_times_out() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+    let provider = AnthropicProvider::new(SecretString::from("test-key"), "claude-test", 1024)
+        .unwrap()
+        .with_url(server.uri());
+
+    let messages = [Message::user_text("hello")];
+    let request =
+        CompletionRequest::new("", &messages, &[]).with_timeout(Duration::from_millis(100));
+    let started = std::time::Instant::now();
+    let err = complete_guarded(&provider, &request, &mut |_| {})
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, CherubError::ProviderTimeout { partial, .. } if partial.is_empty()),
+        "{err:?}"
+    );
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
@@ -7,8 +7,12 @@ use secrecy::SecretString;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
-use cherub::providers::Provider;
+use std::time::Duration;
+
+use cherub::error::CherubError;
 use cherub::providers::anthropic::AnthropicProvider;
+use cherub::providers::{CompletionRequest, Provider};
+use cherub::retry::RetryConfig;
 
 /// Valid mock 200 response body that matches the Anthropic API wire format.
 const MOCK_SUCCESS_BODY: &str = r#"{"content":[{"type":"text","text":"ok"}],"stop_reason":"end_turn","usage":{"input_tokens":10,"output_tokens":5}}"#;
@@ -41,7 +45,9 @@ async fn retry_succeeds_after_transient_429() {
     let provider = test_provider(&server.uri());
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_ok(), "should succeed after retry: {result:?}");
 }
 
@@ -67,7 +73,9 @@ async fn retry_succeeds_after_server_error() {
     let provider = test_provider(&server.uri());
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_ok(), "should succeed after retry: {result:?}");
 }
 
@@ -85,7 +93,9 @@ async fn no_retry_on_client_error() {
     let provider = test_provider(&server.uri());
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     assert!(result.is_err());
     let err = result.unwrap_err().to_string();
     assert!(err.contains("400"), "error should mention status: {err}");
@@ -106,13 +116,78 @@ async fn max_retries_exhausted() {
     let provider = test_provider(&server.uri());
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
-    let result = provider.complete("system", &messages, &[]).await;
-    assert!(result.is_err());
-    let err = result.unwrap_err().to_string();
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
+    let err = result.unwrap_err();
+    assert!(
+        matches!(err, CherubError::RetriesExhausted { retries: 3, .. }),
+        "exhausted retries should be a distinct error: {err:?}"
+    );
+    let err = err.to_string();
     assert!(err.contains("503"), "error should mention status: {err}");
     assert!(err.contains("3 retries"), "should report 3 retries: {err}");
 }
 
+#[tokio::test]
+async fn configured_max_retries_respected() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+        .expect(2) // 1 initial + 1 retry
+        .mount(&server)
+        .await;
+
+    let provider = test_provider(&server.uri()).with_retry_config(RetryConfig {
+        max_retries: 1,
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+    });
+    let messages = vec![cherub::providers::Message::user_text("hello")];
+
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
+    assert!(
+        matches!(
+            result,
+            Err(CherubError::RetriesExhausted { retries: 1, .. })
+        ),
+        "should stop after one retry: {result:?}"
+    );
+}
+
+#[tokio::test]
+async fn retry_after_beyond_max_delay_not_retried() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("retry-after", "3600")
+                .set_body_string("rate limited"),
+        )
+        .expect(1) // Waiting an hour is not worth it — fail immediately
+        .mount(&server)
+        .await;
+
+    let provider = test_provider(&server.uri());
+    let messages = vec![cherub::providers::Message::user_text("hello")];
+
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
+    let err = result.unwrap_err();
+    assert!(
+        matches!(err, CherubError::Provider(_)),
+        "should surface the rate limit without retrying: {err:?}"
+    );
+    assert!(err.to_string().contains("429"), "{err}");
+}
+
 #[tokio::test]
 async fn retry_after_header_respected() {
     let server = MockServer::start().await;
@@ -140,7 +215,9 @@ async fn retry_after_header_respected() {
     let messages = vec![cherub::providers::Message::user_text("hello")];
 
     let start = std::time::Instant::now();
-    let result = provider.complete("system", &messages, &[]).await;
+    let result = provider
+        .complete(&CompletionRequest::new("system", &messages, &[]))
+        .await;
     let elapsed = start.elapsed();
 
     assert!(result.is_ok(), "should succeed after retry: {result:?}");
@@ -0,0 +1,93 @@
+//! The SQLite store wired into the agent loop: enforcement decisions land in
+//! the audit log, and the conversation survives a restart.
+
+#![cfg(all(feature = "sqlite-store", feature = "testing"))]
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde_json::json;
+
+use cherub::enforcement::policy::Policy;
+use cherub::providers::testing::{MockProvider, MockTurn};
+use cherub::runtime::AgentLoop;
+use cherub::runtime::approval::{ApprovalGate, ApprovalResult, EscalationContext};
+use cherub::runtime::output::NullSink;
+use cherub::storage::sqlite_store::SqliteStore;
+use cherub::storage::{AuditDecision, AuditFilter, AuditStore};
+use cherub::tools::ToolRegistry;
+use cherub::tools::testing::RecordingTool;
+
+const POLICY: &str = r#"
+[tools.bash]
+enabled = true
+
+[tools.bash.actions.read]
+tier = "observe"
+patterns = ["^ls"]
+"#;
+
+struct Deny;
+
+impl ApprovalGate for Deny {
+    async fn request_approval(&self, _context: &EscalationContext<'_>) -> ApprovalResult {
+        ApprovalResult::Denied
+    }
+}
+
+async fn agent(provider: MockProvider, store: &SqliteStore) -> AgentLoop<Deny, NullSink> {
+    let mut agent = AgentLoop::new(
+        Policy::from_str(POLICY).unwrap(),
+        Box::new(provider),
+        ToolRegistry::new().with_recording(RecordingTool::new("bash", "file.txt")),
+        "test".to_owned(),
+        Deny,
+        NullSink,
+        "alice",
+    );
+    agent.with_audit_log(Arc::new(store.clone()));
+    agent
+        .with_persistence(Box::new(store.clone()), "cli", "default")
+        .await
+        .unwrap();
+    agent
+}
+
+#[tokio::test]
+async fn decisions_are_audited_and_history_survives_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cherub.db");
+    let store = SqliteStore::open(&path).await.unwrap();
+
+    let provider = MockProvider::new([
+        MockTurn::ToolCalls(vec![
+            ("bash".to_owned(), json!({"command": "ls"})),
+            ("bash".to_owned(), json!({"command": "curl example.com"})),
+        ]),
+        MockTurn::text("Done."),
+    ]);
+    let mut first = agent(provider, &store).await;
+    first.run_turn_text("look around").await.unwrap();
+    let session_id = first.session_id();
+    let message_count = first.session_messages().len();
+    drop(first);
+
+    let events = store
+        .list(AuditFilter {
+            session_id: Some(session_id),
+            ..AuditFilter::default()
+        })
+        .await
+        .unwrap();
+    let decisions: Vec<AuditDecision> = events.iter().rev().map(|e| e.decision).collect();
+    assert_eq!(decisions, [AuditDecision::Allow, AuditDecision::Reject]);
+    assert_eq!(events[1].action.as_deref(), Some("ls"));
+    assert_eq!(events[1].tier.as_deref(), Some("observe"));
+    assert!(events[1].decision_id.is_some());
+
+    // A new process with a new connection resumes the same conversation.
+    let store = SqliteStore::open(&path).await.unwrap();
+    let second = agent(MockProvider::new([]), &store).await;
+    assert_eq!(second.session_id(), session_id);
+    assert_eq!(second.session_messages().len(), message_count);
+}
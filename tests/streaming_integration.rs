@@ -0,0 +1,107 @@
+//! Integration tests for streaming completions.
+//!
+//! Uses wiremock to serve Anthropic SSE bodies to an `AnthropicProvider`.
+
+use secrecy::SecretString;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cherub::providers::anthropic::AnthropicProvider;
+use cherub::providers::{
+    CompletionRequest, ContentBlock, Message, MessageDelta, Provider, StopReason,
+};
+
+fn test_provider(url: &str) -> AnthropicProvider {
+    AnthropicProvider::new(SecretString::from("test-key"), "claude-test", 1024)
+        .unwrap()
+        .with_url(url.to_owned())
+}
+
+fn sse(events: &[&str]) -> String {
+    events
+        .iter()
+        .map(|data| format!("event: x\ndata: {data}\n\n"))
+        .collect()
+}
+
+async fn serve(events: &[&str]) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_partial_json(serde_json::json!({"stream": true})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(sse(events)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn streams_text_and_tool_call_deltas() {
+    let server = serve(&[
+        r#"{"type":"message_start","message":{"usage":{"input_tokens":10,"output_tokens":1}}}"#,
+        r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Checking"}}"#,
+        r#"{"type":"content_block_stop","index":0}"#,
+        r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"t1","name":"bash","input":{}}}"#,
+        r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\":\"ls\"}"}}"#,
+        r#"{"type":"content_block_stop","index":1}"#,
+        r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":20}}"#,
+        r#"{"type":"message_stop"}"#,
+    ])
+    .await;
+
+    let mut deltas = Vec::new();
+    let (message, usage) = test_provider(&server.uri())
+        .complete_streaming(
+            &CompletionRequest::new("system", &[Message::user_text("list files")], &[]),
+            &mut |delta| deltas.push(delta),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(deltas[0], MessageDelta::Text("Checking".to_owned()));
+    // The tool call is visible before its input has arrived.
+    assert_eq!(
+        deltas[1],
+        MessageDelta::ToolUseStart {
+            id: "t1".to_owned(),
+            name: "bash".to_owned()
+        }
+    );
+    let Message::Assistant {
+        content,
+        stop_reason,
+    } = message
+    else {
+        panic!("expected assistant message");
+    };
+    assert_eq!(stop_reason, StopReason::ToolUse);
+    assert!(matches!(
+        &content[1],
+        ContentBlock::ToolUse { input, .. } if input["command"] == "ls"
+    ));
+    assert_eq!(usage.unwrap().output_tokens, 20);
+}
+
+#[tokio::test]
+async fn error_event_fails_without_retry() {
+    let server = serve(&[
+        r#"{"type":"message_start","message":{"usage":{"input_tokens":10,"output_tokens":1}}}"#,
+        r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+    ])
+    .await;
+
+    let result = test_provider(&server.uri())
+        .complete_streaming(
+            &CompletionRequest::new("system", &[Message::user_text("hi")], &[]),
+            &mut |_| {},
+        )
+        .await;
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("overloaded mid-stream"), "{err}");
+}
@@ -6,5 +6,6 @@ use cherub::enforcement::tier::Tier;
 
 fn main() {
     // This line must produce a compile error: new() is pub(super), not pub.
-    let _token = CapabilityToken::new(Tier::Observe);
+    #[allow(unreachable_code)]
+    let _token = CapabilityToken::new(Tier::Observe, None, todo!(), String::new(), None);
 }